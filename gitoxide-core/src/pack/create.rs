@@ -62,6 +62,9 @@ pub struct Context<W> {
     /// If true, delta objects may refer to their base as reference, allowing it not to be included in the created back.
     /// Otherwise these have to be recompressed in order to make the pack self-contained.
     pub thin: bool,
+    /// If true, objects that would otherwise be written as base objects are delta-compressed against similar objects,
+    /// as configured by `pack.window` and `pack.depth`, while respecting the `delta` attribute of blobs in the index.
+    pub delta_compression: bool,
     /// If set, don't use more than this amount of threads.
     /// Otherwise, usually use as many threads as there are logical cores.
     /// A value of 0 is interpreted as no-limit
@@ -94,6 +97,7 @@ pub fn create<W, P>(
         expansion,
         nondeterministic_thread_count,
         thin,
+        delta_compression,
         thread_limit,
         statistics,
         pack_cache_size_in_bytes,
@@ -110,8 +114,19 @@ where
 
     let repo = gix::discover(repository_path)?.into_sync();
     progress.init(Some(2), progress::steps());
+    let delta_compression = delta_compression
+        .then(|| -> anyhow::Result<_> {
+            let repo = repo.to_thread_local();
+            Ok((repo.pack_delta_compression()?, repo.pack_blobs_without_delta()?))
+        })
+        .transpose()?;
+    let bitmap = (input.is_none() && matches!(expansion, ObjectExpansion::TreeTraversal))
+        .then(|| repo.to_thread_local().pack_bitmap())
+        .transpose()?
+        .flatten();
     let tips = tips.into_iter();
     let make_cancellation_err = || anyhow!("Cancelled by user");
+    let mut bitmap_tips = None;
     let (mut handle, mut input): (_, Box<ObjectIdIter>) = match input {
         None => {
             let mut progress = progress.add_child("traversing");
@@ -128,6 +143,9 @@ where
                     }
                 })
                 .collect::<Result<Vec<_>, _>>()?;
+            if bitmap.is_some() {
+                bitmap_tips = Some(tips.clone());
+            }
             let handle = repo.objects.into_shared_arc().to_cache_arc();
             let iter = Box::new(
                 traverse::commit::Simple::new(tips, handle.clone())
@@ -193,7 +211,14 @@ where
         let input_object_expansion = expansion.into();
         handle.prevent_pack_unload();
         handle.ignore_replacements = true;
-        let (mut counts, count_stats) = if may_use_multiple_threads {
+        let bitmap_counts = match (&bitmap, bitmap_tips) {
+            (Some(bitmap), Some(tips)) => pack::data::output::count::objects_with_bitmap(bitmap, tips, None, &handle)?,
+            _ => None,
+        };
+        let (mut counts, count_stats) = if let Some((counts, count_stats)) = bitmap_counts {
+            progress.inc_by(counts.len());
+            (counts, count_stats)
+        } else if may_use_multiple_threads {
             pack::data::output::count::objects(
                 handle.clone(),
                 input,
@@ -216,6 +241,11 @@ where
         };
         stats.counts = count_stats;
         counts.shrink_to_fit();
+        if let Some((_, blobs_without_delta)) = &delta_compression {
+            for count in counts.iter_mut() {
+                count.no_delta = blobs_without_delta.contains(&count.id);
+            }
+        }
         counts
     };
 
@@ -229,7 +259,10 @@ where
             Box::new(progress),
             pack::data::output::entry::iter_from_counts::Options {
                 thread_limit,
-                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                mode: delta_compression.map_or(
+                    pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                    |(mode, _)| mode,
+                ),
                 allow_thin_pack: thin,
                 chunk_size,
                 version: Default::default(),
@@ -319,6 +352,7 @@ fn human_output(
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             },
     }: Statistics,
     mut out: impl std::io::Write,
@@ -339,10 +373,11 @@ fn human_output(
     #[rustfmt::skip]
     writeln!(
         out,
-        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
+        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
         "decoded and recompressed", decoded_and_recompressed_objects,
         "pack-to-pack copies", objects_copied_from_pack,
         "ref-delta-objects", ref_delta_objects,
+        "delta-compressed objects", delta_compressed_objects,
         "missing objects", missing_objects,
         width = width
     )?;
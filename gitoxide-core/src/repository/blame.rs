@@ -0,0 +1,201 @@
+use std::ops::Range;
+
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// Print each line along with the abbreviated commit, author and date, similar to `git blame`.
+    #[default]
+    Human,
+    /// Print information suitable for consumption by programs, similar to `git blame --porcelain`.
+    Porcelain,
+    /// Print each hunk without the lines themselves, in the format of `git blame --incremental`.
+    ///
+    /// Like `git`, hunks are printed as soon as they are attributed, and thus not in the order of the blamed file.
+    Incremental,
+}
+
+pub struct Options {
+    pub format: Format,
+    /// 0-based line ranges, all lines if empty.
+    pub ranges: Vec<Range<u32>>,
+    pub ignore_whitespace: bool,
+    pub ignore_revs: Vec<String>,
+    pub statistics: bool,
+}
+
+pub(crate) mod function {
+    use std::{collections::HashSet, ffi::OsStr};
+
+    use anyhow::Context;
+    use gix::bstr::{BStr, ByteSlice};
+
+    use crate::repository::blame::{Format, Options};
+
+    pub fn blame(
+        repo: gix::Repository,
+        file: &OsStr,
+        rev: Option<String>,
+        mut out: impl std::io::Write,
+        err: Option<&mut dyn std::io::Write>,
+        Options {
+            format,
+            ranges,
+            ignore_whitespace,
+            ignore_revs,
+            statistics,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        let file = gix::path::os_str_into_bstr(file)?;
+        let file = gix::path::to_unix_separators_on_windows(file);
+        let suspect = repo
+            .rev_parse_single(rev.as_deref().unwrap_or("HEAD"))?
+            .object()?
+            .peel_to_kind(gix::object::Kind::Commit)?
+            .id;
+
+        let mut options = repo.blame_options()?;
+        options.ranges = ranges;
+        options.ignore_whitespace = ignore_whitespace;
+        for rev in ignore_revs {
+            options.ignore_revs.insert(
+                repo.rev_parse_single(rev.as_str())?
+                    .object()?
+                    .peel_to_kind(gix::object::Kind::Commit)?
+                    .id,
+            );
+        }
+
+        let outcome = if format == Format::Incremental {
+            let mut seen = HashSet::new();
+            repo.blame_incremental(file.as_ref(), suspect, options, |entry, commit| {
+                writeln!(
+                    out,
+                    "{} {} {} {}",
+                    entry.commit_id,
+                    entry.start_in_source_file + 1,
+                    entry.start_in_blamed_file + 1,
+                    entry.len
+                )?;
+                if seen.insert(entry.commit_id) {
+                    write_porcelain_header(commit, &mut out)?;
+                }
+                // Like `git`, repeat the filename for known commits as well in incremental mode.
+                let filename: &BStr = entry.source_file_name.as_ref().map_or(file.as_ref(), AsRef::as_ref);
+                writeln!(out, "filename {filename}")?;
+                Ok(())
+            })
+        } else {
+            repo.blame(file.as_ref(), suspect, options)
+        }
+        .with_context(|| format!("Could not blame '{file}'"))?;
+        if let Some(err) = statistics.then_some(err).flatten() {
+            writeln!(err, "{:#?}", outcome.statistics)?;
+        }
+
+        let commits = &outcome.commits;
+        match format {
+            Format::Human => write_human(&outcome, &mut out)?,
+            Format::Porcelain => {
+                let mut seen = HashSet::new();
+                for (entry, lines) in outcome.entries_with_lines() {
+                    let commit = &commits[&entry.commit_id];
+                    writeln!(
+                        out,
+                        "{} {} {} {}",
+                        entry.commit_id,
+                        entry.start_in_source_file + 1,
+                        entry.start_in_blamed_file + 1,
+                        entry.len
+                    )?;
+                    if seen.insert(entry.commit_id) {
+                        write_porcelain_header(commit, &mut out)?;
+                        let filename: &BStr = entry.source_file_name.as_ref().map_or(file.as_ref(), AsRef::as_ref);
+                        writeln!(out, "filename {filename}")?;
+                    }
+                    for (line_index, line) in lines.into_iter().enumerate() {
+                        if line_index != 0 {
+                            writeln!(
+                                out,
+                                "{} {} {}",
+                                entry.commit_id,
+                                entry.start_in_source_file + 1 + line_index as u32,
+                                entry.start_in_blamed_file + 1 + line_index as u32,
+                            )?;
+                        }
+                        write_content_line(&mut out, line)?;
+                    }
+                }
+            }
+            Format::Incremental => {}
+        }
+        Ok(())
+    }
+
+    fn write_human(outcome: &gix::repository::blame::Outcome, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+        let commits = &outcome.commits;
+        let show_filename = outcome.entries.iter().any(|e| e.source_file_name.is_some());
+        let author_width = commits.values().map(|c| c.author.name.len()).max().unwrap_or_default();
+        let filename_width = outcome
+            .entries
+            .iter()
+            .filter_map(|e| e.source_file_name.as_ref().map(|name| name.len()))
+            .max()
+            .unwrap_or_default();
+        let last_line = outcome.entries.last().map_or(0, |e| e.range_in_blamed_file().end);
+        let line_number_width = last_line.to_string().len();
+        for (entry, lines) in outcome.entries_with_lines() {
+            let commit = &commits[&entry.commit_id];
+            let id = if commit.is_boundary {
+                format!("^{}", entry.commit_id.to_hex_with_len(7))
+            } else {
+                entry.commit_id.to_hex_with_len(8).to_string()
+            };
+            let date = commit.author.time.format(gix::date::time::format::ISO8601);
+            for (line_index, line) in lines.into_iter().enumerate() {
+                write!(out, "{id} ")?;
+                if show_filename {
+                    let filename = entry.source_file_name.as_ref().map_or(BStr::new(""), AsRef::as_ref);
+                    write!(out, "{:filename_width$} ", filename.to_str_lossy())?;
+                }
+                write!(
+                    out,
+                    "({:author_width$} {date} {:>line_number_width$}) ",
+                    commit.author.name.to_str_lossy(),
+                    entry.start_in_blamed_file + 1 + line_index as u32,
+                )?;
+                out.write_all(line)?;
+                if !line.ends_with(b"\n") {
+                    writeln!(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_content_line(out: &mut dyn std::io::Write, line: &[u8]) -> std::io::Result<()> {
+        out.write_all(b"\t")?;
+        out.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn write_porcelain_header(
+        commit: &gix::repository::blame::Commit,
+        out: &mut dyn std::io::Write,
+    ) -> std::io::Result<()> {
+        for (role, signature) in [("author", &commit.author), ("committer", &commit.committer)] {
+            writeln!(out, "{role} {}", signature.name)?;
+            writeln!(out, "{role}-mail <{}>", signature.email)?;
+            let time = signature.time.format(gix::date::time::format::RAW);
+            let (seconds, tz) = time.split_once(' ').unwrap_or((time.as_str(), "+0000"));
+            writeln!(out, "{role}-time {seconds}")?;
+            writeln!(out, "{role}-tz {tz}")?;
+        }
+        writeln!(out, "summary {}", commit.summary)?;
+        if commit.is_boundary {
+            writeln!(out, "boundary")?;
+        }
+        Ok(())
+    }
+}
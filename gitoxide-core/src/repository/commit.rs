@@ -1,40 +1,24 @@
-use std::{io::Write, process::Stdio};
-
 use anyhow::{anyhow, bail, Context, Result};
 
-/// Note that this is a quick implementation of commit signature verification that ignores a lot of what
-/// git does and can do, while focussing on the gist of it.
-/// For this to go into `gix`, one will have to implement many more options and various validation programs.
-pub fn verify(repo: gix::Repository, rev_spec: Option<&str>) -> Result<()> {
+/// Verify the signature of the commit at `rev_spec`, or `HEAD`, with the program configured for its format
+/// and write the program's output to `err`, similar to `git verify-commit`.
+pub fn verify(repo: gix::Repository, rev_spec: Option<&str>, mut err: impl std::io::Write) -> Result<()> {
     let rev_spec = rev_spec.unwrap_or("HEAD");
     let commit = repo
         .rev_parse_single(format!("{rev_spec}^{{commit}}").as_str())?
         .object()?
         .into_commit();
-    let (signature, signed_data) = commit
-        .signature()
-        .context("Could not parse commit to obtain signature")?
+    let verification = commit
+        .verify_signature()
+        .context("Could not verify the signature of the commit")?
         .ok_or_else(|| anyhow!("Commit at {rev_spec} is not signed"))?;
+    err.write_all(&verification.output)?;
 
-    let mut signature_storage = tempfile::NamedTempFile::new()?;
-    signature_storage.write_all(signature.as_ref())?;
-    let signed_storage = signature_storage.into_temp_path();
-
-    let mut cmd = std::process::Command::new("gpg");
-    cmd.args(["--keyid-format=long", "--status-fd=1", "--verify"])
-        .arg(&signed_storage)
-        .arg("-")
-        .stdin(Stdio::piped());
-    gix::trace::debug!("About to execute {cmd:?}");
-    let mut child = cmd.spawn()?;
-    child
-        .stdin
-        .take()
-        .expect("configured")
-        .write_all(signed_data.to_bstring().as_ref())?;
-
-    if !child.wait()?.success() {
-        bail!("Command {cmd:?} failed");
+    if verification.status != gix::signing::Status::Good {
+        bail!(
+            "Signature of commit at {rev_spec} could not be verified: {:?}",
+            verification.status
+        );
     }
     Ok(())
 }
@@ -0,0 +1,44 @@
+pub(crate) mod function {
+    use anyhow::bail;
+
+    use crate::OutputFormat;
+
+    pub fn write(
+        repo: gix::Repository,
+        mut options: gix::commitgraph::write::Options,
+        mut out: impl std::io::Write,
+        format: OutputFormat,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("Only human output is currently supported");
+        }
+        if options.changed_paths.is_none() {
+            // Like git, keep changed-path filters if the existing commit-graph has them.
+            options.changed_paths = repo
+                .commit_graph()
+                .ok()
+                .and_then(|graph| graph.bloom_filter_settings().copied());
+        }
+        let mut tips = Vec::new();
+        for reference in repo.references()?.all()? {
+            let mut reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+            let id = reference.peel_to_id_in_place()?;
+            if repo.find_header(id)?.kind() == gix::object::Kind::Commit {
+                tips.push(id.detach());
+            }
+        }
+
+        let outcome = repo.write_commit_graph(tips, options)?;
+        match outcome.path {
+            Some(path) => writeln!(
+                out,
+                "Wrote {} commits to '{}', the commit-graph now consists of {} file(s)",
+                outcome.num_commits,
+                path.display(),
+                outcome.num_files
+            )?,
+            None => writeln!(out, "The commit-graph is up to date")?,
+        }
+        Ok(())
+    }
+}
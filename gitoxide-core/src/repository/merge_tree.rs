@@ -0,0 +1,96 @@
+use anyhow::{bail, Context};
+use gix::bstr::BStr;
+
+pub fn function(
+    repo: gix::Repository,
+    ours: String,
+    theirs: String,
+    base: Option<String>,
+    name_only: bool,
+    mut out: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let options = repo.tree_merge_options()?;
+    let labels = gix::merge::blob::Labels {
+        ancestor: base.as_deref().map(BStr::new),
+        current: Some(BStr::new(&ours)),
+        other: Some(BStr::new(&theirs)),
+    };
+    let tree_merge = match base.as_deref() {
+        Some(base) => repo.merge_trees(
+            peel_to_tree(&repo, base)?,
+            peel_to_tree(&repo, &ours)?,
+            peel_to_tree(&repo, &theirs)?,
+            labels,
+            options,
+        )?,
+        None => {
+            let ours_id = peel_to_commit(&repo, &ours)?;
+            let theirs_id = peel_to_commit(&repo, &theirs)?;
+            repo.merge_commits(ours_id, theirs_id, labels, options)?.tree_merge
+        }
+    };
+
+    writeln!(out, "{}", tree_merge.tree)?;
+    for conflict in &tree_merge.conflicts {
+        if name_only {
+            writeln!(out, "{}", conflict.path)?;
+            continue;
+        }
+        for (stage, entry) in [(1, &conflict.ancestor), (2, &conflict.ours), (3, &conflict.theirs)] {
+            if let Some(entry) = entry {
+                writeln!(out, "{:06o} {} {stage}\t{}", entry.mode.0, entry.id, conflict.path)?;
+            }
+        }
+    }
+    if !name_only && tree_merge.has_conflicts() {
+        writeln!(out)?;
+        for conflict in &tree_merge.conflicts {
+            writeln!(out, "{}", message(conflict))?;
+        }
+    }
+
+    if tree_merge.has_conflicts() {
+        bail!("The merge has {} conflict(s)", tree_merge.conflicts.len());
+    }
+    Ok(())
+}
+
+fn message(conflict: &gix::merge::tree::Conflict) -> String {
+    use gix::merge::tree::ConflictKind;
+    let path = &conflict.path;
+    match &conflict.kind {
+        ConflictKind::Content => format!("CONFLICT (content): Merge conflict in {path}"),
+        ConflictKind::AddAdd => format!("CONFLICT (add/add): Merge conflict in {path}"),
+        ConflictKind::Mode => format!("CONFLICT (mode): Both sides changed the mode of {path} differently"),
+        ConflictKind::Type => format!("CONFLICT (type): Both sides changed the type of {path} differently"),
+        ConflictKind::ModifyDelete => {
+            format!("CONFLICT (modify/delete): {path} was modified on one side and deleted on the other")
+        }
+        ConflictKind::RenameDelete { source } => {
+            format!("CONFLICT (rename/delete): {source} was renamed to {path} on one side and deleted on the other")
+        }
+        ConflictKind::RenameRename { ours, theirs } => {
+            format!("CONFLICT (rename/rename): {path} was renamed to {ours} and to {theirs}")
+        }
+        ConflictKind::DirectoryFile { renamed_to } => {
+            format!("CONFLICT (directory/file): There is a directory in the way of {path}, adding it as {renamed_to}")
+        }
+    }
+}
+
+fn peel_to_tree(repo: &gix::Repository, spec: &str) -> anyhow::Result<gix::ObjectId> {
+    Ok(repo
+        .rev_parse_single(spec)?
+        .object()?
+        .peel_to_kind(gix::object::Kind::Tree)
+        .with_context(|| format!("Could not peel '{spec}' to a tree"))?
+        .id)
+}
+
+fn peel_to_commit(repo: &gix::Repository, spec: &str) -> anyhow::Result<gix::ObjectId> {
+    let object = repo.rev_parse_single(spec)?.object()?;
+    match object.peel_to_kind(gix::object::Kind::Commit) {
+        Ok(commit) => Ok(commit.id),
+        Err(_) => bail!("'{spec}' needs to be a commit, unless --merge-base is given"),
+    }
+}
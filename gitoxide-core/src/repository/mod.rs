@@ -3,11 +3,14 @@ use std::path::PathBuf;
 use anyhow::{Context as AnyhowContext, Result};
 use gix::bstr::BString;
 
-pub fn init(directory: Option<PathBuf>) -> Result<gix::discover::repository::Path> {
+pub fn init(directory: Option<PathBuf>, object_hash: gix::hash::Kind) -> Result<gix::discover::repository::Path> {
     gix::create::into(
         directory.unwrap_or_default(),
         gix::create::Kind::WithWorktree,
-        gix::create::Options::default(),
+        gix::create::Options {
+            object_hash,
+            ..Default::default()
+        },
     )
     .with_context(|| "Repository initialization failed")
 }
@@ -24,6 +27,8 @@ pub mod config;
 mod credential;
 pub use credential::function as credential;
 pub mod attributes;
+pub mod blame;
+pub use blame::function::blame;
 #[cfg(feature = "clean")]
 pub mod clean;
 pub mod dirty;
@@ -44,6 +49,8 @@ mod fsck;
 pub use fsck::function as fsck;
 pub mod index;
 pub mod mailmap;
+mod merge_tree;
+pub use merge_tree::function as merge_tree;
 pub mod odb;
 pub mod remote;
 pub mod revision;
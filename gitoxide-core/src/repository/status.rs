@@ -1,6 +1,6 @@
 use anyhow::bail;
 use gix::bstr::{BStr, BString};
-use gix::status::index_worktree::iter::Item;
+use gix::status::{index_worktree::iter::Item, iter::Item as StatusItem};
 use gix_status::index_as_worktree::{Change, Conflict, EntryStatus};
 use std::path::Path;
 
@@ -108,10 +108,16 @@ pub fn show(
             }
             None => gix::status::Submodule::AsConfigured { check_dirty: false },
         })
-        .into_index_worktree_iter(pathspecs)?;
+        .into_iter(pathspecs)?;
 
     for item in iter.by_ref() {
-        let item = item?;
+        let item = match item? {
+            StatusItem::TreeIndex(change) => {
+                print_tree_index_change(&mut out, prefix, change)?;
+                continue;
+            }
+            StatusItem::IndexWorktree(item) => item,
+        };
         match item {
             Item::Modification {
                 entry: _,
@@ -166,21 +172,54 @@ pub fn show(
 
     let out = iter.outcome_mut().expect("successful iteration has outcome");
 
-    if out.has_changes() && allow_write {
-        out.write_changes().transpose()?;
+    if out.index_worktree.has_changes() && allow_write {
+        out.index_worktree.write_changes().transpose()?;
     }
 
     if statistics {
-        writeln!(err, "{outcome:#?}", outcome = out.index_worktree).ok();
+        writeln!(err, "{outcome:#?}", outcome = out.tree_index).ok();
+        writeln!(err, "{outcome:#?}", outcome = out.index_worktree.index_worktree).ok();
     }
 
-    writeln!(err, "\nhead -> index isn't implemented yet")?;
-    progress.init(Some(out.index.entries().len()), gix::progress::count("files"));
-    progress.set(out.index.entries().len());
+    let num_entries = out.index_worktree.index.entries().len();
+    progress.init(Some(num_entries), gix::progress::count("files"));
+    progress.set(num_entries);
     progress.show_throughput(start);
     Ok(())
 }
 
+fn print_tree_index_change(
+    out: &mut dyn std::io::Write,
+    prefix: &Path,
+    change: gix::status::plumbing::tree_index::Change,
+) -> std::io::Result<()> {
+    use gix::status::plumbing::tree_index::{Change, Summary};
+    let status = match change.summary() {
+        Summary::Added => "A",
+        Summary::Removed => "D",
+        Summary::Modified => "M",
+        Summary::TypeChange => "T",
+        Summary::Renamed => "R",
+        Summary::Copied => "C",
+    };
+    let display_path =
+        |rela_path: &BStr| gix::path::relativize_with_prefix(&gix::path::from_bstr(rela_path), prefix).into_owned();
+    // Staged changes use the first column, just like `git status --short` does.
+    match &change {
+        Change::Rewrite { source_location, .. } => writeln!(
+            out,
+            "{status: <3} {source_rela_path} → {dest_rela_path}",
+            source_rela_path = display_path(source_location.as_ref()).display(),
+            dest_rela_path = display_path(change.location()).display(),
+        ),
+        _ => writeln!(
+            out,
+            "{status: <3} {rela_path}",
+            rela_path = display_path(change.location()).display()
+        ),
+    }
+}
+
 fn print_index_entry_status(
     out: &mut dyn std::io::Write,
     prefix: &Path,
@@ -0,0 +1,153 @@
+use crate::ewah;
+
+/// An uncompressed and growable array of bits, useful to combine bitmaps efficiently before compressing them
+/// with [`to_ewah()`](Bitset::to_ewah()).
+///
+/// Note that instances are equal if the same bits are set, independently of the amount of words they use.
+#[derive(Default, Clone)]
+pub struct Bitset {
+    /// The bits, with the first bit being the least significant bit of the first word.
+    words: Vec<u64>,
+}
+
+impl Bitset {
+    /// Return all words up to the last one with a set bit.
+    fn significant_words(&self) -> &[u64] {
+        let len = self.words.iter().rposition(|word| *word != 0).map_or(0, |pos| pos + 1);
+        &self.words[..len]
+    }
+}
+
+impl PartialEq for Bitset {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_words() == other.significant_words()
+    }
+}
+
+impl Eq for Bitset {}
+
+impl std::hash::Hash for Bitset {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.significant_words().hash(state);
+    }
+}
+
+impl std::fmt::Debug for Bitset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter_set_bits()).finish()
+    }
+}
+
+/// Instantiation
+impl Bitset {
+    /// Create a new instance with enough room for `num_bits` without reallocation.
+    pub fn with_capacity(num_bits: usize) -> Self {
+        Bitset {
+            words: Vec::with_capacity((num_bits + 63) / 64),
+        }
+    }
+
+    /// Create a new instance from uncompressed `words`, with the first bit being the least significant bit of the first word.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        Bitset { words }
+    }
+}
+
+/// Access
+impl Bitset {
+    /// Return `true` if the bit at `index` is set.
+    pub fn is_set(&self, index: usize) -> bool {
+        self.words.get(index / 64).copied().unwrap_or(0) & (1 << (index % 64)) != 0
+    }
+
+    /// Return `true` if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Return the amount of bits that are set.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Return an iterator over the indices of all set bits, in ascending order.
+    pub fn iter_set_bits(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(word_index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    word_index * 64 + bit
+                })
+            })
+        })
+    }
+
+    /// Return the uncompressed words backing this instance.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Compress this instance into an EWAH bitmap, exactly like git would.
+    pub fn to_ewah(&self) -> ewah::Vec {
+        ewah::Vec::from_words(&self.words)
+    }
+}
+
+/// Mutation
+impl Bitset {
+    /// Set the bit at `index`, and return `true` if it wasn't set before.
+    pub fn set(&mut self, index: usize) -> bool {
+        let word_index = index / 64;
+        if word_index >= self.words.len() {
+            self.words.resize(word_index + 1, 0);
+        }
+        let word = &mut self.words[word_index];
+        let mask = 1 << (index % 64);
+        let was_unset = *word & mask == 0;
+        *word |= mask;
+        was_unset
+    }
+
+    /// Set all bits that are set in `other` as well.
+    pub fn or(&mut self, other: &Bitset) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    /// Unset all bits that are set in `other`.
+    pub fn and_not(&mut self, other: &Bitset) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+
+    /// Only keep the bits that are also set in `other`.
+    pub fn and(&mut self, other: &Bitset) {
+        self.words.truncate(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+
+    /// Flip all bits that are set in `other`.
+    pub fn xor(&mut self, other: &Bitset) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+}
+
+impl From<&ewah::Vec> for Bitset {
+    fn from(bitmap: &ewah::Vec) -> Self {
+        Bitset::from_words(bitmap.to_words())
+    }
+}
@@ -91,30 +91,246 @@ mod access {
         pub fn num_bits(&self) -> usize {
             self.num_bits.try_into().expect("we are not on 16 bit systems")
         }
+
+        /// Decompress all bits into words of 64 bits each, with the first bit being the least significant bit of the first word.
+        ///
+        /// Note that trailing words without any set bit may be omitted.
+        pub fn to_words(&self) -> std::vec::Vec<u64> {
+            let mut out = std::vec::Vec::with_capacity((self.num_bits() + 63) / 64);
+            let mut iter = self.bits.iter();
+            while let Some(word) = iter.next() {
+                let fill = if rlw_runbit_is_set(word) { u64::MAX } else { 0 };
+                out.extend(std::iter::repeat(fill).take(rlw_running_len(word) as usize));
+                for _ in 0..rlw_literal_words(word) {
+                    out.push(
+                        *iter
+                            .next()
+                            .expect("BUG: ran out of words while going through uncompressed portion"),
+                    );
+                }
+            }
+            out
+        }
     }
 
     #[inline]
-    fn rlw_running_len_bits(w: &u64) -> u64 {
+    pub(super) fn rlw_running_len_bits(w: &u64) -> u64 {
         rlw_running_len(w) * 64
     }
 
     #[inline]
-    fn rlw_running_len(w: &u64) -> u64 {
+    pub(super) fn rlw_running_len(w: &u64) -> u64 {
         (w >> 1) & RLW_LARGEST_RUNNING_COUNT
     }
 
     #[inline]
-    fn rlw_literal_words(w: &u64) -> u64 {
+    pub(super) fn rlw_literal_words(w: &u64) -> u64 {
         w >> (1 + RLW_RUNNING_BITS)
     }
 
     #[inline]
-    fn rlw_runbit_is_set(w: &u64) -> bool {
+    pub(super) fn rlw_runbit_is_set(w: &u64) -> bool {
         w & 1 == 1
     }
 
-    const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    pub(super) const RLW_RUNNING_BITS: u64 = 4 * 8;
+    pub(super) const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    pub(super) const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << (64 - 1 - RLW_RUNNING_BITS)) - 1;
+}
+
+mod encode {
+    use super::{
+        access::{
+            rlw_literal_words, rlw_runbit_is_set, rlw_running_len, RLW_LARGEST_LITERAL_COUNT,
+            RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS,
+        },
+        Vec,
+    };
+
+    impl Vec {
+        /// Compress the given uncompressed `words`, with the first bit being the least significant bit of the first word.
+        ///
+        /// Trailing words without any set bit are not stored, just like git does it.
+        pub fn from_words(words: &[u64]) -> Self {
+            let out = Builder::from_words(words);
+            Vec {
+                // NOTE: git truncates the same way, there is no way to represent more bits in the on-disk format.
+                num_bits: out.num_bits as u32,
+                bits: out.bits,
+                rlw: out.rlw as u64,
+            }
+        }
+
+        /// Compress the bits at the given `indices`, which must be sorted in ascending order, into a new instance.
+        ///
+        /// This produces the exact same representation as calling `ewah_set()` for each index in git, which means
+        /// that the amount of bits will be one past the highest set bit, or `0` if `indices` is empty.
+        pub fn from_set_bits(indices: impl IntoIterator<Item = usize>) -> Self {
+            let mut words = std::vec::Vec::<u64>::new();
+            let mut num_bits = 0;
+            for index in indices {
+                let word_index = index / 64;
+                if word_index >= words.len() {
+                    words.resize(word_index + 1, 0);
+                }
+                words[word_index] |= 1 << (index % 64);
+                num_bits = index + 1;
+            }
+            let out = if words.is_empty() {
+                Builder::default()
+            } else {
+                Builder::from_words(&words)
+            };
+            Vec {
+                num_bits: num_bits.try_into().expect("no more than u32::MAX bits"),
+                bits: out.bits,
+                rlw: out.rlw as u64,
+            }
+        }
+
+        /// The amount of bytes needed to store this instance with [`write_to()`](Self::write_to()).
+        pub fn size_in_bytes(&self) -> usize {
+            4 /*num bits*/ + 4 /*num words*/ + self.bits.len() * 8 + 4 /*rlw*/
+        }
+
+        /// Write the on-disk representation of this instance to `out`, which can be read back with [`decode()`](super::decode()).
+        pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&(self.bits.len() as u32).to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&(self.rlw as u32).to_be_bytes())
+        }
+    }
+
+    /// Produces the exact same compressed representation as git.
+    struct Builder {
+        bits: std::vec::Vec<u64>,
+        /// The index of the current run-length word in `bits`.
+        rlw: usize,
+        num_bits: u64,
+    }
+
+    impl Default for Builder {
+        fn default() -> Self {
+            Builder {
+                bits: vec![0],
+                rlw: 0,
+                num_bits: 0,
+            }
+        }
+    }
+
+    impl Builder {
+        fn from_words(words: &[u64]) -> Self {
+            let mut out = Builder::default();
+            let mut empty_words = 0;
+            let mut last_word = 0;
+            for &word in words {
+                if word == 0 {
+                    empty_words += 1;
+                    continue;
+                }
+                if last_word != 0 {
+                    out.add(last_word);
+                }
+                if empty_words > 0 {
+                    out.add_empty_words(false, empty_words);
+                    empty_words = 0;
+                }
+                last_word = word;
+            }
+            out.add(last_word);
+            out
+        }
+
+        fn add(&mut self, word: u64) {
+            self.num_bits += 64;
+            match word {
+                0 => self.add_empty_word(false),
+                u64::MAX => self.add_empty_word(true),
+                _ => self.add_literal(word),
+            }
+        }
+
+        fn add_empty_word(&mut self, bit: bool) {
+            let rlw = self.bits[self.rlw];
+            let no_literal = rlw_literal_words(&rlw) == 0;
+            let running_len = rlw_running_len(&rlw);
+            if no_literal && running_len == 0 {
+                self.set_run_bit(bit);
+            }
+            if no_literal && rlw_runbit_is_set(&self.bits[self.rlw]) == bit && running_len < RLW_LARGEST_RUNNING_COUNT {
+                self.set_running_len(running_len + 1);
+            } else {
+                self.push_rlw();
+                self.set_run_bit(bit);
+                self.set_running_len(1);
+            }
+        }
+
+        fn add_literal(&mut self, word: u64) {
+            let literal_words = rlw_literal_words(&self.bits[self.rlw]);
+            if literal_words >= RLW_LARGEST_LITERAL_COUNT {
+                self.push_rlw();
+                self.set_literal_words(1);
+            } else {
+                self.set_literal_words(literal_words + 1);
+            }
+            self.bits.push(word);
+        }
+
+        fn add_empty_words(&mut self, bit: bool, mut count: u64) {
+            if count == 0 {
+                return;
+            }
+            self.num_bits += count * 64;
+            let rlw = self.bits[self.rlw];
+            if rlw_runbit_is_set(&rlw) != bit && rlw_running_len(&rlw) + rlw_literal_words(&rlw) == 0 {
+                self.set_run_bit(bit);
+            } else if rlw_literal_words(&rlw) != 0 || rlw_runbit_is_set(&rlw) != bit {
+                self.push_rlw();
+                self.set_run_bit(bit);
+            }
+            let running_len = rlw_running_len(&self.bits[self.rlw]);
+            let can_add = count.min(RLW_LARGEST_RUNNING_COUNT - running_len);
+            self.set_running_len(running_len + can_add);
+            count -= can_add;
+
+            while count >= RLW_LARGEST_RUNNING_COUNT {
+                self.push_rlw();
+                self.set_run_bit(bit);
+                self.set_running_len(RLW_LARGEST_RUNNING_COUNT);
+                count -= RLW_LARGEST_RUNNING_COUNT;
+            }
+            if count > 0 {
+                self.push_rlw();
+                self.set_run_bit(bit);
+                self.set_running_len(count);
+            }
+        }
+
+        fn push_rlw(&mut self) {
+            self.rlw = self.bits.len();
+            self.bits.push(0);
+        }
+
+        fn set_run_bit(&mut self, bit: bool) {
+            let rlw = &mut self.bits[self.rlw];
+            *rlw = (*rlw & !1) | u64::from(bit);
+        }
+
+        fn set_running_len(&mut self, len: u64) {
+            let rlw = &mut self.bits[self.rlw];
+            *rlw = (*rlw & !(RLW_LARGEST_RUNNING_COUNT << 1)) | (len << 1);
+        }
+
+        fn set_literal_words(&mut self, count: u64) {
+            let rlw = &mut self.bits[self.rlw];
+            *rlw = (*rlw & ((1 << (1 + RLW_RUNNING_BITS)) - 1)) | (count << (1 + RLW_RUNNING_BITS));
+        }
+    }
 }
 
 /// A growable collection of u64 that are seen as stream of individual bits.
@@ -0,0 +1,32 @@
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The error returned by [file()](crate::file()) and [file_incremental()](crate::file_incremental()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("No commit was given")]
+    EmptyTraversal,
+    #[error("The file to blame at '{file_path}' wasn't found in the first commit at {commit_id}")]
+    FileMissing { file_path: BString, commit_id: ObjectId },
+    #[error("The line range {start}..{end} is invalid for a file with {lines} lines")]
+    InvalidLineRange { start: u32, end: u32, lines: u32 },
+    #[error("Couldn't find commit or tree in the object database")]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not find existing iterator over a commit or tree")]
+    FindExistingIter(#[from] gix_object::find::existing_iter::Error),
+    #[error("Could not decode a commit or tree")]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("Failed to obtain the next commit in the commit-graph traversal")]
+    Traverse(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    DiffTree(#[from] gix_diff::tree::changes::Error),
+    #[error("Failure during rename tracking")]
+    RenameTracking(#[from] gix_diff::rewrites::tracker::emit::Error),
+    #[error(transparent)]
+    BlobDiffSetResource(#[from] gix_diff::blob::platform::set_resource::Error),
+    #[error(transparent)]
+    BlobDiffPrepare(#[from] gix_diff::blob::platform::prepare_diff::Error),
+    #[error("The callback for blamed entries failed")]
+    OnEntry(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
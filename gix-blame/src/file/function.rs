@@ -0,0 +1,462 @@
+#![allow(clippy::result_large_err)]
+use std::{collections::HashMap, num::NonZeroU32, ops::Range};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_diff::{
+    blob::{intern::TokenSource, platform::prepare_diff::Operation, sources::byte_lines_with_terminator, ResourceKind},
+    rewrites::{tracker::visit::SourceKind, Tracker},
+    tree::{visit, Visit},
+};
+use gix_hash::{oid, ObjectId};
+use gix_object::{FindExt, FindObjectOrHeader};
+
+use super::{split_hunks, Change, Suspect, UnblamedHunk};
+use crate::{BlameEntry, Error, Options, Outcome, Statistics};
+
+/// Produce a list of consecutive [`BlameEntry`] instances to indicate in which commits the ranges of the file
+/// at `file_path` within the first commit of `traverse` were introduced.
+///
+/// * `odb`
+///    - Access to database objects, also for used for diffing.
+///    - Should have an object cache for good diff performance.
+/// * `traverse`
+///    - The list of commits from the most recent to prior ones, following all parents sorted
+///      by time, as it's important to see commits before their parents. The first commit is the one
+///      containing the file to blame.
+///    - Commits that aren't part of the traversal are considered to have introduced the lines they are
+///      blamed for, which allows to limit the blame to a range of commits.
+/// * `resource_cache`
+///    - Used for diffing blobs of the file, and for rename tracking.
+/// * `file_path`
+///    - The path to the file to blame, relative to the root of the repository.
+/// * `options`
+///    - Control which lines to blame, if whitespace is ignored, which commits to ignore and if renames are followed.
+///
+/// ## The algorithm
+///
+/// Each commit that is currently blamed for some lines, a *suspect*, is compared to each of its parents in turn.
+/// If the file is unchanged in a parent, all lines are passed on to that parent. Otherwise, the file is diffed with
+/// its version in the parent, and all unchanged lines are passed to the parent. Lines that remain after all parents
+/// were checked are attributed to the suspect.
+///
+/// If the file doesn't exist in a parent and `options.rewrites` is set, the trees of both commits are diffed with rename
+/// tracking to find the name of the file in the parent.
+pub fn file<E>(
+    odb: &impl FindObjectOrHeader,
+    traverse: impl IntoIterator<Item = Result<gix_traverse::commit::Info, E>>,
+    resource_cache: &mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    file_incremental(odb, traverse, resource_cache, file_path, options, |_| Ok(()))
+}
+
+/// Like [`file()`], but call `on_entry` with each [`BlameEntry`] as soon as its lines were attributed to a commit,
+/// similar to `git blame --incremental`.
+///
+/// Entries are passed in the order they are found, and those that are adjacent aren't merged yet, unlike the entries of the
+/// returned [`Outcome`]. If `on_entry` fails, the blame is aborted with its error.
+pub fn file_incremental<E>(
+    odb: &impl FindObjectOrHeader,
+    traverse: impl IntoIterator<Item = Result<gix_traverse::commit::Info, E>>,
+    resource_cache: &mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    options: Options,
+    mut on_entry: impl FnMut(&BlameEntry) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>,
+) -> Result<Outcome, Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    let _span = gix_trace::coarse!("gix_blame::file_incremental()", ?file_path);
+    let mut traverse = traverse.into_iter().peekable();
+    let first_commit = match traverse.peek() {
+        Some(Ok(info)) => info.id,
+        Some(Err(_)) => {
+            let err = traverse.next().expect("peeked").expect_err("peeked error");
+            return Err(Error::Traverse(err.into()));
+        }
+        None => return Err(Error::EmptyTraversal),
+    };
+
+    let mut stats = Statistics::default();
+    let (mut buf, mut buf2) = (Vec::new(), Vec::new());
+    let blamed_file_id = find_path_entry_in_commit(odb, &first_commit, file_path, &mut buf, &mut buf2, &mut stats)?
+        .ok_or_else(|| Error::FileMissing {
+            file_path: file_path.to_owned(),
+            commit_id: first_commit,
+        })?;
+    let blob = odb.find_blob(&blamed_file_id, &mut buf)?.data.to_vec();
+    let num_lines = byte_lines_with_terminator(&blob).tokenize().count() as u32;
+    let hunks = ranges_to_hunks(&options.ranges, num_lines)?;
+
+    let mut suspects = HashMap::<ObjectId, Vec<Suspect>>::new();
+    if !hunks.is_empty() {
+        suspects.insert(
+            first_commit,
+            vec![Suspect {
+                path: file_path.to_owned(),
+                id: blamed_file_id,
+                hunks,
+            }],
+        );
+    }
+
+    let mut entries = Vec::new();
+    for item in traverse {
+        if suspects.is_empty() {
+            break;
+        }
+        let item = item.map_err(|err| Error::Traverse(err.into()))?;
+        let Some(commit_suspects) = suspects.remove(&item.id) else {
+            continue;
+        };
+        stats.commits_traversed += 1;
+
+        let is_ignored = options.ignore_revs.contains(&item.id);
+        for suspect in commit_suspects {
+            let mut hunks = suspect.hunks;
+            let mut parents_with_file = Vec::new();
+            for parent_id in item.parent_ids.iter() {
+                let entry = match find_path_entry_in_commit(
+                    odb,
+                    parent_id,
+                    suspect.path.as_ref(),
+                    &mut buf,
+                    &mut buf2,
+                    &mut stats,
+                )? {
+                    Some(id) => Some((suspect.path.clone(), id)),
+                    None => match options.rewrites {
+                        Some(rewrites) => find_rename_source(
+                            odb,
+                            parent_id,
+                            &item.id,
+                            suspect.path.as_ref(),
+                            resource_cache,
+                            rewrites,
+                            &mut stats,
+                        )?,
+                        None => None,
+                    },
+                };
+                if let Some((path, id)) = entry {
+                    if id == suspect.id {
+                        add_hunks(&mut suspects, *parent_id, path, id, std::mem::take(&mut hunks));
+                        break;
+                    }
+                    parents_with_file.push((*parent_id, path, id));
+                }
+            }
+
+            for (idx, (parent_id, path, id)) in parents_with_file.into_iter().enumerate() {
+                if hunks.is_empty() {
+                    break;
+                }
+                let changes = diff_blobs(
+                    odb,
+                    resource_cache,
+                    (id, path.as_ref()),
+                    (suspect.id, suspect.path.as_ref()),
+                    options.ignore_whitespace,
+                    &mut stats,
+                )?;
+                let (for_parent, remaining) = split_hunks(hunks, &changes, is_ignored && idx == 0);
+                hunks = remaining;
+                add_hunks(&mut suspects, parent_id, path, id, for_parent);
+            }
+
+            blame_hunks(
+                &mut entries,
+                item.id,
+                suspect.path.as_ref(),
+                file_path,
+                hunks,
+                &mut on_entry,
+            )?;
+        }
+    }
+
+    // Whatever is left was introduced by commits at the boundary of the traversal.
+    for (commit_id, commit_suspects) in suspects {
+        for suspect in commit_suspects {
+            blame_hunks(
+                &mut entries,
+                commit_id,
+                suspect.path.as_ref(),
+                file_path,
+                suspect.hunks,
+                &mut on_entry,
+            )?;
+        }
+    }
+
+    Ok(Outcome {
+        entries: coalesce_entries(entries),
+        blob,
+        statistics: stats,
+    })
+}
+
+/// Turn `ranges` into hunks to blame in a file with `num_lines`, sorted and without overlap.
+fn ranges_to_hunks(ranges: &[Range<u32>], num_lines: u32) -> Result<Vec<UnblamedHunk>, Error> {
+    if num_lines == 0 {
+        return Ok(Vec::new());
+    }
+    if ranges.is_empty() {
+        return Ok(vec![UnblamedHunk {
+            range_in_blamed_file: 0..num_lines,
+            range_in_suspect: 0..num_lines,
+        }]);
+    }
+
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+    let mut out = Vec::<UnblamedHunk>::with_capacity(ranges.len());
+    for range in ranges {
+        if range.start >= range.end || range.start >= num_lines {
+            return Err(Error::InvalidLineRange {
+                start: range.start,
+                end: range.end,
+                lines: num_lines,
+            });
+        }
+        let range = range.start..range.end.min(num_lines);
+        match out.last_mut() {
+            Some(last) if last.range_in_blamed_file.end >= range.start => {
+                let end = last.range_in_blamed_file.end.max(range.end);
+                last.range_in_blamed_file.end = end;
+                last.range_in_suspect.end = end;
+            }
+            _ => out.push(UnblamedHunk {
+                range_in_blamed_file: range.clone(),
+                range_in_suspect: range,
+            }),
+        }
+    }
+    Ok(out)
+}
+
+fn add_hunks(
+    suspects: &mut HashMap<ObjectId, Vec<Suspect>>,
+    commit_id: ObjectId,
+    path: BString,
+    id: ObjectId,
+    hunks: Vec<UnblamedHunk>,
+) {
+    if hunks.is_empty() {
+        return;
+    }
+    let commit_suspects = suspects.entry(commit_id).or_default();
+    match commit_suspects.iter_mut().find(|s| s.path == path) {
+        Some(suspect) => suspect.hunks.extend(hunks),
+        None => commit_suspects.push(Suspect { path, id, hunks }),
+    }
+}
+
+/// Attribute `hunks` to `commit_id`, and pass each new entry to `on_entry` before adding it to `entries`.
+fn blame_hunks(
+    entries: &mut Vec<BlameEntry>,
+    commit_id: ObjectId,
+    path: &BStr,
+    blamed_path: &BStr,
+    hunks: Vec<UnblamedHunk>,
+    on_entry: &mut impl FnMut(&BlameEntry) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>,
+) -> Result<(), Error> {
+    for hunk in hunks {
+        let Some(len) = NonZeroU32::new(hunk.range_in_suspect.len() as u32) else {
+            continue;
+        };
+        let entry = BlameEntry {
+            start_in_blamed_file: hunk.range_in_blamed_file.start,
+            start_in_source_file: hunk.range_in_suspect.start,
+            len,
+            commit_id,
+            source_file_name: (path != blamed_path).then(|| path.to_owned()),
+        };
+        on_entry(&entry).map_err(Error::OnEntry)?;
+        entries.push(entry);
+    }
+    Ok(())
+}
+
+/// Sort `entries` by their position in the blamed file, and merge those which are adjacent in both the blamed and the source file.
+fn coalesce_entries(mut entries: Vec<BlameEntry>) -> Vec<BlameEntry> {
+    entries.sort_by_key(|entry| entry.start_in_blamed_file);
+    let mut out = Vec::<BlameEntry>::with_capacity(entries.len());
+    for entry in entries {
+        match out.last_mut() {
+            Some(last)
+                if last.commit_id == entry.commit_id
+                    && last.source_file_name == entry.source_file_name
+                    && last.range_in_blamed_file().end == entry.start_in_blamed_file
+                    && last.range_in_source_file().end == entry.start_in_source_file =>
+            {
+                last.len = last.len.saturating_add(entry.len.get());
+            }
+            _ => out.push(entry),
+        }
+    }
+    out
+}
+
+/// Return the id of the blob at `path` in the tree of `commit_id`, or `None` if there is no such blob.
+fn find_path_entry_in_commit(
+    odb: &impl FindObjectOrHeader,
+    commit_id: &oid,
+    path: &BStr,
+    buf: &mut Vec<u8>,
+    buf2: &mut Vec<u8>,
+    stats: &mut Statistics,
+) -> Result<Option<ObjectId>, Error> {
+    let mut id = odb.find_commit_iter(commit_id, buf)?.tree_id()?;
+    let mut components = path.split_str("/").peekable();
+    while let Some(component) = components.next() {
+        let tree = odb.find_tree_iter(&id, buf2)?;
+        stats.trees_decoded += 1;
+        let mut found = None;
+        for entry in tree {
+            let entry = entry?;
+            if entry.filename == component {
+                found = Some((entry.mode, entry.oid.to_owned()));
+                break;
+            }
+        }
+        match found {
+            Some((mode, entry_id)) if components.peek().is_none() => {
+                return Ok((!mode.is_tree() && !mode.is_commit()).then_some(entry_id));
+            }
+            Some((mode, entry_id)) if mode.is_tree() => id = entry_id,
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Diff the trees of `parent_id` and `commit_id` with rename tracking and return the path and id of the source of `path`
+/// in the parent, if `path` was renamed.
+fn find_rename_source(
+    odb: &impl FindObjectOrHeader,
+    parent_id: &oid,
+    commit_id: &oid,
+    path: &BStr,
+    resource_cache: &mut gix_diff::blob::Platform,
+    rewrites: gix_diff::Rewrites,
+    stats: &mut Statistics,
+) -> Result<Option<(BString, ObjectId)>, Error> {
+    let (mut buf, mut lhs_buf, mut rhs_buf) = (Vec::new(), Vec::new(), Vec::new());
+    let lhs_tree = odb.find_commit_iter(parent_id, &mut buf)?.tree_id()?;
+    let rhs_tree = odb.find_commit_iter(commit_id, &mut buf)?.tree_id()?;
+    let lhs = odb.find_tree_iter(&lhs_tree, &mut lhs_buf)?;
+    let rhs = odb.find_tree_iter(&rhs_tree, &mut rhs_buf)?;
+
+    let mut delegate = Collect {
+        recorder: Default::default(),
+        tracker: Tracker::new(rewrites),
+    };
+    gix_diff::tree::Changes::from(lhs).needed_to_obtain(rhs, gix_diff::tree::State::default(), odb, &mut delegate)?;
+    stats.trees_diffed += 1;
+
+    let mut source = None;
+    delegate.tracker.emit(
+        |dest, src| match src.filter(|src| src.kind == SourceKind::Rename && dest.location == path) {
+            Some(src) => {
+                source = Some((src.location.to_owned(), src.id));
+                visit::Action::Cancel
+            }
+            None => visit::Action::Continue,
+        },
+        resource_cache,
+        odb,
+        |_push| Ok::<_, std::convert::Infallible>(()),
+    )?;
+    Ok(source)
+}
+
+/// A delegate that feeds all changes into a rename tracker, while ignoring everything else.
+struct Collect {
+    recorder: gix_diff::tree::Recorder,
+    tracker: Tracker<visit::Change>,
+}
+
+impl Visit for Collect {
+    fn pop_front_tracked_path_and_set_current(&mut self) {
+        self.recorder.pop_front_tracked_path_and_set_current()
+    }
+
+    fn push_back_tracked_path_component(&mut self, component: &BStr) {
+        self.recorder.push_back_tracked_path_component(component)
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.recorder.push_path_component(component)
+    }
+
+    fn pop_path_component(&mut self) {
+        self.recorder.pop_path_component()
+    }
+
+    fn visit(&mut self, change: visit::Change) -> visit::Action {
+        self.tracker.try_push_change(change, self.recorder.path());
+        visit::Action::Continue
+    }
+}
+
+/// Diff the blobs `old` and `new`, each identified by id and path, and return the changed line ranges as
+/// pairs of ranges in `old` and `new` respectively.
+fn diff_blobs(
+    odb: &impl FindObjectOrHeader,
+    resource_cache: &mut gix_diff::blob::Platform,
+    old: (ObjectId, &BStr),
+    new: (ObjectId, &BStr),
+    ignore_whitespace: bool,
+    stats: &mut Statistics,
+) -> Result<Vec<Change>, Error> {
+    resource_cache.set_resource(
+        old.0,
+        gix_object::tree::EntryKind::Blob,
+        old.1,
+        ResourceKind::OldOrSource,
+        odb,
+    )?;
+    resource_cache.set_resource(
+        new.0,
+        gix_object::tree::EntryKind::Blob,
+        new.1,
+        ResourceKind::NewOrDestination,
+        odb,
+    )?;
+    let outcome = resource_cache.prepare_diff()?;
+    stats.blobs_diffed += 1;
+
+    let algorithm = match outcome.operation {
+        Operation::InternalDiff { algorithm } => algorithm,
+        Operation::ExternalCommand { .. } => Default::default(),
+        Operation::SourceOrDestinationIsBinary => {
+            // There are no lines to compare, so everything changed.
+            return Ok(vec![(0..0, 0..u32::MAX)]);
+        }
+    };
+    let old = outcome.old.data.as_slice().unwrap_or_default();
+    let new = outcome.new.data.as_slice().unwrap_or_default();
+
+    let mut changes = Vec::new();
+    let sink = |before: Range<u32>, after: Range<u32>| changes.push((before, after));
+    if ignore_whitespace {
+        let mut input = gix_diff::blob::intern::InternedInput::<Vec<u8>>::default();
+        let without_whitespace =
+            |line: &[u8]| -> Vec<u8> { line.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect() };
+        input.update_before(byte_lines_with_terminator(old).tokenize().map(without_whitespace));
+        input.update_after(byte_lines_with_terminator(new).tokenize().map(without_whitespace));
+        gix_diff::blob::diff(algorithm, &input, sink);
+    } else {
+        let input = gix_diff::blob::intern::InternedInput::new(
+            byte_lines_with_terminator(old),
+            byte_lines_with_terminator(new),
+        );
+        gix_diff::blob::diff(algorithm, &input, sink);
+    }
+    Ok(changes)
+}
@@ -0,0 +1,126 @@
+use std::ops::Range;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+pub(super) mod function;
+
+/// A pair of line ranges in a parent and the suspect respectively, where the lines of the parent were replaced by those of the suspect.
+pub(super) type Change = (Range<u32>, Range<u32>);
+
+/// A hunk of lines in the blamed file which still has to be attributed to a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct UnblamedHunk {
+    /// The lines in the blamed file.
+    pub range_in_blamed_file: Range<u32>,
+    /// The lines in the file as seen by the suspect, which is the commit that is currently blamed for these lines.
+    pub range_in_suspect: Range<u32>,
+}
+
+impl UnblamedHunk {
+    /// Return the part of this hunk that covers `range_in_suspect`, which must be contained in our own range,
+    /// and move it to `start_in_parent` in the file as seen by a parent.
+    fn sub_hunk(&self, range_in_suspect: Range<u32>, start_in_parent: u32) -> UnblamedHunk {
+        let offset = range_in_suspect.start - self.range_in_suspect.start;
+        let len = range_in_suspect.end - range_in_suspect.start;
+        let start = self.range_in_blamed_file.start + offset;
+        UnblamedHunk {
+            range_in_blamed_file: start..start + len,
+            range_in_suspect: start_in_parent..start_in_parent + len,
+        }
+    }
+}
+
+/// A version of the blamed file in a particular commit, along with all hunks it is currently blamed for.
+#[derive(Debug, Clone)]
+pub(super) struct Suspect {
+    /// The path of the file in the commit.
+    pub path: BString,
+    /// The blob of the file in the commit.
+    pub id: ObjectId,
+    /// The hunks the commit is blamed for.
+    pub hunks: Vec<UnblamedHunk>,
+}
+
+/// A portion of the file as seen by a suspect, and where it is located in the file as seen by a parent.
+#[derive(Debug)]
+struct Segment {
+    /// The lines in the file of the suspect.
+    range_in_suspect: Range<u32>,
+    /// The first line of the corresponding lines in the parent.
+    start_in_parent: u32,
+    /// If `Some(…)`, the lines were changed and replace the given lines of the parent.
+    changed_from: Option<Range<u32>>,
+}
+
+/// Build segments that cover all lines of the suspect from `changes`, in order.
+fn segments(changes: &[Change]) -> Vec<Segment> {
+    let mut out = Vec::with_capacity(changes.len() * 2 + 1);
+    let (mut parent_pos, mut suspect_pos) = (0, 0);
+    for (in_parent, in_suspect) in changes {
+        if in_suspect.start > suspect_pos {
+            out.push(Segment {
+                range_in_suspect: suspect_pos..in_suspect.start,
+                start_in_parent: parent_pos,
+                changed_from: None,
+            });
+        }
+        if !in_suspect.is_empty() {
+            out.push(Segment {
+                range_in_suspect: in_suspect.clone(),
+                start_in_parent: in_parent.start,
+                changed_from: Some(in_parent.clone()),
+            });
+        }
+        parent_pos = in_parent.end;
+        suspect_pos = in_suspect.end;
+    }
+    out.push(Segment {
+        range_in_suspect: suspect_pos..u32::MAX,
+        start_in_parent: parent_pos,
+        changed_from: None,
+    });
+    out
+}
+
+/// Split `hunks` according to `changes` between a parent and the suspect, and return `(hunks_for_parent, remaining_hunks)`.
+/// Unchanged lines are passed to the parent, while changed lines remain with the suspect.
+///
+/// If `pass_changed_lines` is `true`, changed lines are passed to the lines at the same offset in the parent as well,
+/// as long as the parent has such lines.
+pub(super) fn split_hunks(
+    hunks: Vec<UnblamedHunk>,
+    changes: &[Change],
+    pass_changed_lines: bool,
+) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    let segments = segments(changes);
+    let (mut for_parent, mut remaining) = (Vec::new(), Vec::new());
+    for hunk in hunks {
+        let first = segments.partition_point(|s| s.range_in_suspect.end <= hunk.range_in_suspect.start);
+        for segment in &segments[first..] {
+            if segment.range_in_suspect.start >= hunk.range_in_suspect.end {
+                break;
+            }
+            let start = segment.range_in_suspect.start.max(hunk.range_in_suspect.start);
+            let end = segment.range_in_suspect.end.min(hunk.range_in_suspect.end);
+            let offset = start - segment.range_in_suspect.start;
+            match &segment.changed_from {
+                None => for_parent.push(hunk.sub_hunk(start..end, segment.start_in_parent + offset)),
+                Some(in_parent) => {
+                    let passed_end = if pass_changed_lines {
+                        (segment.range_in_suspect.start + in_parent.len() as u32).clamp(start, end)
+                    } else {
+                        start
+                    };
+                    if passed_end > start {
+                        for_parent.push(hunk.sub_hunk(start..passed_end, in_parent.start + offset));
+                    }
+                    if end > passed_end {
+                        remaining.push(hunk.sub_hunk(passed_end..end, passed_end));
+                    }
+                }
+            }
+        }
+    }
+    (for_parent, remaining)
+}
@@ -0,0 +1,19 @@
+//! Attribute each line of a file to the commit that introduced it, similar to what `git blame` does.
+//!
+//! The algorithm walks the commit-graph starting at the commit that is to be blamed, diffing the file with its
+//! version in each parent to pass the blame for all unchanged lines on to the parent. Lines that can't be passed on
+//! are attributed to the commit itself.
+//!
+//! Renames are followed by running rename tracking on the trees of a commit and its parent whenever the file
+//! can't be found in the parent anymore.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+mod error;
+pub use error::Error;
+
+mod types;
+pub use types::{BlameEntry, Options, Outcome, Statistics};
+
+mod file;
+pub use file::function::{file, file_incremental};
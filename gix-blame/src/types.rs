@@ -0,0 +1,92 @@
+use std::{collections::HashSet, num::NonZeroU32, ops::Range};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+/// Options to be passed to [`file()`](crate::file()).
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// The 0-based, non-empty line ranges to blame, similar to `git blame -L`, or all lines if empty.
+    ///
+    /// Overlapping ranges are merged, and ranges that reach beyond the end of the file are truncated.
+    pub ranges: Vec<Range<u32>>,
+    /// If `true`, whitespace is ignored when comparing lines, similar to `git blame -w`.
+    pub ignore_whitespace: bool,
+    /// Commits to skip when assigning blame, similar to `git blame --ignore-rev`.
+    ///
+    /// Lines changed by these commits are attributed to the previous commit that touched the corresponding line instead,
+    /// if there is a corresponding line in the parent.
+    pub ignore_revs: HashSet<ObjectId>,
+    /// If `Some(…)`, follow the file across renames, using the given configuration to detect them.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// The outcome of [`file()`](crate::file()).
+#[derive(Debug, Default, Clone)]
+pub struct Outcome {
+    /// One entry in sequential order, to associate a hunk in the blamed file with the commit that introduced it.
+    pub entries: Vec<BlameEntry>,
+    /// The contents of the blamed file as seen in the first commit of the traversal.
+    pub blob: Vec<u8>,
+    /// Additional information about the amount of work performed to produce the blame.
+    pub statistics: Statistics,
+}
+
+impl Outcome {
+    /// Return an iterator over each entry in [`entries`](Self::entries), along with its lines in the blamed file,
+    /// including their line terminators.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&BlameEntry, Vec<&[u8]>)> + '_ {
+        let mut lines = self.blob.lines_with_terminator();
+        let mut line_number = 0;
+        self.entries.iter().map(move |entry| {
+            let skip = entry.start_in_blamed_file - line_number;
+            line_number = entry.range_in_blamed_file().end;
+            let entry_lines = lines
+                .by_ref()
+                .skip(skip as usize)
+                .take(entry.len.get() as usize)
+                .collect();
+            (entry, entry_lines)
+        })
+    }
+}
+
+/// Additional information about the performed operations.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Statistics {
+    /// The amount of commits it traversed until the blame was complete.
+    pub commits_traversed: usize,
+    /// The amount of trees that were decoded to find the entry of the file to blame.
+    pub trees_decoded: usize,
+    /// The amount of tree-diffs to see if the file was renamed.
+    pub trees_diffed: usize,
+    /// The amount of blobs there were compared to each other to learn what changed between commits.
+    pub blobs_diffed: usize,
+}
+
+/// Describes a hunk of lines in the blamed file which was introduced by `commit_id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameEntry {
+    /// The 0-based index of the first line of this hunk in the blamed file.
+    pub start_in_blamed_file: u32,
+    /// The 0-based index of the first line of this hunk in the file as seen in `commit_id`.
+    pub start_in_source_file: u32,
+    /// The amount of lines in this hunk.
+    pub len: NonZeroU32,
+    /// The commit that introduced the lines of this hunk.
+    pub commit_id: ObjectId,
+    /// The path of the file in `commit_id`, if it differs from the path of the blamed file due to a rename.
+    pub source_file_name: Option<BString>,
+}
+
+impl BlameEntry {
+    /// Return the range of lines this entry covers in the blamed file.
+    pub fn range_in_blamed_file(&self) -> Range<u32> {
+        self.start_in_blamed_file..self.start_in_blamed_file + self.len.get()
+    }
+
+    /// Return the range of lines this entry covers in the file as seen in [`commit_id`](Self::commit_id).
+    pub fn range_in_source_file(&self) -> Range<u32> {
+        self.start_in_source_file..self.start_in_source_file + self.len.get()
+    }
+}
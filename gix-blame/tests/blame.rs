@@ -0,0 +1,289 @@
+use std::{collections::HashMap, path::Path};
+
+use gix_blame::{BlameEntry, Options, Outcome};
+use gix_hash::ObjectId;
+
+pub use gix_testtools::Result;
+
+/// A line as seen in the blamed file, with the commit it was attributed to, its 1-based line number in that commit
+/// and in the blamed file, and the name of the file in that commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlamedLine {
+    commit_id: ObjectId,
+    source_line: u32,
+    blamed_line: u32,
+    source_file_name: String,
+}
+
+/// Parse the output of `git blame --porcelain`.
+fn baseline(root: &Path, name: &str) -> crate::Result<Vec<BlamedLine>> {
+    let porcelain = std::fs::read_to_string(root.join(format!("{name}.baseline")))?;
+    let mut filename_by_commit = HashMap::<ObjectId, String>::new();
+    let mut out = Vec::<BlamedLine>::new();
+    for line in porcelain.lines() {
+        if let Some(filename) = line.strip_prefix("filename ") {
+            let last = out.last_mut().expect("filename follows header");
+            last.source_file_name = filename.into();
+            filename_by_commit.insert(last.commit_id, filename.into());
+            continue;
+        }
+        let mut tokens = line.split(' ');
+        let Some(Ok(commit_id)) = tokens.next().map(|hex| ObjectId::from_hex(hex.as_bytes())) else {
+            continue;
+        };
+        let (Some(source_line), Some(blamed_line)) = (tokens.next(), tokens.next()) else {
+            continue;
+        };
+        out.push(BlamedLine {
+            commit_id,
+            source_line: source_line.parse()?,
+            blamed_line: blamed_line.parse()?,
+            source_file_name: filename_by_commit.get(&commit_id).cloned().unwrap_or_default(),
+        });
+    }
+    Ok(out)
+}
+
+type OnEntry<'a> = &'a mut dyn FnMut(&BlameEntry) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+fn blame(file_path: &str, options: Options) -> crate::Result<(Outcome, std::path::PathBuf)> {
+    blame_with(file_path, options, None)
+}
+
+/// Blame `file_path` with [`gix_blame::file_incremental()`] if `on_entry` is set, or with [`gix_blame::file()`] otherwise.
+fn blame_with(
+    file_path: &str,
+    options: Options,
+    on_entry: Option<OnEntry<'_>>,
+) -> crate::Result<(Outcome, std::path::PathBuf)> {
+    let root = gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let tip = ObjectId::from_hex(
+        std::fs::read_to_string(root.join(".git/refs/tags/tip"))?
+            .trim()
+            .as_bytes(),
+    )?;
+    let traverse = gix_traverse::commit::Simple::new(Some(tip), &odb)
+        .sorting(gix_traverse::commit::simple::Sorting::ByCommitTimeNewestFirst)?;
+
+    let attributes = gix_worktree::Stack::new(
+        &root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let mut resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            Default::default(),
+            gix_filter::Pipeline::default(),
+            Vec::new(),
+            Default::default(),
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        attributes,
+    );
+    let outcome = match on_entry {
+        Some(on_entry) => {
+            gix_blame::file_incremental(&odb, traverse, &mut resource_cache, file_path.into(), options, on_entry)?
+        }
+        None => gix_blame::file(&odb, traverse, &mut resource_cache, file_path.into(), options)?,
+    };
+    Ok((outcome, root))
+}
+
+fn lines(outcome: &Outcome, file_path: &str) -> Vec<BlamedLine> {
+    outcome
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .range_in_blamed_file()
+                .zip(entry.range_in_source_file())
+                .map(|(blamed_line, source_line)| BlamedLine {
+                    commit_id: entry.commit_id,
+                    source_line: source_line + 1,
+                    blamed_line: blamed_line + 1,
+                    source_file_name: entry
+                        .source_file_name
+                        .as_ref()
+                        .map_or_else(|| file_path.to_owned(), ToString::to_string),
+                })
+        })
+        .collect()
+}
+
+fn assert_matches_baseline(name: &str, file_path: &str, options: Options) -> crate::Result<Outcome> {
+    let (outcome, root) = blame(file_path, options)?;
+    pretty_assertions::assert_eq!(lines(&outcome, file_path), baseline(&root, name)?, "{name}");
+    Ok(outcome)
+}
+
+fn with_renames() -> Options {
+    Options {
+        rewrites: Some(Default::default()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn simple() -> crate::Result {
+    let outcome = assert_matches_baseline("simple", "simple.txt", Default::default())?;
+    assert_eq!(
+        outcome.entries.len(),
+        6,
+        "adjacent lines of the same commit are combined"
+    );
+    assert_eq!(
+        outcome
+            .entries_with_lines()
+            .map(|(_, lines)| lines.len())
+            .sum::<usize>(),
+        7,
+        "each line is associated with exactly one entry"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge() -> crate::Result {
+    assert_matches_baseline("merge", "merge.txt", Default::default())?;
+    Ok(())
+}
+
+#[test]
+fn rename() -> crate::Result {
+    assert_matches_baseline("rename", "after-rename.txt", with_renames())?;
+
+    let (outcome, _) = blame("after-rename.txt", Default::default())?;
+    assert_eq!(
+        outcome.entries.len(),
+        1,
+        "without rename tracking, the commit that renamed the file is blamed for everything"
+    );
+    Ok(())
+}
+
+#[test]
+fn whitespace() -> crate::Result {
+    assert_matches_baseline("whitespace", "whitespace.txt", Default::default())?;
+    assert_matches_baseline(
+        "whitespace-ignored",
+        "whitespace.txt",
+        Options {
+            ignore_whitespace: true,
+            ..Default::default()
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn ignored_rev() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?;
+    let reformat = ObjectId::from_hex(
+        std::fs::read_to_string(root.join(".git/refs/tags/reformat"))?
+            .trim()
+            .as_bytes(),
+    )?;
+    assert_matches_baseline(
+        "ignored-rev",
+        "ignored.txt",
+        Options {
+            ignore_revs: Some(reformat).into_iter().collect(),
+            ..Default::default()
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+#[allow(clippy::single_range_in_vec_init)]
+fn ranges() -> crate::Result {
+    assert_matches_baseline(
+        "range",
+        "simple.txt",
+        Options {
+            ranges: vec![1..4],
+            ..Default::default()
+        },
+    )?;
+    assert_matches_baseline(
+        "range-multiple",
+        "simple.txt",
+        Options {
+            ranges: vec![4..6, 0..2],
+            ..Default::default()
+        },
+    )?;
+
+    let err = blame(
+        "simple.txt",
+        Options {
+            ranges: vec![7..8],
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The line range 7..8 is invalid for a file with 7 lines"
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental_entries_are_passed_as_they_are_found() -> crate::Result {
+    let mut entries = Vec::new();
+    let (outcome, root) = blame_with(
+        "simple.txt",
+        Default::default(),
+        Some(&mut |entry| {
+            entries.push(entry.clone());
+            Ok(())
+        }),
+    )?;
+    let tip = ObjectId::from_hex(
+        std::fs::read_to_string(root.join(".git/refs/tags/tip"))?
+            .trim()
+            .as_bytes(),
+    )?;
+    assert_eq!(
+        entries.first().map(|e| e.commit_id),
+        Some(tip),
+        "the lines of the most recent commit are found first"
+    );
+
+    entries.sort_by_key(|e| e.start_in_blamed_file);
+    let incremental = Outcome {
+        entries,
+        ..outcome.clone()
+    };
+    assert_eq!(
+        lines(&incremental, "simple.txt"),
+        lines(&outcome, "simple.txt"),
+        "all lines are attributed just like in the final outcome"
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental_errors_abort_the_blame() {
+    let err = blame_with("simple.txt", Default::default(), Some(&mut |_| Err("stop".into()))).unwrap_err();
+    let err = err.downcast::<gix_blame::Error>().expect("blame error");
+    assert!(matches!(*err, gix_blame::Error::OnEntry(_)));
+}
+
+#[test]
+fn missing_file() {
+    let err = blame("does-not-exist", Default::default()).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("The file to blame at 'does-not-exist' wasn't found"));
+}
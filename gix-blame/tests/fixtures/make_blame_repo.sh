@@ -0,0 +1,67 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Commits get increasing dates so that a traversal sorted by commit time sees children before their parents.
+time=946684800
+function commit() {
+  time=$((time + 60))
+  git add -A
+  GIT_AUTHOR_DATE="$time +0000" GIT_COMMITTER_DATE="$time +0000" git commit -q -m "$1"
+  git tag "$1"
+}
+
+# Write the `git blame --porcelain` output for the given arguments into a file named `$1.baseline`.
+function baseline() {
+  local name=${1:?first argument is the name of the baseline}
+  shift
+  git blame --porcelain "$@" > "$name.baseline"
+}
+
+git init -q
+git checkout -q -b main
+
+printf '1\n2\n3\n4\n5\n' > simple.txt
+printf 'a\nb\nc\n' > merge.txt
+seq 1 10 > before-rename.txt
+printf 'if x {\n    y();\n}\n' > whitespace.txt
+printf 'first\nsecond\nthird\n' > ignored.txt
+commit c1
+
+printf '1\n2-changed\n3\n4\n5\n6\n' > simple.txt
+commit c2
+
+printf '0\n1\n2-changed\n4\n5\n6\n' > simple.txt
+git mv before-rename.txt after-rename.txt
+sed -i 's/^5$/5-changed/' after-rename.txt
+commit c3
+
+printf 'if x {\n  y();\n}\nz();\n' > whitespace.txt
+commit c4
+
+printf 'FIRST\nsecond\nTHIRD\n' > ignored.txt
+commit reformat
+
+git checkout -q -b feature c2
+printf 'a\nb-side\nc\n' > merge.txt
+commit side
+
+git checkout -q main
+printf 'a\nb\nc\nd-main\n' > merge.txt
+commit c5
+
+GIT_AUTHOR_DATE="$((time + 60)) +0000" GIT_COMMITTER_DATE="$((time + 60)) +0000" git merge -q --no-edit feature
+git tag merge
+
+printf '1\n2-changed\n3\n4\n5\n6\n7\n' > simple.txt
+time=$((time + 120))
+GIT_AUTHOR_DATE="$time +0000" GIT_COMMITTER_DATE="$time +0000" git commit -q -am c6
+git tag tip
+
+baseline simple simple.txt
+baseline merge merge.txt
+baseline rename after-rename.txt
+baseline whitespace whitespace.txt
+baseline whitespace-ignored -w whitespace.txt
+baseline ignored-rev --ignore-rev reformat ignored.txt
+baseline range -L 2,4 simple.txt
+baseline range-multiple -L 1,2 -L 5,6 simple.txt
@@ -0,0 +1,134 @@
+use std::io::BufRead;
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Header, Prerequisite, Ref, Version};
+
+/// The error returned by [`Header::from_read()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the bundle header")]
+    Io(#[from] std::io::Error),
+    #[error("'{line}' isn't the signature of a supported bundle version")]
+    UnknownSignature { line: BString },
+    #[error("The capability '{name}' isn't supported")]
+    UnknownCapability { name: BString },
+    #[error("The object format '{name}' isn't supported")]
+    UnknownObjectFormat { name: BString },
+    #[error("Capabilities must come before prerequisites and references, but got '{line}'")]
+    MisplacedCapability { line: BString },
+    #[error("The prerequisite line '{line}' is malformed")]
+    InvalidPrerequisite { line: BString },
+    #[error("The reference line '{line}' is malformed")]
+    InvalidRef { line: BString },
+    #[error("The header ended without the empty line that separates it from the pack")]
+    Truncated,
+}
+
+impl Header {
+    /// Read the header of a bundle from `read`, consuming everything up to and including the empty line
+    /// that separates it from the pack, which `read` is positioned at afterwards.
+    pub fn from_read(read: &mut dyn BufRead) -> Result<Self, Error> {
+        Ok(Self::from_read_with_len(read)?.0)
+    }
+
+    /// Like [`from_read()`](Self::from_read()), but also return the amount of bytes that were consumed.
+    pub fn from_read_with_len(read: &mut dyn BufRead) -> Result<(Self, u64), Error> {
+        let mut consumed = 0;
+        let mut line = Vec::new();
+        let mut next_line = |line: &mut Vec<u8>| -> Result<bool, Error> {
+            line.clear();
+            let len = read.read_until(b'\n', line)?;
+            consumed += len as u64;
+            Ok(len != 0 && line.pop() == Some(b'\n'))
+        };
+
+        if !next_line(&mut line)? {
+            return Err(Error::Truncated);
+        }
+        line.push(b'\n');
+        let version = [Version::V2, Version::V3]
+            .into_iter()
+            .find(|version| version.signature() == line.as_slice())
+            .ok_or_else(|| Error::UnknownSignature {
+                line: line[..line.len() - 1].as_bstr().into(),
+            })?;
+
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        let mut may_have_capabilities = version == Version::V3;
+        loop {
+            if !next_line(&mut line)? {
+                return Err(Error::Truncated);
+            }
+            if line.is_empty() {
+                break;
+            }
+            if let Some(capability) = line.strip_prefix(b"@") {
+                if !may_have_capabilities {
+                    return Err(Error::MisplacedCapability {
+                        line: line.as_bstr().into(),
+                    });
+                }
+                let (name, value) = match capability.find_byte(b'=') {
+                    Some(pos) => (&capability[..pos], Some(&capability[pos + 1..])),
+                    None => (capability, None),
+                };
+                match (name, value) {
+                    (b"object-format", Some(value)) => {
+                        header.object_hash = value
+                            .to_str()
+                            .ok()
+                            .and_then(|value| value.parse::<gix_hash::Kind>().ok())
+                            .ok_or_else(|| Error::UnknownObjectFormat { name: value.into() })?;
+                    }
+                    (b"filter", Some(value)) => header.filter = Some(value.into()),
+                    _ => return Err(Error::UnknownCapability { name: name.into() }),
+                }
+                continue;
+            }
+            may_have_capabilities = false;
+
+            let hex_len = header.object_hash.len_in_hex();
+            if let Some(prerequisite) = line.strip_prefix(b"-") {
+                let invalid = || Error::InvalidPrerequisite {
+                    line: line.as_bstr().into(),
+                };
+                let id = prerequisite
+                    .get(..hex_len)
+                    .and_then(|hex| ObjectId::from_hex(hex).ok())
+                    .ok_or_else(invalid)?;
+                let comment = match &prerequisite[hex_len..] {
+                    [] => &[][..],
+                    [b' ', comment @ ..] => comment,
+                    _ => return Err(invalid()),
+                };
+                header.prerequisites.push(Prerequisite {
+                    id,
+                    comment: comment.into(),
+                });
+            } else {
+                let invalid = || Error::InvalidRef {
+                    line: line.as_bstr().into(),
+                };
+                let id = line
+                    .get(..hex_len)
+                    .and_then(|hex| ObjectId::from_hex(hex).ok())
+                    .ok_or_else(invalid)?;
+                let name = match &line[hex_len..] {
+                    [b' ', name @ ..] if !name.is_empty() => name,
+                    _ => return Err(invalid()),
+                };
+                header.refs.push(Ref { id, name: name.into() });
+            }
+        }
+        Ok((header, consumed))
+    }
+}
@@ -0,0 +1,44 @@
+use std::io;
+
+use crate::{Header, Version};
+
+impl Header {
+    /// Write this header to `out`, followed by the empty line after which the pack is expected.
+    ///
+    /// Note that [`Version::V2`] only supports SHA-1 and no filter, see [`Version::minimal()`].
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        if self.version == Version::V2 && (self.object_hash != gix_hash::Kind::Sha1 || self.filter.is_some()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Version 2 bundles can only use SHA-1 and can't be filtered",
+            ));
+        }
+        out.write_all(self.version.signature())?;
+        if self.version == Version::V3 {
+            writeln!(
+                out,
+                "@object-format={}",
+                self.object_hash.to_string().to_ascii_lowercase()
+            )?;
+            if let Some(filter) = &self.filter {
+                out.write_all(b"@filter=")?;
+                out.write_all(filter)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        for prerequisite in &self.prerequisites {
+            write!(out, "-{}", prerequisite.id)?;
+            if !prerequisite.comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(&prerequisite.comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for reference in &self.refs {
+            write!(out, "{} ", reference.id)?;
+            out.write_all(&reference.name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    io::{BufReader, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use crate::{File, Header};
+
+/// The error returned by [`File::at()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open the bundle at '{}'", path.display())]
+    Open { path: PathBuf, source: std::io::Error },
+    #[error("Could not read the header of the bundle at '{}'", path.display())]
+    Decode {
+        path: PathBuf,
+        source: crate::decode::Error,
+    },
+}
+
+impl File {
+    /// Open the bundle at `path` and read its header.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let file = std::fs::File::open(&path).map_err(|source| Error::Open {
+            path: path.clone(),
+            source,
+        })?;
+        let (header, pack_offset) =
+            Header::from_read_with_len(&mut BufReader::new(file)).map_err(|source| Error::Decode {
+                path: path.clone(),
+                source,
+            })?;
+        Ok(File {
+            path,
+            header,
+            pack_offset,
+        })
+    }
+
+    /// Open the bundle file again and return a reader positioned at the start of its pack.
+    pub fn pack(&self) -> std::io::Result<BufReader<std::fs::File>> {
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.pack_offset))?;
+        Ok(BufReader::new(file))
+    }
+}
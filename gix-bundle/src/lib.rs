@@ -0,0 +1,114 @@
+//! Read and write git bundles, files with a pack along with the references it provides and the prerequisite objects
+//! a repository must have to make use of it, as created by `git bundle create`.
+//!
+//! A bundle starts with a [`Header`] that lists the references and prerequisites, which is followed by the pack.
+//! Use [`File::at()`] to read the header of a bundle on disk and learn where its pack starts.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use std::path::{Path, PathBuf};
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+mod encode;
+///
+#[allow(clippy::empty_docs)]
+pub mod file;
+
+/// The version of the bundle format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Version {
+    /// The original format, which only supports SHA-1 and no capabilities.
+    V2,
+    /// The format that supports capabilities like `object-format` and `filter`.
+    V3,
+}
+
+impl Version {
+    /// Return the first line of bundles of this version, including the trailing newline.
+    pub fn signature(&self) -> &'static [u8] {
+        match self {
+            Version::V2 => b"# v2 git bundle\n",
+            Version::V3 => b"# v3 git bundle\n",
+        }
+    }
+
+    /// Return the oldest version that can represent bundles using `object_hash`, and a filter if `has_filter` is `true`.
+    pub fn minimal(object_hash: gix_hash::Kind, has_filter: bool) -> Self {
+        if object_hash == gix_hash::Kind::Sha1 && !has_filter {
+            Version::V2
+        } else {
+            Version::V3
+        }
+    }
+}
+
+/// An object a repository must contain to be able to use the pack in a bundle, as its objects refer to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Prerequisite {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// A comment for human consumption, typically the subject of the commit, which may be empty.
+    pub comment: BString,
+}
+
+/// A reference provided by a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ref {
+    /// The object the reference points to.
+    pub id: ObjectId,
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+}
+
+/// The header of a bundle, which is followed by the pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The version of the bundle format.
+    pub version: Version,
+    /// The kind of hash used by all objects in the bundle.
+    pub object_hash: gix_hash::Kind,
+    /// The filter, like `blob:none`, used to omit objects from the pack, making it usable only in partial clones.
+    pub filter: Option<BString>,
+    /// The objects that a repository must have to use the pack.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references the bundle provides, in order of appearance.
+    pub refs: Vec<Ref>,
+}
+
+impl Header {
+    /// Return all prerequisites for which `contains(id)` returns `false`, i.e. those a repository is missing.
+    pub fn missing_prerequisites(&self, mut contains: impl FnMut(&gix_hash::oid) -> bool) -> Vec<&Prerequisite> {
+        self.prerequisites
+            .iter()
+            .filter(|prerequisite| !contains(&prerequisite.id))
+            .collect()
+    }
+}
+
+/// A bundle on disk, with its parsed header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The path to the bundle file.
+    pub path: PathBuf,
+    /// The header of the bundle.
+    pub header: Header,
+    /// The offset in bytes at which the pack starts.
+    pub pack_offset: u64,
+}
+
+/// Return `true` if the file at `path` starts with the signature of a bundle of any supported version.
+pub fn is_bundle(path: &Path) -> bool {
+    use std::io::Read;
+    let mut signature = [0; 16];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut signature))
+        .is_ok()
+        && [Version::V2, Version::V3]
+            .iter()
+            .any(|version| version.signature() == signature)
+}
@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use gix_bundle::{File, Header, Version};
+use gix_hash::ObjectId;
+use gix_testtools::Result;
+
+fn fixture() -> Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_bundles.sh")
+}
+
+fn ids() -> Result<(ObjectId, ObjectId, ObjectId, String)> {
+    let ids = std::fs::read_to_string(fixture()?.join("ids"))?;
+    let mut lines = ids.lines();
+    let mut next_id = || ObjectId::from_hex(lines.next().expect("present").as_bytes());
+    Ok((
+        next_id()?,
+        next_id()?,
+        next_id()?,
+        lines.next().expect("subject").into(),
+    ))
+}
+
+fn ref_names(header: &Header) -> Vec<String> {
+    header.refs.iter().map(|r| r.name.to_string()).collect()
+}
+
+#[test]
+fn full_bundle_lists_all_refs_without_prerequisites() -> Result {
+    let (main, _parent, tag, _subject) = ids()?;
+    let bundle = File::at(fixture()?.join("full.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V2);
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+    assert!(bundle.header.prerequisites.is_empty());
+    assert_eq!(ref_names(&bundle.header), ["refs/heads/main", "refs/tags/v1", "HEAD"]);
+    assert_eq!(bundle.header.refs[0].id, main);
+    assert_eq!(bundle.header.refs[1].id, tag, "tags aren't peeled");
+
+    let mut pack = bundle.pack()?;
+    let mut signature = [0; 4];
+    std::io::Read::read_exact(&mut pack, &mut signature)?;
+    assert_eq!(&signature, b"PACK", "the pack directly follows the header");
+    Ok(())
+}
+
+#[test]
+fn incremental_bundle_has_prerequisites() -> Result {
+    let (main, parent, _tag, subject) = ids()?;
+    let bundle = File::at(fixture()?.join("incremental.bundle"))?;
+    assert_eq!(bundle.header.prerequisites.len(), 1);
+    assert_eq!(bundle.header.prerequisites[0].id, parent);
+    assert_eq!(bundle.header.prerequisites[0].comment, subject);
+    assert_eq!(ref_names(&bundle.header), ["refs/heads/main"]);
+    assert_eq!(bundle.header.refs[0].id, main);
+
+    assert!(bundle.header.missing_prerequisites(|id| id == parent).is_empty());
+    assert_eq!(
+        bundle.header.missing_prerequisites(|_| false)[0].id,
+        parent,
+        "prerequisites which aren't contained are returned"
+    );
+    Ok(())
+}
+
+#[test]
+fn v3_and_sha256_bundles() -> Result {
+    let bundle = File::at(fixture()?.join("v3.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V3);
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+
+    let bundle = File::at(fixture()?.join("sha256.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V3, "SHA-256 needs V3");
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha256);
+    let id = std::fs::read_to_string(fixture()?.join("sha256-id"))?;
+    assert_eq!(bundle.header.refs[0].id, ObjectId::from_hex(id.trim().as_bytes())?);
+    Ok(())
+}
+
+#[test]
+fn write_to_round_trips() -> Result {
+    for name in ["full.bundle", "incremental.bundle", "v3.bundle", "sha256.bundle"] {
+        let path = fixture()?.join(name);
+        let bundle = File::at(&path)?;
+        let mut out = Vec::new();
+        bundle.header.write_to(&mut out)?;
+        let data = std::fs::read(&path)?;
+        assert_eq!(out, &data[..bundle.pack_offset as usize], "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn filter_capability() -> Result {
+    let header = Header::from_read(&mut &b"# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n\n"[..])?;
+    assert_eq!(header.filter.as_ref().expect("set"), "blob:none");
+    assert_eq!(Version::minimal(header.object_hash, true), Version::V3);
+    assert_eq!(Version::minimal(gix_hash::Kind::Sha1, false), Version::V2);
+
+    let mut out = Vec::new();
+    Header {
+        version: Version::V2,
+        ..header
+    }
+    .write_to(&mut out)
+    .expect_err("filters can't be written to V2 bundles");
+    Ok(())
+}
+
+#[test]
+fn invalid_headers() {
+    for (input, expected) in [
+        (&b"# v4 git bundle\n\n"[..], "isn't the signature"),
+        (b"# v3 git bundle\n@unknown\n\n", "isn't supported"),
+        (b"# v2 git bundle\n@object-format=sha1\n\n", "must come before"),
+        (b"# v2 git bundle\n-abc\n\n", "prerequisite line"),
+        (
+            b"# v2 git bundle\n0000000000000000000000000000000000000000\n\n",
+            "reference line",
+        ),
+        (b"# v2 git bundle\n", "ended without the empty line"),
+    ] {
+        let err = Header::from_read(&mut &input[..]).expect_err("invalid");
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn is_bundle() -> Result {
+    assert!(gix_bundle::is_bundle(&fixture()?.join("full.bundle")));
+    assert!(!gix_bundle::is_bundle(&fixture()?.join("ids")));
+    assert!(!gix_bundle::is_bundle(&fixture()?.join("does-not-exist")));
+    Ok(())
+}
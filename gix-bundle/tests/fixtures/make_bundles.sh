@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  for i in 1 2 3; do
+    echo $i > file
+    git add file
+    git commit -q -m "commit $i"
+  done
+  git tag -a -m "annotated" v1 HEAD~1
+  git bundle create -q ../full.bundle --all
+  git bundle create -q ../incremental.bundle main~1..main
+  git bundle create -q --version=3 ../v3.bundle main
+  git rev-parse main main~1 v1 > ../ids
+  git log -1 --format=%s main~1 >> ../ids
+)
+
+git init -q --object-format=sha256 sha256
+(cd sha256
+  echo content > file
+  git add file
+  git commit -q -m "initial"
+  git bundle create -q ../sha256.bundle main
+  git rev-parse main > ../sha256-id
+)
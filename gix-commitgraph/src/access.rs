@@ -1,4 +1,4 @@
-use crate::{file, file::Commit, File, Graph, Position};
+use crate::{bloom, file, file::Commit, File, Graph, Position};
 
 /// Access
 impl Graph {
@@ -17,6 +17,14 @@ impl Graph {
         Some(r.file.commit_at(r.file_pos))
     }
 
+    /// Returns the settings of the changed-path Bloom filters of the topmost file that has them.
+    ///
+    /// Note that filters of commits in other files may only be used if their [settings](Commit::bloom_filter_settings())
+    /// are compatible.
+    pub fn bloom_filter_settings(&self) -> Option<&bloom::Settings> {
+        self.files.iter().rev().find_map(File::bloom_filter_settings)
+    }
+
     /// Returns the `hash` at the given position `pos`.
     ///
     /// # Panics
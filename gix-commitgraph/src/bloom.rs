@@ -0,0 +1,243 @@
+//! Changed-path Bloom filters as stored in the `BIDX` and `BDAT` chunks of commit-graph files.
+//!
+//! Each commit may have a filter which records all paths that changed compared to its first parent, along with all of their
+//! leading directories. This allows to quickly rule out commits that didn't change a given path without having to diff trees.
+use std::collections::HashSet;
+
+use bstr::{BStr, ByteSlice};
+
+/// The bloom filter which is stored for commits with more changes than we are willing to record.
+///
+/// As all bits are set, it claims to contain every path.
+pub(crate) const TOO_LARGE: &[u8] = &[0xff];
+/// The bloom filter which is stored for commits without any change.
+pub(crate) const EMPTY: &[u8] = &[0x00];
+
+const SEED0: u32 = 0x293a_e76f;
+const SEED1: u32 = 0x7e64_6e2c;
+
+/// The configuration of changed-path Bloom filters, along with the limits used when computing them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Settings {
+    /// The version of the hash function, `1` for the original implementation that treats bytes as signed,
+    /// or `2` for the fixed one that treats them as unsigned.
+    pub hash_version: u32,
+    /// The amount of hashes computed for each path.
+    pub num_hashes: u32,
+    /// The amount of bits to use per changed path.
+    pub bits_per_entry: u32,
+    /// The maximum amount of changed paths a filter is computed for. Commits with more changed paths get a filter that matches
+    /// all paths.
+    ///
+    /// Note that this value isn't stored in commit-graph files and will always be the default when read from one.
+    pub max_changed_paths: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            hash_version: 1,
+            num_hashes: 7,
+            bits_per_entry: 10,
+            max_changed_paths: 512,
+        }
+    }
+}
+
+impl Settings {
+    /// Return `true` if filters created with `other` can be used interchangeably with the ones created with these settings.
+    pub fn is_compatible_with(&self, other: &Settings) -> bool {
+        self.hash_version == other.hash_version
+            && self.num_hashes == other.num_hashes
+            && self.bits_per_entry == other.bits_per_entry
+    }
+}
+
+/// The hashes of a path, suitable for querying [filters][FilterRef::contains()] created with the same [`Settings`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key for `path` using `settings`.
+    pub fn new(path: &BStr, settings: &Settings) -> Self {
+        let hash0 = murmur3(SEED0, path, settings.hash_version);
+        let hash1 = murmur3(SEED1, path, settings.hash_version);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+}
+
+/// An owned changed-path Bloom filter.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Filter {
+    data: Vec<u8>,
+}
+
+impl Filter {
+    /// Create a new filter from the `paths` of all files (i.e. non-tree entries) that were changed,
+    /// as configured by `settings`.
+    ///
+    /// All leading directories of each path are added to the filter as well.
+    pub fn from_changed_paths<'a>(paths: impl IntoIterator<Item = &'a BStr>, settings: &Settings) -> Self {
+        let mut num_changes = 0;
+        let mut all_paths = HashSet::<&BStr>::new();
+        for path in paths {
+            num_changes += 1;
+            if num_changes > settings.max_changed_paths {
+                return Filter { data: TOO_LARGE.into() };
+            }
+            let mut path = path;
+            loop {
+                all_paths.insert(path);
+                match path.rfind_byte(b'/') {
+                    Some(pos) => path = path[..pos].as_bstr(),
+                    None => break,
+                }
+            }
+        }
+
+        let len = (all_paths.len() * settings.bits_per_entry as usize + 7) / 8;
+        if len == 0 {
+            return Filter { data: EMPTY.into() };
+        }
+        let mut filter = Filter { data: vec![0; len] };
+        for path in all_paths {
+            filter.insert(&Key::new(path, settings));
+        }
+        filter
+    }
+
+    /// Create a filter from `data` as previously obtained by [`as_bytes()`][Filter::as_bytes()].
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Filter { data }
+    }
+
+    /// Return the bytes of this filter as they are stored in commit-graph files.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Return a borrowed version of this filter.
+    pub fn as_ref(&self) -> FilterRef<'_> {
+        FilterRef { data: &self.data }
+    }
+
+    fn insert(&mut self, key: &Key) {
+        let num_bits = self.data.len() as u64 * 8;
+        for hash in &key.hashes {
+            let pos = u64::from(*hash) % num_bits;
+            self.data[(pos / 8) as usize] |= 1 << (pos % 8);
+        }
+    }
+}
+
+/// A changed-path Bloom filter as stored in a commit-graph file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct FilterRef<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> FilterRef<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        FilterRef { data }
+    }
+
+    /// Return `false` if the path represented by `key` definitely wasn't changed, or `true` if it might have been changed.
+    ///
+    /// Note that `key` must have been created with the settings of the commit-graph file this filter was obtained from.
+    pub fn contains(&self, key: &Key) -> bool {
+        if self.data.is_empty() {
+            return true;
+        }
+        let num_bits = self.data.len() as u64 * 8;
+        key.hashes.iter().all(|hash| {
+            let pos = u64::from(*hash) % num_bits;
+            self.data[(pos / 8) as usize] & (1 << (pos % 8)) != 0
+        })
+    }
+
+    /// Return the bytes of this filter as they are stored in the commit-graph file.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return an owned version of this filter.
+    pub fn to_owned(&self) -> Filter {
+        Filter {
+            data: self.data.to_owned(),
+        }
+    }
+}
+
+/// The 32-bit murmur3 hash as used by `git`, where `version` 1 sign-extends bytes just like `git` did on most platforms.
+fn murmur3(mut seed: u32, data: &[u8], version: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    const R1: u32 = 15;
+    const R2: u32 = 13;
+    const M: u32 = 5;
+    const N: u32 = 0xe654_6b64;
+
+    let byte = |b: u8| -> u32 {
+        if version == 1 {
+            b as i8 as u32
+        } else {
+            u32::from(b)
+        }
+    };
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in chunks.by_ref() {
+        let mut k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        k = k.wrapping_mul(C1);
+        k = k.rotate_left(R1);
+        k = k.wrapping_mul(C2);
+
+        seed ^= k;
+        seed = seed.rotate_left(R2).wrapping_mul(M).wrapping_add(N);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (shift, b) in tail.iter().enumerate() {
+            k1 ^= byte(*b) << (shift * 8);
+        }
+        k1 = k1.wrapping_mul(C1);
+        k1 = k1.rotate_left(R1);
+        k1 = k1.wrapping_mul(C2);
+        seed ^= k1;
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::murmur3;
+
+    #[test]
+    fn murmur3_matches_git() {
+        // Values as produced by `murmur3_seeded()` in `git`, with version 1 relying on `char` being signed.
+        assert_eq!(murmur3(0, b"", 1), 0x0000_0000);
+        assert_eq!(murmur3(0, b"Hello world!", 1), 0x627b_0c2c);
+        assert_eq!(
+            murmur3(0, b"The quick brown fox jumps over the lazy dog", 1),
+            0x2e4f_f723
+        );
+        assert_eq!(murmur3(0, b"\x99\xaa\xbb\xcc\xdd\xee\xff", 1), 0xdd92_776e, "signed");
+        assert_eq!(murmur3(0, b"\x99\xaa\xbb\xcc\xdd\xee\xff", 2), 0xa183_ccfd, "unsigned");
+    }
+}
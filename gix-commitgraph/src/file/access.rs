@@ -4,7 +4,8 @@ use std::{
 };
 
 use crate::{
-    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH},
+    bloom,
+    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK},
     File,
 };
 
@@ -15,6 +16,36 @@ impl File {
         self.base_graph_count
     }
 
+    /// Returns the settings used to create the changed-path Bloom filters in this file, if there are any.
+    pub fn bloom_filter_settings(&self) -> Option<&bloom::Settings> {
+        self.bloom_filter_settings.as_ref()
+    }
+
+    /// Returns the changed-path Bloom filter for the commit at the given lexicographical position, if there is one.
+    ///
+    /// It's `None` if this file doesn't have Bloom filters, or if no filter was computed for the commit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds.
+    pub fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::FilterRef<'_>> {
+        assert!(
+            pos.0 < self.num_commits(),
+            "expected lexicographical position less than {}, got {}",
+            self.num_commits(),
+            pos.0
+        );
+        let index_offset = self.bloom_filter_index_offset?;
+        let data = &self.data[self.bloom_filter_data_range.clone()?];
+        let end_at = |pos: u32| -> usize {
+            let start = index_offset + pos as usize * 4;
+            u32::from_be_bytes(self.data[start..][..4].try_into().expect("4 bytes")) as usize
+        };
+        let start = if pos.0 == 0 { 0 } else { end_at(pos.0 - 1) };
+        let end = end_at(pos.0);
+        (start < end).then(|| data.get(start..end).map(bloom::FilterRef::new))?
+    }
+
     /// Returns the commit data for the commit located at the given lexicographical position.
     ///
     /// `pos` must range from 0 to `self.num_commits()`.
@@ -26,6 +57,11 @@ impl File {
         Commit::new(self, pos)
     }
 
+    /// Returns `true` if this file stores corrected commit dates, i.e. generation numbers v2.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// The kind of hash used in this File.
     ///
     /// Note that it is always conforming to the hash used in the owning repository.
@@ -127,6 +163,21 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the corrected commit date offset of the commit at `pos` from the Generation Data (GDA2) chunk, looking up
+    /// overflowing values in the Generation Data Overflow (GDO2) chunk.
+    pub(crate) fn corrected_commit_date_offset(&self, pos: file::Position) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().expect("4 bytes"));
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(u64::from(offset));
+        }
+        let overflow = &self.data[self.generation_data_overflow_range.clone()?];
+        let index = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize;
+        Some(u64::from_be_bytes(
+            overflow.get(index * 8..)?.get(..8)?.try_into().expect("8 bytes"),
+        ))
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -5,6 +5,7 @@ use std::{
 };
 
 use crate::{
+    bloom,
     file::{self, EXTENDED_EDGES_MASK, LAST_EXTENDED_EDGE_MASK, NO_PARENT},
     File, Position,
 };
@@ -50,9 +51,6 @@ impl<'a> Commit<'a> {
             root_tree_id: gix_hash::oid::from_bytes_unchecked(&bytes[..file.hash_len]),
             parent1: ParentEdge::from_raw(read_u32(&bytes[file.hash_len..][..4])),
             parent2: ParentEdge::from_raw(read_u32(&bytes[file.hash_len + 4..][..4])),
-            // TODO: Add support for corrected commit date offset overflow.
-            //      See https://github.com/git/git/commit/e8b63005c48696a26f976f5f9b0ccaf1983e439d and
-            //          https://github.com/git/git/commit/f90fca638e99a031dce8e3aca72427b2f9b4bb38 for more details and hints at a test.
             generation: read_u32(&bytes[file.hash_len + 8..][..4]) >> 2,
             commit_timestamp: u64::from_be_bytes(bytes[file.hash_len + 8..][..8].try_into().unwrap())
                 & 0x0003_ffff_ffff,
@@ -74,6 +72,28 @@ impl<'a> Commit<'a> {
         self.generation
     }
 
+    /// Returns the corrected commit date of this commit, also known as generation number v2, if the owning file contains it.
+    ///
+    /// It is the largest of the [committer timestamp][Self::committer_timestamp()] and the corrected commit date of each parent plus one,
+    /// which makes it a generation number that is closer to the actual commit time.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file
+            .corrected_commit_date_offset(self.pos)
+            .map(|offset| self.commit_timestamp + offset)
+    }
+
+    /// Returns the changed-path Bloom filter of this commit, if the owning file contains one for it.
+    ///
+    /// Use it with keys created from the [settings](File::bloom_filter_settings()) of the file owning this commit.
+    pub fn bloom_filter(&self) -> Option<bloom::FilterRef<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
+
+    /// Returns the settings of the changed-path Bloom filters of the file owning this commit, if it has any.
+    pub fn bloom_filter_settings(&self) -> Option<&'a bloom::Settings> {
+        self.file.bloom_filter_settings()
+    }
+
     /// Returns an iterator over the parent positions for lookup in the owning [Graph][crate::Graph].
     pub fn iter_parents(self) -> Parents<'a> {
         // I didn't find a combinator approach that a) was as strict as ParentIterator, b) supported
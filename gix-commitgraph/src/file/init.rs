@@ -2,9 +2,12 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use crate::{
+    bloom,
     file::{
-        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-        EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_DATA_HEADER_LEN,
+        BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
+        EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN,
+        OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
     },
     File,
 };
@@ -184,6 +187,89 @@ impl File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let num_commits_from_chunk = |id: ChunkId, chunk_range: std::ops::Range<usize>, entry_size: usize| {
+            let chunk_size = chunk_range.len();
+            if chunk_size % entry_size != 0 {
+                return Err(Error::InvalidChunkSize {
+                    id,
+                    msg: format!("chunk size {chunk_size} is not a multiple of {entry_size}"),
+                });
+            }
+            let num_commits: u32 = (chunk_size / entry_size)
+                .try_into()
+                .expect("number of commits in chunk to fit in 32 bits");
+            if num_commits != commit_data_count {
+                return Err(Error::CommitCountMismatch {
+                    chunk1_id: COMMIT_DATA_CHUNK_ID,
+                    chunk1_commits: commit_data_count,
+                    chunk2_id: id,
+                    chunk2_commits: num_commits,
+                });
+            }
+            Ok(chunk_range.start)
+        };
+
+        let generation_data_offset = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_CHUNK_ID, |chunk_range| {
+                num_commits_from_chunk(GENERATION_DATA_CHUNK_ID, chunk_range, 4)
+            })
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() % 8 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                        msg: format!("chunk size {} is not a multiple of 8", chunk_range.len()),
+                    });
+                }
+                Ok(chunk_range)
+            })
+            .ok()
+            .transpose()?;
+
+        let bloom_filter_index_offset = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID, |chunk_range| {
+                num_commits_from_chunk(BLOOM_FILTER_INDEX_CHUNK_ID, chunk_range, 4)
+            })
+            .ok()
+            .transpose()?;
+        let bloom_filter_data = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() < BLOOM_FILTER_DATA_HEADER_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!(
+                            "chunk size {} is too small to hold the {BLOOM_FILTER_DATA_HEADER_LEN} bytes header",
+                            chunk_range.len()
+                        ),
+                    });
+                }
+                let header = &data[chunk_range.start..][..BLOOM_FILTER_DATA_HEADER_LEN];
+                let settings = bloom::Settings {
+                    hash_version: read_u32(&header[..4]),
+                    num_hashes: read_u32(&header[4..8]),
+                    bits_per_entry: read_u32(&header[8..]),
+                    ..Default::default()
+                };
+                Ok((
+                    chunk_range.start + BLOOM_FILTER_DATA_HEADER_LEN..chunk_range.end,
+                    settings,
+                ))
+            })
+            .ok()
+            .transpose()?;
+        // Filters we can't read are ignored, just like `git` does.
+        let (bloom_filter_index_offset, bloom_filter_data_range, bloom_filter_settings) =
+            match (bloom_filter_index_offset, bloom_filter_data) {
+                (Some(index_offset), Some((data_range, settings)))
+                    if matches!(settings.hash_version, 1 | 2) && settings.num_hashes > 0 =>
+                {
+                    (Some(index_offset), Some(data_range), Some(settings))
+                }
+                _ => (None, None, None),
+            };
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -221,9 +307,14 @@ impl File {
             base_graph_count,
             base_graphs_list_offset,
             commit_data_offset,
+            bloom_filter_index_offset,
+            bloom_filter_data_range,
+            bloom_filter_settings,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path,
             hash_len: object_hash.len_in_bytes(),
@@ -252,6 +343,10 @@ impl TryFrom<&Path> for File {
     }
 }
 
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes(b.try_into().expect("4 bytes"))
+}
+
 // Copied from gix-odb/pack/index/init.rs
 fn read_fan(d: &[u8]) -> ([u32; FAN_LEN], usize) {
     assert!(d.len() >= FAN_LEN * 4);
@@ -11,22 +11,33 @@ pub mod verify;
 
 const COMMIT_DATA_ENTRY_SIZE_SANS_HASH: usize = 16;
 pub(crate) const FAN_LEN: usize = 256;
-const HEADER_LEN: usize = 8;
+pub(crate) const HEADER_LEN: usize = 8;
 
-const SIGNATURE: &[u8] = b"CGPH";
+pub(crate) const SIGNATURE: &[u8] = b"CGPH";
 
-type ChunkId = gix_chunk::Id;
-const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
-const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
-const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
-const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
-const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
+pub(crate) type ChunkId = gix_chunk::Id;
+pub(crate) const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
+pub(crate) const BLOOM_FILTER_DATA_CHUNK_ID: ChunkId = *b"BDAT";
+pub(crate) const BLOOM_FILTER_INDEX_CHUNK_ID: ChunkId = *b"BIDX";
+pub(crate) const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
+pub(crate) const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
+pub(crate) const GENERATION_DATA_CHUNK_ID: ChunkId = *b"GDA2";
+pub(crate) const GENERATION_DATA_OVERFLOW_CHUNK_ID: ChunkId = *b"GDO2";
+pub(crate) const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
+pub(crate) const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
+
+/// The size of the header of the `BDAT` chunk, consisting of the hash version, the number of hashes and the bits per entry.
+pub(crate) const BLOOM_FILTER_DATA_HEADER_LEN: usize = 3 * 4;
 
 // Note that git's commit-graph-format.txt as of v2.28.0 gives an incorrect value 0x0700_0000 for
 // NO_PARENT. Fixed in https://github.com/git/git/commit/4d515253afcef985e94400adbfed7044959f9121 .
-const NO_PARENT: u32 = 0x7000_0000;
-const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
-const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+pub(crate) const NO_PARENT: u32 = 0x7000_0000;
+pub(crate) const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
+pub(crate) const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+/// If set in a `GDA2` entry, the remaining bits are an index into the `GDO2` chunk.
+pub(crate) const GENERATION_DATA_OVERFLOW_MASK: u32 = 0x8000_0000;
+/// The largest corrected commit date offset that can be stored directly in the `GDA2` chunk.
+pub(crate) const GENERATION_DATA_OFFSET_MAX: u64 = (1 << 31) - 1;
 
 /// The position of a given commit within a graph file, starting at 0.
 ///
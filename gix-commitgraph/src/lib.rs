@@ -1,4 +1,4 @@
-//! Read, verify, and traverse git commit graphs.
+//! Read, write, verify, and traverse git commit graphs.
 //!
 //! A [commit graph][Graph] is an index of commits in the git commit history.
 //! The [Graph] stores commit data in a way that accelerates lookups considerably compared to
@@ -7,6 +7,8 @@
 //! As generating the full commit graph from scratch can take some time, git may write new commits
 //! to separate [files][File] instead of overwriting the original file.
 //! Eventually, git will merge these files together as the number of files grows.
+//!
+//! New graphs, or new layers of existing ones, can be created with [`write()`].
 //! ## Feature Flags
 #![cfg_attr(
     all(doc, feature = "document-features"),
@@ -24,10 +26,15 @@ use std::path::Path;
 pub struct File {
     base_graph_count: u8,
     base_graphs_list_offset: Option<usize>,
+    bloom_filter_index_offset: Option<usize>,
+    bloom_filter_data_range: Option<std::ops::Range<usize>>,
+    bloom_filter_settings: Option<bloom::Settings>,
     commit_data_offset: usize,
     data: memmap2::Mmap,
     extra_edges_list_range: Option<std::ops::Range<usize>>,
     fan: [u32; file::FAN_LEN],
+    generation_data_offset: Option<usize>,
+    generation_data_overflow_range: Option<std::ops::Range<usize>>,
     oid_lookup_offset: usize,
     path: std::path::PathBuf,
     hash_len: usize,
@@ -49,11 +56,14 @@ pub fn at(path: impl AsRef<Path>) -> Result<Graph, init::Error> {
 }
 
 mod access;
+pub mod bloom;
 pub mod file;
 ///
 #[allow(clippy::empty_docs)]
 pub mod init;
 pub mod verify;
+pub mod write;
+pub use write::function::write;
 
 /// The number of generations that are considered 'infinite' commit history.
 pub const GENERATION_NUMBER_INFINITY: u32 = 0xffff_ffff;
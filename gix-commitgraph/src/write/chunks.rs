@@ -0,0 +1,189 @@
+use std::io::Write;
+
+use crate::{
+    bloom,
+    file::{
+        BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_DATA_HEADER_LEN,
+        BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID, EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK, FAN_LEN,
+        GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OFFSET_MAX, GENERATION_DATA_OVERFLOW_CHUNK_ID,
+        GENERATION_DATA_OVERFLOW_MASK, HEADER_LEN, LAST_EXTENDED_EDGE_MASK, NO_PARENT, OID_FAN_CHUNK_ID,
+        OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    write::Commit,
+};
+
+/// Everything needed to write a single commit-graph file, with all slices having one entry per commit.
+pub(crate) struct Layer<'a> {
+    /// The commits to write, sorted by id.
+    pub commits: &'a [Commit],
+    /// The graph positions of the parents of each commit.
+    pub parents: &'a [Vec<u32>],
+    /// The topological level of each commit, i.e. generation number v1.
+    pub topo_levels: &'a [u32],
+    /// The corrected commit date of each commit, if generation data should be written.
+    pub corrected_dates: Option<&'a [u64]>,
+    /// The changed-path Bloom filter of each commit, if these should be written.
+    pub bloom_filters: Option<(&'a bloom::Settings, &'a [bloom::Filter])>,
+    /// The checksums of all files this one builds upon.
+    pub base_graphs: &'a [gix_hash::ObjectId],
+}
+
+impl Layer<'_> {
+    /// Write this layer to `out` and return the checksum of the written file.
+    pub(crate) fn write_to(&self, out: impl Write, object_hash: gix_hash::Kind) -> std::io::Result<gix_hash::ObjectId> {
+        let hash_len = object_hash.len_in_bytes();
+        let num_commits = self.commits.len() as u64;
+        let num_extra_edges: usize = self
+            .parents
+            .iter()
+            .filter(|parents| parents.len() > 2)
+            .map(|parents| parents.len() - 1)
+            .sum();
+        let num_overflows = self.corrected_dates.map_or(0, |dates| {
+            self.commits
+                .iter()
+                .zip(dates)
+                .filter(|(commit, date)| **date - commit.committer_time > GENERATION_DATA_OFFSET_MAX)
+                .count()
+        });
+
+        let mut cf = gix_chunk::file::Index::for_writing();
+        cf.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        cf.plan_chunk(OID_LOOKUP_CHUNK_ID, num_commits * hash_len as u64);
+        cf.plan_chunk(COMMIT_DATA_CHUNK_ID, num_commits * (hash_len as u64 + 16));
+        if self.corrected_dates.is_some() {
+            cf.plan_chunk(GENERATION_DATA_CHUNK_ID, num_commits * 4);
+            if num_overflows != 0 {
+                cf.plan_chunk(GENERATION_DATA_OVERFLOW_CHUNK_ID, num_overflows as u64 * 8);
+            }
+        }
+        if num_extra_edges != 0 {
+            cf.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, num_extra_edges as u64 * 4);
+        }
+        if let Some((_, filters)) = self.bloom_filters {
+            cf.plan_chunk(BLOOM_FILTER_INDEX_CHUNK_ID, num_commits * 4);
+            cf.plan_chunk(
+                BLOOM_FILTER_DATA_CHUNK_ID,
+                (BLOOM_FILTER_DATA_HEADER_LEN + filters.iter().map(|f| f.as_bytes().len()).sum::<usize>()) as u64,
+            );
+        }
+        if !self.base_graphs.is_empty() {
+            cf.plan_chunk(BASE_GRAPHS_LIST_CHUNK_ID, (self.base_graphs.len() * hash_len) as u64);
+        }
+
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[
+            1, /* version */
+            object_hash as u8,
+            cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            self.base_graphs
+                .len()
+                .try_into()
+                .expect("BUG: callers assure there are no more than 255 base graphs"),
+        ])?;
+
+        let mut chunk_write = cf.into_write(&mut out, HEADER_LEN)?;
+        while let Some(chunk_to_write) = chunk_write.next_chunk() {
+            match chunk_to_write {
+                OID_FAN_CHUNK_ID => {
+                    let mut count = 0u32;
+                    let mut commits = self.commits.iter().peekable();
+                    for first_byte in 0..FAN_LEN {
+                        while commits.next_if(|c| c.id.first_byte() as usize == first_byte).is_some() {
+                            count += 1;
+                        }
+                        chunk_write.write_all(&count.to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for commit in self.commits {
+                        chunk_write.write_all(commit.id.as_bytes())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    let mut extra_edge_index = 0;
+                    for ((commit, parents), topo_level) in self.commits.iter().zip(self.parents).zip(self.topo_levels) {
+                        chunk_write.write_all(commit.tree.as_bytes())?;
+                        let (parent1, parent2) = match parents.as_slice() {
+                            [] => (NO_PARENT, NO_PARENT),
+                            [p1] => (*p1, NO_PARENT),
+                            [p1, p2] => (*p1, *p2),
+                            [p1, rest @ ..] => {
+                                let edge = EXTENDED_EDGES_MASK | extra_edge_index;
+                                extra_edge_index += rest.len() as u32;
+                                (*p1, edge)
+                            }
+                        };
+                        chunk_write.write_all(&parent1.to_be_bytes())?;
+                        chunk_write.write_all(&parent2.to_be_bytes())?;
+                        let time = commit.committer_time;
+                        chunk_write.write_all(&((topo_level << 2) | ((time >> 32) & 0x3) as u32).to_be_bytes())?;
+                        chunk_write.write_all(&(time as u32).to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_CHUNK_ID => {
+                    let dates = self.corrected_dates.expect("present if planned");
+                    let mut overflow_index = 0;
+                    for (commit, date) in self.commits.iter().zip(dates) {
+                        let offset = date - commit.committer_time;
+                        let value = if offset > GENERATION_DATA_OFFSET_MAX {
+                            overflow_index += 1;
+                            GENERATION_DATA_OVERFLOW_MASK | (overflow_index - 1)
+                        } else {
+                            offset as u32
+                        };
+                        chunk_write.write_all(&value.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    let dates = self.corrected_dates.expect("present if planned");
+                    for (commit, date) in self.commits.iter().zip(dates) {
+                        let offset = date - commit.committer_time;
+                        if offset > GENERATION_DATA_OFFSET_MAX {
+                            chunk_write.write_all(&offset.to_be_bytes())?;
+                        }
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for parents in self.parents.iter().filter(|parents| parents.len() > 2) {
+                        let (last, rest) = parents[1..].split_last().expect("more than two parents");
+                        for parent in rest {
+                            chunk_write.write_all(&parent.to_be_bytes())?;
+                        }
+                        chunk_write.write_all(&(LAST_EXTENDED_EDGE_MASK | last).to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_INDEX_CHUNK_ID => {
+                    let (_, filters) = self.bloom_filters.expect("present if planned");
+                    let mut end = 0u32;
+                    for filter in filters {
+                        end += filter.as_bytes().len() as u32;
+                        chunk_write.write_all(&end.to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_DATA_CHUNK_ID => {
+                    let (settings, filters) = self.bloom_filters.expect("present if planned");
+                    for value in [settings.hash_version, settings.num_hashes, settings.bits_per_entry] {
+                        chunk_write.write_all(&value.to_be_bytes())?;
+                    }
+                    for filter in filters {
+                        chunk_write.write_all(filter.as_bytes())?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for id in self.base_graphs {
+                        chunk_write.write_all(id.as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+        }
+        drop(chunk_write);
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+        Ok(checksum)
+    }
+}
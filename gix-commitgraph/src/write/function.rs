@@ -0,0 +1,357 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use bstr::{BString, ByteSlice};
+
+use crate::{
+    bloom,
+    write::{chunks::Layer, Commit, Error, Options, Outcome, Split},
+    File, Graph, Position, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+/// Write `commits` hashed with `object_hash` into the commit-graph in `info_dir`, typically `.git/objects/info`, as configured
+/// by `options`, and return information about the written file.
+///
+/// When writing a layer of a split commit-graph with [`Split::Merge`] or [`Split::NoMerge`], commits that are already part of
+/// the existing commit-graph are skipped, so it's sufficient to pass all commits that aren't in it yet.
+/// Otherwise, all commits that should be part of the commit-graph have to be passed.
+/// In any case, all parents of the given `commits` must either be part of `commits` or of the commit-graph layers that are kept.
+///
+/// If [changed-path Bloom filters](Options::changed_paths) are enabled, `changed_paths(commit)` is called for each commit that
+/// doesn't have a usable filter yet to obtain the paths of all files that changed compared to its first parent, or all files of
+/// its tree if it has no parent. If it returns `None`, no filter is stored for the commit.
+pub fn write<E>(
+    info_dir: &Path,
+    commits: impl IntoIterator<Item = Commit>,
+    object_hash: gix_hash::Kind,
+    mut changed_paths: impl FnMut(&Commit) -> Result<Option<Vec<BString>>, E>,
+    options: Options,
+) -> Result<Outcome, Error<E>>
+where
+    E: std::error::Error + 'static,
+{
+    let (graph, existing_is_monolithic) = match load_existing(info_dir)? {
+        Some((graph, is_monolithic)) => (Some(graph), is_monolithic),
+        None => (None, false),
+    };
+    let files: &[File] = graph.as_ref().map_or(&[], |graph| graph.files.as_slice());
+    if let Some(actual) = files.first().map(File::object_hash).filter(|kind| *kind != object_hash) {
+        return Err(Error::ObjectHashMismatch {
+            expected: object_hash,
+            actual,
+        });
+    }
+
+    let mut commits: Vec<_> = commits.into_iter().collect();
+    if matches!(options.split, Some(Split::Merge | Split::NoMerge)) {
+        commits.retain(|commit| !files.iter().any(|file| file.lookup(commit.id).is_some()));
+    }
+    if commits.is_empty() {
+        return Ok(Outcome {
+            path: None,
+            num_commits: 0,
+            num_files: files.len(),
+        });
+    }
+
+    let num_kept_files = match options.split {
+        None | Some(Split::Replace) => 0,
+        Some(Split::NoMerge) => files.len(),
+        Some(Split::Merge) => {
+            let mut num_kept_files = files.len();
+            let mut num_commits = commits.len() as u64;
+            while let Some(top) = num_kept_files.checked_sub(1).map(|idx| &files[idx]) {
+                let top_commits = u64::from(top.num_commits());
+                if top_commits <= u64::from(options.size_multiple) * num_commits
+                    || options.max_commits.map_or(false, |max| num_commits > u64::from(max))
+                {
+                    num_commits += top_commits;
+                    num_kept_files -= 1;
+                } else {
+                    break;
+                }
+            }
+            for file in &files[num_kept_files..] {
+                for commit in file.iter_commits() {
+                    commits.push(Commit {
+                        id: commit.id().to_owned(),
+                        tree: commit.root_tree_id().to_owned(),
+                        parents: commit
+                            .iter_parents()
+                            .map(|pos| pos.map(|pos| files_id_at(files, pos)))
+                            .collect::<Result<_, _>>()?,
+                        committer_time: commit.committer_timestamp(),
+                    });
+                }
+            }
+            num_kept_files
+        }
+    };
+    if num_kept_files > usize::from(u8::MAX) {
+        return Err(Error::TooManyFiles);
+    }
+    commits.sort_by_key(|c| c.id);
+    commits.dedup_by(|a, b| a.id == b.id);
+
+    let base_files = &files[..num_kept_files];
+    let num_base_commits: u32 = base_files.iter().map(File::num_commits).sum();
+    let num_commits = u64::from(num_base_commits) + commits.len() as u64;
+    if num_commits > u64::from(MAX_COMMITS) {
+        return Err(Error::TooManyCommits(num_commits));
+    }
+
+    let parents = commits
+        .iter()
+        .map(|commit| {
+            commit
+                .parents
+                .iter()
+                .map(|parent| match commits.binary_search_by(|c| c.id.cmp(parent)) {
+                    Ok(idx) => Ok(num_base_commits + idx as u32),
+                    Err(_) => graph
+                        .as_ref()
+                        .and_then(|graph| graph.lookup(parent))
+                        .filter(|pos| pos.0 < num_base_commits)
+                        .map(|pos| pos.0)
+                        .ok_or(Error::ParentMissing {
+                            id: commit.id,
+                            parent: *parent,
+                        }),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let (topo_levels, corrected_dates) = generations(&commits, &parents, graph.as_ref(), num_base_commits);
+    let write_generation_data = options.generation_data && base_files.iter().all(File::has_generation_data);
+
+    let bloom_filters = options
+        .changed_paths
+        .map(|settings| {
+            commits
+                .iter()
+                .map(|commit| {
+                    if let Some(filter) = graph
+                        .as_ref()
+                        .and_then(|graph| existing_bloom_filter(graph, &commit.id, &settings))
+                    {
+                        return Ok(filter);
+                    }
+                    Ok(match changed_paths(commit).map_err(Error::ChangedPaths)? {
+                        Some(paths) => bloom::Filter::from_changed_paths(paths.iter().map(|p| p.as_bstr()), &settings),
+                        None => bloom::Filter::from_bytes(Vec::new()),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error<E>>>()
+                .map(|filters| (settings, filters))
+        })
+        .transpose()?;
+
+    let base_graphs: Vec<_> = base_files.iter().map(|file| file.checksum().to_owned()).collect();
+    let layer = Layer {
+        commits: &commits,
+        parents: &parents,
+        topo_levels: &topo_levels,
+        corrected_dates: write_generation_data.then_some(corrected_dates.as_slice()),
+        bloom_filters: bloom_filters
+            .as_ref()
+            .map(|(settings, filters)| (settings, filters.as_slice())),
+        base_graphs: &base_graphs,
+    };
+    // Release all memory maps before files are renamed or deleted.
+    drop(graph);
+
+    let graphs_dir = info_dir.join("commit-graphs");
+    let monolithic_path = info_dir.join("commit-graph");
+    let chain_path = graphs_dir.join("commit-graph-chain");
+    let path = match options.split {
+        None => {
+            let mut lock = gix_lock::File::acquire_to_update_resource(
+                &monolithic_path,
+                gix_lock::acquire::Fail::Immediately,
+                None,
+            )?;
+            layer
+                .write_to(std::io::BufWriter::new(&mut lock), object_hash)
+                .map_err(io_error(&monolithic_path))?;
+            lock.commit()?;
+
+            remove_if_present(&chain_path).map_err(io_error(&chain_path))?;
+            remove_unreferenced_layers(&graphs_dir, &[]).map_err(io_error(&graphs_dir))?;
+            monolithic_path
+        }
+        Some(_) => {
+            std::fs::create_dir_all(&graphs_dir).map_err(io_error(&graphs_dir))?;
+            let mut tempfile = gix_lock::tempfile::new(
+                &graphs_dir,
+                gix_lock::tempfile::ContainingDirectory::Exists,
+                gix_lock::tempfile::AutoRemove::Tempfile,
+            )
+            .map_err(io_error(&graphs_dir))?;
+            let checksum = layer
+                .write_to(std::io::BufWriter::new(&mut tempfile), object_hash)
+                .map_err(io_error(&graphs_dir))?;
+            let path = layer_path(&graphs_dir, &checksum);
+            tempfile.persist(&path)?;
+
+            if existing_is_monolithic {
+                if num_kept_files == 1 {
+                    let base_path = layer_path(&graphs_dir, &base_graphs[0]);
+                    std::fs::rename(&monolithic_path, &base_path).map_err(io_error(&base_path))?;
+                } else {
+                    remove_if_present(&monolithic_path).map_err(io_error(&monolithic_path))?;
+                }
+            }
+
+            let mut chain = base_graphs;
+            chain.push(checksum);
+            let mut lock =
+                gix_lock::File::acquire_to_update_resource(&chain_path, gix_lock::acquire::Fail::Immediately, None)?;
+            for id in &chain {
+                writeln!(lock, "{id}").map_err(io_error(&chain_path))?;
+            }
+            lock.commit()?;
+
+            remove_unreferenced_layers(&graphs_dir, &chain).map_err(io_error(&graphs_dir))?;
+            path
+        }
+    };
+
+    Ok(Outcome {
+        path: Some(path),
+        num_commits: commits.len() as u32,
+        num_files: num_kept_files + 1,
+    })
+}
+
+/// Load the commit-graph in `info_dir` and return it along with `true` if it is a single `commit-graph` file.
+fn load_existing(info_dir: &Path) -> Result<Option<(Graph, bool)>, crate::init::Error> {
+    let monolithic_path = info_dir.join("commit-graph");
+    if monolithic_path.is_file() {
+        return Graph::from_file(&monolithic_path).map(|graph| Some((graph, true)));
+    }
+    let graphs_dir = info_dir.join("commit-graphs");
+    if graphs_dir.join("commit-graph-chain").is_file() {
+        return Graph::from_commit_graphs_dir(&graphs_dir).map(|graph| Some((graph, false)));
+    }
+    Ok(None)
+}
+
+fn files_id_at(files: &[File], pos: Position) -> gix_hash::ObjectId {
+    let mut remaining = pos.0;
+    for file in files {
+        match remaining.checked_sub(file.num_commits()) {
+            Some(v) => remaining = v,
+            None => return file.id_at(crate::file::Position(remaining)).to_owned(),
+        }
+    }
+    panic!("graph position too large: {}", pos.0);
+}
+
+/// Compute the topological level and the corrected commit date of each of `commits`, whose parents are at the graph positions
+/// in `parents`, with positions below `num_base_commits` referring to commits in `graph`.
+fn generations(
+    commits: &[Commit],
+    parents: &[Vec<u32>],
+    graph: Option<&Graph>,
+    num_base_commits: u32,
+) -> (Vec<u32>, Vec<u64>) {
+    let mut levels = vec![0u32; commits.len()];
+    let mut dates = vec![0u64; commits.len()];
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        stack.push(start);
+        while let Some(&idx) = stack.last() {
+            if levels[idx] != 0 {
+                stack.pop();
+                continue;
+            }
+            let mut max_level = 0;
+            let mut max_date = 0;
+            let mut parents_done = true;
+            for &pos in &parents[idx] {
+                match pos.checked_sub(num_base_commits) {
+                    Some(parent_idx) => {
+                        let parent_idx = parent_idx as usize;
+                        if levels[parent_idx] == 0 {
+                            parents_done = false;
+                            stack.push(parent_idx);
+                        } else {
+                            max_level = max_level.max(levels[parent_idx]);
+                            max_date = max_date.max(dates[parent_idx]);
+                        }
+                    }
+                    None => {
+                        let parent = graph
+                            .expect("base commits are only referenced if there is a graph")
+                            .commit_at(Position(pos));
+                        max_level = max_level.max(parent.generation());
+                        max_date = max_date.max(
+                            parent
+                                .corrected_commit_date()
+                                .unwrap_or_else(|| parent.committer_timestamp()),
+                        );
+                    }
+                }
+            }
+            if parents_done {
+                stack.pop();
+                levels[idx] = (max_level + 1).min(GENERATION_NUMBER_MAX);
+                dates[idx] = commits[idx].committer_time.max(max_date + 1);
+            }
+        }
+    }
+    (levels, dates)
+}
+
+fn existing_bloom_filter(graph: &Graph, id: &gix_hash::oid, settings: &bloom::Settings) -> Option<bloom::Filter> {
+    graph.files.iter().find_map(|file| {
+        let pos = file.lookup(id)?;
+        file.bloom_filter_settings()
+            .filter(|existing| existing.is_compatible_with(settings))?;
+        file.bloom_filter_at(pos).map(|filter| filter.to_owned())
+    })
+}
+
+fn layer_path(graphs_dir: &Path, checksum: &gix_hash::oid) -> PathBuf {
+    graphs_dir.join(format!("graph-{checksum}.graph"))
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
+
+/// Remove all layer files in `graphs_dir` which aren't part of `chain`.
+fn remove_unreferenced_layers(graphs_dir: &Path, chain: &[gix_hash::ObjectId]) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(graphs_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(hex) = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix("graph-"))
+            .and_then(|name| name.strip_suffix(".graph"))
+        else {
+            continue;
+        };
+        if !chain.iter().any(|id| id.to_hex().to_string() == hex) {
+            remove_if_present(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn io_error<E: std::error::Error + 'static>(path: &Path) -> impl FnOnce(std::io::Error) -> Error<E> + '_ {
+    move |source| Error::Io {
+        source,
+        path: path.to_owned(),
+    }
+}
@@ -0,0 +1,111 @@
+//! Write commit-graph files, either as a single file or as new layer of a split commit-graph chain.
+use std::path::PathBuf;
+
+use crate::bloom;
+
+mod chunks;
+pub(crate) mod function;
+
+/// A commit as it is written into a commit-graph file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: gix_hash::ObjectId,
+    /// The id of the commit's root tree.
+    pub tree: gix_hash::ObjectId,
+    /// The ids of all parents of the commit, in order.
+    pub parents: Vec<gix_hash::ObjectId>,
+    /// The committer time in seconds since the Unix epoch.
+    pub committer_time: u64,
+}
+
+/// Determines how commits are written into a commit-graph chain, mirroring the values of `git commit-graph write --split`.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Split {
+    /// Write new commits into a new layer on top of the chain, and merge it with the layers below it
+    /// as long as these aren't larger than [`Options::size_multiple`] times the commits to write so far.
+    #[default]
+    Merge,
+    /// Write new commits into a new layer on top of the chain, without ever merging layers.
+    NoMerge,
+    /// Write all commits into a single layer which replaces all existing layers.
+    Replace,
+}
+
+/// Options for use in [`write()`](crate::write()).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Options {
+    /// If `None`, write all commits into the single `commit-graph` file and remove an existing commit-graph chain.
+    /// Otherwise, write a layer of a commit-graph chain as configured.
+    pub split: Option<Split>,
+    /// If `true`, write corrected commit dates, also known as generation numbers v2, for use in traversals.
+    ///
+    /// Note that these are omitted when writing a layer on top of layers that don't have them.
+    pub generation_data: bool,
+    /// If set, write changed-path Bloom filters configured by the given settings.
+    pub changed_paths: Option<bloom::Settings>,
+    /// The factor by which a layer must be larger than the commits to write for it not to be merged, used with [`Split::Merge`].
+    pub size_multiple: u32,
+    /// If set, and with [`Split::Merge`], merge layers as long as the new layer would contain more than the given amount of commits.
+    pub max_commits: Option<u32>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            split: None,
+            generation_data: true,
+            changed_paths: None,
+            size_multiple: 2,
+            max_commits: None,
+        }
+    }
+}
+
+/// The outcome of [`write()`](crate::write()).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    /// The path to the file that was written, or `None` if there was nothing to write.
+    pub path: Option<PathBuf>,
+    /// The amount of commits written into the file at `path`.
+    pub num_commits: u32,
+    /// The amount of files the commit-graph consists of after writing.
+    pub num_files: usize,
+}
+
+/// The error returned by [`write()`](crate::write()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E: std::error::Error + 'static> {
+    #[error(transparent)]
+    Init(#[from] crate::init::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::file::commit::Error),
+    #[error("Could not write commit-graph data to '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("The existing commit-graph uses hash {actual:?}, but {expected:?} was requested")]
+    ObjectHashMismatch {
+        expected: gix_hash::Kind,
+        actual: gix_hash::Kind,
+    },
+    #[error("Parent {parent} of commit {id} is neither part of the commits to write nor of the existing commit-graph")]
+    ParentMissing {
+        id: gix_hash::ObjectId,
+        parent: gix_hash::ObjectId,
+    },
+    #[error(
+        "The commit-graph would contain {0} commits, but only {} commits are allowed",
+        crate::MAX_COMMITS
+    )]
+    TooManyCommits(u64),
+    #[error("A commit-graph chain can consist of no more than 256 files")]
+    TooManyFiles,
+    #[error(transparent)]
+    AcquireLock(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    PersistTempfile(#[from] gix_lock::tempfile::handle::persist::Error<gix_lock::tempfile::handle::Writable>),
+    #[error("Could not obtain the changed paths of a commit")]
+    ChangedPaths(#[source] E),
+}
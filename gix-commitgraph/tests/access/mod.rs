@@ -69,6 +69,21 @@ fn generation_numbers_overflow_is_handled_in_chained_graph() {
     }
 }
 
+#[test]
+fn corrected_commit_dates_with_overflow_in_chained_graph() {
+    let names = ["future-1", "old-1", "future-2", "old-2", "extra"];
+    let (cg, refs) = graph_and_expected("generation_number_overflow.sh", &names);
+    let future_date = 4147483646;
+    for (offset, name) in names.iter().enumerate() {
+        let commit = cg.commit_by_id(refs[*name].id()).expect("present");
+        assert_eq!(
+            commit.corrected_commit_date(),
+            Some(future_date + offset as u64),
+            "{name}: each commit is at least one second younger than its parent, even if its own date is in the past"
+        );
+    }
+}
+
 #[test]
 fn octupus_merges() {
     let (cg, refs) = graph_and_expected(
@@ -0,0 +1,64 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function history() {
+  git checkout -q --orphan main
+  git commit -q --allow-empty -m root
+
+  mkdir -p dir/sub other
+  echo a > a
+  echo b > dir/b
+  echo c > dir/sub/c
+  git add .
+  git commit -q -m "nested files"
+
+  git checkout -q -b side1 main
+  echo side1 > dir/side1
+  git add . && git commit -q -m side1
+
+  git checkout -q -b side2 main
+  echo side2 > other/side2
+  git add . && git commit -q -m side2
+
+  git checkout -q -b side3 main
+  git rm -q dir/sub/c
+  git commit -q -m side3
+
+  git checkout -q main
+  git merge -q -m octopus --no-ff side1 side2 side3 >/dev/null
+
+  echo x > "dir/$(printf '\xc3\xa9')"
+  git add . && git commit -q -m "non-ascii path"
+  git tag first
+
+  mkdir many
+  for i in $(seq 600); do
+    echo $i > many/$i
+  done
+  git add . && git commit -q -m "too many changes"
+  git commit -q --allow-empty -m "no change"
+  git tag second
+
+  echo changed > a
+  git commit -q -am "modify a"
+  git tag third
+}
+
+git init -q monolithic
+(cd monolithic
+  history
+  git commit-graph write --no-progress --reachable --changed-paths
+)
+
+git init -q split
+(cd split
+  history
+  mkdir ../split-expected
+  git rev-parse first | git commit-graph write --no-progress --stdin-commits --changed-paths --split
+  cp -R .git/objects/info/commit-graphs ../split-expected/first
+  git rev-parse second | git commit-graph write --no-progress --stdin-commits --changed-paths --split=no-merge
+  cp -R .git/objects/info/commit-graphs ../split-expected/second
+  git rev-parse third | git commit-graph write --no-progress --stdin-commits --changed-paths --split
+  cp -R .git/objects/info/commit-graphs ../split-expected/third
+  rm -Rf .git/objects/info/commit-graphs
+)
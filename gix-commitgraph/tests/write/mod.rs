@@ -0,0 +1,269 @@
+use std::{path::Path, process::Command};
+
+use bstr::{BString, ByteSlice};
+use gix_commitgraph::{
+    bloom,
+    write::{Commit, Options, Split},
+    Graph,
+};
+use gix_testtools::{scripted_fixture_read_only, tempfile};
+
+fn git(repo: &Path, args: &[&str]) -> Vec<u8> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .env_remove("GIT_DIR")
+        .output()
+        .expect("git can be executed");
+    assert!(output.status.success(), "{args:?} failed");
+    output.stdout
+}
+
+fn commits(repo: &Path, rev: &str) -> Vec<Commit> {
+    git(repo, &["log", "--format=%H %T %ct %P", rev])
+        .lines()
+        .map(|line| {
+            let mut tokens = line.to_str().expect("ascii").split_whitespace();
+            let mut next_id = || gix_hash::ObjectId::from_hex(tokens.next().expect("present").as_bytes());
+            Commit {
+                id: next_id().expect("valid"),
+                tree: next_id().expect("valid"),
+                committer_time: tokens.next().expect("present").parse().expect("number"),
+                parents: tokens
+                    .map(|hex| gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("valid"))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn changed_paths(repo: &Path) -> impl FnMut(&Commit) -> Result<Option<Vec<BString>>, std::convert::Infallible> + '_ {
+    move |commit| {
+        let id = commit.id.to_string();
+        let mut args = vec!["diff-tree", "-r", "-z", "--name-only", "--no-commit-id"];
+        let parent;
+        match commit.parents.first() {
+            Some(first_parent) => {
+                parent = first_parent.to_string();
+                args.push(&parent);
+            }
+            None => args.push("--root"),
+        }
+        args.push(&id);
+        Ok(Some(
+            git(repo, &args)
+                .split_str(b"\0")
+                .filter(|path| !path.is_empty())
+                .map(Into::into)
+                .collect(),
+        ))
+    }
+}
+
+fn options(split: Option<Split>) -> Options {
+    Options {
+        split,
+        changed_paths: Some(bloom::Settings::default()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn monolithic_graph_matches_git() -> gix_testtools::Result {
+    let fixture = scripted_fixture_read_only("write.sh")?;
+    let repo = fixture.join("monolithic");
+    let tmp = tempfile::tempdir()?;
+
+    let outcome = gix_commitgraph::write(
+        tmp.path(),
+        commits(&repo, "third"),
+        gix_hash::Kind::Sha1,
+        changed_paths(&repo),
+        options(None),
+    )?;
+    let path = tmp.path().join("commit-graph");
+    assert_eq!(outcome.path.as_deref(), Some(path.as_path()));
+    assert_eq!(outcome.num_commits, 10);
+    assert_eq!(outcome.num_files, 1);
+    assert_eq!(
+        std::fs::read(&path)?,
+        std::fs::read(repo.join(".git/objects/info/commit-graph"))?,
+        "generation data, extra edges and changed-path filters are written just like git does"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_graph_layers_match_git() -> gix_testtools::Result {
+    let fixture = scripted_fixture_read_only("write.sh")?;
+    let repo = fixture.join("split");
+    let tmp = tempfile::tempdir()?;
+
+    for (rev, split, expected_num_files) in [
+        ("first", Split::Merge, 1),
+        ("second", Split::NoMerge, 2),
+        ("third", Split::Merge, 2),
+    ] {
+        let outcome = gix_commitgraph::write(
+            tmp.path(),
+            commits(&repo, rev),
+            gix_hash::Kind::Sha1,
+            changed_paths(&repo),
+            options(Some(split)),
+        )?;
+        assert_eq!(outcome.num_files, expected_num_files, "{rev}");
+
+        let expected_dir = fixture.join("split-expected").join(rev);
+        let actual_dir = tmp.path().join("commit-graphs");
+        assert_eq!(
+            std::fs::read(actual_dir.join("commit-graph-chain"))?.as_bstr(),
+            std::fs::read(expected_dir.join("commit-graph-chain"))?.as_bstr(),
+            "{rev}: the chain has the same layers as the one created by git"
+        );
+        assert_eq!(
+            files_in(&actual_dir)?,
+            files_in(&expected_dir)?,
+            "{rev}: layers that are merged are removed"
+        );
+        for name in files_in(&expected_dir)? {
+            assert_eq!(
+                std::fs::read(actual_dir.join(&name))?,
+                std::fs::read(expected_dir.join(&name))?,
+                "{rev}: {name} is written like git does"
+            );
+        }
+    }
+
+    let outcome = gix_commitgraph::write(
+        tmp.path(),
+        commits(&repo, "third"),
+        gix_hash::Kind::Sha1,
+        changed_paths(&repo),
+        options(Some(Split::NoMerge)),
+    )?;
+    assert_eq!(
+        outcome,
+        gix_commitgraph::write::Outcome {
+            path: None,
+            num_commits: 0,
+            num_files: 2
+        },
+        "nothing is written if all commits are known already"
+    );
+    Ok(())
+}
+
+#[test]
+fn replace_and_switch_from_chain_to_single_file() -> gix_testtools::Result {
+    let fixture = scripted_fixture_read_only("write.sh")?;
+    let repo = fixture.join("split");
+    let tmp = tempfile::tempdir()?;
+    let graphs_dir = tmp.path().join("commit-graphs");
+
+    for (rev, split) in [("first", Split::NoMerge), ("second", Split::NoMerge)] {
+        gix_commitgraph::write(
+            tmp.path(),
+            commits(&repo, rev),
+            gix_hash::Kind::Sha1,
+            changed_paths(&repo),
+            options(Some(split)),
+        )?;
+    }
+    assert_eq!(files_in(&graphs_dir)?.len(), 3, "two layers and the chain file");
+
+    let outcome = gix_commitgraph::write(
+        tmp.path(),
+        commits(&repo, "third"),
+        gix_hash::Kind::Sha1,
+        changed_paths(&repo),
+        options(Some(Split::Replace)),
+    )?;
+    assert_eq!(outcome.num_files, 1);
+    assert_eq!(outcome.num_commits, 10, "all commits are written into the only layer");
+    assert_eq!(files_in(&graphs_dir)?.len(), 2, "one layer and the chain file");
+    assert_eq!(Graph::from_info_dir(tmp.path())?.num_commits(), 10);
+
+    let outcome = gix_commitgraph::write(
+        tmp.path(),
+        commits(&repo, "third"),
+        gix_hash::Kind::Sha1,
+        |_| Ok::<_, std::convert::Infallible>(None),
+        options(None),
+    )?;
+    assert_eq!(outcome.path, Some(tmp.path().join("commit-graph")));
+    assert_eq!(files_in(&graphs_dir)?.len(), 0, "the chain was removed");
+
+    let graph = Graph::from_info_dir(tmp.path())?;
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    assert_eq!(graph.num_commits(), 10);
+    assert!(
+        graph.iter_commits().all(|c| c.bloom_filter().is_some()),
+        "existing filters are reused instead of asking for changed paths"
+    );
+
+    let outcome = gix_commitgraph::write(
+        tmp.path(),
+        commits(&repo, "second"),
+        gix_hash::Kind::Sha1,
+        changed_paths(&repo),
+        options(Some(Split::Merge)),
+    )?;
+    assert_eq!(outcome.num_commits, 0, "all commits are present already");
+    assert_eq!(outcome.path, None);
+    Ok(())
+}
+
+#[test]
+fn bloom_filters_and_corrected_dates_can_be_read() -> gix_testtools::Result {
+    let fixture = scripted_fixture_read_only("write.sh")?;
+    let repo = fixture.join("monolithic");
+    let graph = Graph::from_info_dir(&repo.join(".git/objects/info"))?;
+    let settings = *graph.bloom_filter_settings().expect("filters are present");
+    assert_eq!(settings, bloom::Settings::default());
+    let key = |path: &str| bloom::Key::new(path.into(), &settings);
+
+    let modify_a = graph.commit_by_id(rev_parse(&repo, "third")).expect("present");
+    let filter = modify_a.bloom_filter().expect("computed");
+    assert!(filter.contains(&key("a")));
+    assert!(!filter.contains(&key("dir")), "unchanged paths are not contained");
+
+    let non_ascii = graph.commit_by_id(rev_parse(&repo, "first")).expect("present");
+    let filter = non_ascii.bloom_filter().expect("computed");
+    assert!(filter.contains(&key("dir")), "leading directories are contained");
+    assert!(
+        filter.contains(&bloom::Key::new(b"dir/\xc3\xa9".as_bstr(), &settings)),
+        "paths with bytes beyond ASCII are hashed like git does"
+    );
+
+    let no_change = graph.commit_by_id(rev_parse(&repo, "second")).expect("present");
+    assert_eq!(no_change.bloom_filter().expect("computed").as_bytes(), &[0]);
+    assert!(!no_change.bloom_filter().expect("computed").contains(&key("a")));
+
+    let too_many = graph.commit_by_id(rev_parse(&repo, "second~1")).expect("present");
+    let filter = too_many.bloom_filter().expect("computed");
+    assert_eq!(filter.as_bytes(), &[0xff]);
+    assert!(filter.contains(&key("anything")));
+
+    let root = graph.commit_by_id(rev_parse(&repo, "first~3")).expect("present");
+    assert_eq!(root.generation(), 1);
+    assert_eq!(root.corrected_commit_date(), Some(root.committer_timestamp()));
+    assert_eq!(
+        modify_a.corrected_commit_date(),
+        Some(modify_a.committer_timestamp() + 7),
+        "all commits have the same date, which is why it's incremented for each generation"
+    );
+    Ok(())
+}
+
+fn rev_parse(repo: &Path, rev: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(git(repo, &["rev-parse", rev]).trim()).expect("valid")
+}
+
+fn files_in(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut names = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.file_name().into_string().expect("valid UTF-8")))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    Ok(names)
+}
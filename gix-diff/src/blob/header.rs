@@ -0,0 +1,262 @@
+//! Facilities to write the header of a file diff in the format of `git diff`, which precedes the hunks written by
+//! [`UnifiedDiff`](crate::blob::UnifiedDiff).
+use std::io::Write;
+
+use bstr::{BStr, BString};
+
+/// The kind of location that one side of the diff is coming from, used to produce [mnemonic prefixes](Prefixes::mnemonic()).
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub enum Location {
+    /// A tree of a commit, with the mnemonic prefix `c/`.
+    Commit,
+    /// The index, with the mnemonic prefix `i/`.
+    Index,
+    /// The working tree, with the mnemonic prefix `w/`.
+    Worktree,
+    /// Any other object, with the mnemonic prefix `o/`.
+    Object,
+}
+
+impl Location {
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Location::Commit => "c/",
+            Location::Index => "i/",
+            Location::Worktree => "w/",
+            Location::Object => "o/",
+        }
+    }
+}
+
+/// Determines which prefixes to use for the paths of the old and new side of a diff.
+#[derive(Default, Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub enum PrefixStyle {
+    /// Use `a/` and `b/`.
+    #[default]
+    Default,
+    /// Don't use any prefix, like with `diff.noprefix`.
+    None,
+    /// Use prefixes that indicate the [location](Location) of each side, like with `diff.mnemonicPrefix`.
+    Mnemonic,
+}
+
+impl PrefixStyle {
+    /// Produce the prefixes to use if the old side comes from `old` and the new side from `new`.
+    pub fn to_prefixes(&self, old: Location, new: Location) -> Prefixes {
+        match self {
+            PrefixStyle::Default => Prefixes::default(),
+            PrefixStyle::None => Prefixes::none(),
+            PrefixStyle::Mnemonic => Prefixes::mnemonic(old, new),
+        }
+    }
+}
+
+/// The prefixes to put in front of the paths of the old and new side of a diff.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Prefixes {
+    /// The prefix of the path of the old side, like `a/`.
+    pub old: BString,
+    /// The prefix of the path of the new side, like `b/`.
+    pub new: BString,
+}
+
+impl Default for Prefixes {
+    fn default() -> Self {
+        Prefixes {
+            old: "a/".into(),
+            new: "b/".into(),
+        }
+    }
+}
+
+/// Instantiation
+impl Prefixes {
+    /// Don't use any prefix.
+    pub fn none() -> Self {
+        Prefixes {
+            old: BString::default(),
+            new: BString::default(),
+        }
+    }
+
+    /// Use prefixes that indicate where the `old` and `new` sides come from, like `c/` and `w/` when comparing a commit
+    /// to the working tree.
+    pub fn mnemonic(old: Location, new: Location) -> Self {
+        Prefixes {
+            old: old.mnemonic().into(),
+            new: new.mnemonic().into(),
+        }
+    }
+}
+
+/// One side of a file diff.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Side<'a> {
+    /// The path of the file, relative to the root of the repository.
+    pub path: &'a BStr,
+    /// The id of the file's content.
+    pub id: &'a gix_hash::oid,
+    /// The kind of file.
+    pub mode: gix_object::tree::EntryKind,
+}
+
+/// The kind of rewrite that led from the old to the new side.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum RewriteKind {
+    /// The old file was renamed to the new file.
+    Rename,
+    /// The new file is a copy of the old file.
+    Copy,
+}
+
+/// Information about a rewrite of a file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Rewrite {
+    /// The kind of rewrite.
+    pub kind: RewriteKind,
+    /// The similarity of the old and new file in percent, from 0 to 100.
+    pub similarity: u8,
+}
+
+/// Information about a changed file, suitable to write the header of its diff like `git diff` does.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Header<'a> {
+    /// The old side of the diff, or `None` if the file was added.
+    pub old: Option<Side<'a>>,
+    /// The new side of the diff, or `None` if the file was deleted.
+    pub new: Option<Side<'a>>,
+    /// If set, the old side was renamed or copied to the new side.
+    pub rewrite: Option<Rewrite>,
+}
+
+impl Header<'_> {
+    /// Write the extended header to `out`, starting with the `diff --git` line and ending with the `index` line, using `prefixes`
+    /// for the paths and abbreviating object ids to `hex_len` characters.
+    pub fn write_to(&self, out: &mut dyn Write, prefixes: &Prefixes, hex_len: usize) -> std::io::Result<()> {
+        let (old, new) = self.sides();
+        out.write_all(b"diff --git ")?;
+        write_path(out, prefixes.old.as_ref(), old.path)?;
+        out.write_all(b" ")?;
+        write_path(out, prefixes.new.as_ref(), new.path)?;
+        out.write_all(b"\n")?;
+
+        match (self.old.as_ref(), self.new.as_ref()) {
+            (None, Some(new)) => writeln!(out, "new file mode {:06o}", new.mode as u16)?,
+            (Some(old), None) => writeln!(out, "deleted file mode {:06o}", old.mode as u16)?,
+            (Some(old), Some(new)) if old.mode != new.mode => {
+                writeln!(out, "old mode {:06o}", old.mode as u16)?;
+                writeln!(out, "new mode {:06o}", new.mode as u16)?;
+            }
+            _ => {}
+        }
+
+        if let Some(rewrite) = self.rewrite {
+            let kind = match rewrite.kind {
+                RewriteKind::Rename => "rename",
+                RewriteKind::Copy => "copy",
+            };
+            writeln!(out, "similarity index {}%", rewrite.similarity)?;
+            write!(out, "{kind} from ")?;
+            write_path(out, "".into(), old.path)?;
+            write!(out, "\n{kind} to ")?;
+            write_path(out, "".into(), new.path)?;
+            out.write_all(b"\n")?;
+        }
+
+        let null = gix_hash::ObjectId::null(old.id.kind());
+        let old_id = self.old.as_ref().map_or(null.as_ref(), |side| side.id);
+        let new_id = self.new.as_ref().map_or(null.as_ref(), |side| side.id);
+        if old_id != new_id {
+            write!(
+                out,
+                "index {}..{}",
+                old_id.to_hex_with_len(hex_len),
+                new_id.to_hex_with_len(hex_len)
+            )?;
+            match (self.old.as_ref(), self.new.as_ref()) {
+                (Some(old), Some(new)) if old.mode == new.mode => writeln!(out, " {:06o}", old.mode as u16)?,
+                _ => out.write_all(b"\n")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the `---` and `+++` lines with the paths of both sides to `out`, which precede the hunks of the diff,
+    /// using `prefixes` for the paths.
+    pub fn write_file_names(&self, out: &mut dyn Write, prefixes: &Prefixes) -> std::io::Result<()> {
+        for (marker, prefix, side) in [
+            ("---", prefixes.old.as_ref(), self.old.as_ref()),
+            ("+++", prefixes.new.as_ref(), self.new.as_ref()),
+        ] {
+            write!(out, "{marker} ")?;
+            match side {
+                Some(side) => {
+                    write_path(out, prefix, side.path)?;
+                    if side.path.contains(&b' ') {
+                        out.write_all(b"\t")?;
+                    }
+                }
+                None => out.write_all(b"/dev/null")?,
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Write the line that indicates that binary files differ to `out`, to be written instead of the hunks of the diff,
+    /// using `prefixes` for the paths.
+    pub fn write_binary_files_differ(&self, out: &mut dyn Write, prefixes: &Prefixes) -> std::io::Result<()> {
+        out.write_all(b"Binary files ")?;
+        for (idx, (prefix, side)) in [
+            (prefixes.old.as_ref(), self.old.as_ref()),
+            (prefixes.new.as_ref(), self.new.as_ref()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if idx == 1 {
+                out.write_all(b" and ")?;
+            }
+            match side {
+                Some(side) => write_path(out, prefix, side.path)?,
+                None => out.write_all(b"/dev/null")?,
+            }
+        }
+        out.write_all(b" differ\n")
+    }
+
+    /// Return both sides, with the missing side substituted by the existing one.
+    fn sides(&self) -> (Side<'_>, Side<'_>) {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => (old, new),
+            (Some(side), None) | (None, Some(side)) => (side, side),
+            (None, None) => unreachable!("BUG: a header needs at least one side"),
+        }
+    }
+}
+
+/// Write `prefix` and `path` to `out`, quoted like `git` does it if `path` contains unusual characters.
+fn write_path(out: &mut dyn Write, prefix: &BStr, path: &BStr) -> std::io::Result<()> {
+    let needs_quoting = |b: &u8| *b == b'"' || *b == b'\\' || *b < 0x20 || *b >= 0x7f;
+    if !prefix.iter().chain(path.iter()).any(needs_quoting) {
+        out.write_all(prefix)?;
+        return out.write_all(path);
+    }
+    out.write_all(b"\"")?;
+    for &b in prefix.iter().chain(path.iter()) {
+        match b {
+            b'\x07' => out.write_all(b"\\a")?,
+            b'\x08' => out.write_all(b"\\b")?,
+            b'\t' => out.write_all(b"\\t")?,
+            b'\n' => out.write_all(b"\\n")?,
+            b'\x0b' => out.write_all(b"\\v")?,
+            b'\x0c' => out.write_all(b"\\f")?,
+            b'\r' => out.write_all(b"\\r")?,
+            b'"' => out.write_all(b"\\\"")?,
+            b'\\' => out.write_all(b"\\\\")?,
+            b if needs_quoting(&b) => write!(out, "\\{b:03o}")?,
+            b => out.write_all(&[b])?,
+        }
+    }
+    out.write_all(b"\"")
+}
@@ -1,9 +1,12 @@
 //! For using text diffs, please have a look at the [`imara-diff` documentation](https://docs.rs/imara-diff),
 //! maintained by [Pascal Kuthe](https://github.com/pascalkuthe).
-use std::{collections::HashMap, path::PathBuf};
+//!
+//! Note that [`Algorithm`] and [`diff()`] shadow their `imara-diff` counterparts to add the [patience](patience) algorithm.
+use std::{collections::HashMap, hash::Hash, path::PathBuf};
 
 use bstr::BString;
 pub use imara_diff::*;
+use imara_diff::{intern::InternedInput, intern::Token};
 
 ///
 #[allow(clippy::empty_docs)]
@@ -13,6 +16,72 @@ pub mod pipeline;
 #[allow(clippy::empty_docs)]
 pub mod platform;
 
+pub mod unified_diff;
+pub use unified_diff::{ContextSize, UnifiedDiff};
+
+pub mod header;
+
+pub mod stat;
+
+pub mod patience;
+
+/// The diff algorithm to use, covering all algorithms offered by `git`.
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Algorithm {
+    /// A variation of the patience algorithm that uses a histogram to find the least common subsequence,
+    /// see [`imara_diff::Algorithm::Histogram`].
+    #[default]
+    Histogram,
+    /// Myers algorithm with heuristics to speed up pathological cases, see [`imara_diff::Algorithm::Myers`].
+    Myers,
+    /// Myers algorithm without heuristics to guarantee a minimal edit sequence, see [`imara_diff::Algorithm::MyersMinimal`].
+    MyersMinimal,
+    /// The [patience](patience) algorithm, which only matches up lines that are unique in both files.
+    Patience,
+}
+
+impl From<imara_diff::Algorithm> for Algorithm {
+    fn from(value: imara_diff::Algorithm) -> Self {
+        match value {
+            imara_diff::Algorithm::Histogram => Algorithm::Histogram,
+            imara_diff::Algorithm::Myers => Algorithm::Myers,
+            imara_diff::Algorithm::MyersMinimal => Algorithm::MyersMinimal,
+        }
+    }
+}
+
+/// Compute an edit-script that transforms `input.before` into `input.after` using the specified `algorithm`,
+/// and pass each change to `sink` in order.
+pub fn diff<S: Sink, T: Eq + Hash>(algorithm: Algorithm, input: &InternedInput<T>, sink: S) -> S::Out {
+    diff_with_tokens(
+        algorithm,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+        sink,
+    )
+}
+
+/// Compute an edit-script that transforms `before` into `after` using the specified `algorithm`,
+/// and pass each change to `sink` in order.
+///
+/// `num_tokens` is the amount of distinct tokens, as returned by the interner.
+pub fn diff_with_tokens<S: Sink>(
+    algorithm: Algorithm,
+    before: &[Token],
+    after: &[Token],
+    num_tokens: u32,
+    sink: S,
+) -> S::Out {
+    let algorithm = match algorithm {
+        Algorithm::Histogram => imara_diff::Algorithm::Histogram,
+        Algorithm::Myers => imara_diff::Algorithm::Myers,
+        Algorithm::MyersMinimal => imara_diff::Algorithm::MyersMinimal,
+        Algorithm::Patience => return patience::diff(before, after, num_tokens, sink),
+    };
+    imara_diff::diff_with_tokens(algorithm, before, after, num_tokens, sink)
+}
+
 /// Information about the diff performed to detect similarity.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct DiffLineStats {
@@ -0,0 +1,420 @@
+//! An implementation of the patience diff algorithm as [implemented by `git`](https://github.com/git/git/blob/master/xdiff/xpatience.c),
+//! along with support for anchors like `git diff --anchored=<text>`.
+//!
+//! It finds lines that are unique in both files, matches them up using the longest common subsequence, and recurses
+//! into the gaps between them. If there are no unique lines in common, it falls back to [Myers](imara_diff::Algorithm::Myers).
+//!
+//! Groups of changed lines are slid down as far as possible afterwards, or up to align them with changes in the other file.
+//! This matches `git diff --patience --no-indent-heuristic`. The indent heuristic that `git` enables by default isn't
+//! implemented, so hunks may be placed differently than by plain `git diff --patience` when sliders are ambiguous.
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use imara_diff::{
+    intern::{InternedInput, Token},
+    Sink,
+};
+
+/// Compute the diff between `before` and `after` with the patience diff algorithm and pass all changes to `sink`.
+///
+/// `num_tokens` is the amount of distinct tokens, as returned by the interner.
+pub fn diff<S: Sink>(before: &[Token], after: &[Token], num_tokens: u32, sink: S) -> S::Out {
+    diff_inner(before, after, num_tokens, &|_| false, sink)
+}
+
+/// Compute the diff of `input` with the patience diff algorithm and pass all changes to `sink`, while assuring that
+/// unique lines that start with any of the given `anchors` are never shown as removed or added, just like
+/// `git diff --anchored=<text>` does.
+pub fn anchored<S, T>(input: &InternedInput<T>, anchors: &[&[u8]], sink: S) -> S::Out
+where
+    S: Sink,
+    T: Eq + Hash + AsRef<[u8]>,
+{
+    let is_anchor = |token: Token| {
+        let line = input.interner[token].as_ref();
+        anchors.iter().any(|anchor| line.starts_with(anchor))
+    };
+    diff_inner(
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+        &is_anchor,
+        sink,
+    )
+}
+
+fn diff_inner<S: Sink>(
+    before: &[Token],
+    after: &[Token],
+    num_tokens: u32,
+    is_anchor: &dyn Fn(Token) -> bool,
+    mut sink: S,
+) -> S::Out {
+    let mut state = State {
+        before,
+        after,
+        num_tokens,
+        is_anchor,
+        changed_before: vec![false; before.len()],
+        changed_after: vec![false; after.len()],
+    };
+    state.run(0..before.len() as u32, 0..after.len() as u32);
+    compact(before, &mut state.changed_before, &state.changed_after);
+    compact(after, &mut state.changed_after, &state.changed_before);
+
+    let (mut i1, mut i2) = (0, 0);
+    let (n1, n2) = (before.len(), after.len());
+    while i1 < n1 || i2 < n2 {
+        while i1 < n1 && i2 < n2 && !state.changed_before[i1] && !state.changed_after[i2] {
+            i1 += 1;
+            i2 += 1;
+        }
+        let (start1, start2) = (i1, i2);
+        while i1 < n1 && state.changed_before[i1] {
+            i1 += 1;
+        }
+        while i2 < n2 && state.changed_after[i2] {
+            i2 += 1;
+        }
+        if start1 == i1 && start2 == i2 {
+            break;
+        }
+        sink.process_change(start1 as u32..i1 as u32, start2 as u32..i2 as u32);
+    }
+    sink.finish()
+}
+
+/// Whether the line is unique on the side of `after`.
+#[derive(Debug, Copy, Clone)]
+enum Line2 {
+    /// The line wasn't seen in `after` yet.
+    Unseen,
+    /// The line was seen exactly once in `after`.
+    Unique(u32),
+    /// The line was seen more than once in either `before` or `after`.
+    NonUnique,
+}
+
+/// A line that appears in `before`.
+struct Entry {
+    line1: u32,
+    line2: Line2,
+    anchor: bool,
+    /// The previous entry in the longest common subsequence.
+    previous: Option<usize>,
+    /// The next entry in the longest common subsequence.
+    next: Option<usize>,
+}
+
+impl Entry {
+    fn line2(&self) -> u32 {
+        match self.line2 {
+            Line2::Unique(line) => line,
+            Line2::Unseen | Line2::NonUnique => unreachable!("BUG: only unique lines are part of a sequence"),
+        }
+    }
+}
+
+struct State<'a> {
+    before: &'a [Token],
+    after: &'a [Token],
+    num_tokens: u32,
+    is_anchor: &'a dyn Fn(Token) -> bool,
+    changed_before: Vec<bool>,
+    changed_after: Vec<bool>,
+}
+
+impl State<'_> {
+    fn run(&mut self, range1: Range<u32>, range2: Range<u32>) {
+        if range1.is_empty() || range2.is_empty() {
+            self.mark_changed(range1, range2);
+            return;
+        }
+
+        // Record all lines in `before` in order, and if they are unique in both files.
+        let mut entries = Vec::<Entry>::new();
+        let mut entry_by_token = HashMap::<Token, usize>::new();
+        for line1 in range1.clone() {
+            let token = self.before[line1 as usize];
+            match entry_by_token.get(&token) {
+                Some(&idx) => entries[idx].line2 = Line2::NonUnique,
+                None => {
+                    entry_by_token.insert(token, entries.len());
+                    entries.push(Entry {
+                        line1,
+                        line2: Line2::Unseen,
+                        anchor: (self.is_anchor)(token),
+                        previous: None,
+                        next: None,
+                    });
+                }
+            }
+        }
+        let mut has_matches = false;
+        for line2 in range2.clone() {
+            if let Some(&idx) = entry_by_token.get(&self.after[line2 as usize]) {
+                has_matches = true;
+                let entry = &mut entries[idx];
+                entry.line2 = match entry.line2 {
+                    Line2::Unseen => Line2::Unique(line2),
+                    Line2::Unique(_) | Line2::NonUnique => Line2::NonUnique,
+                };
+            }
+        }
+
+        if !has_matches {
+            self.mark_changed(range1, range2);
+            return;
+        }
+
+        match longest_common_sequence(&mut entries) {
+            Some(first) => self.walk_common_sequence(&entries, Some(first), range1, range2),
+            None => self.fall_back_to_myers(range1, range2),
+        }
+    }
+
+    fn walk_common_sequence(
+        &mut self,
+        entries: &[Entry],
+        mut first: Option<usize>,
+        range1: Range<u32>,
+        range2: Range<u32>,
+    ) {
+        let (mut line1, mut line2) = (range1.start, range2.start);
+        loop {
+            // Grow the line ranges of common lines.
+            let (next1, next2) = match first {
+                Some(idx) => {
+                    let (mut next1, mut next2) = (entries[idx].line1, entries[idx].line2());
+                    while next1 > line1 && next2 > line2 && self.is_match(next1 - 1, next2 - 1) {
+                        next1 -= 1;
+                        next2 -= 1;
+                    }
+                    (next1, next2)
+                }
+                None => (range1.end, range2.end),
+            };
+            while line1 < next1 && line2 < next2 && self.is_match(line1, line2) {
+                line1 += 1;
+                line2 += 1;
+            }
+
+            if next1 > line1 || next2 > line2 {
+                self.run(line1..next1, line2..next2);
+            }
+
+            let Some(mut idx) = first else {
+                return;
+            };
+            while let Some(next) = entries[idx].next {
+                let (current, next_entry) = (&entries[idx], &entries[next]);
+                if next_entry.line1 != current.line1 + 1 || next_entry.line2() != current.line2() + 1 {
+                    break;
+                }
+                idx = next;
+            }
+            line1 = entries[idx].line1 + 1;
+            line2 = entries[idx].line2() + 1;
+            first = entries[idx].next;
+        }
+    }
+
+    fn fall_back_to_myers(&mut self, range1: Range<u32>, range2: Range<u32>) {
+        let (offset1, offset2) = (range1.start, range2.start);
+        let (changed_before, changed_after) = (&mut self.changed_before, &mut self.changed_after);
+        imara_diff::diff_with_tokens(
+            imara_diff::Algorithm::Myers,
+            &self.before[range1.start as usize..range1.end as usize],
+            &self.after[range2.start as usize..range2.end as usize],
+            self.num_tokens,
+            |before: Range<u32>, after: Range<u32>| {
+                changed_before[(offset1 + before.start) as usize..(offset1 + before.end) as usize].fill(true);
+                changed_after[(offset2 + after.start) as usize..(offset2 + after.end) as usize].fill(true);
+            },
+        );
+    }
+
+    fn mark_changed(&mut self, range1: Range<u32>, range2: Range<u32>) {
+        self.changed_before[range1.start as usize..range1.end as usize].fill(true);
+        self.changed_after[range2.start as usize..range2.end as usize].fill(true);
+    }
+
+    fn is_match(&self, line1: u32, line2: u32) -> bool {
+        self.before[line1 as usize] == self.after[line2 as usize]
+    }
+}
+
+/// Find the longest sequence of unique lines that appear in the same order in both files using patience sorting,
+/// link its `entries` and return the index of the first one, or `None` if there are no unique common lines.
+///
+/// Anchors are always made part of the sequence, even if this makes it shorter.
+fn longest_common_sequence(entries: &mut [Entry]) -> Option<usize> {
+    let mut sequence = Vec::<usize>::with_capacity(entries.len());
+    let mut anchor_idx = None::<usize>;
+    for idx in 0..entries.len() {
+        let Line2::Unique(line2) = entries[idx].line2 else {
+            continue;
+        };
+        // The position in `sequence` of the largest entry that is smaller than this one.
+        let (mut left, mut right) = (-1isize, sequence.len() as isize);
+        while left + 1 < right {
+            let middle = left + (right - left) / 2;
+            if entries[sequence[middle as usize]].line2() > line2 {
+                right = middle;
+            } else {
+                left = middle;
+            }
+        }
+        entries[idx].previous = (left >= 0).then(|| sequence[left as usize]);
+        let pos = (left + 1) as usize;
+        if matches!(anchor_idx, Some(anchor_idx) if pos <= anchor_idx) {
+            continue;
+        }
+        if entries[idx].anchor {
+            anchor_idx = Some(pos);
+            sequence.truncate(pos);
+            sequence.push(idx);
+        } else if pos == sequence.len() {
+            sequence.push(idx);
+        } else {
+            sequence[pos] = idx;
+        }
+    }
+
+    let mut idx = *sequence.last()?;
+    entries[idx].next = None;
+    while let Some(previous) = entries[idx].previous {
+        entries[previous].next = Some(idx);
+        idx = previous;
+    }
+    Some(idx)
+}
+
+/// A possibly empty group of changed lines, with `end` being exclusive.
+#[derive(Debug, Copy, Clone)]
+struct Group {
+    start: usize,
+    end: usize,
+}
+
+impl Group {
+    fn first(changed: &[bool]) -> Self {
+        let mut end = 0;
+        while end < changed.len() && changed[end] {
+            end += 1;
+        }
+        Group { start: 0, end }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Move to the next group and return `true`, or return `false` if this is the last group.
+    fn next(&mut self, changed: &[bool]) -> bool {
+        if self.end == changed.len() {
+            return false;
+        }
+        self.start = self.end + 1;
+        self.end = self.start;
+        while self.end < changed.len() && changed[self.end] {
+            self.end += 1;
+        }
+        true
+    }
+
+    /// Move to the previous group and return `true`, or return `false` if this is the first group.
+    fn previous(&mut self, changed: &[bool]) -> bool {
+        if self.start == 0 {
+            return false;
+        }
+        self.end = self.start - 1;
+        self.start = self.end;
+        while self.start > 0 && changed[self.start - 1] {
+            self.start -= 1;
+        }
+        true
+    }
+
+    /// Slide the group down by one line if possible, merging it with the following group if they touch.
+    fn slide_down(&mut self, tokens: &[Token], changed: &mut [bool]) -> bool {
+        if self.end == tokens.len() || tokens[self.start] != tokens[self.end] {
+            return false;
+        }
+        changed[self.start] = false;
+        changed[self.end] = true;
+        self.start += 1;
+        self.end += 1;
+        while self.end < changed.len() && changed[self.end] {
+            self.end += 1;
+        }
+        true
+    }
+
+    /// Slide the group up by one line if possible, merging it with the preceding group if they touch.
+    fn slide_up(&mut self, tokens: &[Token], changed: &mut [bool]) -> bool {
+        if self.start == 0 || tokens[self.start - 1] != tokens[self.end - 1] {
+            return false;
+        }
+        self.start -= 1;
+        self.end -= 1;
+        changed[self.start] = true;
+        changed[self.end] = false;
+        while self.start > 0 && changed[self.start - 1] {
+            self.start -= 1;
+        }
+        true
+    }
+}
+
+/// Slide each group of `changed` lines in `tokens` down as far as possible, unless it can be aligned with a group of changes
+/// in the other file as indicated by `other_changed`, just like `xdl_change_compact()` in `git` does.
+fn compact(tokens: &[Token], changed: &mut [bool], other_changed: &[bool]) {
+    let mut group = Group::first(changed);
+    let mut other_group = Group::first(other_changed);
+    loop {
+        if !group.is_empty() {
+            let mut earliest_end;
+            let mut end_matching_other;
+            loop {
+                let group_size = group.end - group.start;
+                end_matching_other = None;
+
+                while group.slide_up(tokens, changed) {
+                    assert!(other_group.previous(other_changed), "BUG: group sync broken sliding up");
+                }
+                earliest_end = group.end;
+                if !other_group.is_empty() {
+                    end_matching_other = Some(group.end);
+                }
+
+                while group.slide_down(tokens, changed) {
+                    assert!(other_group.next(other_changed), "BUG: group sync broken sliding down");
+                    if !other_group.is_empty() {
+                        end_matching_other = Some(group.end);
+                    }
+                }
+                if group_size == group.end - group.start {
+                    break;
+                }
+            }
+
+            if group.end != earliest_end && end_matching_other.is_some() {
+                while other_group.is_empty() {
+                    assert!(group.slide_up(tokens, changed), "BUG: match disappeared");
+                    assert!(
+                        other_group.previous(other_changed),
+                        "BUG: group sync broken sliding to match"
+                    );
+                }
+            }
+        }
+
+        if !group.next(changed) {
+            break;
+        }
+        assert!(
+            other_group.next(other_changed),
+            "BUG: group sync broken moving to next group"
+        );
+    }
+}
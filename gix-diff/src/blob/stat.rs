@@ -0,0 +1,240 @@
+//! Facilities to summarize changes to files like `git diff --stat`, `--numstat` and `--shortstat` do.
+use std::io::Write;
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// The amount of changes in a file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Counts {
+    /// The file is text, and the given amount of lines was changed.
+    Text {
+        /// The amount of added lines.
+        insertions: u32,
+        /// The amount of removed lines.
+        removals: u32,
+    },
+    /// The file is binary, so only the sizes in bytes are known, which are `0` if the file doesn't exist on that side.
+    Binary {
+        /// The size of the old version of the file.
+        old_size: u64,
+        /// The size of the new version of the file.
+        new_size: u64,
+    },
+}
+
+/// Information about a single changed file, to be displayed in a summary.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct FileStat {
+    /// The path to display, which can be obtained with [`rename_display_path()`] if the file was renamed or copied.
+    pub path: BString,
+    /// How much was changed.
+    pub counts: Counts,
+}
+
+/// Produce the path to display for a file that was renamed or copied from `old` to `new`, like `dir/{old => new}/file`,
+/// factoring out common leading and trailing directories just like `git` does.
+pub fn rename_display_path(old: &BStr, new: &BStr) -> BString {
+    let (a, b) = (old.as_bytes(), new.as_bytes());
+    let mut prefix_len = 0;
+    for (idx, (lhs, rhs)) in a.iter().zip(b.iter()).enumerate() {
+        if lhs != rhs {
+            break;
+        }
+        if *lhs == b'/' {
+            prefix_len = idx + 1;
+        }
+    }
+
+    // The suffix starts at a slash, and may reach one into the common prefix to see its slash.
+    let mut suffix_len = 0;
+    let min_pos = prefix_len.saturating_sub(usize::from(prefix_len > 0));
+    let (mut ia, mut ib) = (a.len(), b.len());
+    loop {
+        // Positions at the end of the strings compare equal, just like the null terminator of C strings.
+        let lhs = a.get(ia).copied();
+        let rhs = b.get(ib).copied();
+        if lhs != rhs {
+            break;
+        }
+        if lhs == Some(b'/') {
+            suffix_len = a.len() - ia;
+        }
+        if ia == min_pos || ib == min_pos {
+            break;
+        }
+        ia -= 1;
+        ib -= 1;
+    }
+
+    let a_mid = a.len().saturating_sub(prefix_len + suffix_len);
+    let b_mid = b.len().saturating_sub(prefix_len + suffix_len);
+    let mut out = BString::default();
+    let has_common_parts = prefix_len + suffix_len > 0;
+    if has_common_parts {
+        out.extend_from_slice(&a[..prefix_len]);
+        out.push(b'{');
+    }
+    out.extend_from_slice(&a[prefix_len..][..a_mid]);
+    out.extend_from_slice(b" => ");
+    out.extend_from_slice(&b[prefix_len..][..b_mid]);
+    if has_common_parts {
+        out.push(b'}');
+        out.extend_from_slice(&a[a.len() - suffix_len..]);
+    }
+    out
+}
+
+/// Write one line per file in `files` to `out` like `git diff --numstat` does.
+pub fn write_numstat(out: &mut dyn Write, files: &[FileStat]) -> std::io::Result<()> {
+    for file in files {
+        match file.counts {
+            Counts::Text { insertions, removals } => write!(out, "{insertions}\t{removals}\t")?,
+            Counts::Binary { .. } => out.write_all(b"-\t-\t")?,
+        }
+        out.write_all(&file.path)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Write the summary line of changes in `files` to `out` like `git diff --shortstat` does, or nothing if `files` is empty.
+pub fn write_shortstat(out: &mut dyn Write, files: &[FileStat]) -> std::io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let (insertions, removals) = files.iter().fold((0u64, 0u64), |(ins, rem), file| match file.counts {
+        Counts::Text { insertions, removals } => (ins + u64::from(insertions), rem + u64::from(removals)),
+        Counts::Binary { .. } => (ins, rem),
+    });
+    let plural = |n: u64| if n == 1 { "" } else { "s" };
+    write!(out, " {} file{} changed", files.len(), plural(files.len() as u64))?;
+    if insertions != 0 || removals == 0 {
+        write!(out, ", {insertions} insertion{}(+)", plural(insertions))?;
+    }
+    if removals != 0 || insertions == 0 {
+        write!(out, ", {removals} deletion{}(-)", plural(removals))?;
+    }
+    out.write_all(b"\n")
+}
+
+/// Write a line with a graph of the changes of each file in `files` to `out`, followed by a summary line,
+/// like `git diff --stat` does, fitting everything into `width` columns.
+///
+/// Note that `git` uses a `width` of `80` if the output isn't a terminal.
+pub fn write_stat(out: &mut dyn Write, files: &[FileStat], width: usize) -> std::io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    const BIN: &str = "Bin";
+    let mut max_change = 0;
+    let mut max_len = 0;
+    let mut number_width = 0;
+    // Binary files show 'Bin XXX -> YYY bytes' in place of the count and the graph.
+    let mut bin_width = 0;
+    for file in files {
+        max_len = max_len.max(file.path.chars().count());
+        match file.counts {
+            Counts::Text { insertions, removals } => {
+                max_change = max_change.max(insertions as usize + removals as usize);
+            }
+            Counts::Binary { old_size, new_size } => {
+                bin_width = bin_width.max(14 + decimal_width(old_size as usize) + decimal_width(new_size as usize));
+                number_width = BIN.len();
+            }
+        }
+    }
+    number_width = number_width.max(decimal_width(max_change));
+    // Guarantee at least 6 columns for the graph and 10 for the name.
+    let width = width.max(16 + 6 + number_width);
+
+    let mut graph_width = if max_change + 4 > bin_width {
+        max_change
+    } else {
+        bin_width - 4
+    };
+    let mut name_width = max_len;
+    // Leave 6 columns for the ' | ' separator and the spaces around the number.
+    if name_width + number_width + 6 + graph_width > width {
+        let max_graph_width = (width * 3 / 8).saturating_sub(number_width + 6);
+        if graph_width > max_graph_width {
+            graph_width = max_graph_width.max(6);
+        }
+        let remaining = width.saturating_sub(number_width + 6 + graph_width);
+        if name_width > remaining {
+            name_width = remaining;
+        } else {
+            graph_width = width - number_width - 6 - name_width;
+        }
+    }
+
+    for file in files {
+        let (prefix, name) = truncate_name(file.path.as_ref(), name_width);
+        let padding = name_width.saturating_sub(prefix.len() + name.chars().count());
+        write!(out, " {prefix}")?;
+        out.write_all(name)?;
+        write!(out, "{:padding$} |", "")?;
+        match file.counts {
+            Counts::Binary { old_size, new_size } => {
+                write!(out, " {BIN:>number_width$}")?;
+                if old_size != 0 || new_size != 0 {
+                    write!(out, " {old_size} -> {new_size} bytes")?;
+                }
+                out.write_all(b"\n")?;
+            }
+            Counts::Text { insertions, removals } => {
+                let (mut added, mut deleted) = (insertions as usize, removals as usize);
+                let total = added + deleted;
+                write!(out, " {total:>number_width$}{}", if total != 0 { " " } else { "" })?;
+                if graph_width <= max_change {
+                    let mut scaled_total = scale_linear(total, graph_width, max_change);
+                    if scaled_total < 2 && added != 0 && deleted != 0 {
+                        scaled_total = 2;
+                    }
+                    if added < deleted {
+                        added = scale_linear(added, graph_width, max_change);
+                        deleted = scaled_total - added;
+                    } else {
+                        deleted = scale_linear(deleted, graph_width, max_change);
+                        added = scaled_total - deleted;
+                    }
+                }
+                writeln!(out, "{}{}", "+".repeat(added), "-".repeat(deleted))?;
+            }
+        }
+    }
+    write_shortstat(out, files)
+}
+
+/// Truncate `name` from the front to fit into `width`, and return the prefix to show along with the remaining name.
+fn truncate_name(name: &BStr, width: usize) -> (&'static str, &[u8]) {
+    let len = name.chars().count();
+    if len <= width {
+        return ("", name.as_bytes());
+    }
+    let keep = width.saturating_sub(3);
+    let mut start = name
+        .char_indices()
+        .nth(len - keep)
+        .map_or(name.len(), |(start, _, _)| start);
+    if let Some(slash) = name[start..].find_byte(b'/') {
+        start += slash;
+    }
+    ("...", &name[start..])
+}
+
+fn decimal_width(mut n: usize) -> usize {
+    let mut width = 1;
+    while n >= 10 {
+        n /= 10;
+        width += 1;
+    }
+    width
+}
+
+/// Scale `n` which is at most `max_change` to fit into `width`, ensuring that non-zero values remain visible.
+fn scale_linear(n: usize, width: usize, max_change: usize) -> usize {
+    if n == 0 || max_change == 0 {
+        return 0;
+    }
+    1 + (n * (width.saturating_sub(1)) / max_change)
+}
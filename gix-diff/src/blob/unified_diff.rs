@@ -0,0 +1,245 @@
+//! Facilities to produce the unified diff format, as produced by `git diff` or `diff -u`.
+use std::{hash::Hash, io::Write, ops::Range};
+
+use imara_diff::{
+    intern::{InternedInput, Interner, Token},
+    Sink,
+};
+
+/// Defines the size of the context printed before and after each change.
+///
+/// Similar to the `-U` option of `git diff` or `diff`.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct ContextSize {
+    /// Defines the amount of unchanged lines to print before and after each change.
+    pub symmetrical: u32,
+}
+
+impl Default for ContextSize {
+    fn default() -> Self {
+        ContextSize::symmetrical(3)
+    }
+}
+
+/// Instantiation
+impl ContextSize {
+    /// Create a symmetrical context with `n` lines before and after a changed hunk.
+    pub fn symmetrical(n: u32) -> Self {
+        ContextSize { symmetrical: n }
+    }
+}
+
+/// The maximum length in bytes of the function name shown in hunk headers, just like in `git`.
+const MAX_FUNCTION_NAME_LEN: usize = 80;
+
+/// A [`Sink`] that writes hunks in the unified diff format to a [`std::io::Write`] implementation, just like `git diff` does
+/// after writing the file header.
+///
+/// Lines are expected to be interned including their line terminator, as done by
+/// [`Outcome::interned_input()`](crate::blob::platform::prepare_diff::Outcome::interned_input()).
+/// Lines without terminator, i.e. the last line of a file without trailing newline, are marked with
+/// `\ No newline at end of file` like `git` does.
+///
+/// Note that the output is only guaranteed to match the one of `git` if the same diff algorithm is used and if
+/// no `diff.<driver>.xfuncname` is configured, as function names in hunk headers are found with `git`s default rules.
+pub struct UnifiedDiff<'a, T, W>
+where
+    T: Hash + Eq + AsRef<[u8]>,
+    W: Write,
+{
+    before: &'a [Token],
+    after: &'a [Token],
+    interner: &'a Interner<T>,
+
+    context_size: ContextSize,
+    inter_hunk_context: u32,
+    function_names: bool,
+
+    /// The position in `before` up to which all lines were handled.
+    pos: u32,
+    before_hunk_start: u32,
+    after_hunk_start: u32,
+    before_hunk_len: u32,
+    after_hunk_len: u32,
+    /// The function name found for the previous hunk, if there was one.
+    function_name: Option<Vec<u8>>,
+    /// The line in `before` up to which we searched for function names.
+    function_name_search_pos: u32,
+
+    buffer: Vec<u8>,
+    out: W,
+    err: Option<std::io::Error>,
+}
+
+/// Lifecycle
+impl<'a, T, W> UnifiedDiff<'a, T, W>
+where
+    T: Hash + Eq + AsRef<[u8]>,
+    W: Write,
+{
+    /// Create a new instance to write hunks of the diff of `input` to `out`, with `context_size` lines of context
+    /// around each change.
+    pub fn new(input: &'a InternedInput<T>, out: W, context_size: ContextSize) -> Self {
+        UnifiedDiff {
+            before: &input.before,
+            after: &input.after,
+            interner: &input.interner,
+            context_size,
+            inter_hunk_context: 0,
+            function_names: true,
+            pos: 0,
+            before_hunk_start: 0,
+            after_hunk_start: 0,
+            before_hunk_len: 0,
+            after_hunk_len: 0,
+            function_name: None,
+            function_name_search_pos: 0,
+            buffer: Vec::new(),
+            out,
+            err: None,
+        }
+    }
+
+    /// Show up to `lines` additional unchanged lines between hunks to merge them, similar to `--inter-hunk-context` of `git diff`.
+    pub fn inter_hunk_context(mut self, lines: u32) -> Self {
+        self.inter_hunk_context = lines;
+        self
+    }
+
+    /// If `enabled`, which is the default, show the closest line that looks like a function name before each hunk in its header,
+    /// using the default rules of `git`, that is the line starts with a letter, `_` or `$`.
+    pub fn function_names(mut self, enabled: bool) -> Self {
+        self.function_names = enabled;
+        self
+    }
+}
+
+impl<'a, T, W> UnifiedDiff<'a, T, W>
+where
+    T: Hash + Eq + AsRef<[u8]>,
+    W: Write,
+{
+    fn has_hunk(&self) -> bool {
+        self.before_hunk_len != 0 || self.after_hunk_len != 0
+    }
+
+    fn print_tokens(&mut self, tokens_range: Range<u32>, is_before: bool, prefix: u8) {
+        let tokens = if is_before { self.before } else { self.after };
+        let tokens = &tokens[tokens_range.start as usize..tokens_range.end as usize];
+        for &token in tokens {
+            let line = self.interner[token].as_ref();
+            self.buffer.push(prefix);
+            self.buffer.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                self.buffer.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    /// Print unchanged lines from our position up to `end` in `before`.
+    fn print_context(&mut self, end: u32) {
+        let len = end - self.pos;
+        self.print_tokens(self.pos..end, true, b' ');
+        self.before_hunk_len += len;
+        self.after_hunk_len += len;
+        self.pos = end;
+    }
+
+    /// Find the closest line before the start of the current hunk which looks like a function name.
+    fn update_function_name(&mut self) {
+        let search_end = self.before_hunk_start;
+        for line_idx in (self.function_name_search_pos..search_end).rev() {
+            let line = self.interner[self.before[line_idx as usize]].as_ref();
+            if matches!(line.first(), Some(b) if b.is_ascii_alphabetic() || *b == b'_' || *b == b'$') {
+                let mut name = &line[..line.len().min(MAX_FUNCTION_NAME_LEN)];
+                while let Some((last, rest)) = name.split_last() {
+                    if !last.is_ascii_whitespace() {
+                        break;
+                    }
+                    name = rest;
+                }
+                self.function_name = Some(name.to_owned());
+                break;
+            }
+        }
+        self.function_name_search_pos = self.function_name_search_pos.max(search_end);
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.has_hunk() {
+            return Ok(());
+        }
+        let end = (self.pos + self.context_size.symmetrical).min(self.before.len() as u32);
+        self.print_context(end);
+
+        self.out.write_all(b"@@ -")?;
+        write_range(&mut self.out, self.before_hunk_start, self.before_hunk_len)?;
+        self.out.write_all(b" +")?;
+        write_range(&mut self.out, self.after_hunk_start, self.after_hunk_len)?;
+        self.out.write_all(b" @@")?;
+        if self.function_names {
+            self.update_function_name();
+            if let Some(name) = self.function_name.as_ref().filter(|name| !name.is_empty()) {
+                self.out.write_all(b" ")?;
+                self.out.write_all(name)?;
+            }
+        }
+        self.out.write_all(b"\n")?;
+        self.out.write_all(&self.buffer)?;
+
+        self.buffer.clear();
+        self.before_hunk_len = 0;
+        self.after_hunk_len = 0;
+        Ok(())
+    }
+}
+
+/// Write a range of lines like `git` does, with `start` being 0-based.
+fn write_range(out: &mut dyn Write, start: u32, len: u32) -> std::io::Result<()> {
+    match len {
+        0 => write!(out, "{start},0"),
+        1 => write!(out, "{}", start + 1),
+        _ => write!(out, "{},{len}", start + 1),
+    }
+}
+
+impl<T, W> Sink for UnifiedDiff<'_, T, W>
+where
+    T: Hash + Eq + AsRef<[u8]>,
+    W: Write,
+{
+    type Out = std::io::Result<W>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        if self.err.is_some() {
+            return;
+        }
+        let context_size = self.context_size.symmetrical;
+        if self.has_hunk() && before.start - self.pos > 2 * context_size + self.inter_hunk_context {
+            if let Err(err) = self.flush() {
+                self.err = Some(err);
+                return;
+            }
+        }
+        if !self.has_hunk() {
+            let start = before.start.saturating_sub(context_size).max(self.pos);
+            self.before_hunk_start = start;
+            self.after_hunk_start = after.start - (before.start - start);
+            self.pos = start;
+        }
+        self.print_context(before.start);
+        self.print_tokens(before.clone(), true, b'-');
+        self.print_tokens(after.clone(), false, b'+');
+        self.before_hunk_len += before.end - before.start;
+        self.after_hunk_len += after.end - after.start;
+        self.pos = before.end;
+    }
+
+    fn finish(mut self) -> Self::Out {
+        if let Some(err) = self.err.take() {
+            return Err(err);
+        }
+        self.flush()?;
+        Ok(self.out)
+    }
+}
@@ -1,2 +1,5 @@
+mod patience;
 pub(crate) mod pipeline;
 mod platform;
+mod render;
+mod unified_diff;
@@ -0,0 +1,80 @@
+use gix_diff::blob::{
+    intern::InternedInput, patience, sources::byte_lines_with_terminator, Algorithm, ContextSize, UnifiedDiff,
+};
+
+fn input(name: &str) -> crate::Result<(Vec<u8>, Vec<u8>)> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?.join("pairs");
+    Ok((
+        std::fs::read(dir.join(format!("{name}.old")))?,
+        std::fs::read(dir.join(format!("{name}.new")))?,
+    ))
+}
+
+fn hunks(name: &str, anchors: Option<&[&[u8]]>, algorithm: Algorithm) -> crate::Result<String> {
+    let (old, new) = input(name)?;
+    let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+    let sink = UnifiedDiff::new(&input, Vec::new(), ContextSize::default());
+    let out = match anchors {
+        Some(anchors) => patience::anchored(&input, anchors, sink)?,
+        None => gix_diff::blob::diff(algorithm, &input, sink)?,
+    };
+    Ok(String::from_utf8(out)?)
+}
+
+fn baseline(name: &str) -> crate::Result<String> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?;
+    Ok(std::fs::read_to_string(dir.join(format!("{name}.baseline")))?)
+}
+
+#[test]
+fn matches_unique_lines() -> crate::Result {
+    let actual = hunks("patience", None, Algorithm::Patience)?;
+    assert_eq!(actual, baseline("patience")?);
+    assert_ne!(
+        actual,
+        baseline("patience-myers")?,
+        "the algorithms are expected to produce different results here"
+    );
+    assert_eq!(hunks("patience", None, Algorithm::Myers)?, baseline("patience-myers")?);
+    Ok(())
+}
+
+#[test]
+fn falls_back_to_myers_without_unique_lines() -> crate::Result {
+    assert_eq!(
+        hunks("patience-without-unique-lines", None, Algorithm::Patience)?,
+        baseline("patience-without-unique-lines")?
+    );
+    Ok(())
+}
+
+#[test]
+fn anchored() -> crate::Result {
+    assert_eq!(
+        hunks("anchored", None, Algorithm::Patience)?,
+        baseline("anchored-patience")?
+    );
+    assert_eq!(
+        hunks("anchored", Some(&[b"a"]), Algorithm::Patience)?,
+        baseline("anchored")?
+    );
+    assert_eq!(
+        hunks("anchored", Some(&[b"a", b"f"]), Algorithm::Patience)?,
+        baseline("anchored-two")?,
+        "anchors that come later in the old file can't override earlier ones"
+    );
+    assert_eq!(
+        hunks("anchored", Some(&[]), Algorithm::Patience)?,
+        baseline("anchored-patience")?,
+        "without anchors, it's just like patience"
+    );
+    Ok(())
+}
+
+#[test]
+fn empty_sides() -> crate::Result {
+    for name in ["from-empty", "to-empty"] {
+        assert_eq!(hunks(name, None, Algorithm::Patience)?, baseline(name)?, "{name}");
+    }
+    Ok(())
+}
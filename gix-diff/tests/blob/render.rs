@@ -0,0 +1,237 @@
+use gix_diff::blob::{
+    header::{Header, Location, PrefixStyle, Prefixes, Rewrite, RewriteKind, Side},
+    intern::InternedInput,
+    sink::Counter,
+    sources::byte_lines_with_terminator,
+    stat::{self, Counts, FileStat},
+    Algorithm, ContextSize, UnifiedDiff,
+};
+use gix_object::{
+    bstr::{BStr, BString, ByteSlice},
+    tree::{EntryKind, EntryMode},
+    FindExt,
+};
+
+/// A change as parsed from the output of `git diff --raw --no-abbrev`.
+struct Change {
+    old: Option<(EntryKind, gix_hash::ObjectId, BString)>,
+    new: Option<(EntryKind, gix_hash::ObjectId, BString)>,
+    rewrite: Option<Rewrite>,
+}
+
+impl Change {
+    fn header(&self) -> Header<'_> {
+        fn side((mode, id, path): &(EntryKind, gix_hash::ObjectId, BString)) -> Side<'_> {
+            Side {
+                path: path.as_ref(),
+                id: id.as_ref(),
+                mode: *mode,
+            }
+        }
+        Header {
+            old: self.old.as_ref().map(side),
+            new: self.new.as_ref().map(side),
+            rewrite: self.rewrite,
+        }
+    }
+}
+
+fn changes(dir: &std::path::Path) -> crate::Result<Vec<Change>> {
+    let raw = std::fs::read(dir.join("changes.raw"))?;
+    let mut out = Vec::new();
+    for line in raw.lines() {
+        let (info, paths) = line.split_once_str("\t").expect("tab-separated");
+        let mut tokens = info[1..].split_str(" ");
+        let mut next = || tokens.next().expect("all tokens present");
+        let old_mode = EntryMode(u16::from_str_radix(next().to_str()?, 8)?);
+        let new_mode = EntryMode(u16::from_str_radix(next().to_str()?, 8)?);
+        let old_id = gix_hash::ObjectId::from_hex(next())?;
+        let new_id = gix_hash::ObjectId::from_hex(next())?;
+        let status = next();
+        let mut paths = paths.split_str("\t");
+        let old_path: BString = paths.next().expect("at least one path").into();
+        let new_path: BString = paths.next().map_or_else(|| old_path.clone(), Into::into);
+        let rewrite = match status[0] {
+            b'R' | b'C' => Some(Rewrite {
+                kind: if status[0] == b'R' {
+                    RewriteKind::Rename
+                } else {
+                    RewriteKind::Copy
+                },
+                similarity: status[1..].to_str()?.parse()?,
+            }),
+            _ => None,
+        };
+        out.push(Change {
+            old: (status[0] != b'A').then(|| (old_mode.into(), old_id, old_path)),
+            new: (status[0] != b'D').then(|| (new_mode.into(), new_id, new_path)),
+            rewrite,
+        });
+    }
+    Ok(out)
+}
+
+fn blob(odb: &gix_odb::Handle, side: Option<&(EntryKind, gix_hash::ObjectId, BString)>) -> crate::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    Ok(match side {
+        Some((_, id, _)) => odb.find_blob(id, &mut buf)?.data.to_owned(),
+        None => Vec::new(),
+    })
+}
+
+fn patch(prefixes: &Prefixes, context_size: u32) -> crate::Result<String> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?;
+    let odb = gix_odb::at(dir.join(".git/objects"))?;
+    let mut out = Vec::new();
+    for change in changes(&dir)? {
+        let header = change.header();
+        header.write_to(&mut out, prefixes, 7)?;
+        let (old, new) = (blob(&odb, change.old.as_ref())?, blob(&odb, change.new.as_ref())?);
+        if old == new {
+            continue;
+        }
+        if old.contains(&0) || new.contains(&0) {
+            header.write_binary_files_differ(&mut out, prefixes)?;
+            continue;
+        }
+        header.write_file_names(&mut out, prefixes)?;
+        let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+        out = gix_diff::blob::diff(
+            Algorithm::Myers,
+            &input,
+            UnifiedDiff::new(&input, out, ContextSize::symmetrical(context_size)),
+        )?;
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+fn file_stats() -> crate::Result<Vec<FileStat>> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?;
+    let odb = gix_odb::at(dir.join(".git/objects"))?;
+    let mut out = Vec::new();
+    for change in changes(&dir)? {
+        let (old, new) = (blob(&odb, change.old.as_ref())?, blob(&odb, change.new.as_ref())?);
+        let counts = if old.contains(&0) || new.contains(&0) {
+            Counts::Binary {
+                old_size: old.len() as u64,
+                new_size: new.len() as u64,
+            }
+        } else {
+            let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+            let counter = gix_diff::blob::diff(Algorithm::Myers, &input, Counter::new(()));
+            Counts::Text {
+                insertions: counter.insertions,
+                removals: counter.removals,
+            }
+        };
+        let path = match (&change.old, &change.new) {
+            (Some((_, _, old)), Some((_, _, new))) if old != new => {
+                stat::rename_display_path(old.as_ref(), new.as_ref())
+            }
+            (Some((_, _, path)), _) | (_, Some((_, _, path))) => path.clone(),
+            (None, None) => unreachable!("one side is always set"),
+        };
+        out.push(FileStat { path, counts });
+    }
+    Ok(out)
+}
+
+fn baseline(name: &str) -> crate::Result<String> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?;
+    Ok(std::fs::read_to_string(dir.join(name))?)
+}
+
+#[test]
+fn full_patch_with_extended_headers() -> crate::Result {
+    pretty_assertions::assert_eq!(patch(&Prefixes::default(), 3)?, baseline("patch.baseline")?);
+    pretty_assertions::assert_eq!(patch(&Prefixes::none(), 3)?, baseline("patch-no-prefix.baseline")?);
+    pretty_assertions::assert_eq!(patch(&Prefixes::default(), 1)?, baseline("patch-U1.baseline")?);
+    Ok(())
+}
+
+#[test]
+fn prefix_styles() {
+    assert_eq!(
+        PrefixStyle::Default.to_prefixes(Location::Commit, Location::Worktree),
+        Prefixes::default()
+    );
+    assert_eq!(
+        PrefixStyle::None.to_prefixes(Location::Commit, Location::Worktree),
+        Prefixes::none()
+    );
+    let mnemonic = PrefixStyle::Mnemonic.to_prefixes(Location::Index, Location::Worktree);
+    assert_eq!(
+        (mnemonic.old.as_bstr(), mnemonic.new.as_bstr()),
+        ("i/".into(), "w/".into())
+    );
+    let mnemonic = PrefixStyle::Mnemonic.to_prefixes(Location::Commit, Location::Object);
+    assert_eq!(
+        (mnemonic.old.as_bstr(), mnemonic.new.as_bstr()),
+        ("c/".into(), "o/".into())
+    );
+}
+
+#[test]
+fn quoted_paths() -> crate::Result {
+    let id = gix_hash::Kind::Sha1.null();
+    let header = Header {
+        old: Some(Side {
+            path: "tab\there".into(),
+            id: &id,
+            mode: EntryKind::Blob,
+        }),
+        new: Some(Side {
+            path: "\"quoted\" \u{e4}".into(),
+            id: &id,
+            mode: EntryKind::Blob,
+        }),
+        rewrite: Some(Rewrite {
+            kind: RewriteKind::Copy,
+            similarity: 100,
+        }),
+    };
+    let mut out = Vec::new();
+    header.write_to(&mut out, &Prefixes::default(), 7)?;
+    assert_eq!(
+        out.as_bstr(),
+        "diff --git \"a/tab\\there\" \"b/\\\"quoted\\\" \\303\\244\"\nsimilarity index 100%\ncopy from \"tab\\there\"\ncopy to \"\\\"quoted\\\" \\303\\244\"\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn stat_numstat_and_shortstat() -> crate::Result {
+    let files = file_stats()?;
+    for (name, width) in [("stat.baseline", 80), ("stat-50.baseline", 50)] {
+        let mut out = Vec::new();
+        stat::write_stat(&mut out, &files, width)?;
+        pretty_assertions::assert_eq!(out.as_bstr(), baseline(name)?.as_str(), "{name}");
+    }
+
+    let mut out = Vec::new();
+    stat::write_numstat(&mut out, &files)?;
+    pretty_assertions::assert_eq!(out.as_bstr(), baseline("numstat.baseline")?.as_str());
+
+    let mut out = Vec::new();
+    stat::write_shortstat(&mut out, &files)?;
+    pretty_assertions::assert_eq!(out.as_bstr(), baseline("shortstat.baseline")?.as_str());
+    Ok(())
+}
+
+#[test]
+fn rename_display_path() {
+    for (old, new, expected) in [
+        ("a/b/c", "a/d/c", "a/{b => d}/c"),
+        ("a/b/c", "a/b/d", "a/b/{c => d}"),
+        ("a", "b", "a => b"),
+        ("dir/file", "file", "dir/file => file"),
+        ("file", "dir/file", "file => dir/file"),
+        ("a/file", "a/b/file", "a/{ => b}/file"),
+    ] {
+        assert_eq!(
+            stat::rename_display_path(BStr::new(old), BStr::new(new)),
+            expected,
+            "{old} -> {new}"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use gix_diff::blob::{intern::InternedInput, sources::byte_lines_with_terminator, Algorithm, ContextSize, UnifiedDiff};
+
+fn hunks(name: &str, context_size: u32, inter_hunk_context: u32, function_names: bool) -> crate::Result<String> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?.join("pairs");
+    let old = std::fs::read(dir.join(format!("{name}.old")))?;
+    let new = std::fs::read(dir.join(format!("{name}.new")))?;
+    let input = InternedInput::new(byte_lines_with_terminator(&old), byte_lines_with_terminator(&new));
+    let out = gix_diff::blob::diff(
+        Algorithm::Myers,
+        &input,
+        UnifiedDiff::new(&input, Vec::new(), ContextSize::symmetrical(context_size))
+            .inter_hunk_context(inter_hunk_context)
+            .function_names(function_names),
+    )?;
+    Ok(String::from_utf8(out)?)
+}
+
+fn baseline(name: &str) -> crate::Result<String> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_diff_render_repo.sh")?;
+    Ok(std::fs::read_to_string(dir.join(format!("{name}.baseline")))?)
+}
+
+#[test]
+fn context_sizes() -> crate::Result {
+    for (context_size, suffix) in [(3, ""), (0, "-U0"), (1, "-U1"), (20, "-U20")] {
+        assert_eq!(
+            hunks("single", context_size, 0, true)?,
+            baseline(&format!("single{suffix}"))?,
+            "-U{context_size}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn hunks_are_merged_if_their_context_overlaps() -> crate::Result {
+    assert_eq!(hunks("far-apart", 3, 0, true)?, baseline("far-apart")?);
+    assert_eq!(
+        hunks("far-apart", 7, 0, true)?,
+        baseline("far-apart-U7")?,
+        "the hunks are 20 lines apart, which is still not enough"
+    );
+    assert_eq!(hunks("far-apart", 10, 0, true)?, baseline("far-apart-U10")?);
+    Ok(())
+}
+
+#[test]
+fn inter_hunk_context() -> crate::Result {
+    assert_eq!(
+        hunks("far-apart", 7, 5, true)?,
+        baseline("far-apart-inter-hunk-context")?,
+        "the gap of 19 lines is filled with 2 * 7 lines of context and 5 lines of inter-hunk context"
+    );
+    assert_eq!(
+        hunks("far-apart", 7, 4, true)?,
+        baseline("far-apart-inter-hunk-context-too-small")?
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_newline_at_end_of_file() -> crate::Result {
+    for name in ["no-newline-at-end", "newline-added-at-end"] {
+        assert_eq!(hunks(name, 3, 0, true)?, baseline(name)?, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn empty_files_and_insertions() -> crate::Result {
+    for name in ["from-empty", "to-empty", "insertion-only"] {
+        assert_eq!(hunks(name, 3, 0, true)?, baseline(name)?, "{name}");
+    }
+    assert_eq!(hunks("insertion-only", 0, 0, true)?, baseline("insertion-only-U0")?);
+    Ok(())
+}
+
+#[test]
+fn function_names_in_hunk_headers() -> crate::Result {
+    assert_eq!(hunks("function-names", 3, 0, true)?, baseline("function-names")?);
+    assert_eq!(hunks("function-names", 0, 0, true)?, baseline("function-names-U0")?);
+
+    let actual = hunks("function-names", 3, 0, false)?;
+    assert_eq!(
+        actual.lines().filter(|line| line.starts_with("@@")).collect::<Vec<_>>(),
+        ["@@ -2,7 +2,7 @@", "@@ -10,7 +10,7 @@", "@@ -19,4 +19,4 @@"],
+        "function names can be turned off"
+    );
+    Ok(())
+}
@@ -0,0 +1,150 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+# Pairs of files to be compared with `git diff --no-index`, whose hunks are kept as baseline.
+mkdir pairs
+function pair() {
+  local name=${1:?first argument is the name of the pair}
+  printf "$2" > "pairs/$name.old"
+  printf "$3" > "pairs/$name.new"
+}
+
+# Write the hunks produced by `git diff --no-index` for the pair `$1` with the remaining arguments into `$1[-$suffix].baseline`.
+function hunks() {
+  local name=${1:?first argument is the name of the pair}
+  local suffix=${2?second argument is the suffix of the baseline file}
+  shift 2
+  git diff --no-index "$@" "pairs/$name.old" "pairs/$name.new" | sed -n '/^@@/,$p' > "$name$suffix.baseline" || true
+}
+
+pair single "$(seq 1 20)\n" "$(seq 1 20 | sed 's/^10$/10-changed/')\n"
+hunks single ""
+hunks single "-U0" -U0
+hunks single "-U1" -U1
+hunks single "-U20" -U20
+
+pair far-apart "$(seq 1 30)\n" "$(seq 1 30 | sed -e 's/^5$/5-changed/' -e 's/^25$/25-changed/')\n"
+hunks far-apart ""
+hunks far-apart "-U7" -U7
+hunks far-apart "-U10" -U10
+hunks far-apart "-inter-hunk-context" -U7 --inter-hunk-context=5
+hunks far-apart "-inter-hunk-context-too-small" -U7 --inter-hunk-context=4
+
+pair no-newline-at-end 'a\nb\nc' 'a\nb\nd'
+hunks no-newline-at-end ""
+pair newline-added-at-end 'a\nb' 'a\nb\n'
+hunks newline-added-at-end ""
+pair from-empty '' 'a\nb\n'
+hunks from-empty ""
+pair to-empty 'a\nb\n' ''
+hunks to-empty ""
+pair insertion-only 'a\nb\nc\n' 'a\nb\nnew\nc\n'
+hunks insertion-only ""
+hunks insertion-only "-U0" -U0
+
+pair function-names 'fn first() {\n    1\n    2\n    3\n    4\n    5\n}\n\nfn second() {\n    1\n    2\n    3\n    4\n    5\n}\n_private\n    1\n    2\n    3\n    4\n    5\n    6\n' \
+                    'fn first() {\n    1\n    2\n    3\n    4-changed\n    5\n}\n\nfn second() {\n    1\n    2\n    3\n    4-changed\n    5\n}\n_private\n    1\n    2\n    3\n    4\n    5\n    6-changed\n'
+hunks function-names ""
+hunks function-names "-U0" -U0
+
+cat > pairs/patience.old <<'EOF'
+#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+EOF
+cat > pairs/patience.new <<'EOF'
+#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+EOF
+hunks patience "-myers" --diff-algorithm=myers
+hunks patience "" --patience
+
+pair patience-without-unique-lines 'a\na\nb\nb\na\nb\n' 'b\na\na\nb\nb\na\n'
+hunks patience-without-unique-lines "" --patience
+
+pair anchored 'a\nb\nc\nd\ne\nf\ng\n' 'c\nd\ne\nf\ng\na\nb\n'
+hunks anchored "-patience" --patience
+hunks anchored "" --anchored=a
+hunks anchored "-two" --anchored=a --anchored=f
+
+# A history with all kinds of changes to render as patch and summaries.
+seq 1 10 > modified.txt
+seq 1 10 > "with space.txt"
+echo exec > mode-change.sh
+seq 1 10 > mode-and-content.sh
+seq 1 10 > deleted.txt
+mkdir -p dir/sub
+seq 1 20 > dir/sub/old-name.txt
+seq 100 120 > pure-rename.txt
+printf '\0binary\0' > binary.bin
+git add . && git commit -q -m base
+git tag base
+
+sed -i 's/^5$/5-changed/' modified.txt "with space.txt"
+chmod +x mode-change.sh mode-and-content.sh
+echo 11 >> mode-and-content.sh
+git rm -q deleted.txt
+seq 1 5 > added.txt
+git mv dir/sub/old-name.txt dir/sub/new-name.txt
+echo 21 >> dir/sub/new-name.txt
+mkdir moved && git mv pure-rename.txt moved/
+printf '\0binary\0changed\0' > binary.bin
+git add . && git commit -q -m tip
+git tag tip
+
+git diff --raw --no-abbrev -M base tip > changes.raw
+git diff -M base tip > patch.baseline
+git diff -M --no-prefix base tip > patch-no-prefix.baseline
+git diff -M -U1 base tip > patch-U1.baseline
+git diff -M --stat base tip > stat.baseline
+git diff -M --stat=50 base tip > stat-50.baseline
+git diff -M --numstat base tip > numstat.baseline
+git diff -M --shortstat base tip > shortstat.baseline
@@ -3,7 +3,9 @@ use std::path::{Path, PathBuf};
 
 use bstr::{BStr, BString, ByteSlice};
 
-use crate::walk::{classify, readdir, Action, Context, Delegate, Error, ForDeletionMode, Options, Outcome};
+use crate::walk::{
+    classify, readdir, untracked_cache, Action, Context, Delegate, Error, ForDeletionMode, Options, Outcome,
+};
 use crate::{entry, EntryRef};
 
 /// A function to perform a git-style, unsorted, directory walk.
@@ -101,12 +103,18 @@ pub fn walk(
     }
 
     let mut state = readdir::State::new(worktree_root, ctx.current_dir, options.for_deletion.is_some());
+    state.untracked_cache = untracked_cache::Update::new(worktree_root, &root, options, &mut ctx);
     let may_collapse = root != worktree_root && state.may_collapse(&current);
+    let cached_entries = state
+        .untracked_cache
+        .as_mut()
+        .and_then(|cache| cache.enter_directory(&current, buf.as_bstr(), &root_info, &ctx));
     let (action, _) = readdir::recursive(
         may_collapse,
         &mut current,
         &mut buf,
         root_info,
+        cached_entries,
         &mut ctx,
         options,
         delegate,
@@ -116,6 +124,10 @@ pub fn walk(
     if action != Action::Cancel {
         state.emit_remaining(may_collapse, options, &mut out, delegate);
         assert_eq!(state.on_hold.len(), 0, "BUG: after emission, on hold must be empty");
+        if let Some(mut cache) = state.untracked_cache.take() {
+            cache.leave_directory();
+            cache.apply(&mut ctx);
+        }
     }
     gix_trace::debug!(statistics = ?out);
     Ok((out, root.to_owned()))
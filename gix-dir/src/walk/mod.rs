@@ -257,6 +257,27 @@ pub struct Context<'a> {
     ///
     /// If the `traversal_root` is not in the `worktree_root` passed to [walk()](crate::walk()).
     pub explicit_traversal_root: Option<&'a std::path::Path>,
+    /// If not `None`, the untracked cache to consult to avoid reading directories that didn't change, and to update
+    /// with the entries of all directories that had to be read.
+    ///
+    /// It's only used if the walk starts at the worktree root, without pathspec patterns, and if only untracked
+    /// entries are emitted while `excludes` are available, as that's what the cache can represent.
+    /// Note that it will be reset if it was created for another worktree or with different [`Options::emit_untracked`].
+    pub untracked_cache: Option<UntrackedCache<'a>>,
+}
+
+/// Information to consult and update the untracked cache, as stored in the `UNTR` extension of an index, during a [`walk()`](function::walk()).
+pub struct UntrackedCache<'a> {
+    /// The cache to consult and update.
+    ///
+    /// It's only updated if the walk completed, so it can be written back to the index.
+    pub cache: &'a mut gix_index::extension::UntrackedCache,
+    /// The path to the `$GIT_DIR/info/exclude` file, whose modification invalidates all cached directories.
+    pub info_exclude: Option<&'a std::path::Path>,
+    /// The path to the `core.excludesFile`, whose modification invalidates all cached directories.
+    pub excludes_file: Option<&'a std::path::Path>,
+    /// Options to control which parts of the stat information of directories are compared to see if they changed.
+    pub stat: gix_index::entry::stat::Options,
 }
 
 /// Additional information collected as outcome of [`walk()`](function::walk()).
@@ -305,3 +326,4 @@ pub enum Error {
 mod classify;
 pub(crate) mod function;
 mod readdir;
+mod untracked_cache;
@@ -1,14 +1,20 @@
 use bstr::{BStr, BString, ByteSlice};
 use std::borrow::Cow;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
 
 use crate::entry::{PathspecMatch, Status};
 use crate::walk::function::{can_recurse, emit_entry};
 use crate::walk::EmissionMode::CollapseDirectory;
-use crate::walk::{classify, Action, CollapsedEntriesEmissionMode, Context, Delegate, Error, Options, Outcome};
+use crate::walk::{
+    classify, untracked_cache, Action, CollapsedEntriesEmissionMode, Context, Delegate, Error, Options, Outcome,
+};
 use crate::{entry, walk, Entry, EntryRef};
 
+/// If `cached_entries` is `Some`, these are the names of the entries to visit along with their kind, if known, as obtained from
+/// the untracked cache, instead of reading them from disk.
+///
 /// ### Deviation
 ///
 /// Git mostly silently ignores IO errors and stops iterating seemingly quietly, while we error loudly.
@@ -18,6 +24,7 @@ pub(super) fn recursive(
     current: &mut PathBuf,
     current_bstr: &mut BString,
     current_info: classify::Outcome,
+    cached_entries: Option<Vec<(BString, Option<entry::Kind>)>>,
     ctx: &mut Context<'_>,
     opts: Options,
     delegate: &mut dyn Delegate,
@@ -27,77 +34,76 @@ pub(super) fn recursive(
     if ctx.should_interrupt.map_or(false, |flag| flag.load(Ordering::Relaxed)) {
         return Err(Error::Interrupted);
     }
-    out.read_dir_calls += 1;
-    let entries = gix_fs::read_dir(current, opts.precompose_unicode).map_err(|err| Error::ReadDir {
-        path: current.to_owned(),
-        source: err,
-    })?;
 
     let mut num_entries = 0;
     let mark = state.mark(may_collapse);
     let mut prevent_collapse = false;
-    for entry in entries {
-        let entry = entry.map_err(|err| Error::DirEntry {
-            parent_directory: current.to_owned(),
-            source: err,
-        })?;
-        // Important to count right away, otherwise the directory could be seen as empty even though it's not.
-        // That is, this should be independent of the kind.
-        num_entries += 1;
-
-        let prev_len = current_bstr.len();
-        if prev_len != 0 {
-            current_bstr.push(b'/');
+    match cached_entries {
+        Some(entries) => {
+            // Tracked entries aren't cached, but make this directory non-empty and prevent it from being collapsed.
+            if current_info.status == Status::Tracked {
+                num_entries += 1;
+                prevent_collapse = true;
+            }
+            for (name, kind) in entries {
+                num_entries += 1;
+                let file_name = gix_path::from_bstr(name);
+                let kind = kind.or_else(|| {
+                    current
+                        .join(&file_name)
+                        .symlink_metadata()
+                        .ok()
+                        .map(|md| md.file_type().into())
+                });
+                let action = visit_entry(
+                    file_name.as_os_str(),
+                    || kind,
+                    current,
+                    current_bstr,
+                    ctx,
+                    opts,
+                    delegate,
+                    out,
+                    state,
+                    &mut prevent_collapse,
+                )?;
+                if action != Action::Continue {
+                    return Ok((action, prevent_collapse));
+                }
+            }
         }
-        let file_name = entry.file_name();
-        current_bstr.extend_from_slice(
-            gix_path::try_os_str_into_bstr(Cow::Borrowed(file_name.as_ref()))
-                .expect("no illformed UTF-8")
-                .as_ref(),
-        );
-        current.push(file_name);
-
-        let info = classify::path(
-            current,
-            current_bstr,
-            if prev_len == 0 { 0 } else { prev_len + 1 },
-            None,
-            || entry.file_type().ok().map(Into::into),
-            opts,
-            ctx,
-        )?;
+        None => {
+            out.read_dir_calls += 1;
+            let entries = gix_fs::read_dir(current, opts.precompose_unicode).map_err(|err| Error::ReadDir {
+                path: current.to_owned(),
+                source: err,
+            })?;
+            for entry in entries {
+                let entry = entry.map_err(|err| Error::DirEntry {
+                    parent_directory: current.to_owned(),
+                    source: err,
+                })?;
+                // Important to count right away, otherwise the directory could be seen as empty even though it's not.
+                // That is, this should be independent of the kind.
+                num_entries += 1;
 
-        if can_recurse(
-            current_bstr.as_bstr(),
-            info,
-            opts.for_deletion,
-            false, /* is root */
-            delegate,
-        ) {
-            let subdir_may_collapse = state.may_collapse(current);
-            let (action, subdir_prevent_collapse) = recursive(
-                subdir_may_collapse,
-                current,
-                current_bstr,
-                info,
-                ctx,
-                opts,
-                delegate,
-                out,
-                state,
-            )?;
-            prevent_collapse |= subdir_prevent_collapse;
-            if action != Action::Continue {
-                return Ok((action, prevent_collapse));
-            }
-        } else if !state.held_for_directory_collapse(current_bstr.as_bstr(), info, &opts) {
-            let action = emit_entry(Cow::Borrowed(current_bstr.as_bstr()), info, None, opts, out, delegate);
-            if action != Action::Continue {
-                return Ok((action, prevent_collapse));
+                let action = visit_entry(
+                    entry.file_name().as_ref(),
+                    || entry.file_type().ok().map(Into::into),
+                    current,
+                    current_bstr,
+                    ctx,
+                    opts,
+                    delegate,
+                    out,
+                    state,
+                    &mut prevent_collapse,
+                )?;
+                if action != Action::Continue {
+                    return Ok((action, prevent_collapse));
+                }
             }
         }
-        current_bstr.truncate(prev_len);
-        current.pop();
     }
 
     let res = mark.reduce_held_entries(
@@ -115,9 +121,95 @@ pub(super) fn recursive(
     Ok((res, prevent_collapse))
 }
 
+/// Classify the entry with `file_name` in the directory at `current`, and recurse into it or emit it.
+#[allow(clippy::too_many_arguments)]
+fn visit_entry(
+    file_name: &OsStr,
+    on_demand_disk_kind: impl FnOnce() -> Option<entry::Kind>,
+    current: &mut PathBuf,
+    current_bstr: &mut BString,
+    ctx: &mut Context<'_>,
+    opts: Options,
+    delegate: &mut dyn Delegate,
+    out: &mut Outcome,
+    state: &mut State,
+    prevent_collapse: &mut bool,
+) -> Result<Action, Error> {
+    let prev_len = current_bstr.len();
+    if prev_len != 0 {
+        current_bstr.push(b'/');
+    }
+    let filename_start_idx = if prev_len == 0 { 0 } else { prev_len + 1 };
+    current_bstr.extend_from_slice(
+        gix_path::try_os_str_into_bstr(Cow::Borrowed(file_name))
+            .expect("no illformed UTF-8")
+            .as_ref(),
+    );
+    current.push(file_name);
+
+    let info = classify::path(
+        current,
+        current_bstr,
+        filename_start_idx,
+        None,
+        on_demand_disk_kind,
+        opts,
+        ctx,
+    )?;
+
+    if can_recurse(
+        current_bstr.as_bstr(),
+        info,
+        opts.for_deletion,
+        false, /* is root */
+        delegate,
+    ) {
+        let cached_entries = state
+            .untracked_cache
+            .as_mut()
+            .and_then(|cache| cache.enter_directory(current, current_bstr.as_bstr(), &info, ctx));
+        let subdir_may_collapse = state.may_collapse(current);
+        let (action, subdir_prevent_collapse) = recursive(
+            subdir_may_collapse,
+            current,
+            current_bstr,
+            info,
+            cached_entries,
+            ctx,
+            opts,
+            delegate,
+            out,
+            state,
+        )?;
+        *prevent_collapse |= subdir_prevent_collapse;
+        if action != Action::Continue {
+            return Ok(action);
+        }
+        if let Some(cache) = state.untracked_cache.as_mut() {
+            let has_untracked = cache.leave_directory();
+            cache.record(current_bstr[filename_start_idx..].as_bstr(), &info, Some(has_untracked));
+        }
+    } else {
+        if let Some(cache) = state.untracked_cache.as_mut() {
+            cache.record(current_bstr[filename_start_idx..].as_bstr(), &info, None);
+        }
+        if !state.held_for_directory_collapse(current_bstr.as_bstr(), info, &opts) {
+            let action = emit_entry(Cow::Borrowed(current_bstr.as_bstr()), info, None, opts, out, delegate);
+            if action != Action::Continue {
+                return Ok(action);
+            }
+        }
+    }
+    current_bstr.truncate(prev_len);
+    current.pop();
+    Ok(Action::Continue)
+}
+
 pub(super) struct State {
     /// The entries to hold back until it's clear what to do with them.
     pub on_hold: Vec<Entry>,
+    /// The untracked cache to consult and to update, if it is usable for this walk.
+    pub untracked_cache: Option<untracked_cache::Update>,
     /// The path the user is currently in, as seen from the workdir root.
     worktree_relative_current_dir: Option<PathBuf>,
 }
@@ -154,6 +246,7 @@ impl State {
         };
         Self {
             on_hold: Vec::new(),
+            untracked_cache: None,
             worktree_relative_current_dir,
         }
     }
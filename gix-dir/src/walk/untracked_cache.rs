@@ -0,0 +1,334 @@
+use std::path::Path;
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_index::extension::untracked_cache::{dir_flags, Directory, OidStat};
+
+use crate::entry;
+use crate::walk::{classify, Context, EmissionMode, Options};
+
+/// The name of the per-directory exclude file, the only one supported by the untracked cache.
+const EXCLUDE_FILENAME_PER_DIR: &str = ".gitignore";
+
+/// The state to consult an untracked cache with, and to produce its updated version while walking the worktree.
+pub(super) struct Update {
+    /// The directories of the updated cache, in the order in which they were entered.
+    directories: Vec<Directory>,
+    /// The directories that are currently entered, from the root to the current one.
+    stack: Vec<Frame>,
+    /// If `true`, untracked directories are listed in their parent directory, instead of only having their own block.
+    show_other_directories: bool,
+    stat_options: gix_index::entry::stat::Options,
+    index_timestamp: filetime::FileTime,
+    object_hash: gix_index::hash::Kind,
+}
+
+struct Frame {
+    /// The index of the directory in the untracked cache we are updating, if it knew this directory.
+    previous: Option<usize>,
+    /// The index into our own list of directories.
+    index: usize,
+    /// If `true`, the exclude file of this directory or of one of its parents changed, which invalidates all cached entries below.
+    excludes_changed: bool,
+    /// If `true`, untracked entries were recorded in this directory or one of its sub-directories.
+    has_untracked: bool,
+}
+
+/// Lifecycle
+impl Update {
+    /// Return a new instance if the untracked cache in `ctx` can be used for a walk from `traversal_root` with `opts`.
+    ///
+    /// The cache is reset if it was created for a different worktree or a different kind of walk, and
+    /// all of its directories are invalidated if the global exclude files changed.
+    pub(super) fn new(
+        worktree_root: &Path,
+        traversal_root: &Path,
+        opts: Options,
+        ctx: &mut Context<'_>,
+    ) -> Option<Self> {
+        let is_usable = traversal_root == worktree_root
+            && ctx.excludes.is_some()
+            && ctx.pathspec.patterns().len() == 0
+            && opts.emit_ignored.is_none()
+            && !opts.emit_tracked
+            && !opts.emit_pruned
+            && !opts.emit_empty_directories
+            && opts.for_deletion.is_none()
+            && !opts.recurse_repositories;
+        let index_timestamp = ctx.index.timestamp();
+        let object_hash = ctx.index.object_hash();
+        let untracked = ctx.untracked_cache.as_mut().filter(|_| is_usable)?;
+        let stat_options = untracked.stat;
+        let cache = &mut *untracked.cache;
+
+        let flags = match opts.emit_untracked {
+            EmissionMode::Matching => 0,
+            EmissionMode::CollapseDirectory => dir_flags::SHOW_OTHER_DIRECTORIES | dir_flags::HIDE_EMPTY_DIRECTORIES,
+        };
+        let identifier = identifier(worktree_root);
+        if cache.identifier.split_str(b"\0").next() != identifier.split_str(b"\0").next()
+            || cache.dir_flags != flags
+            || cache.exclude_filename_per_dir != EXCLUDE_FILENAME_PER_DIR
+        {
+            *cache = gix_index::extension::UntrackedCache {
+                identifier,
+                info_exclude: None,
+                excludes_file: None,
+                exclude_filename_per_dir: EXCLUDE_FILENAME_PER_DIR.into(),
+                dir_flags: flags,
+                directories: Vec::new(),
+            };
+        }
+
+        let info_exclude = global_exclude_file(
+            untracked.info_exclude,
+            cache.info_exclude.as_ref(),
+            object_hash,
+            index_timestamp,
+            stat_options,
+        );
+        let excludes_file = global_exclude_file(
+            untracked.excludes_file,
+            cache.excludes_file.as_ref(),
+            object_hash,
+            index_timestamp,
+            stat_options,
+        );
+        if id(info_exclude.as_ref()) != id(cache.info_exclude.as_ref())
+            || id(excludes_file.as_ref()) != id(cache.excludes_file.as_ref())
+        {
+            cache.directories.clear();
+        }
+        cache.info_exclude = info_exclude;
+        cache.excludes_file = excludes_file;
+
+        Some(Update {
+            directories: Vec::new(),
+            stack: Vec::new(),
+            show_other_directories: flags & dir_flags::SHOW_OTHER_DIRECTORIES != 0,
+            stat_options,
+            index_timestamp,
+            object_hash,
+        })
+    }
+
+    /// Replace the directories of the untracked cache in `ctx` with the ones we recorded,
+    /// which must only be done after the whole worktree was traversed.
+    pub(super) fn apply(self, ctx: &mut Context<'_>) {
+        debug_assert!(self.stack.is_empty(), "BUG: all directories must have been left");
+        if let Some(untracked) = ctx.untracked_cache.as_mut() {
+            untracked.cache.directories = self.directories;
+        }
+    }
+}
+
+/// Traversal
+impl Update {
+    /// Enter the directory at `current` with worktree-relative `rela_path`, classified as `info`, and return its entries
+    /// if the untracked cache knows them and they are still valid.
+    /// Otherwise, the directory has to be read from disk and its entries [must be recorded](Self::record()).
+    ///
+    /// Each call must be followed by a call to [`leave_directory()`](Self::leave_directory()).
+    pub(super) fn enter_directory(
+        &mut self,
+        current: &Path,
+        rela_path: &BStr,
+        info: &classify::Outcome,
+        ctx: &Context<'_>,
+    ) -> Option<Vec<(BString, Option<entry::Kind>)>> {
+        let cache = &*ctx.untracked_cache.as_ref().expect("present if we exist").cache;
+        let name = rela_path
+            .rfind_byte(b'/')
+            .map_or(rela_path, |pos| rela_path[pos + 1..].as_bstr());
+        let previous = match self.stack.last() {
+            None => (!cache.directories.is_empty()).then_some(0),
+            Some(parent) => parent.previous.and_then(|parent| {
+                cache.directories[parent]
+                    .sub_directories
+                    .iter()
+                    .copied()
+                    .find(|idx| cache.directories[*idx].name == name)
+            }),
+        };
+        let previous_dir = previous.map(|idx| &cache.directories[idx]);
+
+        let stat = gix_index::fs::Metadata::from_path_no_follow(current)
+            .ok()
+            .and_then(|md| gix_index::entry::Stat::from_fs(&md).ok());
+        let exclude_file_oid = self.exclude_file_oid(current, rela_path, ctx.index);
+        let excludes_changed = self.stack.last().map_or(false, |parent| parent.excludes_changed)
+            || previous_dir.map_or(false, |dir| dir.exclude_file_oid != exclude_file_oid);
+        let is_valid = !excludes_changed
+            && previous_dir.map_or(false, |dir| {
+                !dir.check_only
+                    && dir.stat.zip(stat).map_or(false, |(cached, current)| {
+                        !cached.is_racy(self.index_timestamp, self.stat_options)
+                            && cached.matches(&current, self.stat_options)
+                    })
+            });
+        let entries = previous_dir.filter(|_| is_valid).map(|dir| {
+            let mut entries: Vec<_> = dir
+                .untracked_entries
+                .iter()
+                .map(|name| match name.strip_suffix(b"/") {
+                    Some(dir_name) => (dir_name.into(), Some(entry::Kind::Directory)),
+                    None => (name.clone(), None),
+                })
+                .collect();
+            entries.extend(
+                dir.sub_directories
+                    .iter()
+                    .map(|idx| &cache.directories[*idx].name)
+                    .filter(|sub_dir_name| {
+                        !dir.untracked_entries
+                            .iter()
+                            .any(|name| name.strip_suffix(b"/") == Some(sub_dir_name.as_slice()))
+                    })
+                    .map(|sub_dir_name| (sub_dir_name.clone(), Some(entry::Kind::Directory))),
+            );
+            entries
+        });
+
+        let index = self.directories.len();
+        self.directories.push(Directory {
+            name: name.to_owned(),
+            untracked_entries: Vec::new(),
+            sub_directories: Vec::new(),
+            stat,
+            exclude_file_oid,
+            check_only: self.show_other_directories
+                && !self.stack.is_empty()
+                && info.status == entry::Status::Untracked,
+        });
+        if let Some(parent) = self.stack.last() {
+            self.directories[parent.index].sub_directories.push(index);
+        }
+        self.stack.push(Frame {
+            previous,
+            index,
+            excludes_changed,
+            has_untracked: false,
+        });
+        entries
+    }
+
+    /// Leave the directory that was entered last, and return `true` if it or one of its sub-directories has untracked entries.
+    pub(super) fn leave_directory(&mut self) -> bool {
+        let frame = self.stack.pop().expect("BUG: each directory that is left was entered");
+        if let Some(parent) = self.stack.last_mut() {
+            parent.has_untracked |= frame.has_untracked;
+        }
+        frame.has_untracked
+    }
+
+    /// Record the entry with `file_name` in the current directory if it is untracked according to `info`.
+    /// `recursed_has_untracked` is `Some(has_untracked)` if it is a directory that was recursed into.
+    pub(super) fn record(&mut self, file_name: &BStr, info: &classify::Outcome, recursed_has_untracked: Option<bool>) {
+        if info.status != entry::Status::Untracked {
+            return;
+        }
+        let is_dir = match recursed_has_untracked {
+            None => info.disk_kind.map_or(false, |kind| kind.is_dir()),
+            Some(has_untracked) => {
+                if !(self.show_other_directories && has_untracked) {
+                    return;
+                }
+                true
+            }
+        };
+        let frame = self
+            .stack
+            .last_mut()
+            .expect("BUG: entries are recorded inside of a directory");
+        frame.has_untracked = true;
+        let mut name = file_name.to_owned();
+        if is_dir {
+            name.push_byte(b'/');
+        }
+        self.directories[frame.index].untracked_entries.push(name);
+    }
+}
+
+/// Utilities
+impl Update {
+    /// Return the id of the exclude file in the directory at `current` with worktree-relative `rela_path`, if there is one.
+    /// Prefer the id in the `index` if the file is known to be unchanged, and hash the file otherwise.
+    fn exclude_file_oid(
+        &self,
+        current: &Path,
+        rela_path: &BStr,
+        index: &gix_index::State,
+    ) -> Option<gix_index::hash::ObjectId> {
+        let mut exclude_file_path = BString::from(rela_path);
+        if !exclude_file_path.is_empty() {
+            exclude_file_path.push_byte(b'/');
+        }
+        exclude_file_path.push_str(EXCLUDE_FILENAME_PER_DIR);
+        if let Some(entry) = index.entry_by_path(exclude_file_path.as_bstr()).filter(|entry| {
+            entry
+                .flags
+                .intersects(gix_index::entry::Flags::UPTODATE | gix_index::entry::Flags::SKIP_WORKTREE)
+        }) {
+            return Some(entry.id);
+        }
+        let data = std::fs::read(current.join(EXCLUDE_FILENAME_PER_DIR)).ok()?;
+        Some(gix_object::compute_hash(
+            self.object_hash,
+            gix_object::Kind::Blob,
+            &data,
+        ))
+    }
+}
+
+/// Return the id and stat of the global exclude file at `path`, and reuse the id of `previous` if its stat is unchanged.
+fn global_exclude_file(
+    path: Option<&Path>,
+    previous: Option<&OidStat>,
+    object_hash: gix_index::hash::Kind,
+    index_timestamp: filetime::FileTime,
+    stat_options: gix_index::entry::stat::Options,
+) -> Option<OidStat> {
+    let path = path?;
+    let md = gix_index::fs::Metadata::from_path_no_follow(path).ok()?;
+    let stat = gix_index::entry::Stat::from_fs(&md).ok()?;
+    if let Some(previous) = previous.filter(|previous| {
+        !previous.stat.is_racy(index_timestamp, stat_options) && previous.stat.matches(&stat, stat_options)
+    }) {
+        return Some(OidStat { stat, id: previous.id });
+    }
+    let data = std::fs::read(path).ok()?;
+    Some(OidStat {
+        stat,
+        id: gix_object::compute_hash(object_hash, gix_object::Kind::Blob, &data),
+    })
+}
+
+fn id(oid_stat: Option<&OidStat>) -> Option<&gix_index::hash::oid> {
+    oid_stat.map(|oid_stat| oid_stat.id.as_ref()).filter(|id| !id.is_null())
+}
+
+/// Identify the worktree and the system it is on, just like Git does, so caches for other locations can be discarded.
+fn identifier(worktree_root: &Path) -> BString {
+    let worktree_root = gix_path::realpath(worktree_root).unwrap_or_else(|_| worktree_root.to_owned());
+    let mut identifier = BString::from("Location ");
+    identifier.push_str(gix_path::into_bstr(worktree_root.as_path()).as_ref());
+    identifier.push_str(", system ");
+    identifier.push_str(system_name());
+    // The trailing null byte is stored by Git as well, for compatibility with older versions.
+    identifier.push_byte(0);
+    identifier
+}
+
+/// The name of the operating system as returned by `uname`.
+fn system_name() -> &'static str {
+    match std::env::consts::OS {
+        "linux" | "android" => "Linux",
+        "macos" | "ios" => "Darwin",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        "netbsd" => "NetBSD",
+        "openbsd" => "OpenBSD",
+        "dragonfly" => "DragonFly",
+        "solaris" | "illumos" => "SunOS",
+        other => other,
+    }
+}
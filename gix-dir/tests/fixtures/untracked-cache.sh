@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init untracked-cache
+(cd untracked-cache
+  mkdir -p tracked-dir/sub untracked-dir/nested
+  echo "*.o" > .gitignore
+  touch tracked tracked-dir/tracked tracked-dir/sub/tracked
+  git add .
+  git commit -m "init"
+
+  touch untracked tracked-dir/untracked tracked-dir/sub/untracked ignored.o tracked-dir/ignored.o
+  touch untracked-dir/file untracked-dir/nested/file
+)
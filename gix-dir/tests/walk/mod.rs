@@ -4276,3 +4276,112 @@ fn type_mismatch_ignore_case_clash_file_is_dir() {
          If there was no special handling for this, it would have found the file (`d` in the index, icase), which would have been wrong."
     );
 }
+
+#[test]
+fn untracked_cache_is_populated_and_then_used_to_skip_unchanged_directories() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("untracked-cache.sh")?;
+    let root = tmp.path().join("untracked-cache");
+    for emit_untracked in [Matching, CollapseDirectory] {
+        let mut cache = gix_index::extension::UntrackedCache::default();
+        let walk_with_cache = |cache: &mut gix_index::extension::UntrackedCache| {
+            try_collect_filtered_opts_collect(
+                &root,
+                None,
+                |keep, ctx| {
+                    walk(
+                        &root,
+                        ctx,
+                        walk::Options {
+                            emit_untracked,
+                            ..options()
+                        },
+                        keep,
+                    )
+                },
+                None::<&str>,
+                Options {
+                    untracked_cache: Some(cache),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let ((out, _root), entries) = walk_with_cache(&mut cache)?;
+        assert_eq!(out.read_dir_calls, 5, "everything had to be read initially");
+        assert!(cache.identifier.ends_with(b"\0"), "it's initialized just like Git does");
+        assert_eq!(cache.exclude_filename_per_dir, ".gitignore");
+        assert_eq!(
+            cache.directories.len(),
+            5,
+            "each directory has its own block, and the root comes first"
+        );
+        assert_eq!(cache.directories[0].name, "");
+
+        let previous_cache = cache.clone();
+        let ((out, _root), cached_entries) = walk_with_cache(&mut cache)?;
+        assert_eq!(
+            out.read_dir_calls,
+            if emit_untracked == Matching { 0 } else { 2 },
+            "only untracked directories have to be read again if they are collapsed"
+        );
+        assert_eq!(cached_entries, entries, "the cache produces the same result");
+        assert_eq!(cache, previous_cache, "nothing changed");
+    }
+    Ok(())
+}
+
+#[test]
+fn untracked_cache_is_invalidated_by_changes_to_directories_and_exclude_files() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("untracked-cache.sh")?;
+    let root = tmp.path().join("untracked-cache");
+    let mut cache = gix_index::extension::UntrackedCache::default();
+    let mut walk_with_cache = || {
+        try_collect_filtered_opts_collect(
+            &root,
+            None,
+            |keep, ctx| walk(&root, ctx, options(), keep),
+            None::<&str>,
+            Options {
+                untracked_cache: Some(&mut cache),
+                // Changes to tracked exclude files can only be seen if they aren't considered up-to-date.
+                fresh_index: false,
+                ..Default::default()
+            },
+        )
+    };
+    let (_out, entries) = walk_with_cache()?;
+    assert_eq!(
+        entries,
+        [
+            entry("tracked-dir/sub/untracked", Untracked, File),
+            entry("tracked-dir/untracked", Untracked, File),
+            entry("untracked", Untracked, File),
+            entry("untracked-dir/file", Untracked, File),
+            entry("untracked-dir/nested/file", Untracked, File),
+        ]
+    );
+
+    std::fs::write(root.join("tracked-dir/sub/new"), b"")?;
+    let ((out, _root), entries) = walk_with_cache()?;
+    assert_eq!(out.read_dir_calls, 1, "only the changed directory is read");
+    assert_eq!(
+        entries,
+        [
+            entry("tracked-dir/sub/new", Untracked, File),
+            entry("tracked-dir/sub/untracked", Untracked, File),
+            entry("tracked-dir/untracked", Untracked, File),
+            entry("untracked", Untracked, File),
+            entry("untracked-dir/file", Untracked, File),
+            entry("untracked-dir/nested/file", Untracked, File),
+        ]
+    );
+
+    std::fs::write(root.join(".gitignore"), b"*.o\nuntracked*\n")?;
+    let ((out, _root), entries) = walk_with_cache()?;
+    assert_eq!(
+        out.read_dir_calls, 3,
+        "a changed exclude file invalidates its directory and everything below, while the now ignored directory isn't entered"
+    );
+    assert_eq!(entries, [entry("tracked-dir/sub/new", Untracked, File)]);
+    Ok(())
+}
@@ -274,6 +274,7 @@ pub fn try_collect_filtered_opts(
         fresh_index,
         git_dir,
         should_interrupt,
+        untracked_cache,
     }: Options<'_>,
 ) -> Result<(walk::Outcome, PathBuf), walk::Error> {
     let git_dir = worktree_root.join(git_dir.unwrap_or(".git"));
@@ -304,6 +305,13 @@ pub fn try_collect_filtered_opts(
                 e.flags |= gix_index::entry::Flags::UPTODATE;
             });
     }
+    if untracked_cache.is_some() {
+        // pretend the index was written after all changes to the worktree so cached directory stats aren't racy.
+        index.set_timestamp(filetime::FileTime::from_unix_time(
+            filetime::FileTime::now().unix_seconds() + 60,
+            0,
+        ));
+    }
     let mut search = gix_pathspec::Search::from_specs(
         patterns.into_iter().map(|spec| {
             gix_pathspec::parse(spec.as_ref(), gix_pathspec::Defaults::default()).expect("tests use valid pattern")
@@ -349,6 +357,16 @@ pub fn try_collect_filtered_opts(
             objects: &gix_object::find::Never,
             explicit_traversal_root,
             should_interrupt,
+            untracked_cache: untracked_cache.map(|cache| walk::UntrackedCache {
+                cache,
+                info_exclude: None,
+                excludes_file: None,
+                // changes happen within the same second, so only nanoseconds can tell them apart.
+                stat: gix_index::entry::stat::Options {
+                    use_nsec: true,
+                    ..Default::default()
+                },
+            }),
         },
     )
 }
@@ -357,6 +375,7 @@ pub struct Options<'a> {
     pub fresh_index: bool,
     pub git_dir: Option<&'a str>,
     pub should_interrupt: Option<&'a AtomicBool>,
+    pub untracked_cache: Option<&'a mut gix_index::extension::UntrackedCache>,
 }
 
 impl<'a> Options<'a> {
@@ -374,6 +393,7 @@ impl<'a> Default for Options<'a> {
             fresh_index: true,
             git_dir: None,
             should_interrupt: None,
+            untracked_cache: None,
         }
     }
 }
@@ -14,3 +14,20 @@ fn size_of_sha1() {
         if cfg!(target_arch = "x86") { 96 } else { 104 }
     )
 }
+
+#[test]
+fn hasher_produces_ids_of_its_kind() {
+    // `gix-hash` supports `Sha256` due to our dev-dependencies, even if our own `sha256` feature isn't enabled.
+    for (kind, expected) in [
+        (gix_hash::Kind::Sha1, "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+        (
+            gix_hash::Kind::Sha256,
+            "473a0f4c3be8a93681a267e3b1e9a7dcda1185436fe141f7749120a303721813",
+        ),
+    ] {
+        let mut hasher = gix_features::hash::hasher(kind);
+        assert_eq!(hasher.kind(), kind);
+        hasher.update(b"blob 0\0");
+        assert_eq!(hasher.digest().to_string(), expected);
+    }
+}
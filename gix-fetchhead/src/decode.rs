@@ -0,0 +1,95 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Entry, FetchHead, NOT_FOR_MERGE};
+
+/// The error returned by [`FetchHead::from_bytes()`] and [`Entry::from_line()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number}: expected three tab-separated fields, got '{line}'")]
+    MissingField { line_number: usize, line: BString },
+    #[error("Line {line_number}: invalid object id")]
+    ObjectId {
+        line_number: usize,
+        source: gix_hash::decode::Error,
+    },
+    #[error("Line {line_number}: expected '{NOT_FOR_MERGE}' or nothing, got '{marker}'")]
+    Marker { line_number: usize, marker: BString },
+    #[error("Line {line_number}: the description of the remote reference is malformed: '{description}'")]
+    Description { line_number: usize, description: BString },
+}
+
+/// Parsing
+impl FetchHead {
+    /// Parse all lines of a `FETCH_HEAD` file in `input`, ignoring empty lines.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        for (line_number, line) in input.lines().enumerate() {
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            entries.push(Entry::from_line(line, line_number + 1)?);
+        }
+        Ok(FetchHead { entries })
+    }
+}
+
+/// The prefixes of descriptions of remote references, along with the prefix of the reference name they stand for.
+const KINDS: &[(&[u8], &[u8])] = &[
+    (b"branch '", b"refs/heads/"),
+    (b"tag '", b"refs/tags/"),
+    (b"remote-tracking branch '", b"refs/remotes/"),
+    (b"'", b""),
+];
+
+impl Entry {
+    /// Parse a single `line` of a `FETCH_HEAD` file, with `line_number` being used for error messages.
+    pub fn from_line(line: &[u8], line_number: usize) -> Result<Self, Error> {
+        let mut fields = line.splitn(3, |b| *b == b'\t');
+        let (Some(hex), Some(marker), Some(description)) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(Error::MissingField {
+                line_number,
+                line: line.into(),
+            });
+        };
+        let id = ObjectId::from_hex(hex).map_err(|source| Error::ObjectId { line_number, source })?;
+        let for_merge = match marker {
+            b"" => true,
+            m if m == NOT_FOR_MERGE.as_bytes() => false,
+            _ => {
+                return Err(Error::Marker {
+                    line_number,
+                    marker: marker.into(),
+                })
+            }
+        };
+
+        let description = description.trim_end_with(|c| c == '\r');
+        let Some((name_prefix, rest)) = KINDS
+            .iter()
+            .find_map(|(prefix, name_prefix)| description.strip_prefix(*prefix).map(|rest| (name_prefix, rest)))
+        else {
+            return Ok(Entry {
+                id,
+                for_merge,
+                remote_ref: None,
+                url: description.into(),
+            });
+        };
+        let Some(pos) = rest.find(b"' of ") else {
+            return Err(Error::Description {
+                line_number,
+                description: description.into(),
+            });
+        };
+        let mut remote_ref = BString::from(*name_prefix);
+        remote_ref.extend_from_slice(&rest[..pos]);
+        Ok(Entry {
+            id,
+            for_merge,
+            remote_ref: Some(remote_ref),
+            url: rest[pos + b"' of ".len()..].into(),
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use bstr::ByteSlice;
+
+use crate::{Entry, FetchHead, Kind, NOT_FOR_MERGE};
+
+/// Serialization
+impl FetchHead {
+    /// Write all entries to `out` in the format of a `FETCH_HEAD` file.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        for entry in &self.entries {
+            entry.write_to(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Entry {
+    /// Write this entry as a single line to `out`, the way `git fetch` does.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        out.write_all(self.id.to_hex().to_string().as_bytes())?;
+        out.write_all(b"\t")?;
+        if !self.for_merge {
+            out.write_all(NOT_FOR_MERGE.as_bytes())?;
+        }
+        out.write_all(b"\t")?;
+        if let Some(name) = self.short_name() {
+            let kind = match self.kind() {
+                Kind::Branch => "branch ",
+                Kind::Tag => "tag ",
+                Kind::RemoteTrackingBranch => "remote-tracking branch ",
+                Kind::Head | Kind::Other => "",
+            };
+            write!(out, "{kind}'")?;
+            out.write_all(name.as_bytes())?;
+            out.write_all(b"' of ")?;
+        }
+        out.write_all(self.url.as_bytes())?;
+        out.write_all(b"\n")
+    }
+}
@@ -1,2 +1,107 @@
-#![deny(rust_2018_idioms)]
+//! Read and write `.git/FETCH_HEAD`, the file in which `git fetch` records what it fetched.
+//!
+//! Each line of the file is an [`Entry`] which names the object that was fetched, whether it should be merged by
+//! `git pull` or `git merge FETCH_HEAD`, and where it came from. The first entry is what `FETCH_HEAD` resolves to.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+mod encode;
+
+/// The marker of entries that aren't meant to be merged.
+pub const NOT_FOR_MERGE: &str = "not-for-merge";
+
+/// A single line of a `FETCH_HEAD` file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The object the remote reference pointed to, which isn't peeled.
+    pub id: ObjectId,
+    /// If `true`, the entry is to be merged by `git pull`, or it is marked as `not-for-merge` otherwise.
+    pub for_merge: bool,
+    /// The full name of the reference on the remote, like `refs/heads/main`, or `None` if it was `HEAD`.
+    ///
+    /// If an object was fetched by its id, this is its hexadecimal representation.
+    pub remote_ref: Option<BString>,
+    /// The URL of the remote, typically without credentials and without a trailing `.git`, see [`shorten_url()`].
+    pub url: BString,
+}
+
+/// The kind of reference an [`Entry`] was fetched from, used to describe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Kind {
+    /// The remote `HEAD`.
+    Head,
+    /// A branch in `refs/heads/`.
+    Branch,
+    /// A tag in `refs/tags/`.
+    Tag,
+    /// A remote tracking branch in `refs/remotes/`.
+    RemoteTrackingBranch,
+    /// Any other reference, or an object fetched by its id.
+    Other,
+}
+
+/// All entries of a `FETCH_HEAD` file, in order.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct FetchHead {
+    /// The entries, with those to merge typically coming first.
+    pub entries: Vec<Entry>,
+}
+
+impl Entry {
+    /// Return the kind of reference this entry was fetched from.
+    pub fn kind(&self) -> Kind {
+        match self.remote_ref.as_ref().map(|name| name.as_bytes()) {
+            None => Kind::Head,
+            Some(name) if name.starts_with(b"refs/heads/") => Kind::Branch,
+            Some(name) if name.starts_with(b"refs/tags/") => Kind::Tag,
+            Some(name) if name.starts_with(b"refs/remotes/") => Kind::RemoteTrackingBranch,
+            Some(_) => Kind::Other,
+        }
+    }
+
+    /// Return the name of the remote reference as shown in the description of the entry, like `main` for `refs/heads/main`,
+    /// or `None` if it was fetched from `HEAD`.
+    pub fn short_name(&self) -> Option<&BStr> {
+        let name = self.remote_ref.as_ref()?.as_bstr();
+        let prefix: &[u8] = match self.kind() {
+            Kind::Branch => b"refs/heads/",
+            Kind::Tag => b"refs/tags/",
+            Kind::RemoteTrackingBranch => b"refs/remotes/",
+            Kind::Head | Kind::Other => b"",
+        };
+        Some(name[prefix.len()..].as_bstr())
+    }
+}
+
+/// Access
+impl FetchHead {
+    /// Return the entry that `FETCH_HEAD` resolves to, which is the first one.
+    pub fn head(&self) -> Option<&Entry> {
+        self.entries.first()
+    }
+
+    /// Return all entries that are to be merged, in order.
+    pub fn to_merge(&self) -> impl Iterator<Item = &Entry> + '_ {
+        self.entries.iter().filter(|entry| entry.for_merge)
+    }
+}
+
+/// Shorten `url` like `git` does before writing it to `FETCH_HEAD`, by removing trailing slashes and a trailing `.git`.
+///
+/// Credentials are expected to be removed by the caller.
+pub fn shorten_url(url: &BStr) -> &BStr {
+    let mut url = url.as_bytes();
+    while let Some(stripped) = url.strip_suffix(b"/") {
+        url = stripped;
+    }
+    match url.strip_suffix(b".git") {
+        Some(stripped) if !stripped.is_empty() => stripped.as_bstr(),
+        _ => url.as_bstr(),
+    }
+}
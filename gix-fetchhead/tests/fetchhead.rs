@@ -0,0 +1,157 @@
+use gix_fetchhead::{Entry, FetchHead, Kind};
+use gix_hash::ObjectId;
+
+use gix_testtools::Result;
+
+fn fixture(name: &str) -> Result<(Vec<u8>, FetchHead)> {
+    let dir = gix_testtools::scripted_fixture_read_only("make_fetch_head.sh")?;
+    let data = std::fs::read(dir.join(name))?;
+    let fetch_head = FetchHead::from_bytes(&data)?;
+    Ok((data, fetch_head))
+}
+
+fn summary(fetch_head: &FetchHead) -> Vec<(bool, Kind, Option<String>, String)> {
+    fetch_head
+        .entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.for_merge,
+                entry.kind(),
+                entry.short_name().map(ToString::to_string),
+                entry.url.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn configured_refspecs_merge_the_upstream_branch_only() -> Result {
+    let (_, fetch_head) = fixture("default")?;
+    assert_eq!(
+        summary(&fetch_head),
+        [
+            (true, Kind::Branch, Some("main".into()), "../remote".into()),
+            (false, Kind::Branch, Some("feature".into()), "../remote".into()),
+        ]
+    );
+    assert_eq!(
+        fetch_head.entries[0].remote_ref.as_ref().expect("set"),
+        "refs/heads/main"
+    );
+    assert_eq!(fetch_head.head(), fetch_head.entries.first());
+    assert_eq!(fetch_head.to_merge().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn all_kinds_of_references() -> Result {
+    let (_, fetch_head) = fixture("explicit")?;
+    assert_eq!(
+        summary(&fetch_head),
+        [
+            (true, Kind::Branch, Some("feature".into()), "../remote".into()),
+            (true, Kind::Tag, Some("v1".into()), "../remote".into()),
+            (true, Kind::Other, Some("refs/pull/1/head".into()), "../remote".into()),
+            (
+                true,
+                Kind::RemoteTrackingBranch,
+                Some("upstream/main".into()),
+                "../remote".into()
+            ),
+        ]
+    );
+    let remote_refs: Vec<_> = fetch_head
+        .entries
+        .iter()
+        .map(|entry| entry.remote_ref.as_ref().expect("set").to_string())
+        .collect();
+    assert_eq!(
+        remote_refs,
+        [
+            "refs/heads/feature",
+            "refs/tags/v1",
+            "refs/pull/1/head",
+            "refs/remotes/upstream/main"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn remote_head_and_appended_entries() -> Result {
+    let (_, fetch_head) = fixture("head")?;
+    assert_eq!(summary(&fetch_head), [(true, Kind::Head, None, "../remote".into())]);
+
+    let (_, appended) = fixture("appended")?;
+    assert_eq!(appended.entries[0], fetch_head.entries[0]);
+    assert_eq!(
+        summary(&appended)[1],
+        (true, Kind::Tag, Some("lightweight".into()), "../remote".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result {
+    for name in ["default", "explicit", "head", "appended"] {
+        let (data, fetch_head) = fixture(name)?;
+        let mut out = Vec::new();
+        fetch_head.write_to(&mut out)?;
+        assert_eq!(out, data, "{name}: writing produces exactly what git wrote");
+    }
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    let hex = "7eb585fe93a2a5875bc1f95ac4ea46fc98e9b7be";
+    for (line, expected) in [
+        (
+            hex.to_string(),
+            "Line 1: expected three tab-separated fields, got '7eb585fe93a2a5875bc1f95ac4ea46fc98e9b7be'",
+        ),
+        ("abc\t\turl".into(), "Line 1: invalid object id"),
+        (
+            format!("{hex}\tfor-merge\turl"),
+            "Line 1: expected 'not-for-merge' or nothing, got 'for-merge'",
+        ),
+        (
+            format!("{hex}\t\tbranch 'main of url"),
+            "Line 1: the description of the remote reference is malformed: 'branch 'main of url'",
+        ),
+    ] {
+        assert_eq!(Entry::from_line(line.as_bytes(), 1).unwrap_err().to_string(), expected);
+    }
+}
+
+#[test]
+fn shorten_url() {
+    for (input, expected) in [
+        ("https://example.com/repo.git", "https://example.com/repo"),
+        ("https://example.com/repo.git/", "https://example.com/repo"),
+        ("../remote//", "../remote"),
+        ("git@example.com:repo", "git@example.com:repo"),
+        (".git", ".git"),
+    ] {
+        assert_eq!(gix_fetchhead::shorten_url(input.into()), expected);
+    }
+}
+
+#[test]
+fn entries_can_be_written_without_parsing() -> Result {
+    let entry = Entry {
+        id: ObjectId::null(gix_hash::Kind::Sha1),
+        for_merge: false,
+        remote_ref: Some("refs/heads/main".into()),
+        url: "https://example.com/repo".into(),
+    };
+    let mut out = Vec::new();
+    entry.write_to(&mut out)?;
+    assert_eq!(
+        out,
+        b"0000000000000000000000000000000000000000\tnot-for-merge\tbranch 'main' of https://example.com/repo\n"
+    );
+    assert_eq!(Entry::from_line(&out[..out.len() - 1], 1)?, entry);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q remote.git
+(cd remote.git
+  git checkout -q -b main
+  git commit -q --allow-empty -m "first"
+  git tag -m "annotated" v1
+  git tag lightweight
+  git branch feature
+  git commit -q --allow-empty -m "second"
+  git update-ref refs/pull/1/head HEAD~1
+  git update-ref refs/remotes/upstream/main HEAD
+)
+
+git clone -q remote.git local
+(cd local
+  git remote set-url origin ../remote.git/
+
+  git fetch -q
+  cp .git/FETCH_HEAD ../default
+
+  git fetch -q origin feature v1 refs/pull/1/head refs/remotes/upstream/main
+  cp .git/FETCH_HEAD ../explicit
+
+  git fetch -q ../remote.git
+  cp .git/FETCH_HEAD ../head
+
+  git fetch -q --append origin lightweight
+  cp .git/FETCH_HEAD ../appended
+)
@@ -44,6 +44,12 @@ pub enum Error {
         status: driver::process::Status,
         command: String,
     },
+    #[error("The in-process filter '{name}' failed to {operation}")]
+    InProcess {
+        name: BString,
+        operation: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
 }
 
 /// Additional information for use in the [`State::apply()`] method.
@@ -75,6 +81,9 @@ impl State {
     /// Note that it's not an error if there is no filter process for `operation` or if a long-running process doesn't supported
     /// the desired capability.
     ///
+    /// Filters [registered as in-process](State::register_in_process()) under the name of `driver` take precedence
+    /// over its programs, and always apply.
+    ///
     /// ### Deviation
     ///
     /// If a long running process returns the 'abort' status after receiving the data, it will be removed similar to how `git` does it.
@@ -109,6 +118,14 @@ impl State {
         delay: Delay,
         ctx: Context<'_, '_>,
     ) -> Result<Option<MaybeDelayed<'a>>, Error> {
+        if let Some(filter) = self.in_process.get(&driver.name) {
+            let read = filter.apply(operation, src, ctx).map_err(|source| Error::InProcess {
+                name: driver.name.clone(),
+                operation: operation.as_str(),
+                source,
+            })?;
+            return Ok(Some(MaybeDelayed::Immediate(read)));
+        }
         match self.maybe_launch_process(driver, operation, ctx.rela_path)? {
             Some(Process::SingleFile { mut child, command }) => {
                 std::io::copy(src, &mut child.stdin.take().expect("configured"))?;
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use bstr::{BStr, BString, ByteSlice, ByteVec};
 
@@ -59,6 +59,20 @@ impl Operation {
     }
 }
 
+/// A filter driver implemented in-process, which is used instead of the programs of a [`Driver`] with the same name.
+///
+/// Use [`State::register_in_process()`] to make it available.
+pub trait InProcess: Send + Sync {
+    /// Apply `operation` to all data read from `src` and return a reader for the filtered result, with `ctx`
+    /// providing information about the entry being processed.
+    fn apply(
+        &self,
+        operation: Operation,
+        src: &mut dyn std::io::Read,
+        ctx: apply::Context<'_, '_>,
+    ) -> Result<Box<dyn std::io::Read>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
 /// State required to handle `process` filters, which are running until all their work is done.
 ///
 /// These can be significantly faster on some platforms as they are launched only once, while supporting asynchronous processing.
@@ -75,6 +89,8 @@ pub struct State {
     /// Note that these processes are expected to shut-down once their stdin/stdout are dropped, so nothing else
     /// needs to be done to clean them up after drop.
     running: HashMap<BString, process::Client>,
+    /// Filters implemented in-process by the name of the driver they stand in for.
+    in_process: HashMap<BString, Arc<dyn InProcess>>,
 
     /// The context to pass to spawned filter programs.
     pub context: gix_command::Context,
@@ -86,15 +102,22 @@ impl State {
     pub fn new(context: gix_command::Context) -> Self {
         Self {
             running: Default::default(),
+            in_process: Default::default(),
             context,
         }
     }
+
+    /// Use `filter` for all drivers named `name`, instead of launching their programs.
+    pub fn register_in_process(&mut self, name: impl Into<BString>, filter: Arc<dyn InProcess>) {
+        self.in_process.insert(name.into(), filter);
+    }
 }
 
 impl Clone for State {
     fn clone(&self) -> Self {
         State {
             running: Default::default(),
+            in_process: self.in_process.clone(),
             context: self.context.clone(),
         }
     }
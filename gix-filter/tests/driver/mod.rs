@@ -370,6 +370,72 @@ pub(crate) mod apply {
         Ok(())
     }
 
+    struct Uppercase;
+
+    impl driver::InProcess for Uppercase {
+        fn apply(
+            &self,
+            operation: Operation,
+            src: &mut dyn std::io::Read,
+            ctx: apply::Context<'_, '_>,
+        ) -> Result<Box<dyn std::io::Read>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            if ctx.rela_path == "fail" {
+                return Err("requested failure".into());
+            }
+            let mut buf = Vec::new();
+            src.read_to_end(&mut buf)?;
+            match operation {
+                Operation::Clean => buf.make_ascii_lowercase(),
+                Operation::Smudge => buf.make_ascii_uppercase(),
+            }
+            Ok(Box::new(std::io::Cursor::new(buf)))
+        }
+    }
+
+    #[test]
+    fn in_process_filters_take_precedence_over_programs() -> crate::Result {
+        let mut state = gix_filter::driver::State::default();
+        state.register_in_process("arrow", std::sync::Arc::new(Uppercase));
+        let mut driver = driver_with_process();
+        driver.name = "arrow".into();
+
+        for (operation, input, expected) in [
+            (Operation::Smudge, "hello", "HELLO"),
+            (Operation::Clean, "HELLO", "hello"),
+        ] {
+            let mut filtered = state
+                .apply(&driver, &mut input.as_bytes(), operation, context_from_path("a"))?
+                .expect("in-process filters always apply");
+            let mut buf = Vec::new();
+            filtered.read_to_end(&mut buf)?;
+            assert_eq!(buf.as_bstr(), expected);
+        }
+
+        driver.clean = None;
+        driver.smudge = None;
+        driver.process = None;
+        assert!(
+            state
+                .apply(&driver, &mut &b""[..], Operation::Smudge, context_from_path("a"))?
+                .is_some(),
+            "drivers don't need programs to be defined for in-process filters to apply"
+        );
+
+        let err = match state.apply(&driver, &mut &b""[..], Operation::Clean, context_from_path("fail")) {
+            Ok(_) => panic!("the filter fails on request"),
+            Err(err) => err,
+        };
+        assert_eq!(err.to_string(), "The in-process filter 'arrow' failed to clean");
+        assert_eq!(
+            state
+                .shutdown(gix_filter::driver::shutdown::Mode::WaitForProcesses)?
+                .len(),
+            0,
+            "no process was launched"
+        );
+        Ok(())
+    }
+
     pub(crate) fn extract_delayed_key(res: Option<apply::MaybeDelayed<'_>>) -> driver::Key {
         match res {
             Some(apply::MaybeDelayed::Immediate(_)) | None => {
@@ -57,6 +57,8 @@ impl std::fmt::Debug for oid {
             "{}({})",
             match self.kind() {
                 Kind::Sha1 => "Sha1",
+                #[cfg(feature = "sha256")]
+                Kind::Sha256 => "Sha256",
             },
             self.to_hex(),
         )
@@ -77,14 +79,17 @@ impl oid {
     #[inline]
     pub fn try_from_bytes(digest: &[u8]) -> Result<&Self, Error> {
         match digest.len() {
-            20 => Ok(
-                #[allow(unsafe_code)]
-                unsafe {
-                    &*(digest as *const [u8] as *const oid)
-                },
-            ),
-            len => Err(Error::InvalidByteSliceLength(len)),
+            SIZE_OF_SHA1_DIGEST => {}
+            #[cfg(feature = "sha256")]
+            crate::SIZE_OF_SHA256_DIGEST => {}
+            len => return Err(Error::InvalidByteSliceLength(len)),
         }
+        Ok(
+            #[allow(unsafe_code)]
+            unsafe {
+                &*(digest as *const [u8] as *const oid)
+            },
+        )
     }
 
     /// Create an OID from the input `value` slice without performing any safety check.
@@ -146,6 +151,8 @@ impl oid {
     pub fn is_null(&self) -> bool {
         match self.kind() {
             Kind::Sha1 => &self.bytes == oid::null_sha1().as_bytes(),
+            #[cfg(feature = "sha256")]
+            Kind::Sha256 => &self.bytes == oid::null_sha256().as_bytes(),
         }
     }
 }
@@ -176,6 +183,13 @@ impl oid {
     pub(crate) fn null_sha1() -> &'static Self {
         oid::from_bytes([0u8; SIZE_OF_SHA1_DIGEST].as_ref())
     }
+
+    /// Returns a Sha256 digest with all bytes being initialized to zero.
+    #[cfg(feature = "sha256")]
+    #[inline]
+    pub(crate) fn null_sha256() -> &'static Self {
+        oid::from_bytes([0u8; crate::SIZE_OF_SHA256_DIGEST].as_ref())
+    }
 }
 
 impl AsRef<oid> for &oid {
@@ -198,6 +212,8 @@ impl ToOwned for oid {
     fn to_owned(&self) -> Self::Owned {
         match self.kind() {
             Kind::Sha1 => ObjectId::Sha1(self.bytes.try_into().expect("no bug in hash detection")),
+            #[cfg(feature = "sha256")]
+            Kind::Sha256 => ObjectId::Sha256(self.bytes.try_into().expect("no bug in hash detection")),
         }
     }
 }
@@ -208,6 +224,13 @@ impl<'a> From<&'a [u8; SIZE_OF_SHA1_DIGEST]> for &'a oid {
     }
 }
 
+#[cfg(feature = "sha256")]
+impl<'a> From<&'a [u8; crate::SIZE_OF_SHA256_DIGEST]> for &'a oid {
+    fn from(v: &'a [u8; crate::SIZE_OF_SHA256_DIGEST]) -> Self {
+        oid::from_bytes(v.as_ref())
+    }
+}
+
 impl std::fmt::Display for &oid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for b in self.as_bytes() {
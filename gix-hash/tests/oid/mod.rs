@@ -18,4 +18,6 @@ mod to_hex_with_len {
 fn is_null() {
     assert!(gix_hash::Kind::Sha1.null().is_null());
     assert!(gix_hash::Kind::Sha1.null().as_ref().is_null());
+    assert!(gix_hash::Kind::Sha256.null().is_null());
+    assert!(gix_hash::Kind::Sha256.null().as_ref().is_null());
 }
@@ -108,8 +108,8 @@ mod try_from {
 
     #[test]
     fn id_to_long() {
-        let input = "abcdefabcdefabcdefabcdefabcdefabcdefabcd123123123123123123";
-        let expected = Error::TooLong { hex_len: 58 };
+        let input = "abcdefabcdefabcdefabcdefabcdefabcdefabcd1231231231231231231231231";
+        let expected = Error::TooLong { hex_len: 65 };
         let actual = Prefix::try_from(input).unwrap_err();
         assert_eq!(actual, expected);
     }
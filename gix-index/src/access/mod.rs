@@ -6,10 +6,6 @@ use filetime::FileTime;
 use crate::entry::{Stage, StageRaw};
 use crate::{entry, extension, AccelerateLookup, Entry, PathStorage, PathStorageRef, State, Version};
 
-// TODO: integrate this somehow, somewhere, depending on later usage.
-#[allow(dead_code)]
-mod sparse;
-
 /// General information and entries
 impl State {
     /// Return the version used to store this state's information on disk.
@@ -17,6 +13,14 @@ impl State {
         self.version
     }
 
+    /// Set the `version` to use when storing this state on disk.
+    ///
+    /// Note that only [`Version::V4`] is retained as is when writing, as the path compression it provides is optional.
+    /// Otherwise the lowest version capable of storing all entries is used, just like `git` does it.
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version
+    }
+
     /// Returns time at which the state was created, indicating its freshness compared to other files on disk.
     pub fn timestamp(&self) -> FileTime {
         self.timestamp
@@ -487,6 +491,13 @@ impl State {
             path_start..self.path_backing.len()
         };
 
+        let path_str = self.path_backing[path.clone()].as_bstr();
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path_str);
+        }
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(path_str);
+        }
         self.entries.push(Entry {
             stat,
             id,
@@ -496,6 +507,87 @@ impl State {
         });
     }
 
+    /// Add an unconflicted entry with `stat`, `id`, `flags` and `mode` at `path`, replacing all entries that exist
+    /// at `path` in any stage, and return the index of the new entry in [`entries()`](Self::entries()).
+    ///
+    /// Unlike [`dangerously_push_entry()`](Self::dangerously_push_entry()), entries are kept sorted, and entries that
+    /// would conflict with the new one are removed, which are files at any of the leading directories of `path`,
+    /// as well as all entries below `path` if it was a directory before.
+    /// The `TREE` extension and the untracked cache are invalidated accordingly.
+    pub fn upsert_entry(
+        &mut self,
+        stat: entry::Stat,
+        id: gix_hash::ObjectId,
+        flags: entry::Flags,
+        mode: entry::Mode,
+        path: &BStr,
+    ) -> usize {
+        let flags = flags - entry::Flags::STAGE_MASK;
+        if let Some(range) = self.entry_range(path) {
+            if range.len() > 1 {
+                let keep = range.start;
+                self.remove_entries(|idx, _, _| range.contains(&idx) && idx != keep);
+            }
+            if let Some(untracked) = self.untracked.as_mut() {
+                untracked.invalidate_path(path);
+            }
+            if let Some(tree) = self.tree.as_mut() {
+                tree.invalidate_path(path);
+            }
+            let entry = &mut self.entries[range.start];
+            entry.stat = stat;
+            entry.id = id;
+            entry.flags = flags;
+            entry.mode = mode;
+            return range.start;
+        }
+
+        let is_leading_directory = |entry_path: &BStr| {
+            path.len() > entry_path.len() && path.starts_with(entry_path) && path[entry_path.len()] == b'/'
+        };
+        let has_file_at_leading_directory = path
+            .find_iter(b"/")
+            .map(|pos| path[..pos].as_bstr())
+            .any(|dir| self.entry_index_by_path(dir).is_ok());
+        let mut dir = path.to_owned();
+        dir.push_byte(b'/');
+        let has_entries_below = self.prefixed_entries_range(dir.as_ref()).is_some();
+        if has_file_at_leading_directory || has_entries_below {
+            self.remove_entries(|_, entry_path, _| {
+                entry_path.starts_with(dir.as_slice()) || is_leading_directory(entry_path)
+            });
+        }
+
+        let path_range = {
+            let path_start = self.path_backing.len();
+            self.path_backing.push_str(path);
+            path_start..self.path_backing.len()
+        };
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path);
+        }
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(path);
+        }
+        let idx = self
+            .entry_index_by_path(path)
+            .expect_err("there is no entry at path anymore");
+        self.entries.insert(
+            idx,
+            Entry {
+                stat,
+                id,
+                flags,
+                mode,
+                path: path_range,
+            },
+        );
+        if mode == entry::Mode::DIR {
+            self.is_sparse = true;
+        }
+        idx
+    }
+
     /// Unconditionally sort entries as needed to perform lookups quickly.
     pub fn sort_entries(&mut self) {
         let path_backing = &self.path_backing;
@@ -527,9 +619,19 @@ impl State {
     pub fn remove_entries(&mut self, mut should_remove: impl FnMut(usize, &BStr, &mut Entry) -> bool) {
         let mut index = 0;
         let paths = &self.path_backing;
+        let untracked = &mut self.untracked;
+        let tree = &mut self.tree;
         self.entries.retain_mut(|e| {
             let path = e.path_in(paths);
             let res = !should_remove(index, path, e);
+            if !res {
+                if let Some(untracked) = untracked.as_mut() {
+                    untracked.invalidate_path(path);
+                }
+                if let Some(tree) = tree.as_mut() {
+                    tree.invalidate_path(path);
+                }
+            }
             index += 1;
             res
         });
@@ -542,7 +644,15 @@ impl State {
     pub fn tree(&self) -> Option<&extension::Tree> {
         self.tree.as_ref()
     }
+    /// Access the `tree` extension mutably, which is needed if entries are changed through
+    /// [`entries_mut()`](Self::entries_mut()), as their path has to be [invalidated](extension::Tree::invalidate_path()).
+    pub fn tree_mut(&mut self) -> Option<&mut extension::Tree> {
+        self.tree.as_mut()
+    }
     /// Access the `link` extension.
+    ///
+    /// Note that when read with [`File::at()`](crate::File::at()), the entries of the shared index are already merged
+    /// into ours, which is why the extension won't have [bitmaps](extension::Link::bitmaps) then.
     pub fn link(&self) -> Option<&extension::Link> {
         self.link.as_ref()
     }
@@ -554,10 +664,55 @@ impl State {
     pub fn untracked(&self) -> Option<&extension::UntrackedCache> {
         self.untracked.as_ref()
     }
+    /// Obtain the untracked extension for modification, for instance to update it after a directory walk.
+    pub fn untracked_mut(&mut self) -> Option<&mut extension::UntrackedCache> {
+        self.untracked.as_mut()
+    }
+    /// Set the untracked extension to `untracked`, or remove it if `None`, returning the previous value.
+    pub fn set_untracked(&mut self, untracked: Option<extension::UntrackedCache>) -> Option<extension::UntrackedCache> {
+        std::mem::replace(&mut self.untracked, untracked)
+    }
     /// Obtain the fsmonitor extension.
     pub fn fs_monitor(&self) -> Option<&extension::FsMonitor> {
         self.fs_monitor.as_ref()
     }
+    /// Set the fsmonitor extension to `fs_monitor`, or remove it if `None`, returning the previous value.
+    ///
+    /// Use [`FsMonitor::from_entries()`](extension::FsMonitor::from_entries()) to create it from the
+    /// [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flags of our entries.
+    pub fn set_fs_monitor(&mut self, fs_monitor: Option<extension::FsMonitor>) -> Option<extension::FsMonitor> {
+        std::mem::replace(&mut self.fs_monitor, fs_monitor)
+    }
+    /// Set the [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag on all entries that the fsmonitor extension
+    /// doesn't consider dirty, and clear it on all others, similar to what `git` does after reading the index.
+    ///
+    /// Return `false` if there is no fsmonitor extension or if its bitmap doesn't fit our entries, in which case
+    /// the flag is cleared on all entries.
+    pub fn mark_fs_monitor_valid_entries(&mut self) -> bool {
+        let mut is_valid = match self.fs_monitor.as_ref() {
+            Some(fs_monitor) => fs_monitor.entry_dirty().num_bits() <= self.entries.len(),
+            None => false,
+        };
+        for entry in &mut self.entries {
+            entry.flags.set(entry::Flags::FSMONITOR_VALID, is_valid);
+        }
+        if let Some(fs_monitor) = self.fs_monitor.as_ref().filter(|_| is_valid) {
+            let entries = &mut self.entries;
+            is_valid = fs_monitor
+                .entry_dirty()
+                .for_each_set_bit(|index| {
+                    entries.get_mut(index)?.flags.remove(entry::Flags::FSMONITOR_VALID);
+                    Some(())
+                })
+                .is_some();
+            if !is_valid {
+                for entry in entries {
+                    entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+                }
+            }
+        }
+        is_valid
+    }
     /// Return `true` if the end-of-index extension was present when decoding this index.
     pub fn had_end_of_index_marker(&self) -> bool {
         self.end_of_index_at_decode_time
@@ -82,6 +82,16 @@ impl Flags {
         (*self & Flags::STAGE_MASK).bits() >> 12
     }
 
+    /// Set or clear the [`SKIP_WORKTREE`](Self::SKIP_WORKTREE) flag according to `value`, while keeping the
+    /// [`EXTENDED`](Self::EXTENDED) flag consistent so it's persisted as expected.
+    pub fn set_skip_worktree(&mut self, value: bool) {
+        self.set(Self::SKIP_WORKTREE, value);
+        self.set(
+            Self::EXTENDED,
+            self.intersects(Self::SKIP_WORKTREE | Self::INTENT_TO_ADD),
+        );
+    }
+
     /// Transform ourselves to a storage representation to keep all flags which are to be persisted,
     /// skipping all extended flags. Note that the caller has to check for the `EXTENDED` bit to be present
     /// and write extended flags as well if so.
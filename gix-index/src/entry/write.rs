@@ -1,8 +1,37 @@
-use crate::{entry, Entry, State};
+use crate::{entry, util::write_var_int, Entry, State};
 
 impl Entry {
     /// Serialize ourselves to `out` with path access via `state`, without padding.
     pub fn write_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
+        self.write_stat_id_flags_and_path(&mut out, self.path(state))
+    }
+
+    /// Serialize ourselves to `out` with `path`, which doesn't have to be our own path, without padding.
+    pub(crate) fn write_stat_id_flags_and_path(
+        &self,
+        mut out: impl std::io::Write,
+        path: &[u8],
+    ) -> std::io::Result<()> {
+        self.write_stat_id_and_flags(&mut out, path.len())?;
+        out.write_all(path)?;
+        out.write_all(b"\0")
+    }
+
+    /// Serialize ourselves to `out` with `path`, which is stored as delta to `previous_path` as done by index version 4.
+    pub(crate) fn write_to_with_prefix_compression(
+        &self,
+        mut out: impl std::io::Write,
+        path: &[u8],
+        previous_path: &[u8],
+    ) -> std::io::Result<()> {
+        self.write_stat_id_and_flags(&mut out, path.len())?;
+        let common_len = path.iter().zip(previous_path).take_while(|(a, b)| a == b).count();
+        write_var_int(&mut out, (previous_path.len() - common_len) as u64)?;
+        out.write_all(&path[common_len..])?;
+        out.write_all(b"\0")
+    }
+
+    fn write_stat_id_and_flags(&self, mut out: impl std::io::Write, path_len: usize) -> std::io::Result<()> {
         let stat = self.stat;
         out.write_all(&stat.ctime.secs.to_be_bytes())?;
         out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
@@ -15,11 +44,10 @@ impl Entry {
         out.write_all(&stat.gid.to_be_bytes())?;
         out.write_all(&stat.size.to_be_bytes())?;
         out.write_all(self.id.as_bytes())?;
-        let path = self.path(state);
-        let path_len: u16 = if path.len() >= entry::Flags::PATH_LEN.bits() as usize {
+        let path_len: u16 = if path_len >= entry::Flags::PATH_LEN.bits() as usize {
             entry::Flags::PATH_LEN.bits() as u16
         } else {
-            path.len()
+            path_len
                 .try_into()
                 .expect("we just checked that the length is smaller than 0xfff")
         };
@@ -31,7 +59,6 @@ impl Entry {
                     .to_be_bytes(),
             )?;
         }
-        out.write_all(path)?;
-        out.write_all(b"\0")
+        Ok(())
     }
 }
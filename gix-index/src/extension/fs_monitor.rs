@@ -1,20 +1,32 @@
 use bstr::BString;
 
 use crate::{
+    entry,
     extension::{FsMonitor, Signature},
     util::{read_u32, read_u64, split_at_byte_exclusive},
+    Entry,
 };
 
-#[derive(Clone)]
-#[allow(dead_code)]
+/// The token identifying the point in time of the last query to the filesystem monitor, used to ask it for
+/// all changes since then.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
-    V1 { nanos_since_1970: u64 },
-    V2 { token: BString },
+    /// The token of version 1 of the hook protocol, the time of the last query.
+    V1 {
+        /// The amount of nanoseconds since the unix epoch.
+        nanos_since_1970: u64,
+    },
+    /// The token of version 2 of the hook protocol, an opaque string chosen by the filesystem monitor.
+    V2 {
+        /// The token as returned by the filesystem monitor.
+        token: BString,
+    },
 }
 
+/// The signature of the fsmonitor extension.
 pub const SIGNATURE: Signature = *b"FSMN";
 
-pub fn decode(data: &[u8]) -> Option<FsMonitor> {
+pub(crate) fn decode(data: &[u8]) -> Option<FsMonitor> {
     let (version, data) = read_u32(data)?;
     let (token, data) = match version {
         1 => {
@@ -37,3 +49,75 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
 
     FsMonitor { token, entry_dirty }.into()
 }
+
+impl FsMonitor {
+    /// Create a new instance with `token`, marking all `entries` as dirty unless they have the
+    /// [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag set.
+    pub fn from_entries(token: Token, entries: &[Entry]) -> Self {
+        FsMonitor {
+            token,
+            entry_dirty: gix_bitmap::ewah::Vec::from_set_bits(
+                entries.iter().enumerate().filter_map(|(index, entry)| {
+                    (!entry.flags.contains(entry::Flags::FSMONITOR_VALID)).then_some(index)
+                }),
+            ),
+        }
+    }
+
+    /// Return the token of the last query to the filesystem monitor.
+    pub fn token(&self) -> &Token {
+        &self.token
+    }
+
+    /// Return the bitmap with a set bit for each entry that was considered dirty by the filesystem monitor at the time of
+    /// the last query. Entries past the end of the bitmap are considered valid, just like `git` does it.
+    pub fn entry_dirty(&self) -> &gix_bitmap::ewah::Vec {
+        &self.entry_dirty
+    }
+
+    /// Return the bitmap of entries that are considered dirty, adjusted to skip over `entries` that are removed
+    /// while writing, or `None` if it doesn't fit the `entries` anymore. The latter would make `git` abort.
+    pub(crate) fn entry_dirty_for_writing(&self, entries: &[Entry]) -> Option<gix_bitmap::ewah::Vec> {
+        let mut dirty = vec![false; entries.len()];
+        self.entry_dirty.for_each_set_bit(|index| {
+            *dirty.get_mut(index)? = true;
+            Some(())
+        })?;
+        Some(gix_bitmap::ewah::Vec::from_set_bits(
+            entries
+                .iter()
+                .zip(dirty)
+                .filter(|(entry, _)| !entry.flags.contains(entry::Flags::REMOVE))
+                .enumerate()
+                .filter_map(|(index, (_, is_dirty))| is_dirty.then_some(index)),
+        ))
+    }
+}
+
+/// Serialize `fs_monitor` to `out`, using `entry_dirty` instead of its own bitmap to allow it to be adjusted to the entries
+/// that are actually written.
+pub(crate) fn write_to(
+    fs_monitor: &FsMonitor,
+    entry_dirty: &gix_bitmap::ewah::Vec,
+    mut out: impl std::io::Write,
+) -> std::io::Result<()> {
+    let mut buf = Vec::<u8>::with_capacity(4 + 8 + 4 + entry_dirty.size_in_bytes());
+    match &fs_monitor.token {
+        Token::V1 { nanos_since_1970 } => {
+            buf.extend_from_slice(&1_u32.to_be_bytes());
+            buf.extend_from_slice(&nanos_since_1970.to_be_bytes());
+        }
+        Token::V2 { token } => {
+            buf.extend_from_slice(&2_u32.to_be_bytes());
+            buf.extend_from_slice(token);
+            buf.push(0);
+        }
+    }
+    let ewah_size = u32::try_from(entry_dirty.size_in_bytes()).expect("less than 4GB bitmap");
+    buf.extend_from_slice(&ewah_size.to_be_bytes());
+    entry_dirty.write_to(&mut buf)?;
+
+    out.write_all(&SIGNATURE)?;
+    out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB fsmonitor extension")).to_be_bytes())?;
+    out.write_all(&buf)
+}
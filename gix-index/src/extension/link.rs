@@ -69,6 +69,20 @@ pub(crate) fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Result<Link, d
 }
 
 impl Link {
+    /// Serialize this instance to `out`.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        let mut buf = Vec::<u8>::with_capacity(self.shared_index_checksum.as_slice().len());
+        buf.extend_from_slice(self.shared_index_checksum.as_bytes());
+        if let Some(bitmaps) = &self.bitmaps {
+            bitmaps.delete.write_to(&mut buf)?;
+            bitmaps.replace.write_to(&mut buf)?;
+        }
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB link extension")).to_be_bytes())?;
+        out.write_all(&buf)
+    }
+
     pub(crate) fn dissolve_into(
         self,
         split_index: &mut crate::File,
@@ -42,26 +42,25 @@ pub struct Link {
 }
 
 /// The extension for untracked files.
-#[allow(dead_code)]
-#[derive(Clone)]
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
 pub struct UntrackedCache {
     /// Something identifying the location and machine that this cache is for.
     /// Should the repository be copied to a different machine, the entire cache can immediately be invalidated.
-    identifier: BString,
+    pub identifier: BString,
     /// Stat for the .git/info/exclude file
-    info_exclude: Option<untracked_cache::OidStat>,
+    pub info_exclude: Option<untracked_cache::OidStat>,
     /// Stat for the `core.excludesfile`
-    excludes_file: Option<untracked_cache::OidStat>,
+    pub excludes_file: Option<untracked_cache::OidStat>,
     /// Usually `.gitignore`
-    exclude_filename_per_dir: BString,
-    dir_flags: u32,
+    pub exclude_filename_per_dir: BString,
+    /// The flags of the directory walk that produced the cache, see [`untracked_cache::dir_flags`].
+    pub dir_flags: u32,
 
     /// A list of directories and sub-directories, with `directories[0]` being the root.
-    directories: Vec<untracked_cache::Directory>,
+    pub directories: Vec<untracked_cache::Directory>,
 }
 
 /// The extension for keeping state on recent information provided by the filesystem monitor.
-#[allow(dead_code)]
 #[derive(Clone)]
 pub struct FsMonitor {
     token: fs_monitor::Token,
@@ -71,7 +70,9 @@ pub struct FsMonitor {
 
 mod iter;
 
-pub(crate) mod fs_monitor;
+///
+#[allow(clippy::empty_docs)]
+pub mod fs_monitor;
 
 ///
 #[allow(clippy::empty_docs)]
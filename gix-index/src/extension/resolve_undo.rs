@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use bstr::BString;
 use gix_hash::ObjectId;
 
@@ -62,3 +64,22 @@ pub fn decode(mut data: &[u8], object_hash: gix_hash::Kind) -> Option<Paths> {
     }
     out.into()
 }
+
+pub fn write_to(paths: &Paths, mut out: impl std::io::Write) -> std::io::Result<()> {
+    let mut buf = Vec::<u8>::new();
+    for path in paths {
+        buf.extend_from_slice(&path.name);
+        buf.push(0);
+        for stage in &path.stages {
+            let mode = stage.map_or(0, |s| s.mode);
+            write!(buf, "{mode:o}\0")?;
+        }
+        for stage in path.stages.iter().flatten() {
+            buf.extend_from_slice(stage.id.as_bytes());
+        }
+    }
+
+    out.write_all(&SIGNATURE)?;
+    out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB resolve-undo extension")).to_be_bytes())?;
+    out.write_all(&buf)
+}
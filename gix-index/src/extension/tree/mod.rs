@@ -1,4 +1,6 @@
-use crate::extension::Signature;
+use bstr::{BStr, ByteSlice};
+
+use crate::extension::{Signature, Tree};
 
 /// The signature for tree extensions
 pub const SIGNATURE: Signature = *b"TREE";
@@ -12,11 +14,37 @@ pub use decode::decode;
 
 mod write;
 
+/// Mutation
+impl Tree {
+    /// Mark this tree and all trees leading to the entry at the repository-relative `path` as invalid,
+    /// as the entry was added, removed or changed.
+    /// If `path` itself is a known tree, it's removed entirely as it may have been replaced by a file.
+    ///
+    /// Invalid trees have to be recomputed from the index entries when writing trees, and can't be used to skip
+    /// their entries anymore.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        let mut tree = self;
+        tree.num_entries = None;
+        let mut components = path.split_str(b"/").peekable();
+        while let Some(name) = components.next() {
+            let Some(pos) = tree.children.iter().position(|child| child.name.as_slice() == name) else {
+                break;
+            };
+            if components.peek().is_none() {
+                tree.children.remove(pos);
+                break;
+            }
+            tree = &mut tree.children[pos];
+            tree.num_entries = None;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     #[test]
     fn size_of_tree() {
-        assert_eq!(std::mem::size_of::<crate::extension::Tree>(), 88);
+        assert_eq!(std::mem::size_of::<crate::extension::Tree>(), 104);
     }
 }
@@ -1,14 +1,14 @@
-use bstr::BString;
+use bstr::{BStr, BString};
 use gix_hash::ObjectId;
 
 use crate::{
     entry,
     extension::{Signature, UntrackedCache},
-    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int},
+    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int, write_var_int},
 };
 
 /// A structure to track filesystem stat information along with an object id, linking a worktree file with what's in our ODB.
-#[derive(Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct OidStat {
     /// The file system stat information
     pub stat: entry::Stat,
@@ -17,26 +17,74 @@ pub struct OidStat {
 }
 
 /// A directory with information about its untracked files, and its sub-directories
-#[derive(Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Directory {
     /// The directories name, or an empty string if this is the root directory.
     pub name: BString,
-    /// Untracked files and directory names
+    /// Untracked files and directory names, with the latter having a trailing slash.
     pub untracked_entries: Vec<BString>,
     /// indices for sub-directories similar to this one.
     pub sub_directories: Vec<usize>,
 
-    /// The directories stat data, if available or valid // TODO: or is it the exclude file?
+    /// The directories stat data at the time its untracked entries were recorded, or `None` if the directory is invalid
+    /// and needs to be read again.
     pub stat: Option<entry::Stat>,
     /// The oid of a .gitignore file, if it exists
     pub exclude_file_oid: Option<ObjectId>,
-    /// TODO: figure out what this really does
+    /// If `true`, the directory was only read to see if it contains untracked files, and `untracked_entries` may not be complete.
     pub check_only: bool,
 }
 
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
+/// Flags to describe the directory walk that produced an untracked cache, as stored in [`UntrackedCache::dir_flags`].
+pub mod dir_flags {
+    /// Untracked directories are listed with a trailing slash instead of listing their untracked files.
+    pub const SHOW_OTHER_DIRECTORIES: u32 = 1 << 1;
+    /// Untracked directories without untracked files aren't listed.
+    pub const HIDE_EMPTY_DIRECTORIES: u32 = 1 << 2;
+}
+
+impl UntrackedCache {
+    /// Invalidate the directory that contains the worktree-relative `rela_path`, typically because an entry with that path
+    /// was added to or removed from the index.
+    ///
+    /// If [untracked directories are shown](dir_flags::SHOW_OTHER_DIRECTORIES), all of its parent directories are invalidated
+    /// as well as they may list one of the directories leading to `rela_path`.
+    pub fn invalidate_path(&mut self, rela_path: &BStr) {
+        if self.directories.is_empty() {
+            return;
+        }
+        let invalidate_parents = self.dir_flags & dir_flags::SHOW_OTHER_DIRECTORIES != 0;
+        let mut dir_index = 0;
+        let mut components = rela_path.split(|b| *b == b'/').peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                self.invalidate_directory(dir_index);
+                break;
+            }
+            if invalidate_parents {
+                self.invalidate_directory(dir_index);
+            }
+            match self.directories[dir_index]
+                .sub_directories
+                .iter()
+                .find(|idx| self.directories[**idx].name == component)
+            {
+                Some(idx) => dir_index = *idx,
+                None => break,
+            }
+        }
+    }
+
+    fn invalidate_directory(&mut self, dir_index: usize) {
+        let dir = &mut self.directories[dir_index];
+        dir.stat = None;
+        dir.untracked_entries.clear();
+    }
+}
+
 // #[allow(unused)]
 /// Decode an untracked cache extension from `data`, assuming object hashes are of type `object_hash`.
 pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache> {
@@ -47,17 +95,19 @@ pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache
     let (identifier, data) = split_at_pos(data, identifier_len.try_into().ok()?)?;
 
     let hash_len = object_hash.len_in_bytes();
-    let (info_exclude, data) = decode_oid_stat(data, hash_len)?;
-    let (excludes_file, data) = decode_oid_stat(data, hash_len)?;
+    let (info_exclude_stat, data) = crate::decode::stat(data)?;
+    let (excludes_file_stat, data) = crate::decode::stat(data)?;
     let (dir_flags, data) = read_u32(data)?;
+    let (info_exclude_id, data) = split_at_pos(data, hash_len)?;
+    let (excludes_file_id, data) = split_at_pos(data, hash_len)?;
     let (exclude_filename_per_dir, data) = split_at_byte_exclusive(data, 0)?;
 
     let (num_directory_blocks, data) = var_int(data)?;
 
     let mut res = UntrackedCache {
         identifier: identifier.into(),
-        info_exclude: (!info_exclude.id.is_null()).then_some(info_exclude),
-        excludes_file: (!excludes_file.id.is_null()).then_some(excludes_file),
+        info_exclude: oid_stat(info_exclude_stat, info_exclude_id),
+        excludes_file: oid_stat(excludes_file_stat, excludes_file_id),
         exclude_filename_per_dir: exclude_filename_per_dir.into(),
         dir_flags,
         directories: Vec::new(),
@@ -141,14 +191,102 @@ fn decode_directory_block<'a>(data: &'a [u8], directories: &mut Vec<Directory>)
     data.into()
 }
 
-fn decode_oid_stat(data: &[u8], hash_len: usize) -> Option<(OidStat, &[u8])> {
-    let (stat, data) = crate::decode::stat(data)?;
-    let (hash, data) = split_at_pos(data, hash_len)?;
-    Some((
-        OidStat {
-            stat,
-            id: ObjectId::from_bytes_or_panic(hash),
-        },
-        data,
-    ))
+fn oid_stat(stat: entry::Stat, hash: &[u8]) -> Option<OidStat> {
+    let id = ObjectId::from_bytes_or_panic(hash);
+    (!id.is_null() || stat != entry::Stat::default()).then_some(OidStat { stat, id })
+}
+
+/// Serialize `cache` to `out`, assuming object hashes are of type `object_hash`.
+pub fn write_to(
+    cache: &UntrackedCache,
+    object_hash: gix_hash::Kind,
+    mut out: impl std::io::Write,
+) -> std::io::Result<()> {
+    let mut buf = Vec::<u8>::with_capacity(1024);
+    write_var_int(&mut buf, cache.identifier.len() as u64)?;
+    buf.extend_from_slice(&cache.identifier);
+
+    let null = object_hash.null();
+    let null_stat = entry::Stat::default();
+    let (info_exclude_stat, info_exclude_id) = cache
+        .info_exclude
+        .as_ref()
+        .map_or((&null_stat, &null), |s| (&s.stat, &s.id));
+    let (excludes_file_stat, excludes_file_id) = cache
+        .excludes_file
+        .as_ref()
+        .map_or((&null_stat, &null), |s| (&s.stat, &s.id));
+    crate::write::stat(&mut buf, info_exclude_stat)?;
+    crate::write::stat(&mut buf, excludes_file_stat)?;
+    buf.extend_from_slice(&cache.dir_flags.to_be_bytes());
+    buf.extend_from_slice(info_exclude_id.as_bytes());
+    buf.extend_from_slice(excludes_file_id.as_bytes());
+    buf.extend_from_slice(&cache.exclude_filename_per_dir);
+    buf.push(0);
+
+    write_var_int(&mut buf, cache.directories.len() as u64)?;
+    if !cache.directories.is_empty() {
+        let mut state = WriteState::default();
+        write_directory_block(&cache.directories, 0, &mut state, &mut buf)?;
+
+        gix_bitmap::ewah::Vec::from_set_bits(state.valid).write_to(&mut buf)?;
+        gix_bitmap::ewah::Vec::from_set_bits(state.check_only).write_to(&mut buf)?;
+        gix_bitmap::ewah::Vec::from_set_bits(state.hash_valid).write_to(&mut buf)?;
+        buf.extend_from_slice(&state.stats);
+        buf.extend_from_slice(&state.hashes);
+        // the safeguard for string lists, which is checked when decoding
+        buf.push(0);
+    }
+
+    out.write_all(&SIGNATURE)?;
+    out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB untracked cache extension")).to_be_bytes())?;
+    out.write_all(&buf)
+}
+
+#[derive(Default)]
+struct WriteState {
+    /// The index of the next directory to be written.
+    index: usize,
+    valid: Vec<usize>,
+    check_only: Vec<usize>,
+    hash_valid: Vec<usize>,
+    stats: Vec<u8>,
+    hashes: Vec<u8>,
+}
+
+fn write_directory_block(
+    directories: &[Directory],
+    directory_index: usize,
+    state: &mut WriteState,
+    out: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let dir = &directories[directory_index];
+    let index = state.index;
+    state.index += 1;
+
+    if dir.check_only {
+        state.check_only.push(index);
+    }
+    if let Some(stat) = &dir.stat {
+        state.valid.push(index);
+        crate::write::stat(&mut state.stats, stat)?;
+    }
+    if let Some(id) = &dir.exclude_file_oid {
+        state.hash_valid.push(index);
+        state.hashes.extend_from_slice(id.as_bytes());
+    }
+
+    write_var_int(&mut *out, dir.untracked_entries.len() as u64)?;
+    write_var_int(&mut *out, dir.sub_directories.len() as u64)?;
+    out.extend_from_slice(&dir.name);
+    out.push(0);
+    for name in &dir.untracked_entries {
+        out.extend_from_slice(name);
+        out.push(0);
+    }
+
+    for subdir_index in &dir.sub_directories {
+        write_directory_block(directories, *subdir_index, state, out)?;
+    }
+    Ok(())
 }
@@ -97,8 +97,14 @@ impl File {
 
         let (state, checksum) = State::from_bytes(&data, mtime, object_hash, options)?;
         let mut file = File { state, path, checksum };
-        if let Some(mut link) = file.link.take() {
+        if let Some(link) = file.link.take() {
+            let shared_index_checksum = link.shared_index_checksum;
             link.dissolve_into(&mut file, object_hash, skip_hash, options)?;
+            // Remember the shared index so it can be reused when writing the index back as split index.
+            file.link = Some(extension::Link {
+                shared_index_checksum,
+                bitmaps: None,
+            });
         }
 
         Ok(file)
@@ -1,6 +1,8 @@
+use std::{cmp::Ordering, path::Path};
+
 use gix_features::hash;
 
-use crate::{write, File, Version};
+use crate::{entry, extension, write, Entry, File, PathStorage, State, Version};
 
 /// The error produced by [`File::write()`].
 #[derive(Debug, thiserror::Error)]
@@ -14,45 +16,234 @@ pub enum Error {
     CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
 }
 
+/// The percentage of entries that may not be shared with the shared index before a new shared index is written,
+/// the default of `splitIndex.maxPercentChange`.
+const MAX_PERCENT_CHANGE: usize = 20;
+
 impl File {
     /// Write the index to `out` with `options`, to be readable by [`File::at()`], returning the version that was actually written
     /// to retain all information of this index.
+    ///
+    /// Note that a split index is written with all entries of its shared index, use [`File::write()`] to keep it split.
     pub fn write_to(
         &self,
-        mut out: impl std::io::Write,
+        out: impl std::io::Write,
         options: write::Options,
     ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
-        let _span = gix_features::trace::detail!("gix_index::File::write_to()", skip_hash = options.skip_hash);
-        let (version, hash) = if options.skip_hash {
-            let out: &mut dyn std::io::Write = &mut out;
-            let version = self.state.write_to(out, options)?;
-            (version, self.state.object_hash.null())
-        } else {
-            let mut hasher = hash::Write::new(&mut out, self.state.object_hash);
-            let out: &mut dyn std::io::Write = &mut hasher;
-            let version = self.state.write_to(out, options)?;
-            (version, gix_hash::ObjectId::from(hasher.hash.digest()))
-        };
-        out.write_all(hash.as_slice())?;
-        Ok((version, hash))
+        self.write_to_inner(out, options, None)
     }
 
     /// Write ourselves to the path we were read from after acquiring a lock, using `options`.
     ///
+    /// If we were read from a split index, the entries that aren't in its shared index are written along with a `link`
+    /// extension to the shared index. If too many entries changed compared to the shared index, or if it can't be read,
+    /// a new shared index is written next to our path first.
+    ///
     /// Note that the hash produced will be stored which is why we need to be mutable.
     pub fn write(&mut self, options: write::Options) -> Result<(), Error> {
         let _span = gix_features::trace::detail!("gix_index::File::write()", path = ?self.path);
+        let split = match self.state.link.as_ref() {
+            Some(link) if link.bitmaps.is_none() => Some(self.prepare_split_index(link.shared_index_checksum)?),
+            _ => None,
+        };
         let mut lock = std::io::BufWriter::with_capacity(
             64 * 1024,
             gix_lock::File::acquire_to_update_resource(&self.path, gix_lock::acquire::Fail::Immediately, None)?,
         );
-        let (version, digest) = self.write_to(&mut lock, options)?;
+        let (version, digest) = self.write_to_inner(
+            &mut lock,
+            options,
+            split.as_ref().map(|split| write::SplitIndex {
+                link: &split.link,
+                entries: &split.entries,
+                path_backing: &split.path_backing,
+            }),
+        )?;
         match lock.into_inner() {
             Ok(lock) => lock.commit()?,
             Err(err) => return Err(err.into_error().into()),
         };
         self.state.version = version;
         self.checksum = Some(digest);
+        if let Some(split) = split {
+            self.state.link = Some(extension::Link {
+                shared_index_checksum: split.link.shared_index_checksum,
+                bitmaps: None,
+            });
+        }
         Ok(())
     }
+
+    fn write_to_inner(
+        &self,
+        mut out: impl std::io::Write,
+        options: write::Options,
+        split_index: Option<write::SplitIndex<'_>>,
+    ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
+        let _span = gix_features::trace::detail!("gix_index::File::write_to()", skip_hash = options.skip_hash);
+        let (version, hash) = if options.skip_hash {
+            let out: &mut dyn std::io::Write = &mut out;
+            let version = self.state.write_to_inner(out, options, split_index)?;
+            (version, self.state.object_hash.null())
+        } else {
+            let mut hasher = hash::Write::new(&mut out, self.state.object_hash);
+            let out: &mut dyn std::io::Write = &mut hasher;
+            let version = self.state.write_to_inner(out, options, split_index)?;
+            (version, hasher.hash.digest())
+        };
+        out.write_all(hash.as_slice())?;
+        Ok((version, hash))
+    }
+
+    /// Compute the entries that differ from the shared index with `shared_index_checksum`, or write a new shared index
+    /// if that isn't possible or worth it.
+    fn prepare_split_index(&self, shared_index_checksum: gix_hash::ObjectId) -> Result<SplitIndex, Error> {
+        let dir = self.path.parent().expect("index file in a directory");
+        let shared_index = File::at(
+            shared_index_path(dir, shared_index_checksum),
+            self.state.object_hash,
+            false,
+            crate::decode::Options {
+                expected_checksum: Some(shared_index_checksum),
+                ..Default::default()
+            },
+        )
+        .ok();
+        if let Some(split) = shared_index.and_then(|shared_index| {
+            SplitIndex::from_shared_index(&self.state, &shared_index.state, shared_index_checksum)
+        }) {
+            return Ok(split);
+        }
+
+        let shared_index_checksum = self.write_shared_index(dir)?;
+        Ok(SplitIndex {
+            link: extension::Link {
+                shared_index_checksum,
+                bitmaps: Some(extension::link::Bitmaps {
+                    delete: gix_bitmap::ewah::Vec::from_set_bits(None),
+                    replace: gix_bitmap::ewah::Vec::from_set_bits(None),
+                }),
+            },
+            entries: Vec::new(),
+            path_backing: Vec::new(),
+        })
+    }
+
+    /// Write all of our entries into a new shared index in `dir`, without any extension, and return its checksum.
+    fn write_shared_index(&self, dir: &Path) -> Result<gix_hash::ObjectId, Error> {
+        let mut buf = Vec::new();
+        let mut hasher = hash::Write::new(&mut buf, self.state.object_hash);
+        self.state.write_to_inner(
+            &mut hasher,
+            write::Options {
+                extensions: write::Extensions::None,
+                skip_hash: false,
+            },
+            None,
+        )?;
+        let checksum = hasher.hash.digest();
+        buf.extend_from_slice(checksum.as_slice());
+
+        let mut lock = gix_lock::File::acquire_to_update_resource(
+            shared_index_path(dir, checksum),
+            gix_lock::acquire::Fail::Immediately,
+            None,
+        )?;
+        std::io::Write::write_all(&mut lock, &buf)?;
+        lock.commit()?;
+        Ok(checksum)
+    }
+}
+
+fn shared_index_path(dir: &Path, checksum: gix_hash::ObjectId) -> std::path::PathBuf {
+    dir.join(format!("sharedindex.{checksum}"))
+}
+
+/// The entries to write into a split index, relative to its shared index.
+struct SplitIndex {
+    link: extension::Link,
+    entries: Vec<Entry>,
+    path_backing: PathStorage,
+}
+
+impl SplitIndex {
+    /// Compare the entries in `state` to the ones in `shared`, and return `None` if too many of them differ.
+    ///
+    /// Replaced entries are stored without path in order of the shared entries they replace, followed by all new entries.
+    fn from_shared_index(state: &State, shared: &State, shared_index_checksum: gix_hash::ObjectId) -> Option<Self> {
+        let mut delete = Vec::new();
+        let mut replace = Vec::new();
+        let mut entries = Vec::new();
+        let mut new_entries = Vec::new();
+
+        let mut ours = state
+            .entries
+            .iter()
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            .peekable();
+        let cmp = |ours: &Entry, shared_entry: &Entry| {
+            Entry::cmp_filepaths(ours.path(state), shared_entry.path(shared))
+                .then_with(|| ours.stage().cmp(&shared_entry.stage()))
+        };
+        for (shared_index, shared_entry) in shared.entries.iter().enumerate() {
+            while let Some(entry) = ours.next_if(|e| cmp(e, shared_entry) == Ordering::Less) {
+                new_entries.push(entry);
+            }
+            match ours.next_if(|e| cmp(e, shared_entry) == Ordering::Equal) {
+                Some(entry) => {
+                    if !is_same_on_disk(entry, shared_entry) {
+                        replace.push(shared_index);
+                        entries.push(Entry {
+                            path: 0..0,
+                            ..entry.clone()
+                        });
+                    }
+                }
+                None => delete.push(shared_index),
+            }
+        }
+        new_entries.extend(ours);
+
+        let num_entries = state.entries.len()
+            - state
+                .entries
+                .iter()
+                .filter(|e| e.flags.contains(entry::Flags::REMOVE))
+                .count();
+        let num_not_shared = entries.len() + new_entries.len();
+        if num_not_shared * 100 > num_entries * MAX_PERCENT_CHANGE {
+            return None;
+        }
+
+        let mut path_backing = Vec::new();
+        entries.extend(new_entries.into_iter().map(|entry| {
+            let path = entry.path(state);
+            let start = path_backing.len();
+            path_backing.extend_from_slice(path);
+            Entry {
+                path: start..path_backing.len(),
+                ..entry.clone()
+            }
+        }));
+        Some(SplitIndex {
+            link: extension::Link {
+                shared_index_checksum,
+                bitmaps: Some(extension::link::Bitmaps {
+                    delete: gix_bitmap::ewah::Vec::from_set_bits(delete),
+                    replace: gix_bitmap::ewah::Vec::from_set_bits(replace),
+                }),
+            },
+            entries,
+            path_backing,
+        })
+    }
+}
+
+/// Return `true` if `a` and `b` would be serialized the same, ignoring their path.
+fn is_same_on_disk(a: &Entry, b: &Entry) -> bool {
+    a.stat == b.stat
+        && a.id == b.id
+        && a.mode == b.mode
+        && a.flags.to_storage().bits() == b.flags.to_storage().bits()
+        && entry::at_rest::FlagsExtended::from_flags(a.flags) == entry::at_rest::FlagsExtended::from_flags(b.flags)
 }
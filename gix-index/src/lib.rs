@@ -26,6 +26,10 @@ pub mod entry;
 
 mod access;
 
+///
+#[allow(clippy::empty_docs)]
+pub mod sparse;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod init;
@@ -201,6 +205,23 @@ pub(crate) mod util {
         (num, data).into()
     }
 
+    #[inline]
+    pub fn write_var_int(mut out: impl std::io::Write, mut n: u64) -> std::io::Result<()> {
+        let mut buf = [0u8; 10];
+        let mut bytes_written = 1;
+        buf[buf.len() - 1] = n as u8 & 0b0111_1111;
+        for byte in buf.iter_mut().rev().skip(1) {
+            n >>= 7;
+            if n == 0 {
+                break;
+            }
+            n -= 1;
+            *byte = 0b1000_0000 | (n as u8 & 0b0111_1111);
+            bytes_written += 1;
+        }
+        out.write_all(&buf[buf.len() - bytes_written..])
+    }
+
     #[inline]
     pub fn read_u32(data: &[u8]) -> Option<(u32, &[u8])> {
         split_at_pos(data, 4).map(|(num, data)| (u32::from_be_bytes(num.try_into().unwrap()), data))
@@ -244,7 +265,7 @@ pub(crate) mod util {
 
 #[test]
 fn size_of_entry() {
-    assert_eq!(std::mem::size_of::<crate::Entry>(), 80);
+    assert_eq!(std::mem::size_of::<crate::Entry>(), 96);
 
     // the reason we have our own time is half the size.
     assert_eq!(std::mem::size_of::<crate::entry::stat::Time>(), 8);
@@ -0,0 +1,308 @@
+/// Configuration related to sparse indexes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// If true, certain entries in the index will be excluded / skipped for certain operations,
+    /// based on the ignore patterns in the `.git/info/sparse-checkout` file. These entries will
+    /// carry the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This typically is the value of `core.sparseCheckout` in the git configuration.
+    pub sparse_checkout: bool,
+
+    /// Interpret the `.git/info/sparse-checkout` file using _cone mode_.
+    ///
+    /// If true, _cone mode_ is active and entire directories will be included in the checkout, as well as files in the root
+    /// of the repository.
+    /// If false, non-cone mode is active and entries to _include_ will be matched with patterns like those found in `.gitignore` files.
+    ///
+    /// This typically is the value of `core.sparseCheckoutCone` in the git configuration.
+    pub directory_patterns_only: bool,
+
+    /// If true, will attempt to write a sparse index file which only works in cone mode.
+    ///
+    /// A sparse index has [`DIR` entries][crate::entry::Mode::DIR] that represent entire directories to be skipped
+    /// during checkout and other operations due to the added presence of
+    /// the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    ///
+    /// This is typically the value of `index.sparse` in the git configuration.
+    pub write_sparse_index: bool,
+}
+
+impl Options {
+    /// Derive a valid mode from all parameters that affect the 'sparseness' of the index.
+    ///
+    /// Some combinations of them degenerate to one particular mode.
+    pub fn sparse_mode(&self) -> Mode {
+        match (
+            self.sparse_checkout,
+            self.directory_patterns_only,
+            self.write_sparse_index,
+        ) {
+            (true, true, true) => Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+            (true, true, false) => Mode::IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+            (true, false, _) => Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+            (false, _, _) => Mode::Disabled,
+        }
+    }
+}
+
+/// Describes the configuration how a sparse index should be written, or if one should be written at all.
+#[derive(Debug)]
+pub enum Mode {
+    /// index with DIR entries for exclusion and included entries, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
+    /// index with all file entries and skip worktree flags for exclusion, directory-only include patterns in `.git/info/sparse-checkout` file.
+    IncludeDirectoriesStoreAllEntriesSkipUnmatched,
+    /// index with all file entries and skip-worktree flags for exclusion, `ignore` patterns to include entries in `.git/info/sparse-checkout` file.
+    IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
+    /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
+    Disabled,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod expand {
+    /// The error returned by [State::expand_sparse_directories()](crate::State::expand_sparse_directories()).
+    pub type Error = crate::init::from_tree::Error;
+}
+
+mod function {
+    use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+    use crate::{
+        entry::{Flags, Mode, Stage, Stat},
+        Entry, State,
+    };
+
+    /// Sparse directories
+    impl State {
+        /// Replace each [sparse directory entry](Mode::is_sparse()) for which `should_expand(directory)` returns `true`
+        /// with the entries of the tree it points to, recursively, using `objects` to access trees.
+        /// `directory` is the repository-relative path of the sparse directory without its trailing slash,
+        /// and `validate` controls how path components of the expanded entries are validated.
+        ///
+        /// All expanded entries are marked with the [`SKIP_WORKTREE`](Flags::SKIP_WORKTREE) flag as they are outside
+        /// of the worktree, just like the directory they were contained in.
+        /// Return the amount of expanded directories.
+        ///
+        /// Note that the index ceases to be sparse once no sparse directory entry is left.
+        pub fn expand_sparse_directories<Find>(
+            &mut self,
+            objects: Find,
+            validate: gix_validate::path::component::Options,
+            mut should_expand: impl FnMut(&BStr) -> bool,
+        ) -> Result<usize, super::expand::Error>
+        where
+            Find: gix_object::Find,
+        {
+            let needs_expansion =
+                |e: &Entry, paths: &crate::PathStorageRef, should_expand: &mut dyn FnMut(&BStr) -> bool| {
+                    e.mode.is_sparse() && should_expand(directory_path(e.path_in(paths)))
+                };
+            if !self
+                .entries
+                .iter()
+                .any(|e| needs_expansion(e, &self.path_backing, &mut should_expand))
+            {
+                return Ok(0);
+            }
+
+            let _span = gix_features::trace::coarse!("gix_index::State::expand_sparse_directories()");
+            let mut num_expanded = 0;
+            let mut entries = Vec::with_capacity(self.entries.len());
+            for entry in &self.entries {
+                if !needs_expansion(entry, &self.path_backing, &mut should_expand) {
+                    entries.push(entry.clone());
+                    continue;
+                }
+                let directory: BString = directory_path(entry.path_in(&self.path_backing)).to_owned();
+                let expanded = State::from_tree(&entry.id, &objects, validate)?;
+                for expanded_entry in expanded.entries() {
+                    let path_start = self.path_backing.len();
+                    self.path_backing.push_str(&directory);
+                    self.path_backing.push(b'/');
+                    self.path_backing.push_str(expanded_entry.path(&expanded));
+                    let mut flags = expanded_entry.flags;
+                    flags.set_skip_worktree(true);
+                    entries.push(Entry {
+                        stat: Stat::default(),
+                        id: expanded_entry.id,
+                        flags,
+                        mode: expanded_entry.mode,
+                        path: path_start..self.path_backing.len(),
+                    });
+                }
+                num_expanded += 1;
+            }
+            self.entries = entries;
+            self.is_sparse = self.entries.iter().any(|e| e.mode.is_sparse());
+            Ok(num_expanded)
+        }
+
+        /// Replace all entries of directories for which `should_collapse(directory)` returns `true` with a single
+        /// [sparse directory entry](Mode::DIR) pointing to the tree of the directory, turning this index into a sparse index.
+        /// `directory` is the repository-relative path of the directory without trailing slash, and typically a directory
+        /// outside of the sparse-checkout cone.
+        ///
+        /// A directory is only collapsed if all of its entries are [excluded from the worktree](Flags::SKIP_WORKTREE),
+        /// unconflicted and not submodules. Otherwise, its sub-directories are tried instead.
+        /// The trees of collapsed directories are computed from their entries and passed to `write_tree()`, which is
+        /// expected to write them into the object database and return their id, so they can be expanded later.
+        ///
+        /// Return the amount of directories that were collapsed.
+        pub fn collapse_sparse_directories<E>(
+            &mut self,
+            mut should_collapse: impl FnMut(&BStr) -> bool,
+            mut write_tree: impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+        ) -> Result<usize, E> {
+            let _span = gix_features::trace::coarse!("gix_index::State::collapse_sparse_directories()");
+            let mut collapse = Collapse {
+                entries: std::mem::take(&mut self.entries),
+                path_backing: &mut self.path_backing,
+                out: Vec::new(),
+                num_collapsed: 0,
+            };
+            let res = collapse.range(0..collapse.entries.len(), 0, &mut should_collapse, &mut write_tree);
+            let Collapse {
+                entries,
+                out,
+                num_collapsed,
+                ..
+            } = collapse;
+            if let Err(err) = res {
+                self.entries = entries;
+                return Err(err);
+            }
+            self.entries = out;
+            self.is_sparse = true;
+            Ok(num_collapsed)
+        }
+    }
+
+    struct Collapse<'a> {
+        entries: Vec<Entry>,
+        path_backing: &'a mut Vec<u8>,
+        out: Vec<Entry>,
+        num_collapsed: usize,
+    }
+
+    impl Collapse<'_> {
+        /// Collapse all entries in `range`, which are in a directory whose path with trailing slash is `dir_len` bytes long.
+        fn range<E>(
+            &mut self,
+            range: std::ops::Range<usize>,
+            dir_len: usize,
+            should_collapse: &mut dyn FnMut(&BStr) -> bool,
+            write_tree: &mut dyn FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+        ) -> Result<(), E> {
+            let mut idx = range.start;
+            while idx < range.end {
+                let entry = &self.entries[idx];
+                let path = entry.path_in(self.path_backing);
+                let Some(subdir_len) = sub_directory_len(path, dir_len, entry.mode).filter(|len| len + 1 < path.len())
+                else {
+                    self.out.push(entry.clone());
+                    idx += 1;
+                    continue;
+                };
+                let end = idx
+                    + self.entries[idx..range.end]
+                        .iter()
+                        .take_while(|e| {
+                            let p = e.path_in(self.path_backing);
+                            p.len() > subdir_len && p[..=subdir_len] == path[..=subdir_len]
+                        })
+                        .count();
+                let directory = path[..subdir_len].as_bstr();
+                if should_collapse(directory) && self.entries[idx..end].iter().all(is_collapsible) {
+                    let directory: BString = directory.to_owned();
+                    let id = self.write_tree(idx..end, subdir_len + 1, write_tree)?;
+                    let path_start = self.path_backing.len();
+                    self.path_backing.extend_from_slice(&directory);
+                    self.path_backing.push(b'/');
+                    let mut flags = Flags::empty();
+                    flags.set_skip_worktree(true);
+                    self.out.push(Entry {
+                        stat: Stat::default(),
+                        id,
+                        flags,
+                        mode: Mode::DIR,
+                        path: path_start..self.path_backing.len(),
+                    });
+                    self.num_collapsed += 1;
+                } else {
+                    self.range(idx..end, subdir_len + 1, should_collapse, write_tree)?;
+                }
+                idx = end;
+            }
+            Ok(())
+        }
+
+        /// Write the tree for all entries in `range`, which are in a directory whose path with trailing slash is `dir_len` bytes long.
+        fn write_tree<E>(
+            &self,
+            range: std::ops::Range<usize>,
+            dir_len: usize,
+            write_tree: &mut dyn FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+        ) -> Result<gix_hash::ObjectId, E> {
+            let mut tree = gix_object::Tree::empty();
+            let mut idx = range.start;
+            while idx < range.end {
+                let entry = &self.entries[idx];
+                let path = entry.path_in(self.path_backing);
+                match sub_directory_len(path, dir_len, entry.mode) {
+                    None => {
+                        tree.entries.push(gix_object::tree::Entry {
+                            mode: entry
+                                .mode
+                                .to_tree_entry_mode()
+                                .expect("collapsible entries have valid modes"),
+                            filename: path[dir_len..].into(),
+                            oid: entry.id,
+                        });
+                        idx += 1;
+                    }
+                    Some(subdir_len) => {
+                        let (end, id) = if entry.mode.is_sparse() {
+                            (idx + 1, entry.id)
+                        } else {
+                            let end = idx
+                                + self.entries[idx..range.end]
+                                    .iter()
+                                    .take_while(|e| {
+                                        let p = e.path_in(self.path_backing);
+                                        p.len() > subdir_len && p[..=subdir_len] == path[..=subdir_len]
+                                    })
+                                    .count();
+                            (end, self.write_tree(idx..end, subdir_len + 1, write_tree)?)
+                        };
+                        tree.entries.push(gix_object::tree::Entry {
+                            mode: gix_object::tree::EntryKind::Tree.into(),
+                            filename: path[dir_len..subdir_len].into(),
+                            oid: id,
+                        });
+                        idx = end;
+                    }
+                }
+            }
+            tree.entries.sort();
+            write_tree(&tree)
+        }
+    }
+
+    /// Return the length of the path of the sub-directory of the directory whose path with trailing slash is `dir_len` bytes long,
+    /// if `path` is in such a sub-directory, or `None` if it's directly contained in the directory.
+    /// Sparse directory entries are considered to be a sub-directory as well.
+    fn sub_directory_len(path: &BStr, dir_len: usize, mode: Mode) -> Option<usize> {
+        let slash_pos = dir_len + path[dir_len..].find_byte(b'/')?;
+        (slash_pos + 1 < path.len() || mode.is_sparse()).then_some(slash_pos)
+    }
+
+    fn is_collapsible(entry: &Entry) -> bool {
+        entry.flags.contains(Flags::SKIP_WORKTREE) && entry.stage() == Stage::Unconflicted && !entry.mode.is_submodule()
+    }
+
+    /// Sparse directory entries are stored with a trailing slash, which we strip here.
+    fn directory_path(path: &BStr) -> &BStr {
+        path.strip_suffix(b"/").unwrap_or(path).as_bstr()
+    }
+}
@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::{entry, extension, write::util::CountBytes, State, Version};
+use crate::{entry, extension, write::util::CountBytes, Entry, PathStorageRef, State, Version};
 
 /// A way to specify which of the optional extensions to write.
 #[derive(Default, Debug, Copy, Clone)]
@@ -19,6 +19,12 @@ pub enum Extensions {
         tree_cache: bool,
         /// Write the end-of-index-entry extension.
         end_of_index_entry: bool,
+        /// Write the resolve-undo extension, if present.
+        resolve_undo: bool,
+        /// Write the untracked-cache extension, if present.
+        untracked_cache: bool,
+        /// Write the filesystem-monitor extension, if present.
+        fs_monitor: bool,
     },
     /// Write no optional extension at all for what should be the smallest possible index
     None,
@@ -33,9 +39,15 @@ impl Extensions {
             Extensions::Given {
                 tree_cache,
                 end_of_index_entry,
+                resolve_undo,
+                untracked_cache,
+                fs_monitor,
             } => match signature {
                 extension::tree::SIGNATURE => tree_cache,
                 extension::end_of_index_entry::SIGNATURE => end_of_index_entry,
+                extension::resolve_undo::SIGNATURE => resolve_undo,
+                extension::untracked_cache::SIGNATURE => untracked_cache,
+                extension::fs_monitor::SIGNATURE => fs_monitor,
                 _ => &false,
             }
             .then(|| signature),
@@ -45,7 +57,8 @@ impl Extensions {
 
 /// The options for use when [writing an index][State::write_to()].
 ///
-/// Note that default options write either index V2 or V3 depending on the content of the entries.
+/// Note that default options write either index V2 or V3 depending on the content of the entries, unless the index
+/// is at [version 4](Version::V4) already.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Options {
     /// Configures which extensions to write.
@@ -58,36 +71,57 @@ pub struct Options {
     pub skip_hash: bool,
 }
 
+/// The entries to write in place of the ones in [`State`] when writing the split index that is linked to a shared index.
+pub(crate) struct SplitIndex<'a> {
+    /// The link extension with its bitmaps to describe how `entries` relate to the shared index.
+    pub link: &'a extension::Link,
+    /// The entries that replace entries in the shared index, followed by all entries that aren't in the shared index.
+    pub entries: &'a [Entry],
+    /// The storage for the paths of `entries`.
+    pub path_backing: &'a PathStorageRef,
+}
+
 impl State {
     /// Serialize this instance to `out` with [`options`][Options].
-    pub fn write_to(
+    ///
+    /// Note that the `link` extension is only written if it was decoded as part of this state, i.e. if this state is
+    /// a split index whose entries were not merged with those of its shared index.
+    pub fn write_to(&self, out: impl std::io::Write, options: Options) -> std::io::Result<Version> {
+        self.write_to_inner(out, options, None)
+    }
+
+    pub(crate) fn write_to_inner(
         &self,
         out: impl std::io::Write,
         Options {
             extensions,
             skip_hash: _,
         }: Options,
+        split_index: Option<SplitIndex<'_>>,
     ) -> std::io::Result<Version> {
         let _span = gix_features::trace::detail!("gix_index::State::write()");
         let version = self.detect_required_version();
 
+        let (entries_to_write, path_backing, link) = match split_index {
+            Some(split) => (split.entries, split.path_backing, Some(split.link)),
+            None => (
+                self.entries.as_slice(),
+                self.path_backing.as_slice(),
+                self.link.as_ref().filter(|link| link.bitmaps.is_some()),
+            ),
+        };
+
         let mut write = CountBytes::new(out);
-        let num_entries: u32 = self
-            .entries()
-            .len()
-            .try_into()
-            .expect("definitely not 4billion entries");
-        let removed_entries: u32 = self
-            .entries()
+        let num_entries: u32 = entries_to_write
             .iter()
-            .filter(|e| e.flags.contains(entry::Flags::REMOVE))
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
             .count()
             .try_into()
-            .expect("definitely not too many entries");
+            .expect("definitely not 4billion entries");
 
-        let offset_to_entries = header(&mut write, version, num_entries - removed_entries)?;
-        let offset_to_extensions = entries(&mut write, self, offset_to_entries)?;
-        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions)?;
+        let offset_to_entries = header(&mut write, version, num_entries)?;
+        let offset_to_extensions = entries(&mut write, entries_to_write, path_backing, version, offset_to_entries)?;
+        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions, link)?;
 
         if num_entries > 0
             && extensions
@@ -106,17 +140,45 @@ impl State {
         mut write: CountBytes<T>,
         offset_to_extensions: u32,
         extensions: Extensions,
+        link: Option<&extension::Link>,
     ) -> std::io::Result<(Vec<(extension::Signature, u32)>, T)>
     where
         T: std::io::Write,
     {
         type WriteExtFn<'a> = &'a dyn Fn(&mut dyn std::io::Write) -> Option<std::io::Result<extension::Signature>>;
         let extensions: &[WriteExtFn<'_>] = &[
+            &|write| link.map(|link| link.write_to(write).map(|_| extension::link::SIGNATURE)),
             &|write| {
                 extensions
                     .should_write(extension::tree::SIGNATURE)
                     .and_then(|signature| self.tree().map(|tree| tree.write_to(write).map(|_| signature)))
             },
+            &|write| {
+                extensions
+                    .should_write(extension::resolve_undo::SIGNATURE)
+                    .and_then(|signature| {
+                        self.resolve_undo()
+                            .map(|paths| extension::resolve_undo::write_to(paths, write).map(|_| signature))
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::untracked_cache::SIGNATURE)
+                    .and_then(|signature| {
+                        self.untracked().map(|cache| {
+                            extension::untracked_cache::write_to(cache, self.object_hash, write).map(|_| signature)
+                        })
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::fs_monitor::SIGNATURE)
+                    .and_then(|signature| {
+                        let fs_monitor = self.fs_monitor()?;
+                        let entry_dirty = fs_monitor.entry_dirty_for_writing(&self.entries)?;
+                        Some(extension::fs_monitor::write_to(fs_monitor, &entry_dirty, write).map(|_| signature))
+                    })
+            },
             &|write| {
                 self.is_sparse()
                     .then(|| extension::sparse::write_to(write).map(|_| extension::sparse::SIGNATURE))
@@ -139,6 +201,9 @@ impl State {
 
 impl State {
     fn detect_required_version(&self) -> Version {
+        if self.version == Version::V4 {
+            return Version::V4;
+        }
         self.entries
             .iter()
             .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
@@ -164,12 +229,25 @@ fn header<T: std::io::Write>(
     Ok(out.count)
 }
 
-fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_size: u32) -> Result<u32, std::io::Error> {
-    for entry in state.entries() {
+fn entries<T: std::io::Write>(
+    out: &mut CountBytes<T>,
+    entries: &[Entry],
+    path_backing: &PathStorageRef,
+    version: Version,
+    header_size: u32,
+) -> Result<u32, std::io::Error> {
+    let mut previous_path: &[u8] = &[];
+    for entry in entries {
         if entry.flags.contains(entry::Flags::REMOVE) {
             continue;
         }
-        entry.write_to(&mut *out, state)?;
+        let path = entry.path_in(path_backing);
+        if version == Version::V4 {
+            entry.write_to_with_prefix_compression(&mut *out, path, previous_path)?;
+            previous_path = path;
+            continue;
+        }
+        entry.write_stat_id_flags_and_path(&mut *out, path)?;
         match (out.count - header_size) % 8 {
             0 => {}
             n => {
@@ -182,6 +260,18 @@ fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_siz
     Ok(out.count)
 }
 
+pub(crate) fn stat(mut out: impl std::io::Write, stat: &entry::Stat) -> std::io::Result<()> {
+    out.write_all(&stat.ctime.secs.to_be_bytes())?;
+    out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
+    out.write_all(&stat.mtime.secs.to_be_bytes())?;
+    out.write_all(&stat.mtime.nsecs.to_be_bytes())?;
+    out.write_all(&stat.dev.to_be_bytes())?;
+    out.write_all(&stat.ino.to_be_bytes())?;
+    out.write_all(&stat.uid.to_be_bytes())?;
+    out.write_all(&stat.gid.to_be_bytes())?;
+    out.write_all(&stat.size.to_be_bytes())
+}
+
 mod util {
     pub struct CountBytes<T> {
         pub count: u32,
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+export GIT_INDEX_VERSION=2
+git init -q --object-format=sha256
+git config index.recordEndOfIndexEntries true
+
+mkdir dir
+touch a dir/b dir/c
+git add .
+git commit -m "empty"
+
+git rev-parse @^{tree} > head.tree
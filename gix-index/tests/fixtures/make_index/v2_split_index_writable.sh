@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+export GIT_INDEX_VERSION=2
+git init -q
+git config index.threads 1
+
+for i in $(seq 0 11); do
+  echo $i > f$i
+done
+git add .
+git commit -q -m "initial"
+
+git update-index --split-index
+git config splitIndex.maxPercentChange 100
+
+echo new > g
+git add g
@@ -0,0 +1,36 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# An index with the resolve-undo, untracked-cache and fsmonitor extensions, using path-prefix compression.
+git init -q
+git config index.threads 1
+
+mkdir -p dir/sub untracked-dir
+touch a b dir/c dir/sub/d untracked untracked-dir/e dir/untracked
+echo "ignored" > .gitignore
+echo base > conflict
+git add a b conflict dir .gitignore
+git commit -q -m "initial"
+
+git checkout -q -b other
+echo other > conflict
+git commit -q -am "other"
+git checkout -q -
+echo ours > conflict
+git commit -q -am "ours"
+git merge other >/dev/null || true
+echo resolved > conflict
+git add conflict
+
+git config core.untrackedCache true
+cat > fsmonitor-hook <<'HOOK'
+#!/bin/sh
+printf 'token-1\0dir/c\0b\0'
+HOOK
+chmod +x fsmonitor-hook
+git config core.fsmonitor "$PWD/fsmonitor-hook"
+git config core.fsmonitorHookVersion 2
+
+git status --porcelain >/dev/null
+git status --porcelain >/dev/null
+git update-index --index-version 4
@@ -214,6 +214,144 @@ fn remove_entries() {
     file.remove_entries(|_, _, _| unreachable!("should not be called"));
 }
 
+#[test]
+fn remove_entries_invalidates_untracked_cache() {
+    use gix_index::extension::{untracked_cache::Directory, UntrackedCache};
+    let mut file = Fixture::Loose("conflicting-file").open();
+    let valid_dir = |name: &str, sub_directories: Vec<usize>| Directory {
+        name: name.into(),
+        untracked_entries: vec!["untracked".into()],
+        sub_directories,
+        stat: Some(Default::default()),
+        exclude_file_oid: None,
+        check_only: false,
+    };
+    file.set_untracked(Some(UntrackedCache {
+        directories: vec![valid_dir("", vec![1]), valid_dir("dir", Vec::new())],
+        ..Default::default()
+    }));
+
+    file.remove_entries(|_, path, _| path == "file");
+    let directories = &file.untracked().expect("still present").directories;
+    assert_eq!(
+        directories[0].stat, None,
+        "the directory containing the removed entry is invalidated"
+    );
+    assert!(directories[0].untracked_entries.is_empty());
+    assert_eq!(
+        directories[1],
+        valid_dir("dir", Vec::new()),
+        "other directories remain valid"
+    );
+}
+
+#[test]
+fn remove_entries_invalidates_tree() {
+    let mut file = Fixture::Generated("v2_more_files").open();
+    file.remove_entries(|_, path, _| path == "d/b");
+    let tree = file.tree().expect("still present");
+    assert_eq!(tree.num_entries, None, "the root tree is invalidated");
+    assert_eq!(tree.children.len(), 1, "the subtree itself remains");
+    assert_eq!(
+        tree.children[0].num_entries, None,
+        "the tree containing the entry is invalidated"
+    );
+}
+
+#[test]
+fn upsert_entry_replaces_all_stages() {
+    let mut file = Fixture::Loose("conflicting-file").open();
+    let id = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1);
+    let idx = file.upsert_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::from_bits_retain(3 << 12),
+        gix_index::entry::Mode::FILE,
+        "file".into(),
+    );
+    assert_eq!(idx, 0);
+    assert_eq!(file.entries().len(), 1, "all conflicting stages are replaced");
+    let entry = &file.entries()[0];
+    assert_eq!(entry.stage(), Stage::Unconflicted, "the stage is always reset");
+    assert_eq!(entry.id, id);
+    assert_eq!(entry.path(&file), "file");
+    file.verify_entries().expect("still sorted");
+}
+
+#[test]
+fn upsert_entry_inserts_sorted_and_invalidates_tree() {
+    let mut file = Fixture::Generated("v2_more_files").open();
+    let id = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1);
+    let idx = file.upsert_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::empty(),
+        gix_index::entry::Mode::FILE,
+        "bb".into(),
+    );
+    assert_eq!(idx, 2);
+    assert_eq!(paths(&file), ["a", "b", "bb", "c", "d/a", "d/b", "d/c"]);
+    file.verify_entries().expect("sorted");
+
+    let tree = file.tree().expect("present");
+    assert_eq!(tree.num_entries, None, "root is invalidated");
+    assert_eq!(tree.children[0].num_entries, Some(3), "unrelated subtrees remain valid");
+
+    let idx = file.upsert_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::empty(),
+        gix_index::entry::Mode::SYMLINK,
+        "d/b".into(),
+    );
+    assert_eq!(idx, 5, "existing entries are updated in place");
+    assert_eq!(file.entries()[idx].mode, gix_index::entry::Mode::SYMLINK);
+    assert_eq!(file.entries().len(), 7);
+    assert_eq!(file.tree().expect("present").children[0].num_entries, None);
+}
+
+#[test]
+fn upsert_entry_removes_directory_file_conflicts() {
+    let mut file = Fixture::Generated("v2_more_files").open();
+    let id = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1);
+    let idx = file.upsert_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::empty(),
+        gix_index::entry::Mode::FILE,
+        "d".into(),
+    );
+    assert_eq!(idx, 3);
+    assert_eq!(
+        paths(&file),
+        ["a", "b", "c", "d"],
+        "the directory is replaced by a file"
+    );
+    assert!(
+        file.tree().expect("present").children.is_empty(),
+        "the tree that became a file is removed"
+    );
+
+    let idx = file.upsert_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::empty(),
+        gix_index::entry::Mode::FILE,
+        "a/x/y".into(),
+    );
+    assert_eq!(idx, 0);
+    assert_eq!(
+        paths(&file),
+        ["a/x/y", "b", "c", "d"],
+        "the file at a leading directory is replaced"
+    );
+    file.verify_entries().expect("sorted");
+}
+
+fn paths(file: &gix_index::State) -> Vec<&bstr::BStr> {
+    file.entries().iter().map(|e| e.path(file)).collect()
+}
+
 #[test]
 fn sort_entries() {
     let mut file = Fixture::Generated("v4_more_files_IEOT").open();
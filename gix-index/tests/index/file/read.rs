@@ -208,6 +208,44 @@ fn fsmn_v1() {
     assert!(file.fs_monitor().is_some());
 }
 
+#[test]
+fn fsmn_v2_marks_valid_entries() {
+    let mut file = Fixture::Generated("v4_optional_extensions").open();
+    let fs_monitor = file.fs_monitor().expect("present");
+    assert_eq!(
+        fs_monitor.token(),
+        &gix_index::extension::fs_monitor::Token::V2 {
+            token: "token-1".into()
+        }
+    );
+
+    assert!(file.mark_fs_monitor_valid_entries());
+    let dirty: Vec<_> = file
+        .entries()
+        .iter()
+        .filter(|e| !e.flags.contains(Flags::FSMONITOR_VALID))
+        .map(|e| e.path(&file).to_owned())
+        .collect();
+    assert_eq!(dirty, ["b", "dir/c"], "the hook reports these as changed on each query");
+
+    let fs_monitor = gix_index::extension::FsMonitor::from_entries(
+        gix_index::extension::fs_monitor::Token::V2 {
+            token: "token-2".into(),
+        },
+        file.entries(),
+    );
+    let previous = file.set_fs_monitor(Some(fs_monitor)).expect("was set");
+    assert_eq!(
+        previous.entry_dirty().to_words(),
+        file.fs_monitor().expect("set").entry_dirty().to_words(),
+        "the bitmap is recreated from the flags"
+    );
+
+    file.set_fs_monitor(None);
+    assert!(!file.mark_fs_monitor_valid_entries());
+    assert!(file.entries().iter().all(|e| !e.flags.contains(Flags::FSMONITOR_VALID)));
+}
+
 #[test]
 fn v3_added_files() {
     let file = Fixture::Generated("v3_added_files").open();
@@ -350,8 +388,8 @@ fn split_index_and_regular_index_of_same_content_are_indeed_the_same() {
     );
 
     assert!(
-        split.link().is_none(),
-        "link extension is dissolved, merging the shared index permanently into the split one (for now)"
+        split.link().expect("present").bitmaps.is_none(),
+        "link extension is dissolved, merging the shared index into the split one, but remains to be able to write it back"
     );
 
     let regular = verify(
@@ -15,11 +15,19 @@ fn roundtrips() -> crate::Result {
             options_with(write::Extensions::Given {
                 tree_cache: true,
                 end_of_index_entry: true,
+                resolve_undo: false,
+                untracked_cache: false,
+                fs_monitor: false,
             }),
         ),
         (Generated("V2_empty"), only_tree_ext()),
         (Generated("v2_more_files"), only_tree_ext()),
         (Generated("v2_all_file_kinds"), only_tree_ext()),
+        (Loose("REUC"), all_but_end_of_index_entry()),
+        (Loose("UNTR"), all_but_end_of_index_entry()),
+        (Loose("UNTR-with-oids"), all_but_end_of_index_entry()),
+        (Loose("FSMN"), all_but_end_of_index_entry()),
+        (Generated("v4_optional_extensions"), all_but_end_of_index_entry()),
     ];
 
     for (fixture, options) in input {
@@ -37,6 +45,31 @@ fn roundtrips() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn roundtrips_sha256() -> crate::Result {
+    let path = crate::index::fixture_index_path("v2_sha256");
+    let expected = gix_index::File::at(&path, gix_hash::Kind::Sha256, false, Default::default())?;
+    assert_eq!(expected.object_hash(), gix_hash::Kind::Sha256);
+    assert!(expected.tree().is_some(), "the tree extension is present");
+    let expected_bytes = std::fs::read(&path)?;
+
+    let options = options_with(write::Extensions::Given {
+        tree_cache: true,
+        end_of_index_entry: true,
+        resolve_undo: false,
+        untracked_cache: false,
+        fs_monitor: false,
+    });
+    let mut out_bytes = Vec::new();
+    let (actual_version, digest) = expected.write_to(&mut out_bytes, options)?;
+    let (actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha256, Default::default())?;
+
+    compare_states_against_baseline(&actual, actual_version, &expected, options, "v2_sha256");
+    compare_raw_bytes(&out_bytes, &expected_bytes, "v2_sha256");
+    assert_eq!(Some(digest), expected.checksum(), "the trailing hash is SHA256 as well");
+    Ok(())
+}
+
 #[test]
 fn skip_hash() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -133,9 +166,8 @@ fn state_comparisons_with_various_extension_configurations() {
         Generated("v2_more_files"),
         Generated("v2_all_file_kinds"),
         Generated("v2_split_index"),
-        // TODO: this fails because git allows to configure the index version while gitoxide doesn't
-        //       the fixture artificially sets the version to V4 and gitoxide writes it back out as the lowest required version, V2
-        // Generated("v4_more_files_IEOT"),
+        Generated("v4_more_files_IEOT"),
+        Generated("v4_optional_extensions"),
         Generated("v3_skip_worktree"),
         Generated("v3_added_files"),
         Generated("v3_sparse_index_non_cone"),
@@ -149,10 +181,16 @@ fn state_comparisons_with_various_extension_configurations() {
             options_with(write::Extensions::Given {
                 tree_cache: true,
                 end_of_index_entry: false,
+                resolve_undo: false,
+                untracked_cache: false,
+                fs_monitor: false,
             }),
             options_with(write::Extensions::Given {
                 tree_cache: false,
                 end_of_index_entry: true,
+                resolve_undo: true,
+                untracked_cache: true,
+                fs_monitor: true,
             }),
         ] {
             let expected = fixture.open();
@@ -168,6 +206,132 @@ fn state_comparisons_with_various_extension_configurations() {
     }
 }
 
+#[test]
+fn split_index_roundtrips_with_link_extension() -> crate::Result {
+    let path = gix_testtools::scripted_fixture_read_only_standalone(
+        std::path::Path::new("make_index").join("v2_split_vs_regular_index.sh"),
+    )?
+    .join("split/.git/index");
+    let expected_bytes = std::fs::read(&path)?;
+    let (expected, _) = State::from_bytes(
+        &expected_bytes,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    let link = expected.link().expect("not dissolved when decoding the state directly");
+    assert!(link.bitmaps.is_some());
+
+    let options = only_tree_ext();
+    let mut out_bytes = Vec::new();
+    let (actual_version, _digest) =
+        gix_index::File::from_state(expected.clone(), &path).write_to(&mut out_bytes, options)?;
+    let (actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(
+        actual.link().map(|link| link.shared_index_checksum),
+        Some(link.shared_index_checksum)
+    );
+
+    assert_eq!(actual_version, expected.version());
+    assert_eq!(actual.entries().len(), expected.entries().len());
+    compare_raw_bytes(&out_bytes, &expected_bytes, "v2_split_vs_regular_index");
+    Ok(())
+}
+
+#[test]
+fn split_index_is_written_against_its_shared_index() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_index_writable.sh")?;
+    let path = tmp.path().join(".git/index");
+    let mut index = gix_index::File::at(&path, gix_hash::Kind::Sha1, false, Default::default())?;
+    let shared_index_checksum = index.link().expect("split index").shared_index_checksum;
+    assert!(index.link().and_then(|link| link.bitmaps.as_ref()).is_none());
+
+    index.entries_mut()[0].flags.insert(entry::Flags::REMOVE);
+    index.entries_mut()[1].stat.size += 1;
+    let expected_entries: Vec<_> = entries_with_paths(&index).into_iter().skip(1).collect();
+    index.write(Default::default())?;
+    assert_eq!(
+        index.link().map(|link| link.shared_index_checksum),
+        Some(shared_index_checksum),
+        "few changes don't warrant a new shared index"
+    );
+
+    let (split, _) = State::from_bytes(
+        &std::fs::read(&path)?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert_eq!(
+        split.entries().len(),
+        2,
+        "only the replaced entry and the new one are stored in the split index"
+    );
+    assert!(
+        split.entries()[0].path(&split).is_empty(),
+        "replaced entries have no path"
+    );
+    assert_eq!(split.entries()[1].path(&split), "g");
+
+    let actual = gix_index::File::at(&path, gix_hash::Kind::Sha1, false, Default::default())?;
+    assert_eq!(
+        entries_with_paths(&actual),
+        expected_entries,
+        "all changes are visible after merging"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_index_writes_new_shared_index_if_too_many_entries_changed() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_index_writable.sh")?;
+    let path = tmp.path().join(".git/index");
+    let mut index = gix_index::File::at(&path, gix_hash::Kind::Sha1, false, Default::default())?;
+    let shared_index_checksum = index.link().expect("split index").shared_index_checksum;
+
+    for entry in index.entries_mut() {
+        entry.stat.size += 1;
+    }
+    let expected_entries = entries_with_paths(&index);
+    index.write(Default::default())?;
+    let new_shared_index_checksum = index.link().expect("still split").shared_index_checksum;
+    assert_ne!(new_shared_index_checksum, shared_index_checksum);
+    assert!(
+        tmp.path()
+            .join(format!(".git/sharedindex.{new_shared_index_checksum}"))
+            .is_file(),
+        "the new shared index was written"
+    );
+
+    let (split, _) = State::from_bytes(
+        &std::fs::read(&path)?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert_eq!(split.entries().len(), 0, "all entries are in the shared index");
+
+    let actual = gix_index::File::at(&path, gix_hash::Kind::Sha1, false, Default::default())?;
+    assert_eq!(entries_with_paths(&actual), expected_entries);
+    Ok(())
+}
+
+#[test]
+fn version_4_is_retained_and_can_be_set() -> crate::Result {
+    let mut expected = Generated("v2").open();
+    expected.set_version(Version::V4);
+
+    let mut buf = Vec::new();
+    let (actual_version, _digest) = expected.write_to(&mut buf, Default::default())?;
+    assert_eq!(actual_version, Version::V4, "path-prefix compression is used");
+
+    let (actual, _) = State::from_bytes(&buf, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(actual.version(), Version::V4);
+    assert_eq!(actual.entries(), expected.entries());
+    assert_eq!(actual.path_backing(), expected.path_backing());
+    Ok(())
+}
+
 #[test]
 fn extended_flags_automatically_upgrade_the_version_to_avoid_data_loss() -> crate::Result {
     let mut expected = Generated("v2").open();
@@ -210,6 +374,14 @@ fn remove_flag_is_respected() -> crate::Result {
     Ok(())
 }
 
+fn entries_with_paths(state: &State) -> Vec<(bstr::BString, entry::Stat, gix_hash::ObjectId, entry::Mode)> {
+    state
+        .entries()
+        .iter()
+        .map(|e| (e.path(state).to_owned(), e.stat, e.id, e.mode))
+        .collect()
+}
+
 fn compare_states_against_baseline(
     actual: &State,
     actual_version: Version,
@@ -293,6 +465,22 @@ fn only_tree_ext() -> Options {
         extensions: write::Extensions::Given {
             end_of_index_entry: false,
             tree_cache: true,
+            resolve_undo: false,
+            untracked_cache: false,
+            fs_monitor: false,
+        },
+        skip_hash: false,
+    }
+}
+
+fn all_but_end_of_index_entry() -> Options {
+    Options {
+        extensions: write::Extensions::Given {
+            end_of_index_entry: false,
+            tree_cache: true,
+            resolve_undo: true,
+            untracked_cache: true,
+            fs_monitor: true,
         },
         skip_hash: false,
     }
@@ -0,0 +1,135 @@
+use bstr::BStr;
+use gix_index::entry::{Flags, Mode};
+
+use crate::index::Fixture;
+
+fn odb(fixture: &Fixture) -> gix_odb::Handle {
+    let git_dir = fixture.to_path().parent().expect("index is in .git").to_owned();
+    gix_odb::at(git_dir.join("objects")).expect("valid odb")
+}
+
+fn entries(index: &gix_index::State) -> Vec<(&BStr, Mode, Flags, gix_hash::ObjectId)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index), e.mode, e.flags, e.id))
+        .collect()
+}
+
+fn hash_tree(tree: &gix_object::Tree) -> Result<gix_hash::ObjectId, std::convert::Infallible> {
+    use gix_object::WriteTo;
+    let mut buf = Vec::new();
+    tree.write_to(&mut buf).expect("writing to memory never fails");
+    Ok(gix_object::compute_hash(
+        gix_hash::Kind::Sha1,
+        gix_object::Kind::Tree,
+        &buf,
+    ))
+}
+
+#[test]
+fn expand_sparse_directories_matches_a_non_sparse_index() -> crate::Result {
+    let fixture = Fixture::Generated("v3_sparse_index");
+    let mut index = fixture.open();
+    let expected = Fixture::Generated("v3_skip_worktree").open();
+
+    let num_expanded = index.expand_sparse_directories(odb(&fixture), Default::default(), |_| true)?;
+    assert_eq!(num_expanded, 2, "c1/c3 and d");
+    assert!(
+        !index.is_sparse(),
+        "without sparse directories the index isn't sparse anymore"
+    );
+    assert_eq!(entries(&index), entries(&expected));
+    index.verify_entries()?;
+    Ok(())
+}
+
+#[test]
+fn expand_sparse_directories_selectively() -> crate::Result {
+    let fixture = Fixture::Generated("v3_sparse_index");
+    let mut index = fixture.open();
+
+    let num_expanded = index.expand_sparse_directories(odb(&fixture), Default::default(), |dir| dir == "d")?;
+    assert_eq!(num_expanded, 1);
+    assert!(index.is_sparse(), "c1/c3 is still a sparse directory");
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .filter(|e| e.mode.is_sparse())
+            .map(|e| e.path(&index))
+            .collect::<Vec<_>>(),
+        ["c1/c3/"]
+    );
+    assert!(index
+        .entries()
+        .iter()
+        .filter(|e| e.path(&index).starts_with(b"d/"))
+        .all(|e| e.flags.contains(Flags::SKIP_WORKTREE | Flags::EXTENDED)));
+    index.verify_entries()?;
+    Ok(())
+}
+
+#[test]
+fn collapse_sparse_directories_matches_git() -> crate::Result {
+    let mut index = Fixture::Generated("v3_skip_worktree").open();
+    let expected = Fixture::Generated("v3_sparse_index").open();
+
+    let outside_of_cone = |dir: &BStr| !(dir == "c1" || dir.starts_with(b"c1/c2"));
+    let num_collapsed = index.collapse_sparse_directories(outside_of_cone, hash_tree)?;
+    assert_eq!(num_collapsed, 2);
+    assert!(index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected));
+    index.verify_entries()?;
+    Ok(())
+}
+
+#[test]
+fn collapse_sparse_directories_skips_directories_with_entries_in_the_worktree() -> crate::Result {
+    let mut index = Fixture::Generated("v3_skip_worktree").open();
+    let num_collapsed = index.collapse_sparse_directories(|_| true, hash_tree)?;
+    assert_eq!(
+        num_collapsed, 2,
+        "c1 has files in the worktree, so only c1/c3 and d can be collapsed"
+    );
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .filter(|e| e.mode.is_sparse())
+            .map(|e| e.path(&index))
+            .collect::<Vec<_>>(),
+        ["c1/c3/", "d/"]
+    );
+
+    let num_collapsed = index.collapse_sparse_directories(|_| true, hash_tree)?;
+    assert_eq!(num_collapsed, 0, "sparse directories are kept as they are");
+    Ok(())
+}
+
+#[test]
+fn collapse_and_expand_roundtrip() -> crate::Result {
+    let fixture = Fixture::Generated("v3_skip_worktree");
+    let mut index = fixture.open();
+    let expected = entries(&index)
+        .into_iter()
+        .map(|(p, m, f, id)| (p.to_owned(), m, f, id))
+        .collect::<Vec<_>>();
+
+    index.collapse_sparse_directories(|dir| dir == "d/c4", hash_tree)?;
+    index.collapse_sparse_directories(|dir| dir == "d", hash_tree)?;
+    assert_eq!(
+        index.entries().iter().filter(|e| e.mode.is_sparse()).count(),
+        1,
+        "d/c4 is merged into d"
+    );
+    index.expand_sparse_directories(odb(&fixture), Default::default(), |_| true)?;
+    assert_eq!(
+        entries(&index)
+            .into_iter()
+            .map(|(p, m, f, id)| (p.to_owned(), m, f, id))
+            .collect::<Vec<_>>(),
+        expected
+    );
+    Ok(())
+}
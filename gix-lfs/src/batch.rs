@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, io};
+
+use gix_transport::client::http;
+
+use crate::{transfer, Pointer};
+
+/// The media type of requests to and responses from the batch API.
+pub const MEDIA_TYPE: &str = "application/vnd.git-lfs+json";
+
+/// The error returned by [`Client::batch()`] and its [transfer adapter](transfer::Adapter) implementation.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] http::Error),
+    #[error("Could not send the request or receive the response")]
+    Io(#[from] io::Error),
+    #[error("Could not serialize the request or deserialize the response")]
+    Json(#[from] serde_json::Error),
+    #[error("The server didn't mention object {oid} in its response")]
+    MissingObject { oid: String },
+    #[error("The server failed to provide object {oid} with code {code}: {message}")]
+    Object { oid: String, code: u32, message: String },
+    #[error("The server didn't provide the '{action}' action for object {oid}")]
+    MissingAction { oid: String, action: &'static str },
+    #[error("The server only offers the '{name}' transfer, but only 'basic' is supported")]
+    UnsupportedTransfer { name: String },
+    #[error(
+        "Uploading object {oid} needs a PUT request, which can't be performed with the current HTTP implementation"
+    )]
+    UploadUnsupported { oid: String },
+}
+
+/// The operation to perform on objects in a batch [`Request`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// Download objects from the server.
+    Download,
+    /// Upload objects to the server.
+    Upload,
+}
+
+/// An object in a batch [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ObjectSpec {
+    /// The hexadecimal `sha256` hash of the object.
+    pub oid: String,
+    /// The size of the object in bytes.
+    pub size: u64,
+}
+
+impl From<&Pointer> for ObjectSpec {
+    fn from(pointer: &Pointer) -> Self {
+        ObjectSpec {
+            oid: pointer.oid.to_string(),
+            size: pointer.size,
+        }
+    }
+}
+
+/// The reference that objects in a [`Request`] belong to.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Ref {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: String,
+}
+
+/// A request to the batch API, to learn how to transfer `objects`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Request {
+    /// What to do with `objects`.
+    pub operation: Operation,
+    /// The names of the transfer adapters we support, in order of preference.
+    pub transfers: Vec<String>,
+    /// The reference the objects belong to, if known.
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<Ref>,
+    /// The objects to transfer.
+    pub objects: Vec<ObjectSpec>,
+    /// The hash algorithm used for object ids.
+    pub hash_algo: String,
+}
+
+impl Request {
+    /// Create a request to perform `operation` on the objects `pointers` refer to, using the `basic` transfer.
+    pub fn new<'a>(operation: Operation, pointers: impl IntoIterator<Item = &'a Pointer>) -> Self {
+        Request {
+            operation,
+            transfers: vec!["basic".into()],
+            reference: None,
+            objects: pointers.into_iter().map(Into::into).collect(),
+            hash_algo: "sha256".into(),
+        }
+    }
+}
+
+/// The response to a batch [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Response {
+    /// The transfer adapter chosen by the server, with `None` meaning `basic`.
+    #[serde(default)]
+    pub transfer: Option<String>,
+    /// The objects of the request, along with instructions on how to transfer them.
+    pub objects: Vec<Object>,
+    /// The hash algorithm used for object ids.
+    #[serde(default)]
+    pub hash_algo: Option<String>,
+}
+
+/// An object in a batch [`Response`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Object {
+    /// The hexadecimal `sha256` hash of the object.
+    pub oid: String,
+    /// The size of the object in bytes.
+    pub size: u64,
+    /// If `true`, the actions don't need additional authentication.
+    #[serde(default)]
+    pub authenticated: Option<bool>,
+    /// The actions to perform to transfer the object, which are missing if no transfer is needed.
+    #[serde(default)]
+    pub actions: Option<Actions>,
+    /// An error if the object can't be transferred.
+    #[serde(default)]
+    pub error: Option<ObjectError>,
+}
+
+/// The actions to transfer an [`Object`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Actions {
+    /// How to download the object.
+    #[serde(default)]
+    pub download: Option<Action>,
+    /// How to upload the object.
+    #[serde(default)]
+    pub upload: Option<Action>,
+    /// How to verify the object after uploading it.
+    #[serde(default)]
+    pub verify: Option<Action>,
+}
+
+/// A single request to perform as part of transferring an [`Object`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Action {
+    /// The URL to send the request to.
+    pub href: String,
+    /// Additional headers to send along with the request.
+    #[serde(default)]
+    pub header: BTreeMap<String, String>,
+    /// The amount of seconds after which the action expires.
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+    /// The time at which the action expires, in ISO 8601 format.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+/// The reason an [`Object`] can't be transferred.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ObjectError {
+    /// The HTTP status code describing the error.
+    pub code: u32,
+    /// A human-readable description of the error.
+    pub message: String,
+}
+
+/// Return the URL of the LFS server for the `http://` or `https://` `remote_url` of a repository,
+/// the way `git-lfs` derives it.
+pub fn endpoint(remote_url: &str) -> String {
+    let url = remote_url.trim_end_matches('/');
+    if url.ends_with(".git") {
+        format!("{url}/info/lfs")
+    } else {
+        format!("{url}.git/info/lfs")
+    }
+}
+
+/// A client for the batch API of an LFS server, which is also a [transfer adapter](transfer::Adapter)
+/// using the `basic` transfer.
+pub struct Client<H> {
+    http: H,
+    endpoint: String,
+    headers: Vec<String>,
+}
+
+/// Lifecycle
+impl<H: http::Http> Client<H> {
+    /// Create a new instance to send requests to the LFS server at `endpoint` using `http`.
+    ///
+    /// Use [`endpoint()`] to obtain it from the URL of a remote.
+    pub fn new(http: H, endpoint: impl Into<String>) -> Self {
+        Client {
+            http,
+            endpoint: endpoint.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Send `header`, like `Authorization: Basic <credentials>`, along with each batch request.
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+}
+
+/// Operations
+impl<H: http::Http> Client<H> {
+    /// Send `request` to the batch endpoint of the server and return its response.
+    pub fn batch(&mut self, request: &Request) -> Result<Response, Error> {
+        let url = format!("{}/objects/batch", self.endpoint);
+        let headers = [format!("Accept: {MEDIA_TYPE}"), format!("Content-Type: {MEDIA_TYPE}")];
+        let http::PostResponse {
+            mut post_body, body, ..
+        } = self.http.post(
+            &url,
+            &self.endpoint,
+            headers.iter().chain(&self.headers),
+            http::PostBodyDataKind::BoundedAndFitsIntoMemory,
+        )?;
+        serde_json::to_writer(&mut post_body, request)?;
+        drop(post_body);
+        let response: Response = serde_json::from_reader(body)?;
+        match response.transfer.as_deref() {
+            None | Some("basic") => Ok(response),
+            Some(name) => Err(Error::UnsupportedTransfer { name: name.into() }),
+        }
+    }
+
+    /// Ask the server how to perform `operation` on the object `pointer` refers to, and return the action to use,
+    /// or `None` if there is nothing to do.
+    fn action(&mut self, operation: Operation, pointer: &Pointer) -> Result<Option<Action>, Error> {
+        let oid = pointer.oid.to_string();
+        let object = self
+            .batch(&Request::new(operation, Some(pointer)))?
+            .objects
+            .into_iter()
+            .find(|obj| obj.oid == oid)
+            .ok_or_else(|| Error::MissingObject { oid: oid.clone() })?;
+        if let Some(err) = object.error {
+            return Err(Error::Object {
+                oid,
+                code: err.code,
+                message: err.message,
+            });
+        }
+        let actions = object.actions;
+        Ok(match operation {
+            Operation::Download => Some(actions.and_then(|a| a.download).ok_or(Error::MissingAction {
+                oid,
+                action: "download",
+            })?),
+            Operation::Upload => actions.and_then(|a| a.upload),
+        })
+    }
+}
+
+impl<H: http::Http> transfer::Adapter for Client<H> {
+    fn download(&mut self, pointer: &Pointer, out: &mut dyn io::Write) -> Result<(), transfer::Error> {
+        let action = self
+            .action(Operation::Download, pointer)?
+            .expect("download actions are always present");
+        let headers = action.header.iter().map(|(name, value)| format!("{name}: {value}"));
+        let http::GetResponse { mut body, .. } = self
+            .http
+            .get(&action.href, &action.href, headers)
+            .map_err(Error::from)?;
+        io::copy(&mut body, out)?;
+        Ok(())
+    }
+
+    fn upload(&mut self, pointer: &Pointer, _src: &mut dyn io::Read) -> Result<(), transfer::Error> {
+        match self.action(Operation::Upload, pointer)? {
+            None => Ok(()),
+            Some(_) => Err(Error::UploadUnsupported {
+                oid: pointer.oid.to_string(),
+            }
+            .into()),
+        }
+    }
+}
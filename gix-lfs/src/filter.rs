@@ -0,0 +1,134 @@
+use std::io::{self, Read};
+
+use bstr::BString;
+use gix_filter::driver::{apply, InProcess, Operation};
+use gix_hash::ObjectId;
+
+use crate::{pointer, transfer, Filter, Pointer, Store};
+
+/// The error returned by [`Filter::clean()`] and [`Filter::smudge()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Object {oid} isn't available locally, and there is no way to download it")]
+    ObjectNotFound { oid: ObjectId },
+    #[error("Pointers with extensions can't be smudged, found extension '{name}'")]
+    UnsupportedExtension { name: BString },
+    #[error("Could not read the data to filter")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Store(#[from] crate::store::Error),
+    #[error(transparent)]
+    Transfer(#[from] transfer::Error),
+}
+
+/// Lifecycle
+impl Filter {
+    /// Create a new instance which keeps the contents of large files in `store`.
+    ///
+    /// Without a [transfer adapter](Self::with_transfer()), objects that aren't present in `store` can't be smudged.
+    pub fn new(store: Store) -> Self {
+        Filter {
+            store,
+            transfer: None,
+            keep_pointer_if_missing: false,
+        }
+    }
+
+    /// Use `adapter` to download objects that are missing in our store when smudging.
+    pub fn with_transfer(mut self, adapter: impl transfer::Adapter + Send + 'static) -> Self {
+        self.transfer = Some(std::sync::Mutex::new(Box::new(adapter)));
+        self
+    }
+
+    /// If `toggle` is `true`, pointers of objects which aren't present in our store and can't be downloaded are
+    /// checked out as they are, similar to what `git-lfs` does if `GIT_LFS_SKIP_SMUDGE` is set.
+    /// Otherwise, smudging them fails.
+    pub fn with_keep_pointer_if_missing(mut self, toggle: bool) -> Self {
+        self.keep_pointer_if_missing = toggle;
+        self
+    }
+}
+
+/// Access
+impl Filter {
+    /// The store holding the contents of large files.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+}
+
+/// Operations
+impl Filter {
+    /// Store all data read from `src` and return the pointer to write into `git` instead, or `None` if `src` is empty
+    /// as empty files are stored as they are.
+    ///
+    /// If `src` is a pointer already, it's returned without storing anything.
+    pub fn clean(&self, src: &mut dyn Read) -> Result<Option<Pointer>, Error> {
+        let mut head = Vec::with_capacity(pointer::MAX_SIZE);
+        (&mut *src).take(pointer::MAX_SIZE as u64).read_to_end(&mut head)?;
+        if head.is_empty() {
+            return Ok(None);
+        }
+        if let Ok(pointer) = Pointer::from_bytes(&head) {
+            return Ok(Some(pointer));
+        }
+        Ok(Some(self.store.insert(&mut head.as_slice().chain(src))?))
+    }
+
+    /// Return the file with the contents `pointer` refers to, downloading it first if it isn't present locally.
+    ///
+    /// `None` is returned if the object is missing and [pointers should be kept](Self::with_keep_pointer_if_missing()).
+    pub fn smudge(&self, pointer: &Pointer) -> Result<Option<std::fs::File>, Error> {
+        if let Some(ext) = pointer.extensions.first() {
+            return Err(Error::UnsupportedExtension { name: ext.name.clone() });
+        }
+        if let Some(file) = self.store.open(pointer)? {
+            return Ok(Some(file));
+        }
+        let Some(transfer) = self.transfer.as_ref() else {
+            return if self.keep_pointer_if_missing {
+                Ok(None)
+            } else {
+                Err(Error::ObjectNotFound { oid: pointer.oid })
+            };
+        };
+        let mut writer = self.store.writer()?;
+        transfer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .download(pointer, &mut writer)?;
+        writer.commit(Some(pointer))?;
+        Ok(Some(
+            self.store
+                .open(pointer)?
+                .ok_or(Error::ObjectNotFound { oid: pointer.oid })?,
+        ))
+    }
+}
+
+impl InProcess for Filter {
+    fn apply(
+        &self,
+        operation: Operation,
+        src: &mut dyn Read,
+        _ctx: apply::Context<'_, '_>,
+    ) -> Result<Box<dyn Read>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(match operation {
+            Operation::Clean => Box::new(io::Cursor::new(
+                self.clean(src)?.map(|pointer| pointer.to_bstring()).unwrap_or_default(),
+            )),
+            Operation::Smudge => {
+                let mut buf = Vec::new();
+                src.read_to_end(&mut buf)?;
+                match Pointer::from_bytes(&buf) {
+                    Ok(pointer) => match self.smudge(&pointer)? {
+                        Some(file) => Box::new(file),
+                        None => Box::new(io::Cursor::new(buf)),
+                    },
+                    Err(_not_a_pointer) => Box::new(io::Cursor::new(buf)),
+                }
+            }
+        })
+    }
+}
@@ -1,2 +1,93 @@
-#![deny(rust_2018_idioms)]
+//! Support for [Git LFS](https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md), which stores large files outside
+//! of the object database and replaces them with small *pointer files* in git.
+//!
+//! * [`Pointer`] files can be parsed and generated.
+//! * The local object [`Store`] keeps large files in `.git/lfs/objects`, the way `git-lfs` does.
+//! * The in-process [`Filter`] implements the `clean` and `smudge` operations of the `filter=lfs` attribute, and
+//!   plugs into [`gix_filter::Pipeline`] so `git-lfs` doesn't need to be installed.
+//! * Objects are transferred from and to remotes with a [transfer adapter](transfer::Adapter), either from a
+//!   [directory](transfer::File) or with the [batch API](batch::Client) over HTTP.
+//!
+//! ## Feature Flags
+#![cfg_attr(
+    all(doc, feature = "document-features"),
+    doc = ::document_features::document_features!()
+)]
+#![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod pointer;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod store;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod transfer;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod filter;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "http-client")]
+pub mod batch;
+
+/// The name of the filter driver that LFS uses in `.gitattributes`, as in `filter=lfs`.
+pub const DRIVER_NAME: &str = "lfs";
+
+/// A pointer file, which is what `git` stores in place of a large file.
+///
+/// It looks like this:
+///
+/// ```text
+/// version https://git-lfs.github.com/spec/v1
+/// oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+/// size 12345
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pointer {
+    /// The `sha256` hash of the contents of the large file.
+    pub oid: ObjectId,
+    /// The size of the large file in bytes.
+    pub size: u64,
+    /// Extensions which were applied to the large file before it was stored, in order of their priority.
+    pub extensions: Vec<Extension>,
+}
+
+/// A pointer extension, which transformed the contents of a file before they were stored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Extension {
+    /// The priority of the extension from `0` to `9`, determining the order in which extensions are applied.
+    pub priority: u8,
+    /// The name of the extension.
+    pub name: BString,
+    /// The `sha256` hash of the contents before the extension was applied.
+    pub oid: ObjectId,
+}
+
+/// A store for the contents of large files, typically located at `.git/lfs`.
+///
+/// Objects are stored in `objects/<oid[0..2]>/<oid[2..4]>/<oid>`, with temporary files being written to `tmp`.
+#[derive(Debug, Clone)]
+pub struct Store {
+    root: std::path::PathBuf,
+}
+
+/// The in-process filter for the `filter=lfs` attribute.
+///
+/// Register it with [`gix_filter::driver::State::register_in_process()`] using the [`DRIVER_NAME`], and make sure
+/// a [driver](gix_filter::Driver) of that name is configured.
+pub struct Filter {
+    store: Store,
+    transfer: Option<std::sync::Mutex<Box<dyn transfer::Adapter + Send>>>,
+    keep_pointer_if_missing: bool,
+}
@@ -0,0 +1,160 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Extension, Pointer};
+
+/// The version of the pointer format written by us, and by `git-lfs`.
+pub const VERSION: &str = "https://git-lfs.github.com/spec/v1";
+/// A pre-release version of the pointer format which is still accepted when reading.
+pub const LEGACY_VERSION: &str = "https://hawser.github.com/spec/v1";
+/// Files at least this large are never considered pointers.
+pub const MAX_SIZE: usize = 1024;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [`Pointer::from_bytes()`][crate::Pointer::from_bytes()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Pointer files are smaller than {} bytes, got {size} bytes", super::MAX_SIZE)]
+        TooLarge { size: usize },
+        #[error("Pointer files must start with a 'version' line")]
+        MissingVersion,
+        #[error("Unknown pointer version '{version}'")]
+        UnknownVersion { version: BString },
+        #[error("Expected a line with a key and a value separated by a space, got '{line}'")]
+        MalformedLine { line: BString },
+        #[error("The '{key}' line is missing")]
+        MissingKey { key: &'static str },
+        #[error("Only 'sha256' object ids are supported, got '{value}'")]
+        UnsupportedOid { value: BString },
+        #[error("The object id is invalid")]
+        Oid(#[from] gix_hash::decode::Error),
+        #[error("The size '{value}' is not a valid number")]
+        Size { value: BString },
+        #[error("The extension '{key}' should be named 'ext-<priority>-<name>'")]
+        Extension { key: BString },
+    }
+}
+
+/// Parsing
+impl Pointer {
+    /// Parse the pointer file in `data`.
+    ///
+    /// Unknown keys are ignored, and extensions are returned in the order of their priority.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, decode::Error> {
+        use decode::Error;
+        if data.len() >= MAX_SIZE {
+            return Err(Error::TooLarge { size: data.len() });
+        }
+        let mut lines = data.lines();
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(b"version "))
+            .ok_or(Error::MissingVersion)?;
+        if version != VERSION.as_bytes() && version != LEGACY_VERSION.as_bytes() {
+            return Err(Error::UnknownVersion {
+                version: version.into(),
+            });
+        }
+
+        let (mut oid, mut size, mut extensions) = (None, None, Vec::new());
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (key, value) = line
+                .split_once_str(b" ")
+                .ok_or_else(|| Error::MalformedLine { line: line.into() })?;
+            match key {
+                b"oid" => oid = Some(parse_oid(value)?),
+                b"size" => {
+                    size = Some(
+                        value
+                            .to_str()
+                            .ok()
+                            .and_then(|size| size.parse().ok())
+                            .ok_or_else(|| Error::Size { value: value.into() })?,
+                    )
+                }
+                _ if key.starts_with(b"ext-") => {
+                    let extension_error = || Error::Extension { key: key.into() };
+                    let (priority, name) = key[4..].split_once_str(b"-").ok_or_else(extension_error)?;
+                    let priority = match priority {
+                        [digit @ b'0'..=b'9'] => digit - b'0',
+                        _ => return Err(extension_error()),
+                    };
+                    extensions.push(Extension {
+                        priority,
+                        name: name.into(),
+                        oid: parse_oid(value)?,
+                    });
+                }
+                _ => {}
+            }
+        }
+        extensions.sort_by_key(|ext| ext.priority);
+        Ok(Pointer {
+            oid: oid.ok_or(Error::MissingKey { key: "oid" })?,
+            size: size.ok_or(Error::MissingKey { key: "size" })?,
+            extensions,
+        })
+    }
+}
+
+fn parse_oid(value: &[u8]) -> Result<ObjectId, decode::Error> {
+    let hex = value
+        .strip_prefix(b"sha256:")
+        .filter(|hex| hex.len() == gix_hash::Kind::Sha256.len_in_hex())
+        .ok_or_else(|| decode::Error::UnsupportedOid { value: value.into() })?;
+    Ok(ObjectId::from_hex(hex)?)
+}
+
+/// Serialization
+impl Pointer {
+    /// Write this pointer to `out` in the canonical format, the way `git-lfs` does.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "version {VERSION}")?;
+        for ext in &self.extensions {
+            write!(out, "ext-{}-", ext.priority)?;
+            out.write_all(&ext.name)?;
+            writeln!(out, " sha256:{}", ext.oid)?;
+        }
+        writeln!(out, "oid sha256:{}", self.oid)?;
+        writeln!(out, "size {}", self.size)
+    }
+
+    /// Return this pointer in the canonical format as written by [`write_to()`](Self::write_to()).
+    pub fn to_bstring(&self) -> BString {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf).expect("writing to memory never fails");
+        buf.into()
+    }
+}
+
+/// Generation
+impl Pointer {
+    /// Compute the pointer for the data read from `src`, without storing it.
+    pub fn from_read(src: &mut dyn io::Read) -> io::Result<Self> {
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha256);
+        let mut buf = [0u8; 8192];
+        let mut size = 0;
+        loop {
+            let num_read = match src.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            hasher.update(&buf[..num_read]);
+            size += num_read as u64;
+        }
+        Ok(Pointer {
+            oid: hasher.digest(),
+            size,
+            extensions: Vec::new(),
+        })
+    }
+}
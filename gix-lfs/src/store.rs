@@ -0,0 +1,154 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+
+use crate::{Pointer, Store};
+
+/// The error returned by [`Store`] methods which add objects.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not create directory at '{}'", path.display())]
+    CreateDirectory { path: PathBuf, source: io::Error },
+    #[error("Could not write object data to a temporary file")]
+    Io(#[from] io::Error),
+    #[error("Could not move object into place at '{}'", path.display())]
+    Persist {
+        path: PathBuf,
+        source: tempfile::PersistError,
+    },
+    #[error("Expected object {expected} with {expected_size} bytes, but got {actual} with {actual_size} bytes")]
+    Mismatch {
+        expected: ObjectId,
+        expected_size: u64,
+        actual: ObjectId,
+        actual_size: u64,
+    },
+}
+
+/// Lifecycle
+impl Store {
+    /// Create a store at the `lfs` directory, which is typically `.git/lfs`.
+    ///
+    /// Note that the directory is created lazily when the first object is added.
+    pub fn at(lfs_dir: impl Into<PathBuf>) -> Self {
+        Store { root: lfs_dir.into() }
+    }
+}
+
+/// Access
+impl Store {
+    /// The directory containing the `objects` and `tmp` directories.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return the path at which the object with `oid` is stored, whether it exists or not.
+    pub fn object_path(&self, oid: &gix_hash::oid) -> PathBuf {
+        let hex = oid.to_hex().to_string();
+        let mut path = self.root.join("objects");
+        path.push(&hex[..2]);
+        path.push(&hex[2..4]);
+        path.push(hex);
+        path
+    }
+
+    /// Return `true` if the object `pointer` refers to is present with the expected size.
+    pub fn contains(&self, pointer: &Pointer) -> bool {
+        std::fs::metadata(self.object_path(&pointer.oid))
+            .ok()
+            .map(|meta| meta.len())
+            == Some(pointer.size)
+    }
+
+    /// Open the object `pointer` refers to for reading, or return `None` if it isn't present with the expected size.
+    pub fn open(&self, pointer: &Pointer) -> io::Result<Option<std::fs::File>> {
+        if !self.contains(pointer) {
+            return Ok(None);
+        }
+        std::fs::File::open(self.object_path(&pointer.oid)).map(Some)
+    }
+}
+
+/// Writing
+impl Store {
+    /// Return a writer to add a new object to the store once it's [committed](Writer::commit()).
+    pub fn writer(&self) -> Result<Writer<'_>, Error> {
+        let tmp_dir = self.root.join("tmp");
+        std::fs::create_dir_all(&tmp_dir).map_err(|source| Error::CreateDirectory {
+            path: tmp_dir.clone(),
+            source,
+        })?;
+        Ok(Writer {
+            store: self,
+            file: tempfile::NamedTempFile::new_in(tmp_dir)?,
+            hasher: gix_features::hash::hasher(gix_hash::Kind::Sha256),
+            size: 0,
+        })
+    }
+
+    /// Add all data read from `src` as object and return its pointer.
+    pub fn insert(&self, src: &mut dyn io::Read) -> Result<Pointer, Error> {
+        let mut writer = self.writer()?;
+        io::copy(src, &mut writer)?;
+        writer.commit(None)
+    }
+}
+
+/// A writer for a new object in a [`Store`], which hashes all data as it's written.
+pub struct Writer<'a> {
+    store: &'a Store,
+    file: tempfile::NamedTempFile,
+    hasher: gix_features::hash::Hasher,
+    size: u64,
+}
+
+impl Writer<'_> {
+    /// Move the written object into place and return its pointer.
+    ///
+    /// If `expected` is set, the object is only stored if its hash and size match, or an error is returned.
+    pub fn commit(self, expected: Option<&Pointer>) -> Result<Pointer, Error> {
+        let pointer = Pointer {
+            oid: self.hasher.digest(),
+            size: self.size,
+            extensions: Vec::new(),
+        };
+        if let Some(expected) = expected {
+            if expected.oid != pointer.oid || expected.size != pointer.size {
+                return Err(Error::Mismatch {
+                    expected: expected.oid,
+                    expected_size: expected.size,
+                    actual: pointer.oid,
+                    actual_size: pointer.size,
+                });
+            }
+        }
+        let path = self.store.object_path(&pointer.oid);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|source| Error::CreateDirectory {
+                path: dir.to_owned(),
+                source,
+            })?;
+        }
+        self.file
+            .persist(&path)
+            .map_err(|source| Error::Persist { path, source })?;
+        Ok(pointer)
+    }
+}
+
+impl io::Write for Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_written = self.file.write(buf)?;
+        self.hasher.update(&buf[..num_written]);
+        self.size += num_written as u64;
+        Ok(num_written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -0,0 +1,43 @@
+use std::{io, path::PathBuf};
+
+use crate::{
+    transfer::{Adapter, Error},
+    Pointer, Store,
+};
+
+/// A transfer adapter which uses the [`Store`] in another directory as remote, like the `lfs` directory of a local clone.
+///
+/// This is what `git-lfs` does for remotes with `file://` URLs, and it's useful for testing as no network is involved.
+pub struct File {
+    remote: Store,
+}
+
+impl File {
+    /// Use the `lfs` directory of the remote, typically `<remote-git-dir>/lfs`, for transfers.
+    pub fn at(lfs_dir: impl Into<PathBuf>) -> Self {
+        File {
+            remote: Store::at(lfs_dir),
+        }
+    }
+}
+
+impl Adapter for File {
+    fn download(&mut self, pointer: &Pointer, out: &mut dyn io::Write) -> Result<(), Error> {
+        let mut file = self
+            .remote
+            .open(pointer)?
+            .ok_or(Error::ObjectNotFound { oid: pointer.oid })?;
+        io::copy(&mut file, out)?;
+        Ok(())
+    }
+
+    fn upload(&mut self, pointer: &Pointer, src: &mut dyn io::Read) -> Result<(), Error> {
+        if self.remote.contains(pointer) {
+            return Ok(());
+        }
+        let mut writer = self.remote.writer()?;
+        io::copy(src, &mut writer)?;
+        writer.commit(Some(pointer))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use std::io;
+
+use gix_hash::ObjectId;
+
+use crate::Pointer;
+
+/// The error returned by [`Adapter`] implementations.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The remote doesn't have object {oid}")]
+    ObjectNotFound { oid: ObjectId },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Store(#[from] crate::store::Error),
+    #[cfg(feature = "http-client")]
+    #[error(transparent)]
+    Batch(#[from] crate::batch::Error),
+}
+
+/// A way to move the contents of large files between the local [store](crate::Store) and a remote.
+pub trait Adapter {
+    /// Write the contents of the object `pointer` refers to into `out`.
+    fn download(&mut self, pointer: &Pointer, out: &mut dyn io::Write) -> Result<(), Error>;
+
+    /// Send the contents of the object `pointer` refers to, as read from `src`, to the remote.
+    fn upload(&mut self, pointer: &Pointer, src: &mut dyn io::Read) -> Result<(), Error>;
+}
+
+impl<T: Adapter + ?Sized> Adapter for Box<T> {
+    fn download(&mut self, pointer: &Pointer, out: &mut dyn io::Write) -> Result<(), Error> {
+        (**self).download(pointer, out)
+    }
+
+    fn upload(&mut self, pointer: &Pointer, src: &mut dyn io::Read) -> Result<(), Error> {
+        (**self).upload(pointer, src)
+    }
+}
+
+mod file;
+pub use file::File;
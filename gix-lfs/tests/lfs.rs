@@ -0,0 +1,489 @@
+use gix_lfs::{Pointer, Store};
+use gix_testtools::Result;
+
+const HELLO_WORLD_OID: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+fn hello_world_pointer() -> Pointer {
+    Pointer {
+        oid: gix_hash::ObjectId::from_hex(HELLO_WORLD_OID.as_bytes()).expect("valid"),
+        size: 11,
+        extensions: Vec::new(),
+    }
+}
+
+fn store_in(dir: &gix_testtools::tempfile::TempDir) -> Store {
+    Store::at(dir.path().join("lfs"))
+}
+
+mod pointer {
+    use gix_lfs::{Extension, Pointer};
+
+    use crate::{hello_world_pointer, HELLO_WORLD_OID};
+
+    #[test]
+    fn round_trip() -> crate::Result {
+        let input = format!("version https://git-lfs.github.com/spec/v1\noid sha256:{HELLO_WORLD_OID}\nsize 11\n");
+        let pointer = Pointer::from_bytes(input.as_bytes())?;
+        assert_eq!(pointer, hello_world_pointer());
+        assert_eq!(pointer.to_bstring(), input);
+        Ok(())
+    }
+
+    #[test]
+    fn extensions_are_ordered_by_priority_and_unknown_keys_are_ignored() -> crate::Result {
+        let input = format!(
+            "version https://hawser.github.com/spec/v1\next-1-bar sha256:{HELLO_WORLD_OID}\next-0-foo sha256:{HELLO_WORLD_OID}\noid sha256:{HELLO_WORLD_OID}\nsize 11\nunknown value\n"
+        );
+        let pointer = Pointer::from_bytes(input.as_bytes())?;
+        let oid = hello_world_pointer().oid;
+        assert_eq!(
+            pointer.extensions,
+            [
+                Extension {
+                    priority: 0,
+                    name: "foo".into(),
+                    oid
+                },
+                Extension {
+                    priority: 1,
+                    name: "bar".into(),
+                    oid
+                }
+            ]
+        );
+        assert_eq!(
+            pointer.to_bstring(),
+            format!(
+                "version https://git-lfs.github.com/spec/v1\next-0-foo sha256:{HELLO_WORLD_OID}\next-1-bar sha256:{HELLO_WORLD_OID}\noid sha256:{HELLO_WORLD_OID}\nsize 11\n"
+            ),
+            "the current version is written, along with canonically ordered keys"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let version = "version https://git-lfs.github.com/spec/v1\n";
+        for (input, expected) in [
+            ("".to_string(), "Pointer files must start with a 'version' line"),
+            (
+                "version https://example.com/v2\n".into(),
+                "Unknown pointer version 'https://example.com/v2'",
+            ),
+            (format!("{version}size 11\n"), "The 'oid' line is missing"),
+            (
+                format!("{version}oid sha256:{HELLO_WORLD_OID}\n"),
+                "The 'size' line is missing",
+            ),
+            (
+                format!("{version}oid sha1:{}\n", &HELLO_WORLD_OID[..40]),
+                "Only 'sha256' object ids are supported, got 'sha1:b94d27b9934d3e08a52e52d7da7dabfac484efe3'",
+            ),
+            (
+                format!("{version}oid sha256:{HELLO_WORLD_OID}\nsize -1\n"),
+                "The size '-1' is not a valid number",
+            ),
+            (
+                format!("{version}novalue\n"),
+                "Expected a line with a key and a value separated by a space, got 'novalue'",
+            ),
+            (
+                format!("{version}ext-10-foo sha256:{HELLO_WORLD_OID}\n"),
+                "The extension 'ext-10-foo' should be named 'ext-<priority>-<name>'",
+            ),
+            (
+                "x".repeat(1024),
+                "Pointer files are smaller than 1024 bytes, got 1024 bytes",
+            ),
+        ] {
+            assert_eq!(
+                Pointer::from_bytes(input.as_bytes()).unwrap_err().to_string(),
+                expected,
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_read() -> crate::Result {
+        assert_eq!(Pointer::from_read(&mut &b"hello world"[..])?, hello_world_pointer());
+        Ok(())
+    }
+}
+
+mod store {
+    use std::io::{Read, Write};
+
+    use crate::{hello_world_pointer, store_in, HELLO_WORLD_OID};
+
+    #[test]
+    fn insert_and_open() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let store = store_in(&tmp);
+        let expected = hello_world_pointer();
+        assert!(!store.contains(&expected));
+        assert!(store.open(&expected)?.is_none());
+
+        assert_eq!(store.insert(&mut &b"hello world"[..])?, expected);
+        assert_eq!(
+            store.object_path(&expected.oid),
+            tmp.path().join("lfs/objects/b9/4d").join(HELLO_WORLD_OID),
+            "the same layout as `git-lfs` is used"
+        );
+        assert!(store.contains(&expected));
+        let mut buf = String::new();
+        store.open(&expected)?.expect("present").read_to_string(&mut buf)?;
+        assert_eq!(buf, "hello world");
+
+        let mut different_size = expected.clone();
+        different_size.size += 1;
+        assert!(!store.contains(&different_size), "sizes must match as well");
+        Ok(())
+    }
+
+    #[test]
+    fn writer_verifies_expected_objects() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let store = store_in(&tmp);
+        let expected = hello_world_pointer();
+        let mut writer = store.writer()?;
+        writer.write_all(b"hello")?;
+        let err = writer.commit(Some(&expected)).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Expected object {HELLO_WORLD_OID} with 11 bytes, but got ")),
+            "{err}"
+        );
+        assert!(!store.contains(&expected));
+        assert_eq!(
+            std::fs::read_dir(store.root().join("tmp"))?.count(),
+            0,
+            "temporary files are removed"
+        );
+
+        let mut writer = store.writer()?;
+        writer.write_all(b"hello world")?;
+        assert_eq!(writer.commit(Some(&expected))?, expected);
+        assert!(store.contains(&expected));
+        Ok(())
+    }
+}
+
+mod transfer {
+    use gix_lfs::transfer::{Adapter, File};
+
+    use crate::{hello_world_pointer, store_in};
+
+    #[test]
+    fn file_upload_and_download() -> crate::Result {
+        let remote = gix_testtools::tempfile::TempDir::new()?;
+        let mut adapter = File::at(remote.path().join("lfs"));
+        let pointer = hello_world_pointer();
+
+        let mut out = Vec::new();
+        assert_eq!(
+            adapter.download(&pointer, &mut out).unwrap_err().to_string(),
+            format!("The remote doesn't have object {}", pointer.oid)
+        );
+
+        assert!(
+            adapter.upload(&pointer, &mut &b"hello"[..]).is_err(),
+            "the data must match the pointer"
+        );
+        adapter.upload(&pointer, &mut &b"hello world"[..])?;
+        assert!(store_in(&remote).contains(&pointer));
+
+        adapter.download(&pointer, &mut out)?;
+        assert_eq!(out, b"hello world");
+        Ok(())
+    }
+}
+
+mod filter {
+    use std::{io::Read, sync::Arc};
+
+    use gix_filter::driver::{apply, Operation};
+    use gix_lfs::{transfer, Filter};
+
+    use crate::{hello_world_pointer, store_in, HELLO_WORLD_OID};
+
+    fn apply(state: &mut gix_filter::driver::State, operation: Operation, input: &[u8]) -> crate::Result<Vec<u8>> {
+        let driver = gix_filter::Driver {
+            name: gix_lfs::DRIVER_NAME.into(),
+            clean: None,
+            smudge: None,
+            process: None,
+            required: true,
+        };
+        let mut out = Vec::new();
+        state
+            .apply(
+                &driver,
+                &mut &*input,
+                operation,
+                apply::Context {
+                    rela_path: "large.bin".into(),
+                    ref_name: None,
+                    treeish: None,
+                    blob: None,
+                },
+            )?
+            .expect("in-process filters always apply")
+            .read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn clean_and_smudge_through_driver_state() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut state = gix_filter::driver::State::default();
+        state.register_in_process(gix_lfs::DRIVER_NAME, Arc::new(Filter::new(store_in(&tmp))));
+
+        let pointer = apply(&mut state, Operation::Clean, b"hello world")?;
+        assert_eq!(pointer, hello_world_pointer().to_bstring());
+        assert!(store_in(&tmp).contains(&hello_world_pointer()));
+
+        assert_eq!(
+            apply(&mut state, Operation::Clean, &pointer)?,
+            pointer,
+            "pointers are passed through"
+        );
+        assert_eq!(
+            apply(&mut state, Operation::Clean, b"")?,
+            b"",
+            "empty files are stored as they are"
+        );
+
+        assert_eq!(apply(&mut state, Operation::Smudge, &pointer)?, b"hello world");
+        assert_eq!(
+            apply(&mut state, Operation::Smudge, b"not a pointer")?,
+            b"not a pointer",
+            "everything that isn't a pointer is passed through"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn smudge_missing_objects() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let pointer = hello_world_pointer();
+
+        let filter = Filter::new(store_in(&tmp));
+        assert_eq!(
+            filter.smudge(&pointer).unwrap_err().to_string(),
+            format!("Object {HELLO_WORLD_OID} isn't available locally, and there is no way to download it")
+        );
+        let filter = filter.with_keep_pointer_if_missing(true);
+        assert!(filter.smudge(&pointer)?.is_none());
+
+        let remote = gix_testtools::tempfile::TempDir::new()?;
+        let mut adapter = transfer::File::at(remote.path().join("lfs"));
+        transfer::Adapter::upload(&mut adapter, &pointer, &mut &b"hello world"[..])?;
+
+        let mut state = gix_filter::driver::State::default();
+        state.register_in_process(
+            gix_lfs::DRIVER_NAME,
+            Arc::new(Filter::new(store_in(&tmp)).with_transfer(adapter)),
+        );
+        assert_eq!(
+            apply(&mut state, Operation::Smudge, &pointer.to_bstring())?,
+            b"hello world",
+            "missing objects are downloaded"
+        );
+        assert!(store_in(&tmp).contains(&pointer), "and stored locally");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "http-client")]
+mod batch {
+    use std::{
+        cell::RefCell,
+        io::{Cursor, Empty},
+        rc::Rc,
+    };
+
+    use gix_lfs::{batch, transfer::Adapter};
+    use gix_transport::client::http;
+
+    use crate::{hello_world_pointer, HELLO_WORLD_OID};
+
+    #[derive(Default, Clone)]
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The method, URL and headers of each request.
+    type Requests = Rc<RefCell<Vec<(&'static str, String, Vec<String>)>>>;
+
+    /// Respond to POST requests with `batch_response` and to GET requests with `data`, while recording all requests.
+    struct Mock {
+        batch_response: String,
+        data: Vec<u8>,
+        requests: Requests,
+        posted: SharedBuf,
+    }
+
+    impl http::Http for Mock {
+        type Headers = Empty;
+        type ResponseBody = Cursor<Vec<u8>>;
+        type PostBody = SharedBuf;
+
+        fn get(
+            &mut self,
+            url: &str,
+            _base_url: &str,
+            headers: impl IntoIterator<Item = impl AsRef<str>>,
+        ) -> Result<http::GetResponse<Self::Headers, Self::ResponseBody>, http::Error> {
+            self.requests.borrow_mut().push(("GET", url.into(), collect(headers)));
+            Ok(http::GetResponse {
+                headers: std::io::empty(),
+                body: Cursor::new(self.data.clone()),
+            })
+        }
+
+        fn post(
+            &mut self,
+            url: &str,
+            _base_url: &str,
+            headers: impl IntoIterator<Item = impl AsRef<str>>,
+            _body: http::PostBodyDataKind,
+        ) -> Result<http::PostResponse<Self::Headers, Self::ResponseBody, Self::PostBody>, http::Error> {
+            self.requests.borrow_mut().push(("POST", url.into(), collect(headers)));
+            Ok(http::PostResponse {
+                post_body: self.posted.clone(),
+                headers: std::io::empty(),
+                body: Cursor::new(self.batch_response.clone().into_bytes()),
+            })
+        }
+
+        fn configure(
+            &mut self,
+            _config: &dyn std::any::Any,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            Ok(())
+        }
+    }
+
+    fn collect(headers: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<String> {
+        headers.into_iter().map(|h| h.as_ref().to_owned()).collect()
+    }
+
+    fn client_responding_with(batch_response: String) -> batch::Client<Mock> {
+        batch::Client::new(
+            Mock {
+                batch_response,
+                data: b"hello world".to_vec(),
+                requests: Default::default(),
+                posted: SharedBuf::default(),
+            },
+            batch::endpoint("https://example.com/repo"),
+        )
+        .with_header("Authorization: Basic secret")
+    }
+
+    #[test]
+    fn endpoint() {
+        for url in [
+            "https://example.com/repo",
+            "https://example.com/repo.git",
+            "https://example.com/repo.git/",
+        ] {
+            assert_eq!(batch::endpoint(url), "https://example.com/repo.git/info/lfs");
+        }
+    }
+
+    #[test]
+    fn download() -> crate::Result {
+        let mut client = client_responding_with(format!(
+            r#"{{"transfer": "basic", "objects": [{{"oid": "{HELLO_WORLD_OID}", "size": 11, "actions": {{"download": {{"href": "https://cdn.example.com/obj", "header": {{"X-Token": "t"}}, "expires_in": 3600}}}}}}]}}"#
+        ));
+        let mut out = Vec::new();
+        client.download(&hello_world_pointer(), &mut out)?;
+        assert_eq!(out, b"hello world");
+        Ok(())
+    }
+
+    #[test]
+    fn requests_are_sent_as_specified() -> crate::Result {
+        let mock = Mock {
+            batch_response: format!(
+                r#"{{"objects": [{{"oid": "{HELLO_WORLD_OID}", "size": 11, "actions": {{"download": {{"href": "https://cdn.example.com/obj", "header": {{"X-Token": "t"}}}}}}}}]}}"#
+            ),
+            data: b"hello world".to_vec(),
+            requests: Default::default(),
+            posted: SharedBuf::default(),
+        };
+        let (posted, requests) = (mock.posted.clone(), mock.requests.clone());
+        let mut client = batch::Client::new(mock, batch::endpoint("https://example.com/repo"))
+            .with_header("Authorization: Basic secret");
+        let mut out = Vec::new();
+        client.download(&hello_world_pointer(), &mut out)?;
+
+        let request: serde_json::Value = serde_json::from_slice(&posted.0.borrow())?;
+        assert_eq!(
+            request,
+            serde_json::json!({
+                "operation": "download",
+                "transfers": ["basic"],
+                "objects": [{"oid": HELLO_WORLD_OID, "size": 11}],
+                "hash_algo": "sha256"
+            })
+        );
+        assert_eq!(
+            *requests.borrow(),
+            [
+                (
+                    "POST",
+                    "https://example.com/repo.git/info/lfs/objects/batch".to_string(),
+                    vec![
+                        "Accept: application/vnd.git-lfs+json".to_string(),
+                        "Content-Type: application/vnd.git-lfs+json".into(),
+                        "Authorization: Basic secret".into()
+                    ]
+                ),
+                (
+                    "GET",
+                    "https://cdn.example.com/obj".into(),
+                    vec!["X-Token: t".to_string()]
+                )
+            ],
+            "headers of actions are used for downloads, while custom headers are only used for batch requests"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn object_errors_and_missing_uploads() -> crate::Result {
+        let pointer = hello_world_pointer();
+        let mut client = client_responding_with(format!(
+            r#"{{"objects": [{{"oid": "{HELLO_WORLD_OID}", "size": 11, "error": {{"code": 404, "message": "Object does not exist"}}}}]}}"#
+        ));
+        let mut out = Vec::new();
+        assert_eq!(
+            client.download(&pointer, &mut out).unwrap_err().to_string(),
+            format!("The server failed to provide object {HELLO_WORLD_OID} with code 404: Object does not exist")
+        );
+
+        let mut client = client_responding_with(format!(
+            r#"{{"objects": [{{"oid": "{HELLO_WORLD_OID}", "size": 11}}]}}"#
+        ));
+        client.upload(&pointer, &mut &b"hello world"[..])?;
+
+        let mut client = client_responding_with(r#"{"transfer": "ssh", "objects": []}"#.into());
+        assert_eq!(
+            client.upload(&pointer, &mut &b""[..]).unwrap_err().to_string(),
+            "The server only offers the 'ssh' transfer, but only 'basic' is supported"
+        );
+        Ok(())
+    }
+}
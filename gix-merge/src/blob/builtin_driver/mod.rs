@@ -0,0 +1,9 @@
+///
+#[allow(clippy::empty_docs)]
+pub mod binary;
+pub use binary::function::merge as binary;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod text;
+pub use text::function::merge as text;
@@ -0,0 +1,322 @@
+use std::num::NonZeroU8;
+
+/// The way the built-in [text driver](crate::blob::BuiltinDriver::Text) will express
+/// merge conflicts in the resulting file.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ConflictStyle {
+    /// Only show the zealously minified conflicting lines of the local changes and the incoming (other) changes,
+    /// hiding the base version entirely.
+    ///
+    /// ```text
+    /// line1-changed-by-both
+    /// <<<<<<< local
+    /// line2-to-be-changed-in-incoming
+    /// =======
+    /// line2-changed
+    /// >>>>>>> incoming
+    /// ```
+    #[default]
+    Merge,
+    /// Show non-minimized hunks of local changes, the base, and the incoming (other) changes.
+    ///
+    /// This mode does not hide any information.
+    ///
+    /// ```text
+    /// <<<<<<< local
+    /// line1-changed-by-both
+    /// line2-to-be-changed-in-incoming
+    /// ||||||| 9a8d80c
+    /// line1-to-be-changed-by-both
+    /// line2-to-be-changed-in-incoming
+    /// =======
+    /// line1-changed-by-both
+    /// line2-changed
+    /// >>>>>>> incoming
+    /// ```
+    Diff3,
+    /// Like [`Diff3`](Self::Diff3), but will show *minimized* hunks of local change and the incoming (other) changes,
+    /// as well as non-minimized hunks of the base.
+    ///
+    /// ```text
+    /// line1-changed-by-both
+    /// <<<<<<< local
+    /// line2-to-be-changed-in-incoming
+    /// ||||||| 9a8d80c
+    /// line1-to-be-changed-by-both
+    /// line2-to-be-changed-in-incoming
+    /// =======
+    /// line2-changed
+    /// >>>>>>> incoming
+    /// ```
+    ZealousDiff3,
+}
+
+/// What to do when encountering a conflict in the [text merge](function::merge).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Conflict {
+    /// Keep the conflict by marking it in the source file.
+    Keep {
+        /// How to visualize conflicts in merged files.
+        style: ConflictStyle,
+        /// The amount of markers to draw, typically 7, as set by the `conflict-marker-size` git-attribute.
+        marker_size: NonZeroU8,
+    },
+    /// Chose our side to resolve a conflict.
+    ResolveWithOurs,
+    /// Chose their side to resolve a conflict.
+    ResolveWithTheirs,
+    /// Place our and their lines one after another, in any order, to resolve a conflict.
+    ResolveWithUnion,
+}
+
+impl Conflict {
+    /// The amount of conflict marker characters to print by default.
+    pub const DEFAULT_MARKER_SIZE: u8 = 7;
+
+    /// The amount of conflict markers to print if this instance contains them, or `None` otherwise
+    pub fn marker_size(&self) -> Option<u8> {
+        match self {
+            Conflict::Keep { marker_size, .. } => Some(marker_size.get()),
+            Conflict::ResolveWithOurs | Conflict::ResolveWithTheirs | Conflict::ResolveWithUnion => None,
+        }
+    }
+}
+
+impl Default for Conflict {
+    fn default() -> Self {
+        Conflict::Keep {
+            style: Default::default(),
+            marker_size: Conflict::DEFAULT_MARKER_SIZE.try_into().expect("non-zero"),
+        }
+    }
+}
+
+/// Options for the builtin [text driver](crate::blob::BuiltinDriver::Text).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Determine of the diff will be performed.
+    /// Defaults to [`Myers`](gix_diff::blob::Algorithm::Myers), just like `git`.
+    pub diff_algorithm: gix_diff::blob::Algorithm,
+    /// Decide what to do to automatically resolve conflicts, or to keep them.
+    pub conflict: Conflict,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            conflict: Default::default(),
+            diff_algorithm: gix_diff::blob::Algorithm::Myers,
+        }
+    }
+}
+
+pub(super) mod function {
+    use std::ops::Range;
+
+    use bstr::BStr;
+    use gix_diff::blob::intern::{InternedInput, Token, TokenSource};
+
+    use crate::blob::{
+        builtin_driver::text::{Conflict, ConflictStyle, Options},
+        Labels, Resolution,
+    };
+
+    /// Merge `current` and `other` with `ancestor` as base according to `opts`, and write the result to `out`.
+    ///
+    /// Use `labels` to annotate conflict sections, and `input` to hold the interned lines of all three inputs,
+    /// which is cleared before use so it can be reused across invocations.
+    ///
+    /// Overlapping or adjacent changes of both sides to the same region of `ancestor` are conflicts, unless both sides
+    /// changed the region in the same way.
+    pub fn merge<'a>(
+        out: &mut Vec<u8>,
+        input: &mut InternedInput<&'a [u8]>,
+        labels: Labels<'_>,
+        current: &'a [u8],
+        ancestor: &'a [u8],
+        other: &'a [u8],
+        opts: Options,
+    ) -> Resolution {
+        out.clear();
+        input.clear();
+        input.update_before(tokens(ancestor));
+        input.update_after(tokens(current));
+        let current_hunks = hunks(opts.diff_algorithm, input, Side::Current);
+        let current_tokens = std::mem::take(&mut input.after);
+        input.update_after(tokens(other));
+        let other_hunks = hunks(opts.diff_algorithm, input, Side::Other);
+
+        let mut resolution = Resolution::Complete;
+        let mut current_hunks = current_hunks.into_iter().peekable();
+        let mut other_hunks = other_hunks.into_iter().peekable();
+        let (mut current_offset, mut other_offset) = (0i64, 0i64);
+        let mut ancestor_pos = 0u32;
+        loop {
+            let first = match (current_hunks.peek(), other_hunks.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Side::Current,
+                (None, Some(_)) => Side::Other,
+                (Some(current), Some(other)) => {
+                    if current.before.start <= other.before.start {
+                        Side::Current
+                    } else {
+                        Side::Other
+                    }
+                }
+            };
+            let first = match first {
+                Side::Current => current_hunks.next(),
+                Side::Other => other_hunks.next(),
+            }
+            .expect("peeked");
+            let (group_start, mut group_end) = (first.before.start, first.before.end);
+            let (mut current_group, mut other_group) = (Vec::new(), Vec::new());
+            match first.side {
+                Side::Current => current_group.push(first),
+                Side::Other => other_group.push(first),
+            }
+            loop {
+                if let Some(hunk) = current_hunks.next_if(|h| h.before.start <= group_end) {
+                    group_end = group_end.max(hunk.before.end);
+                    current_group.push(hunk);
+                } else if let Some(hunk) = other_hunks.next_if(|h| h.before.start <= group_end) {
+                    group_end = group_end.max(hunk.before.end);
+                    other_group.push(hunk);
+                } else {
+                    break;
+                }
+            }
+
+            write_tokens(out, input, &input.before[ancestor_pos as usize..group_start as usize]);
+            let group = group_start..group_end;
+            let current_range = side_range(&current_group, &group, current_offset);
+            let other_range = side_range(&other_group, &group, other_offset);
+            let current_side = &current_tokens[current_range.clone()];
+            let other_side = &input.after[other_range.clone()];
+            current_offset += offset_of(&current_group);
+            other_offset += offset_of(&other_group);
+
+            if other_group.is_empty() || current_side == other_side {
+                write_tokens(out, input, current_side);
+            } else if current_group.is_empty() {
+                write_tokens(out, input, other_side);
+            } else {
+                let ancestor_side = &input.before[group.start as usize..group.end as usize];
+                let (prefix, suffix) = match opts.conflict {
+                    Conflict::Keep {
+                        style: ConflictStyle::Diff3,
+                        ..
+                    } => (0, 0),
+                    _ => common_prefix_and_suffix(current_side, other_side),
+                };
+                write_tokens(out, input, &current_side[..prefix]);
+                let current_side = &current_side[prefix..current_side.len() - suffix];
+                let other_side_trimmed = &other_side[prefix..other_side.len() - suffix];
+                match opts.conflict {
+                    Conflict::Keep { style, marker_size } => {
+                        let marker_size = marker_size.get() as usize;
+                        write_marker(out, b'<', marker_size, labels.current);
+                        write_tokens(out, input, current_side);
+                        if style != ConflictStyle::Merge {
+                            write_marker(out, b'|', marker_size, labels.ancestor);
+                            write_tokens(out, input, ancestor_side);
+                        }
+                        write_marker(out, b'=', marker_size, None);
+                        write_tokens(out, input, other_side_trimmed);
+                        write_marker(out, b'>', marker_size, labels.other);
+                        resolution = Resolution::Conflict;
+                    }
+                    Conflict::ResolveWithOurs => write_tokens(out, input, current_side),
+                    Conflict::ResolveWithTheirs => write_tokens(out, input, other_side_trimmed),
+                    Conflict::ResolveWithUnion => {
+                        write_tokens(out, input, current_side);
+                        ensure_newline(out);
+                        write_tokens(out, input, other_side_trimmed);
+                    }
+                }
+                if resolution == Resolution::Complete {
+                    resolution = Resolution::CompleteWithAutoResolvedConflict;
+                }
+                write_tokens(out, input, &other_side[other_side.len() - suffix..]);
+            }
+            ancestor_pos = group.end;
+        }
+        write_tokens(out, input, &input.before[ancestor_pos as usize..]);
+        resolution
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    enum Side {
+        Current,
+        Other,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Hunk {
+        side: Side,
+        before: Range<u32>,
+        after: Range<u32>,
+    }
+
+    fn tokens(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+        gix_diff::blob::sources::byte_lines_with_terminator(input).tokenize()
+    }
+
+    /// Return the changes needed to turn `input.before` into `input.after`, with `input.after` being assigned to `side`.
+    fn hunks(algorithm: gix_diff::blob::Algorithm, input: &InternedInput<&[u8]>, side: Side) -> Vec<Hunk> {
+        let mut out = Vec::new();
+        gix_diff::blob::diff(algorithm, input, |before: Range<u32>, after: Range<u32>| {
+            out.push(Hunk { side, before, after });
+        });
+        out
+    }
+
+    /// Return the range of tokens on the side of `hunks` that corresponds to the `group` range in the ancestor.
+    /// `offset` is the difference in tokens between the side and the ancestor accumulated by all previous hunks.
+    fn side_range(hunks: &[Hunk], group: &Range<u32>, offset: i64) -> Range<usize> {
+        match (hunks.first(), hunks.last()) {
+            (Some(first), Some(last)) => {
+                (first.after.start - (first.before.start - group.start)) as usize
+                    ..(last.after.end + (group.end - last.before.end)) as usize
+            }
+            _ => (group.start as i64 + offset) as usize..(group.end as i64 + offset) as usize,
+        }
+    }
+
+    fn offset_of(hunks: &[Hunk]) -> i64 {
+        hunks.iter().map(|h| h.after.len() as i64 - h.before.len() as i64).sum()
+    }
+
+    fn common_prefix_and_suffix(a: &[Token], b: &[Token]) -> (usize, usize) {
+        let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+        let suffix = a[prefix..]
+            .iter()
+            .rev()
+            .zip(b[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        (prefix, suffix)
+    }
+
+    fn write_tokens(out: &mut Vec<u8>, input: &InternedInput<&[u8]>, tokens: &[Token]) {
+        for token in tokens {
+            out.extend_from_slice(input.interner[*token]);
+        }
+    }
+
+    fn ensure_newline(out: &mut Vec<u8>) {
+        if !out.is_empty() && !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+
+    fn write_marker(out: &mut Vec<u8>, marker: u8, marker_size: usize, label: Option<&BStr>) {
+        ensure_newline(out);
+        out.extend(std::iter::repeat(marker).take(marker_size));
+        if let Some(label) = label {
+            out.push(b' ');
+            out.extend_from_slice(label);
+        }
+        out.push(b'\n');
+    }
+}
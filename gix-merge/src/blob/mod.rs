@@ -0,0 +1,141 @@
+use bstr::{BStr, BString};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod builtin_driver;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod platform;
+pub use platform::Platform;
+
+/// Identify a merge resolution.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Resolution {
+    /// Everything could be resolved during the merge.
+    Complete,
+    /// Conflicts were encountered, but they were resolved automatically as instructed by the configured
+    /// [way to handle them](builtin_driver::text::Conflict).
+    CompleteWithAutoResolvedConflict,
+    /// A conflict is still present in the form of conflict markers, or a choice had to be made for binary files.
+    Conflict,
+}
+
+/// A way to classify the kind of merge to perform, as chosen by the `merge` git-attribute.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BuiltinDriver {
+    /// Perform a merge between text-sources such that conflicts are marked according to
+    /// `merge.conflictStyle` in the `git-config`.
+    ///
+    /// If any of the inputs, *base*, *ours* or *theirs* looks like non-text/binary,
+    /// the [`Binary`](Self::Binary) driver will be used instead.
+    ///
+    /// Also see [`builtin_driver::text::Conflict`].
+    #[default]
+    Text,
+    /// Merge 'unmergable' content by choosing *ours* or *theirs*, without performing a fine-grained merge.
+    ///
+    /// This is used if the `merge` attribute is unset, i.e. `-merge`.
+    Binary,
+    /// Merge text-sources and resolve conflicts by adding conflicting lines one after another,
+    /// in random order, without adding conflict markers either.
+    ///
+    /// This can be useful for files that change a lot, but will remain usable merely by adding
+    /// all changed lines.
+    Union,
+}
+
+impl BuiltinDriver {
+    /// Return the name of this driver as it is used in the `merge` attribute, like `union` in `*.txt merge=union`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuiltinDriver::Text => "text",
+            BuiltinDriver::Binary => "binary",
+            BuiltinDriver::Union => "union",
+        }
+    }
+
+    /// Return the driver whose name is `name`, or `None` if there is no such built-in driver.
+    pub fn by_name(name: &str) -> Option<Self> {
+        [BuiltinDriver::Text, BuiltinDriver::Binary, BuiltinDriver::Union]
+            .into_iter()
+            .find(|driver| driver.as_str() == name)
+    }
+}
+
+/// Define a driver program that merges three versions of a file, as configured in `[merge "<name>"]` sections.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Driver {
+    /// The name of the driver, as referred to by `[merge "name"]` in the git configuration.
+    pub name: BString,
+    /// The human-readable version of `name`, only to be used for displaying driver-information to the user.
+    pub display_name: BString,
+    /// The command to execute to perform the merge entirely like `<command> %O %A %B %L %P %S %X %Y`.
+    ///
+    /// * **%O**
+    ///     - the common ancestor version, or *base*.
+    /// * **%A**
+    ///     - the current version, or *ours*.
+    /// * **%B**
+    ///     - the other version, or *theirs*.
+    /// * **%L**
+    ///     - The conflict-marker size as positive number.
+    /// * **%P**
+    ///     - The path in which the merged result would be stored, as workspace-relative path, of the current/ours side.
+    /// * **%S**
+    ///     - The conflict-label for the common ancestor or *base*.
+    /// * **%X**
+    ///     - The conflict-label for the current version or *ours*.
+    /// * **%Y**
+    ///     - The conflict-label for the other version or *theirs*.
+    ///
+    /// Note that conflict-labels are behind the conflict markers, to annotate them.
+    ///
+    /// A typical invocation with all arguments substituted could then look like this:
+    ///
+    /// ```text
+    /// <driver-program> .merge_file_nR2Qs1 .merge_file_WYXCJe .merge_file_UWbzrm 7 file e2a2970 HEAD feature
+    /// ```
+    ///
+    /// The driver is expected to leave its version in the file at `%A`, by overwriting it.
+    pub command: BString,
+    /// If set, this is the `name` of the driver to use when a virtual-merge-base is created, as a merge of all
+    /// available merge-bases if there are more than one.
+    ///
+    /// This value can also be special built-in drivers named `text`, `binary` or `union`. Note that user-defined
+    /// drivers with the same name will be preferred over built-in ones, but only for files whose git attributes
+    /// specified the driver by *name*.
+    pub recursive: Option<BString>,
+}
+
+/// The way the built-in or user-defined [`Driver`] is chosen for a particular path.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DriverChoice {
+    /// Use the given built-in driver to perform the merge.
+    BuiltIn(BuiltinDriver),
+    /// Use the user-provided driver program using the index into [the platform drivers array](platform::Platform::drivers()).
+    Index(usize),
+}
+
+impl Default for DriverChoice {
+    fn default() -> Self {
+        DriverChoice::BuiltIn(Default::default())
+    }
+}
+
+/// The labels to use for annotating conflict markers, or to pass to [user-defined drivers](Driver).
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Labels<'a> {
+    /// The label for the common ancestor, or *base*, used with the `diff3` conflict style.
+    pub ancestor: Option<&'a BStr>,
+    /// The label for the current version, or *ours*.
+    pub current: Option<&'a BStr>,
+    /// The label for the other version, or *theirs*.
+    pub other: Option<&'a BStr>,
+}
+
+/// Return `true` if `data` looks like binary data, using the same heuristic as `git` does.
+pub(crate) fn is_binary(data: &[u8]) -> bool {
+    const FIRST_FEW_BYTES: usize = 8000;
+    data[..data.len().min(FIRST_FEW_BYTES)].contains(&0)
+}
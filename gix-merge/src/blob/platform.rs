@@ -0,0 +1,297 @@
+use std::{io::Write, num::NonZeroU8, process::Stdio};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::blob::{builtin_driver, is_binary, BuiltinDriver, Driver, DriverChoice, Labels, Resolution};
+
+/// A platform to merge blobs, which picks the [driver](DriverChoice) to use according to the `merge`
+/// git-attribute of each path, and which applies the `conflict-marker-size` attribute as well.
+pub struct Platform {
+    /// All available merge drivers, sorted by name.
+    drivers: Vec<Driver>,
+    /// The way to obtain attributes for each path.
+    attr_stack: gix_worktree::Stack,
+    /// A pre-initialized container for the attributes we are interested in.
+    attrs: gix_worktree::attributes::search::Outcome,
+    /// Options to control how merges are performed.
+    pub options: Options,
+}
+
+/// Options for use in [`Platform::new()`].
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// Options for the built-in text driver, which are also used by the `union` driver.
+    pub text: builtin_driver::text::Options,
+    /// If `Some(…)`, resolve conflicts of binary files by picking the given side, instead of keeping the conflict.
+    pub resolve_binary_with: Option<builtin_driver::binary::ResolveWith>,
+    /// The name of the driver to use if the `merge` attribute isn't specified for a path, like `merge.default`.
+    ///
+    /// It may refer to one of the [drivers](Platform::drivers()) or to one of the built-in ones.
+    /// If `None`, the [text driver](BuiltinDriver::Text) is used.
+    pub default_driver: Option<BString>,
+}
+
+/// Lifecycle
+impl Platform {
+    /// Create a new instance with `drivers` to use for paths that ask for them by name, `attr_stack` to look up
+    /// the `merge` and `conflict-marker-size` git-attributes, and `options` to control how merges are done.
+    pub fn new(mut drivers: Vec<Driver>, attr_stack: gix_worktree::Stack, options: Options) -> Self {
+        drivers.sort_by(|a, b| a.name.cmp(&b.name));
+        let attrs = attr_stack.selected_attribute_matches(["merge", "conflict-marker-size"]);
+        Platform {
+            drivers,
+            attr_stack,
+            attrs,
+            options,
+        }
+    }
+}
+
+/// Access
+impl Platform {
+    /// Return all drivers that this instance was initialized with, sorted by name.
+    pub fn drivers(&self) -> &[Driver] {
+        &self.drivers
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge {
+    use bstr::BString;
+
+    /// The error returned by [Platform::merge()](super::Platform::merge()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not obtain attributes for '{rela_path}'")]
+        Attributes { rela_path: BString, source: std::io::Error },
+        #[error("Could not write a temporary file to pass to merge driver '{driver}'")]
+        WriteTempfile { driver: BString, source: std::io::Error },
+        #[error("Failed to run merge driver '{driver}' with command {command:?}")]
+        RunDriver {
+            driver: BString,
+            command: BString,
+            source: std::io::Error,
+        },
+        #[error("Could not read the merge result of driver '{driver}'")]
+        ReadResult { driver: BString, source: std::io::Error },
+    }
+}
+
+/// Merging
+impl Platform {
+    /// Determine the driver to use for `rela_path` according to its git-attributes, along with the conflict-marker size
+    /// if it was overridden by attributes.
+    /// `objects` is used to read `.gitattributes` files from the object database if needed.
+    pub fn driver_for_path(
+        &mut self,
+        rela_path: &BStr,
+        objects: &dyn gix_object::Find,
+    ) -> Result<(DriverChoice, Option<NonZeroU8>), merge::Error> {
+        use gix_worktree::attributes::StateRef;
+        let entry = self
+            .attr_stack
+            .at_entry(rela_path, None, objects)
+            .map_err(|err| merge::Error::Attributes {
+                rela_path: rela_path.to_owned(),
+                source: err,
+            })?;
+        entry.matching_attributes(&mut self.attrs);
+
+        let mut attrs = self.attrs.iter_selected();
+        let merge = attrs.next().expect("first selected attribute").assignment.state;
+        let marker_size = attrs.next().expect("second selected attribute").assignment.state;
+        let driver = match merge {
+            StateRef::Set => DriverChoice::BuiltIn(BuiltinDriver::Text),
+            StateRef::Unset => DriverChoice::BuiltIn(BuiltinDriver::Binary),
+            StateRef::Value(name) => self.find_driver(name.as_bstr()),
+            StateRef::Unspecified => self
+                .options
+                .default_driver
+                .as_ref()
+                .map(|name| self.find_driver(name.as_ref()))
+                .unwrap_or_default(),
+        };
+        let marker_size = match marker_size {
+            StateRef::Value(size) => size
+                .as_bstr()
+                .to_str()
+                .ok()
+                .and_then(|size| size.parse::<u8>().ok())
+                .and_then(NonZeroU8::new),
+            _ => None,
+        };
+        Ok((driver, marker_size))
+    }
+
+    /// Merge `current` and `other` with `ancestor` as common base and write the result into `out`,
+    /// using the driver that the git-attributes of `rela_path` ask for.
+    /// `labels` are used to annotate conflict markers, and `objects` is used to read `.gitattributes` files if needed.
+    ///
+    /// Note that binary files are always merged with the [binary driver](BuiltinDriver::Binary), unless a
+    /// user-defined driver is used.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge(
+        &mut self,
+        out: &mut Vec<u8>,
+        rela_path: &BStr,
+        labels: Labels<'_>,
+        current: &[u8],
+        ancestor: &[u8],
+        other: &[u8],
+        objects: &dyn gix_object::Find,
+    ) -> Result<Resolution, merge::Error> {
+        let (driver, marker_size) = self.driver_for_path(rela_path, objects)?;
+        let mut text_options = self.options.text;
+        if let (Some(size), builtin_driver::text::Conflict::Keep { marker_size, .. }) =
+            (marker_size, &mut text_options.conflict)
+        {
+            *marker_size = size;
+        }
+
+        let builtin = match driver {
+            DriverChoice::Index(idx) => {
+                let driver = &self.drivers[idx];
+                return run_driver(
+                    driver,
+                    out,
+                    rela_path,
+                    labels,
+                    text_options
+                        .conflict
+                        .marker_size()
+                        .unwrap_or(builtin_driver::text::Conflict::DEFAULT_MARKER_SIZE),
+                    current,
+                    ancestor,
+                    other,
+                );
+            }
+            DriverChoice::BuiltIn(builtin) => builtin,
+        };
+
+        out.clear();
+        if current == other || ancestor == other {
+            out.extend_from_slice(current);
+            return Ok(Resolution::Complete);
+        } else if ancestor == current {
+            out.extend_from_slice(other);
+            return Ok(Resolution::Complete);
+        }
+
+        let builtin = if [current, ancestor, other].into_iter().any(is_binary) {
+            BuiltinDriver::Binary
+        } else {
+            builtin
+        };
+        let mut input = gix_diff::blob::intern::InternedInput::default();
+        Ok(match builtin {
+            BuiltinDriver::Text => {
+                builtin_driver::text(out, &mut input, labels, current, ancestor, other, text_options)
+            }
+            BuiltinDriver::Union => {
+                text_options.conflict = builtin_driver::text::Conflict::ResolveWithUnion;
+                builtin_driver::text(out, &mut input, labels, current, ancestor, other, text_options)
+            }
+            BuiltinDriver::Binary => {
+                let (pick, resolution) = builtin_driver::binary(self.options.resolve_binary_with);
+                out.extend_from_slice(match pick {
+                    builtin_driver::binary::Pick::Ancestor => ancestor,
+                    builtin_driver::binary::Pick::Ours => current,
+                    builtin_driver::binary::Pick::Theirs => other,
+                });
+                resolution
+            }
+        })
+    }
+
+    fn find_driver(&self, name: &BStr) -> DriverChoice {
+        self.drivers
+            .binary_search_by(|d| d.name.as_bstr().cmp(name))
+            .ok()
+            .map(DriverChoice::Index)
+            .or_else(|| {
+                name.to_str()
+                    .ok()
+                    .and_then(BuiltinDriver::by_name)
+                    .map(DriverChoice::BuiltIn)
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Run the user-defined `driver` according to protocol and place its result into `out`.
+#[allow(clippy::too_many_arguments)]
+fn run_driver(
+    driver: &Driver,
+    out: &mut Vec<u8>,
+    rela_path: &BStr,
+    labels: Labels<'_>,
+    marker_size: u8,
+    current: &[u8],
+    ancestor: &[u8],
+    other: &[u8],
+) -> Result<Resolution, merge::Error> {
+    let write_tempfile = |data: &[u8]| -> Result<_, std::io::Error> {
+        let mut tmp = gix_tempfile::new(
+            std::env::temp_dir(),
+            gix_tempfile::ContainingDirectory::Exists,
+            gix_tempfile::AutoRemove::Tempfile,
+        )?;
+        tmp.write_all(data)?;
+        let path = tmp.with_mut(|f| f.path().to_owned())?;
+        Ok((tmp.close()?, path))
+    };
+    let map_write_err = |err| merge::Error::WriteTempfile {
+        driver: driver.name.clone(),
+        source: err,
+    };
+    let (_ancestor_tmp, ancestor_path) = write_tempfile(ancestor).map_err(map_write_err)?;
+    let (_current_tmp, current_path) = write_tempfile(current).map_err(map_write_err)?;
+    let (_other_tmp, other_path) = write_tempfile(other).map_err(map_write_err)?;
+
+    let quote_path = |path: &std::path::Path| gix_quote::single(gix_path::into_bstr(path).as_ref());
+    let mut command = BString::default();
+    let mut bytes = driver.command.iter().copied();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            command.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'O') => command.extend_from_slice(&quote_path(&ancestor_path)),
+            Some(b'A') => command.extend_from_slice(&quote_path(&current_path)),
+            Some(b'B') => command.extend_from_slice(&quote_path(&other_path)),
+            Some(b'L') => command.extend_from_slice(marker_size.to_string().as_bytes()),
+            Some(b'P') => command.extend_from_slice(&gix_quote::single(rela_path)),
+            Some(b'S') => command.extend_from_slice(&gix_quote::single(labels.ancestor.unwrap_or_default())),
+            Some(b'X') => command.extend_from_slice(&gix_quote::single(labels.current.unwrap_or_default())),
+            Some(b'Y') => command.extend_from_slice(&gix_quote::single(labels.other.unwrap_or_default())),
+            Some(other) => command.extend_from_slice(&[b'%', other]),
+            None => command.push(b'%'),
+        }
+    }
+
+    let status = gix_command::prepare(gix_path::from_bstr(command.as_bstr()).into_owned())
+        .with_shell()
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .and_then(|mut child| child.wait())
+        .map_err(|err| merge::Error::RunDriver {
+            driver: driver.name.clone(),
+            command: command.clone(),
+            source: err,
+        })?;
+
+    *out = std::fs::read(&current_path).map_err(|err| merge::Error::ReadResult {
+        driver: driver.name.clone(),
+        source: err,
+    })?;
+    Ok(if status.success() {
+        Resolution::Complete
+    } else {
+        Resolution::Conflict
+    })
+}
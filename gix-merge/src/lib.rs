@@ -0,0 +1,20 @@
+//! Implement three-way merges of blobs and trees, similar to what `git merge-file` and `git merge-tree` do.
+//!
+//! Blob merges are performed by [drivers](blob::Driver) which are chosen by the `merge` git-attribute, and tree merges
+//! use rename tracking to figure out which blobs need merging in the first place.
+//! ## Feature Flags
+#![cfg_attr(
+    all(doc, feature = "document-features"),
+    doc = ::document_features::document_features!()
+)]
+#![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blob;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree;
@@ -0,0 +1,437 @@
+#![allow(clippy::result_large_err)]
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_diff::{
+    rewrites::{tracker::visit::SourceKind, Tracker},
+    tree::{visit, Visit},
+};
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+
+use crate::{
+    blob::{Labels, Resolution},
+    tree::{Conflict, ConflictKind, Entry, Error, Options, Outcome},
+};
+
+/// Perform a three-way merge of the trees `our_tree` and `their_tree` with `ancestor_tree` as common base,
+/// and write the merged tree along with all merged blobs using `write`, which receives objects to write and returns their id.
+///
+/// Files changed by both sides are merged with `blob_merge`, using `labels` to annotate conflict markers.
+/// If [rename tracking](Options::rewrites) is enabled, `diff_cache` is used to compute the similarity of
+/// added and deleted files on each side. `objects` is used to lookup all trees and blobs.
+///
+/// Conflicts don't cause an error, but are [recorded](Outcome::conflicts) while a reasonable version
+/// of each conflicting entry is placed into the merged tree, similar to what `git merge-tree` does.
+#[allow(clippy::too_many_arguments)]
+pub fn merge<E>(
+    ancestor_tree: &gix_hash::oid,
+    our_tree: &gix_hash::oid,
+    their_tree: &gix_hash::oid,
+    labels: Labels<'_>,
+    objects: &impl gix_object::FindObjectOrHeader,
+    mut write: impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+    blob_merge: &mut crate::blob::Platform,
+    diff_cache: &mut gix_diff::blob::Platform,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    let _span = gix_trace::coarse!("gix_merge::tree::merge()");
+    let mut ancestor = BTreeMap::new();
+    flatten(objects, ancestor_tree, &mut BString::default(), &mut ancestor)?;
+    let mut ours = changes(ancestor_tree, our_tree, objects, diff_cache, options)?;
+    let mut theirs = changes(ancestor_tree, their_tree, objects, diff_cache, options)?;
+
+    let mut conflicts = Vec::new();
+    let mut renamed_from = HashMap::new();
+    follow_renames(&ancestor, &ours, &mut theirs, true, &mut renamed_from, &mut conflicts);
+    follow_renames(&ancestor, &theirs, &mut ours, false, &mut renamed_from, &mut conflicts);
+
+    let mut merged = ancestor.clone();
+    let mut bufs = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let paths: BTreeSet<_> = ours.changes.keys().chain(theirs.changes.keys()).cloned().collect();
+    for path in paths {
+        let base = match renamed_from.get(&path) {
+            Some(source) => ancestor.get(source).copied(),
+            None => ancestor.get(&path).copied(),
+        };
+        let ours_entry = ours.changes.get(&path).copied().unwrap_or(base);
+        let theirs_entry = theirs.changes.get(&path).copied().unwrap_or(base);
+        let entry = if ours_entry == theirs_entry || theirs_entry == base {
+            ours_entry
+        } else if ours_entry == base {
+            theirs_entry
+        } else {
+            let (entry, kind) = match (ours_entry, theirs_entry) {
+                (Some(o), Some(t)) if o.mode.is_blob() && t.mode.is_blob() => {
+                    let base = base.filter(|base| base.mode.is_blob());
+                    let (mode, mut kind) = if o.mode == t.mode {
+                        (o.mode, None)
+                    } else if matches!(base, Some(base) if base.mode == o.mode) {
+                        (t.mode, None)
+                    } else if matches!(base, Some(base) if base.mode == t.mode) {
+                        (o.mode, None)
+                    } else {
+                        (o.mode, Some(ConflictKind::Mode))
+                    };
+                    let id = if o.id == t.id {
+                        o.id
+                    } else {
+                        let (ours_buf, theirs_buf, base_buf, out) = &mut bufs;
+                        let current = objects.find_blob(&o.id, ours_buf)?.data;
+                        let other = objects.find_blob(&t.id, theirs_buf)?.data;
+                        let ancestor_data = match base {
+                            Some(base) => objects.find_blob(&base.id, base_buf)?.data,
+                            None => &[],
+                        };
+                        let resolution =
+                            blob_merge.merge(out, path.as_ref(), labels, current, ancestor_data, other, objects)?;
+                        if resolution == Resolution::Conflict {
+                            kind = Some(if base.is_some() {
+                                ConflictKind::Content
+                            } else {
+                                ConflictKind::AddAdd
+                            });
+                        }
+                        write(&gix_object::BlobRef { data: out }).map_err(|err| Error::WriteObject(err.into()))?
+                    };
+                    (Some(Entry { mode, id }), kind)
+                }
+                (Some(o), Some(t)) => {
+                    let kind = if o.mode.kind() != t.mode.kind() {
+                        ConflictKind::Type
+                    } else if base.is_none() {
+                        ConflictKind::AddAdd
+                    } else {
+                        ConflictKind::Content
+                    };
+                    (Some(o), Some(kind))
+                }
+                (Some(modified), None) | (None, Some(modified)) => (Some(modified), Some(ConflictKind::ModifyDelete)),
+                (None, None) => unreachable!("equal entries were handled before"),
+            };
+            if let Some(kind) = kind {
+                conflicts.push(Conflict {
+                    kind,
+                    path: path.clone(),
+                    ancestor: base,
+                    ours: ours_entry,
+                    theirs: theirs_entry,
+                });
+            }
+            entry
+        };
+        match entry {
+            Some(entry) => merged.insert(path, entry),
+            None => merged.remove(&path),
+        };
+    }
+
+    move_files_out_of_the_way_of_directories(&mut merged, &ours, labels, &mut conflicts);
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let entries: Vec<_> = merged.iter().map(|(path, entry)| (path.as_bstr(), *entry)).collect();
+    let tree = write_tree(&entries, &mut write)?;
+    Ok(Outcome { tree, conflicts })
+}
+
+/// The changes of one side of the merge relative to the common ancestor.
+#[derive(Default)]
+struct Side {
+    /// The new state of each changed non-tree entry, or `None` if it was deleted.
+    changes: BTreeMap<BString, Option<Entry>>,
+    /// `(source, destination)` pairs of renamed files.
+    renames: Vec<(BString, BString)>,
+}
+
+impl Side {
+    fn record(&mut self, location: &BStr, change: visit::Change) {
+        use visit::Change::*;
+        let entry = match change {
+            Addition { entry_mode, .. } | Deletion { entry_mode, .. } if entry_mode.is_tree() => return,
+            Modification {
+                previous_entry_mode,
+                entry_mode,
+                ..
+            } if previous_entry_mode.is_tree() && entry_mode.is_tree() => return,
+            Modification { entry_mode, .. } if entry_mode.is_tree() => None,
+            Deletion { .. } => None,
+            Addition { entry_mode, oid } | Modification { entry_mode, oid, .. } => Some(Entry {
+                mode: entry_mode,
+                id: oid,
+            }),
+        };
+        self.changes.insert(location.to_owned(), entry);
+    }
+}
+
+/// Collect all changes from `ancestor_tree` to `tree`, with renames if `options` asks for it.
+fn changes(
+    ancestor_tree: &gix_hash::oid,
+    tree: &gix_hash::oid,
+    objects: &impl gix_object::FindObjectOrHeader,
+    diff_cache: &mut gix_diff::blob::Platform,
+    options: Options,
+) -> Result<Side, Error> {
+    let (mut lhs_buf, mut rhs_buf) = (Vec::new(), Vec::new());
+    let lhs = objects.find_tree_iter(ancestor_tree, &mut lhs_buf)?;
+    let rhs = objects.find_tree_iter(tree, &mut rhs_buf)?;
+    let mut delegate = Collect {
+        recorder: Default::default(),
+        tracker: options.rewrites.map(Tracker::new),
+        side: Side::default(),
+    };
+    gix_diff::tree::Changes::from(lhs).needed_to_obtain(
+        rhs,
+        gix_diff::tree::State::default(),
+        objects,
+        &mut delegate,
+    )?;
+
+    let Collect { tracker, mut side, .. } = delegate;
+    if let Some(mut tracker) = tracker {
+        tracker.emit(
+            |dest, source| {
+                if let Some(source) = source.filter(|source| source.kind == SourceKind::Rename) {
+                    side.changes.insert(source.location.to_owned(), None);
+                    side.renames
+                        .push((source.location.to_owned(), dest.location.to_owned()));
+                }
+                side.record(dest.location, dest.change);
+                visit::Action::Continue
+            },
+            diff_cache,
+            objects,
+            |_push| Ok::<_, std::convert::Infallible>(()),
+        )?;
+    }
+    Ok(side)
+}
+
+struct Collect {
+    recorder: gix_diff::tree::Recorder,
+    tracker: Option<Tracker<visit::Change>>,
+    side: Side,
+}
+
+impl Visit for Collect {
+    fn pop_front_tracked_path_and_set_current(&mut self) {
+        self.recorder.pop_front_tracked_path_and_set_current()
+    }
+
+    fn push_back_tracked_path_component(&mut self, component: &BStr) {
+        self.recorder.push_back_tracked_path_component(component)
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.recorder.push_path_component(component)
+    }
+
+    fn pop_path_component(&mut self) {
+        self.recorder.pop_path_component()
+    }
+
+    fn visit(&mut self, change: visit::Change) -> visit::Action {
+        let change = match self.tracker.as_mut() {
+            Some(tracker) => tracker.try_push_change(change, self.recorder.path()),
+            None => Some(change),
+        };
+        if let Some(change) = change {
+            self.side.record(self.recorder.path(), change);
+        }
+        visit::Action::Continue
+    }
+}
+
+/// Make the renames of `this` side visible to the `other` side by moving its changes to the renamed source
+/// over to the destination of the rename, and remember which destination has which source in `renamed_from`.
+/// Renames that can't be followed are recorded in `conflicts`.
+fn follow_renames(
+    ancestor: &BTreeMap<BString, Entry>,
+    this: &Side,
+    other: &mut Side,
+    this_is_ours: bool,
+    renamed_from: &mut HashMap<BString, BString>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let by_side = |this: Option<Entry>, other: Option<Entry>| if this_is_ours { (this, other) } else { (other, this) };
+    for (source, dest) in &this.renames {
+        let base = ancestor.get(source).copied();
+        if let Some((_, other_dest)) = other.renames.iter().find(|(other_source, _)| other_source == source) {
+            if !this_is_ours {
+                continue;
+            }
+            if other_dest == dest {
+                renamed_from.insert(dest.clone(), source.clone());
+            } else {
+                let (ours, theirs) = by_side(
+                    this.changes.get(dest).copied().flatten(),
+                    other.changes.get(other_dest).copied().flatten(),
+                );
+                conflicts.push(Conflict {
+                    kind: ConflictKind::RenameRename {
+                        ours: if this_is_ours { dest.clone() } else { other_dest.clone() },
+                        theirs: if this_is_ours { other_dest.clone() } else { dest.clone() },
+                    },
+                    path: source.clone(),
+                    ancestor: base,
+                    ours,
+                    theirs,
+                });
+            }
+            continue;
+        }
+        if other.changes.contains_key(dest) {
+            // Both sides now have an entry at `dest`, which is merged like any other addition on both sides.
+            continue;
+        }
+        match other.changes.get(source) {
+            Some(None) => {
+                let (ours, theirs) = by_side(this.changes.get(dest).copied().flatten(), None);
+                conflicts.push(Conflict {
+                    kind: ConflictKind::RenameDelete { source: source.clone() },
+                    path: dest.clone(),
+                    ancestor: base,
+                    ours,
+                    theirs,
+                });
+            }
+            Some(Some(_)) => {
+                let change = other.changes.remove(source).expect("present");
+                other.changes.insert(dest.clone(), change);
+                renamed_from.insert(dest.clone(), source.clone());
+            }
+            None => {
+                renamed_from.insert(dest.clone(), source.clone());
+            }
+        }
+    }
+}
+
+/// Move all files in `merged` that are in the way of a directory of the same name to a unique path with the side's label as suffix,
+/// just like `git` does.
+fn move_files_out_of_the_way_of_directories(
+    merged: &mut BTreeMap<BString, Entry>,
+    ours: &Side,
+    labels: Labels<'_>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let clashing_files: Vec<BString> = {
+        let mut dirs = HashSet::<&BStr>::new();
+        for path in merged.keys() {
+            let mut dir = path.as_bstr();
+            while let Some(pos) = dir.rfind_byte(b'/') {
+                dir = dir[..pos].as_bstr();
+                if !dirs.insert(dir) {
+                    break;
+                }
+            }
+        }
+        merged
+            .keys()
+            .filter(|path| dirs.contains(path.as_bstr()))
+            .cloned()
+            .collect()
+    };
+
+    for path in clashing_files {
+        let entry = merged.remove(&path).expect("present");
+        let is_ours = matches!(ours.changes.get(&path), Some(Some(ours)) if *ours == entry);
+        let label = if is_ours {
+            labels.current.unwrap_or("ours".into())
+        } else {
+            labels.other.unwrap_or("theirs".into())
+        };
+        let mut renamed_to = path.clone();
+        renamed_to.push(b'~');
+        renamed_to.extend(label.iter().map(|b| if *b == b'/' { b'_' } else { *b }));
+        let prefix_len = renamed_to.len();
+        let mut suffix = 0;
+        while merged.contains_key(&renamed_to) {
+            renamed_to.truncate(prefix_len);
+            renamed_to.push_str(format!("_{suffix}"));
+            suffix += 1;
+        }
+        merged.insert(renamed_to.clone(), entry);
+        conflicts.push(Conflict {
+            kind: ConflictKind::DirectoryFile { renamed_to },
+            path,
+            ancestor: None,
+            ours: is_ours.then_some(entry),
+            theirs: (!is_ours).then_some(entry),
+        });
+    }
+}
+
+/// Recursively read `tree` and place all of its non-tree entries into `out`, keyed by their path.
+fn flatten(
+    objects: &impl gix_object::Find,
+    tree: &gix_hash::oid,
+    prefix: &mut BString,
+    out: &mut BTreeMap<BString, Entry>,
+) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    let tree = objects.find_tree(tree, &mut buf)?;
+    for entry in tree.entries {
+        let prefix_len = prefix.len();
+        if prefix_len != 0 {
+            prefix.push(b'/');
+        }
+        prefix.extend_from_slice(entry.filename);
+        if entry.mode.is_tree() {
+            flatten(objects, entry.oid, prefix, out)?;
+        } else {
+            out.insert(
+                prefix.clone(),
+                Entry {
+                    mode: entry.mode,
+                    id: entry.oid.to_owned(),
+                },
+            );
+        }
+        prefix.truncate(prefix_len);
+    }
+    Ok(())
+}
+
+/// Write `entries`, sorted by path, as tree along with all of its sub-trees and return its id.
+fn write_tree<E>(
+    entries: &[(&BStr, Entry)],
+    write: &mut impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+) -> Result<ObjectId, Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    let mut tree = gix_object::Tree::empty();
+    let mut entries = entries;
+    while let Some((path, entry)) = entries.first() {
+        match path.find_byte(b'/') {
+            None => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: entry.mode,
+                    filename: (*path).to_owned(),
+                    oid: entry.id,
+                });
+                entries = &entries[1..];
+            }
+            Some(pos) => {
+                let dir = &path[..pos + 1];
+                let num_children = entries.iter().take_while(|(path, _)| path.starts_with(dir)).count();
+                let children: Vec<_> = entries[..num_children]
+                    .iter()
+                    .map(|(path, entry)| (path[pos + 1..].as_bstr(), *entry))
+                    .collect();
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: gix_object::tree::EntryKind::Tree.into(),
+                    filename: path[..pos].into(),
+                    oid: write_tree(&children, write)?,
+                });
+                entries = &entries[num_children..];
+            }
+        }
+    }
+    tree.entries.sort();
+    write(&tree).map_err(|err| Error::WriteObject(err.into()))
+}
@@ -0,0 +1,112 @@
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+mod function;
+pub use function::merge;
+
+/// The error returned by [`merge()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    FindExistingIter(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    Diff(#[from] gix_diff::tree::changes::Error),
+    #[error("Failure during rename tracking")]
+    RenameTracking(#[from] gix_diff::rewrites::tracker::emit::Error),
+    #[error(transparent)]
+    MergeBlob(#[from] crate::blob::platform::merge::Error),
+    #[error("Could not write merged object")]
+    WriteObject(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Options for use in [`merge()`].
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Options {
+    /// If `Some(…)`, track renames on both sides of the merge so that changes to a renamed file are merged into
+    /// its new location, similar to `merge.renames` in `git`.
+    ///
+    /// Copies will be treated as additions.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// A non-tree entry as it is seen in one of the trees of a merge.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Entry {
+    /// The mode of the entry.
+    pub mode: EntryMode,
+    /// The id of the object the entry points to.
+    pub id: ObjectId,
+}
+
+/// Describes a conflict encountered while merging trees.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Conflict {
+    /// The kind of conflict.
+    pub kind: ConflictKind,
+    /// The path at which the conflict is located in the merged tree.
+    pub path: BString,
+    /// The entry in the common ancestor, if there was one.
+    pub ancestor: Option<Entry>,
+    /// The entry on our side, if there was one.
+    pub ours: Option<Entry>,
+    /// The entry on their side, if there was one.
+    pub theirs: Option<Entry>,
+}
+
+/// Identify the kind of [`Conflict`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConflictKind {
+    /// Both sides changed the same file in different ways, and the merged file contains conflict markers.
+    ///
+    /// If the file is binary, a symbolic link or a submodule, our version is used instead.
+    Content,
+    /// Both sides added a file at the same path with different content, and the merged file contains conflict markers.
+    AddAdd,
+    /// Both sides changed the executable bit of a file in different ways, and our mode is used.
+    Mode,
+    /// Both sides turned the entry into different kinds of entries, like a file and a symbolic link, and ours is used.
+    Type,
+    /// One side modified the file while the other side deleted it, and the modified version is kept.
+    ModifyDelete,
+    /// One side renamed the file from `source` while the other side deleted it, and the renamed version is kept.
+    RenameDelete {
+        /// The location of the file before it was renamed.
+        source: BString,
+    },
+    /// Both sides renamed the file from `source` to different locations, and both renamed versions are kept.
+    ///
+    /// The [conflict path](Conflict::path) is set to `source`.
+    RenameRename {
+        /// The location our side renamed the file to.
+        ours: BString,
+        /// The location their side renamed the file to.
+        theirs: BString,
+    },
+    /// A file was placed where the other side has a directory, so the file was moved to `renamed_to` instead.
+    DirectoryFile {
+        /// The path the file was moved to.
+        renamed_to: BString,
+    },
+}
+
+/// The result of [`merge()`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    /// The id of the tree that was written as result of the merge.
+    ///
+    /// It may contain files with conflict markers if there were [conflicts](Self::conflicts).
+    pub tree: ObjectId,
+    /// All conflicts that were encountered, which couldn't be resolved automatically, sorted by path.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if there were conflicts that couldn't be resolved automatically.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
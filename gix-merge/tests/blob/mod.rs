@@ -0,0 +1,323 @@
+mod builtin_driver {
+    use gix_merge::blob::{builtin_driver, Resolution};
+
+    #[test]
+    fn binary() {
+        use builtin_driver::binary::{Pick, ResolveWith};
+        assert_eq!(
+            builtin_driver::binary(None),
+            (Pick::Ours, Resolution::Conflict),
+            "by default it picks ours and marks it as conflict"
+        );
+        assert_eq!(
+            builtin_driver::binary(Some(ResolveWith::Ancestor)),
+            (Pick::Ancestor, Resolution::CompleteWithAutoResolvedConflict),
+            "Otherwise we can pick anything and it will mark it as complete"
+        );
+        assert_eq!(
+            builtin_driver::binary(Some(ResolveWith::Ours)),
+            (Pick::Ours, Resolution::CompleteWithAutoResolvedConflict)
+        );
+        assert_eq!(
+            builtin_driver::binary(Some(ResolveWith::Theirs)),
+            (Pick::Theirs, Resolution::CompleteWithAutoResolvedConflict)
+        );
+    }
+
+    mod text {
+        use bstr::ByteSlice;
+        use gix_merge::blob::{
+            builtin_driver,
+            builtin_driver::text::{Conflict, ConflictStyle},
+            Resolution,
+        };
+        use pretty_assertions::assert_str_eq;
+
+        #[test]
+        fn baseline() -> crate::Result {
+            let root = gix_testtools::scripted_fixture_read_only("text-baseline.sh")?;
+            let marker_size = Conflict::DEFAULT_MARKER_SIZE.try_into()?;
+            let cases = [
+                (
+                    "merge",
+                    Conflict::Keep {
+                        style: ConflictStyle::Merge,
+                        marker_size,
+                    },
+                ),
+                (
+                    "diff3",
+                    Conflict::Keep {
+                        style: ConflictStyle::Diff3,
+                        marker_size,
+                    },
+                ),
+                (
+                    "zdiff3",
+                    Conflict::Keep {
+                        style: ConflictStyle::ZealousDiff3,
+                        marker_size,
+                    },
+                ),
+                ("ours", Conflict::ResolveWithOurs),
+                ("theirs", Conflict::ResolveWithTheirs),
+                ("union", Conflict::ResolveWithUnion),
+            ];
+
+            let mut num_cases = 0;
+            let mut out = Vec::new();
+            for dir in std::fs::read_dir(&root)? {
+                let dir = dir?.path();
+                let ours = std::fs::read(dir.join("ours"))?;
+                let base = std::fs::read(dir.join("base"))?;
+                let theirs = std::fs::read(dir.join("theirs"))?;
+                let mut input = gix_diff::blob::intern::InternedInput::default();
+                for (name, conflict) in cases {
+                    let expected = std::fs::read(dir.join(format!("{name}.expected")))?;
+                    let resolution = builtin_driver::text(
+                        &mut out,
+                        &mut input,
+                        gix_merge::blob::Labels {
+                            ancestor: Some("base".into()),
+                            current: Some("ours".into()),
+                            other: Some("theirs".into()),
+                        },
+                        &ours,
+                        &base,
+                        &theirs,
+                        builtin_driver::text::Options {
+                            conflict,
+                            ..Default::default()
+                        },
+                    );
+                    assert_str_eq!(
+                        out.as_bstr().to_str_lossy(),
+                        expected.as_bstr().to_str_lossy(),
+                        "{dir:?} with {name}"
+                    );
+                    let has_markers = expected.find(b"<<<<<<<").is_some();
+                    assert_eq!(
+                        resolution == Resolution::Conflict,
+                        has_markers,
+                        "{dir:?} with {name}: conflicts are signalled only if markers are present"
+                    );
+                    num_cases += 1;
+                }
+            }
+            assert_eq!(num_cases, 9 * cases.len(), "all cases were seen");
+            Ok(())
+        }
+    }
+}
+
+mod platform {
+    use bstr::ByteSlice;
+    use gix_merge::blob::{builtin_driver, platform, BuiltinDriver, Driver, DriverChoice, Labels, Resolution};
+
+    fn new_platform(drivers: Vec<Driver>, options: platform::Options) -> crate::Result<gix_merge::blob::Platform> {
+        let root = gix_testtools::scripted_fixture_read_only("make_blob_repo.sh")?;
+        let attributes = gix_worktree::Stack::new(
+            &root,
+            gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+                Default::default(),
+                None,
+                gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+                Default::default(),
+            )),
+            gix_worktree::glob::pattern::Case::Sensitive,
+            Vec::new(),
+            Vec::new(),
+        );
+        Ok(gix_merge::blob::Platform::new(drivers, attributes, options))
+    }
+
+    fn labels() -> Labels<'static> {
+        Labels {
+            ancestor: Some("base".into()),
+            current: Some("ours".into()),
+            other: Some("theirs".into()),
+        }
+    }
+
+    fn drivers() -> Vec<Driver> {
+        vec![
+            Driver {
+                name: "failing".into(),
+                command: "exit 1".into(),
+                ..Default::default()
+            },
+            Driver {
+                name: "custom".into(),
+                command: "{ cat %B; echo %L %P %S %X %Y; } > %A".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn driver_for_path_follows_attributes() -> crate::Result {
+        let mut platform = new_platform(
+            drivers(),
+            platform::Options {
+                default_driver: Some("union".into()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(platform.drivers()[0].name, "custom", "drivers are sorted by name");
+        let objects = gix_object::find::Never;
+        for (path, expected) in [
+            ("a.union", DriverChoice::BuiltIn(BuiltinDriver::Union)),
+            ("a.binary", DriverChoice::BuiltIn(BuiltinDriver::Binary)),
+            ("a.text", DriverChoice::BuiltIn(BuiltinDriver::Text)),
+            ("a.custom", DriverChoice::Index(0)),
+            ("a.failing", DriverChoice::Index(1)),
+            ("a.unknown", DriverChoice::BuiltIn(BuiltinDriver::Text)),
+            ("unspecified", DriverChoice::BuiltIn(BuiltinDriver::Union)),
+        ] {
+            let (actual, marker_size) = platform.driver_for_path(path.into(), &objects)?;
+            assert_eq!(actual, expected, "{path}");
+            assert_eq!(marker_size, None);
+        }
+
+        let (_, marker_size) = platform.driver_for_path("a.markers".into(), &objects)?;
+        assert_eq!(marker_size.map(std::num::NonZeroU8::get), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn builtin_drivers() -> crate::Result {
+        let mut platform = new_platform(drivers(), Default::default())?;
+        let objects = gix_object::find::Never;
+        let mut out = Vec::new();
+
+        let res = platform.merge(
+            &mut out,
+            "a.text".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::Conflict);
+        assert_eq!(out.as_bstr(), "<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n");
+
+        let res = platform.merge(
+            &mut out,
+            "a.markers".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::Conflict);
+        assert_eq!(out.as_bstr(), "<<< ours\nours\n===\ntheirs\n>>> theirs\n");
+
+        let res = platform.merge(
+            &mut out,
+            "a.union".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::CompleteWithAutoResolvedConflict);
+        assert_eq!(out.as_bstr(), "ours\ntheirs\n");
+
+        let res = platform.merge(
+            &mut out,
+            "a.binary".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::Conflict);
+        assert_eq!(out.as_bstr(), "ours\n", "binary merges pick ours by default");
+
+        let res = platform.merge(
+            &mut out,
+            "a.binary".into(),
+            labels(),
+            b"base\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(
+            res,
+            Resolution::Complete,
+            "trivial merges are handled by all built-in drivers"
+        );
+        assert_eq!(out.as_bstr(), "theirs\n");
+
+        let res = platform.merge(
+            &mut out,
+            "a.text".into(),
+            labels(),
+            b"ours\0",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(
+            res,
+            Resolution::Conflict,
+            "binary content forces the use of the binary driver"
+        );
+        assert_eq!(out.as_bstr(), "ours\0");
+
+        platform.options.resolve_binary_with = Some(builtin_driver::binary::ResolveWith::Theirs);
+        let res = platform.merge(
+            &mut out,
+            "a.text".into(),
+            labels(),
+            b"ours\0",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::CompleteWithAutoResolvedConflict);
+        assert_eq!(out.as_bstr(), "theirs\n");
+        Ok(())
+    }
+
+    #[test]
+    fn custom_drivers() -> crate::Result {
+        let mut platform = new_platform(drivers(), Default::default())?;
+        let objects = gix_object::find::Never;
+        let mut out = Vec::new();
+
+        let res = platform.merge(
+            &mut out,
+            "a.custom".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::Complete);
+        assert_eq!(
+            out.as_bstr(),
+            "theirs\n7 a.custom base ours theirs\n",
+            "the driver receives all arguments and writes its result into the file of the current version"
+        );
+
+        let res = platform.merge(
+            &mut out,
+            "a.failing".into(),
+            labels(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            &objects,
+        )?;
+        assert_eq!(res, Resolution::Conflict, "a non-zero exit code indicates a conflict");
+        assert_eq!(out.as_bstr(), "ours\n", "the current version is returned as is");
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+
+cat <<EOF >.gitattributes
+*.union merge=union
+*.binary -merge
+*.text merge
+*.custom merge=custom
+*.failing merge=failing
+*.unknown merge=does-not-exist
+*.markers conflict-marker-size=3
+EOF
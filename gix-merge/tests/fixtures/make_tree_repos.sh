@@ -0,0 +1,132 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Each case is a repository with the branches `base`, `ours` and `theirs`, along with the output
+# of `git merge-tree` in `expected`, which is the merged tree id followed by conflicting paths.
+function baseline() {
+  git merge-tree --write-tree --name-only --no-messages ours theirs > expected || true
+  git rev-parse base^{tree} ours^{tree} theirs^{tree} > trees
+}
+
+function commit() {
+  git add -A && git commit -q -m "$1"
+}
+
+git init -q clean
+(cd clean
+  mkdir dir
+  echo a > a && echo b > dir/b && echo c > c
+  printf '1\n2\n3\n4\n5\n6\n7\n' > same-file
+  commit base && git branch base
+  git checkout -q -b ours
+  echo a-ours > a && rm c && echo new > dir/new-ours && sed -i 's/2/2-ours/' same-file
+  commit ours
+  git checkout -q -b theirs base
+  echo b-theirs > dir/b && echo new > new-theirs && sed -i 's/6/6-theirs/' same-file
+  commit theirs
+  baseline
+)
+
+git init -q content-conflict
+(cd content-conflict
+  printf '1\n2\n3\n' > file && echo unrelated > unrelated
+  commit base && git branch base
+  git checkout -q -b ours
+  printf '1\nours\n3\n' > file
+  commit ours
+  git checkout -q -b theirs base
+  printf '1\ntheirs\n3\n' > file && echo changed > unrelated
+  commit theirs
+  baseline
+)
+
+git init -q modify-delete
+(cd modify-delete
+  echo content > file && echo other > other
+  commit base && git branch base
+  git checkout -q -b ours
+  echo changed > file
+  commit ours
+  git checkout -q -b theirs base
+  rm file
+  commit theirs
+  baseline
+)
+
+git init -q add-add
+(cd add-add
+  echo content > unrelated
+  commit base && git branch base
+  git checkout -q -b ours
+  printf 'same\nours\n' > new
+  commit ours
+  git checkout -q -b theirs base
+  printf 'same\ntheirs\n' > new
+  commit theirs
+  baseline
+)
+
+git init -q rename-modify
+(cd rename-modify
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > file
+  commit base && git branch base
+  git checkout -q -b ours
+  git mv file renamed
+  commit ours
+  git checkout -q -b theirs base
+  printf '1\n2\n3\n4\n5\n6\n7\n8\ntheirs\n' > file
+  commit theirs
+  baseline
+)
+
+git init -q rename-delete
+(cd rename-delete
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > file && echo other > other
+  commit base && git branch base
+  git checkout -q -b ours
+  git mv file renamed
+  commit ours
+  git checkout -q -b theirs base
+  rm file
+  commit theirs
+  baseline
+)
+
+git init -q rename-rename
+(cd rename-rename
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > file && echo other > other
+  commit base && git branch base
+  git checkout -q -b ours
+  git mv file renamed-ours
+  commit ours
+  git checkout -q -b theirs base
+  git mv file renamed-theirs
+  commit theirs
+  baseline
+)
+
+git init -q directory-file
+(cd directory-file
+  echo content > unrelated
+  commit base && git branch base
+  git checkout -q -b ours
+  echo file > d
+  commit ours
+  git checkout -q -b theirs base
+  mkdir d && echo file > d/file
+  commit theirs
+  baseline
+)
+
+git init -q mode-change
+(cd mode-change
+  printf '1\n2\n3\n' > file
+  commit base && git branch base
+  git checkout -q -b ours
+  chmod +x file
+  commit ours
+  git checkout -q -b theirs base
+  printf '1\n2\ntheirs\n' > file
+  commit theirs
+  baseline
+)
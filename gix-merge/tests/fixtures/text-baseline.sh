@@ -0,0 +1,89 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Create a directory for each case with the `base`, `ours` and `theirs` version of a file,
+# and store what `git merge-file` makes of them with all supported conflict styles and resolutions.
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  (cd "$name"
+    git merge-file --stdout -L ours -L base -L theirs ours base theirs > merge.expected || true
+    for style in diff3 zdiff3; do
+      git merge-file --stdout --$style -L ours -L base -L theirs ours base theirs > "$style.expected" || true
+    done
+    for resolution in ours theirs union; do
+      git merge-file --stdout --$resolution -L ours -L base -L theirs ours base theirs > "$resolution.expected" || true
+    done
+  )
+}
+
+mkdir non-overlapping
+(cd non-overlapping
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > base
+  printf '1\nours\n3\n4\n5\n6\n7\n8\n9\n' > ours
+  printf '1\n2\n3\n4\n5\n6\n7\ntheirs\n9\n' > theirs
+)
+baseline non-overlapping
+
+mkdir same-change
+(cd same-change
+  printf '1\n2\n3\n' > base
+  printf '1\nsame\n3\n' > ours
+  cp ours theirs
+)
+baseline same-change
+
+mkdir single-line-conflict
+(cd single-line-conflict
+  printf '1\n2\n3\n' > base
+  printf '1\nours\n3\n' > ours
+  printf '1\ntheirs\n3\n' > theirs
+)
+baseline single-line-conflict
+
+mkdir conflict-with-common-prefix-and-suffix
+(cd conflict-with-common-prefix-and-suffix
+  printf '1\n2\n3\n4\n5\n' > base
+  printf '1\nsame\nours\nsame-too\n5\n' > ours
+  printf '1\nsame\ntheirs\nsame-too\n5\n' > theirs
+)
+baseline conflict-with-common-prefix-and-suffix
+
+mkdir adjacent-changes
+(cd adjacent-changes
+  printf '1\n2\n3\n4\n' > base
+  printf '1\nours\n3\n4\n' > ours
+  printf '1\n2\ntheirs\n4\n' > theirs
+)
+baseline adjacent-changes
+
+mkdir deletion-and-change
+(cd deletion-and-change
+  printf '1\n2\n3\n4\n5\n6\n7\n' > base
+  printf '1\n4\n5\n6\n7\n' > ours
+  printf '1\n2\n3\n4\n5\nchanged\n7\n' > theirs
+)
+baseline deletion-and-change
+
+mkdir additions-at-end
+(cd additions-at-end
+  printf '1\n2\n' > base
+  printf '1\n2\nours\n' > ours
+  printf '1\n2\ntheirs\n' > theirs
+)
+baseline additions-at-end
+
+mkdir missing-newline-at-end
+(cd missing-newline-at-end
+  printf '1\n2\n3' > base
+  printf '1\n2\nours' > ours
+  printf '1\n2\ntheirs' > theirs
+)
+baseline missing-newline-at-end
+
+mkdir multiple-conflicts
+(cd multiple-conflicts
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > base
+  printf '1\nours\n3\n4\n5\n6\n7\nours\n9\n' > ours
+  printf '1\ntheirs\n3\n4\n5\n6\n7\ntheirs\n9\n' > theirs
+)
+baseline multiple-conflicts
@@ -0,0 +1,176 @@
+use gix_merge::tree::{ConflictKind, Options, Outcome};
+
+fn merge(case: &str, options: Options) -> crate::Result<(Outcome, gix_hash::ObjectId)> {
+    let root = gix_testtools::scripted_fixture_read_only("make_tree_repos.sh")?.join(case);
+    let objects = gix_odb::at(root.join(".git/objects"))?;
+    let trees: Vec<_> = std::fs::read_to_string(root.join("trees"))?
+        .lines()
+        .map(|hex| gix_hash::ObjectId::from_hex(hex.as_bytes()))
+        .collect::<Result<_, _>>()?;
+    let expected_tree = std::fs::read_to_string(root.join("expected"))?
+        .lines()
+        .next()
+        .map(|hex| gix_hash::ObjectId::from_hex(hex.as_bytes()))
+        .expect("tree id")?;
+
+    let attributes = gix_worktree::Stack::new(
+        &root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let mut blob_merge = gix_merge::blob::Platform::new(Vec::new(), attributes.clone(), Default::default());
+    let mut diff_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            Default::default(),
+            gix_filter::Pipeline::default(),
+            Vec::new(),
+            Default::default(),
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        attributes,
+    );
+    let outcome = gix_merge::tree::merge(
+        &trees[0],
+        &trees[1],
+        &trees[2],
+        gix_merge::blob::Labels {
+            ancestor: Some("base".into()),
+            current: Some("ours".into()),
+            other: Some("theirs".into()),
+        },
+        &objects,
+        |obj| -> Result<_, std::io::Error> {
+            let mut buf = Vec::new();
+            obj.write_to(&mut buf)?;
+            Ok(gix_object::compute_hash(gix_hash::Kind::Sha1, obj.kind(), &buf))
+        },
+        &mut blob_merge,
+        &mut diff_cache,
+        options,
+    )?;
+    Ok((outcome, expected_tree))
+}
+
+fn with_renames() -> Options {
+    Options {
+        rewrites: Some(Default::default()),
+    }
+}
+
+fn kinds(outcome: &Outcome) -> Vec<(&str, ConflictKind)> {
+    outcome
+        .conflicts
+        .iter()
+        .map(|c| (std::str::from_utf8(&c.path).expect("valid UTF-8"), c.kind.clone()))
+        .collect()
+}
+
+#[test]
+fn clean() -> crate::Result {
+    let (outcome, expected) = merge("clean", Default::default())?;
+    assert_eq!(outcome.tree, expected);
+    assert!(!outcome.has_conflicts());
+    Ok(())
+}
+
+#[test]
+fn mode_change_and_content_change_are_combined() -> crate::Result {
+    let (outcome, expected) = merge("mode-change", Default::default())?;
+    assert_eq!(outcome.tree, expected);
+    assert!(!outcome.has_conflicts());
+    Ok(())
+}
+
+#[test]
+fn content_conflict() -> crate::Result {
+    let (outcome, expected) = merge("content-conflict", Default::default())?;
+    assert_eq!(outcome.tree, expected, "conflict markers are written like git does");
+    assert_eq!(kinds(&outcome), [("file", ConflictKind::Content)]);
+    Ok(())
+}
+
+#[test]
+fn add_add() -> crate::Result {
+    let (outcome, expected) = merge("add-add", Default::default())?;
+    assert_eq!(outcome.tree, expected);
+    assert_eq!(kinds(&outcome), [("new", ConflictKind::AddAdd)]);
+    Ok(())
+}
+
+#[test]
+fn modify_delete() -> crate::Result {
+    let (outcome, expected) = merge("modify-delete", Default::default())?;
+    assert_eq!(outcome.tree, expected, "the modified file is kept");
+    assert_eq!(kinds(&outcome), [("file", ConflictKind::ModifyDelete)]);
+    Ok(())
+}
+
+#[test]
+fn directory_file() -> crate::Result {
+    let (outcome, expected) = merge("directory-file", Default::default())?;
+    assert_eq!(outcome.tree, expected, "the file is moved out of the way");
+    assert_eq!(
+        kinds(&outcome),
+        [(
+            "d",
+            ConflictKind::DirectoryFile {
+                renamed_to: "d~ours".into()
+            }
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_modify() -> crate::Result {
+    let (outcome, expected) = merge("rename-modify", with_renames())?;
+    assert_eq!(
+        outcome.tree, expected,
+        "the modification is applied to the renamed file"
+    );
+    assert!(!outcome.has_conflicts());
+
+    let (outcome, _) = merge("rename-modify", Default::default())?;
+    assert_eq!(
+        kinds(&outcome),
+        [("file", ConflictKind::ModifyDelete)],
+        "without rename tracking, a rename is a deletion"
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_delete() -> crate::Result {
+    let (outcome, expected) = merge("rename-delete", with_renames())?;
+    assert_eq!(outcome.tree, expected, "the renamed file is kept");
+    assert_eq!(
+        kinds(&outcome),
+        [("renamed", ConflictKind::RenameDelete { source: "file".into() })]
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_rename() -> crate::Result {
+    let (outcome, expected) = merge("rename-rename", with_renames())?;
+    assert_eq!(outcome.tree, expected, "both renamed files are kept");
+    assert_eq!(
+        kinds(&outcome),
+        [(
+            "file",
+            ConflictKind::RenameRename {
+                ours: "renamed-ours".into(),
+                theirs: "renamed-theirs".into()
+            }
+        )]
+    );
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use bstr::ByteSlice;
+
+/// Append `theirs` to `ours`, separated by an empty line, similar to `git notes append` and the `union` merge strategy.
+///
+/// If one of both is empty, the other one is returned.
+pub fn concatenate(ours: &[u8], theirs: &[u8]) -> Vec<u8> {
+    if ours.is_empty() {
+        return theirs.to_owned();
+    }
+    if theirs.is_empty() {
+        return ours.to_owned();
+    }
+    let ours = ours.strip_suffix(b"\n").unwrap_or(ours);
+    let mut out = Vec::with_capacity(ours.len() + 2 + theirs.len());
+    out.extend_from_slice(ours);
+    out.extend_from_slice(b"\n\n");
+    out.extend_from_slice(theirs);
+    out
+}
+
+/// Combine all lines of `ours` and `theirs`, sort them and remove duplicates as well as empty lines,
+/// similar to the `cat_sort_uniq` merge strategy.
+pub fn cat_sort_uniq(ours: &[u8], theirs: &[u8]) -> Vec<u8> {
+    let mut lines: Vec<_> = ours
+        .lines()
+        .chain(theirs.lines())
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+
+    let mut out = Vec::with_capacity(ours.len() + theirs.len());
+    for line in lines {
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+    out
+}
@@ -0,0 +1,51 @@
+/// The error returned by [`find()`](crate::find()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingIter(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+}
+
+pub(super) mod function {
+    use gix_hash::{oid, ObjectId};
+    use gix_object::FindExt;
+
+    use super::Error;
+
+    /// Return the id of the note blob that annotates `target` in the notes `tree`, or `None` if there is no such note,
+    /// using `objects` to read `tree` and only the sub-trees that may contain the note.
+    ///
+    /// This is more efficient than reading all notes with [`Notes::from_tree()`](crate::Notes::from_tree()) if only
+    /// a few notes are needed.
+    pub fn find(tree: &oid, target: &oid, objects: &impl gix_object::Find) -> Result<Option<ObjectId>, Error> {
+        let hex = target.to_hex().to_string();
+        let mut remaining = hex.as_bytes();
+        let mut tree = tree.to_owned();
+        let mut buf = Vec::new();
+        loop {
+            let mut fan_out_tree = None;
+            for entry in objects.find_tree_iter(&tree, &mut buf)? {
+                let entry = entry?;
+                if entry.mode.is_blob() && entry.filename == remaining {
+                    return Ok(Some(entry.oid.to_owned()));
+                }
+                if entry.mode.is_tree()
+                    && entry.filename.len() == 2
+                    && remaining.len() > 2
+                    && remaining.starts_with(entry.filename)
+                {
+                    fan_out_tree = Some(entry.oid.to_owned());
+                }
+            }
+            match fan_out_tree {
+                Some(id) => {
+                    tree = id;
+                    remaining = &remaining[2..];
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
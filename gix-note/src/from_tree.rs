@@ -0,0 +1,69 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::{oid, ObjectId};
+use gix_object::FindExt;
+
+use crate::Notes;
+
+/// The error returned by [`Notes::from_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+}
+
+/// Instantiation
+impl Notes {
+    /// Read all notes from the notes `tree`, using `objects` to read the tree and its sub-trees.
+    ///
+    /// Notes may be stored with any fan-out, and entries that aren't notes are retained as well.
+    /// If an object is annotated more than once, which can only happen with differing fan-outs, the last note wins.
+    pub fn from_tree(tree: &oid, objects: &impl gix_object::Find) -> Result<Self, Error> {
+        let mut notes = Notes::default();
+        read_tree(objects, tree, &mut BString::default(), true, &mut notes)?;
+        Ok(notes)
+    }
+}
+
+/// Read `tree` at `path` into `out`, with `fan_out` being `true` if `path` consists of fan-out directories only, so
+/// it may contain notes.
+fn read_tree(
+    objects: &impl gix_object::Find,
+    tree: &oid,
+    path: &mut BString,
+    fan_out: bool,
+    out: &mut Notes,
+) -> Result<(), Error> {
+    let hex_len = tree.kind().len_in_hex();
+    let mut buf = Vec::new();
+    let tree = objects.find_tree(tree, &mut buf)?;
+    for entry in tree.entries {
+        let prefix_len = path.len();
+        if prefix_len != 0 {
+            path.push(b'/');
+        }
+        path.extend_from_slice(entry.filename);
+
+        let hex = fan_out
+            .then(|| path.iter().filter(|b| **b != b'/').copied().collect::<Vec<_>>())
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+        match hex {
+            Some(hex) if entry.mode.is_blob() && hex.len() == hex_len => {
+                let target = ObjectId::from_hex(&hex).expect("valid hex of the right length");
+                out.notes.insert(target, entry.oid.to_owned());
+            }
+            Some(hex) if entry.mode.is_tree() && entry.filename.len() == 2 && hex.len() < hex_len => {
+                read_tree(objects, entry.oid, path, true, out)?;
+            }
+            _ if entry.mode.is_tree() => {
+                read_tree(objects, entry.oid, path, false, out)?;
+            }
+            _ => {
+                out.non_notes
+                    .insert(path.as_bstr().to_owned(), (entry.mode, entry.oid.to_owned()));
+            }
+        }
+        path.truncate(prefix_len);
+    }
+    Ok(())
+}
@@ -1,2 +1,51 @@
-#![deny(rust_2018_idioms)]
+//! Read and write [git notes](https://git-scm.com/docs/git-notes), which attach additional information to objects
+//! without changing them.
+//!
+//! Notes are stored in a tree which is the tree of the commit a notes reference like `refs/notes/commits` points to.
+//! Each note is a blob whose path is the hexadecimal id of the object it annotates. To keep trees small, large notes
+//! trees use a *fan-out*, where the first two hexadecimal characters of the id of annotated objects become a directory,
+//! possibly repeatedly, so a note may be stored at `ab/cdef…` instead of `abcdef…`.
+//!
+//! Notes trees may also contain entries which aren't notes, and these are retained when notes are modified.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use std::collections::BTreeMap;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+/// All notes in a notes tree, along with all of its entries that aren't notes.
+///
+/// Use [`Notes::from_tree()`] to read them, and [`Notes::write_tree()`] to write them back as tree with a suitable fan-out.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Notes {
+    /// The id of each note blob, by the id of the object it annotates.
+    notes: BTreeMap<ObjectId, ObjectId>,
+    /// Entries that aren't notes, by their slash-separated path relative to the root of the notes tree.
+    non_notes: BTreeMap<BString, (EntryMode, ObjectId)>,
+}
+
+mod notes;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod find;
+pub use find::function::find;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod from_tree;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
+
+/// Functions to combine the content of two notes into one.
+pub mod combine;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge;
+pub use merge::function::merge;
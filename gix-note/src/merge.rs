@@ -0,0 +1,138 @@
+use gix_hash::ObjectId;
+
+use crate::Notes;
+
+/// The error returned by [`merge()`](crate::merge()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not write combined note")]
+    WriteObject(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Determines how notes are resolved that were changed differently on both sides of a merge, similar to `notes.mergeStrategy`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Don't resolve conflicting notes, but report them as [conflicts](Outcome::conflicts) and keep our note for now.
+    #[default]
+    Manual,
+    /// Keep our note.
+    Ours,
+    /// Use their note.
+    Theirs,
+    /// Append their note to ours with [`combine::concatenate()`](crate::combine::concatenate()).
+    Union,
+    /// Combine the lines of both notes with [`combine::cat_sort_uniq()`](crate::combine::cat_sort_uniq()).
+    CatSortUniq,
+}
+
+/// A note that was changed differently on both sides of a merge and couldn't be resolved automatically.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The annotated object.
+    pub target: ObjectId,
+    /// The note blob in the merge-base, or `None` if there was no note.
+    pub base: Option<ObjectId>,
+    /// Our note blob, or `None` if we removed the note.
+    pub ours: Option<ObjectId>,
+    /// Their note blob, or `None` if they removed the note.
+    pub theirs: Option<ObjectId>,
+}
+
+/// The result of [`merge()`](crate::merge()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The merged notes, which contain our notes in place of all [conflicts](Self::conflicts).
+    pub notes: Notes,
+    /// The notes that couldn't be resolved with [`Strategy::Manual`], sorted by annotated object.
+    /// It's always empty with all other strategies.
+    pub conflicts: Vec<Conflict>,
+}
+
+pub(super) mod function {
+    use std::collections::BTreeSet;
+
+    use gix_hash::ObjectId;
+    use gix_object::FindExt;
+
+    use super::{Conflict, Error, Outcome, Strategy};
+    use crate::Notes;
+
+    /// Merge the notes of `ours` and `theirs`, with `base` being the notes of their merge-base, similar to `git notes merge`.
+    ///
+    /// Notes changed by only one side are taken from that side, and `strategy` decides how to resolve notes that were
+    /// changed differently by both sides. `objects` is used to read notes that have to be combined, and `write` to write
+    /// the combined notes.
+    ///
+    /// Entries that aren't notes are taken from `ours`.
+    pub fn merge<E>(
+        base: &Notes,
+        ours: &Notes,
+        theirs: &Notes,
+        strategy: Strategy,
+        objects: &impl gix_object::Find,
+        mut write: impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+    ) -> Result<Outcome, Error>
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let mut notes = ours.clone();
+        let mut conflicts = Vec::new();
+        let targets: BTreeSet<_> = base
+            .notes
+            .keys()
+            .chain(ours.notes.keys())
+            .chain(theirs.notes.keys())
+            .collect();
+        let (mut ours_buf, mut theirs_buf) = (Vec::new(), Vec::new());
+        for target in targets {
+            let (base_note, our_note, their_note) = (
+                base.get(target).copied(),
+                ours.get(target).copied(),
+                theirs.get(target).copied(),
+            );
+            let merged = if our_note == their_note || their_note == base_note {
+                continue;
+            } else if our_note == base_note {
+                their_note
+            } else {
+                match strategy {
+                    Strategy::Manual => {
+                        conflicts.push(Conflict {
+                            target: *target,
+                            base: base_note,
+                            ours: our_note,
+                            theirs: their_note,
+                        });
+                        continue;
+                    }
+                    Strategy::Ours => continue,
+                    Strategy::Theirs => their_note,
+                    Strategy::Union | Strategy::CatSortUniq => match (our_note, their_note) {
+                        (Some(our_note), Some(their_note)) => {
+                            let ours = objects.find_blob(&our_note, &mut ours_buf)?.data;
+                            let theirs = objects.find_blob(&their_note, &mut theirs_buf)?.data;
+                            let combined = if strategy == Strategy::Union {
+                                crate::combine::concatenate(ours, theirs)
+                            } else {
+                                crate::combine::cat_sort_uniq(ours, theirs)
+                            };
+                            Some(
+                                write(&gix_object::BlobRef { data: &combined })
+                                    .map_err(|err| Error::WriteObject(err.into()))?,
+                            )
+                        }
+                        (note, None) | (None, note) => note,
+                    },
+                }
+            };
+            match merged {
+                Some(note) => notes.insert(*target, note),
+                None => notes.remove(target),
+            };
+        }
+        Ok(Outcome { notes, conflicts })
+    }
+}
@@ -0,0 +1,54 @@
+use bstr::BStr;
+use gix_hash::{oid, ObjectId};
+use gix_object::tree::EntryMode;
+
+use crate::Notes;
+
+/// Access
+impl Notes {
+    /// Return the id of the note blob that annotates `target`, if there is one.
+    pub fn get(&self, target: &oid) -> Option<&ObjectId> {
+        self.notes.get(target)
+    }
+
+    /// Return `true` if `target` is annotated.
+    pub fn contains(&self, target: &oid) -> bool {
+        self.notes.contains_key(target)
+    }
+
+    /// Return an iterator over all `(target, note)` pairs, with `target` being the annotated object and `note` the id of
+    /// the note blob, sorted by `target`.
+    pub fn iter(&self) -> impl Iterator<Item = (&ObjectId, &ObjectId)> + '_ {
+        self.notes.iter()
+    }
+
+    /// Return an iterator over all entries of the notes tree that aren't notes, as `(path, mode, id)`, sorted by path.
+    pub fn non_notes(&self) -> impl Iterator<Item = (&BStr, EntryMode, &ObjectId)> + '_ {
+        self.non_notes
+            .iter()
+            .map(|(path, (mode, id))| (path.as_ref(), *mode, id))
+    }
+
+    /// Return the amount of notes.
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Return `true` if there are no notes.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+/// Mutation
+impl Notes {
+    /// Annotate `target` with the blob `note`, and return the id of the note blob it previously had.
+    pub fn insert(&mut self, target: ObjectId, note: ObjectId) -> Option<ObjectId> {
+        self.notes.insert(target, note)
+    }
+
+    /// Remove the note of `target` and return the id of its blob, if there was one.
+    pub fn remove(&mut self, target: &oid) -> Option<ObjectId> {
+        self.notes.remove(target)
+    }
+}
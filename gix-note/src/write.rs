@@ -0,0 +1,92 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::Notes;
+
+/// The error returned by [`Notes::write_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    EditTree(#[from] gix_object::tree::editor::Error),
+    #[error("Could not write notes tree")]
+    WriteObject(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Like `git fast-import`, add a fan-out directory once a tree would contain this many notes.
+const MAX_NOTES_PER_TREE: usize = 256;
+
+/// Serialization
+impl Notes {
+    /// Write all notes and entries that aren't notes as tree using `write`, and return the id of the top-level tree.
+    ///
+    /// Like `git fast-import`, a level of fan-out directories is used for each factor of 256 notes, so that each tree
+    /// contains less than 256 notes.
+    pub fn write_tree<E>(
+        &self,
+        mut write: impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+    ) -> Result<ObjectId, Error>
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|(target, note)| (target.to_hex().to_string(), *note))
+            .collect();
+        let mut entries = Vec::with_capacity(notes.len() + self.non_notes.len());
+        note_paths(&notes, 0, &mut BString::default(), &mut entries);
+        entries.extend(
+            self.non_notes
+                .iter()
+                .map(|(path, (mode, id))| (path.clone(), *mode, *id)),
+        );
+
+        // All trees are created here, so none of them has to be looked up.
+        let object_hash = self.notes.keys().next().map_or_else(Default::default, ObjectId::kind);
+        let mut editor =
+            gix_object::tree::Editor::new(gix_object::Tree::empty(), &gix_object::find::Never, object_hash);
+        for (path, mode, id) in entries {
+            editor.upsert(path.split_str("/"), mode.kind(), id)?;
+        }
+        editor
+            .write(|tree| write(tree))
+            .map_err(|err| Error::WriteObject(err.into()))
+    }
+}
+
+/// Add the paths of all `notes`, which share their first `consumed` hex characters, to `out`, using `dir` as directory.
+fn note_paths(
+    notes: &[(String, ObjectId)],
+    consumed: usize,
+    dir: &mut BString,
+    out: &mut Vec<(BString, EntryMode, ObjectId)>,
+) {
+    let Some(hex_len) = notes.first().map(|(hex, _)| hex.len()) else {
+        return;
+    };
+    if notes.len() < MAX_NOTES_PER_TREE || hex_len - consumed <= 2 {
+        for (hex, note) in notes {
+            let mut path = dir.clone();
+            path.extend_from_slice(&hex.as_bytes()[consumed..]);
+            out.push((path, gix_object::tree::EntryKind::Blob.into(), *note));
+        }
+        return;
+    }
+
+    let mut notes = notes;
+    while let Some((hex, _)) = notes.first() {
+        let fan_out = &hex[consumed..][..2];
+        let num_notes = notes
+            .iter()
+            .take_while(|(hex, _)| &hex[consumed..][..2] == fan_out)
+            .count();
+        let dir_len = dir.len();
+        dir.extend_from_slice(fan_out.as_bytes());
+        dir.push(b'/');
+        note_paths(&notes[..num_notes], consumed + 2, dir, out);
+        dir.truncate(dir_len);
+        notes = &notes[num_notes..];
+    }
+}
@@ -0,0 +1,52 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q flat
+(cd flat
+  for round in 1 2 3; do
+    echo "$round" > file
+    git add file
+    git commit -qm "commit $round"
+  done
+  git notes add -m "first note" HEAD~2
+  git notes add -m "second note" HEAD~1
+  git notes --ref=other add -m "other note" HEAD
+
+  # git retains entries that aren't notes, so add one by hand.
+  non_note=$(echo "not a note" | git hash-object -w --stdin)
+  tree=$( (git ls-tree refs/notes/commits; printf '100644 blob %s\tREADME\n' "$non_note") | git mktree)
+  commit=$(git commit-tree "$tree" -p refs/notes/commits -m "add an entry that isn't a note")
+  git update-ref refs/notes/commits "$commit"
+
+  git notes list > ../flat.list
+  git rev-parse refs/notes/commits^{tree} > ../flat.tree
+)
+
+git init -q fan-out
+(cd fan-out
+  # fast-import uses a fan-out once there are 256 notes or more.
+  {
+    for round in $(seq 300); do
+      echo "commit refs/heads/main"
+      echo "mark :$round"
+      echo "committer author <author@example.com> $round +0000"
+      echo "data <<EOF"
+      echo "commit $round"
+      echo "EOF"
+    done
+    echo "commit refs/notes/commits"
+    echo "committer author <author@example.com> 1000 +0000"
+    echo "data <<EOF"
+    echo "notes"
+    echo "EOF"
+    for round in $(seq 300); do
+      echo "N inline :$round"
+      echo "data <<EOF"
+      echo "note $round"
+      echo "EOF"
+    done
+  } | git fast-import --quiet
+
+  git notes list > ../fan-out.list
+  git rev-parse refs/notes/commits^{tree} > ../fan-out.tree
+)
@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+
+pub use gix_testtools::Result;
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_notes.sh")
+}
+
+fn odb(root: &Path, name: &str) -> crate::Result<gix_odb::Handle> {
+    Ok(gix_odb::at(root.join(name).join(".git/objects"))?)
+}
+
+/// Return the notes tree of the repository `name`.
+fn notes_tree(root: &Path, name: &str) -> crate::Result<ObjectId> {
+    Ok(ObjectId::from_hex(
+        std::fs::read_to_string(root.join(format!("{name}.tree")))?
+            .trim()
+            .as_bytes(),
+    )?)
+}
+
+/// Parse the output of `git notes list` as `(target, note)` pairs.
+fn baseline(root: &Path, name: &str) -> crate::Result<Vec<(ObjectId, ObjectId)>> {
+    let mut out = Vec::new();
+    for line in std::fs::read_to_string(root.join(format!("{name}.list")))?.lines() {
+        let (note, target) = line.split_once(' ').expect("two ids per line");
+        out.push((
+            ObjectId::from_hex(target.as_bytes())?,
+            ObjectId::from_hex(note.as_bytes())?,
+        ));
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Compute the id of each written object without storing it.
+fn hash_only(object: &dyn gix_object::WriteTo) -> std::io::Result<ObjectId> {
+    let mut buf = Vec::new();
+    object.write_to(&mut buf)?;
+    Ok(gix_object::compute_hash(gix_hash::Kind::Sha1, object.kind(), &buf))
+}
+
+mod from_tree {
+    use gix_object::bstr::BStr;
+
+    use super::{baseline, fixture, notes_tree, odb};
+    use gix_note::Notes;
+
+    #[test]
+    fn flat_with_entry_that_is_no_note() -> crate::Result {
+        let root = fixture()?;
+        let notes = Notes::from_tree(&notes_tree(&root, "flat")?, &odb(&root, "flat")?)?;
+        let actual: Vec<_> = notes.iter().map(|(target, note)| (*target, *note)).collect();
+        assert_eq!(actual, baseline(&root, "flat")?);
+        assert_eq!(notes.len(), 2);
+
+        let non_notes: Vec<_> = notes.non_notes().map(|(path, mode, _id)| (path, mode)).collect();
+        assert_eq!(
+            non_notes,
+            [(BStr::new("README"), gix_object::tree::EntryKind::Blob.into())],
+            "entries that aren't notes are retained"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fan_out() -> crate::Result {
+        let root = fixture()?;
+        let notes = Notes::from_tree(&notes_tree(&root, "fan-out")?, &odb(&root, "fan-out")?)?;
+        let actual: Vec<_> = notes.iter().map(|(target, note)| (*target, *note)).collect();
+        assert_eq!(actual, baseline(&root, "fan-out")?);
+        assert_eq!(notes.len(), 300);
+        assert_eq!(notes.non_notes().count(), 0);
+        Ok(())
+    }
+}
+
+mod find {
+    use super::{baseline, fixture, notes_tree, odb};
+
+    #[test]
+    fn with_and_without_fan_out() -> crate::Result {
+        let root = fixture()?;
+        for name in ["flat", "fan-out"] {
+            let tree = notes_tree(&root, name)?;
+            let odb = odb(&root, name)?;
+            for (target, note) in baseline(&root, name)? {
+                assert_eq!(gix_note::find(&tree, &target, &odb)?, Some(note));
+            }
+            assert_eq!(
+                gix_note::find(&tree, &tree, &odb)?,
+                None,
+                "the notes tree itself isn't annotated"
+            );
+        }
+        Ok(())
+    }
+}
+
+mod write {
+    use gix_hash::ObjectId;
+    use gix_note::Notes;
+
+    use super::{fixture, hash_only, notes_tree, odb};
+
+    #[test]
+    fn roundtrip_produces_the_same_trees_as_git() -> crate::Result {
+        let root = fixture()?;
+        for name in ["flat", "fan-out"] {
+            let tree = notes_tree(&root, name)?;
+            let notes = Notes::from_tree(&tree, &odb(&root, name)?)?;
+            assert_eq!(notes.write_tree(hash_only)?, tree, "{name}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fan_out_starts_at_256_notes() -> crate::Result {
+        for (num_notes, expected_fan_out) in [(255, false), (256, true)] {
+            let mut notes = Notes::default();
+            for round in 0..num_notes {
+                let target = gix_object::compute_hash(
+                    gix_hash::Kind::Sha1,
+                    gix_object::Kind::Blob,
+                    round.to_string().as_bytes(),
+                );
+                notes.insert(target, ObjectId::empty_blob(gix_hash::Kind::Sha1));
+            }
+
+            let mut trees = Vec::new();
+            notes.write_tree(|object| {
+                if let Some(tree) = object_as_tree(object) {
+                    trees.push(tree);
+                }
+                hash_only(object)
+            })?;
+            let root = trees.last().expect("root is written last");
+            assert!(
+                root.entries.iter().all(|entry| entry.mode.is_tree() == expected_fan_out
+                    && entry.filename.len() == if expected_fan_out { 2 } else { 40 }),
+                "{num_notes} notes"
+            );
+            assert_eq!(trees.len(), if expected_fan_out { 1 + root.entries.len() } else { 1 });
+        }
+        Ok(())
+    }
+
+    fn object_as_tree(object: &dyn gix_object::WriteTo) -> Option<gix_object::Tree> {
+        (object.kind() == gix_object::Kind::Tree).then(|| {
+            let mut buf = Vec::new();
+            object.write_to(&mut buf).expect("in-memory write works");
+            gix_object::TreeRef::from_bytes(&buf, gix_hash::Kind::Sha1)
+                .expect("valid tree")
+                .into()
+        })
+    }
+}
+
+mod combine {
+    use gix_note::combine::{cat_sort_uniq, concatenate};
+
+    #[test]
+    fn concatenate_separates_notes_with_an_empty_line() {
+        assert_eq!(concatenate(b"ours\n", b"theirs\n"), b"ours\n\ntheirs\n");
+        assert_eq!(concatenate(b"ours", b"theirs"), b"ours\n\ntheirs");
+        assert_eq!(concatenate(b"", b"theirs\n"), b"theirs\n");
+        assert_eq!(concatenate(b"ours\n", b""), b"ours\n");
+    }
+
+    #[test]
+    fn cat_sort_uniq_sorts_lines_and_removes_duplicates_and_empty_lines() {
+        assert_eq!(cat_sort_uniq(b"b\na\n\nc\n", b"c\nd\na"), b"a\nb\nc\nd\n");
+        assert_eq!(cat_sort_uniq(b"", b""), b"");
+    }
+}
+
+mod merge {
+    use gix_hash::ObjectId;
+    use gix_note::{
+        merge::{Conflict, Strategy},
+        Notes,
+    };
+    use gix_odb::Write;
+
+    fn target(name: &str) -> ObjectId {
+        gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Commit, name.as_bytes())
+    }
+
+    fn notes(odb: &gix_odb::Handle, notes: &[(&str, &str)]) -> crate::Result<Notes> {
+        let mut out = Notes::default();
+        for (name, content) in notes {
+            out.insert(target(name), odb.write_buf(gix_object::Kind::Blob, content.as_bytes())?);
+        }
+        Ok(out)
+    }
+
+    fn content(odb: &gix_odb::Handle, notes: &Notes, name: &str) -> crate::Result<Option<String>> {
+        use gix_object::FindExt;
+        let Some(note) = notes.get(&target(name)) else {
+            return Ok(None);
+        };
+        let mut buf = Vec::new();
+        Ok(Some(String::from_utf8(odb.find_blob(note, &mut buf)?.data.to_owned())?))
+    }
+
+    #[test]
+    fn one_sided_changes_and_conflicts_with_all_strategies() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let odb = gix_odb::at(tmp.path())?;
+        let base = notes(
+            &odb,
+            &[
+                ("unchanged", "same\n"),
+                ("modified-by-us", "base\n"),
+                ("removed-by-them", "base\n"),
+                ("conflict", "base\n"),
+                ("removed-by-us-modified-by-them", "base\n"),
+            ],
+        )?;
+        let ours = notes(
+            &odb,
+            &[
+                ("unchanged", "same\n"),
+                ("modified-by-us", "ours\n"),
+                ("removed-by-them", "base\n"),
+                ("conflict", "b\na\n"),
+                ("added-by-both", "same\n"),
+            ],
+        )?;
+        let theirs = notes(
+            &odb,
+            &[
+                ("unchanged", "same\n"),
+                ("modified-by-us", "base\n"),
+                ("conflict", "c\na\n"),
+                ("removed-by-us-modified-by-them", "theirs\n"),
+                ("added-by-both", "same\n"),
+                ("added-by-them", "theirs\n"),
+            ],
+        )?;
+
+        for (strategy, expected_conflict, expected_deleted_or_modified) in [
+            (Strategy::Manual, "b\na\n", None),
+            (Strategy::Ours, "b\na\n", None),
+            (Strategy::Theirs, "c\na\n", Some("theirs\n")),
+            (Strategy::Union, "b\na\n\nc\na\n", Some("theirs\n")),
+            (Strategy::CatSortUniq, "a\nb\nc\n", Some("theirs\n")),
+        ] {
+            let out = gix_note::merge(&base, &ours, &theirs, strategy, &odb, |object| odb.write(object))?;
+            let notes = &out.notes;
+            assert_eq!(content(&odb, notes, "unchanged")?.as_deref(), Some("same\n"));
+            assert_eq!(content(&odb, notes, "modified-by-us")?.as_deref(), Some("ours\n"));
+            assert_eq!(content(&odb, notes, "removed-by-them")?, None);
+            assert_eq!(content(&odb, notes, "added-by-both")?.as_deref(), Some("same\n"));
+            assert_eq!(content(&odb, notes, "added-by-them")?.as_deref(), Some("theirs\n"));
+            assert_eq!(
+                content(&odb, notes, "conflict")?.as_deref(),
+                Some(expected_conflict),
+                "{strategy:?}"
+            );
+            assert_eq!(
+                content(&odb, notes, "removed-by-us-modified-by-them")?.as_deref(),
+                expected_deleted_or_modified,
+                "{strategy:?}"
+            );
+
+            if strategy == Strategy::Manual {
+                let mut expected = vec![
+                    Conflict {
+                        target: target("conflict"),
+                        base: base.get(&target("conflict")).copied(),
+                        ours: ours.get(&target("conflict")).copied(),
+                        theirs: theirs.get(&target("conflict")).copied(),
+                    },
+                    Conflict {
+                        target: target("removed-by-us-modified-by-them"),
+                        base: base.get(&target("removed-by-us-modified-by-them")).copied(),
+                        ours: None,
+                        theirs: theirs.get(&target("removed-by-us-modified-by-them")).copied(),
+                    },
+                ];
+                expected.sort_by_key(|conflict| conflict.target);
+                assert_eq!(out.conflicts, expected);
+            } else {
+                assert!(out.conflicts.is_empty(), "{strategy:?} resolves all conflicts");
+            }
+        }
+        Ok(())
+    }
+}
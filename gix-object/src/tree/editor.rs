@@ -0,0 +1,289 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+
+use crate::{
+    tree::{Editor, Entry, EntryKind, EntryMode},
+    FindExt, Tree,
+};
+
+/// The error returned by [`Editor::upsert()`] and [`Editor::remove()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot edit the root tree itself, the path must have at least one component")]
+    EmptyPath,
+    #[error("Path component {component:?} must neither be empty nor contain a slash or newline")]
+    InvalidPathComponent { component: BString },
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::find::existing_object::Error),
+}
+
+/// Lifecycle
+impl<'a> Editor<'a> {
+    /// Create a new editor that applies all edits to `root`, and loads trees with `find` as needed.
+    /// `object_hash` is the kind of hash used by the object database.
+    pub fn new(root: Tree, find: &'a dyn crate::Find, object_hash: gix_hash::Kind) -> Self {
+        Editor {
+            find,
+            object_hash,
+            trees: HashMap::from_iter(Some((BString::default(), root))),
+            path_buf: BString::default(),
+            tree_buf: Vec::new(),
+        }
+    }
+}
+
+/// Mutation
+impl<'a> Editor<'a> {
+    /// Insert an entry of `kind` pointing to `id` at `rela_path`, which is given as its components, or replace the entry
+    /// that is already there.
+    ///
+    /// Trees leading up to the entry are created as needed, replacing non-tree entries that are in their way.
+    /// If `kind` is [`EntryKind::Tree`], `id` must point to an existing tree and replaces all previous edits underneath `rela_path`.
+    pub fn upsert<I, C>(&mut self, rela_path: I, kind: EntryKind, id: ObjectId) -> Result<&mut Self, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        set_path(&mut self.path_buf, rela_path)?;
+        {
+            let Editor {
+                find,
+                object_hash,
+                trees,
+                path_buf,
+                tree_buf,
+            } = self;
+            let path = path_buf.as_bstr();
+            let mut start = 0;
+            let mut ends = component_ends(path).peekable();
+            while let Some(end) = ends.next() {
+                let name = &path[start..end];
+                let parent = trees
+                    .get_mut(&path[..start.saturating_sub(1)])
+                    .expect("parents are loaded before their children");
+                let existing = position(&parent.entries, name);
+                if ends.peek().is_none() {
+                    let mode: EntryMode = kind.into();
+                    match existing {
+                        Some(idx) if parent.entries[idx].mode.is_tree() == mode.is_tree() => {
+                            let entry = &mut parent.entries[idx];
+                            entry.mode = mode;
+                            entry.oid = id;
+                        }
+                        _ => {
+                            if let Some(idx) = existing {
+                                parent.entries.remove(idx);
+                            }
+                            insert(&mut parent.entries, mode, name, id);
+                        }
+                    }
+                    forget_subtrees(trees, path);
+                    break;
+                }
+
+                let tree_path = &path[..end];
+                match existing.map(|idx| &parent.entries[idx]) {
+                    Some(entry) if entry.mode.is_tree() => {
+                        let oid = entry.oid;
+                        if !trees.contains_key(tree_path) {
+                            let tree = load_tree(*find, oid, tree_buf)?;
+                            trees.insert(tree_path.to_owned(), tree);
+                        }
+                    }
+                    _ => {
+                        if let Some(idx) = existing {
+                            parent.entries.remove(idx);
+                        }
+                        insert(&mut parent.entries, EntryKind::Tree.into(), name, object_hash.null());
+                        trees.insert(tree_path.to_owned(), Tree::empty());
+                    }
+                }
+                start = end + 1;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Remove the entry at `rela_path`, which is given as its components, along with everything underneath it if it's a tree.
+    ///
+    /// Trees that become empty due to this will be removed when [writing](Self::write()). It's not an error if there is
+    /// no entry at `rela_path`.
+    pub fn remove<I, C>(&mut self, rela_path: I) -> Result<&mut Self, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        set_path(&mut self.path_buf, rela_path)?;
+        {
+            let Editor {
+                find,
+                trees,
+                path_buf,
+                tree_buf,
+                ..
+            } = self;
+            let path = path_buf.as_bstr();
+            // Trees loaded only to find out that there is nothing to remove are unloaded again so they aren't written needlessly.
+            let mut loaded = Vec::new();
+            let mut start = 0;
+            let mut ends = component_ends(path).peekable();
+            while let Some(end) = ends.next() {
+                let name = &path[start..end];
+                let parent = trees
+                    .get_mut(&path[..start.saturating_sub(1)])
+                    .expect("parents are loaded before their children");
+                let existing = position(&parent.entries, name);
+                if ends.peek().is_none() {
+                    if let Some(idx) = existing {
+                        parent.entries.remove(idx);
+                        forget_subtrees(trees, path);
+                        loaded.clear();
+                    }
+                    break;
+                }
+
+                let tree_path = &path[..end];
+                match existing.map(|idx| &parent.entries[idx]) {
+                    Some(entry) if entry.mode.is_tree() => {
+                        let oid = entry.oid;
+                        if !trees.contains_key(tree_path) {
+                            let tree = load_tree(*find, oid, tree_buf)?;
+                            trees.insert(tree_path.to_owned(), tree);
+                            loaded.push(tree_path.to_owned());
+                        }
+                    }
+                    _ => break,
+                }
+                start = end + 1;
+            }
+            for tree_path in loaded {
+                trees.remove(&tree_path);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Write all trees that were edited, from the bottom up, by passing each of them to `out` to obtain its id,
+    /// and return the id of the root tree.
+    ///
+    /// Trees that became empty are removed from their parent instead of being written. Afterwards, the editor
+    /// can be used for further edits based on the written root tree.
+    /// If `out` fails, all trees that weren't written yet remain in memory so `write()` can be called again.
+    pub fn write<E>(&mut self, mut out: impl FnMut(&Tree) -> Result<ObjectId, E>) -> Result<ObjectId, E> {
+        let mut paths: Vec<_> = self.trees.keys().cloned().collect();
+        // Parents sort before their children, so in reverse each tree is written after all of its children.
+        paths.sort();
+        for path in paths.iter().rev() {
+            let tree = self.trees.remove(path).expect("we collected all paths");
+            let (parent_path, name) = match path.rfind_byte(b'/') {
+                Some(pos) => (&path[..pos], &path[pos + 1..]),
+                None if path.is_empty() => {
+                    let res = out(&tree);
+                    self.trees.insert(path.clone(), tree);
+                    return res;
+                }
+                None => (&b""[..], &path[..]),
+            };
+            let parent = self
+                .trees
+                .get_mut(parent_path.as_bstr())
+                .expect("parents are written after their children");
+            let idx = position(&parent.entries, name.as_bstr()).expect("trees are always listed in their parent");
+            if tree.entries.is_empty() {
+                parent.entries.remove(idx);
+                continue;
+            }
+            match out(&tree) {
+                Ok(id) => parent.entries[idx].oid = id,
+                Err(err) => {
+                    self.trees.insert(path.clone(), tree);
+                    return Err(err);
+                }
+            }
+        }
+        unreachable!("the root tree is always present")
+    }
+}
+
+/// Validate all components of `rela_path` and join them with slashes into `buf`.
+fn set_path<I, C>(buf: &mut BString, rela_path: I) -> Result<(), Error>
+where
+    I: IntoIterator<Item = C>,
+    C: AsRef<BStr>,
+{
+    buf.clear();
+    for component in rela_path {
+        let component = component.as_ref();
+        if component.is_empty() || component.find_byteset(b"/\n").is_some() {
+            return Err(Error::InvalidPathComponent {
+                component: component.to_owned(),
+            });
+        }
+        if !buf.is_empty() {
+            buf.push_byte(b'/');
+        }
+        buf.extend_from_slice(component);
+    }
+    if buf.is_empty() {
+        return Err(Error::EmptyPath);
+    }
+    Ok(())
+}
+
+/// Return the end offset of each component of `path`.
+fn component_ends(path: &BStr) -> impl Iterator<Item = usize> + '_ {
+    path.find_iter(b"/").chain(Some(path.len()))
+}
+
+fn load_tree(find: &dyn crate::Find, id: ObjectId, buf: &mut Vec<u8>) -> Result<Tree, Error> {
+    // The empty tree doesn't have to exist in the object database.
+    if id.is_empty_tree() {
+        return Ok(Tree::empty());
+    }
+    Ok(find.find_tree(&id, buf)?.into())
+}
+
+/// Forget all trees at or underneath `path` as they were replaced or removed.
+fn forget_subtrees(trees: &mut HashMap<BString, Tree>, path: &BStr) {
+    trees.retain(|tree_path, _| {
+        !(tree_path.starts_with(path) && tree_path.get(path.len()).map_or(true, |b| *b == b'/'))
+    });
+}
+
+/// Compare `entry` to an entry named `name` which is a tree if `is_tree` is `true`, in the order git expects in trees.
+fn cmp_entry_with_name(entry: &Entry, name: &BStr, is_tree: bool) -> Ordering {
+    let common = entry.filename.len().min(name.len());
+    entry.filename[..common].cmp(&name[..common]).then_with(|| {
+        let a = entry
+            .filename
+            .get(common)
+            .or_else(|| entry.mode.is_tree().then_some(&b'/'));
+        let b = name.get(common).or_else(|| is_tree.then_some(&b'/'));
+        a.cmp(&b)
+    })
+}
+
+/// Return the index of the entry named `name` in the sorted `entries`, no matter what kind it is.
+fn position(entries: &[Entry], name: &BStr) -> Option<usize> {
+    [false, true]
+        .into_iter()
+        .find_map(|is_tree| entries.binary_search_by(|e| cmp_entry_with_name(e, name, is_tree)).ok())
+}
+
+/// Insert a new entry into `entries` so they remain sorted.
+fn insert(entries: &mut Vec<Entry>, mode: EntryMode, name: &BStr, oid: ObjectId) {
+    let idx = entries
+        .binary_search_by(|e| cmp_entry_with_name(e, name, mode.is_tree()))
+        .unwrap_or_else(|idx| idx);
+    entries.insert(
+        idx,
+        Entry {
+            mode,
+            filename: name.to_owned(),
+            oid,
+        },
+    );
+}
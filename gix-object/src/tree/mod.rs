@@ -5,11 +5,33 @@ use crate::{
     tree,
 };
 
+///
+#[allow(clippy::empty_docs)]
+pub mod editor;
 mod ref_iter;
 ///
 #[allow(clippy::empty_docs)]
 pub mod write;
 
+/// An editor to change a tree by path, which loads only the trees it needs from an object database and writes only those
+/// that were changed.
+///
+/// Each edit refers to a path relative to the root tree, and all trees leading up to the edited entry are loaded lazily and kept
+/// in memory, with their entries in the sort order git expects. Call [`write()`](Self::write()) to obtain the id of the new root tree.
+pub struct Editor<'a> {
+    /// A way to lookup trees.
+    find: &'a dyn crate::Find,
+    /// The kind of hash to use for placeholder ids of trees that are yet to be written.
+    object_hash: gix_hash::Kind,
+    /// All trees we currently hold in memory, keyed by their path relative to the root, which is the empty path.
+    /// Each of these has been or is about to be edited and will be written.
+    trees: std::collections::HashMap<BString, crate::Tree>,
+    /// A buffer to build paths with.
+    path_buf: BString,
+    /// A buffer to load trees into.
+    tree_buf: Vec<u8>,
+}
+
 /// The mode of items storable in a tree, similar to the file mode on a unix file system.
 ///
 /// Used in [`mutable::Entry`][crate::tree::Entry] and [`EntryRef`].
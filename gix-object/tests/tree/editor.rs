@@ -0,0 +1,237 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use gix_hash::ObjectId;
+use gix_object::{
+    bstr::{BStr, BString, ByteSlice},
+    tree::{self, Editor, EntryKind},
+    Tree, TreeRef, WriteTo,
+};
+
+/// An in-memory object database which only knows trees, and counts how many of them were written.
+#[derive(Default)]
+struct Odb {
+    trees: RefCell<HashMap<ObjectId, Vec<u8>>>,
+    writes: RefCell<usize>,
+}
+
+impl gix_object::Find for Odb {
+    fn try_find<'a>(
+        &self,
+        id: &gix_hash::oid,
+        buffer: &'a mut Vec<u8>,
+    ) -> Result<Option<gix_object::Data<'a>>, gix_object::find::Error> {
+        let trees = self.trees.borrow();
+        Ok(trees.get(id).map(|data| {
+            buffer.clear();
+            buffer.extend_from_slice(data);
+            gix_object::Data::new(gix_object::Kind::Tree, gix_hash::Kind::Sha1, buffer)
+        }))
+    }
+}
+
+impl Odb {
+    fn write(&self, tree: &Tree) -> Result<ObjectId, std::io::Error> {
+        let mut buf = Vec::new();
+        tree.write_to(&mut buf)?;
+        let id = gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Tree, &buf);
+        self.trees.borrow_mut().insert(id, buf);
+        *self.writes.borrow_mut() += 1;
+        Ok(id)
+    }
+
+    fn writes(&self) -> usize {
+        std::mem::take(&mut *self.writes.borrow_mut())
+    }
+
+    /// Return all non-tree entries underneath `id` as `(path, mode, id)`, in the order they are stored.
+    fn list(&self, id: ObjectId) -> Vec<(BString, tree::EntryMode, ObjectId)> {
+        let mut out = Vec::new();
+        self.list_inner(id, BString::default(), &mut out);
+        out
+    }
+
+    fn list_inner(&self, id: ObjectId, prefix: BString, out: &mut Vec<(BString, tree::EntryMode, ObjectId)>) {
+        let data = self.trees.borrow().get(&id).cloned().expect("tree was written");
+        let tree = TreeRef::from_bytes(&data, gix_hash::Kind::Sha1).expect("valid tree");
+        for entry in tree.entries {
+            let mut path = prefix.clone();
+            if !path.is_empty() {
+                path.push(b'/');
+            }
+            path.extend_from_slice(entry.filename);
+            if entry.mode.is_tree() {
+                self.list_inner(entry.oid.to_owned(), path, out);
+            } else {
+                out.push((path, entry.mode, entry.oid.to_owned()));
+            }
+        }
+    }
+}
+
+fn path(path: &str) -> impl Iterator<Item = &BStr> {
+    path.split('/').map(|c| c.as_bytes().as_bstr())
+}
+
+fn blob(n: u8) -> ObjectId {
+    ObjectId::from([n; 20])
+}
+
+fn paths(entries: Vec<(BString, tree::EntryMode, ObjectId)>) -> Vec<BString> {
+    entries.into_iter().map(|(path, _, _)| path).collect()
+}
+
+#[test]
+fn from_empty_tree_in_git_sort_order() -> crate::Result {
+    let odb = Odb::default();
+    let mut edit = Editor::new(Tree::empty(), &odb, gix_hash::Kind::Sha1);
+    for (rela_path, n) in [
+        ("file/a", 1),
+        ("file.toml", 2),
+        ("file0", 3),
+        ("bin.d", 4),
+        ("bin", 5),
+        ("file.to", 6),
+        ("file.toml.bin", 7),
+        ("file/b/c", 8),
+    ] {
+        edit.upsert(path(rela_path), EntryKind::Blob, blob(n))?;
+    }
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(odb.writes(), 3, "root, 'file' and 'file/b'");
+    assert_eq!(
+        paths(odb.list(id)),
+        [
+            "bin",
+            "bin.d",
+            "file.to",
+            "file.toml",
+            "file.toml.bin",
+            "file/a",
+            "file/b/c",
+            "file0"
+        ],
+        "trees sort as if they had a trailing slash"
+    );
+    Ok(())
+}
+
+#[test]
+fn only_edited_trees_are_loaded_and_written() -> crate::Result {
+    let odb = Odb::default();
+    let mut edit = Editor::new(Tree::empty(), &odb, gix_hash::Kind::Sha1);
+    edit.upsert(path("a/1"), EntryKind::Blob, blob(1))?
+        .upsert(path("b/c/2"), EntryKind::BlobExecutable, blob(2))?;
+    let base = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(odb.writes(), 4);
+    let root: Tree = TreeRef::from_bytes(&odb.trees.borrow()[&base], gix_hash::Kind::Sha1)?.into();
+    let a_id = root.entries[0].oid;
+
+    let mut edit = Editor::new(root.clone(), &odb, gix_hash::Kind::Sha1);
+    edit.upsert(path("b/c/3"), EntryKind::Link, blob(3))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(odb.writes(), 3, "'a' is untouched");
+    let new_root: Tree = TreeRef::from_bytes(&odb.trees.borrow()[&id], gix_hash::Kind::Sha1)?.into();
+    assert_eq!(new_root.entries[0].oid, a_id);
+    assert_eq!(
+        odb.list(id),
+        [
+            ("a/1".into(), EntryKind::Blob.into(), blob(1)),
+            ("b/c/2".into(), EntryKind::BlobExecutable.into(), blob(2)),
+            ("b/c/3".into(), EntryKind::Link.into(), blob(3)),
+        ]
+    );
+
+    let mut edit = Editor::new(root, &odb, gix_hash::Kind::Sha1);
+    edit.remove(path("b/c/does-not-exist"))?
+        .remove(path("a/1/not-a-tree"))?
+        .remove(path("missing/2"))?;
+    assert_eq!(
+        edit.write(|tree| odb.write(tree))?,
+        base,
+        "removing entries that don't exist changes nothing"
+    );
+    assert_eq!(odb.writes(), 1, "only the root is written");
+    Ok(())
+}
+
+#[test]
+fn remove_prunes_empty_trees() -> crate::Result {
+    let odb = Odb::default();
+    let mut edit = Editor::new(Tree::empty(), &odb, gix_hash::Kind::Sha1);
+    edit.upsert(path("a/b/c/1"), EntryKind::Blob, blob(1))?
+        .upsert(path("a/2"), EntryKind::Blob, blob(2))?;
+    let base = edit.write(|tree| odb.write(tree))?;
+
+    edit.remove(path("a/b/c/1"))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(paths(odb.list(id)), ["a/2"], "'a/b' and 'a/b/c' are gone");
+
+    edit.remove(Some("a"))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(
+        id,
+        ObjectId::empty_tree(gix_hash::Kind::Sha1),
+        "the root tree may be empty"
+    );
+
+    edit.upsert(Some("a"), EntryKind::Tree, base)?
+        .upsert(path("a/b/c/3"), EntryKind::Blob, blob(3))?
+        .remove(path("a/b/c/1"))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(
+        paths(odb.list(id)),
+        ["a/a/2", "a/a/b/c/1", "a/b/c/3"],
+        "trees can be inserted and edited"
+    );
+    Ok(())
+}
+
+#[test]
+fn entries_in_the_way_are_replaced() -> crate::Result {
+    let odb = Odb::default();
+    let mut edit = Editor::new(Tree::empty(), &odb, gix_hash::Kind::Sha1);
+    edit.upsert(Some("a"), EntryKind::Blob, blob(1))?
+        .upsert(path("b/c"), EntryKind::Blob, blob(2))?
+        .upsert(path("a/1"), EntryKind::Blob, blob(3))?
+        .upsert(Some("b"), EntryKind::BlobExecutable, blob(4))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(
+        odb.list(id),
+        [
+            ("a/1".into(), EntryKind::Blob.into(), blob(3)),
+            ("b".into(), EntryKind::BlobExecutable.into(), blob(4)),
+        ],
+        "blobs turn into trees and back, and pending edits underneath are discarded"
+    );
+    assert_eq!(odb.writes(), 2, "root and 'a'");
+
+    edit.upsert(Some("b"), EntryKind::Tree, ObjectId::empty_tree(gix_hash::Kind::Sha1))?
+        .upsert(path("b/new"), EntryKind::Blob, blob(5))?;
+    let id = edit.write(|tree| odb.write(tree))?;
+    assert_eq!(
+        paths(odb.list(id)),
+        ["a/1", "b/new"],
+        "the empty tree doesn't have to be in the object database"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_paths() {
+    let odb = Odb::default();
+    let mut edit = Editor::new(Tree::empty(), &odb, gix_hash::Kind::Sha1);
+    assert!(matches!(
+        edit.upsert(None::<&BStr>, EntryKind::Blob, blob(1)),
+        Err(tree::editor::Error::EmptyPath)
+    ));
+    for invalid in ["a//b", "a/", "", "a/new\nline"] {
+        assert!(matches!(
+            edit.remove(invalid.split('/').map(|c| c.as_bytes().as_bstr())),
+            Err(tree::editor::Error::InvalidPathComponent { .. })
+        ));
+    }
+    assert!(matches!(
+        edit.upsert(Some("a/b"), EntryKind::Blob, blob(1)),
+        Err(tree::editor::Error::InvalidPathComponent { component }) if component == "a/b"
+    ));
+}
@@ -1,3 +1,5 @@
+mod editor;
+
 mod iter {
     use gix_object::{bstr::ByteSlice, tree, tree::EntryRef, TreeRefIter};
 
@@ -5,13 +7,17 @@ mod iter {
 
     #[test]
     fn empty() {
-        assert_eq!(TreeRefIter::from_bytes(&[]).count(), 0, "empty trees are definitely ok");
+        assert_eq!(
+            TreeRefIter::from_bytes(&[], gix_hash::Kind::Sha1).count(),
+            0,
+            "empty trees are definitely ok"
+        );
     }
 
     #[test]
     fn error_handling() {
         let data = fixture_name("tree", "everything.tree");
-        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2]);
+        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2], gix_hash::Kind::Sha1);
         let entries = iter.collect::<Vec<_>>();
         assert!(
             entries.last().expect("at least one token").is_err(),
@@ -22,7 +28,8 @@ mod iter {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree")).collect::<Result<Vec<_>, _>>()?,
+            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)
+                .collect::<Result<Vec<_>, _>>()?,
             vec![
                 EntryRef {
                     mode: tree::EntryKind::BlobExecutable.into(),
@@ -63,7 +70,7 @@ mod from_bytes {
     #[test]
     fn empty() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&[])?,
+            TreeRef::from_bytes(&[], gix_hash::Kind::Sha1)?,
             TreeRef { entries: vec![] },
             "empty trees are valid despite usually rare in the wild"
         );
@@ -73,7 +80,7 @@ mod from_bytes {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"))?,
+            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)?,
             TreeRef {
                 entries: vec![
                     EntryRef {
@@ -107,18 +114,55 @@ mod from_bytes {
         Ok(())
     }
 
+    #[test]
+    fn sha256() -> crate::Result {
+        use gix_object::WriteTo;
+        let blob = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha256);
+        let tree = gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha256);
+        let expected = TreeRef {
+            entries: vec![
+                EntryRef {
+                    mode: tree::EntryKind::Blob.into(),
+                    filename: b"a".as_bstr(),
+                    oid: &blob,
+                },
+                EntryRef {
+                    mode: tree::EntryKind::Tree.into(),
+                    filename: b"b".as_bstr(),
+                    oid: &tree,
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        expected.write_to(&mut buf)?;
+        assert_eq!(TreeRef::from_bytes(&buf, gix_hash::Kind::Sha256)?, expected);
+        assert_eq!(
+            TreeRefIter::from_bytes(&buf, gix_hash::Kind::Sha256).collect::<Result<Vec<_>, _>>()?,
+            expected.entries
+        );
+        assert!(
+            TreeRef::from_bytes(&buf, gix_hash::Kind::Sha1).is_err(),
+            "the hash kind determines how entries are parsed"
+        );
+        Ok(())
+    }
+
     #[test]
     fn invalid() {
         let fixture = fixture_name("tree", "definitely-special.tree");
         let partial_tree = &fixture[..fixture.len() / 2];
-        let err = TreeRef::from_bytes(partial_tree).unwrap_err().to_string();
+        let err = TreeRef::from_bytes(partial_tree, gix_hash::Kind::Sha1)
+            .unwrap_err()
+            .to_string();
         if cfg!(feature = "verbose-object-parsing-errors") {
             assert!(err.starts_with("object parsing failed at `100644"), "{err}");
         } else {
             assert_eq!(err, "object parsing failed");
         }
         assert_eq!(
-            TreeRefIter::from_bytes(partial_tree).take_while(Result::is_ok).count(),
+            TreeRefIter::from_bytes(partial_tree, gix_hash::Kind::Sha1)
+                .take_while(Result::is_ok)
+                .count(),
             9,
             "we can decode about half of it before failing"
         );
@@ -126,7 +170,10 @@ mod from_bytes {
 
     #[test]
     fn fuzzed() {
-        assert!(gix_object::TreeRef::from_bytes(b"2").is_err(), "fail, but don't crash");
+        assert!(
+            gix_object::TreeRef::from_bytes(b"2", gix_hash::Kind::Sha1).is_err(),
+            "fail, but don't crash"
+        );
     }
 
     #[test]
@@ -142,12 +189,14 @@ mod from_bytes {
         ] {
             let fixture = fixture_name("tree", &format!("{name}.tree"));
             assert_eq!(
-                TreeRef::from_bytes(&fixture)?.entries.len(),
+                TreeRef::from_bytes(&fixture, gix_hash::Kind::Sha1)?.entries.len(),
                 expected_entry_count,
                 "{name}"
             );
             assert_eq!(
-                TreeRefIter::from_bytes(&fixture).map(Result::unwrap).count(),
+                TreeRefIter::from_bytes(&fixture, gix_hash::Kind::Sha1)
+                    .map(Result::unwrap)
+                    .count(),
                 expected_entry_count,
                 "{name}"
             );
@@ -164,7 +213,7 @@ mod entries {
         let root = gix_testtools::scripted_fixture_read_only("make_trees.sh")?;
         let input = std::fs::read(root.join("tree.baseline"))?;
 
-        let mut tree = TreeRef::from_bytes(&input)?;
+        let mut tree = TreeRef::from_bytes(&input, gix_hash::Kind::Sha1)?;
         let expected = tree.entries.clone();
 
         tree.entries.sort();
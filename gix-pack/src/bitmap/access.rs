@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use gix_bitmap::Bitset;
+
+use crate::bitmap::{decode, init::HEADER_LEN_WITHOUT_HASH, Entry, File, Index, Objects, Version};
+
+/// Basic file information
+impl File {
+    /// The version of the bitmap file.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+    /// The path of the opened bitmap file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// The kind of hash we assume.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+    /// The amount of objects in the pack or multi-pack index this file belongs to.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+    /// The checksum of the pack or multi-pack index this file belongs to.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from_bytes_or_panic(
+            &self.data[HEADER_LEN_WITHOUT_HASH..][..self.object_hash.len_in_bytes()],
+        )
+    }
+    /// Return `true` if the file contains a lookup table to find the bitmap of each commit without reading all of them.
+    pub fn has_lookup_table(&self) -> bool {
+        self.has_lookup_table
+    }
+    /// Return `true` if the file contains the name-hash of each object, see [`File::name_hash()`].
+    pub fn has_name_hash_cache(&self) -> bool {
+        self.name_hash_cache_ofs.is_some()
+    }
+}
+
+/// Access
+impl File {
+    /// All commits that have a bitmap, sorted by [index](Entry::index).
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Find the position within [`entries()`](File::entries()) of the commit at `index` in the pack index or multi-pack index,
+    /// or `None` if it doesn't have a bitmap.
+    pub fn lookup(&self, index: u32) -> Option<usize> {
+        self.entries.binary_search_by_key(&index, |entry| entry.index).ok()
+    }
+
+    /// Return the bitmap of all objects reachable from the commit at `entry_index` within [`entries()`](File::entries()).
+    pub fn bitmap_at(&self, entry_index: usize) -> Result<Bitset, decode::Error> {
+        let mut bitmap = Bitset::default();
+        let mut entry = &self.entries[entry_index];
+        for _ in 0..=self.entries.len() {
+            let (ewah, _) =
+                gix_bitmap::ewah::decode(&self.data[entry.offset..]).map_err(|source| decode::Error::Ewah {
+                    index: entry.index,
+                    source,
+                })?;
+            bitmap.xor(&Bitset::from(&ewah));
+            match entry.xor_base {
+                Some(base) => entry = &self.entries[base as usize],
+                None => return Ok(bitmap),
+            }
+        }
+        Err(decode::Error::XorCycle {
+            index: self.entries[entry_index].index,
+        })
+    }
+
+    /// Return the bitmap of all objects of the given `kind`.
+    pub fn objects_of_kind(&self, kind: gix_object::Kind) -> &Bitset {
+        &self.objects_by_kind[match kind {
+            gix_object::Kind::Commit => 0,
+            gix_object::Kind::Tree => 1,
+            gix_object::Kind::Blob => 2,
+            gix_object::Kind::Tag => 3,
+        }]
+    }
+
+    /// Return the name-hash of the object at `index` in the pack index or multi-pack index, if the name-hash cache is present.
+    ///
+    /// It's a hash of the path at which the object was seen, used to find similar objects for delta compression.
+    pub fn name_hash(&self, index: u32) -> Option<u32> {
+        let ofs = self.name_hash_cache_ofs?;
+        (index < self.num_objects).then(|| crate::read_u32(&self.data[ofs + index as usize * 4..][..4]))
+    }
+}
+
+/// Access
+impl Objects {
+    /// The amount of objects.
+    pub fn num_objects(&self) -> u32 {
+        match self {
+            Objects::Pack(index) => index.num_objects(),
+            Objects::MultiPack(index) => index.num_objects(),
+        }
+    }
+
+    /// Return the object id at `index`.
+    pub fn oid_at_index(&self, index: u32) -> &gix_hash::oid {
+        match self {
+            Objects::Pack(file) => file.oid_at_index(index),
+            Objects::MultiPack(file) => file.oid_at_index(index),
+        }
+    }
+
+    /// Return the index of `id`, or `None` if it isn't contained.
+    pub fn lookup(&self, id: &gix_hash::oid) -> Option<u32> {
+        match self {
+            Objects::Pack(index) => index.lookup(id),
+            Objects::MultiPack(index) => index.lookup(id),
+        }
+    }
+}
+
+/// Access
+impl Index {
+    /// The bitmap file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// The objects the bitmap file refers to.
+    pub fn objects(&self) -> &Objects {
+        &self.objects
+    }
+
+    /// The amount of objects, and thus the maximum amount of bits in each bitmap.
+    pub fn num_objects(&self) -> u32 {
+        self.index_by_position.len() as u32
+    }
+
+    /// Return the position of the bit representing `id`, or `None` if it isn't contained.
+    pub fn position(&self, id: &gix_hash::oid) -> Option<usize> {
+        self.objects
+            .lookup(id)
+            .map(|index| self.position_by_index[index as usize] as usize)
+    }
+
+    /// Return the id of the object represented by the bit at `position`.
+    pub fn id_at_position(&self, position: usize) -> &gix_hash::oid {
+        self.objects.oid_at_index(self.index_by_position[position])
+    }
+
+    /// Return the kind of the object represented by the bit at `position`, or `None` if it's unknown.
+    pub fn kind_at_position(&self, position: usize) -> Option<gix_object::Kind> {
+        use gix_object::Kind::*;
+        [Commit, Tree, Blob, Tag]
+            .into_iter()
+            .find(|kind| self.file.objects_of_kind(*kind).is_set(position))
+    }
+
+    /// Return the name-hash of the object represented by the bit at `position`, if the name-hash cache is present.
+    pub fn name_hash_at_position(&self, position: usize) -> Option<u32> {
+        self.file.name_hash(self.index_by_position[position])
+    }
+
+    /// Return the stored bitmap of the commit at `position`, or `None` if there is none.
+    pub fn bitmap_at_position(&self, position: usize) -> Option<Result<Bitset, decode::Error>> {
+        self.file
+            .lookup(self.index_by_position[position])
+            .map(|entry_index| self.file.bitmap_at(entry_index))
+    }
+
+    /// Return the stored bitmap of `commit`, or `None` if there is none.
+    ///
+    /// Use [`reachable()`](Index::reachable()) to obtain the objects reachable from any commit.
+    pub fn bitmap(&self, commit: &gix_hash::oid) -> Option<Result<Bitset, decode::Error>> {
+        self.position(commit)
+            .and_then(|position| self.bitmap_at_position(position))
+    }
+}
@@ -0,0 +1,12 @@
+/// Returned by [`File::bitmap_at()`](crate::bitmap::File::bitmap_at()).
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not decode the bitmap of the commit at index {index}")]
+    Ewah {
+        index: u32,
+        source: gix_bitmap::ewah::decode::Error,
+    },
+    #[error("The bitmap of the commit at index {index} is xored with itself")]
+    XorCycle { index: u32 },
+}
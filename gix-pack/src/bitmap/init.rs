@@ -0,0 +1,283 @@
+use std::path::Path;
+
+use gix_bitmap::Bitset;
+
+use crate::bitmap::{Entry, File, Index, Objects, Version};
+
+/// Returned by [`File::at()`] and the constructors of [`Index`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open bitmap file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: &'static str },
+    #[error("Unsupported bitmap version: {version}")]
+    UnsupportedVersion { version: u16 },
+    #[error("Could not decode a bitmap stored in the file")]
+    Ewah(#[from] gix_bitmap::ewah::decode::Error),
+    #[error("The bitmap belongs to {actual}, but the index belongs to {expected}")]
+    ChecksumMismatch {
+        expected: gix_hash::ObjectId,
+        actual: gix_hash::ObjectId,
+    },
+    #[error("The multi-pack index doesn't have a reverse index, which is required to interpret its bitmap")]
+    MissingReverseIndex,
+}
+
+pub(crate) const SIGNATURE: &[u8] = b"BITM";
+pub(crate) const HEADER_LEN_WITHOUT_HASH: usize = 4 /*signature*/ + 2 /*version*/ + 2 /*flags*/ + 4 /*num entries*/;
+/// The size of an entry in the lookup table.
+pub(crate) const LOOKUP_TABLE_ENTRY_LEN: usize = 4 /*index*/ + 8 /*offset*/ + 4 /*xor row*/;
+/// The size of the entry header which precedes each commit bitmap.
+pub(crate) const ENTRY_HEADER_LEN: usize = 4 /*index*/ + 1 /*xor offset*/ + 1 /*flags*/;
+/// The value of an xor row in the lookup table if there is no xor base.
+pub(crate) const NO_XOR_ROW: u32 = u32::MAX;
+
+/// The flags stored in the header of the bitmap file.
+pub(crate) mod flags {
+    /// All objects reachable from a commit are part of its bitmap, which is required.
+    pub const FULL_DAG: u16 = 0x1;
+    /// The file contains the name-hash of each object.
+    pub const HASH_CACHE: u16 = 0x4;
+    /// The file contains a lookup table for quick access to commit bitmaps.
+    pub const LOOKUP_TABLE: u16 = 0x10;
+}
+
+/// Instantiation
+impl File {
+    /// Open the bitmap file at `path`, which must have been written for an index with `num_objects` objects
+    /// and the given `object_hash`.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind, num_objects: u32) -> Result<File, Error> {
+        Self::at_inner(path.as_ref(), object_hash, num_objects)
+    }
+
+    fn at_inner(path: &Path, object_hash: gix_hash::Kind, num_objects: u32) -> Result<File, Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let hash_len = object_hash.len_in_bytes();
+        if data.len() < HEADER_LEN_WITHOUT_HASH + hash_len * 2 {
+            return Err(Error::Corrupt {
+                message: "bitmap file is truncated and too short",
+            });
+        }
+        let (signature, rest) = data.split_at(SIGNATURE.len());
+        if signature != SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Invalid signature",
+            });
+        }
+        let (version, rest) = rest.split_at(2);
+        let version = match u16::from_be_bytes(version.try_into().expect("2 bytes")) {
+            1 => Version::V1,
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let (options, rest) = rest.split_at(2);
+        let options = u16::from_be_bytes(options.try_into().expect("2 bytes"));
+        if options & flags::FULL_DAG == 0 {
+            return Err(Error::Corrupt {
+                message: "Only bitmaps of the full object graph are supported",
+            });
+        }
+        let num_entries = crate::read_u32(&rest[..4]) as usize;
+
+        let mut end = data.len() - hash_len;
+        let name_hash_cache_ofs = if options & flags::HASH_CACHE != 0 {
+            end = end
+                .checked_sub(num_objects as usize * 4)
+                .filter(|end| *end >= HEADER_LEN_WITHOUT_HASH + hash_len)
+                .ok_or(Error::Corrupt {
+                    message: "bitmap file is too small to contain the name-hash cache",
+                })?;
+            Some(end)
+        } else {
+            None
+        };
+        let lookup_table = if options & flags::LOOKUP_TABLE != 0 {
+            end = end
+                .checked_sub(num_entries * LOOKUP_TABLE_ENTRY_LEN)
+                .filter(|end| *end >= HEADER_LEN_WITHOUT_HASH + hash_len)
+                .ok_or(Error::Corrupt {
+                    message: "bitmap file is too small to contain the lookup table",
+                })?;
+            Some(&data[end..][..num_entries * LOOKUP_TABLE_ENTRY_LEN])
+        } else {
+            None
+        };
+
+        let mut rest = &data[HEADER_LEN_WITHOUT_HASH + hash_len..end];
+        let mut objects_by_kind: [Bitset; 4] = Default::default();
+        for bitmap in objects_by_kind.iter_mut() {
+            let (ewah, remaining) = gix_bitmap::ewah::decode(rest)?;
+            *bitmap = Bitset::from(&ewah);
+            rest = remaining;
+        }
+
+        let has_lookup_table = lookup_table.is_some();
+        let entries = match lookup_table {
+            Some(table) => entries_from_lookup_table(table, end)?,
+            None => entries_from_bitmaps(rest, end - rest.len(), num_entries)?,
+        };
+
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            version,
+            object_hash,
+            num_objects,
+            objects_by_kind,
+            entries,
+            name_hash_cache_ofs,
+            has_lookup_table,
+        })
+    }
+}
+
+fn entries_from_lookup_table(table: &[u8], end: usize) -> Result<Vec<Entry>, Error> {
+    let num_entries = table.len() / LOOKUP_TABLE_ENTRY_LEN;
+    let mut entries = Vec::<Entry>::with_capacity(num_entries);
+    for row in table.chunks_exact(LOOKUP_TABLE_ENTRY_LEN) {
+        let index = crate::read_u32(&row[..4]);
+        let offset = usize::try_from(crate::read_u64(&row[4..][..8]))
+            .ok()
+            .filter(|offset| offset + ENTRY_HEADER_LEN < end)
+            .ok_or(Error::Corrupt {
+                message: "bitmap offset in lookup table is out of bounds",
+            })?;
+        let xor_row = crate::read_u32(&row[12..][..4]);
+        if matches!(entries.last(), Some(last) if last.index >= index) {
+            return Err(Error::Corrupt {
+                message: "lookup table isn't sorted by commit",
+            });
+        }
+        entries.push(Entry {
+            index,
+            xor_base: match xor_row {
+                NO_XOR_ROW => None,
+                row if (row as usize) < num_entries => Some(row),
+                _ => {
+                    return Err(Error::Corrupt {
+                        message: "xor row in lookup table is out of bounds",
+                    })
+                }
+            },
+            offset: offset + ENTRY_HEADER_LEN,
+        });
+    }
+    Ok(entries)
+}
+
+/// Learn about all entries by skipping over each of the `num_entries` bitmaps in `data`, which is at `data_ofs` in the file.
+fn entries_from_bitmaps(mut data: &[u8], data_ofs: usize, num_entries: usize) -> Result<Vec<Entry>, Error> {
+    let corrupt = |message| Error::Corrupt { message };
+    let mut entries = Vec::<Entry>::with_capacity(num_entries);
+    let mut offset = data_ofs;
+    for entry_index in 0..num_entries {
+        let header = data
+            .get(..ENTRY_HEADER_LEN + 8)
+            .ok_or(corrupt("eof reading bitmap entry"))?;
+        let index = crate::read_u32(&header[..4]);
+        let xor_offset = header[4] as usize;
+        let num_words = crate::read_u32(&header[ENTRY_HEADER_LEN + 4..][..4]) as usize;
+        let entry_len = ENTRY_HEADER_LEN + 4 /*num bits*/ + 4 /*num words*/ + num_words * 8 + 4 /*rlw*/;
+        if data.len() < entry_len {
+            return Err(corrupt("eof reading bitmap"));
+        }
+        entries.push(Entry {
+            index,
+            xor_base: match xor_offset {
+                0 => None,
+                xor_offset => Some(
+                    entry_index
+                        .checked_sub(xor_offset)
+                        .ok_or(corrupt("xor offset of bitmap entry is out of bounds"))? as u32,
+                ),
+            },
+            offset: offset + ENTRY_HEADER_LEN,
+        });
+        data = &data[entry_len..];
+        offset += entry_len;
+    }
+
+    // Entries are stored in any order, so sort them by commit and adjust their xor bases accordingly.
+    let mut sorted: Vec<_> = (0..entries.len()).collect();
+    sorted.sort_by_key(|entry_index| entries[*entry_index].index);
+    let mut sorted_index_by_entry_index = vec![0u32; entries.len()];
+    for (sorted_index, entry_index) in sorted.iter().enumerate() {
+        sorted_index_by_entry_index[*entry_index] = sorted_index as u32;
+    }
+    Ok(sorted
+        .into_iter()
+        .map(|entry_index| {
+            let entry = entries[entry_index];
+            Entry {
+                xor_base: entry.xor_base.map(|base| sorted_index_by_entry_index[base as usize]),
+                ..entry
+            }
+        })
+        .collect())
+}
+
+/// Instantiation
+impl Index {
+    /// Combine the bitmap `file` with the `objects` it was written for.
+    pub fn new(file: File, objects: Objects) -> Result<Self, Error> {
+        let expected = match &objects {
+            Objects::Pack(index) => index.pack_checksum(),
+            Objects::MultiPack(index) => index.checksum(),
+        };
+        let actual = file.checksum();
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+
+        let index_by_position: Vec<u32> = match &objects {
+            Objects::Pack(index) => {
+                let mut indices: Vec<_> = (0..index.num_objects()).collect();
+                indices.sort_by_key(|entry_index| index.pack_offset_at_index(*entry_index));
+                indices
+            }
+            Objects::MultiPack(index) => (0..index.num_objects())
+                .map(|position| index.index_at_pseudo_pack_position(position))
+                .collect::<Option<_>>()
+                .ok_or(Error::MissingReverseIndex)?,
+        };
+        let mut position_by_index = vec![0; index_by_position.len()];
+        for (position, index) in index_by_position.iter().enumerate() {
+            *position_by_index.get_mut(*index as usize).ok_or(Error::Corrupt {
+                message: "reverse index refers to objects that don't exist",
+            })? = position as u32;
+        }
+        Ok(Index {
+            file,
+            objects,
+            index_by_position,
+            position_by_index,
+        })
+    }
+
+    /// Open the bitmap file that belongs to the pack `index`, which is expected next to it with the `.bitmap` extension.
+    pub fn at_pack_index(index: crate::index::File) -> Result<Self, Error> {
+        let file = File::at(
+            index.path().with_extension("bitmap"),
+            index.object_hash(),
+            index.num_objects(),
+        )?;
+        Self::new(file, Objects::Pack(index))
+    }
+
+    /// Open the bitmap file that belongs to the multi-pack `index`, which is expected next to it as
+    /// `multi-pack-index-<checksum>.bitmap`.
+    pub fn at_multi_index(index: crate::multi_index::File) -> Result<Self, Error> {
+        let path = index
+            .path()
+            .with_file_name(format!("multi-pack-index-{}.bitmap", index.checksum()));
+        let file = File::at(path, index.object_hash(), index.num_objects())?;
+        Self::new(file, Objects::MultiPack(index))
+    }
+}
@@ -0,0 +1,82 @@
+//! Reachability bitmaps (`*.bitmap`) of packs and multi-pack indices.
+//!
+//! A bitmap file stores, for a selection of commits, which objects are reachable from them. Each bit refers to an object
+//! in _pack order_, i.e. sorted by offset in the pack, or in _pseudo-pack order_ for multi-pack indices. Along with the
+//! index whose objects they refer to, bitmaps can be used as [`Index`] to quickly compute the set of objects reachable
+//! from any commit, or the objects to send to a remote which has some commits already.
+use gix_bitmap::Bitset;
+use memmap2::Mmap;
+
+/// The version of a bitmap file.
+#[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    #[default]
+    V1 = 1,
+}
+
+/// A commit along with the location of its reachability bitmap.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Entry {
+    /// The index of the commit in the pack index or multi-pack index the bitmap belongs to.
+    pub index: u32,
+    /// The index of the entry whose bitmap has to be xored with our bitmap to obtain the actual bitmap, within
+    /// [`File::entries()`].
+    pub xor_base: Option<u32>,
+    /// The offset of the EWAH bitmap within the file.
+    offset: usize,
+}
+
+/// A representation of a `.bitmap` file, mapping commits to the objects reachable from them.
+pub struct File {
+    data: Mmap,
+    path: std::path::PathBuf,
+    version: Version,
+    object_hash: gix_hash::Kind,
+    num_objects: u32,
+    /// The bitmaps of commits, trees, blobs and tags, in that order.
+    objects_by_kind: [Bitset; 4],
+    /// All commits that have a bitmap, sorted by index.
+    entries: Vec<Entry>,
+    /// The offset to the name-hash cache, with one `u32` per object in index order.
+    name_hash_cache_ofs: Option<usize>,
+    /// If `true`, the lookup table was used to learn about entries.
+    has_lookup_table: bool,
+}
+
+/// The objects a [bitmap file](File) refers to.
+pub enum Objects {
+    /// The objects of a single pack, for bitmaps named after the pack.
+    Pack(crate::index::File),
+    /// The objects of all packs in the multi-pack index, for bitmaps named after the multi-pack index checksum.
+    MultiPack(crate::multi_index::File),
+}
+
+/// A [bitmap file](File) along with the objects it refers to, to use it for reachability queries.
+pub struct Index {
+    file: File,
+    objects: Objects,
+    /// The index of each object in `objects`, by position in the bitmap.
+    index_by_position: Vec<u32>,
+    /// The position in the bitmap of each object, by index in `objects`.
+    position_by_index: Vec<u32>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod reachable;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
+
+mod access;
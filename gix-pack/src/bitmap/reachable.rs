@@ -0,0 +1,238 @@
+use gix_bitmap::Bitset;
+use gix_hash::ObjectId;
+use gix_object::{
+    bstr::{BStr, BString, ByteVec},
+    FindExt,
+};
+
+use crate::bitmap::Index;
+
+/// Returned by [`Index::reachable()`] and [`Index::objects_to_send()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Find(#[from] gix_object::find::existing::Error),
+    #[error("Could not decode object {id}")]
+    Decode {
+        id: ObjectId,
+        source: gix_object::decode::Error,
+    },
+    #[error(transparent)]
+    Bitmap(#[from] crate::bitmap::decode::Error),
+}
+
+/// Reachability
+impl Index {
+    /// Return the bitmap of all objects reachable from `tips`, which may be commits, tags, trees or blobs, with `objects`
+    /// being used to read objects which aren't covered by stored bitmaps.
+    ///
+    /// Return `None` if any of the reachable objects isn't contained in the objects the bitmap refers to, in which case
+    /// reachability has to be computed without bitmaps.
+    pub fn reachable(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        objects: &dyn gix_object::Find,
+    ) -> Result<Option<Bitset>, Error> {
+        let mut bitmap = Bitset::with_capacity(self.num_objects() as usize);
+        let complete = self.traversal(objects, false).fill(
+            &mut bitmap,
+            tips,
+            &mut |position, bitmap| self.or_stored(position, bitmap),
+            &mut |_, _| {},
+        )?;
+        Ok(complete.then_some(bitmap))
+    }
+
+    /// Return the bitmap of all objects reachable from `wants` which aren't reachable from `haves`, i.e. the objects that
+    /// would have to be sent to a remote which has `haves` and wants `wants`.
+    ///
+    /// Use [`id_at_position()`](Index::id_at_position()) to obtain the id of each object in the returned bitmap.
+    ///
+    /// `haves` which aren't contained in the objects the bitmap refers to are ignored, which may cause more objects to be
+    /// sent than needed. Return `None` if any of the objects reachable from `wants` isn't contained in the objects the bitmap
+    /// refers to, in which case the objects to send have to be computed without bitmaps.
+    pub fn objects_to_send(
+        &self,
+        wants: impl IntoIterator<Item = ObjectId>,
+        haves: impl IntoIterator<Item = ObjectId>,
+        objects: &dyn gix_object::Find,
+    ) -> Result<Option<Bitset>, Error> {
+        let mut have_bitmap = Bitset::with_capacity(self.num_objects() as usize);
+        self.traversal(objects, true).fill(
+            &mut have_bitmap,
+            haves,
+            &mut |position, bitmap| self.or_stored(position, bitmap),
+            &mut |_, _| {},
+        )?;
+
+        // Start with what the other side has to avoid traversing into it, and remove it once we are done.
+        let mut bitmap = have_bitmap.clone();
+        let complete = self.traversal(objects, false).fill(
+            &mut bitmap,
+            wants,
+            &mut |position, bitmap| self.or_stored(position, bitmap),
+            &mut |_, _| {},
+        )?;
+        Ok(complete.then(|| {
+            bitmap.and_not(&have_bitmap);
+            bitmap
+        }))
+    }
+
+    fn traversal<'a>(&'a self, objects: &'a dyn gix_object::Find, skip_unknown: bool) -> Traversal<'a> {
+        Traversal {
+            position: Box::new(move |id| self.position(id)),
+            objects,
+            skip_unknown,
+            buf: Vec::new(),
+            path: BString::default(),
+        }
+    }
+
+    fn or_stored(&self, position: usize, bitmap: &mut Bitset) -> Result<bool, Error> {
+        Ok(match self.bitmap_at_position(position).transpose()? {
+            Some(stored) => {
+                bitmap.or(&stored);
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+/// Return the position of an object in a bitmap, if it's known.
+pub(crate) type PositionFn<'a> = Box<dyn Fn(&gix_hash::oid) -> Option<usize> + 'a>;
+
+/// A traversal of the commit graph and trees, which marks all encountered objects by their position in a bitmap.
+pub(crate) struct Traversal<'a> {
+    /// Return the position of an object in the bitmap, or `None` if it's unknown.
+    pub position: PositionFn<'a>,
+    pub objects: &'a dyn gix_object::Find,
+    /// If `true`, unknown objects are ignored, otherwise the traversal stops once the first unknown object is encountered.
+    pub skip_unknown: bool,
+    pub buf: Vec<u8>,
+    pub path: BString,
+}
+
+impl Traversal<'_> {
+    /// Mark all objects reachable from `tips` in `bitmap`, without traversing into objects which are already marked.
+    ///
+    /// `or_stored(position, bitmap)` is called for each commit before traversing it, and returns `true` if it could merge a
+    /// stored bitmap of the commit into `bitmap` so it doesn't have to be traversed.
+    /// `on_object(position, path)` is called for each tree and blob that was marked while traversing trees, along with the
+    /// path at which it was first seen.
+    ///
+    /// Return `false` if an unknown object was encountered and the traversal stopped.
+    pub fn fill(
+        &mut self,
+        bitmap: &mut Bitset,
+        tips: impl IntoIterator<Item = ObjectId>,
+        or_stored: &mut dyn FnMut(usize, &mut Bitset) -> Result<bool, Error>,
+        on_object: &mut dyn FnMut(usize, &BStr),
+    ) -> Result<bool, Error> {
+        let mut commits = Vec::new();
+        for mut id in tips {
+            loop {
+                let Some(position) = (self.position)(&id) else {
+                    if self.skip_unknown {
+                        break;
+                    }
+                    return Ok(false);
+                };
+                if bitmap.is_set(position) {
+                    break;
+                }
+                let object = self.objects.find(&id, &mut self.buf)?;
+                match object.kind {
+                    gix_object::Kind::Tag => {
+                        bitmap.set(position);
+                        id = gix_object::TagRefIter::from_bytes(object.data)
+                            .target_id()
+                            .map_err(|source| Error::Decode { id, source })?;
+                        continue;
+                    }
+                    gix_object::Kind::Commit => commits.push(id),
+                    gix_object::Kind::Tree => {
+                        self.path.clear();
+                        if !self.fill_tree(bitmap, id, on_object)? {
+                            return Ok(false);
+                        }
+                    }
+                    gix_object::Kind::Blob => {
+                        bitmap.set(position);
+                    }
+                }
+                break;
+            }
+        }
+
+        while let Some(id) = commits.pop() {
+            let Some(position) = (self.position)(&id) else {
+                if self.skip_unknown {
+                    continue;
+                }
+                return Ok(false);
+            };
+            if bitmap.is_set(position) || or_stored(position, bitmap)? {
+                continue;
+            }
+            bitmap.set(position);
+            let mut commit = gix_object::CommitRefIter::from_bytes(self.objects.find(&id, &mut self.buf)?.data);
+            let tree_id = commit.tree_id().map_err(|source| Error::Decode { id, source })?;
+            commits.extend(commit.parent_ids());
+            self.path.clear();
+            if !self.fill_tree(bitmap, tree_id, on_object)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn fill_tree(
+        &mut self,
+        bitmap: &mut Bitset,
+        id: ObjectId,
+        on_object: &mut dyn FnMut(usize, &BStr),
+    ) -> Result<bool, Error> {
+        let Some(position) = (self.position)(&id) else {
+            return Ok(self.skip_unknown);
+        };
+        if !bitmap.set(position) {
+            return Ok(true);
+        }
+        on_object(position, self.path.as_ref());
+        let tree = self.objects.find(&id, &mut self.buf)?;
+        let tree: gix_object::Tree = gix_object::TreeRef::from_bytes(tree.data, id.kind())
+            .map_err(|source| Error::Decode { id, source })?
+            .into();
+        for entry in tree.entries {
+            if entry.mode.is_commit() {
+                continue;
+            }
+            let prev_len = self.path.len();
+            if prev_len != 0 {
+                self.path.push_byte(b'/');
+            }
+            self.path.push_str(&entry.filename);
+            let complete = if entry.mode.is_tree() {
+                self.fill_tree(bitmap, entry.oid, on_object)?
+            } else {
+                match (self.position)(&entry.oid) {
+                    Some(position) => {
+                        if bitmap.set(position) {
+                            on_object(position, self.path.as_ref());
+                        }
+                        true
+                    }
+                    None => self.skip_unknown,
+                }
+            };
+            self.path.truncate(prev_len);
+            if !complete {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    collections::VecDeque,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use gix_bitmap::{ewah, Bitset};
+use gix_features::progress::{Count, DynNestedProgress, Progress};
+use gix_hash::ObjectId;
+use gix_object::{
+    bstr::{BStr, BString},
+    FindExt,
+};
+
+use crate::bitmap::{
+    init::{flags, NO_XOR_ROW, SIGNATURE},
+    reachable::Traversal,
+    File, Version,
+};
+
+mod error {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [`File::write_from_pack_index()`][crate::bitmap::File::write_from_pack_index()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error(transparent)]
+        Find(#[from] gix_object::find::existing::Error),
+        #[error("Could not decode commit {id}")]
+        Decode {
+            id: ObjectId,
+            source: gix_object::decode::Error,
+        },
+        #[error(transparent)]
+        Traverse(#[from] crate::bitmap::reachable::Error),
+        #[error("Object {id} is not contained in the pack")]
+        NotFound { id: ObjectId },
+        #[error("Objects reachable from commit {id} are not contained in the pack")]
+        Incomplete { id: ObjectId },
+    }
+}
+pub use error::Error;
+
+/// Options for use in [`File::write_from_pack_index()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// If `true`, store a hash of the path at which each object was seen, to help finding similar objects for delta compression.
+    pub name_hash_cache: bool,
+    /// If `true`, store a table that allows to find the bitmap of each commit without reading all bitmaps.
+    pub lookup_table: bool,
+}
+
+impl Default for Options {
+    /// Use the same defaults as git.
+    fn default() -> Self {
+        Options {
+            name_hash_cache: true,
+            lookup_table: false,
+        }
+    }
+}
+
+/// The result of [`File::write_from_pack_index()`].
+pub struct Outcome {
+    /// The amount of commits for which a bitmap was stored.
+    pub num_bitmaps: usize,
+    /// The checksum of the written bitmap file.
+    pub checksum: ObjectId,
+}
+
+/// The progress ids used in [`File::write_from_pack_index()`].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// Counts the objects whose kind we learn, and the commits we decode.
+    ReadingObjects,
+    /// Counts the commits for which bitmaps are computed.
+    BuildingBitmaps,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::ReadingObjects => *b"BWRO",
+            ProgressId::BuildingBitmaps => *b"BWBB",
+        }
+    }
+}
+
+/// Like git, only search this many previously written bitmaps for one to xor with.
+const MAX_XOR_OFFSET: usize = 10;
+
+/// A commit in the pack.
+struct Commit {
+    index: u32,
+    parents: Vec<ObjectId>,
+    time: i64,
+}
+
+impl File {
+    /// Write a bitmap file for the pack whose `index` is given to `out`, with `objects` providing access to the objects in the pack.
+    ///
+    /// Commits to store bitmaps for are selected with the same heuristics as git, which selects more commits among the most recent ones,
+    /// and prefers `tips`, typically the commits references point to, and merge commits.
+    ///
+    /// Note that the pack must contain all objects reachable from the commits in it.
+    /// Progress is sent to `progress` and interruptions checked via `should_interrupt`.
+    pub fn write_from_pack_index(
+        index: &crate::index::File,
+        tips: &[ObjectId],
+        objects: &dyn gix_object::FindObjectOrHeader,
+        out: &mut dyn std::io::Write,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        Options {
+            name_hash_cache,
+            lookup_table,
+        }: Options,
+    ) -> Result<Outcome, Error> {
+        let num_objects = index.num_objects();
+        let mut index_by_position: Vec<u32> = (0..num_objects).collect();
+        index_by_position.sort_by_key(|entry_index| index.pack_offset_at_index(*entry_index));
+        let mut position_by_index = vec![0; num_objects as usize];
+        for (position, index) in index_by_position.iter().enumerate() {
+            position_by_index[*index as usize] = position;
+        }
+
+        let mut objects_by_kind: [Bitset; 4] = Default::default();
+        let mut commits = Vec::new();
+        {
+            let start = Instant::now();
+            let mut progress = progress.add_child_with_id("Reading objects".into(), ProgressId::ReadingObjects.into());
+            progress.init(Some(num_objects as usize), gix_features::progress::count("objects"));
+            let mut buf = Vec::new();
+            for (entry_index, position) in position_by_index.iter().enumerate() {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let id = index.oid_at_index(entry_index as u32);
+                let header = objects
+                    .try_header(id)
+                    .map_err(gix_object::find::existing::Error::Find)?
+                    .ok_or_else(|| Error::NotFound { id: id.to_owned() })?;
+                objects_by_kind[kind_index(header.kind)].set(*position);
+                if header.kind == gix_object::Kind::Commit {
+                    let data = objects.find(id, &mut buf)?;
+                    let commit = gix_object::CommitRef::from_bytes(data.data).map_err(|source| Error::Decode {
+                        id: id.to_owned(),
+                        source,
+                    })?;
+                    commits.push(Commit {
+                        index: entry_index as u32,
+                        parents: commit.parents().collect(),
+                        time: commit.committer.time.seconds,
+                    });
+                }
+                progress.inc();
+            }
+            progress.show_throughput(start);
+        }
+
+        let selected = {
+            let tips: Vec<_> = tips
+                .iter()
+                .filter_map(|tip| peeled_commit_index(index, objects, *tip))
+                .collect();
+            select_commits(&mut commits, &tips)
+        };
+        let generation = generations(index, &commits);
+        let mut selected: Vec<_> = selected
+            .into_iter()
+            .map(|commit_index| commits[commit_index].index)
+            .collect();
+        selected.sort_by_key(|index| (generation(*index), *index));
+
+        let mut name_hashes = vec![0u32; if name_hash_cache { num_objects as usize } else { 0 }];
+        let mut has_name_hash = Bitset::default();
+        // The bitmaps in the order they are written, along with their index and their xor offset.
+        let mut bitmaps = Vec::<(u32, u8, ewah::Vec)>::with_capacity(selected.len());
+        {
+            let start = Instant::now();
+            let mut progress =
+                progress.add_child_with_id("Building bitmaps".into(), ProgressId::BuildingBitmaps.into());
+            progress.init(Some(selected.len()), gix_features::progress::count("commits"));
+            // The complete bitmap of each commit by its position, as `bitmaps` may only contain its xor with another bitmap.
+            let mut stored = std::collections::HashMap::<usize, ewah::Vec>::new();
+            let mut recent = VecDeque::<Bitset>::with_capacity(MAX_XOR_OFFSET);
+            let mut traversal = Traversal {
+                position: Box::new(|id| index.lookup(id).map(|index| position_by_index[index as usize])),
+                objects,
+                skip_unknown: false,
+                buf: Vec::new(),
+                path: BString::default(),
+            };
+            for commit_index in selected {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let id = index.oid_at_index(commit_index).to_owned();
+                let mut bitmap = Bitset::with_capacity(num_objects as usize);
+                let complete = traversal.fill(
+                    &mut bitmap,
+                    Some(id),
+                    &mut |position, bitmap| {
+                        Ok(match stored.get(&position) {
+                            Some(stored) => {
+                                bitmap.or(&Bitset::from(stored));
+                                true
+                            }
+                            None => false,
+                        })
+                    },
+                    &mut |position, path| {
+                        if name_hash_cache && has_name_hash.set(position) {
+                            name_hashes[index_by_position[position] as usize] = name_hash(path);
+                        }
+                    },
+                )?;
+                if !complete {
+                    return Err(Error::Incomplete { id });
+                }
+
+                let ewah = bitmap.to_ewah();
+                let mut best = (0, ewah.clone());
+                for (xor_offset, base) in recent.iter().rev().enumerate() {
+                    let mut xored = bitmap.clone();
+                    xored.xor(base);
+                    let xored = xored.to_ewah();
+                    if xored.size_in_bytes() < best.1.size_in_bytes() {
+                        best = (xor_offset + 1, xored);
+                    }
+                }
+                stored.insert(position_by_index[commit_index as usize], ewah);
+                bitmaps.push((commit_index, best.0 as u8, best.1));
+                if recent.len() == MAX_XOR_OFFSET {
+                    recent.pop_front();
+                }
+                recent.push_back(bitmap);
+                progress.inc();
+            }
+            progress.show_throughput(start);
+        }
+
+        let object_hash = index.object_hash();
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        let mut options = flags::FULL_DAG;
+        if name_hash_cache {
+            options |= flags::HASH_CACHE;
+        }
+        if lookup_table {
+            options |= flags::LOOKUP_TABLE;
+        }
+        out.write_all(SIGNATURE)?;
+        out.write_all(&(Version::V1 as u16).to_be_bytes())?;
+        out.write_all(&options.to_be_bytes())?;
+        out.write_all(&(bitmaps.len() as u32).to_be_bytes())?;
+        out.write_all(index.pack_checksum().as_slice())?;
+        let mut offset = (super::init::HEADER_LEN_WITHOUT_HASH + object_hash.len_in_bytes()) as u64;
+        for bitmap in &objects_by_kind {
+            let bitmap = bitmap.to_ewah();
+            bitmap.write_to(&mut out)?;
+            offset += bitmap.size_in_bytes() as u64;
+        }
+
+        let mut offsets = Vec::with_capacity(bitmaps.len());
+        for (commit_index, xor_offset, bitmap) in &bitmaps {
+            offsets.push(offset);
+            out.write_all(&commit_index.to_be_bytes())?;
+            out.write_all(&[*xor_offset, 0 /* flags */])?;
+            bitmap.write_to(&mut out)?;
+            offset += (super::init::ENTRY_HEADER_LEN + bitmap.size_in_bytes()) as u64;
+        }
+
+        if lookup_table {
+            let mut rows: Vec<_> = (0..bitmaps.len()).collect();
+            rows.sort_by_key(|bitmap_index| bitmaps[*bitmap_index].0);
+            let mut row_by_bitmap_index = vec![0; bitmaps.len()];
+            for (row, bitmap_index) in rows.iter().enumerate() {
+                row_by_bitmap_index[*bitmap_index] = row as u32;
+            }
+            for bitmap_index in rows {
+                let (commit_index, xor_offset, _) = &bitmaps[bitmap_index];
+                let xor_row = match *xor_offset as usize {
+                    0 => NO_XOR_ROW,
+                    xor_offset => row_by_bitmap_index[bitmap_index - xor_offset],
+                };
+                out.write_all(&commit_index.to_be_bytes())?;
+                out.write_all(&offsets[bitmap_index].to_be_bytes())?;
+                out.write_all(&xor_row.to_be_bytes())?;
+            }
+        }
+
+        if name_hash_cache {
+            for hash in name_hashes {
+                out.write_all(&hash.to_be_bytes())?;
+            }
+        }
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+        Ok(Outcome {
+            num_bitmaps: bitmaps.len(),
+            checksum,
+        })
+    }
+}
+
+fn kind_index(kind: gix_object::Kind) -> usize {
+    match kind {
+        gix_object::Kind::Commit => 0,
+        gix_object::Kind::Tree => 1,
+        gix_object::Kind::Blob => 2,
+        gix_object::Kind::Tag => 3,
+    }
+}
+
+/// Return the index of the commit `id` points to, peeling tags along the way, or `None` if it's not a commit in the pack.
+fn peeled_commit_index(
+    index: &crate::index::File,
+    objects: &dyn gix_object::FindObjectOrHeader,
+    mut id: ObjectId,
+) -> Option<u32> {
+    let mut buf = Vec::new();
+    loop {
+        let object = objects.try_find(&id, &mut buf).ok()??;
+        match object.kind {
+            gix_object::Kind::Tag => id = gix_object::TagRefIter::from_bytes(object.data).target_id().ok()?,
+            gix_object::Kind::Commit => return index.lookup(id),
+            gix_object::Kind::Tree | gix_object::Kind::Blob => return None,
+        }
+    }
+}
+
+/// Select the commits to store bitmaps for from `commits`, and return their indices into `commits`,
+/// which are sorted by commit time in the process.
+///
+/// Like git, select all commits if there are only a few, otherwise select commits with increasing distance from
+/// the most recent one, preferring `tips` and merge commits within each region.
+fn select_commits(commits: &mut [Commit], tips: &[u32]) -> Vec<usize> {
+    const MIN_COMMITS: usize = 100;
+    commits.sort_by(|a, b| b.time.cmp(&a.time).then(a.index.cmp(&b.index)));
+    if commits.len() < MIN_COMMITS {
+        return (0..commits.len()).collect();
+    }
+
+    let mut selected = Vec::new();
+    let mut index = 0;
+    loop {
+        let next = next_commit_distance(index);
+        if index + next >= commits.len() {
+            break;
+        }
+        let chosen = if next == 0 {
+            index
+        } else {
+            let mut chosen = index + next;
+            for (candidate, commit) in commits.iter().enumerate().skip(index).take(next + 1) {
+                if tips.contains(&commit.index) {
+                    chosen = candidate;
+                    break;
+                }
+                if commit.parents.len() > 1 {
+                    chosen = candidate;
+                }
+            }
+            chosen
+        };
+        selected.push(chosen);
+        index += next + 1;
+    }
+    selected
+}
+
+/// Return how many commits to skip after the commit at `index` in the list of commits sorted by time, to obtain a dense
+/// selection of recent commits, with more and more space between older ones.
+fn next_commit_distance(index: usize) -> usize {
+    const MIN_COMMITS: usize = 100;
+    const MAX_COMMITS: usize = 5000;
+    const MUST_REGION: usize = 100;
+    const MIN_REGION: usize = 20000;
+
+    if index <= MUST_REGION {
+        return 0;
+    }
+    if index <= MIN_REGION {
+        return (index - MUST_REGION).min(MIN_COMMITS);
+    }
+    (index - MIN_REGION).clamp(MIN_COMMITS, MAX_COMMITS)
+}
+
+/// Return a function to obtain the generation of each commit by index, which is larger than the one of all of its parents.
+fn generations<'a>(index: &crate::index::File, commits: &'a [Commit]) -> impl Fn(u32) -> u32 + 'a {
+    let commit_by_index: std::collections::HashMap<u32, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(commit_index, commit)| (commit.index, commit_index))
+        .collect();
+    let parents: Vec<Vec<usize>> = commits
+        .iter()
+        .map(|commit| {
+            commit
+                .parents
+                .iter()
+                .filter_map(|id| index.lookup(id))
+                .filter_map(|index| commit_by_index.get(&index).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut generation = vec![0u32; commits.len()];
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        if generation[start] != 0 {
+            continue;
+        }
+        stack.push(start);
+        while let Some(&commit_index) = stack.last() {
+            let mut pending = false;
+            for &parent in &parents[commit_index] {
+                if generation[parent] == 0 {
+                    stack.push(parent);
+                    pending = true;
+                }
+            }
+            if !pending {
+                stack.pop();
+                generation[commit_index] = 1 + parents[commit_index]
+                    .iter()
+                    .map(|parent| generation[*parent])
+                    .max()
+                    .unwrap_or(0);
+            }
+        }
+    }
+    let generation_by_index: std::collections::HashMap<u32, u32> = commits
+        .iter()
+        .zip(generation)
+        .map(|(commit, generation)| (commit.index, generation))
+        .collect();
+    move |index| generation_by_index.get(&index).copied().unwrap_or(0)
+}
+
+/// Hash `path` the same way git does, to make paths with the same ending produce similar values.
+fn name_hash(path: &BStr) -> u32 {
+    path.iter()
+        .filter(|byte| !byte.is_ascii_whitespace() && **byte != 0x0b)
+        .fold(0u32, |hash, byte| (hash >> 2).wrapping_add(u32::from(*byte) << 24))
+}
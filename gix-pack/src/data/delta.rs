@@ -1,3 +1,11 @@
+//! Decode and encode the delta instructions used by delta objects in packs.
+//!
+//! Deltas consist of a header with the size of the base and the result object, followed by instructions
+//! to either copy a range of the base object or to insert data stored in the delta itself.
+
+mod encode;
+pub use encode::{encode, Index};
+
 /// Given the decompressed pack delta `d`, decode a size in bytes (either the base object size or the result object size)
 /// Equivalent to [this canonical git function](https://github.com/git/git/blob/311531c9de557d25ac087c1637818bd2aad6eb3a/delta.h#L89)
 pub fn decode_header_size(d: &[u8]) -> (u64, usize) {
@@ -15,6 +23,12 @@ pub fn decode_header_size(d: &[u8]) -> (u64, usize) {
     (size, consumed)
 }
 
+/// Apply the delta instructions in `data`, without their header, to `base` and write the result into `target`,
+/// which must have the exact size of the result.
+///
+/// # Panics
+///
+/// If the instructions are malformed or don't match the sizes of `base` and `target`.
 pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     let mut i = 0;
     while let Some(cmd) = data.get(i) {
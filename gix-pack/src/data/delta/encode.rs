@@ -0,0 +1,211 @@
+/// The amount of bytes in a block that is indexed and matched, similar to `RABIN_WINDOW` in git.
+const BLOCK_LEN: usize = 16;
+/// The maximum amount of base offsets to remember per block hash, similar to `HASH_LIMIT` in git.
+const MAX_OFFSETS_PER_HASH: usize = 64;
+/// The largest amount of bytes a single copy instruction can copy, which is encoded as size 0.
+const MAX_COPY_LEN: usize = 0x10000;
+/// The largest amount of bytes a single insert instruction can hold.
+const MAX_INSERT_LEN: usize = 0x7f;
+/// The multiplier of the rolling hash.
+const PRIME: u32 = 0x0100_0193;
+/// The factor of the first byte in a block's hash, i.e. `PRIME` to the power of `BLOCK_LEN - 1`.
+const OUTGOING_FACTOR: u32 = {
+    let mut factor = 1u32;
+    let mut exponent = 1;
+    while exponent < BLOCK_LEN {
+        factor = factor.wrapping_mul(PRIME);
+        exponent += 1;
+    }
+    factor
+};
+
+/// An index over the blocks of a delta base object which accelerates finding the portions of target objects that can be copied
+/// from it.
+///
+/// Build it once per base object with [`Index::new()`] to [`encode`](Index::encode()) any amount of target objects against it.
+#[derive(Debug, Clone)]
+pub struct Index {
+    /// Pairs of `(block_hash, base_offset)`, sorted by hash.
+    blocks: Vec<(u32, u32)>,
+    /// The length of the base object we index, to detect misuse.
+    base_len: usize,
+}
+
+impl Index {
+    /// Create a new instance by indexing all non-overlapping blocks of `base`.
+    pub fn new(base: &[u8]) -> Self {
+        let indexable_len = base.len().min(u32::MAX as usize);
+        let mut blocks: Vec<_> = (0..indexable_len / BLOCK_LEN)
+            .map(|block| {
+                let offset = block * BLOCK_LEN;
+                (hash(&base[offset..][..BLOCK_LEN]), offset as u32)
+            })
+            .collect();
+        blocks.sort_unstable();
+        let mut last_hash = None;
+        let mut offsets_with_hash = 0;
+        blocks.retain(|(hash, _)| {
+            if last_hash != Some(*hash) {
+                last_hash = Some(*hash);
+                offsets_with_hash = 0;
+            }
+            offsets_with_hash += 1;
+            offsets_with_hash <= MAX_OFFSETS_PER_HASH
+        });
+        Index {
+            blocks,
+            base_len: base.len(),
+        }
+    }
+
+    /// Encode `target` as delta against `base`, which must be the data this index was created from, and write
+    /// the delta instructions including their header into `out`, which is cleared beforehand.
+    ///
+    /// If `max_size` is set, stop encoding and return `false` as soon as the delta would get larger than that.
+    /// Otherwise, `true` is returned, and `out` can be used to re-create `target` with [`apply()`](super::apply()).
+    ///
+    /// # Panics
+    ///
+    /// If `base` isn't the data this index was created from, at least as far as its length is concerned.
+    pub fn encode(&self, base: &[u8], target: &[u8], max_size: Option<usize>, out: &mut Vec<u8>) -> bool {
+        assert_eq!(base.len(), self.base_len, "BUG: delta base must be the one we indexed");
+        let max_size = max_size.unwrap_or(usize::MAX);
+        out.clear();
+        encode_header_size(base.len() as u64, out);
+        encode_header_size(target.len() as u64, out);
+
+        let mut insert_start = 0;
+        let mut pos = 0;
+        let mut rolling_hash = (target.len() >= BLOCK_LEN).then(|| hash(&target[..BLOCK_LEN]));
+        while let Some(block_hash) = rolling_hash.filter(|_| pos + BLOCK_LEN <= target.len()) {
+            match self.longest_match(block_hash, base, &target[pos..]) {
+                Some((mut base_offset, mut len)) => {
+                    let mut target_start = pos;
+                    while target_start > insert_start
+                        && base_offset > 0
+                        && base[base_offset - 1] == target[target_start - 1]
+                    {
+                        base_offset -= 1;
+                        target_start -= 1;
+                        len += 1;
+                    }
+                    encode_inserts(&target[insert_start..target_start], out);
+                    encode_copies(base_offset, len, out);
+                    if out.len() > max_size {
+                        return false;
+                    }
+                    pos = target_start + len;
+                    insert_start = pos;
+                    rolling_hash = (pos + BLOCK_LEN <= target.len()).then(|| hash(&target[pos..][..BLOCK_LEN]));
+                }
+                None => {
+                    if pos + BLOCK_LEN < target.len() {
+                        rolling_hash = Some(roll(block_hash, target[pos], target[pos + BLOCK_LEN]));
+                    }
+                    pos += 1;
+                    if pos - insert_start >= MAX_INSERT_LEN {
+                        encode_inserts(&target[insert_start..pos], out);
+                        insert_start = pos;
+                        if out.len() > max_size {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        encode_inserts(&target[insert_start..], out);
+        out.len() <= max_size
+    }
+
+    /// Return `(base_offset, len)` of the longest portion of `base` that equals the beginning of `target`,
+    /// as long as it's at least a block long.
+    fn longest_match(&self, block_hash: u32, base: &[u8], target: &[u8]) -> Option<(usize, usize)> {
+        let start = self.blocks.partition_point(|(hash, _)| *hash < block_hash);
+        let max_len = target.len();
+        let mut best: Option<(usize, usize)> = None;
+        for &(_, base_offset) in self.blocks[start..].iter().take_while(|(hash, _)| *hash == block_hash) {
+            let base_offset = base_offset as usize;
+            let max_len = max_len
+                .min(base.len() - base_offset)
+                .min(u32::MAX as usize - base_offset);
+            let len = base[base_offset..][..max_len]
+                .iter()
+                .zip(&target[..max_len])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if len >= BLOCK_LEN && best.map_or(true, |(_, best_len)| len > best_len) {
+                best = Some((base_offset, len));
+                if len == max_len {
+                    break;
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Encode `target` as delta against `base` into `out`, which is cleared beforehand.
+///
+/// Use an [`Index`] directly to encode multiple targets against the same base, or to limit the size of the delta.
+pub fn encode(base: &[u8], target: &[u8], out: &mut Vec<u8>) {
+    Index::new(base).encode(base, target, None, out);
+}
+
+fn hash(block: &[u8]) -> u32 {
+    block.iter().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(PRIME).wrapping_add(u32::from(*byte))
+    })
+}
+
+/// Remove `outgoing` from the beginning of the block `hash` was computed for, and add `incoming` to its end.
+fn roll(hash: u32, outgoing: u8, incoming: u8) -> u32 {
+    hash.wrapping_sub(u32::from(outgoing).wrapping_mul(OUTGOING_FACTOR))
+        .wrapping_mul(PRIME)
+        .wrapping_add(u32::from(incoming))
+}
+
+/// The inverse of [`decode_header_size()`](super::decode_header_size()).
+fn encode_header_size(mut size: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn encode_inserts(data: &[u8], out: &mut Vec<u8>) {
+    for chunk in data.chunks(MAX_INSERT_LEN) {
+        out.push(chunk.len() as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+fn encode_copies(mut base_offset: usize, mut len: usize, out: &mut Vec<u8>) {
+    while len != 0 {
+        let copy_len = len.min(MAX_COPY_LEN);
+        let cmd_index = out.len();
+        out.push(0x80);
+        let mut cmd = 0x80;
+        for (bit, byte) in (base_offset as u32).to_le_bytes().into_iter().enumerate() {
+            if byte != 0 {
+                cmd |= 1 << bit;
+                out.push(byte);
+            }
+        }
+        if copy_len != MAX_COPY_LEN {
+            for (bit, byte) in (copy_len as u32).to_le_bytes().into_iter().take(3).enumerate() {
+                if byte != 0 {
+                    cmd |= 1 << (bit + 4);
+                    out.push(byte);
+                }
+            }
+        }
+        out[cmd_index] = cmd;
+        base_offset += copy_len;
+        len -= copy_len;
+    }
+}
@@ -131,4 +131,6 @@ impl File {
     }
 }
 
-pub(crate) mod delta;
+///
+#[allow(clippy::empty_docs)]
+pub mod delta;
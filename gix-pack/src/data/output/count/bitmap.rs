@@ -0,0 +1,41 @@
+use gix_hash::ObjectId;
+
+use crate::data::output::{
+    count::{objects::Outcome, PackLocation},
+    Count,
+};
+
+/// Count all objects reachable from `wants` which aren't reachable from `haves`, using the reachability `bitmap` to avoid
+/// traversing commits and trees that it has bitmaps for, while `objects` is used to read all other commits and trees.
+///
+/// The returned counts are sorted by their position in the pack, and their pack location isn't looked up yet.
+/// `haves` that aren't known to `bitmap` are ignored, which may cause more objects to be counted than needed.
+///
+/// Return `None` if any of the objects reachable from `wants` isn't known to `bitmap`, so counting has to be done
+/// with [`objects()`](super::objects()) instead.
+pub fn objects_with_bitmap(
+    bitmap: &crate::bitmap::Index,
+    wants: impl IntoIterator<Item = ObjectId>,
+    haves: impl IntoIterator<Item = ObjectId>,
+    objects: &dyn gix_object::Find,
+) -> Result<Option<(Vec<Count>, Outcome)>, crate::bitmap::reachable::Error> {
+    let wants: Vec<_> = wants.into_iter().collect();
+    let Some(to_send) = bitmap.objects_to_send(wants.iter().copied(), haves, objects)? else {
+        return Ok(None);
+    };
+    let counts: Vec<_> = to_send
+        .iter_set_bits()
+        .map(|position| Count {
+            id: bitmap.id_at_position(position).to_owned(),
+            entry_pack_location: PackLocation::NotLookedUp,
+            no_delta: false,
+        })
+        .collect();
+    let outcome = Outcome {
+        input_objects: wants.len(),
+        expanded_objects: counts.len().saturating_sub(wants.len()),
+        decoded_objects: 0,
+        total_objects: counts.len(),
+    };
+    Ok(Some((counts, outcome)))
+}
@@ -35,10 +35,14 @@ impl Count {
         Count {
             id: oid.into(),
             entry_pack_location: PackLocation::LookedUp(location),
+            no_delta: false,
         }
     }
 }
 
+mod bitmap;
+pub use bitmap::objects_with_bitmap;
+
 #[path = "objects/mod.rs"]
 mod objects_impl;
 pub use objects_impl::{objects, objects_unthreaded};
@@ -10,7 +10,7 @@ pub(crate) mod function {
         },
     };
 
-    use super::{reduce, util, Error, Mode, Options, Outcome, ProgressId};
+    use super::{delta, reduce, util, Error, Mode, Options, Outcome, ProgressId};
     use crate::data::output;
 
     /// Given a known list of object `counts`, calculate entries ready to be put into a data pack.
@@ -98,7 +98,7 @@ pub(crate) mod function {
             progress.lock().show_throughput(start);
         }
         let counts_range_by_pack_id = match mode {
-            Mode::PackCopyAndBaseObjects => {
+            Mode::PackCopyAndBaseObjects | Mode::DeltaCompression { .. } => {
                 let mut progress = progress.add_child_with_id("sorting".into(), ProgressId::SortEntries.into());
                 progress.init(Some(counts.len()), gix_features::progress::count("counts"));
                 let start = std::time::Instant::now();
@@ -135,6 +135,21 @@ pub(crate) mod function {
             }
         };
 
+        let plan = match mode {
+            Mode::PackCopyAndBaseObjects => None,
+            Mode::DeltaCompression { window, depth } => Some(Arc::new(delta::plan(
+                &counts,
+                &counts_range_by_pack_id,
+                &db,
+                window,
+                depth,
+                allow_thin_pack,
+                thread_limit,
+                chunk_size,
+                &mut *progress,
+            ))),
+        };
+
         let counts = Arc::new(counts);
         let progress = Arc::new(parking_lot::Mutex::new(progress));
         let chunks = util::ChunkRanges::new(chunk_size, counts.len());
@@ -155,15 +170,33 @@ pub(crate) mod function {
             },
             {
                 let counts = Arc::clone(&counts);
+                let plan = plan.clone();
                 move |(chunk_id, chunk_range): (SequenceId, std::ops::Range<usize>), (buf, progress)| {
                     let mut out = Vec::new();
-                    let chunk = &counts[chunk_range];
                     let mut stats = Outcome::default();
                     let mut pack_offsets_to_id = None;
-                    progress.init(Some(chunk.len()), gix_features::progress::count("objects"));
+                    progress.init(Some(chunk_range.len()), gix_features::progress::count("objects"));
 
-                    for count in chunk.iter() {
-                        out.push(match count
+                    for position in chunk_range {
+                        let index = plan.as_ref().map_or(position, |plan| plan.index_by_position[position]);
+                        let count = &counts[index];
+                        if let Some((plan, delta)) = plan
+                            .as_ref()
+                            .and_then(|plan| plan.deltas[index].as_ref().map(|delta| (plan, delta)))
+                        {
+                            stats.delta_compressed_objects += 1;
+                            out.push(output::Entry {
+                                id: count.id,
+                                kind: output::entry::Kind::DeltaRef {
+                                    object_index: plan.position_by_index[delta.base_index],
+                                },
+                                decompressed_size: delta.decompressed_size,
+                                compressed_data: delta.compressed_data.clone(),
+                            });
+                            progress.inc();
+                            continue;
+                        }
+                        let mut entry = match count
                             .entry_pack_location
                             .as_ref()
                             .and_then(|l| db.entry_by_location(l).map(|pe| (l, pe)))
@@ -233,7 +266,11 @@ pub(crate) mod function {
                                     Ok(output::Entry::invalid())
                                 }
                             },
-                        }?);
+                        }?;
+                        if let (Some(plan), output::entry::Kind::DeltaRef { object_index }) = (&plan, &mut entry.kind) {
+                            *object_index = plan.position_by_index[*object_index];
+                        }
+                        out.push(entry);
                         progress.inc();
                     }
                     Ok((chunk_id, out, stats))
@@ -244,6 +281,8 @@ pub(crate) mod function {
     }
 }
 
+mod delta;
+
 mod util {
     #[derive(Clone)]
     pub struct ChunkRanges {
@@ -335,6 +374,9 @@ mod types {
         pub objects_copied_from_pack: usize,
         /// The amount of objects that ref to their base as ref-delta, an indication for a thin back being created.
         pub ref_delta_objects: usize,
+        /// The amount of objects for which a delta was computed by searching for a similar object in the pack,
+        /// which only happens with [`Mode::DeltaCompression`].
+        pub delta_compressed_objects: usize,
     }
 
     impl Outcome {
@@ -345,12 +387,14 @@ mod types {
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             }: Self,
         ) {
             self.decoded_and_recompressed_objects += decoded_objects;
             self.missing_objects += missing_objects;
             self.objects_copied_from_pack += objects_copied_from_pack;
             self.ref_delta_objects += ref_delta_objects;
+            self.delta_compressed_objects += delta_compressed_objects;
         }
     }
 
@@ -363,6 +407,17 @@ mod types {
         /// from existing pack compression and spending the smallest possible time on compressing unpacked objects at
         /// the cost of bandwidth.
         PackCopyAndBaseObjects,
+        /// Like [`PackCopyAndBaseObjects`](Mode::PackCopyAndBaseObjects), but objects that would otherwise be written as base
+        /// objects are delta-compressed against similar objects of the same kind, similar to what `git pack-objects` does.
+        /// Objects whose [`no_delta`](crate::data::output::Count::no_delta) flag is set are never delta-compressed.
+        ///
+        /// This produces the smallest packs at the cost of decoding all objects that aren't copied as delta from existing packs.
+        DeltaCompression {
+            /// The amount of similar objects to try as delta base for each object, like `pack.window`.
+            window: usize,
+            /// The maximum length of delta chains to create, like `pack.depth`.
+            depth: usize,
+        },
     }
 
     /// Configuration options for the pack generation functions provided in [`iter_from_counts()`][crate::data::output::entry::iter_from_counts()].
@@ -418,6 +473,8 @@ mod types {
         ResolveCounts,
         /// Layout pack entries for placement into a pack (by pack-id and by offset).
         SortEntries,
+        /// The amount of objects that were checked for a suitable delta base.
+        DeltaCompression,
     }
 
     impl From<ProgressId> for gix_features::progress::Id {
@@ -425,6 +482,7 @@ mod types {
             match v {
                 ProgressId::ResolveCounts => *b"ECRC",
                 ProgressId::SortEntries => *b"ECSE",
+                ProgressId::DeltaCompression => *b"ECDC",
             }
         }
     }
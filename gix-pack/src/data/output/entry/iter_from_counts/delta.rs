@@ -0,0 +1,390 @@
+use std::{
+    collections::VecDeque,
+    io::Write,
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use gix_features::{
+    parallel,
+    progress::{prodash::DynNestedProgress, Count, Progress},
+};
+
+use super::{util, ProgressId};
+use crate::{data, data::output};
+
+/// A delta found by the delta search, to be written instead of the object it was computed for.
+pub struct Delta {
+    /// The index into `counts` of the object serving as delta base.
+    pub base_index: usize,
+    /// The size of the delta instructions.
+    pub decompressed_size: usize,
+    /// The deflated delta instructions.
+    pub compressed_data: Vec<u8>,
+}
+
+/// Describes which objects are written as computed delta, and in which order objects are written.
+pub struct Plan {
+    /// The index into `counts` of each object, in the order they are written to the pack.
+    pub index_by_position: Vec<usize>,
+    /// The position in the pack of each object, by index into `counts`.
+    pub position_by_index: Vec<usize>,
+    /// The computed delta of each object by index into `counts`, if one was found.
+    pub deltas: Vec<Option<Delta>>,
+}
+
+/// How an object is going to be written when delta compression is enabled.
+#[derive(Clone, Copy)]
+enum Class {
+    /// The object is copied as delta from an existing pack, with its base being in the pack at the given index into `counts`,
+    /// or outside of the pack if `None`.
+    ReusedDelta { base_index: Option<usize> },
+    /// The object is a candidate for the delta search.
+    Candidate { kind: gix_object::Kind, size: usize },
+    /// The object isn't delta-compressed, as it's missing or shouldn't be.
+    Base,
+}
+
+/// An object in the sliding window of potential delta bases.
+struct WindowEntry {
+    index: usize,
+    kind: gix_object::Kind,
+    data: Vec<u8>,
+    delta_index: Option<data::delta::Index>,
+    depth: usize,
+}
+
+/// Search deltas for all `counts` (sorted by pack location) within a sliding `window` of similar objects, and keep delta chains
+/// no longer than `depth`, to return a plan on how to write them so bases are always written before their deltas.
+///
+/// Existing deltas that can be copied from the pack their object is stored in are reused, and objects that
+/// cannot be found are left to be handled when writing entries.
+#[allow(clippy::too_many_arguments)]
+pub fn plan<Find>(
+    counts: &[output::Count],
+    counts_range_by_pack_id: &[(u32, Range<usize>)],
+    db: &Find,
+    window: usize,
+    depth: usize,
+    allow_thin_pack: bool,
+    thread_limit: Option<usize>,
+    chunk_size: usize,
+    progress: &mut dyn DynNestedProgress,
+) -> Plan
+where
+    Find: crate::Find + Send + Clone,
+{
+    let classes = classify(
+        counts,
+        counts_range_by_pack_id,
+        db,
+        allow_thin_pack,
+        thread_limit,
+        chunk_size,
+    );
+
+    let mut candidates: Vec<_> = classes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, class)| match class {
+            Class::Candidate { kind, size } => Some((*kind, *size, index)),
+            _ => None,
+        })
+        .collect();
+    candidates.sort_by(|(lhs_kind, lhs_size, lhs_index), (rhs_kind, rhs_size, rhs_index)| {
+        lhs_kind
+            .cmp(rhs_kind)
+            .then(rhs_size.cmp(lhs_size))
+            .then(lhs_index.cmp(rhs_index))
+    });
+
+    let mut progress = progress.add_child_with_id("delta compression".into(), ProgressId::DeltaCompression.into());
+    progress.init(Some(candidates.len()), gix_features::progress::count("objects"));
+    let start = std::time::Instant::now();
+    let num_threads = parallel::num_threads(thread_limit);
+    let segment_size = (candidates.len() / num_threads).max(window * 4).max(1);
+    let found = if window == 0 || depth == 0 {
+        Vec::new()
+    } else {
+        let counter = progress.counter();
+        parallel::in_parallel_if(
+            || candidates.len() > segment_size,
+            util::ChunkRanges::new(segment_size, candidates.len()),
+            thread_limit,
+            |_n| (Vec::<u8>::new(), Vec::<u8>::new()),
+            {
+                let candidates = &candidates;
+                let db = db.clone();
+                move |segment, (buf, delta_buf)| {
+                    let found = search_segment(
+                        counts,
+                        &candidates[segment],
+                        &db,
+                        window,
+                        depth,
+                        buf,
+                        delta_buf,
+                        &counter,
+                    );
+                    Ok::<_, ()>(found)
+                }
+            },
+            Collect::default(),
+        )
+        .expect("infallible - objects that can't be read are not delta-compressed")
+    };
+    progress.set(candidates.len());
+    progress.show_throughput(start);
+
+    let mut deltas: Vec<Option<Delta>> = std::iter::repeat_with(|| None).take(counts.len()).collect();
+    for (index, delta) in found {
+        deltas[index] = Some(delta);
+    }
+
+    let base_of = |index: usize| match classes[index] {
+        Class::ReusedDelta { base_index } => base_index,
+        Class::Candidate { .. } => deltas[index].as_ref().map(|delta| delta.base_index),
+        Class::Base => None,
+    };
+    let mut position_by_index = vec![usize::MAX; counts.len()];
+    let mut index_by_position = Vec::with_capacity(counts.len());
+    let mut chain = Vec::new();
+    for index in 0..counts.len() {
+        let mut current = Some(index);
+        while let Some(index) = current.filter(|index| position_by_index[*index] == usize::MAX) {
+            chain.push(index);
+            current = base_of(index);
+        }
+        while let Some(index) = chain.pop() {
+            position_by_index[index] = index_by_position.len();
+            index_by_position.push(index);
+        }
+    }
+
+    Plan {
+        index_by_position,
+        position_by_index,
+        deltas,
+    }
+}
+
+/// Learn how each of the `counts` would be written.
+///
+/// Note that this has to match what [`output::Entry::from_pack_entry()`] would do.
+fn classify<Find>(
+    counts: &[output::Count],
+    counts_range_by_pack_id: &[(u32, Range<usize>)],
+    db: &Find,
+    allow_thin_pack: bool,
+    thread_limit: Option<usize>,
+    chunk_size: usize,
+) -> Vec<Class>
+where
+    Find: crate::Find + Send + Clone,
+{
+    let mut classes = vec![Class::Base; counts.len()];
+    let classified = parallel::in_parallel_if(
+        || counts.len() > 4_000,
+        util::ChunkRanges::new(chunk_size, counts.len()),
+        thread_limit,
+        |_n| Vec::<u8>::new(),
+        {
+            let db = db.clone();
+            move |range: Range<usize>, buf| {
+                Ok::<_, ()>(
+                    range
+                        .map(|index| {
+                            let class = classify_one(counts, index, counts_range_by_pack_id, &db, allow_thin_pack, buf);
+                            (index, class)
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }
+        },
+        Collect::default(),
+    )
+    .expect("infallible - objects that can't be read are not delta-compressed");
+    for (index, class) in classified {
+        classes[index] = class;
+    }
+    classes
+}
+
+fn classify_one(
+    counts: &[output::Count],
+    index: usize,
+    counts_range_by_pack_id: &[(u32, Range<usize>)],
+    db: &dyn crate::Find,
+    allow_thin_pack: bool,
+    buf: &mut Vec<u8>,
+) -> Class {
+    let count = &counts[index];
+    if let Some(location) = count.entry_pack_location.as_ref() {
+        let header = db
+            .entry_by_location(location)
+            .filter(|entry| entry.version == data::Version::V2)
+            .and_then(|entry| data::Entry::from_bytes(&entry.data, 0, count.id.as_slice().len()).ok());
+        if let Some(entry) = header {
+            use data::entry::Header::*;
+            match entry.header {
+                Commit | Tree | Blob | Tag => {
+                    return if count.no_delta {
+                        Class::Base
+                    } else {
+                        Class::Candidate {
+                            kind: entry.header.as_kind().expect("base object"),
+                            size: entry.decompressed_size as usize,
+                        }
+                    }
+                }
+                OfsDelta { base_distance } => {
+                    let pack_range = &counts_range_by_pack_id[counts_range_by_pack_id
+                        .binary_search_by_key(&location.pack_id, |e| e.0)
+                        .expect("pack-id always present")]
+                    .1;
+                    let base_offset = location.pack_offset - base_distance;
+                    match counts[pack_range.clone()].binary_search_by(|e| {
+                        e.entry_pack_location
+                            .as_ref()
+                            .expect("packed")
+                            .pack_offset
+                            .cmp(&base_offset)
+                    }) {
+                        Ok(base_index) => {
+                            return Class::ReusedDelta {
+                                base_index: Some(pack_range.start + base_index),
+                            }
+                        }
+                        Err(_) if allow_thin_pack => return Class::ReusedDelta { base_index: None },
+                        Err(_) => {}
+                    }
+                }
+                RefDelta { .. } => {}
+            }
+        }
+    }
+    if count.no_delta {
+        return Class::Base;
+    }
+    match db.try_find(&count.id, buf) {
+        Ok(Some((obj, _location))) => Class::Candidate {
+            kind: obj.kind,
+            size: obj.data.len(),
+        },
+        Ok(None) | Err(_) => Class::Base,
+    }
+}
+
+/// Find deltas for the given `candidates` of `(kind, size, index)`, sorted so that similar objects are close to each other.
+#[allow(clippy::too_many_arguments)]
+fn search_segment<Find>(
+    counts: &[output::Count],
+    candidates: &[(gix_object::Kind, usize, usize)],
+    db: &Find,
+    window_size: usize,
+    max_depth: usize,
+    buf: &mut Vec<u8>,
+    delta_buf: &mut Vec<u8>,
+    counter: &AtomicUsize,
+) -> Vec<(usize, Delta)>
+where
+    Find: crate::Find,
+{
+    let mut out = Vec::new();
+    let mut window = VecDeque::<WindowEntry>::with_capacity(window_size + 1);
+    let mut best_delta = Vec::new();
+    for &(kind, size, index) in candidates {
+        counter.fetch_add(1, Ordering::Relaxed);
+        let count = &counts[index];
+        let data = match db.try_find(&count.id, buf) {
+            Ok(Some((obj, _location))) => obj.data.to_vec(),
+            Ok(None) | Err(_) => continue,
+        };
+
+        let hash_len = count.id.kind().len_in_bytes();
+        let mut best: Option<usize> = None;
+        for (window_pos, base) in window.iter_mut().enumerate().rev() {
+            if base.kind != kind || base.depth >= max_depth {
+                continue;
+            }
+            // Like git, allow smaller deltas for bases that are already deep in a delta chain, and give up if the size difference
+            // alone would make the delta too large.
+            let mut max_size = (size / 2).saturating_sub(hash_len) * (max_depth - base.depth) / max_depth;
+            if best.is_some() {
+                max_size = max_size.min(best_delta.len().saturating_sub(1));
+            }
+            if max_size == 0 || size < base.data.len() / 32 || size.saturating_sub(base.data.len()) >= max_size {
+                continue;
+            }
+            let delta_index = base
+                .delta_index
+                .get_or_insert_with(|| data::delta::Index::new(&base.data));
+            if delta_index.encode(&base.data, &data, Some(max_size), delta_buf) {
+                std::mem::swap(&mut best_delta, delta_buf);
+                best = Some(window_pos);
+            }
+        }
+
+        let depth = match best {
+            Some(window_pos) => {
+                let base = &window[window_pos];
+                let mut compressed = gix_features::zlib::stream::deflate::Write::new(Vec::new());
+                if compressed
+                    .write_all(&best_delta)
+                    .and_then(|_| compressed.flush())
+                    .is_err()
+                {
+                    continue;
+                }
+                out.push((
+                    index,
+                    Delta {
+                        base_index: base.index,
+                        decompressed_size: best_delta.len(),
+                        compressed_data: compressed.into_inner(),
+                    },
+                ));
+                base.depth + 1
+            }
+            None => 0,
+        };
+        window.push_back(WindowEntry {
+            index,
+            kind,
+            data,
+            delta_index: None,
+            depth,
+        });
+        if window.len() > window_size {
+            window.pop_front();
+        }
+    }
+    out
+}
+
+/// Collect all items produced in parallel, in any order.
+struct Collect<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Collect<T> {
+    fn default() -> Self {
+        Collect { items: Vec::new() }
+    }
+}
+
+impl<T> parallel::Reduce for Collect<T> {
+    type Input = Result<Vec<T>, ()>;
+    type FeedProduce = ();
+    type Output = Vec<T>;
+    type Error = ();
+
+    fn feed(&mut self, item: Self::Input) -> Result<Self::FeedProduce, Self::Error> {
+        self.items.extend(item?);
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.items)
+    }
+}
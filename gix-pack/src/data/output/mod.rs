@@ -15,6 +15,11 @@ pub struct Count {
     pub id: ObjectId,
     /// A way to locate a pack entry in the object database, only available if the object is in a pack.
     pub entry_pack_location: count::PackLocation,
+    /// If `true`, the object is never delta-compressed against other objects, which is typically set for blobs
+    /// whose `delta` attribute is unset.
+    ///
+    /// Note that deltas copied from existing packs are still reused.
+    pub no_delta: bool,
 }
 
 /// An entry to be written to a file.
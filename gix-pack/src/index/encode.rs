@@ -87,7 +87,7 @@ mod function {
         // Write header
         let mut out = Count::new(std::io::BufWriter::with_capacity(
             8 * 4096,
-            hash::Write::new(out, kind.hash()),
+            hash::Write::new(out, pack_hash.kind()),
         ));
         out.write_all(V2_SIGNATURE)?;
         out.write_all(&(kind as u32).to_be_bytes())?;
@@ -139,7 +139,7 @@ mod function {
 
         let bytes_written_without_trailer = out.bytes;
         let out = out.inner.into_inner()?;
-        let index_hash: gix_hash::ObjectId = out.hash.digest().into();
+        let index_hash = out.hash.digest();
         out.inner.write_all(index_hash.as_slice())?;
         out.inner.flush()?;
 
@@ -86,7 +86,9 @@ pub enum Version {
 }
 
 impl Version {
-    /// The kind of hash to produce to be compatible to this kind of index
+    /// The kind of hash to produce to be compatible to this kind of index by default.
+    ///
+    /// Note that `V2` indices are also used for other kinds of hashes, which isn't recorded in the file itself.
     pub fn hash(&self) -> gix_hash::Kind {
         match self {
             Version::V1 | Version::V2 => gix_hash::Kind::Sha1,
@@ -129,6 +129,17 @@ impl File {
         (pack_index, pack_offset)
     }
 
+    /// Given the `position` of an object in pseudo-pack order, ranging from 0 to [File::num_objects()], return its
+    /// entry index, or `None` if the optional reverse index isn't present.
+    ///
+    /// The pseudo-pack order is the one used by multi-pack reachability bitmaps. It sorts objects by the pack they are in,
+    /// with the preferred pack first, and then by their offset in the pack.
+    pub fn index_at_pseudo_pack_position(&self, position: u32) -> Option<EntryIndex> {
+        debug_assert!(position < self.num_objects, "position out of bounds");
+        self.reverse_index_ofs
+            .map(|ofs| crate::read_u32(&self.data[ofs + position as usize * 4..][..4]))
+    }
+
     /// Return an iterator over all entries within this file.
     pub fn iter(&self) -> impl Iterator<Item = Entry> + '_ {
         (0..self.num_objects).map(move |idx| {
@@ -273,3 +273,19 @@ pub mod large_offsets {
         8 * large_offsets as u64
     }
 }
+
+/// Information about the reverse index chunk, which stores the objects in pseudo-pack order.
+pub mod reverse_index {
+    use std::ops::Range;
+
+    /// The id uniquely identifying the reverse index, which is a table of entry indices sorted by pseudo-pack order.
+    ///
+    /// The pseudo-pack order sorts objects by the pack they are in, with the preferred pack first, and then by their offset.
+    pub const ID: gix_chunk::Id = *b"RIDX";
+
+    /// Returns true if the `offset` range seems to match the size required for `num_objects`.
+    pub fn is_valid(offset: &Range<usize>, num_objects: u32) -> bool {
+        let entry_size = 4 /* entry index */;
+        offset.end - offset.start == num_objects as usize * entry_size
+    }
+}
@@ -34,6 +34,7 @@ pub struct File {
     lookup_ofs: usize,
     offsets_ofs: usize,
     large_offsets_ofs: Option<usize>,
+    reverse_index_ofs: Option<usize>,
 }
 
 ///
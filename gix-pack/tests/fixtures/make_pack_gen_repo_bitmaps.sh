@@ -0,0 +1,58 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function write_files() {
+  local base_dir=${1:?directory to write them into}
+  local num_files=${2:?amount of files to write}
+  local nonce=${3:?something to make files more unique}
+
+  mkdir -p "$base_dir"
+  for file_id in $(seq -w "$num_files"); do
+    seq "$file_id" > "$base_dir/$file_id"
+    echo "$nonce" >> "$base_dir/$file_id"
+  done
+}
+
+function commits() {
+  local from=${1:?first round}
+  local to=${2:?last round}
+  local dirs=(. a b c a/a a/b a/c a/a/a)
+
+  for round in $(seq "$from" "$to"); do
+    dir_index=$(( round % ${#dirs[@]} ))
+    num_files=$(( round % 7 + 1 ))
+    write_files "${dirs[$dir_index]}" $num_files "$round"
+    git add .
+    git commit -qm "$round $num_files"
+  done
+}
+
+git init -q pack
+(cd pack
+  git checkout -q -b main
+  commits 1 60
+  git checkout -q -b other
+  commits 61 80
+  git checkout -q main
+  commits 81 120
+  git merge -q --no-ff -s ours -m "merge other" other
+  commits 121 150
+  git tag -m "a tag object" annotated
+  git tag lightweight HEAD~10
+
+  git -c pack.writeBitmapHashCache=true -c pack.writeBitmapLookupTable=true repack -adbq
+)
+
+git init -q multi-pack
+(cd multi-pack
+  git checkout -q -b main
+  commits 1 40
+  git repack -adq
+  git checkout -q -b other
+  commits 41 60
+  git checkout -q main
+  commits 61 80
+  git tag -m "a tag object" annotated
+  git repack -dq
+  git -c pack.writeBitmapHashCache=true -c pack.writeBitmapLookupTable=true multi-pack-index write --bitmap
+)
@@ -0,0 +1,371 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use bstr::ByteSlice;
+use gix_bitmap::Bitset;
+use gix_hash::ObjectId;
+use gix_object::{FindExt, FindHeader, Kind};
+use gix_pack::bitmap;
+
+fn fixture(repo: &str) -> PathBuf {
+    crate::scripted_fixture_read_only("make_pack_gen_repo_bitmaps.sh")
+        .expect("test fixture exists")
+        .join(repo)
+        .join(".git/objects")
+}
+
+fn pack_index(objects_dir: &Path) -> gix_pack::index::File {
+    let index_path = std::fs::read_dir(objects_dir.join("pack"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension() == Some("idx".as_ref()))
+        .expect("single pack");
+    gix_pack::index::File::at(index_path, gix_hash::Kind::Sha1).unwrap()
+}
+
+fn odb(objects_dir: &Path) -> gix_odb::Handle {
+    gix_odb::at(objects_dir).unwrap()
+}
+
+/// Traverse the object graph without any help, to learn which objects are reachable from `tips`.
+fn reachable_ids(odb: &gix_odb::Handle, tips: impl IntoIterator<Item = ObjectId>) -> HashSet<ObjectId> {
+    let mut out = HashSet::new();
+    let mut buf = Vec::new();
+    let mut stack: Vec<_> = tips.into_iter().collect();
+    while let Some(id) = stack.pop() {
+        if !out.insert(id) {
+            continue;
+        }
+        let object = odb.find(&id, &mut buf).unwrap();
+        match object.kind {
+            Kind::Commit => {
+                let commit = gix_object::CommitRef::from_bytes(object.data).unwrap();
+                stack.push(commit.tree());
+                stack.extend(commit.parents());
+            }
+            Kind::Tree => {
+                let tree = gix_object::TreeRef::from_bytes(object.data, id.kind()).unwrap();
+                stack.extend(
+                    tree.entries
+                        .iter()
+                        .filter(|entry| !entry.mode.is_commit())
+                        .map(|entry| entry.oid.to_owned()),
+                );
+            }
+            Kind::Tag => stack.push(gix_object::TagRefIter::from_bytes(object.data).target_id().unwrap()),
+            Kind::Blob => {}
+        }
+    }
+    out
+}
+
+fn ids(index: &bitmap::Index, bitmap: &Bitset) -> HashSet<ObjectId> {
+    bitmap
+        .iter_set_bits()
+        .map(|position| index.id_at_position(position).to_owned())
+        .collect()
+}
+
+fn rev_parse(objects_dir: &Path, spec: &str) -> ObjectId {
+    let out = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(objects_dir.parent().unwrap())
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{spec} must exist");
+    ObjectId::from_hex(out.stdout.trim_end()).unwrap()
+}
+
+/// Assert that all stored bitmaps match the objects that are actually reachable, and that all types are correct.
+fn assert_bitmaps_are_correct(index: &bitmap::Index, odb: &gix_odb::Handle) {
+    let file = index.file();
+    assert_ne!(file.entries().len(), 0, "there must be bitmaps to check");
+    for (entry_index, entry) in file.entries().iter().enumerate() {
+        let commit = index.objects().oid_at_index(entry.index).to_owned();
+        let bitmap = file.bitmap_at(entry_index).unwrap();
+        assert_eq!(
+            ids(index, &bitmap),
+            reachable_ids(odb, Some(commit)),
+            "bitmap of {commit} must match the objects reachable from it"
+        );
+        assert_eq!(file.lookup(entry.index), Some(entry_index));
+    }
+
+    for position in 0..index.num_objects() as usize {
+        let id = index.id_at_position(position);
+        let expected = odb.try_header(id).unwrap().expect("present").kind;
+        assert_eq!(index.kind_at_position(position), Some(expected), "{id}");
+        assert_eq!(index.position(id), Some(position));
+    }
+}
+
+mod read {
+    use gix_pack::bitmap;
+
+    use super::{assert_bitmaps_are_correct, fixture, ids, odb, pack_index, reachable_ids, rev_parse};
+
+    #[test]
+    fn pack_bitmap_with_lookup_table_and_name_hash_cache() -> crate::Result {
+        let objects_dir = fixture("pack");
+        let index = bitmap::Index::at_pack_index(pack_index(&objects_dir))?;
+        let file = index.file();
+        assert_eq!(file.version(), bitmap::Version::V1);
+        assert!(file.has_lookup_table());
+        assert!(file.has_name_hash_cache());
+        assert_eq!(file.num_objects(), 1124);
+        assert_eq!(
+            file.entries().len(),
+            105,
+            "git selected only some of the 152 commits due to its heuristics"
+        );
+        assert!(
+            file.entries().iter().any(|entry| entry.xor_base.is_some()),
+            "some bitmaps are xored with others to save space"
+        );
+        assert_bitmaps_are_correct(&index, &odb(&objects_dir));
+
+        let tree = rev_parse(&objects_dir, "main^{tree}");
+        assert_ne!(
+            index.name_hash_at_position(index.position(&tree).expect("present")),
+            None,
+            "the name-hash cache has one entry per object"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_pack_bitmap() -> crate::Result {
+        let objects_dir = fixture("multi-pack");
+        let multi_index = gix_pack::multi_index::File::at(objects_dir.join("pack/multi-pack-index"))?;
+        assert_eq!(multi_index.num_indices(), 2);
+        let index = bitmap::Index::at_multi_index(multi_index)?;
+        assert!(index.file().has_lookup_table());
+        assert!(index.file().has_name_hash_cache());
+        let odb = odb(&objects_dir);
+        assert_bitmaps_are_correct(&index, &odb);
+
+        let tip = rev_parse(&objects_dir, "annotated");
+        let reachable = index.reachable(Some(tip), &odb)?.expect("all objects are known");
+        assert_eq!(ids(&index, &reachable), reachable_ids(&odb, Some(tip)));
+        Ok(())
+    }
+}
+
+mod reachable {
+    use gix_hash::ObjectId;
+    use gix_pack::bitmap;
+
+    use super::{fixture, ids, odb, pack_index, reachable_ids, rev_parse};
+
+    #[test]
+    fn from_any_kind_of_tip_with_and_without_bitmaps() -> crate::Result {
+        let objects_dir = fixture("pack");
+        let index = bitmap::Index::at_pack_index(pack_index(&objects_dir))?;
+        let odb = odb(&objects_dir);
+        for spec in [
+            "main",
+            "other",
+            "annotated",
+            "lightweight",
+            "main~3",
+            "main~3^{tree}",
+            "main:a/1",
+        ] {
+            let tip = rev_parse(&objects_dir, spec);
+            let reachable = index.reachable(Some(tip), &odb)?.expect("all objects are known");
+            assert_eq!(ids(&index, &reachable), reachable_ids(&odb, Some(tip)), "{spec}");
+        }
+        let commits_without_bitmap = ["main~1", "main~2", "main~3"]
+            .iter()
+            .filter(|spec| index.bitmap(&rev_parse(&objects_dir, spec)).is_none())
+            .count();
+        assert_ne!(commits_without_bitmap, 0, "some commits needed to be traversed");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_objects_cannot_be_handled() -> crate::Result {
+        let objects_dir = fixture("pack");
+        let index = bitmap::Index::at_pack_index(pack_index(&objects_dir))?;
+        let unknown = ObjectId::from_hex(b"ffffffffffffffffffffffffffffffffffffffff")?;
+        assert_eq!(index.reachable(Some(unknown), &odb(&objects_dir))?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn objects_to_send() -> crate::Result {
+        let objects_dir = fixture("pack");
+        let index = bitmap::Index::at_pack_index(pack_index(&objects_dir))?;
+        let odb = odb(&objects_dir);
+        let unknown = ObjectId::from_hex(b"ffffffffffffffffffffffffffffffffffffffff")?;
+        for (wants, haves) in [
+            (&["main"][..], &["other"][..]),
+            (&["main", "other"], &["main~40"]),
+            (&["annotated"], &["main~1", "other~5"]),
+            (&["other"], &["main"]),
+        ] {
+            let wants: Vec<_> = wants.iter().map(|spec| rev_parse(&objects_dir, spec)).collect();
+            let haves: Vec<_> = haves.iter().map(|spec| rev_parse(&objects_dir, spec)).collect();
+            let to_send = index
+                .objects_to_send(wants.iter().copied(), haves.iter().copied().chain(Some(unknown)), &odb)?
+                .expect("all wanted objects are known");
+            let expected: std::collections::HashSet<_> = reachable_ids(&odb, wants.iter().copied())
+                .difference(&reachable_ids(&odb, haves.iter().copied()))
+                .copied()
+                .collect();
+            assert_eq!(ids(&index, &to_send), expected, "unknown haves are ignored");
+        }
+        Ok(())
+    }
+}
+
+mod write {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_features::progress;
+    use gix_pack::bitmap;
+
+    use super::{assert_bitmaps_are_correct, fixture, odb, pack_index, rev_parse};
+
+    #[test]
+    fn roundtrip_with_all_options() -> crate::Result {
+        let objects_dir = fixture("pack");
+        let odb = odb(&objects_dir);
+        let tips = [rev_parse(&objects_dir, "main"), rev_parse(&objects_dir, "annotated")];
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        for (name_hash_cache, lookup_table) in [(false, false), (true, false), (false, true), (true, true)] {
+            let index = pack_index(&objects_dir);
+            let mut out = Vec::new();
+            let outcome = bitmap::File::write_from_pack_index(
+                &index,
+                &tips,
+                &odb,
+                &mut out,
+                &mut progress::Discard,
+                &AtomicBool::default(),
+                bitmap::write::Options {
+                    name_hash_cache,
+                    lookup_table,
+                },
+            )?;
+            assert_eq!(
+                outcome.num_bitmaps, 105,
+                "git's heuristics select the same amount of commits in this case"
+            );
+            assert_eq!(outcome.checksum.as_slice(), &out[out.len() - 20..]);
+
+            let path = tmp.path().join("pack.bitmap");
+            std::fs::write(&path, &out)?;
+            let file = bitmap::File::at(&path, gix_hash::Kind::Sha1, index.num_objects())?;
+            assert_eq!(file.has_name_hash_cache(), name_hash_cache);
+            assert_eq!(file.has_lookup_table(), lookup_table);
+            assert_eq!(file.entries().len(), outcome.num_bitmaps);
+            let index = bitmap::Index::new(file, bitmap::Objects::Pack(index))?;
+            assert_bitmaps_are_correct(&index, &odb);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn git_can_use_written_bitmaps() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable_standalone("make_pack_gen_repo_bitmaps.sh")?;
+        let objects_dir = tmp.path().join("pack/.git/objects");
+        let index = pack_index(&objects_dir);
+        let bitmap_path = index.path().with_extension("bitmap");
+        let mut out = Vec::new();
+        bitmap::File::write_from_pack_index(
+            &index,
+            &[rev_parse(&objects_dir, "main"), rev_parse(&objects_dir, "other")],
+            &odb(&objects_dir),
+            &mut out,
+            &mut progress::Discard,
+            &AtomicBool::default(),
+            bitmap::write::Options {
+                name_hash_cache: true,
+                lookup_table: true,
+            },
+        )?;
+        std::fs::write(bitmap_path, out)?;
+
+        for tip in ["main", "other", "main~7"] {
+            let output = std::process::Command::new("git")
+                .args(["rev-list", "--test-bitmap", tip])
+                .current_dir(objects_dir.parent().unwrap())
+                .output()?;
+            assert!(
+                output.status.success(),
+                "git validates the bitmap of {tip}: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ewah_roundtrip() -> crate::Result {
+        let cases: [&[u64]; 6] = [
+            &[],
+            &[0, 0, 0],
+            &[u64::MAX, u64::MAX, 1, 0, 0, u64::MAX, 0],
+            &[0, 0, 5, 7, 0, 0, 0, 1 << 63],
+            &[1, 2, 3, 4, u64::MAX, 0, u64::MAX, u64::MAX],
+            &[u64::MAX; 5],
+        ];
+        for words in cases {
+            let bitset = gix_bitmap::Bitset::from_words(words.to_vec());
+            let mut out = Vec::new();
+            let ewah = bitset.to_ewah();
+            ewah.write_to(&mut out)?;
+            assert_eq!(out.len(), ewah.size_in_bytes());
+            let (decoded, rest) = gix_bitmap::ewah::decode(&out)?;
+            assert!(rest.is_empty());
+            assert_eq!(
+                gix_bitmap::Bitset::from(&decoded),
+                bitset,
+                "trailing words without set bits are not stored"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn count_objects_with_bitmap() -> crate::Result {
+    let objects_dir = fixture("pack");
+    let index = bitmap::Index::at_pack_index(pack_index(&objects_dir))?;
+    let odb = odb(&objects_dir);
+    let wants = [rev_parse(&objects_dir, "main")];
+    let haves = [rev_parse(&objects_dir, "main~20")];
+    let (counts, outcome) = gix_pack::data::output::count::objects_with_bitmap(&index, wants, haves, &odb)?
+        .expect("all objects are in the pack");
+    let expected: HashSet<_> = reachable_ids(&odb, wants)
+        .difference(&reachable_ids(&odb, haves))
+        .copied()
+        .collect();
+    assert_eq!(counts.iter().map(|count| count.id).collect::<HashSet<_>>(), expected);
+    assert_eq!(outcome.total_objects, expected.len());
+    assert_eq!(outcome.input_objects, 1);
+
+    let mut db = std::sync::Arc::new(gix_odb::Store::at_opts(
+        objects_dir,
+        &mut None.into_iter(),
+        gix_odb::store::init::Options::default(),
+    )?)
+    .to_cache_arc();
+    db.prevent_pack_unload();
+    let stream = gix_pack::data::output::entry::iter_from_counts(
+        counts,
+        db,
+        Box::new(gix_features::progress::Discard),
+        Default::default(),
+    );
+    let num_entries: usize = stream
+        .map(|chunk| chunk.map(|(_, entries)| entries.len()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .sum();
+    assert_eq!(num_entries, expected.len(), "unresolved pack locations are looked up");
+    Ok(())
+}
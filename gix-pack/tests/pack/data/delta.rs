@@ -0,0 +1,75 @@
+use gix_pack::data::delta;
+
+fn roundtrip(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut instructions = Vec::new();
+    delta::encode(base, target, &mut instructions);
+
+    let (base_size, consumed) = delta::decode_header_size(&instructions);
+    assert_eq!(base_size, base.len() as u64);
+    let (target_size, consumed_target) = delta::decode_header_size(&instructions[consumed..]);
+    assert_eq!(target_size, target.len() as u64);
+
+    let mut actual = vec![0; target.len()];
+    delta::apply(base, &mut actual, &instructions[consumed + consumed_target..]);
+    assert_eq!(actual, target, "the delta recreates the target");
+    instructions
+}
+
+fn lines(range: std::ops::Range<usize>) -> Vec<u8> {
+    range.map(|n| format!("line {n}\n")).collect::<String>().into_bytes()
+}
+
+#[test]
+fn identical_objects_are_a_single_copy() {
+    let base = lines(0..1000);
+    let instructions = roundtrip(&base, &base);
+    assert!(instructions.len() < 16, "{} bytes", instructions.len());
+}
+
+#[test]
+fn insertions_and_deletions() {
+    let base = lines(0..1000);
+    let mut target = lines(0..300);
+    target.extend_from_slice(b"something new in the middle\n");
+    target.extend(lines(400..1000));
+    let instructions = roundtrip(&base, &target);
+    assert!(instructions.len() < 64, "{} bytes", instructions.len());
+
+    roundtrip(&target, &base);
+}
+
+#[test]
+fn copies_larger_than_what_a_single_instruction_can_hold() {
+    let base = lines(0..20_000);
+    assert!(base.len() > 0x10000 * 2);
+    let mut target = base.clone();
+    target.extend_from_slice(b"appended");
+    roundtrip(&base, &target);
+}
+
+#[test]
+fn unrelated_and_empty_objects() {
+    roundtrip(b"", b"");
+    roundtrip(b"", &lines(0..10));
+    roundtrip(&lines(0..10), b"");
+    let unrelated = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+    let instructions = roundtrip(&lines(0..100), &unrelated);
+    assert!(
+        instructions.len() > unrelated.len(),
+        "insert instructions add some overhead"
+    );
+}
+
+#[test]
+fn max_size_aborts_encoding() {
+    let base = lines(0..1000);
+    let index = delta::Index::new(&base);
+    let mut out = Vec::new();
+    let unrelated = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+    assert!(!index.encode(&base, &unrelated, Some(500), &mut out));
+    assert!(index.encode(&base, &base, Some(500), &mut out));
+    assert!(
+        index.encode(&base, &lines(500..1000), Some(500), &mut out),
+        "the index can be reused"
+    );
+}
@@ -121,6 +121,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 16,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3"),
                 None,
@@ -154,6 +155,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 103,
                     ref_delta_objects: 74,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("25114bd8820b393c402cd53ad8ec7f6a84bb0633"),
                 Some(hex_to_id("29ab9797aff1ca826afb699680356695d19c5acb")),
@@ -187,6 +189,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 29,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("d83d42128e40957c5174920189a0390b5a70f446"),
                 None,
@@ -208,6 +211,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -229,6 +233,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -417,3 +422,89 @@ fn write_and_verify(
 
     Ok(())
 }
+
+#[test]
+fn delta_compression() -> crate::Result {
+    let db = db(DbKind::DeterministicGeneratedContent)?;
+    let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+    let count = |no_delta: bool| -> crate::Result<Vec<output::Count>> {
+        let (mut counts, _stats) = output::count::objects(
+            db.clone(),
+            Box::new(std::iter::once(Ok(head))),
+            &progress::Discard,
+            &AtomicBool::new(false),
+            count::objects::Options {
+                input_object_expansion: count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+                thread_limit: Some(1),
+                ..Default::default()
+            },
+        )?;
+        for count in &mut counts {
+            count.no_delta = no_delta;
+        }
+        Ok(counts)
+    };
+    let entries = |counts: Vec<output::Count>, mode: entry::iter_from_counts::Mode| -> crate::Result<_> {
+        let mut entries_iter = output::entry::iter_from_counts(
+            counts,
+            db.clone(),
+            Box::new(progress::Discard),
+            output::entry::iter_from_counts::Options {
+                mode,
+                ..Default::default()
+            },
+        );
+        let entries: Vec<_> = InOrderIter::from(entries_iter.by_ref())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok((entries, entries_iter.finalize()?))
+    };
+    let compressed_size = |entries: &[output::Entry]| entries.iter().map(|e| e.compressed_data.len()).sum::<usize>();
+
+    let (copied_entries, copied_stats) = entries(count(false)?, entry::iter_from_counts::Mode::PackCopyAndBaseObjects)?;
+    assert_eq!(copied_stats.decoded_and_recompressed_objects, 74);
+
+    let delta_mode = entry::iter_from_counts::Mode::DeltaCompression { window: 10, depth: 50 };
+    let (delta_entries, delta_stats) = entries(count(false)?, delta_mode)?;
+    assert_eq!(delta_entries.len(), copied_entries.len());
+    assert!(
+        delta_stats.delta_compressed_objects > 50,
+        "most objects that would have to be recompressed can be delta-compressed, got {}",
+        delta_stats.delta_compressed_objects
+    );
+    assert_eq!(
+        delta_stats.delta_compressed_objects
+            + delta_stats.decoded_and_recompressed_objects
+            + delta_stats.objects_copied_from_pack,
+        delta_entries.len()
+    );
+    assert!(
+        compressed_size(&delta_entries) < compressed_size(&copied_entries),
+        "delta compression makes packs smaller"
+    );
+    for (position, entry) in delta_entries.iter().enumerate() {
+        if let entry::Kind::DeltaRef { object_index } = entry.kind {
+            assert!(object_index < position, "bases are always written before their deltas");
+        }
+    }
+    write_and_verify(
+        db.clone(),
+        delta_entries,
+        hex_to_id("0000000000000000000000000000000000000000"),
+        None,
+    )?;
+
+    let (entries, stats) = entries(count(true)?, delta_mode)?;
+    assert_eq!(
+        stats.delta_compressed_objects, 0,
+        "objects that mustn't be deltified aren't"
+    );
+    assert_eq!(
+        stats, copied_stats,
+        "this is the same as not doing any delta compression"
+    );
+    write_and_verify(db, entries, hex_to_id("0000000000000000000000000000000000000000"), None)?;
+    Ok(())
+}
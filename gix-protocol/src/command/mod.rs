@@ -133,6 +133,7 @@ mod with_io {
                                 feature => server_capabilities.contains(feature),
                             })
                             .map(|s| (s, None))
+                            .chain(object_format(server_capabilities))
                             .collect()
                     }
                     gix_transport::Protocol::V2 => {
@@ -151,10 +152,14 @@ mod with_io {
                             .copied()
                             .filter(|feature| supported_features.iter().any(|supported| supported == feature))
                             .map(|s| (s, None))
+                            .chain(object_format(server_capabilities))
                             .collect()
                     }
                 },
-                Command::LsRefs => vec![],
+                Command::LsRefs => match version {
+                    gix_transport::Protocol::V0 | gix_transport::Protocol::V1 => vec![],
+                    gix_transport::Protocol::V2 => object_format(server_capabilities).into_iter().collect(),
+                },
             }
         }
         /// Panics if the given arguments and features don't match what's statically known. It's considered a bug in the delegate.
@@ -200,7 +205,7 @@ mod with_io {
                             continue;
                         }
                         match *feature {
-                            "agent" => {}
+                            "agent" | "object-format" => {}
                             _ => panic!("{}: V2 feature/capability {} is not supported", self.as_str(), feature),
                         }
                     }
@@ -208,6 +213,13 @@ mod with_io {
             }
         }
     }
+
+    /// Servers using a hash other than SHA1 only accept commands from clients that acknowledge it, so produce the
+    /// `object-format` feature to do so if needed.
+    fn object_format(server_capabilities: &Capabilities) -> Option<Feature> {
+        let format = server_capabilities.capability("object-format")?.value()?;
+        (format != "sha1").then(|| ("object-format", Some(format.to_string().into())))
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +113,20 @@ mod v2 {
                     &[]
                 );
             }
+
+            #[test]
+            fn object_format_is_acknowledged_unless_it_is_the_default() {
+                assert_eq!(
+                    Command::LsRefs
+                        .default_features(gix_transport::Protocol::V2, &capabilities("object-format", "sha256")),
+                    &[("object-format", Some("sha256".into()))]
+                );
+                assert_eq!(
+                    Command::LsRefs
+                        .default_features(gix_transport::Protocol::V2, &capabilities("object-format", "sha1")),
+                    &[]
+                );
+            }
         }
 
         mod validate {
@@ -1,7 +1,9 @@
-//! An abstraction over [fetching][fetch()] a pack from the server.
+//! An abstraction over [fetching][fetch()] a pack from the server, along with the building blocks to [push][push] to it.
 //!
 //! This implementation hides the transport layer, statefulness and the protocol version to the [fetch delegate][fetch::Delegate],
 //! the actual client implementation.
+//!
+//! The server side of fetches is provided by the `upload_pack` module, which answers requests of clients using protocol V2.
 //! ## Feature Flags
 #![cfg_attr(
     all(doc, feature = "document-features"),
@@ -44,9 +46,21 @@ pub use fetch_fn::{fetch, FetchConnection};
 mod remote_progress;
 pub use remote_progress::RemoteProgress;
 
+///
+#[allow(clippy::empty_docs)]
+pub mod push;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blocking-server")]
+pub mod upload_pack;
+
 #[cfg(all(feature = "blocking-client", feature = "async-client"))]
 compile_error!("Cannot set both 'blocking-client' and 'async-client' features as they are mutually exclusive");
 
+#[cfg(all(feature = "blocking-server", feature = "async-client"))]
+compile_error!("Cannot set both 'blocking-server' and 'async-client' features as they are mutually exclusive");
+
 ///
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 #[allow(clippy::empty_docs)]
@@ -0,0 +1,88 @@
+//! Types and utilities to drive the client side of the `receive-pack` service, which is used to push objects and update references.
+//!
+//! Note that pushes are only defined for protocol V0 and V1, and the server will respond to V2 requests with a V0 advertisement.
+use bstr::{BString, ByteSlice, ByteVec};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod response;
+pub use response::Report;
+
+/// All capabilities of `receive-pack` which may be requested by a client when sending its commands.
+pub const CAPABILITIES: &[&str] = &[
+    "report-status",
+    "report-status-v2",
+    "side-band-64k",
+    "atomic",
+    "push-options",
+    "quiet",
+    "ofs-delta",
+    "delete-refs",
+    "agent",
+    "object-format",
+];
+
+/// A single reference update to be requested from the server, transmitted as `<old-id> <new-id> <ref-name>`.
+///
+/// A null `old` id creates the reference, and a null `new` id deletes it.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// The id the reference is expected to have on the remote, or a null id if it is expected to not exist.
+    pub old_id: gix_hash::ObjectId,
+    /// The id the reference should point to after the update, or a null id if it should be deleted.
+    pub new_id: gix_hash::ObjectId,
+    /// The full name of the reference to update on the remote, like `refs/heads/main`.
+    pub ref_name: BString,
+}
+
+impl Command {
+    /// Return `true` if this command deletes its reference.
+    pub fn is_delete(&self) -> bool {
+        self.new_id.is_null()
+    }
+
+    /// Return `true` if this command creates its reference.
+    pub fn is_create(&self) -> bool {
+        self.old_id.is_null()
+    }
+
+    /// Produce the line to send to the server, without trailing newline, and with `features` appended after
+    /// a null byte if they are not empty.
+    ///
+    /// The first command of a push must carry all `features` the client wishes to use, subsequent ones must not.
+    pub fn to_line(&self, features: &[(&str, Option<&str>)]) -> BString {
+        let mut out = BString::from(format!("{} {} ", self.old_id, self.new_id));
+        out.push_str(&self.ref_name);
+        if !features.is_empty() {
+            out.push_byte(0);
+            for (idx, (name, value)) in features.iter().enumerate() {
+                if idx != 0 {
+                    out.push_byte(b' ');
+                }
+                out.push_str(name);
+                if let Some(value) = value {
+                    out.push_byte(b'=');
+                    out.push_str(value);
+                }
+            }
+        }
+        out
+    }
+
+    /// Parse a command from `line` as it is produced by [`to_line()`][Self::to_line()], ignoring any features and
+    /// a trailing newline.
+    pub fn from_line(line: &[u8]) -> Option<Self> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.find_byte(0).map_or(line, |pos| &line[..pos]);
+        let mut tokens = line.splitn(3, |b| *b == b' ');
+        let old_id = gix_hash::ObjectId::from_hex(tokens.next()?).ok()?;
+        let new_id = gix_hash::ObjectId::from_hex(tokens.next()?).ok()?;
+        let ref_name = tokens.next().filter(|name| !name.is_empty())?.into();
+        Some(Command {
+            old_id,
+            new_id,
+            ref_name,
+        })
+    }
+}
@@ -0,0 +1,187 @@
+use bstr::{BString, ByteSlice};
+use gix_transport::packetline;
+
+/// The error returned by [`Report::from_lines()`] and [`Report::from_packetlines()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The report did not start with an 'unpack' line: {line:?}")]
+    MissingUnpackStatus { line: Option<BString> },
+    #[error("Could not parse report line {line:?}")]
+    MalformedLine { line: BString },
+    #[error("Received option line {line:?} without preceding 'ok' line")]
+    UnexpectedOption { line: BString },
+    #[error("Could not decode packetline of the report")]
+    Packetline(#[from] packetline::decode::Error),
+    #[error("The report ended unexpectedly in the middle of a packetline")]
+    Incomplete,
+}
+
+/// The status of unpacking the pack we sent, as reported by the server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unpack {
+    /// The pack was received and unpacked successfully, or there was no pack to unpack.
+    Ok,
+    /// The pack could not be unpacked, with the `message` of the server indicating why.
+    Failed {
+        /// The error message as received from the server.
+        message: BString,
+    },
+}
+
+/// Additional information the server may provide for updated refs if the `report-status-v2` capability is used.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The name of the reference that was actually updated, if it differs from the one we requested, for instance
+    /// because a hook rewrote it.
+    pub ref_name: Option<BString>,
+    /// The id the reference had before the update, if it differs from what we assumed.
+    pub old_id: Option<gix_hash::ObjectId>,
+    /// The id the reference has after the update, if it differs from what we requested.
+    pub new_id: Option<gix_hash::ObjectId>,
+    /// If `true`, the update was not a fast-forward.
+    pub forced_update: bool,
+}
+
+/// The outcome of a single reference update as reported by the server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
+    /// The reference was updated.
+    Ok {
+        /// Additional information available with `report-status-v2`, one for each updated reference that is
+        /// affected by the command. Empty if there was no additional information.
+        options: Vec<Options>,
+    },
+    /// The reference update was rejected.
+    Rejected {
+        /// The reason for the rejection, like `non-fast-forward` or `atomic push failed`.
+        reason: BString,
+    },
+}
+
+/// The status of a single command sent to the server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandStatus {
+    /// The full name of the reference as we sent it in the command.
+    pub ref_name: BString,
+    /// The outcome of the update.
+    pub status: Status,
+}
+
+/// The parsed `report-status` or `report-status-v2` of a `receive-pack` server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// Whether or not the pack could be unpacked.
+    pub unpack: Unpack,
+    /// The status of each command in the order the server sent them.
+    pub commands: Vec<CommandStatus>,
+}
+
+impl Report {
+    /// Parse a report from `data` which is the concatenation of packet lines up to and including the terminating
+    /// flush packet, as it is received on the data channel if the `side-band-64k` capability is in use.
+    pub fn from_packetlines(mut data: &[u8]) -> Result<Self, Error> {
+        let mut lines = Vec::new();
+        while !data.is_empty() {
+            match packetline::decode::streaming(data)? {
+                packetline::decode::Stream::Complete { line, bytes_consumed } => {
+                    data = &data[bytes_consumed..];
+                    match line.as_slice() {
+                        Some(line) => lines.push(line),
+                        None => break,
+                    }
+                }
+                packetline::decode::Stream::Incomplete { .. } => return Err(Error::Incomplete),
+            }
+        }
+        Self::from_lines(lines)
+    }
+
+    /// Parse a report from `lines`, each of which is the data of a single packet line with an optional trailing newline.
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a [u8]>) -> Result<Self, Error> {
+        let mut lines = lines
+            .into_iter()
+            .map(|line| line.strip_suffix(b"\n").unwrap_or(line).as_bstr());
+        let unpack = match lines.next() {
+            Some(line) => match line.strip_prefix(b"unpack ") {
+                Some(b"ok") => Unpack::Ok,
+                Some(message) => Unpack::Failed {
+                    message: message.into(),
+                },
+                None => {
+                    return Err(Error::MissingUnpackStatus {
+                        line: Some(line.to_owned()),
+                    })
+                }
+            },
+            None => return Err(Error::MissingUnpackStatus { line: None }),
+        };
+
+        let mut commands = Vec::<CommandStatus>::new();
+        for line in lines {
+            if let Some(ref_name) = line.strip_prefix(b"ok ") {
+                commands.push(CommandStatus {
+                    ref_name: ref_name.into(),
+                    status: Status::Ok { options: Vec::new() },
+                });
+            } else if let Some(rest) = line.strip_prefix(b"ng ") {
+                let (ref_name, reason) = match rest.find_byte(b' ') {
+                    Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+                    None => (rest, &b""[..]),
+                };
+                commands.push(CommandStatus {
+                    ref_name: ref_name.into(),
+                    status: Status::Rejected { reason: reason.into() },
+                });
+            } else if let Some(option) = line.strip_prefix(b"option ") {
+                let options = match commands.last_mut() {
+                    Some(CommandStatus {
+                        status: Status::Ok { options },
+                        ..
+                    }) => options,
+                    _ => return Err(Error::UnexpectedOption { line: line.into() }),
+                };
+                parse_option(option, options).ok_or_else(|| Error::MalformedLine { line: line.into() })?;
+            } else {
+                return Err(Error::MalformedLine { line: line.into() });
+            }
+        }
+        Ok(Report { unpack, commands })
+    }
+
+    /// Return `true` if the pack was unpacked and all reference updates were accepted.
+    pub fn is_success(&self) -> bool {
+        self.unpack == Unpack::Ok && self.commands.iter().all(|c| matches!(c.status, Status::Ok { .. }))
+    }
+}
+
+/// Parse a single `option` and add it to `options`, where each `option refname` line starts a new set of options
+/// unless the current one is still empty.
+///
+/// Like `git`, unknown options are ignored as newer servers may send them.
+fn parse_option(option: &[u8], options: &mut Vec<Options>) -> Option<()> {
+    let (name, value) = match option.find_byte(b' ') {
+        Some(pos) => (&option[..pos], Some(&option[pos + 1..])),
+        None => (option, None),
+    };
+    if !matches!(name, b"refname" | b"old-oid" | b"new-oid" | b"forced-update") {
+        return Some(());
+    }
+    if options.is_empty() || (name == b"refname" && options.last() != Some(&Options::default())) {
+        options.push(Options::default());
+    }
+    let current = options.last_mut().expect("just pushed");
+    match (name, value) {
+        (b"refname", Some(name)) => current.ref_name = Some(name.into()),
+        (b"old-oid", Some(hex)) => current.old_id = Some(gix_hash::ObjectId::from_hex(hex).ok()?),
+        (b"new-oid", Some(hex)) => current.new_id = Some(gix_hash::ObjectId::from_hex(hex).ok()?),
+        (b"forced-update", None) => current.forced_update = true,
+        _ => return None,
+    }
+    Some(())
+}
@@ -0,0 +1,95 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use gix_transport::{Protocol, Service};
+
+/// The error returned by [`Request::read()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the initial request")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    DecodePacketline(#[from] gix_packetline::decode::Error),
+    #[error("The initial request '{line}' is malformed")]
+    Malformed { line: BString },
+}
+
+/// The initial request a client sends to a `git daemon`-style server after connecting via `git://`,
+/// like `git-upload-pack /project.git\0host=example.com\0\0version=2\0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Request {
+    /// The service the client wants to use.
+    pub service: Service,
+    /// The path to the repository, as sent by the client.
+    pub path: BString,
+    /// The host and port the client connected to, like `example.com:9418`, if it was sent.
+    pub host: Option<BString>,
+    /// Additional parameters, like `version=2`.
+    pub extra_parameters: Vec<BString>,
+}
+
+impl Request {
+    /// Parse the request from the data of the first packet `line` sent by the client.
+    pub fn from_bytes(line: &[u8]) -> Option<Self> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let mut fields = line.split(|b| *b == 0);
+        let (service, path) = fields.next()?.split_once_str(" ")?;
+        let service = match service {
+            b"git-upload-pack" => Service::UploadPack,
+            b"git-receive-pack" => Service::ReceivePack,
+            _ => return None,
+        };
+        let mut host = None;
+        let mut extra_parameters = Vec::new();
+        let mut in_extra_parameters = false;
+        for field in fields {
+            if in_extra_parameters {
+                if !field.is_empty() {
+                    extra_parameters.push(field.into());
+                }
+            } else if field.is_empty() {
+                in_extra_parameters = true;
+            } else {
+                host = Some(field.strip_prefix(b"host=")?.into());
+            }
+        }
+        Some(Request {
+            service,
+            path: path.into(),
+            host,
+            extra_parameters,
+        })
+    }
+
+    /// Read the first packet line from `input` and parse it as request.
+    pub fn read(mut input: impl io::Read) -> Result<Self, Error> {
+        let mut hex_len = [0; 4];
+        input.read_exact(&mut hex_len)?;
+        let len = match gix_packetline::decode::hex_prefix(&hex_len)? {
+            gix_packetline::decode::PacketLineOrWantedSize::Wanted(len) => len as usize,
+            gix_packetline::decode::PacketLineOrWantedSize::Line(_) => {
+                return Err(Error::Malformed {
+                    line: hex_len.as_bstr().into(),
+                })
+            }
+        };
+        let mut line = vec![0; len];
+        input.read_exact(&mut line)?;
+        Request::from_bytes(&line).ok_or_else(|| Error::Malformed { line: line.into() })
+    }
+
+    /// Return the protocol version requested by the client with the `version` parameter, or V0 if there is none.
+    pub fn protocol(&self) -> Protocol {
+        match self
+            .extra_parameters
+            .iter()
+            .find_map(|param| param.strip_prefix(b"version="))
+        {
+            Some(b"2") => Protocol::V2,
+            Some(b"1") => Protocol::V1,
+            _ => Protocol::V0,
+        }
+    }
+}
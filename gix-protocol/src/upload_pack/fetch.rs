@@ -0,0 +1,155 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::upload_pack::Error;
+
+/// The arguments of the `fetch` command.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arguments {
+    /// The objects the client wants.
+    pub wants: Vec<ObjectId>,
+    /// The full names of references whose objects the client wants, to be resolved by the server.
+    pub want_refs: Vec<BString>,
+    /// The objects the client has.
+    pub haves: Vec<ObjectId>,
+    /// If `true`, the client has no more `haves` to send and wants the pack right away.
+    pub done: bool,
+    /// If `true`, the pack may contain deltas against objects the client has but that aren't part of the pack.
+    pub thin_pack: bool,
+    /// If `true`, no progress messages should be sent.
+    pub no_progress: bool,
+    /// If `true`, annotated tags pointing to objects in the pack should be sent as well.
+    pub include_tag: bool,
+    /// If `true`, the pack may contain offset deltas.
+    pub ofs_delta: bool,
+    /// The commits which are shallow in the repository of the client.
+    pub shallow: Vec<ObjectId>,
+    /// The amount of commits to send for each want, starting at the want itself.
+    pub deepen: Option<u32>,
+    /// If `true`, `deepen` is relative to the shallow commits of the client.
+    pub deepen_relative: bool,
+    /// Commits older than this time, in seconds since the unix epoch, aren't sent.
+    pub deepen_since: Option<gix_date::SecondsSinceUnixEpoch>,
+    /// Commits reachable from these references or objects aren't sent.
+    pub deepen_not: Vec<BString>,
+    /// The objects to omit from the pack.
+    pub filter: Option<Filter>,
+    /// If `true`, the server must not decide to send the pack before the client sent `done`.
+    pub wait_for_done: bool,
+}
+
+impl Arguments {
+    /// Parse the arguments of a `fetch` request from `lines`.
+    pub fn from_lines(lines: &[BString]) -> Result<Self, Error> {
+        let mut out = Arguments::default();
+        for line in lines {
+            let invalid = || Error::InvalidArgument {
+                command: "fetch",
+                argument: line.clone(),
+            };
+            let (name, value) = match line.find_byte(b' ') {
+                Some(pos) => (&line[..pos], Some(&line[pos + 1..])),
+                None => (line.as_slice(), None),
+            };
+            let parse_id =
+                |value: Option<&[u8]>| value.and_then(|hex| ObjectId::from_hex(hex).ok()).ok_or_else(invalid);
+            match (name, value) {
+                (b"want", _) => out.wants.push(parse_id(value)?),
+                (b"want-ref", Some(name)) => out.want_refs.push(name.into()),
+                (b"have", _) => out.haves.push(parse_id(value)?),
+                (b"shallow", _) => out.shallow.push(parse_id(value)?),
+                (b"deepen", _) => out.deepen = Some(parse_number(value).ok_or_else(invalid)?),
+                (b"deepen-since", _) => out.deepen_since = Some(parse_number(value).ok_or_else(invalid)?),
+                (b"deepen-not", Some(name)) => out.deepen_not.push(name.into()),
+                (b"filter", Some(spec)) => out.filter = Some(Filter::from_spec(spec.as_bstr())?),
+                (b"done", None) => out.done = true,
+                (b"thin-pack", None) => out.thin_pack = true,
+                (b"no-progress", None) => out.no_progress = true,
+                (b"include-tag", None) => out.include_tag = true,
+                (b"ofs-delta", None) => out.ofs_delta = true,
+                (b"deepen-relative", None) => out.deepen_relative = true,
+                (b"wait-for-done", None) => out.wait_for_done = true,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return `true` if the client wants to change the depth of its history.
+    pub fn is_deepening(&self) -> bool {
+        self.deepen.is_some() || self.deepen_since.is_some() || !self.deepen_not.is_empty()
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: Option<&[u8]>) -> Option<T> {
+    value?.to_str().ok()?.parse().ok()
+}
+
+/// A way to omit objects from a pack, as requested with the `filter` argument for partial clones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Filter {
+    /// Omit all blobs, as requested with `blob:none`.
+    BlobNone,
+    /// Omit all blobs larger than the given amount of bytes, as requested with `blob:limit=<n>[kmg]`.
+    BlobLimit(u64),
+}
+
+impl Filter {
+    /// Parse a filter `spec` like `blob:none`.
+    pub fn from_spec(spec: &bstr::BStr) -> Result<Self, Error> {
+        let unsupported = || Error::UnsupportedFilter { spec: spec.to_owned() };
+        if spec == "blob:none" {
+            return Ok(Filter::BlobNone);
+        }
+        let limit = spec
+            .strip_prefix(b"blob:limit=")
+            .and_then(|limit| limit.to_str().ok())
+            .ok_or_else(unsupported)?;
+        let (digits, factor) = match limit.as_bytes().last() {
+            Some(b'k' | b'K') => (&limit[..limit.len() - 1], 1024),
+            Some(b'm' | b'M') => (&limit[..limit.len() - 1], 1024 * 1024),
+            Some(b'g' | b'G') => (&limit[..limit.len() - 1], 1024 * 1024 * 1024),
+            _ => (limit, 1),
+        };
+        digits
+            .parse::<u64>()
+            .ok()
+            .and_then(|limit| limit.checked_mul(factor))
+            .map(Filter::BlobLimit)
+            .ok_or_else(unsupported)
+    }
+
+    /// Return `true` if a blob of `size` bytes should be sent.
+    pub fn includes_blob(&self, size: u64) -> bool {
+        match self {
+            Filter::BlobNone => false,
+            Filter::BlobLimit(limit) => size <= *limit,
+        }
+    }
+}
+
+/// Changes to the shallow commits of the client, as determined by the [delegate](crate::upload_pack::Delegate::shallow()).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShallowUpdate {
+    /// The commits that are shallow after the fetch, i.e. their parents won't be sent.
+    pub shallow: Vec<ObjectId>,
+    /// The shallow commits of the client that aren't shallow anymore, as their parents will be sent.
+    pub unshallow: Vec<ObjectId>,
+}
+
+/// A description of the pack to produce with [`Delegate::write_pack()`](crate::upload_pack::Delegate::write_pack()).
+#[derive(Debug, Clone, Copy)]
+pub struct Pack<'a> {
+    /// The objects the client wants, including the ones of the references it wants.
+    pub wants: &'a [ObjectId],
+    /// The objects the client has which we have as well. They and the objects reachable from them don't have to be sent.
+    pub common: &'a [ObjectId],
+    /// The shallow commits of the client after the fetch, whose parents must not be sent, along with the commits that
+    /// were shallow before and now need their parents.
+    pub shallow: &'a ShallowUpdate,
+    /// All arguments of the request, for options like [`filter`](Arguments::filter) or [`include_tag`](Arguments::include_tag).
+    pub arguments: &'a Arguments,
+}
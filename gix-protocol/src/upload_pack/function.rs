@@ -0,0 +1,275 @@
+use std::{cell::RefCell, io};
+
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_packetline::{encode, Channel, PacketLineRef, StreamingPeekableIter};
+
+use crate::{
+    upload_pack::{fetch, ls_refs, Delegate, Error, Options, Request},
+    Command,
+};
+
+/// The amount of bytes that fit into a single side-band packet line, as it needs one byte to indicate the band.
+const MAX_BAND_DATA_LEN: usize = 65515;
+
+/// Serve all requests of a client read from `input` by writing responses to `out`, until the client ends the session,
+/// using `delegate` to access the repository.
+///
+/// The capability advertisement is sent first, so this is suitable for bidirectional connections like the ones of `ssh`.
+/// Protocol errors are sent to the client as `ERR` packet lines before they are returned.
+pub fn serve(
+    delegate: &mut dyn Delegate,
+    input: impl io::Read,
+    mut out: impl io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    write_capabilities(&mut out, options)?;
+    out.flush()?;
+    let mut lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush], options.trace);
+    while let Some(request) = read_request(&mut lines, &mut out)? {
+        handle(delegate, &request, &mut out, options)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Serve a single request of a client read from `input` by writing the response to `out`, using `delegate` to access
+/// the repository.
+///
+/// This is what smart-HTTP needs for `POST` requests, as the capability advertisement is requested separately
+/// and sent with [`write_capabilities()`].
+pub fn serve_stateless(
+    delegate: &mut dyn Delegate,
+    input: impl io::Read,
+    mut out: impl io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    let mut lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush], options.trace);
+    if let Some(request) = read_request(&mut lines, &mut out)? {
+        handle(delegate, &request, &mut out, options)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Write the capability advertisement of protocol V2 to `out`, which is the first thing a client receives.
+pub fn write_capabilities(mut out: impl io::Write, options: &Options) -> io::Result<()> {
+    for line in [
+        "version 2".to_string(),
+        format!("agent={}", options.agent),
+        "ls-refs=unborn".into(),
+        "fetch=shallow wait-for-done filter ref-in-want".into(),
+        "server-option".into(),
+        format!("object-format={}", options.object_hash.to_string().to_ascii_lowercase()),
+    ] {
+        encode::text_to_write(line.as_bytes(), &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+fn read_request<R: io::Read>(
+    lines: &mut StreamingPeekableIter<R>,
+    out: &mut dyn io::Write,
+) -> Result<Option<Request>, Error> {
+    Request::read(lines).map_err(|err| send_error(err, out))
+}
+
+fn handle(
+    delegate: &mut dyn Delegate,
+    request: &Request,
+    out: &mut dyn io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    let res = match request.command {
+        Command::LsRefs => ls_refs(delegate, request, out, options),
+        Command::Fetch => fetch(delegate, request, out, options),
+    };
+    res.map_err(|err| send_error(err, out))
+}
+
+/// Let the client know about `err` on a best-effort basis, and return it.
+fn send_error(err: Error, out: &mut dyn io::Write) -> Error {
+    if !matches!(err, Error::Io(_)) {
+        encode::error_to_write(err.to_string().as_bytes(), &mut *out).ok();
+        out.flush().ok();
+    }
+    err
+}
+
+fn check_object_format(request: &Request, options: &Options) -> Result<(), Error> {
+    match request.capability("object-format") {
+        Some(requested) if !requested.eq_ignore_ascii_case(options.object_hash.to_string().as_bytes()) => {
+            Err(Error::ObjectFormat {
+                requested: requested.clone(),
+                actual: options.object_hash,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn ls_refs(
+    delegate: &mut dyn Delegate,
+    request: &Request,
+    out: &mut dyn io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    check_object_format(request, options)?;
+    let arguments = ls_refs::Arguments::from_lines(&request.arguments)?;
+    let refs = delegate.refs()?;
+    ls_refs::write_response(&refs, &arguments, out)?;
+    Ok(())
+}
+
+fn fetch(
+    delegate: &mut dyn Delegate,
+    request: &Request,
+    out: &mut dyn io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    check_object_format(request, options)?;
+    let arguments = fetch::Arguments::from_lines(&request.arguments)?;
+    for id in &arguments.wants {
+        if !delegate.contains(id)? {
+            return Err(Error::NotOurRef { id: *id });
+        }
+    }
+    let mut wants = arguments.wants.clone();
+    let mut wanted_refs = Vec::<(ObjectId, &BString)>::new();
+    if !arguments.want_refs.is_empty() {
+        let refs = delegate.refs()?;
+        for name in &arguments.want_refs {
+            let id = refs
+                .iter()
+                .find(|r| &r.name == name)
+                .and_then(|r| r.object)
+                .ok_or_else(|| Error::UnknownRef { name: name.clone() })?;
+            wants.push(id);
+            wanted_refs.push((id, name));
+        }
+    }
+    let mut common = Vec::new();
+    for id in &arguments.haves {
+        if delegate.contains(id)? {
+            common.push(*id);
+        }
+    }
+
+    if !arguments.done {
+        encode::text_to_write(b"acknowledgments", &mut *out)?;
+        if common.is_empty() {
+            encode::text_to_write(b"NAK", &mut *out)?;
+        }
+        for id in &common {
+            encode::text_to_write(format!("ACK {id}").as_bytes(), &mut *out)?;
+        }
+        let is_ready = !arguments.wait_for_done && !common.is_empty() && delegate.is_ready(&wants, &common)?;
+        if !is_ready {
+            encode::flush_to_write(&mut *out)?;
+            return Ok(());
+        }
+        encode::text_to_write(b"ready", &mut *out)?;
+        encode::delim_to_write(&mut *out)?;
+    }
+
+    let shallow = if arguments.is_deepening() || !arguments.shallow.is_empty() {
+        let update = delegate.shallow(&wants, &arguments)?;
+        encode::text_to_write(b"shallow-info", &mut *out)?;
+        for id in &update.shallow {
+            encode::text_to_write(format!("shallow {id}").as_bytes(), &mut *out)?;
+        }
+        for id in &update.unshallow {
+            encode::text_to_write(format!("unshallow {id}").as_bytes(), &mut *out)?;
+        }
+        encode::delim_to_write(&mut *out)?;
+        update
+    } else {
+        fetch::ShallowUpdate::default()
+    };
+
+    if !wanted_refs.is_empty() {
+        encode::text_to_write(b"wanted-refs", &mut *out)?;
+        for (id, name) in wanted_refs {
+            let mut line = BString::from(format!("{id} "));
+            line.extend_from_slice(name);
+            encode::text_to_write(&line, &mut *out)?;
+        }
+        encode::delim_to_write(&mut *out)?;
+    }
+
+    encode::text_to_write(b"packfile", &mut *out)?;
+    let out = RefCell::new(out);
+    let res = delegate.write_pack(
+        fetch::Pack {
+            wants: &wants,
+            common: &common,
+            shallow: &shallow,
+            arguments: &arguments,
+        },
+        &mut Band {
+            out: &out,
+            channel: Channel::Data,
+        },
+        &mut Band {
+            out: &out,
+            channel: Channel::Progress,
+        }
+        .filter(!arguments.no_progress),
+    );
+    let out = out.into_inner();
+    if let Err(err) = res {
+        encode::band_to_write(Channel::Error, err.to_string().as_bytes(), &mut *out).ok();
+        encode::flush_to_write(&mut *out).ok();
+        return Err(err.into());
+    }
+    encode::flush_to_write(&mut *out)?;
+    Ok(())
+}
+
+/// A writer which sends everything on its `channel` using side-band packet lines.
+struct Band<'a, 'b> {
+    out: &'a RefCell<&'b mut dyn io::Write>,
+    channel: Channel,
+}
+
+impl<'a, 'b> Band<'a, 'b> {
+    /// Return a writer that discards all data if `enabled` is `false`.
+    fn filter(self, enabled: bool) -> Filtered<'a, 'b> {
+        Filtered { band: self, enabled }
+    }
+}
+
+impl io::Write for Band<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut out = self.out.borrow_mut();
+        for chunk in buf.chunks(MAX_BAND_DATA_LEN) {
+            encode::band_to_write(self.channel, chunk, &mut **out)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.borrow_mut().flush()
+    }
+}
+
+/// A [`Band`] which may discard everything written to it.
+struct Filtered<'a, 'b> {
+    band: Band<'a, 'b>,
+    enabled: bool,
+}
+
+impl io::Write for Filtered<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.enabled {
+            self.band.write(buf)
+        } else {
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.band.flush()
+    }
+}
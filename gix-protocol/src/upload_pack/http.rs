@@ -0,0 +1,74 @@
+//! Smart-HTTP uses two endpoints relative to the URL of the repository, which are handled by [`serve()`]
+//! once [`Endpoint::from_request()`] identified them:
+//!
+//! * `GET <repository>/info/refs?service=git-upload-pack` for the capability advertisement.
+//! * `POST <repository>/git-upload-pack` for each request of the client.
+//!
+//! The response should be sent with the [content type](Endpoint::content_type()) of the endpoint and
+//! `Cache-Control: no-cache`.
+use std::io;
+
+use crate::upload_pack::{serve_stateless, write_capabilities, Delegate, Error, Options};
+
+/// An endpoint of the `upload-pack` service in smart-HTTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// The capability advertisement.
+    Advertisement,
+    /// The endpoint to send requests to.
+    UploadPack,
+}
+
+impl Endpoint {
+    /// Identify the endpoint for an HTTP request with `method`, `path` and `query` (without leading `?`),
+    /// and return it along with the path to the repository, which is the part of `path` before the endpoint.
+    ///
+    /// Return `None` if the request isn't for the `upload-pack` service.
+    pub fn from_request<'a>(method: &str, path: &'a str, query: Option<&str>) -> Option<(&'a str, Endpoint)> {
+        match method {
+            "GET" => {
+                let repository = path.strip_suffix("/info/refs")?;
+                query?
+                    .split('&')
+                    .any(|param| param == "service=git-upload-pack")
+                    .then_some((repository, Endpoint::Advertisement))
+            }
+            "POST" => Some((path.strip_suffix("/git-upload-pack")?, Endpoint::UploadPack)),
+            _ => None,
+        }
+    }
+
+    /// The content type of the response.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Endpoint::Advertisement => "application/x-git-upload-pack-advertisement",
+            Endpoint::UploadPack => "application/x-git-upload-pack-result",
+        }
+    }
+}
+
+/// Return `true` if the value of the `Git-Protocol` header, like `version=2`, requests protocol V2.
+///
+/// Clients that don't request it can't be served.
+pub fn is_v2(git_protocol_header: &str) -> bool {
+    git_protocol_header.split(':').any(|param| param.trim() == "version=2")
+}
+
+/// Write the response for `endpoint` to `out`, using `delegate` to access the repository and reading the request
+/// from `body` if it's a `POST` request.
+pub fn serve(
+    endpoint: Endpoint,
+    delegate: &mut dyn Delegate,
+    body: impl io::Read,
+    mut out: impl io::Write,
+    options: &Options,
+) -> Result<(), Error> {
+    match endpoint {
+        Endpoint::Advertisement => {
+            write_capabilities(&mut out, options)?;
+            out.flush()?;
+            Ok(())
+        }
+        Endpoint::UploadPack => serve_stateless(delegate, body, out, options),
+    }
+}
@@ -0,0 +1,88 @@
+use std::io;
+
+use bstr::{BString, ByteVec};
+use gix_hash::ObjectId;
+
+use crate::upload_pack::Error;
+
+/// A reference to advertise in response to `ls-refs`, as provided by the [delegate](crate::upload_pack::Delegate::refs()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ref {
+    /// The full name of the reference, like `HEAD` or `refs/heads/main`.
+    pub name: BString,
+    /// The object the reference points to after following symbolic references, or `None` if it is unborn,
+    /// which is only possible for symbolic references.
+    pub object: Option<ObjectId>,
+    /// The full name of the reference this one points to if it is symbolic.
+    pub symref_target: Option<BString>,
+    /// The object that `object` ultimately points to if it is an annotated tag.
+    pub peeled: Option<ObjectId>,
+}
+
+/// The arguments of the `ls-refs` command.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arguments {
+    /// If `true`, the objects annotated tags point to are sent along with them.
+    pub peel: bool,
+    /// If `true`, the targets of symbolic references are sent along with them.
+    pub symrefs: bool,
+    /// If `true`, unborn symbolic references are sent as well.
+    pub unborn: bool,
+    /// Only references starting with one of these prefixes are sent, or all of them if empty.
+    pub prefixes: Vec<BString>,
+}
+
+impl Arguments {
+    /// Parse the arguments of an `ls-refs` request from `lines`.
+    pub fn from_lines(lines: &[BString]) -> Result<Self, Error> {
+        let mut out = Arguments::default();
+        for line in lines {
+            match line.as_slice() {
+                b"peel" => out.peel = true,
+                b"symrefs" => out.symrefs = true,
+                b"unborn" => out.unborn = true,
+                _ => match line.strip_prefix(b"ref-prefix ") {
+                    Some(prefix) => out.prefixes.push(prefix.into()),
+                    None => {
+                        return Err(Error::InvalidArgument {
+                            command: "ls-refs",
+                            argument: line.clone(),
+                        })
+                    }
+                },
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return `true` if `reference` should be sent to the client.
+    pub fn matches(&self, reference: &Ref) -> bool {
+        (reference.object.is_some() || self.unborn)
+            && (self.prefixes.is_empty() || self.prefixes.iter().any(|prefix| reference.name.starts_with(prefix)))
+    }
+}
+
+/// Write the lines for all `refs` that match `arguments` to `out` in packetline format, followed by a flush packet line.
+pub fn write_response(refs: &[Ref], arguments: &Arguments, mut out: impl io::Write) -> io::Result<()> {
+    for reference in refs.iter().filter(|reference| arguments.matches(reference)) {
+        let mut line = match reference.object {
+            Some(id) => BString::from(id.to_string()),
+            None => BString::from("unborn"),
+        };
+        line.push_byte(b' ');
+        line.push_str(&reference.name);
+        if let Some(target) = reference.symref_target.as_ref().filter(|_| arguments.symrefs) {
+            line.push_str(" symref-target:");
+            line.push_str(target);
+        }
+        if let Some(peeled) = reference.peeled.filter(|_| arguments.peel) {
+            line.push_str(format!(" peeled:{peeled}"));
+        }
+        line.push_byte(b'\n');
+        gix_packetline::encode::data_to_write(&line, &mut out)?;
+    }
+    gix_packetline::encode::flush_to_write(&mut out)?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Types and utilities to implement the server side of the `upload-pack` service with protocol V2, which is used by clients
+//! to list references and fetch packs.
+//!
+//! The repository is accessed through a [`Delegate`], while this module takes care of the protocol itself:
+//!
+//! * [`serve()`] handles a whole session over a bidirectional connection, like stdin and stdout of a process spawned
+//!   by `ssh`, or a TCP connection accepted by a `git daemon`-style server whose initial [request](daemon::Request)
+//!   was read already.
+//! * [`serve_stateless()`] handles a single request, as needed by smart-HTTP, with [`http`] providing the
+//!   details to embed it into any HTTP server.
+//!
+//! Note that clients which don't request protocol V2 can't be served.
+use std::io;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod daemon;
+///
+#[allow(clippy::empty_docs)]
+pub mod fetch;
+///
+#[allow(clippy::empty_docs)]
+pub mod http;
+///
+#[allow(clippy::empty_docs)]
+pub mod ls_refs;
+
+mod request;
+pub use request::Request;
+
+mod function;
+pub use function::{serve, serve_stateless, write_capabilities};
+
+/// The error returned by [`serve()`] and [`serve_stateless()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the request or write the response")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    DecodePacketline(#[from] gix_packetline::decode::Error),
+    #[error("Received an unexpected response-end packet line")]
+    UnexpectedResponseEnd,
+    #[error("The request didn't specify a command")]
+    MissingCommand,
+    #[error("The command '{name}' is unknown")]
+    UnknownCommand { name: BString },
+    #[error("The argument '{argument}' is invalid for the '{command}' command")]
+    InvalidArgument { command: &'static str, argument: BString },
+    #[error("The filter '{spec}' isn't supported")]
+    UnsupportedFilter { spec: BString },
+    #[error("The client requested objects in the '{requested}' format, but the repository uses '{actual}'")]
+    ObjectFormat { requested: BString, actual: gix_hash::Kind },
+    #[error("not our ref {id}")]
+    NotOurRef { id: ObjectId },
+    #[error("unknown ref {name}")]
+    UnknownRef { name: BString },
+}
+
+/// Options for use in [`serve()`] and [`serve_stateless()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The name of the server, advertised with the `agent` capability. It should start with `git/`, see [`agent()`](crate::agent()).
+    pub agent: String,
+    /// The kind of hash used by the repository, advertised with the `object-format` capability.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub trace: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            agent: crate::agent(concat!("oxide-", env!("CARGO_PKG_VERSION"))),
+            object_hash: gix_hash::Kind::Sha1,
+            trace: false,
+        }
+    }
+}
+
+/// The part of the server with access to the repository, used by [`serve()`] and [`serve_stateless()`].
+pub trait Delegate {
+    /// Return all references to advertise, including `HEAD`, in the order they should be listed.
+    ///
+    /// They are filtered by the prefixes the client is interested in by the caller.
+    fn refs(&mut self) -> io::Result<Vec<ls_refs::Ref>>;
+
+    /// Return `true` if the object `id` exists in the repository.
+    ///
+    /// It's used to validate wants, and to find the objects the client has in common with us.
+    fn contains(&mut self, id: &gix_hash::oid) -> io::Result<bool>;
+
+    /// Return `true` if the `common` commits allow to produce a pack for `wants` right away, which is the case
+    /// if each of the `wants` has one of the `common` commits as ancestor.
+    ///
+    /// If `false` is returned, the client is asked to send more `have` lines.
+    fn is_ready(&mut self, wants: &[ObjectId], common: &[ObjectId]) -> io::Result<bool>;
+
+    /// Determine which commits become shallow on the client, or stop being shallow, when fetching `wants` with the
+    /// `deepen` settings and the shallow commits of the client, as contained in `arguments`.
+    ///
+    /// It's only called if the client is shallow or wants to deepen its history.
+    fn shallow(&mut self, wants: &[ObjectId], arguments: &fetch::Arguments) -> io::Result<fetch::ShallowUpdate>;
+
+    /// Write a pack with all objects the client needs as specified by `pack` into `out`, and send human-readable
+    /// messages about its progress to `progress`.
+    fn write_pack(
+        &mut self,
+        pack: fetch::Pack<'_>,
+        out: &mut dyn io::Write,
+        progress: &mut dyn io::Write,
+    ) -> io::Result<()>;
+}
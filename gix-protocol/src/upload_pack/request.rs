@@ -0,0 +1,78 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use gix_packetline::{PacketLineRef, StreamingPeekableIter};
+
+use crate::{upload_pack::Error, Command};
+
+/// A request for a single command as sent by a client, with its capabilities and arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The command to invoke.
+    pub command: Command,
+    /// The capabilities sent along with the command, like `agent` or `object-format`, with their values if there are any.
+    pub capabilities: Vec<(BString, Option<BString>)>,
+    /// The arguments to the command, one per line and without trailing newline.
+    pub arguments: Vec<BString>,
+}
+
+impl Request {
+    /// Read the next request from `lines`, or return `None` if the client ended the session by closing the connection
+    /// or by sending a flush packet line instead of a request.
+    ///
+    /// `lines` must stop at flush packet lines, and will be reset to continue reading after the request.
+    pub fn read<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Self>, Error> {
+        lines.reset();
+        let mut command = None;
+        let mut capabilities = Vec::new();
+        let mut arguments = None::<Vec<BString>>;
+        let mut is_first_line = true;
+        loop {
+            let line = match lines.read_line() {
+                None => break,
+                Some(Err(err)) if is_first_line && err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Some(line) => line??,
+            };
+            is_first_line = false;
+            match line {
+                PacketLineRef::Delimiter => arguments = Some(Vec::new()),
+                PacketLineRef::Data(data) => {
+                    let data = data.strip_suffix(b"\n").unwrap_or(data);
+                    match arguments.as_mut() {
+                        Some(arguments) => arguments.push(data.into()),
+                        None => match data.strip_prefix(b"command=") {
+                            Some(name) => {
+                                command = Some(match name {
+                                    b"ls-refs" => Command::LsRefs,
+                                    b"fetch" => Command::Fetch,
+                                    _ => return Err(Error::UnknownCommand { name: name.into() }),
+                                })
+                            }
+                            None => capabilities.push(match data.find_byte(b'=') {
+                                Some(pos) => (data[..pos].into(), Some(data[pos + 1..].into())),
+                                None => (data.into(), None),
+                            }),
+                        },
+                    }
+                }
+                PacketLineRef::ResponseEnd => return Err(Error::UnexpectedResponseEnd),
+                PacketLineRef::Flush => unreachable!("flush packet lines are delimiters"),
+            }
+        }
+        if is_first_line {
+            return Ok(None);
+        }
+        Ok(Some(Request {
+            command: command.ok_or(Error::MissingCommand)?,
+            capabilities,
+            arguments: arguments.unwrap_or_default(),
+        }))
+    }
+
+    /// Return the value of the capability `name`, if it was sent with a value.
+    pub fn capability(&self, name: &str) -> Option<&BString> {
+        self.capabilities
+            .iter()
+            .find_map(|(key, value)| (key == name).then_some(value.as_ref()).flatten())
+    }
+}
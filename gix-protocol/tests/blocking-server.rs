@@ -0,0 +1,3 @@
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+mod upload_pack;
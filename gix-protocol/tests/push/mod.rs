@@ -0,0 +1,206 @@
+fn id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("expect valid hex id")
+}
+
+mod command {
+    use gix_protocol::push::Command;
+
+    use crate::push::id;
+
+    #[test]
+    fn to_line_with_and_without_features() {
+        let cmd = Command {
+            old_id: gix_hash::Kind::Sha1.null(),
+            new_id: id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"),
+            ref_name: "refs/heads/main".into(),
+        };
+        assert!(cmd.is_create());
+        assert!(!cmd.is_delete());
+        assert_eq!(
+            cmd.to_line(&[]),
+            "0000000000000000000000000000000000000000 7b333369de1221f9bfbbe03a3a13e9a09bc1c907 refs/heads/main"
+        );
+        assert_eq!(
+            cmd.to_line(&[("report-status-v2", None), ("agent", Some("git/gix"))]),
+            "0000000000000000000000000000000000000000 7b333369de1221f9bfbbe03a3a13e9a09bc1c907 refs/heads/main\0report-status-v2 agent=git/gix"
+        );
+    }
+
+    #[test]
+    fn from_line_round_trips_and_ignores_features() {
+        let cmd = Command {
+            old_id: id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907"),
+            new_id: gix_hash::Kind::Sha1.null(),
+            ref_name: "refs/tags/v1.0".into(),
+        };
+        assert!(cmd.is_delete());
+        let line = cmd.to_line(&[("atomic", None)]);
+        assert_eq!(Command::from_line(&line), Some(cmd.clone()));
+        let mut line = cmd.to_line(&[]);
+        line.push(b'\n');
+        assert_eq!(Command::from_line(&line), Some(cmd));
+        assert_eq!(Command::from_line(b"invalid"), None);
+    }
+}
+
+mod report {
+    use bstr::ByteSlice;
+    use gix_protocol::push::{
+        response::{CommandStatus, Error, Options, Status, Unpack},
+        Report,
+    };
+
+    use crate::push::id;
+
+    #[test]
+    fn v1_success_and_rejections() -> crate::Result {
+        let report = Report::from_lines([
+            &b"unpack ok\n"[..],
+            b"ok refs/heads/main\n",
+            b"ng refs/heads/feature non-fast-forward\n",
+            b"ng refs/heads/other pre-receive hook declined",
+        ])?;
+        assert_eq!(report.unpack, Unpack::Ok);
+        assert_eq!(
+            report.commands,
+            vec![
+                CommandStatus {
+                    ref_name: "refs/heads/main".into(),
+                    status: Status::Ok { options: Vec::new() }
+                },
+                CommandStatus {
+                    ref_name: "refs/heads/feature".into(),
+                    status: Status::Rejected {
+                        reason: "non-fast-forward".into()
+                    }
+                },
+                CommandStatus {
+                    ref_name: "refs/heads/other".into(),
+                    status: Status::Rejected {
+                        reason: "pre-receive hook declined".into()
+                    }
+                },
+            ]
+        );
+        assert!(!report.is_success());
+        Ok(())
+    }
+
+    #[test]
+    fn unpack_failure() -> crate::Result {
+        let report = Report::from_lines([
+            &b"unpack index-pack abnormal exit"[..],
+            b"ng refs/heads/main unpacker error",
+        ])?;
+        assert_eq!(
+            report.unpack,
+            Unpack::Failed {
+                message: "index-pack abnormal exit".into()
+            }
+        );
+        assert!(!report.is_success());
+        Ok(())
+    }
+
+    #[test]
+    fn v2_options() -> crate::Result {
+        let report = Report::from_lines([
+            &b"unpack ok"[..],
+            b"ok refs/for/main/topic",
+            b"option refname refs/changes/01/1/1",
+            b"option old-oid 0000000000000000000000000000000000000000",
+            b"option new-oid 7b333369de1221f9bfbbe03a3a13e9a09bc1c907",
+            b"option refname refs/changes/01/1/2",
+            b"option forced-update",
+            b"ok refs/heads/main",
+        ])?;
+        assert!(report.is_success());
+        assert_eq!(
+            report.commands[0].status,
+            Status::Ok {
+                options: vec![
+                    Options {
+                        ref_name: Some("refs/changes/01/1/1".into()),
+                        old_id: Some(gix_hash::Kind::Sha1.null()),
+                        new_id: Some(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907")),
+                        forced_update: false,
+                    },
+                    Options {
+                        ref_name: Some("refs/changes/01/1/2".into()),
+                        forced_update: true,
+                        ..Default::default()
+                    }
+                ]
+            }
+        );
+        assert_eq!(report.commands[1].status, Status::Ok { options: Vec::new() });
+        Ok(())
+    }
+
+    #[test]
+    fn v2_options_start_a_new_set_with_each_refname_and_ignore_unknown_ones() -> crate::Result {
+        let report = Report::from_lines([
+            &b"unpack ok"[..],
+            b"ok refs/heads/main",
+            b"option future-option with value",
+            b"option forced-update",
+            b"option refname refs/heads/rewritten",
+            b"option unknown",
+            b"option new-oid 7b333369de1221f9bfbbe03a3a13e9a09bc1c907",
+        ])?;
+        assert_eq!(
+            report.commands[0].status,
+            Status::Ok {
+                options: vec![
+                    Options {
+                        forced_update: true,
+                        ..Default::default()
+                    },
+                    Options {
+                        ref_name: Some("refs/heads/rewritten".into()),
+                        new_id: Some(id("7b333369de1221f9bfbbe03a3a13e9a09bc1c907")),
+                        ..Default::default()
+                    }
+                ]
+            },
+            "a refname following a set without one starts a new set"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn from_packetlines_as_received_via_sideband() -> crate::Result {
+        let report = Report::from_packetlines(b"000eunpack ok\n0017ok refs/heads/main\n0000")?;
+        assert!(report.is_success());
+        assert_eq!(report.commands[0].ref_name.as_bstr(), "refs/heads/main");
+        assert!(matches!(
+            Report::from_packetlines(b"000eunpack ok\n0017ok refs"),
+            Err(Error::Incomplete)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(matches!(
+            Report::from_lines(None::<&[u8]>),
+            Err(Error::MissingUnpackStatus { line: None })
+        ));
+        assert!(matches!(
+            Report::from_lines([&b"ok refs/heads/main"[..]]),
+            Err(Error::MissingUnpackStatus { line: Some(_) })
+        ));
+        assert!(matches!(
+            Report::from_lines([&b"unpack ok"[..], b"option forced-update"]),
+            Err(Error::UnexpectedOption { .. })
+        ));
+        assert!(matches!(
+            Report::from_lines([&b"unpack ok"[..], b"ok refs/heads/main", b"option old-oid not-hex"]),
+            Err(Error::MalformedLine { .. })
+        ));
+        assert!(matches!(
+            Report::from_lines([&b"unpack ok"[..], b"something else"]),
+            Err(Error::MalformedLine { .. })
+        ));
+    }
+}
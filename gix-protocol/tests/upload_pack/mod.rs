@@ -0,0 +1,548 @@
+use std::io;
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+use gix_protocol::upload_pack::{self, fetch, ls_refs, Delegate};
+
+fn id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("valid hex id")
+}
+
+const MAIN: &str = "7b333369de1221f9bfbbe03a3a13e9a09bc1c907";
+const TAG: &str = "f9bfbbe03a3a13e9a09bc1c9077b333369de1221";
+const OLD: &str = "e9a09bc1c9077b333369de1221f9bfbbe03a3a13";
+
+/// A repository with `main`, an annotated tag pointing to it and its parent `OLD`.
+#[derive(Default)]
+struct Repository {
+    packs: Vec<(Vec<ObjectId>, Vec<ObjectId>, fetch::ShallowUpdate)>,
+}
+
+impl Delegate for Repository {
+    fn refs(&mut self) -> io::Result<Vec<ls_refs::Ref>> {
+        Ok(vec![
+            ls_refs::Ref {
+                name: "HEAD".into(),
+                object: Some(id(MAIN)),
+                symref_target: Some("refs/heads/main".into()),
+                peeled: None,
+            },
+            ls_refs::Ref {
+                name: "refs/heads/main".into(),
+                object: Some(id(MAIN)),
+                symref_target: None,
+                peeled: None,
+            },
+            ls_refs::Ref {
+                name: "refs/heads/unborn".into(),
+                object: None,
+                symref_target: Some("refs/heads/missing".into()),
+                peeled: None,
+            },
+            ls_refs::Ref {
+                name: "refs/tags/v1".into(),
+                object: Some(id(TAG)),
+                symref_target: None,
+                peeled: Some(id(MAIN)),
+            },
+        ])
+    }
+
+    fn contains(&mut self, oid: &gix_hash::oid) -> io::Result<bool> {
+        Ok([MAIN, TAG, OLD].iter().any(|hex| id(hex) == oid))
+    }
+
+    fn is_ready(&mut self, _wants: &[ObjectId], common: &[ObjectId]) -> io::Result<bool> {
+        Ok(common.contains(&id(OLD)))
+    }
+
+    fn shallow(&mut self, _wants: &[ObjectId], arguments: &fetch::Arguments) -> io::Result<fetch::ShallowUpdate> {
+        Ok(match arguments.deepen {
+            Some(1) => fetch::ShallowUpdate {
+                shallow: vec![id(MAIN)],
+                unshallow: Vec::new(),
+            },
+            _ => fetch::ShallowUpdate {
+                shallow: Vec::new(),
+                unshallow: arguments.shallow.clone(),
+            },
+        })
+    }
+
+    fn write_pack(
+        &mut self,
+        pack: fetch::Pack<'_>,
+        out: &mut dyn io::Write,
+        progress: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        self.packs
+            .push((pack.wants.to_owned(), pack.common.to_owned(), pack.shallow.clone()));
+        progress.write_all(b"counting objects\n")?;
+        out.write_all(b"PACK")
+    }
+}
+
+/// Encode `lines` as packetlines, with `|` being a delimiter and an empty line being a flush.
+fn request(lines: &[&str]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for line in lines {
+        match *line {
+            "" => gix_packetline::encode::flush_to_write(&mut out),
+            "|" => gix_packetline::encode::delim_to_write(&mut out),
+            line => gix_packetline::encode::text_to_write(line.as_bytes(), &mut out),
+        }
+        .expect("writing to memory works");
+    }
+    out
+}
+
+/// Decode all packetlines in `data`, with side-band lines being prefixed with their band.
+fn response(mut data: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    while !data.is_empty() {
+        let gix_packetline::decode::Stream::Complete { line, bytes_consumed } =
+            gix_packetline::decode::streaming(data).expect("valid packetlines")
+        else {
+            panic!("incomplete packetline")
+        };
+        out.push(match line {
+            gix_packetline::PacketLineRef::Flush => "".into(),
+            gix_packetline::PacketLineRef::Delimiter => "|".into(),
+            gix_packetline::PacketLineRef::ResponseEnd => "RESPONSE_END".into(),
+            gix_packetline::PacketLineRef::Data(data) => match data.first() {
+                Some(band @ 1..=3) => format!("{band}:{}", data[1..].as_bstr()),
+                _ => data.as_bstr().trim_end().to_str_lossy().into_owned(),
+            },
+        });
+        data = &data[bytes_consumed..];
+    }
+    out
+}
+
+fn serve(delegate: &mut Repository, lines: &[&str]) -> (Result<(), upload_pack::Error>, Vec<String>) {
+    let mut out = Vec::new();
+    let res = upload_pack::serve(
+        delegate,
+        request(lines).as_slice(),
+        &mut out,
+        &upload_pack::Options {
+            agent: "git/gix-test".into(),
+            ..Default::default()
+        },
+    );
+    (res, response(&out))
+}
+
+fn capabilities() -> Vec<String> {
+    [
+        "version 2",
+        "agent=git/gix-test",
+        "ls-refs=unborn",
+        "fetch=shallow wait-for-done filter ref-in-want",
+        "server-option",
+        "object-format=sha1",
+        "",
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect()
+}
+
+fn expected(lines: &[&str]) -> Vec<String> {
+    capabilities()
+        .into_iter()
+        .chain(lines.iter().map(ToString::to_string))
+        .collect()
+}
+
+mod serve {
+    use gix_protocol::upload_pack;
+
+    use super::{expected, id, serve, Repository, MAIN, OLD, TAG};
+
+    #[test]
+    fn capabilities_only_if_the_session_ends_right_away() -> crate::Result {
+        for input in [&[][..], &[""]] {
+            let (res, out) = serve(&mut Repository::default(), input);
+            res?;
+            assert_eq!(out, expected(&[]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ls_refs() -> crate::Result {
+        let (res, out) = serve(
+            &mut Repository::default(),
+            &[
+                "command=ls-refs",
+                "agent=git/2.39.5",
+                "object-format=sha1",
+                "|",
+                "symrefs",
+                "peel",
+                "",
+                "command=ls-refs",
+                "|",
+                "ref-prefix refs/heads/",
+                "unborn",
+                "",
+                "",
+            ],
+        );
+        res?;
+        assert_eq!(
+            out,
+            expected(&[
+                &format!("{MAIN} HEAD symref-target:refs/heads/main"),
+                &format!("{MAIN} refs/heads/main"),
+                &format!("{TAG} refs/tags/v1 peeled:{MAIN}"),
+                "",
+                &format!("{MAIN} refs/heads/main"),
+                "unborn refs/heads/unborn",
+                "",
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_negotiation_until_ready() -> crate::Result {
+        let unknown = "0000000000000000000000000000000000000001";
+        let mut repo = Repository::default();
+        let (res, out) = serve(
+            &mut repo,
+            &[
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("have {unknown}"),
+                "",
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("have {unknown}"),
+                &format!("have {TAG}"),
+                "",
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("have {TAG}"),
+                &format!("have {OLD}"),
+                "",
+            ],
+        );
+        res?;
+        assert_eq!(
+            out,
+            expected(&[
+                "acknowledgments",
+                "NAK",
+                "",
+                "acknowledgments",
+                &format!("ACK {TAG}"),
+                "",
+                "acknowledgments",
+                &format!("ACK {TAG}"),
+                &format!("ACK {OLD}"),
+                "ready",
+                "|",
+                "packfile",
+                "2:counting objects\n",
+                "1:PACK",
+                "",
+            ])
+        );
+        assert_eq!(
+            repo.packs,
+            vec![(vec![id(MAIN)], vec![id(TAG), id(OLD)], Default::default())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_done_wait_for_done_and_no_progress() -> crate::Result {
+        let mut repo = Repository::default();
+        let (res, out) = serve(
+            &mut repo,
+            &[
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("have {OLD}"),
+                "wait-for-done",
+                "",
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("have {OLD}"),
+                "no-progress",
+                "done",
+                "",
+            ],
+        );
+        res?;
+        assert_eq!(
+            out,
+            expected(&["acknowledgments", &format!("ACK {OLD}"), "", "packfile", "1:PACK", ""]),
+            "the client decides when it's done, and acknowledgments are skipped then"
+        );
+        assert_eq!(repo.packs.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_shallow_and_want_ref() -> crate::Result {
+        let mut repo = Repository::default();
+        let (res, out) = serve(
+            &mut repo,
+            &[
+                "command=fetch",
+                "|",
+                "want-ref refs/heads/main",
+                "deepen 1",
+                "done",
+                "",
+                "command=fetch",
+                "|",
+                &format!("want {MAIN}"),
+                &format!("shallow {OLD}"),
+                "deepen 5",
+                "done",
+                "",
+            ],
+        );
+        res?;
+        assert_eq!(
+            out,
+            expected(&[
+                "shallow-info",
+                &format!("shallow {MAIN}"),
+                "|",
+                "wanted-refs",
+                &format!("{MAIN} refs/heads/main"),
+                "|",
+                "packfile",
+                "2:counting objects\n",
+                "1:PACK",
+                "",
+                "shallow-info",
+                &format!("unshallow {OLD}"),
+                "|",
+                "packfile",
+                "2:counting objects\n",
+                "1:PACK",
+                "",
+            ])
+        );
+        assert_eq!(repo.packs[0].0, vec![id(MAIN)], "the wanted ref is resolved");
+        assert_eq!(repo.packs[0].2.shallow, vec![id(MAIN)]);
+        Ok(())
+    }
+
+    #[test]
+    fn errors_are_sent_to_the_client() {
+        let unknown = "0000000000000000000000000000000000000001";
+        let (res, out) = serve(
+            &mut Repository::default(),
+            &["command=fetch", "|", &format!("want {unknown}"), "done", ""],
+        );
+        assert!(matches!(res, Err(upload_pack::Error::NotOurRef { .. })));
+        assert_eq!(out, expected(&[&format!("ERR not our ref {unknown}")]));
+
+        let (res, out) = serve(&mut Repository::default(), &["command=push", ""]);
+        assert!(matches!(res, Err(upload_pack::Error::UnknownCommand { .. })));
+        assert_eq!(out, expected(&["ERR The command 'push' is unknown"]));
+
+        let (res, _out) = serve(
+            &mut Repository::default(),
+            &["command=ls-refs", "object-format=sha256", ""],
+        );
+        assert!(matches!(res, Err(upload_pack::Error::ObjectFormat { .. })));
+    }
+
+    #[test]
+    fn stateless() -> crate::Result {
+        let mut out = Vec::new();
+        upload_pack::serve_stateless(
+            &mut Repository::default(),
+            super::request(&[
+                "command=ls-refs",
+                "|",
+                "ref-prefix refs/tags/",
+                "",
+                "command=ls-refs",
+                "",
+            ])
+            .as_slice(),
+            &mut out,
+            &Default::default(),
+        )?;
+        assert_eq!(
+            super::response(&out),
+            [format!("{TAG} refs/tags/v1"), "".into()],
+            "only the first request is answered, without capability advertisement"
+        );
+        Ok(())
+    }
+}
+
+mod arguments {
+    use gix_protocol::upload_pack::{fetch, ls_refs};
+
+    use super::{id, MAIN, OLD};
+
+    #[test]
+    fn fetch() -> crate::Result {
+        let args = fetch::Arguments::from_lines(&[
+            format!("want {MAIN}").into(),
+            format!("have {OLD}").into(),
+            "thin-pack".into(),
+            "ofs-delta".into(),
+            "include-tag".into(),
+            "deepen-since 1234".into(),
+            "deepen-not refs/heads/old".into(),
+            "filter blob:limit=2k".into(),
+        ])?;
+        assert_eq!(
+            args,
+            fetch::Arguments {
+                wants: vec![id(MAIN)],
+                haves: vec![id(OLD)],
+                thin_pack: true,
+                ofs_delta: true,
+                include_tag: true,
+                deepen_since: Some(1234),
+                deepen_not: vec!["refs/heads/old".into()],
+                filter: Some(fetch::Filter::BlobLimit(2048)),
+                ..Default::default()
+            }
+        );
+        assert!(args.is_deepening());
+
+        for invalid in ["want abc", "deepen x", "unknown", "done now"] {
+            assert!(fetch::Arguments::from_lines(&[invalid.into()]).is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn filters() -> crate::Result {
+        assert_eq!(fetch::Filter::from_spec("blob:none".into())?, fetch::Filter::BlobNone);
+        assert_eq!(
+            fetch::Filter::from_spec("blob:limit=1m".into())?,
+            fetch::Filter::BlobLimit(1024 * 1024)
+        );
+        assert_eq!(
+            fetch::Filter::from_spec("blob:limit=100".into())?,
+            fetch::Filter::BlobLimit(100)
+        );
+        for unsupported in ["tree:0", "blob:limit=", "blob:limit=1x", "sparse:oid=abc"] {
+            assert!(fetch::Filter::from_spec(unsupported.into()).is_err(), "{unsupported}");
+        }
+        assert!(fetch::Filter::BlobLimit(10).includes_blob(10));
+        assert!(!fetch::Filter::BlobLimit(10).includes_blob(11));
+        assert!(!fetch::Filter::BlobNone.includes_blob(0));
+        Ok(())
+    }
+
+    #[test]
+    fn ls_refs() -> crate::Result {
+        let args = ls_refs::Arguments::from_lines(&["peel".into(), "ref-prefix refs/tags/".into()])?;
+        assert_eq!(
+            args,
+            ls_refs::Arguments {
+                peel: true,
+                prefixes: vec!["refs/tags/".into()],
+                ..Default::default()
+            }
+        );
+        assert!(ls_refs::Arguments::from_lines(&["ref-prefix".into()]).is_err());
+        Ok(())
+    }
+}
+
+mod daemon {
+    use gix_protocol::{
+        transport::{Protocol, Service},
+        upload_pack::daemon::Request,
+    };
+
+    #[test]
+    fn request() -> crate::Result {
+        let mut line = Vec::new();
+        gix_packetline::encode::data_to_write(
+            b"git-upload-pack /project.git\0host=example.com:9418\0\0version=2\0",
+            &mut line,
+        )?;
+        let request = Request::read(line.as_slice())?;
+        assert_eq!(
+            request,
+            Request {
+                service: Service::UploadPack,
+                path: "/project.git".into(),
+                host: Some("example.com:9418".into()),
+                extra_parameters: vec!["version=2".into()],
+            }
+        );
+        assert_eq!(request.protocol(), Protocol::V2);
+
+        let request = Request::from_bytes(b"git-receive-pack /project.git\0").expect("valid");
+        assert_eq!(request.service, Service::ReceivePack);
+        assert_eq!(request.host, None);
+        assert_eq!(request.protocol(), Protocol::V0);
+
+        assert_eq!(Request::from_bytes(b"git-unknown /project.git\0"), None);
+        assert_eq!(Request::from_bytes(b"git-upload-pack\0"), None);
+        Ok(())
+    }
+}
+
+mod http {
+    use gix_protocol::upload_pack::http::{is_v2, serve, Endpoint};
+
+    use super::Repository;
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(
+            Endpoint::from_request("GET", "/repo.git/info/refs", Some("service=git-upload-pack")),
+            Some(("/repo.git", Endpoint::Advertisement))
+        );
+        assert_eq!(
+            Endpoint::from_request("GET", "/repo.git/info/refs", Some("service=git-receive-pack")),
+            None
+        );
+        assert_eq!(Endpoint::from_request("GET", "/repo.git/info/refs", None), None);
+        assert_eq!(
+            Endpoint::from_request("POST", "/repo.git/git-upload-pack", None),
+            Some(("/repo.git", Endpoint::UploadPack))
+        );
+        assert_eq!(Endpoint::from_request("PUT", "/repo.git/git-upload-pack", None), None);
+        assert_eq!(
+            Endpoint::UploadPack.content_type(),
+            "application/x-git-upload-pack-result"
+        );
+
+        assert!(is_v2("version=2"));
+        assert!(is_v2("object-format=sha1:version=2"));
+        assert!(!is_v2("version=1"));
+    }
+
+    #[test]
+    fn advertisement() -> crate::Result {
+        let mut out = Vec::new();
+        serve(
+            Endpoint::Advertisement,
+            &mut Repository::default(),
+            std::io::empty(),
+            &mut out,
+            &gix_protocol::upload_pack::Options {
+                agent: "git/gix-test".into(),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(super::response(&out), super::capabilities());
+        Ok(())
+    }
+}
@@ -0,0 +1,480 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+use gix_sequencer::{
+    rebase::{State, UpdateRef},
+    todo::{FixupMessage, Instruction},
+};
+
+use crate::{Delegate, DelegateError, Error, Options, Outcome, Stop};
+
+/// Carry out the instructions in the todo list of `state` one by one, starting at `head`, which is the current commit,
+/// until all are done or the rebase has to stop.
+///
+/// Use `objects` to lookup commits and trees, `write` to write new commits, and `delegate` for all other operations.
+/// All instructions that were carried out are moved to the list of done instructions, and `state` is updated to
+/// reflect why the rebase stopped. It's meant to be persisted so the rebase can be resumed later.
+///
+/// When resuming a stopped rebase, `resolved_tree` is the tree the user prepared in the meantime, typically the tree
+/// of the index. After conflicts it's used as tree of the commit that couldn't be created before, and when stopped
+/// for editing it's used to amend the stopped-at commit if it changed. In all other cases, it must be the tree of `head`
+/// or `None`.
+pub fn run<E>(
+    state: &mut State,
+    head: ObjectId,
+    resolved_tree: Option<ObjectId>,
+    objects: &impl gix_object::Find,
+    write: impl FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+    delegate: &mut dyn Delegate,
+    options: Options<'_>,
+) -> Result<Outcome, Error>
+where
+    E: Into<DelegateError>,
+{
+    let mut ctx = Context {
+        objects,
+        write,
+        delegate,
+        options,
+        buf: Vec::new(),
+    };
+    let mut head = match ctx.resume(state, head, resolved_tree)? {
+        Ok(head) => head,
+        Err(outcome) => return Ok(outcome),
+    };
+
+    while !state.todo.instructions.is_empty() {
+        let instruction = state.todo.instructions.remove(0);
+        state.done.instructions.push(instruction.clone());
+        match instruction {
+            Instruction::Comment(_) | Instruction::Noop | Instruction::Drop { .. } => {}
+            Instruction::Pick { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. } => {
+                let original = ctx.commit(commit)?;
+                let fast_forward = !ctx.options.force && original.parents.as_slice() == [head];
+                let tree = if fast_forward {
+                    original.tree
+                } else {
+                    let merge = ctx.cherry_pick(head, commit, &original)?;
+                    if merge.has_conflicts() {
+                        state.stopped_at = Some(commit);
+                        state.author = Some(original.author);
+                        state.message = Some(original.message);
+                        return Ok(stopped(head, Stop::Conflict { merge }));
+                    }
+                    if merge.tree == ctx.tree_of(head)? && original.tree != ctx.parent_tree(&original)? {
+                        // The changes are already present, so the commit would become empty.
+                        continue;
+                    }
+                    merge.tree
+                };
+                let message = if matches!(instruction, Instruction::Reword { .. }) {
+                    ctx.edit_message(original.message.as_ref())?
+                } else {
+                    original.message.clone()
+                };
+                head = if fast_forward && message == original.message {
+                    commit
+                } else {
+                    ctx.write_commit(
+                        tree,
+                        vec![head],
+                        original.author.clone(),
+                        message,
+                        original.encoding.clone(),
+                    )?
+                };
+                if matches!(instruction, Instruction::Edit { .. }) {
+                    return Ok(stop_for_edit(state, commit, head, original));
+                }
+            }
+            Instruction::Squash { commit, .. } | Instruction::Fixup { commit, .. } => {
+                if !has_previous_commit(state) {
+                    return Err(Error::NothingToFixUp {
+                        command: instruction.command_name().expect("not a comment"),
+                    });
+                }
+                let original = ctx.commit(commit)?;
+                let previous = ctx.commit(head)?;
+                let merge = ctx.cherry_pick(head, commit, &original)?;
+
+                let mut message = match state.squash_message.take() {
+                    Some(message) if !state.current_fixups.instructions.is_empty() => message,
+                    _ => previous.message.clone(),
+                };
+                match &instruction {
+                    Instruction::Squash { .. } => {
+                        let other = strip_fixup_subject(original.message.as_ref());
+                        if !other.trim().is_empty() {
+                            message = message.trim_end().into();
+                            message.push_str("\n\n");
+                            message.push_str(other);
+                        }
+                    }
+                    Instruction::Fixup {
+                        message: FixupMessage::Use | FixupMessage::UseAndEdit,
+                        ..
+                    } => message = strip_fixup_subject(original.message.as_ref()).into(),
+                    _ => {}
+                }
+                state.current_fixups.instructions.push(instruction);
+
+                if merge.has_conflicts() {
+                    state.stopped_at = Some(commit);
+                    state.author = Some(previous.author);
+                    state.squash_message = Some(message.clone());
+                    state.message = Some(message);
+                    return Ok(stopped(head, Stop::Conflict { merge }));
+                }
+                head = ctx.finish_fixup(state, previous, merge.tree, message)?;
+            }
+            Instruction::Exec { command } => {
+                let (success, new_head) = ctx.delegate.exec(command.as_ref(), head).map_err(Error::Delegate)?;
+                head = new_head;
+                if !success {
+                    return Ok(stopped(head, Stop::ExecFailed { command }));
+                }
+            }
+            Instruction::Break => return Ok(stopped(head, Stop::Break)),
+            Instruction::Label { name } => ctx.delegate.set_label(name.as_ref(), head).map_err(Error::Delegate)?,
+            Instruction::Reset { name, .. } => head = ctx.resolve_label(name)?,
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary,
+            } => {
+                let (label, other) = match labels.as_slice() {
+                    [label] => (label.clone(), ctx.resolve_label(label.clone())?),
+                    _ => return Err(Error::OctopusMerge { labels }),
+                };
+                let original = commit.map(|id| ctx.commit(id)).transpose()?;
+                if let Some((commit, original)) = commit.zip(original.as_ref()) {
+                    if !ctx.options.force && original.parents.as_slice() == [head, other] {
+                        head = commit;
+                        continue;
+                    }
+                }
+
+                let merge = ctx
+                    .delegate
+                    .merge_commits(
+                        head,
+                        other,
+                        gix_merge::blob::Labels {
+                            ancestor: None,
+                            current: Some("HEAD".into()),
+                            other: Some(label.as_ref()),
+                        },
+                    )
+                    .map_err(Error::Delegate)?;
+                let (author, message) = match original {
+                    Some(original) => (original.author, original.message),
+                    None => (
+                        ctx.options.committer.to_owned(),
+                        match summary.strip_prefix(b"#").map(ByteSlice::trim) {
+                            Some(summary) if !summary.is_empty() => format!("{}\n", summary.as_bstr()).into(),
+                            _ => format!("Merge branch '{label}'\n").into(),
+                        },
+                    ),
+                };
+                if merge.has_conflicts() {
+                    state.stopped_at = commit;
+                    state.author = Some(author);
+                    state.message = Some(message);
+                    return Ok(stopped(head, Stop::Conflict { merge }));
+                }
+                let message = if edit_message {
+                    ctx.edit_message(message.as_ref())?
+                } else {
+                    message
+                };
+                head = ctx.write_commit(merge.tree, vec![head, other], author, message, None)?;
+            }
+            Instruction::UpdateRef { name } => match state.update_refs.iter_mut().find(|update| update.name == name) {
+                Some(update) => update.after = head,
+                None => state.update_refs.push(UpdateRef {
+                    name,
+                    before: ObjectId::null(head.kind()),
+                    after: head,
+                }),
+            },
+        }
+    }
+    Ok(Outcome::Finished { head })
+}
+
+struct Context<'a, Find, Write> {
+    objects: &'a Find,
+    write: Write,
+    delegate: &'a mut dyn Delegate,
+    options: Options<'a>,
+    buf: Vec<u8>,
+}
+
+impl<'a, Find, Write, E> Context<'a, Find, Write>
+where
+    Find: gix_object::Find,
+    Write: FnMut(&dyn gix_object::WriteTo) -> Result<ObjectId, E>,
+    E: Into<DelegateError>,
+{
+    /// Handle what the user did while the rebase was stopped, and return the commit to continue with,
+    /// or the outcome if the rebase has to stop again.
+    fn resume(
+        &mut self,
+        state: &mut State,
+        head: ObjectId,
+        resolved_tree: Option<ObjectId>,
+    ) -> Result<Result<ObjectId, Outcome>, Error> {
+        let head_tree = self.tree_of(head)?;
+        let has_changes = resolved_tree.map_or(Ok(false), |tree| {
+            if tree == head_tree {
+                Ok(false)
+            } else if (state.author.is_some() && state.amend.is_none()) || state.amend == Some(head) {
+                Ok(true)
+            } else {
+                Err(Error::UncommittedChanges)
+            }
+        })?;
+
+        let Some(author) = state.author.take() else {
+            clear_stop(state);
+            return Ok(Ok(head));
+        };
+        let message = state.message.take().unwrap_or_default();
+        if state.amend.is_some() {
+            clear_stop(state);
+            if !has_changes {
+                return Ok(Ok(head));
+            }
+            let previous = self.commit(head)?;
+            let tree = resolved_tree.expect("changes imply a tree");
+            return Ok(Ok(self.write_commit(
+                tree,
+                previous.parents.to_vec(),
+                previous.author,
+                previous.message,
+                previous.encoding,
+            )?));
+        }
+
+        // The rebase stopped due to conflicts, and the commit still has to be created.
+        let tree = resolved_tree.ok_or(Error::MissingResolution)?;
+        let stopped_at = state.stopped_at.take();
+        let current = state.current().cloned();
+        let head = match current {
+            Some(Instruction::Squash { .. } | Instruction::Fixup { .. }) => {
+                let previous = self.commit(head)?;
+                self.finish_fixup(state, previous, tree, message)?
+            }
+            Some(Instruction::Merge {
+                labels, edit_message, ..
+            }) => {
+                let other = match labels.first() {
+                    Some(label) => self.resolve_label(label.clone())?,
+                    None => return Err(Error::OctopusMerge { labels }),
+                };
+                let message = if edit_message {
+                    self.edit_message(message.as_ref())?
+                } else {
+                    message
+                };
+                self.write_commit(tree, vec![head, other], author, message, None)?
+            }
+            Some(
+                ref instruction @ (Instruction::Pick { .. } | Instruction::Reword { .. } | Instruction::Edit { .. }),
+            ) => {
+                let commit = if tree == head_tree {
+                    head
+                } else {
+                    let message = if matches!(instruction, Instruction::Reword { .. }) {
+                        self.edit_message(message.as_ref())?
+                    } else {
+                        message
+                    };
+                    self.write_commit(tree, vec![head], author, message, None)?
+                };
+                if let (Instruction::Edit { commit: original, .. }, true) = (instruction, commit != head) {
+                    let original = self.commit(*original)?;
+                    return Ok(Err(stop_for_edit(
+                        state,
+                        stopped_at.unwrap_or(*instruction.commit().expect("edit has a commit")),
+                        commit,
+                        original,
+                    )));
+                }
+                commit
+            }
+            _ => self.write_commit(tree, vec![head], author, message, None)?,
+        };
+        Ok(Ok(head))
+    }
+
+    /// Create the commit for a `fixup` or `squash` instruction by amending `previous`, which is the current commit,
+    /// with `tree` and `message`, and let the user edit the message if it's the last of a chain of fixups that needs it.
+    fn finish_fixup(
+        &mut self,
+        state: &mut State,
+        previous: gix_object::Commit,
+        tree: ObjectId,
+        message: BString,
+    ) -> Result<ObjectId, Error> {
+        let is_last = !matches!(state.todo.commands().next(), Some(next) if next.is_fixup_or_squash());
+        let message = if is_last {
+            let needs_edit = state.current_fixups.commands().any(|instruction| {
+                matches!(
+                    instruction,
+                    Instruction::Squash { .. }
+                        | Instruction::Fixup {
+                            message: FixupMessage::UseAndEdit,
+                            ..
+                        }
+                )
+            });
+            state.current_fixups.instructions.clear();
+            state.squash_message = None;
+            if needs_edit {
+                self.edit_message(message.as_ref())?
+            } else {
+                message
+            }
+        } else {
+            state.squash_message = Some(message.clone());
+            message
+        };
+        self.write_commit(
+            tree,
+            previous.parents.to_vec(),
+            previous.author,
+            message,
+            previous.encoding,
+        )
+    }
+
+    /// Apply the changes of `commit`, whose object is `original`, onto `head`.
+    fn cherry_pick(
+        &mut self,
+        head: ObjectId,
+        commit: ObjectId,
+        original: &gix_object::Commit,
+    ) -> Result<gix_merge::tree::Outcome, Error> {
+        let base = self.parent_tree(original)?;
+        let head_tree = self.tree_of(head)?;
+        let summary = gix_object::commit::MessageRef::from_bytes(&original.message).summary();
+        let label = format!("{} ({})", commit.to_hex_with_len(7), summary);
+        self.delegate
+            .merge_trees(
+                &base,
+                &head_tree,
+                &original.tree,
+                gix_merge::blob::Labels {
+                    ancestor: None,
+                    current: Some("HEAD".into()),
+                    other: Some(label.as_str().into()),
+                },
+            )
+            .map_err(Error::Delegate)
+    }
+
+    fn commit(&mut self, id: ObjectId) -> Result<gix_object::Commit, Error> {
+        Ok(self.objects.find_commit(&id, &mut self.buf)?.into())
+    }
+
+    fn tree_of(&mut self, commit: ObjectId) -> Result<ObjectId, Error> {
+        Ok(self.objects.find_commit(&commit, &mut self.buf)?.tree())
+    }
+
+    /// Return the tree of the first parent of `commit`, or the empty tree if it has no parent.
+    fn parent_tree(&mut self, commit: &gix_object::Commit) -> Result<ObjectId, Error> {
+        match commit.parents.first() {
+            Some(parent) => self.tree_of(*parent),
+            None => (self.write)(&gix_object::Tree::empty()).map_err(|err| Error::WriteCommit(err.into())),
+        }
+    }
+
+    fn resolve_label(&mut self, name: BString) -> Result<ObjectId, Error> {
+        self.delegate
+            .resolve_label(name.as_ref())
+            .map_err(Error::Delegate)?
+            .ok_or(Error::UnknownLabel { name })
+    }
+
+    fn edit_message(&mut self, message: &BStr) -> Result<BString, Error> {
+        self.delegate.edit_message(message).map_err(Error::Delegate)
+    }
+
+    fn write_commit(
+        &mut self,
+        tree: ObjectId,
+        parents: Vec<ObjectId>,
+        author: gix_actor::Signature,
+        message: BString,
+        encoding: Option<BString>,
+    ) -> Result<ObjectId, Error> {
+        let mut commit = gix_object::Commit {
+            tree,
+            parents: parents.into(),
+            author,
+            committer: self.options.committer.to_owned(),
+            encoding,
+            message,
+            extra_headers: Vec::new(),
+        };
+        self.delegate.sign_commit(&mut commit).map_err(Error::Delegate)?;
+        (self.write)(&commit).map_err(|err| Error::WriteCommit(err.into()))
+    }
+}
+
+fn stopped(head: ObjectId, reason: Stop) -> Outcome {
+    Outcome::Stopped { head, reason }
+}
+
+/// Record in `state` that the rebase stops to allow amending `rewritten`, which is the rewritten version of `commit`,
+/// whose object is `original`.
+fn stop_for_edit(state: &mut State, commit: ObjectId, rewritten: ObjectId, original: gix_object::Commit) -> Outcome {
+    state.stopped_at = Some(commit);
+    state.amend = Some(rewritten);
+    state.author = Some(original.author);
+    state.message = Some(original.message);
+    stopped(rewritten, Stop::Edit { commit: rewritten })
+}
+
+fn clear_stop(state: &mut State) {
+    state.stopped_at = None;
+    state.amend = None;
+    state.author = None;
+    state.message = None;
+}
+
+/// Return `true` if an instruction before the current one created a commit that can be amended.
+fn has_previous_commit(state: &State) -> bool {
+    let done = state.done.num_commands();
+    state.done.commands().take(done.saturating_sub(1)).any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::Pick { .. }
+                | Instruction::Reword { .. }
+                | Instruction::Edit { .. }
+                | Instruction::Squash { .. }
+                | Instruction::Fixup { .. }
+                | Instruction::Merge { .. }
+        )
+    })
+}
+
+/// Remove the first paragraph of `message` if it's the subject of a commit created with `git commit --fixup` or
+/// `git commit --squash`, as it's only meant to identify the commit to fix up.
+fn strip_fixup_subject(message: &BStr) -> &BStr {
+    if ["fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| message.starts_with(prefix.as_bytes()))
+    {
+        match message.find("\n\n") {
+            Some(pos) => message[pos..].trim_start().as_bstr(),
+            None => "".into(),
+        }
+    } else {
+        message
+    }
+}
@@ -1,2 +1,136 @@
-#![deny(rust_2018_idioms)]
+//! Drive rebases by carrying out the instructions of a [todo list](gix_sequencer::todo::List), one at a time,
+//! similar to what `git rebase --interactive` does.
+//!
+//! A rebase starts with a todo list [built](todo::build()) from the commits to rebase, and is then [run](run()) until
+//! it finishes or stops, for instance because of conflicts or an `edit` instruction. Its [state](gix_sequencer::rebase::State)
+//! is meant to be persisted while stopped so that it can be resumed once the user is done, or aborted.
+//!
+//! Everything that needs access to a repository beyond reading and writing objects is handled by a [`Delegate`].
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::{BStr, BString};
+use gix_hash::ObjectId;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod todo;
+
+mod function;
+pub use function::run;
+
+/// The error type used by [`Delegate`] implementations.
+pub type DelegateError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Implementations carry out all operations of a rebase that need more than access to objects.
+pub trait Delegate {
+    /// Perform a three-way merge of `ours` and `theirs` with `ancestor` as common base, and return the outcome including
+    /// the merged tree, annotating conflicts with `labels`.
+    fn merge_trees(
+        &mut self,
+        ancestor: &gix_hash::oid,
+        ours: &gix_hash::oid,
+        theirs: &gix_hash::oid,
+        labels: gix_merge::blob::Labels<'_>,
+    ) -> Result<gix_merge::tree::Outcome, DelegateError>;
+
+    /// Merge the commits `ours` and `theirs` using their merge-base, annotating conflicts with `labels`.
+    fn merge_commits(
+        &mut self,
+        ours: ObjectId,
+        theirs: ObjectId,
+        labels: gix_merge::blob::Labels<'_>,
+    ) -> Result<gix_merge::tree::Outcome, DelegateError>;
+
+    /// Let the user edit `message`, as needed for `reword` and `squash`, and return the edited message.
+    fn edit_message(&mut self, message: &BStr) -> Result<BString, DelegateError>;
+
+    /// Run `command` with the shell after making `head` the current commit, and return `true` if it succeeded,
+    /// along with the current commit after it ran, which is `head` unless the command created new commits.
+    fn exec(&mut self, command: &BStr, head: ObjectId) -> Result<(bool, ObjectId), DelegateError>;
+
+    /// Associate the label `name` with `commit`, so it can be [resolved](Self::resolve_label()) later.
+    fn set_label(&mut self, name: &BStr, commit: ObjectId) -> Result<(), DelegateError>;
+
+    /// Return the commit associated with the label `name`, or resolve `name` as revision if it's no label,
+    /// or return `None` if it couldn't be resolved.
+    fn resolve_label(&mut self, name: &BStr) -> Result<Option<ObjectId>, DelegateError>;
+
+    /// Sign the newly created `commit` before it's written if signing is configured, typically by adding
+    /// a `gpgsig` header to its extra headers, or leave it unchanged otherwise.
+    fn sign_commit(&mut self, commit: &mut gix_object::Commit) -> Result<(), DelegateError>;
+}
+
+/// Options for use in [`run()`].
+#[derive(Debug, Copy, Clone)]
+pub struct Options<'a> {
+    /// The committer of all newly created commits.
+    pub committer: gix_actor::SignatureRef<'a>,
+    /// If `true`, always create new commits even if the original commits could be used as is, similar to
+    /// `git rebase --force-rebase`.
+    pub force: bool,
+}
+
+/// The result of [`run()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// All instructions were carried out.
+    Finished {
+        /// The commit that the rebased branch should point to.
+        head: ObjectId,
+    },
+    /// The rebase stopped before all instructions were carried out, and can be resumed with [`run()`] once
+    /// the user is done.
+    Stopped {
+        /// The current commit, which is what `HEAD` should point to.
+        head: ObjectId,
+        /// The reason for stopping.
+        reason: Stop,
+    },
+}
+
+/// The reason for a rebase to [stop](Outcome::Stopped).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// An `edit` instruction was carried out, and the rewritten `commit` can now be amended.
+    Edit {
+        /// The rewritten commit.
+        commit: ObjectId,
+    },
+    /// A `break` instruction was encountered.
+    Break,
+    /// Picking or merging a commit caused conflicts which have to be resolved by the user.
+    ///
+    /// The merged tree with conflict markers should be placed into the worktree and index for the user to resolve.
+    Conflict {
+        /// The outcome of the merge with conflicts.
+        merge: gix_merge::tree::Outcome,
+    },
+    /// The `command` of an `exec` instruction failed.
+    ExecFailed {
+        /// The command that failed.
+        command: BString,
+    },
+}
+
+/// The error returned by [`run()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not write commit")]
+    WriteCommit(#[source] DelegateError),
+    #[error("The rebase stopped because of conflicts, but no tree with the resolution was provided")]
+    MissingResolution,
+    #[error("There are uncommitted changes which have to be committed or discarded before continuing")]
+    UncommittedChanges,
+    #[error("The label '{name}' could not be resolved")]
+    UnknownLabel { name: BString },
+    #[error("Cannot merge more than one commit at a time, got labels {labels:?}")]
+    OctopusMerge { labels: Vec<BString> },
+    #[error("Cannot '{command}' without a previous commit")]
+    NothingToFixUp { command: &'static str },
+    #[error(transparent)]
+    Delegate(DelegateError),
+}
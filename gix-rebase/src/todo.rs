@@ -0,0 +1,155 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_sequencer::{
+    rebase::UpdateRef,
+    todo::{FixupMessage, Instruction, List},
+};
+
+/// A commit to rebase, as input to [`build()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The first line of its message.
+    pub summary: BString,
+}
+
+/// Options for use in [`build()`].
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// If `true`, move commits whose summary starts with `fixup! `, `squash! ` or `amend! ` right after the commit
+    /// they refer to, and turn them into `fixup`, `squash` or `fixup -C` respectively, similar to `git rebase --autosquash`.
+    pub autosquash: bool,
+    /// Full reference names along with the commit they point to, for which `update-ref` instructions are added after
+    /// the commit, similar to `git rebase --update-refs`.
+    ///
+    /// References that don't point to one of the commits to rebase are ignored. The reference that is rebased
+    /// shouldn't be among them.
+    pub update_refs: Vec<(BString, ObjectId)>,
+}
+
+/// Create a todo list to `pick` all `commits`, ordered from oldest to newest, and apply all transformations
+/// as configured in `options`.
+///
+/// Return the todo list along with the references to update once the rebase is done, for use in
+/// [`State::update_refs`](gix_sequencer::rebase::State::update_refs).
+pub fn build(commits: &[Commit], options: &Options) -> (List, Vec<UpdateRef>) {
+    let mut instructions = Vec::with_capacity(commits.len());
+    let mut update_refs = Vec::new();
+    for commit in commits {
+        instructions.push(Instruction::Pick {
+            commit: commit.id,
+            summary: commit.summary.clone(),
+        });
+        for (name, _) in options.update_refs.iter().filter(|(_, id)| *id == commit.id) {
+            instructions.push(Instruction::UpdateRef { name: name.clone() });
+            update_refs.push(UpdateRef {
+                name: name.clone(),
+                before: commit.id,
+                after: ObjectId::null(commit.id.kind()),
+            });
+        }
+    }
+    if options.autosquash {
+        instructions = autosquash(instructions);
+    }
+    (List { instructions }, update_refs)
+}
+
+/// Rearrange `instructions` like `git` does, by forming a chain of fixups behind each commit that is the target of one.
+fn autosquash(mut instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let summaries: Vec<Option<BString>> = instructions
+        .iter()
+        .map(|insn| match insn {
+            Instruction::Pick { summary, .. } => Some(summary.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut first_with_summary = std::collections::HashMap::<&BStr, usize>::new();
+    let (mut next, mut tail) = (vec![None; instructions.len()], vec![None; instructions.len()]);
+    let mut is_fixup = vec![false; instructions.len()];
+
+    for idx in 0..instructions.len() {
+        let Some(summary) = summaries[idx].as_ref() else {
+            continue;
+        };
+        let command = ["fixup! ", "squash! ", "amend! "]
+            .into_iter()
+            .find(|prefix| summary.starts_with(prefix.as_bytes()));
+        let target = command.and_then(|prefix| {
+            let subject = strip_fixup_prefixes(summary[prefix.len()..].as_bstr());
+            first_with_summary.get(subject).copied().or_else(|| {
+                let by_id = (subject.len() >= 4 && !subject.contains(&b' '))
+                    .then(|| {
+                        (0..idx).find(|&other| {
+                            matches!(instructions[other].commit(), Some(id) if id.to_hex().to_string().as_bytes().starts_with(subject))
+                        })
+                    })
+                    .flatten();
+                by_id.or_else(|| {
+                    (0..idx).find(|&other| {
+                        matches!(&summaries[other], Some(summary) if summary.starts_with(subject))
+                    })
+                })
+            })
+        });
+        match (command, target) {
+            (Some(prefix), Some(target)) => {
+                let Instruction::Pick { commit, summary } =
+                    std::mem::replace(&mut instructions[idx], Instruction::Noop)
+                else {
+                    unreachable!("only picks have a summary")
+                };
+                instructions[idx] = match prefix {
+                    "fixup! " => Instruction::Fixup {
+                        commit,
+                        message: FixupMessage::Discard,
+                        summary,
+                    },
+                    "amend! " => Instruction::Fixup {
+                        commit,
+                        message: FixupMessage::Use,
+                        summary,
+                    },
+                    _ => Instruction::Squash { commit, summary },
+                };
+                is_fixup[idx] = true;
+                let after = tail[target].unwrap_or(target);
+                next[idx] = next[after];
+                next[after] = Some(idx);
+                tail[target] = Some(idx);
+            }
+            _ => {
+                first_with_summary.entry(summary.as_bstr()).or_insert(idx);
+            }
+        }
+    }
+
+    let mut slots: Vec<_> = instructions.into_iter().map(Some).collect();
+    let mut out = Vec::with_capacity(slots.len());
+    for (idx, is_fixup) in is_fixup.into_iter().enumerate() {
+        if is_fixup {
+            continue;
+        }
+        let mut cur = Some(idx);
+        while let Some(idx) = cur {
+            out.extend(slots[idx].take());
+            cur = next[idx];
+        }
+    }
+    out
+}
+
+/// Remove all leading `fixup! `, `squash! ` and `amend! ` prefixes from `subject`, along with leading whitespace.
+fn strip_fixup_prefixes(mut subject: &BStr) -> &BStr {
+    loop {
+        subject = subject.trim_start().as_bstr();
+        match ["fixup! ", "squash! ", "amend! "]
+            .into_iter()
+            .find_map(|prefix| subject.strip_prefix(prefix.as_bytes()))
+        {
+            Some(rest) => subject = rest.as_bstr(),
+            None => return subject,
+        }
+    }
+}
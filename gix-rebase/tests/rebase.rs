@@ -0,0 +1,109 @@
+mod todo {
+    use gix_hash::ObjectId;
+    use gix_rebase::todo::{build, Commit, Options};
+    use gix_sequencer::todo::{FixupMessage, Instruction};
+
+    fn id(n: u8) -> ObjectId {
+        ObjectId::from_bytes_or_panic(&[n; 20])
+    }
+
+    fn commits(summaries: &[&str]) -> Vec<Commit> {
+        summaries
+            .iter()
+            .enumerate()
+            .map(|(n, summary)| Commit {
+                id: id(n as u8 + 1),
+                summary: (*summary).into(),
+            })
+            .collect()
+    }
+
+    /// Render `instructions` as `<command> <n>`, with `n` being the number of the commit, or the reference name.
+    fn render(instructions: &[Instruction]) -> Vec<String> {
+        instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::UpdateRef { name } => format!("update-ref {name}"),
+                Instruction::Fixup {
+                    commit,
+                    message: FixupMessage::Use,
+                    ..
+                } => format!("fixup -C {}", commit.as_bytes()[0]),
+                other => format!(
+                    "{} {}",
+                    other.command_name().expect("no comments"),
+                    other.commit().expect("commit").as_bytes()[0]
+                ),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn picks_all_commits_in_order() {
+        let (list, update_refs) = build(&commits(&["one", "fixup! one"]), &Default::default());
+        assert_eq!(
+            render(&list.instructions),
+            ["pick 1", "pick 2"],
+            "autosquash is off by default"
+        );
+        assert!(update_refs.is_empty());
+    }
+
+    #[test]
+    fn autosquash() {
+        let (list, _) = build(
+            &commits(&[
+                "one",
+                "two words",
+                "three",
+                "fixup! two words",
+                "squash! one",
+                "amend! fixup! two words",
+                "fixup! two",
+                "fixup! 0303",
+                "fixup! unknown",
+            ]),
+            &Options {
+                autosquash: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            render(&list.instructions),
+            [
+                "pick 1",
+                "squash 5",
+                "pick 2",
+                "fixup 4",
+                "fixup -C 6",
+                "fixup 7",
+                "pick 3",
+                "fixup 8",
+                "pick 9"
+            ],
+            "targets are found by summary, its prefix or by id, and fixups are applied in order"
+        );
+    }
+
+    #[test]
+    fn update_refs_are_added_after_the_commit_and_its_fixups() {
+        let (list, update_refs) = build(
+            &commits(&["one", "two", "fixup! one"]),
+            &Options {
+                autosquash: true,
+                update_refs: vec![
+                    ("refs/heads/first".into(), id(1)),
+                    ("refs/heads/elsewhere".into(), id(42)),
+                ],
+            },
+        );
+        assert_eq!(
+            render(&list.instructions),
+            ["pick 1", "fixup 3", "update-ref refs/heads/first", "pick 2"]
+        );
+        assert_eq!(update_refs.len(), 1, "references pointing elsewhere are ignored");
+        assert_eq!(update_refs[0].name, "refs/heads/first");
+        assert_eq!(update_refs[0].before, id(1));
+        assert!(update_refs[0].after.is_null());
+    }
+}
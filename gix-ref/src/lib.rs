@@ -14,6 +14,9 @@
 //!     * one reference maps to a file on disk
 //!   * **packed**
 //!     * references are stored in a single human-readable file, along with their targets if they are symbolic.
+//! * **[reftable][reftable::Stack]**
+//!   * references and their logs are stored in a stack of binary tables, which is used by the [file store][file::Store]
+//!     if [configured](store::init::Options::ref_storage).
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -31,7 +34,7 @@ use gix_object::bstr::{BStr, BString};
 
 #[path = "store/mod.rs"]
 mod store_impl;
-pub use store_impl::{file, packed};
+pub use store_impl::{file, packed, reftable};
 
 mod fullname;
 ///
@@ -65,6 +68,7 @@ pub mod store {
     ///
     #[allow(clippy::empty_docs)]
     pub mod init {
+        pub use super::general::init::Error;
 
         /// Options for use during [initialization](crate::file::Store::at).
         #[derive(Debug, Copy, Clone, Default)]
@@ -79,8 +83,21 @@ pub mod store {
             /// to avoid side effects. This only needs to be `true` on Windows, but can be `true` on other platforms
             /// if they need to remain compatible with Windows.
             pub prohibit_windows_device_names: bool,
+            /// How references are stored, as configured by `extensions.refStorage`.
+            pub ref_storage: super::RefStorage,
         }
     }
+    /// The way references and their logs are stored on disk.
+    #[derive(Default, Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Clone, Copy)]
+    pub enum RefStorage {
+        /// Loose references are stored in one file each, along with a `packed-refs` file, and logs are stored in `logs/`.
+        #[default]
+        Files,
+        /// References and their logs are stored in a stack of tables in the `reftable/` directory.
+        ///
+        /// Only `FETCH_HEAD` and `MERGE_HEAD` are still stored as files.
+        Reftable,
+    }
     /// The way a file store handles the reflog
     #[derive(Default, Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Clone, Copy)]
     pub enum WriteReflog {
@@ -95,16 +112,15 @@ pub mod store {
 
     /// A thread-local handle for interacting with a [`Store`][crate::Store] to find and iterate references.
     #[derive(Clone)]
-    #[allow(dead_code)]
-    pub(crate) struct Handle {
+    pub struct Handle {
         /// A way to access shared state with the requirement that interior mutability doesn't leak or is incorporated into error types
         /// if it could. The latter can't happen if references to said internal aren't ever returned.
         state: handle::State,
     }
 
-    #[allow(dead_code)]
     pub(crate) enum State {
         Loose { store: file::Store },
+        Reftable { store: reftable::Store },
     }
 
     pub(crate) mod general;
@@ -112,14 +128,14 @@ pub mod store {
     ///
     #[path = "general/handle/mod.rs"]
     mod handle;
-    use crate::file;
+    use crate::{file, reftable};
     pub use handle::find;
 }
 
-/// The git reference store.
-/// TODO: Figure out if handles are needed at all, which depends on the ref-table implementation.
-#[allow(dead_code)]
-pub(crate) struct Store {
+/// The git reference store, with references stored in files or in a reftable.
+///
+/// Use [handles](store::Handle) to find references.
+pub struct Store {
     inner: store::State,
 }
 
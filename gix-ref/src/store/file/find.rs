@@ -147,6 +147,9 @@ impl file::Store {
         let full_name = precomposed_partial_name
             .unwrap_or(partial_name)
             .construct_full_name_ref(inbetween, path_buf);
+        if let Some(reftable) = self.reftable_for(full_name) {
+            return Ok(reftable.find_stored(full_name, self.namespace.as_ref())?);
+        }
         let content_buf = self.ref_contents(full_name).map_err(|err| Error::ReadFileContents {
             source: err,
             path: self.reference_path(full_name),
@@ -193,40 +196,49 @@ impl file::Store {
     }
 }
 
+/// Return the directory in which the reference with the full `name` is stored, or its log if `is_reflog` is `true`, along
+/// with the name it is stored under there, for a repository at `git_dir` whose shared references are in `common_dir`.
+pub(crate) fn base_dir_and_relative_name<'a, 'b>(
+    git_dir: &'b Path,
+    common_dir: &'b Path,
+    name: &'a FullNameRef,
+    is_reflog: bool,
+) -> (Cow<'b, Path>, &'a FullNameRef) {
+    let linked_git_dir = |worktree_name: &BStr| common_dir.join("worktrees").join(gix_path::from_bstr(worktree_name));
+    name.category_and_short_name()
+        .map(|(c, sn)| {
+            use crate::Category::*;
+            let sn = FullNameRef::new_unchecked(sn);
+            match c {
+                LinkedPseudoRef { name: worktree_name } => is_reflog
+                    .then(|| (linked_git_dir(worktree_name).into(), sn))
+                    .unwrap_or((common_dir.into(), name)),
+                Tag | LocalBranch | RemoteBranch | Note => (common_dir.into(), name),
+                MainRef | MainPseudoRef => (common_dir.into(), sn),
+                LinkedRef { name: worktree_name } => sn
+                    .category()
+                    .map_or(false, |cat| cat.is_worktree_private())
+                    .then(|| {
+                        if is_reflog {
+                            (linked_git_dir(worktree_name).into(), sn)
+                        } else {
+                            (common_dir.into(), name)
+                        }
+                    })
+                    .unwrap_or((common_dir.into(), sn)),
+                PseudoRef | Bisect | Rewritten | WorktreePrivate => (git_dir.into(), name),
+            }
+        })
+        .unwrap_or((common_dir.into(), name))
+}
+
 impl file::Store {
     pub(crate) fn to_base_dir_and_relative_name<'a>(
         &self,
         name: &'a FullNameRef,
         is_reflog: bool,
     ) -> (Cow<'_, Path>, &'a FullNameRef) {
-        let commondir = self.common_dir_resolved();
-        let linked_git_dir =
-            |worktree_name: &BStr| commondir.join("worktrees").join(gix_path::from_bstr(worktree_name));
-        name.category_and_short_name()
-            .map(|(c, sn)| {
-                use crate::Category::*;
-                let sn = FullNameRef::new_unchecked(sn);
-                match c {
-                    LinkedPseudoRef { name: worktree_name } => is_reflog
-                        .then(|| (linked_git_dir(worktree_name).into(), sn))
-                        .unwrap_or((commondir.into(), name)),
-                    Tag | LocalBranch | RemoteBranch | Note => (commondir.into(), name),
-                    MainRef | MainPseudoRef => (commondir.into(), sn),
-                    LinkedRef { name: worktree_name } => sn
-                        .category()
-                        .map_or(false, |cat| cat.is_worktree_private())
-                        .then(|| {
-                            if is_reflog {
-                                (linked_git_dir(worktree_name).into(), sn)
-                            } else {
-                                (commondir.into(), name)
-                            }
-                        })
-                        .unwrap_or((commondir.into(), sn)),
-                    PseudoRef | Bisect | Rewritten | WorktreePrivate => (self.git_dir.as_path().into(), name),
-                }
-            })
-            .unwrap_or((commondir.into(), name))
+        base_dir_and_relative_name(&self.git_dir, self.common_dir_resolved(), name, is_reflog)
     }
 
     /// Implements the logic required to transform a fully qualified refname into a filesystem path
@@ -390,6 +402,8 @@ mod error {
         PackedRef(#[from] packed::find::Error),
         #[error("Could not open the packed refs buffer when trying to find references.")]
         PackedOpen(#[from] packed::buffer::open::Error),
+        #[error("A reftable lookup failed")]
+        Reftable(#[from] crate::reftable::find::Error),
     }
 
     impl From<Infallible> for Error {
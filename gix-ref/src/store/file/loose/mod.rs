@@ -30,7 +30,7 @@ pub mod reference;
 mod init {
     use std::path::PathBuf;
 
-    use crate::store_impl::file;
+    use crate::{store::RefStorage, store_impl::file};
 
     impl file::Store {
         /// Create a new instance at the given `git_dir`, which commonly is a standard git repository with a
@@ -46,10 +46,10 @@ mod init {
                 object_hash,
                 precompose_unicode,
                 prohibit_windows_device_names,
+                ref_storage,
             }: crate::store::init::Options,
         ) -> Self {
             file::Store {
-                git_dir,
                 packed_buffer_mmap_threshold: packed_refs_mmap_threshold(),
                 common_dir: None,
                 write_reflog,
@@ -58,6 +58,9 @@ mod init {
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                reftable: (ref_storage == RefStorage::Reftable)
+                    .then(|| crate::reftable::Store::at(git_dir.clone(), object_hash)),
+                git_dir,
             }
         }
 
@@ -74,18 +77,22 @@ mod init {
                 object_hash,
                 precompose_unicode,
                 prohibit_windows_device_names,
+                ref_storage,
             }: crate::store::init::Options,
         ) -> Self {
             file::Store {
-                git_dir,
                 packed_buffer_mmap_threshold: packed_refs_mmap_threshold(),
-                common_dir: Some(common_dir),
                 write_reflog,
                 namespace: None,
                 prohibit_windows_device_names,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                reftable: (ref_storage == RefStorage::Reftable).then(|| {
+                    crate::reftable::Store::for_linked_worktree(git_dir.clone(), common_dir.clone(), object_hash)
+                }),
+                git_dir,
+                common_dir: Some(common_dir),
             }
         }
     }
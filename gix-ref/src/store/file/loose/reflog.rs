@@ -16,7 +16,11 @@ impl file::Store {
         Name: TryInto<&'a FullNameRef, Error = E>,
         crate::name::Error: From<E>,
     {
-        Ok(self.reflog_path(name.try_into()?).is_file())
+        let name = name.try_into()?;
+        if let Some(reftable) = self.reftable_for(name) {
+            return Ok(reftable.log_exists(name, self.namespace.as_ref()));
+        }
+        Ok(self.reflog_path(name).is_file())
     }
 
     /// Return a reflog reverse iterator for the given fully qualified `name`, reading chunks from the back into the fixed buffer `buf`.
@@ -27,18 +31,29 @@ impl file::Store {
         &self,
         name: Name,
         buf: &'b mut [u8],
-    ) -> Result<Option<log::iter::Reverse<'b, std::fs::File>>, Error>
+    ) -> Result<Option<log::iter::Reverse<'b, log::iter::Source>>, Error>
     where
         Name: TryInto<&'a FullNameRef, Error = E>,
         crate::name::Error: From<E>,
     {
         let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        if let Some(reftable) = self.reftable_for(name) {
+            let mut lines = Vec::new();
+            return Ok(if reftable.log(name, self.namespace.as_ref(), &mut lines)? {
+                Some(log::iter::reverse(
+                    log::iter::Source::Buffer(std::io::Cursor::new(lines)),
+                    buf,
+                )?)
+            } else {
+                None
+            });
+        }
         let path = self.reflog_path(name);
         if path.is_dir() {
             return Ok(None);
         }
         match std::fs::File::open(&path) {
-            Ok(file) => Ok(Some(log::iter::reverse(file, buf)?)),
+            Ok(file) => Ok(Some(log::iter::reverse(log::iter::Source::File(file), buf)?)),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(err) => Err(err.into()),
         }
@@ -58,6 +73,11 @@ impl file::Store {
         crate::name::Error: From<E>,
     {
         let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        if let Some(reftable) = self.reftable_for(name) {
+            return Ok(reftable
+                .log(name, self.namespace.as_ref(), buf)?
+                .then(|| log::iter::forward(buf)));
+        }
         let path = self.reflog_path(name);
         match std::fs::File::open(&path) {
             Ok(mut file) => {
@@ -173,7 +193,7 @@ pub mod create_or_update {
             }
         }
 
-        fn should_autocreate_reflog(&self, full_name: &Path) -> bool {
+        pub(in crate::store_impl::file) fn should_autocreate_reflog(&self, full_name: &Path) -> bool {
             full_name.starts_with("refs/heads/")
                 || full_name.starts_with("refs/remotes/")
                 || full_name.starts_with("refs/notes/")
@@ -231,6 +251,64 @@ pub mod create_or_update {
     use crate::FullNameRef;
 }
 
+///
+#[allow(clippy::empty_docs)]
+pub mod rewrite {
+    use std::path::PathBuf;
+
+    use crate::{log::Line, store_impl::file, FullNameRef};
+
+    impl file::Store {
+        /// Replace the reflog of `name` with `lines`, ordered from oldest to newest, or remove it if `lines` is empty.
+        ///
+        /// The reference itself isn't changed, which allows to drop individual entries like `git reflog delete` does.
+        /// Note that reflogs of references stored in a reftable can't be rewritten yet.
+        pub fn reflog_rewrite(&self, name: &FullNameRef, lines: &[Line]) -> Result<(), Error> {
+            if self.reftable_for(name).is_some() {
+                return Err(Error::Reftable {
+                    name: name.as_bstr().to_owned(),
+                });
+            }
+            let reflog_path = self.reflog_path(name);
+            if lines.is_empty() {
+                return match std::fs::remove_file(&reflog_path) {
+                    Ok(()) => Ok(()),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    Err(source) => Err(Error::Io { source, reflog_path }),
+                };
+            }
+            let mut lock =
+                gix_lock::File::acquire_to_update_resource(&reflog_path, gix_lock::acquire::Fail::Immediately, None)?;
+            for line in lines {
+                line.write_to(&mut lock).map_err(|source| Error::Io {
+                    source,
+                    reflog_path: reflog_path.clone(),
+                })?;
+            }
+            lock.commit().map_err(|err| Error::Io {
+                source: err.error,
+                reflog_path,
+            })?;
+            Ok(())
+        }
+    }
+
+    /// The error returned by [`file::Store::reflog_rewrite()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog of '{name}' is stored in a reftable and can't be rewritten")]
+        Reftable { name: gix_object::bstr::BString },
+        #[error(transparent)]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("Could not write or remove the reflog at {reflog_path:?}")]
+        Io {
+            source: std::io::Error,
+            reflog_path: PathBuf,
+        },
+    }
+}
+
 mod error {
     /// The error returned by [`crate::file::Store::reflog_iter()`].
     #[derive(Debug, thiserror::Error)]
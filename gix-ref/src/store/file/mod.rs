@@ -41,6 +41,10 @@ pub struct Store {
     /// It's updated only in one spot, which is prior to reading it based on file stamps.
     /// Doing it like this has the benefit of being able to hand snapshots out to people without blocking others from updating it.
     packed: packed::modifiable::MutableSharedBuffer,
+    /// The store to read and write references in reftables with, if references are stored that way.
+    ///
+    /// It's shared among clones so tables only have to be read once.
+    reftable: Option<crate::reftable::Store>,
 }
 
 mod access {
@@ -81,6 +85,15 @@ mod access {
         pub fn common_dir_resolved(&self) -> &Path {
             self.common_dir.as_deref().unwrap_or(&self.git_dir)
         }
+
+        /// Return how references are stored on disk.
+        pub fn ref_storage(&self) -> crate::store::RefStorage {
+            if self.reftable.is_some() {
+                crate::store::RefStorage::Reftable
+            } else {
+                crate::store::RefStorage::Files
+            }
+        }
     }
 }
 
@@ -88,6 +101,8 @@ mod access {
 pub struct Transaction<'s, 'p> {
     store: &'s Store,
     packed_transaction: Option<crate::store_impl::packed::Transaction>,
+    /// The locked reftable stacks to add a table to, one for each stack affected by the updates.
+    reftables: Vec<crate::reftable::stack::Addition>,
     updates: Option<Vec<transaction::Edit>>,
     packed_refs: transaction::PackedRefs<'p>,
 }
@@ -132,3 +147,5 @@ pub mod packed;
 
 mod raw_ext;
 pub use raw_ext::ReferenceExt;
+
+mod reftable;
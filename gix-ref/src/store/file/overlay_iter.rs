@@ -24,6 +24,8 @@ pub struct LooseThenPacked<'p, 's> {
     iter_git_dir: Peekable<SortedLoosePaths>,
     #[allow(dead_code)]
     iter_common_dir: Option<Peekable<SortedLoosePaths>>,
+    /// If set, references are stored in reftables and are read from there instead of the loose and packed ones.
+    iter_reftable: Option<crate::reftable::iter::Iter>,
     buf: Vec<u8>,
 }
 
@@ -149,6 +151,9 @@ impl<'p, 's> Iterator for LooseThenPacked<'p, 's> {
                 None => git_dir.peek().map(|r| (r, IterKind::Git)),
             }
         }
+        if let Some(reftable_iter) = self.iter_reftable.as_mut() {
+            return reftable_iter.next().map(|res| res.map_err(Error::Reftable));
+        }
         match self.iter_packed.as_mut() {
             Some(packed_iter) => match (
                 peek_loose(&mut self.iter_git_dir, self.iter_common_dir.as_mut()),
@@ -410,6 +415,20 @@ impl file::Store {
         common_dir_info: Option<IterInfo<'_>>,
         packed: Option<&'p packed::Buffer>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
+        let iter_reftable = match &self.reftable {
+            Some(reftable) => Some(
+                reftable
+                    .iter_namespaced(
+                        git_dir_info
+                            .prefix()
+                            .map_or_else(|| Cow::Borrowed("refs/".into()), path_to_name)
+                            .as_ref(),
+                        self.namespace.as_ref(),
+                    )
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?,
+            ),
+            None => None,
+        };
         Ok(LooseThenPacked {
             git_dir: self.git_dir(),
             common_dir: self.common_dir(),
@@ -426,6 +445,7 @@ impl file::Store {
             },
             iter_git_dir: git_dir_info.into_iter(),
             iter_common_dir: common_dir_info.map(IterInfo::into_iter),
+            iter_reftable,
             buf: Vec::new(),
             namespace: self.namespace.as_ref(),
         })
@@ -454,6 +474,8 @@ mod error {
         },
         #[error("Invalid reference in line {line_number}: {invalid_line:?}")]
         PackedReference { invalid_line: BString, line_number: usize },
+        #[error("Could not read references from a reftable")]
+        Reftable(#[from] crate::reftable::iter::Error),
     }
 }
 pub use error::Error;
@@ -116,7 +116,7 @@ impl ReferenceExt for Reference {
                 let mut buf = Vec::new();
                 let mut oid = self.target.try_id().expect("peeled ref").to_owned();
                 let peeled_id = loop {
-                    let gix_object::Data { kind, data } =
+                    let gix_object::Data { kind, data, .. } =
                         objects
                             .try_find(&oid, &mut buf)?
                             .ok_or_else(|| peel::to_id::Error::NotFound {
@@ -0,0 +1,8 @@
+use crate::{file, reftable, FullNameRef};
+
+impl file::Store {
+    /// Return the reftable store holding the reference with the full `name`, or `None` if it is stored in a file.
+    pub(crate) fn reftable_for(&self, name: &FullNameRef) -> Option<&reftable::Store> {
+        self.reftable.as_ref().filter(|store| store.stores(name))
+    }
+}
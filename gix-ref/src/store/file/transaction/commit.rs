@@ -1,6 +1,16 @@
+use gix_hash::ObjectId;
+
 use crate::{
-    store_impl::file::{transaction::PackedRefs, Transaction},
-    transaction::{Change, LogChange, RefEdit, RefLog},
+    reftable,
+    store::WriteReflog,
+    store_impl::{
+        file,
+        file::{
+            transaction::{Edit, PackedRefs},
+            Transaction,
+        },
+    },
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
     Target,
 };
 
@@ -35,10 +45,16 @@ impl<'s, 'p> Transaction<'s, 'p> {
             self.packed_refs,
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
         );
+        if !self.reftables.is_empty() {
+            commit_reftables(self.store, self.reftables, &updates, committer)?;
+        }
 
         // Perform updates first so live commits remain referenced
         for change in &mut updates {
             assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
+            if self.store.reftable_for(change.update.name.as_ref()).is_some() {
+                continue;
+            }
             match &change.update.change {
                 // reflog first, then reference
                 Change::Update { log, new, expected } => {
@@ -48,30 +64,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         RefLog::AndReference => (true, true),
                     };
                     if update_reflog {
-                        let log_update = match new {
-                            Target::Symbolic(_) => {
-                                // Special HACK: no reflog for symref changes as there is no OID involved which the reflog needs.
-                                // Unless, the ref is new and we can obtain a peeled id
-                                // identified by the expectation of what could be there, as is the case when cloning.
-                                match expected {
-                                    PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => {
-                                        Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
-                                    }
-                                    _ => None,
-                                }
-                            }
-                            Target::Peeled(new_oid) => {
-                                let previous = match expected {
-                                    // Here, this means that the ref already existed, and that it will receive (even transitively)
-                                    // the given value
-                                    PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
-                                    _ => None,
-                                }
-                                .or(change.leaf_referent_previous_oid);
-                                Some((previous, new_oid))
-                            }
-                        };
-                        if let Some((previous, new_oid)) = log_update {
+                        if let Some((previous, new_oid)) = log_update(new, expected, change.leaf_referent_previous_oid)
+                        {
                             let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
                             if do_update {
                                 self.store.reflog_create_or_append(
@@ -118,6 +112,9 @@ impl<'s, 'p> Transaction<'s, 'p> {
         }
 
         for change in &mut updates {
+            if self.store.reftable_for(change.update.name.as_ref()).is_some() {
+                continue;
+            }
             let (reflog_root, relative_name) = self.store.reflog_base_and_relative_path(change.update.name.as_ref());
             match &change.update.change {
                 Change::Update { .. } => {}
@@ -151,6 +148,9 @@ impl<'s, 'p> Transaction<'s, 'p> {
         }
 
         for change in &mut updates {
+            if self.store.reftable_for(change.update.name.as_ref()).is_some() {
+                continue;
+            }
             let take_lock_and_delete = match &change.update.change {
                 Change::Update {
                     log: LogChange { mode, .. },
@@ -176,6 +176,125 @@ impl<'s, 'p> Transaction<'s, 'p> {
         Ok(updates.into_iter().map(|edit| edit.update).collect())
     }
 }
+
+/// Return the previous and the new object id to write into the log when `new` is written and the reference was `expected`
+/// to have the given value, or `None` if there is nothing to log.
+fn log_update<'a>(
+    new: &'a Target,
+    expected: &'a PreviousValue,
+    leaf_referent_previous_oid: Option<ObjectId>,
+) -> Option<(Option<ObjectId>, &'a ObjectId)> {
+    match new {
+        Target::Symbolic(_) => {
+            // Special HACK: no reflog for symref changes as there is no OID involved which the reflog needs.
+            // Unless, the ref is new and we can obtain a peeled id
+            // identified by the expectation of what could be there, as is the case when cloning.
+            match expected {
+                PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => Some((Some(ObjectId::null(oid.kind())), oid)),
+                _ => None,
+            }
+        }
+        Target::Peeled(new_oid) => {
+            let previous = match expected {
+                // Here, this means that the ref already existed, and that it will receive (even transitively)
+                // the given value
+                PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
+                _ => None,
+            }
+            .or(leaf_referent_previous_oid);
+            Some((previous, new_oid))
+        }
+    }
+}
+
+/// Add a table to each of the locked `reftables` with all `updates` to the references stored in it, along with their logs.
+///
+/// Logs are written in the same cases as reflog files would be, and they are deleted along with their reference.
+fn commit_reftables(
+    store: &file::Store,
+    reftables: Vec<reftable::stack::Addition>,
+    updates: &[Edit],
+    committer: Option<gix_actor::SignatureRef<'_>>,
+) -> Result<(), Error> {
+    let mut records: Vec<(Vec<reftable::Ref>, Vec<reftable::Log>)> =
+        reftables.iter().map(|_| Default::default()).collect();
+    for change in updates {
+        let full_name = change.update.name.as_ref();
+        let Some(reftable) = store.reftable_for(full_name) else {
+            continue;
+        };
+        let (dir, name) = reftable.location(full_name, store.namespace.as_ref());
+        let idx = reftables
+            .iter()
+            .position(|addition| addition.stack().dir() == dir)
+            .expect("all stacks were locked when preparing");
+        let stack = reftables[idx].stack();
+        let update_index = reftables[idx].next_update_index();
+        let (refs, logs) = &mut records[idx];
+        let existing_logs = stack
+            .logs_of(name.as_ref())
+            .map_err(|err| Error::Reftable(err.into()))?;
+        match &change.update.change {
+            Change::Update { log, new, expected } => {
+                if log.mode == RefLog::AndReference {
+                    refs.push(reftable::Ref {
+                        name: name.clone(),
+                        update_index,
+                        value: match new {
+                            Target::Peeled(id) => reftable::RefValue::Val1(*id),
+                            Target::Symbolic(target) => reftable::RefValue::Symref(target.0.clone()),
+                        },
+                    });
+                }
+                let write_log = match store.write_reflog {
+                    WriteReflog::Disable => false,
+                    WriteReflog::Always => true,
+                    WriteReflog::Normal => {
+                        log.force_create_reflog
+                            || store.should_autocreate_reflog(&gix_path::from_bstr(name.as_ref()))
+                            || !existing_logs.is_empty()
+                    }
+                };
+                if let Some((previous, new_oid)) = log_update(new, expected, change.leaf_referent_previous_oid)
+                    .filter(|(previous, new_oid)| write_log && previous.as_ref() != Some(*new_oid))
+                {
+                    let committer = committer.ok_or(file::log::create_or_update::Error::MissingCommitter)?;
+                    logs.push(reftable::Log {
+                        name,
+                        update_index,
+                        value: reftable::LogValue::Update(crate::log::Line {
+                            previous_oid: previous.unwrap_or_else(|| new_oid.kind().null()),
+                            new_oid: new_oid.to_owned(),
+                            signature: committer.to_owned(),
+                            message: log.message.clone(),
+                        }),
+                    });
+                }
+            }
+            Change::Delete { log: mode, .. } => {
+                if *mode == RefLog::AndReference {
+                    refs.push(reftable::Ref {
+                        name,
+                        update_index,
+                        value: reftable::RefValue::Deletion,
+                    });
+                }
+                logs.extend(existing_logs.into_iter().map(|log| reftable::Log {
+                    value: reftable::LogValue::Deletion,
+                    ..log
+                }));
+            }
+        }
+    }
+    for (addition, (refs, logs)) in reftables.into_iter().zip(records) {
+        let dir = addition.stack().dir().to_owned();
+        addition.commit(refs, logs)?;
+        if let Some(reftable) = &store.reftable {
+            reftable.force_refresh_stack(&dir).ok();
+        }
+    }
+    Ok(())
+}
 mod error {
     use gix_object::bstr::BString;
 
@@ -197,8 +316,8 @@ mod error {
         DeleteReflog { full_name: BString, source: std::io::Error },
         #[error("The reflog could not be created or updated")]
         CreateOrUpdateRefLog(#[from] file::log::create_or_update::Error),
+        #[error("The reftable stack could not be read or updated")]
+        Reftable(#[from] crate::reftable::stack::write::Error),
     }
 }
 pub use error::Error;
-
-use crate::transaction::PreviousValue;
@@ -71,6 +71,7 @@ impl file::Store {
         Transaction {
             store: self,
             packed_transaction: None,
+            reftables: Vec::new(),
             updates: None,
             packed_refs: PackedRefs::default(),
         }
@@ -1,6 +1,7 @@
 use crate::{
     packed,
     packed::transaction::buffer_into_transaction,
+    store::RefStorage,
     store_impl::{
         file,
         file::{
@@ -21,6 +22,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
         change: &mut Edit,
         has_global_lock: bool,
         direct_to_packed_refs: bool,
+        reftable: Option<&crate::reftable::Stack>,
     ) -> Result<(), Error> {
         use std::io::Write;
         assert!(
@@ -28,30 +30,44 @@ impl<'s, 'p> Transaction<'s, 'p> {
             "locks can only be acquired once and it's all or nothing"
         );
 
-        let existing_ref = store
-            .ref_contents(change.update.name.as_ref())
-            .map_err(Error::from)
-            .and_then(|maybe_loose| {
-                maybe_loose
-                    .map(|buf| {
-                        loose::Reference::try_from_path(change.update.name.clone(), &buf)
-                            .map(Reference::from)
-                            .map_err(Error::from)
-                    })
-                    .transpose()
-            })
-            .or_else(|err| match err {
-                Error::ReferenceDecode(_) => Ok(None),
-                other => Err(other),
-            })
-            .and_then(|maybe_loose| match (maybe_loose, packed) {
-                (None, Some(packed)) => packed
-                    .try_find(change.update.name.as_ref())
-                    .map(|opt| opt.map(Into::into))
-                    .map_err(Error::from),
-                (None, None) => Ok(None),
-                (maybe_loose, _) => Ok(maybe_loose),
-            })?;
+        // The reftable stack is locked as a whole, so no per-reference locks are needed.
+        let has_global_lock = has_global_lock || reftable.is_some();
+        let existing_ref = match reftable {
+            Some(stack) => {
+                let (_, name) = store
+                    .reftable_for(change.update.name.as_ref())
+                    .expect("only set for references stored in reftables")
+                    .location(change.update.name.as_ref(), store.namespace.as_ref());
+                stack
+                    .find_ref(name.as_ref())
+                    .map_err(|err| Error::Reftable(err.into()))?
+                    .and_then(|r| crate::reftable::store::to_reference(change.update.name.clone(), r.value))
+            }
+            None => store
+                .ref_contents(change.update.name.as_ref())
+                .map_err(Error::from)
+                .and_then(|maybe_loose| {
+                    maybe_loose
+                        .map(|buf| {
+                            loose::Reference::try_from_path(change.update.name.clone(), &buf)
+                                .map(Reference::from)
+                                .map_err(Error::from)
+                        })
+                        .transpose()
+                })
+                .or_else(|err| match err {
+                    Error::ReferenceDecode(_) => Ok(None),
+                    other => Err(other),
+                })
+                .and_then(|maybe_loose| match (maybe_loose, packed) {
+                    (None, Some(packed)) => packed
+                        .try_find(change.update.name.as_ref())
+                        .map(|opt| opt.map(Into::into))
+                        .map_err(Error::from),
+                    (None, None) => Ok(None),
+                    (maybe_loose, _) => Ok(maybe_loose),
+                })?,
+        };
         let lock = match &mut change.update.change {
             Change::Delete { expected, .. } => {
                 let (base, relative_path) = store.reference_path_with_base(change.update.name.as_ref());
@@ -178,7 +194,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     (true, matches!(new, Target::Symbolic(_)))
                 };
 
-                if (is_effective && !direct_to_packed_refs) || is_symbolic {
+                if reftable.is_none() && ((is_effective && !direct_to_packed_refs) || is_symbolic) {
                     let mut lock = lock.take().map_or_else(obtain_lock, Ok)?;
 
                     lock.with_mut(|file| match new {
@@ -254,7 +270,22 @@ impl<'s, 'p> Transaction<'s, 'p> {
             | PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_) => Some(0_usize),
             PackedRefs::DeletionsOnly => None,
         };
-        if maybe_updates_for_packed_refs.is_some()
+        if self.store.ref_storage() == RefStorage::Reftable {
+            for edit in &updates {
+                let name = edit.update.name.as_ref();
+                let Some(reftable) = self.store.reftable_for(name) else {
+                    continue;
+                };
+                let (dir, _) = reftable.location(name, self.store.namespace.as_ref());
+                if !self.reftables.iter().any(|addition| addition.stack().dir() == dir) {
+                    let addition = reftable
+                        .stack(&dir)
+                        .map_err(|err| Error::Reftable(err.into()))?
+                        .lock(ref_files_lock_fail_mode)?;
+                    self.reftables.push(addition);
+                }
+            }
+        } else if maybe_updates_for_packed_refs.is_some()
             || self.store.packed_refs_path().is_file()
             || self.store.packed_refs_lock_path().is_file()
         {
@@ -352,6 +383,14 @@ impl<'s, 'p> Transaction<'s, 'p> {
 
         for cid in 0..updates.len() {
             let change = &mut updates[cid];
+            let reftable = self.store.reftable_for(change.update.name.as_ref()).map(|reftable| {
+                let (dir, _) = reftable.location(change.update.name.as_ref(), self.store.namespace.as_ref());
+                self.reftables
+                    .iter()
+                    .map(crate::reftable::stack::Addition::stack)
+                    .find(|stack| stack.dir() == dir)
+                    .expect("all stacks were locked")
+            });
             if let Err(err) = Self::lock_ref_and_apply_change(
                 self.store,
                 ref_files_lock_fail_mode,
@@ -362,6 +401,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     self.packed_refs,
                     PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
                 ),
+                reftable,
             ) {
                 let err = match err {
                     Error::LockAcquire {
@@ -486,6 +526,8 @@ mod error {
         },
         #[error("Could not read reference")]
         ReferenceDecode(#[from] file::loose::reference::decode::Error),
+        #[error("The reftable stack could not be locked or read")]
+        Reftable(#[from] crate::reftable::stack::write::Error),
     }
 }
 
@@ -1,9 +1,9 @@
-#![allow(dead_code)]
 use crate::{store, Namespace};
 
 #[derive(Clone)]
 pub(crate) enum State {
     Loose { store: crate::file::Store },
+    Reftable { store: crate::reftable::Store },
 }
 
 impl crate::Store {
@@ -28,6 +28,13 @@ impl crate::Store {
                         store
                     },
                 },
+                store::State::Reftable { store } => store::handle::State::Reftable {
+                    store: {
+                        let mut store = store.clone();
+                        store.namespace = namespace;
+                        store
+                    },
+                },
             },
         }
     }
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 mod error {
-    /// The error returned by [`crate::Store::at()`].
+    /// The error returned by [`Store::at()`](crate::Store::at()).
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
@@ -12,21 +12,26 @@ mod error {
 
 pub use error::Error;
 
-use crate::file;
+use crate::{file, reftable, store::RefStorage};
 
-#[allow(dead_code)]
 impl crate::Store {
     /// Create a new store at the given location, typically the `.git/` directory.
     /// Use [`opts`](crate::store::init::Options) to adjust settings.
     ///
     /// Note that if [`precompose_unicode`](crate::store::init::Options::precompose_unicode) is set in the options,
     /// the `git_dir` is also expected to use precomposed unicode, or else some operations that strip prefixes will fail.
+    ///
+    /// References are read from reftables if [`ref_storage`](crate::store::init::Options::ref_storage) says so.
     pub fn at(git_dir: PathBuf, opts: crate::store::init::Options) -> Result<Self, Error> {
-        // for now, just try to read the directory - later we will do that naturally as we have to figure out if it's a ref-table or not.
         std::fs::read_dir(&git_dir)?;
         Ok(crate::Store {
-            inner: crate::store::State::Loose {
-                store: file::Store::at(git_dir, opts),
+            inner: match opts.ref_storage {
+                RefStorage::Files => crate::store::State::Loose {
+                    store: file::Store::at(git_dir, opts),
+                },
+                RefStorage::Reftable => crate::store::State::Reftable {
+                    store: reftable::Store::at(git_dir, opts.object_hash),
+                },
             },
         })
     }
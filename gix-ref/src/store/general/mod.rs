@@ -1 +1 @@
-mod init;
+pub mod init;
@@ -5,3 +5,7 @@ pub mod file;
 ///
 #[allow(clippy::empty_docs)]
 pub mod packed;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod reftable;
@@ -0,0 +1,354 @@
+//! Reading and writing of individual blocks, each holding prefix-compressed records along with a table of restart points.
+use std::borrow::Cow;
+
+use crate::reftable::{
+    decode::Error,
+    record::{decode_key, encode_key, Record},
+    BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_OBJ, BLOCK_TYPE_REF,
+};
+
+/// The maximum amount of restart points a block can hold, as their count is stored in 16 bits.
+const MAX_RESTARTS: usize = u16::MAX as usize;
+
+/// What's needed to decode records.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Context {
+    pub object_hash: gix_hash::Kind,
+    pub min_update_index: u64,
+}
+
+/// Collects records into a single block.
+pub(crate) struct Writer {
+    /// The block so far, starting with room for the file header if this is the first block.
+    buf: Vec<u8>,
+    block_type: u8,
+    header_off: usize,
+    block_size: usize,
+    restart_interval: usize,
+    restarts: Vec<usize>,
+    last_key: Vec<u8>,
+    entries: usize,
+    min_update_index: u64,
+}
+
+impl Writer {
+    pub(crate) fn new(
+        block_type: u8,
+        block_size: usize,
+        header_off: usize,
+        restart_interval: usize,
+        min_update_index: u64,
+    ) -> Self {
+        let mut buf = vec![0; header_off];
+        buf.extend_from_slice(&[block_type, 0, 0, 0]);
+        Writer {
+            buf,
+            block_type,
+            header_off,
+            block_size,
+            restart_interval: restart_interval.max(1),
+            restarts: Vec::new(),
+            last_key: Vec::new(),
+            entries: 0,
+            min_update_index,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    pub(crate) fn block_type(&self) -> u8 {
+        self.block_type
+    }
+
+    pub(crate) fn last_key(&self) -> &[u8] {
+        &self.last_key
+    }
+
+    /// Add `record` with `key` and return `false` if it doesn't fit into the block anymore, leaving the block unchanged.
+    pub(crate) fn add(&mut self, key: &[u8], record: &Record) -> bool {
+        let previous_key: &[u8] = if self.entries % self.restart_interval == 0 {
+            &[]
+        } else {
+            &self.last_key
+        };
+        let mut value = Vec::new();
+        let value_type = record.encode_value(self.min_update_index, &mut value);
+        let mut encoded = Vec::with_capacity(key.len() + value.len() + 4);
+        let mut is_restart = encode_key(previous_key, key, value_type, &mut encoded);
+        encoded.extend_from_slice(&value);
+
+        let mut num_restarts = self.restarts.len();
+        if num_restarts >= MAX_RESTARTS {
+            is_restart = false;
+        }
+        if is_restart {
+            num_restarts += 1;
+        }
+        if self.buf.len() + encoded.len() + 3 * num_restarts + 2 > self.block_size {
+            return false;
+        }
+        if is_restart {
+            self.restarts.push(self.buf.len());
+        }
+        self.buf.extend_from_slice(&encoded);
+        self.last_key.clear();
+        self.last_key.extend_from_slice(key);
+        self.entries += 1;
+        true
+    }
+
+    /// Write the restart table and the block length, and compress log blocks, returning the bytes to write.
+    /// The first `header_off` bytes are left for the caller to fill with the file header.
+    pub(crate) fn finish(mut self) -> std::io::Result<Vec<u8>> {
+        for restart in &self.restarts {
+            self.buf.extend_from_slice(&(*restart as u32).to_be_bytes()[1..]);
+        }
+        self.buf.extend_from_slice(&(self.restarts.len() as u16).to_be_bytes());
+        let len = (self.buf.len() as u32).to_be_bytes();
+        self.buf[self.header_off + 1..self.header_off + 4].copy_from_slice(&len[1..]);
+
+        if self.block_type == BLOCK_TYPE_LOG {
+            use std::io::Write;
+            let skip = self.header_off + 4;
+            let mut out = gix_features::zlib::stream::deflate::Write::new(self.buf[..skip].to_vec());
+            out.write_all(&self.buf[skip..])?;
+            out.flush()?;
+            self.buf = out.into_inner();
+        }
+        Ok(self.buf)
+    }
+}
+
+/// A parsed block, with log blocks decompressed.
+pub(crate) struct Block<'a> {
+    /// The uncompressed block, starting with the file header if this is the first block.
+    data: Cow<'a, [u8]>,
+    pub block_type: u8,
+    header_off: usize,
+    /// The offset at which the restart table starts, which is where the records end.
+    records_end: usize,
+    restart_count: usize,
+    /// The amount of bytes the block occupies in the file, including padding.
+    pub full_size: usize,
+    /// The offset of the block within the table.
+    pub offset: u64,
+}
+
+impl<'a> Block<'a> {
+    /// Parse the block at `offset` in `table`, assuming the block's records are followed by `header_off` bytes of file header
+    /// if it's the first one. `end` is the end of all blocks, the start of the footer.
+    ///
+    /// Return `None` if there is no block at `offset`.
+    pub(crate) fn at(
+        table: &'a [u8],
+        offset: usize,
+        header_off: usize,
+        block_size: usize,
+        end: usize,
+    ) -> Result<Option<Self>, Error> {
+        let corrupt = |message| Error::Block {
+            offset: offset as u64,
+            message,
+        };
+        if offset + header_off + 4 > end {
+            return Ok(None);
+        }
+        let header = &table[offset + header_off..offset + header_off + 4];
+        let block_type = header[0];
+        if ![BLOCK_TYPE_REF, BLOCK_TYPE_LOG, BLOCK_TYPE_OBJ, BLOCK_TYPE_INDEX].contains(&block_type) {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        if len < header_off + 4 + 2 {
+            return Err(corrupt("the block length is too small"));
+        }
+
+        let (data, full_size) = if block_type == BLOCK_TYPE_LOG {
+            let skip = header_off + 4;
+            let mut out = vec![0; len];
+            out[..skip].copy_from_slice(&table[offset..offset + skip]);
+            let mut inflate = gix_features::zlib::Inflate::default();
+            let mut input = &table[offset + skip..end];
+            let mut written = skip;
+            let mut consumed = 0;
+            loop {
+                let (status, read, produced) =
+                    inflate.once(input, &mut out[written..]).map_err(|err| Error::Inflate {
+                        offset: offset as u64,
+                        source: err,
+                    })?;
+                input = &input[read..];
+                consumed += read;
+                written += produced;
+                match status {
+                    gix_features::zlib::Status::StreamEnd => break,
+                    _ if read == 0 && produced == 0 => {
+                        return Err(corrupt(
+                            "the compressed log block ended early or is larger than declared",
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            if written != len {
+                return Err(corrupt("the decompressed log block doesn't match its declared length"));
+            }
+            (Cow::Owned(out), skip + consumed)
+        } else {
+            if offset + len > end {
+                return Err(corrupt("the block extends past the end of the table"));
+            }
+            let padded = block_size.max(len).min(end - offset);
+            // Blocks are usually padded, but if the next byte is a block type, the writer didn't align them.
+            let full_size = if len < padded && table[offset + len] != 0 {
+                len
+            } else {
+                padded
+            };
+            (Cow::Borrowed(&table[offset..offset + len]), full_size)
+        };
+
+        let restart_count = u16::from_be_bytes([data[len - 2], data[len - 1]]) as usize;
+        let records_end = len
+            .checked_sub(2 + 3 * restart_count)
+            .filter(|end| *end >= header_off + 4)
+            .ok_or_else(|| corrupt("the restart table doesn't fit into the block"))?;
+        Ok(Some(Block {
+            data,
+            block_type,
+            header_off,
+            records_end,
+            restart_count,
+            full_size,
+            offset: offset as u64,
+        }))
+    }
+
+    fn restart(&self, idx: usize) -> usize {
+        let pos = self.records_end + idx * 3;
+        u32::from_be_bytes([0, self.data[pos], self.data[pos + 1], self.data[pos + 2]]) as usize
+    }
+
+    /// Iterate all records from the beginning of the block.
+    pub(crate) fn iter(&self, ctx: Context) -> Iter<'_> {
+        Iter {
+            data: &self.data,
+            block_type: self.block_type,
+            pos: self.header_off + 4,
+            end: self.records_end,
+            offset: self.offset,
+            previous_key: Vec::new(),
+            ctx,
+        }
+    }
+
+    /// Return the key of the first record.
+    pub(crate) fn first_key(&self) -> Result<Vec<u8>, Error> {
+        let mut input = &self.data[self.header_off + 4..self.records_end];
+        decode_key(&[], &mut input).map(|(key, _)| key).ok_or(Error::Block {
+            offset: self.offset,
+            message: "the first record couldn't be decoded",
+        })
+    }
+
+    /// Return an iterator positioned at the first record whose key is equal to or greater than `key`,
+    /// using the restart points to skip records that are known to be smaller.
+    pub(crate) fn seek(&self, key: &[u8], ctx: Context) -> Result<Iter<'_>, Error> {
+        let key_at_restart = |idx: usize| -> Result<Vec<u8>, Error> {
+            let pos = self.restart(idx);
+            if pos < self.header_off + 4 || pos >= self.records_end {
+                return Err(Error::Block {
+                    offset: self.offset,
+                    message: "a restart point is out of bounds",
+                });
+            }
+            let mut input = &self.data[pos..self.records_end];
+            decode_key(&[], &mut input).map(|(key, _)| key).ok_or(Error::Block {
+                offset: self.offset,
+                message: "the record at a restart point couldn't be decoded",
+            })
+        };
+
+        // Find the first restart point with a key greater than `key`, the record we look for is before that.
+        let (mut lo, mut hi) = (0, self.restart_count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if key_at_restart(mid)?.as_slice() > key {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        let mut iter = self.iter(ctx);
+        if lo > 0 {
+            iter.pos = self.restart(lo - 1);
+        }
+        loop {
+            let before = (iter.pos, iter.previous_key.clone());
+            match iter.next_key_and_record()? {
+                Some((record_key, _)) if record_key.as_slice() < key => continue,
+                _ => {
+                    iter.pos = before.0;
+                    iter.previous_key = before.1;
+                    break;
+                }
+            }
+        }
+        Ok(iter)
+    }
+}
+
+/// An iterator over the records of a [`Block`].
+pub(crate) struct Iter<'a> {
+    data: &'a [u8],
+    block_type: u8,
+    pos: usize,
+    end: usize,
+    offset: u64,
+    previous_key: Vec<u8>,
+    ctx: Context,
+}
+
+impl<'a> Iter<'a> {
+    fn next_key_and_record(&mut self) -> Result<Option<(Vec<u8>, Record)>, Error> {
+        if self.pos >= self.end {
+            return Ok(None);
+        }
+        let mut input = &self.data[self.pos..self.end];
+        let offset = self.offset;
+        let corrupt = || Error::Block {
+            offset,
+            message: "a record couldn't be decoded",
+        };
+        let (key, value_type) = decode_key(&self.previous_key, &mut input).ok_or_else(corrupt)?;
+        let record = Record::decode(
+            self.block_type,
+            key.clone(),
+            value_type,
+            &mut input,
+            self.ctx.object_hash,
+            self.ctx.min_update_index,
+        )
+        .ok_or_else(corrupt)?;
+        self.pos = self.end - input.len();
+        self.previous_key.clone_from(&key);
+        Ok(Some((key, record)))
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Result<(Vec<u8>, Record), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_key_and_record() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => None,
+            Err(err) => {
+                self.pos = self.end;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// The error returned when reading tables or the records within them.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A table needs at least {expected} bytes for header and footer, got {actual}")]
+    TooShort { expected: usize, actual: usize },
+    #[error("The table doesn't start with the 'REFT' signature")]
+    Magic,
+    #[error("Table format version {version} is unsupported")]
+    UnsupportedVersion { version: u8 },
+    #[error("The hash identified by {hash_id:#x} is unsupported")]
+    UnsupportedHash { hash_id: u32 },
+    #[error("The table is for {actual:?} object ids, but {expected:?} was expected")]
+    ObjectHashMismatch {
+        expected: gix_hash::Kind,
+        actual: gix_hash::Kind,
+    },
+    #[error("The footer doesn't repeat the table header")]
+    FooterHeaderMismatch,
+    #[error("The footer checksum was {actual:#x}, but it should have been {expected:#x}")]
+    Checksum { expected: u32, actual: u32 },
+    #[error("The block at offset {offset} is corrupt: {message}")]
+    Block { offset: u64, message: &'static str },
+    #[error("The log block at offset {offset} could not be decompressed")]
+    Inflate {
+        offset: u64,
+        source: gix_features::zlib::inflate::Error,
+    },
+}
@@ -0,0 +1,108 @@
+use std::io;
+
+pub use error::Error;
+
+use crate::{file::loose, reftable, BString, FullNameRef, Namespace, PartialNameRef, Reference};
+
+impl reftable::Store {
+    /// Find a single reference by the given `partial` name which is required to be a valid reference name.
+    ///
+    /// Returns `Ok(None)` if no such ref exists.
+    ///
+    /// ### Note
+    ///
+    /// The lookup algorithm is the same as the one of [`file::Store::try_find()`](crate::file::Store::try_find()).
+    pub fn try_find<'a, Name, E>(&self, partial: Name) -> Result<Option<Reference>, Error>
+    where
+        Name: TryInto<&'a PartialNameRef, Error = E>,
+        Error: From<E>,
+    {
+        let partial_name = partial.try_into()?;
+        let mut buf = BString::default();
+        for inbetween in &["", "tags", "heads", "remotes"] {
+            let full_name = partial_name.construct_full_name_ref(inbetween, &mut buf);
+            if let Some(r) = self.find_full_name(full_name)? {
+                return Ok(Some(r));
+            }
+        }
+        if partial_name.as_bstr() != "HEAD" {
+            let partial_name = partial_name.to_owned().join("HEAD".into()).expect("HEAD is valid name");
+            let full_name = partial_name.as_ref().construct_full_name_ref("remotes", &mut buf);
+            self.find_full_name(full_name)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn find_full_name(&self, full_name: &FullNameRef) -> Result<Option<Reference>, Error> {
+        if self.stores(full_name) {
+            return self.find_stored(full_name, self.namespace.as_ref());
+        }
+        let (base, relative_path) = self.base_dir_and_relative_path(full_name, self.namespace.as_ref());
+        let path = base.join(relative_path);
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::ReadFileContents { source, path }),
+        };
+        let mut r: Reference = loose::Reference::try_from_path(full_name.to_owned(), &content)
+            .map_err(|err| Error::ReferenceCreation {
+                source: err,
+                relative_path: full_name.to_path().to_owned(),
+            })?
+            .into();
+        if let Some(namespace) = &self.namespace {
+            r.strip_namespace(namespace);
+        }
+        Ok(Some(r))
+    }
+
+    /// Find the reference with the full `name`, which must be [stored](reftable::Store::stores()) in a reftable,
+    /// as part of `namespace`.
+    pub(crate) fn find_stored(
+        &self,
+        full_name: &FullNameRef,
+        namespace: Option<&Namespace>,
+    ) -> Result<Option<Reference>, Error> {
+        let (dir, name) = self.location(full_name, namespace);
+        let mut r = self
+            .stack(&dir)?
+            .find_ref(name.as_ref())?
+            .and_then(|r| reftable::store::to_reference(full_name.to_owned(), r.value));
+        if let (Some(r), Some(namespace)) = (r.as_mut(), namespace) {
+            r.strip_namespace(namespace);
+        }
+        Ok(r)
+    }
+}
+
+mod error {
+    use std::{convert::Infallible, io, path::PathBuf};
+
+    use crate::file;
+
+    /// The error returned by [reftable::Store::try_find()](crate::reftable::Store::try_find()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The ref name or path is not a valid ref name")]
+        RefnameValidation(#[from] crate::name::Error),
+        #[error("Could not open the reftable stack when trying to find references")]
+        Open(#[from] crate::reftable::stack::open::Error),
+        #[error("A reftable lookup failed")]
+        Decode(#[from] crate::reftable::decode::Error),
+        #[error("The ref file {path:?} could not be read in full")]
+        ReadFileContents { source: io::Error, path: PathBuf },
+        #[error("The reference at \"{relative_path}\" could not be instantiated")]
+        ReferenceCreation {
+            source: file::loose::reference::decode::Error,
+            relative_path: PathBuf,
+        },
+    }
+
+    impl From<Infallible> for Error {
+        fn from(_: Infallible) -> Self {
+            unreachable!("this impl is needed to allow passing a known valid partial path as parameter")
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{cmp::Ordering, iter::Peekable};
+
+use gix_object::bstr::{BStr, ByteVec};
+
+pub use error::Error;
+
+use crate::{reftable, FullName, FullNameRef, Namespace, Reference};
+
+impl reftable::Store {
+    /// Return an iterator over all references in `refs/`, sorted by name.
+    ///
+    /// Tables are read as references are returned, instead of loading all references ahead of time.
+    pub fn iter(&self) -> Result<Iter, Error> {
+        self.iter_prefixed("refs/".into())
+    }
+
+    /// As [`iter()`](reftable::Store::iter()), but only returns references whose name starts with `prefix`,
+    /// like `refs/heads/`.
+    pub fn iter_prefixed(&self, prefix: &BStr) -> Result<Iter, Error> {
+        match &self.namespace {
+            None => self.iter_namespaced(prefix, None),
+            Some(namespace) => {
+                let mut namespaced_prefix = namespace.as_bstr().to_owned();
+                namespaced_prefix.push_str(prefix);
+                self.iter_namespaced(namespaced_prefix.as_ref(), Some(namespace))
+            }
+        }
+    }
+
+    /// Return an iterator over all references whose name starts with `prefix`, which already includes `namespace` if set.
+    /// The `namespace` is stripped from all returned references.
+    pub(crate) fn iter_namespaced(&self, prefix: &BStr, namespace: Option<&Namespace>) -> Result<Iter, Error> {
+        Ok(Iter {
+            git_dir: self
+                .stack(&self.git_dir.join("reftable"))?
+                .iter_refs(Some(prefix))?
+                .peekable(),
+            common_dir: self
+                .common_dir
+                .as_ref()
+                .map(|common_dir| -> Result<_, Error> {
+                    Ok(self
+                        .stack(&common_dir.join("reftable"))?
+                        .iter_refs(Some(prefix))?
+                        .peekable())
+                })
+                .transpose()?,
+            namespace: namespace.cloned(),
+        })
+    }
+}
+
+/// An iterator over the references of a [reftable store](reftable::Store), sorted by name.
+///
+/// For linked work-trees, references of the work-tree shadow the ones of the common directory, whose work-tree private
+/// references are skipped.
+pub struct Iter {
+    git_dir: Peekable<reftable::stack::Refs>,
+    common_dir: Option<Peekable<reftable::stack::Refs>>,
+    namespace: Option<Namespace>,
+}
+
+enum Source {
+    GitDir,
+    GitDirAndConsumeCommon,
+    Common,
+}
+
+impl Iterator for Iter {
+    type Item = Result<Reference, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let source = match (self.git_dir.peek(), self.common_dir.as_mut().and_then(Peekable::peek)) {
+                (None, None) => return None,
+                (Some(Err(_)), _) | (Some(_), None) => Source::GitDir,
+                (None, Some(_)) | (Some(Ok(_)), Some(Err(_))) => Source::Common,
+                (Some(Ok(git_dir)), Some(Ok(common))) => match git_dir.name.cmp(&common.name) {
+                    Ordering::Less => Source::GitDir,
+                    Ordering::Equal => Source::GitDirAndConsumeCommon,
+                    Ordering::Greater => Source::Common,
+                },
+            };
+            let res = match source {
+                Source::GitDir => self.git_dir.next(),
+                Source::GitDirAndConsumeCommon => {
+                    drop(self.common_dir.as_mut().and_then(Iterator::next));
+                    self.git_dir.next()
+                }
+                Source::Common => {
+                    let res = self.common_dir.as_mut().and_then(Iterator::next);
+                    if let Some(Ok(r)) = &res {
+                        let name = FullNameRef::new_unchecked(r.name.as_ref());
+                        if matches!(name.category(), Some(category) if category.is_worktree_private()) {
+                            continue;
+                        }
+                    }
+                    res
+                }
+            }?;
+            return Some(
+                res.map(|r| {
+                    let mut r =
+                        reftable::store::to_reference(FullName(r.name), r.value).expect("deletions are never returned");
+                    if let Some(namespace) = &self.namespace {
+                        r.strip_namespace(namespace);
+                    }
+                    r
+                })
+                .map_err(Into::into),
+            );
+        }
+    }
+}
+
+mod error {
+    /// The error returned by [reftable::Store::iter()](crate::reftable::Store::iter()) and its [iterator](super::Iter).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open the reftable stack")]
+        Open(#[from] crate::reftable::stack::open::Error),
+        #[error("Could not read references from a reftable")]
+        Decode(#[from] crate::reftable::decode::Error),
+    }
+}
@@ -0,0 +1,145 @@
+//! Read and write references and their logs in the [reftable format](https://git-scm.com/docs/reftable).
+use std::path::PathBuf;
+
+use gix_features::threading::OwnShared;
+use gix_hash::ObjectId;
+use gix_object::bstr::BString;
+
+/// The magic bytes at the start of every table, and at the start of its footer.
+pub(crate) const MAGIC: &[u8; 4] = b"REFT";
+/// The block size git uses by default.
+pub const DEFAULT_BLOCK_SIZE: u32 = 4096;
+/// The amount of records between restart points git uses by default.
+pub const DEFAULT_RESTART_INTERVAL: usize = 16;
+
+/// The type byte of blocks containing [`Ref`] records.
+pub(crate) const BLOCK_TYPE_REF: u8 = b'r';
+/// The type byte of blocks containing [`Log`] records.
+pub(crate) const BLOCK_TYPE_LOG: u8 = b'l';
+/// The type byte of blocks mapping abbreviated object ids to the ref blocks that refer to them.
+pub(crate) const BLOCK_TYPE_OBJ: u8 = b'o';
+/// The type byte of blocks indexing other blocks by their last key.
+pub(crate) const BLOCK_TYPE_INDEX: u8 = b'i';
+
+/// A reference record as stored in a reftable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Ref {
+    /// The fully qualified name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+    /// The logical timestamp of the transaction that wrote this record.
+    pub update_index: u64,
+    /// The value of the reference.
+    pub value: RefValue,
+}
+
+/// The value of a [`Ref`] record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum RefValue {
+    /// The reference was deleted, hiding all records of the same name in older tables.
+    Deletion,
+    /// The reference points to an object.
+    Val1(ObjectId),
+    /// The reference points to an annotated tag `target`, which ultimately `peeled` to an object that isn't a tag.
+    Val2 {
+        /// The object the reference points to.
+        target: ObjectId,
+        /// The object the `target` peels to.
+        peeled: ObjectId,
+    },
+    /// The reference is symbolic and points to the reference with the given full name.
+    Symref(BString),
+}
+
+/// A reflog record as stored in a reftable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Log {
+    /// The fully qualified name of the reference whose log this is.
+    pub name: BString,
+    /// The logical timestamp of the transaction that wrote this record, which orders all logs of a reference.
+    pub update_index: u64,
+    /// The value of the log entry.
+    pub value: LogValue,
+}
+
+/// The value of a [`Log`] record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum LogValue {
+    /// The log entry with the same name and update index in an older table is deleted.
+    Deletion,
+    /// A regular log entry, whose message never has a trailing newline.
+    Update(crate::log::Line),
+}
+
+/// A single table, immutable once written, and fully loaded into memory.
+#[derive(Debug, Clone)]
+pub struct Table {
+    data: Vec<u8>,
+    object_hash: gix_hash::Kind,
+    version: u8,
+    block_size: u32,
+    min_update_index: u64,
+    max_update_index: u64,
+    footer: table::Footer,
+}
+
+/// A stack of [tables](Table) as listed in the `tables.list` file of a `reftable` directory, from oldest to newest.
+///
+/// Newer tables shadow the records of older ones, so the merged view of all tables is the current state of all references
+/// and their logs.
+#[derive(Debug, Clone)]
+pub struct Stack {
+    dir: PathBuf,
+    object_hash: gix_hash::Kind,
+    /// The file names of all tables along with the loaded table, from oldest to newest.
+    tables: Vec<(String, OwnShared<Table>)>,
+}
+
+/// A store for references and their logs kept in the reftable stacks of a repository, typically in `.git/reftable`.
+///
+/// `FETCH_HEAD` and `MERGE_HEAD` are read from files as they can contain more than a single value, which is why they
+/// aren't stored in reftables.
+#[derive(Debug, Clone)]
+pub struct Store {
+    /// The location of the stack with the references of this repository, or the private ones of a linked work-tree.
+    git_dir: PathBuf,
+    /// Possibly the common directory at which to find the stack with shared references. Only set for linked work-trees.
+    common_dir: Option<PathBuf>,
+    /// The kind of object ids stored in all tables.
+    object_hash: gix_hash::Kind,
+    /// The namespace to use for reads
+    pub namespace: Option<crate::Namespace>,
+    /// The stacks loaded so far, shared among clones so tables only have to be read once.
+    stacks: store::SharedStacks,
+}
+
+pub(crate) mod store;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod find;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod iter;
+
+mod varint;
+
+mod block;
+
+mod record;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod table;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod stack;
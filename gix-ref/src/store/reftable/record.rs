@@ -0,0 +1,288 @@
+//! Encoding and decoding of the records in all block types, along with their prefix-compressed keys.
+use gix_hash::ObjectId;
+
+use crate::{
+    log::Line,
+    reftable::{
+        varint, Log, LogValue, Ref, RefValue, BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_OBJ, BLOCK_TYPE_REF,
+    },
+};
+
+/// A record that maps an abbreviated object id to the offsets of all ref blocks with references pointing to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Obj {
+    pub prefix: Vec<u8>,
+    /// The offsets of the ref blocks, or empty if there were too many of them to store.
+    pub positions: Vec<u64>,
+}
+
+/// A record pointing to the block at `position`, whose last key is `last_key`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Index {
+    pub last_key: Vec<u8>,
+    pub position: u64,
+}
+
+/// Any of the records that can be stored in a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Record {
+    Ref(Ref),
+    Log(Log),
+    Obj(Obj),
+    Index(Index),
+}
+
+/// Return the key of the log record of `name` at `update_index`, which sorts newer entries first.
+pub(crate) fn log_key(name: &[u8], update_index: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(name.len() + 9);
+    key.extend_from_slice(name);
+    key.push(0);
+    key.extend_from_slice(&(u64::MAX - update_index).to_be_bytes());
+    key
+}
+
+impl Record {
+    pub(crate) fn key(&self) -> Vec<u8> {
+        match self {
+            Record::Ref(r) => r.name.to_vec(),
+            Record::Log(l) => log_key(&l.name, l.update_index),
+            Record::Obj(o) => o.prefix.clone(),
+            Record::Index(i) => i.last_key.clone(),
+        }
+    }
+
+    /// Append the value of this record to `out` and return its value type, the 3 bits stored along with the key.
+    pub(crate) fn encode_value(&self, min_update_index: u64, out: &mut Vec<u8>) -> u8 {
+        match self {
+            Record::Ref(r) => {
+                varint::encode(r.update_index - min_update_index, out);
+                match &r.value {
+                    RefValue::Deletion => 0,
+                    RefValue::Val1(id) => {
+                        out.extend_from_slice(id.as_slice());
+                        1
+                    }
+                    RefValue::Val2 { target, peeled } => {
+                        out.extend_from_slice(target.as_slice());
+                        out.extend_from_slice(peeled.as_slice());
+                        2
+                    }
+                    RefValue::Symref(target) => {
+                        encode_string(target, out);
+                        3
+                    }
+                }
+            }
+            Record::Log(l) => match &l.value {
+                LogValue::Deletion => 0,
+                LogValue::Update(line) => {
+                    out.extend_from_slice(line.previous_oid.as_slice());
+                    out.extend_from_slice(line.new_oid.as_slice());
+                    encode_string(&line.signature.name, out);
+                    encode_string(&line.signature.email, out);
+                    varint::encode(line.signature.time.seconds.max(0) as u64, out);
+                    out.extend_from_slice(&tz_offset(&line.signature.time).to_be_bytes());
+                    let mut message = line.message.to_vec();
+                    message.push(b'\n');
+                    encode_string(&message, out);
+                    1
+                }
+            },
+            Record::Obj(o) => {
+                let count = o.positions.len();
+                if count == 0 || count >= 8 {
+                    varint::encode(count as u64, out);
+                }
+                let mut last = 0;
+                for (idx, position) in o.positions.iter().enumerate() {
+                    varint::encode(if idx == 0 { *position } else { position - last }, out);
+                    last = *position;
+                }
+                if (1..8).contains(&count) {
+                    count as u8
+                } else {
+                    0
+                }
+            }
+            Record::Index(i) => {
+                varint::encode(i.position, out);
+                0
+            }
+        }
+    }
+
+    /// Decode the value of a record of `block_type` with the given `key` and `value_type` from `input`.
+    pub(crate) fn decode(
+        block_type: u8,
+        key: Vec<u8>,
+        value_type: u8,
+        input: &mut &[u8],
+        object_hash: gix_hash::Kind,
+        min_update_index: u64,
+    ) -> Option<Record> {
+        Some(match block_type {
+            BLOCK_TYPE_REF => {
+                let update_index = min_update_index.checked_add(varint::decode(input)?)?;
+                let value = match value_type {
+                    0 => RefValue::Deletion,
+                    1 => RefValue::Val1(decode_id(input, object_hash)?),
+                    2 => RefValue::Val2 {
+                        target: decode_id(input, object_hash)?,
+                        peeled: decode_id(input, object_hash)?,
+                    },
+                    3 => RefValue::Symref(decode_string(input)?.into()),
+                    _ => return None,
+                };
+                Record::Ref(Ref {
+                    name: key.into(),
+                    update_index,
+                    value,
+                })
+            }
+            BLOCK_TYPE_LOG => {
+                if key.len() < 9 || key[key.len() - 9] != 0 {
+                    return None;
+                }
+                let update_index =
+                    u64::MAX - u64::from_be_bytes(key[key.len() - 8..].try_into().expect("8 bytes are present"));
+                let mut name = key;
+                name.truncate(name.len() - 9);
+                let value = match value_type {
+                    0 => LogValue::Deletion,
+                    1 => {
+                        let previous_oid = decode_id(input, object_hash)?;
+                        let new_oid = decode_id(input, object_hash)?;
+                        let signature_name = decode_string(input)?;
+                        let email = decode_string(input)?;
+                        let seconds = varint::decode(input)?;
+                        let tz = decode_be16(input)? as i16;
+                        let mut message = decode_string(input)?;
+                        if message.last() == Some(&b'\n') {
+                            message.pop();
+                        }
+                        LogValue::Update(Line {
+                            previous_oid,
+                            new_oid,
+                            signature: gix_actor::Signature {
+                                name: signature_name.into(),
+                                email: email.into(),
+                                time: time_from_tz_offset(seconds, tz),
+                            },
+                            message: message.into(),
+                        })
+                    }
+                    _ => return None,
+                };
+                Record::Log(Log {
+                    name: name.into(),
+                    update_index,
+                    value,
+                })
+            }
+            BLOCK_TYPE_OBJ => {
+                let count = if value_type == 0 {
+                    usize::try_from(varint::decode(input)?).ok()?
+                } else {
+                    value_type as usize
+                };
+                let mut positions = Vec::with_capacity(count.min(input.len()));
+                let mut last = 0u64;
+                for idx in 0..count {
+                    let value = varint::decode(input)?;
+                    last = if idx == 0 { value } else { last.checked_add(value)? };
+                    positions.push(last);
+                }
+                Record::Obj(Obj { prefix: key, positions })
+            }
+            BLOCK_TYPE_INDEX => Record::Index(Index {
+                last_key: key,
+                position: varint::decode(input)?,
+            }),
+            _ => return None,
+        })
+    }
+}
+
+/// Append `key` to `out` with the prefix it shares with `previous_key` removed, along with `value_type`.
+/// Return `true` if no prefix is shared, which makes the record a possible restart point.
+pub(crate) fn encode_key(previous_key: &[u8], key: &[u8], value_type: u8, out: &mut Vec<u8>) -> bool {
+    let prefix_len = previous_key.iter().zip(key).take_while(|(a, b)| a == b).count();
+    varint::encode(prefix_len as u64, out);
+    varint::encode((((key.len() - prefix_len) as u64) << 3) | u64::from(value_type), out);
+    out.extend_from_slice(&key[prefix_len..]);
+    prefix_len == 0
+}
+
+/// Decode a key that shares a prefix with `previous_key` from `input`, returning it along with the value type.
+pub(crate) fn decode_key(previous_key: &[u8], input: &mut &[u8]) -> Option<(Vec<u8>, u8)> {
+    let prefix_len = usize::try_from(varint::decode(input)?).ok()?;
+    let suffix_and_type = varint::decode(input)?;
+    let suffix_len = usize::try_from(suffix_and_type >> 3).ok()?;
+    if prefix_len > previous_key.len() || suffix_len > input.len() {
+        return None;
+    }
+    let mut key = Vec::with_capacity(prefix_len + suffix_len);
+    key.extend_from_slice(&previous_key[..prefix_len]);
+    key.extend_from_slice(&input[..suffix_len]);
+    *input = &input[suffix_len..];
+    Some((key, (suffix_and_type & 0x7) as u8))
+}
+
+fn encode_string(value: &[u8], out: &mut Vec<u8>) {
+    varint::encode(value.len() as u64, out);
+    out.extend_from_slice(value);
+}
+
+fn decode_string(input: &mut &[u8]) -> Option<Vec<u8>> {
+    let len = usize::try_from(varint::decode(input)?).ok()?;
+    if len > input.len() {
+        return None;
+    }
+    let (value, rest) = input.split_at(len);
+    *input = rest;
+    Some(value.to_vec())
+}
+
+fn decode_id(input: &mut &[u8], object_hash: gix_hash::Kind) -> Option<ObjectId> {
+    let len = object_hash.len_in_bytes();
+    if len > input.len() {
+        return None;
+    }
+    let (id, rest) = input.split_at(len);
+    *input = rest;
+    Some(ObjectId::from_bytes_or_panic(id))
+}
+
+fn decode_be16(input: &mut &[u8]) -> Option<u16> {
+    if input.len() < 2 {
+        return None;
+    }
+    let value = u16::from_be_bytes([input[0], input[1]]);
+    *input = &input[2..];
+    Some(value)
+}
+
+/// Git stores the offset as it is written in signatures, so `+0130` is stored as `130`.
+fn tz_offset(time: &gix_date::Time) -> i16 {
+    let offset = time.offset.unsigned_abs();
+    let value = ((offset / 3600) * 100 + (offset % 3600) / 60) as i16;
+    if time.offset < 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+fn time_from_tz_offset(seconds: u64, tz_offset: i16) -> gix_date::Time {
+    let value = i32::from(tz_offset.unsigned_abs());
+    let offset = (value / 100) * 3600 + (value % 100) * 60;
+    gix_date::Time {
+        seconds: seconds as gix_date::SecondsSinceUnixEpoch,
+        offset: if tz_offset < 0 { -offset } else { offset },
+        sign: if tz_offset < 0 {
+            gix_date::time::Sign::Minus
+        } else {
+            gix_date::time::Sign::Plus
+        },
+    }
+}
@@ -0,0 +1,534 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_features::threading::OwnShared;
+use gix_hash::oid;
+use gix_object::bstr::{BStr, BString};
+
+use crate::reftable::{decode, table, Log, LogValue, Ref, RefValue, Stack, Table};
+
+/// The factor by which each table should be larger than the next newer one, triggering compaction otherwise.
+const GEOMETRIC_FACTOR: u64 = 2;
+
+/// Initialization
+impl Stack {
+    /// Open the stack of tables listed in `dir/tables.list`, typically `.git/reftable`, whose tables use `object_hash`.
+    ///
+    /// If `dir` or the list of tables doesn't exist, the stack is empty.
+    pub fn at(dir: impl Into<PathBuf>, object_hash: gix_hash::Kind) -> Result<Self, open::Error> {
+        let mut stack = Stack {
+            dir: dir.into(),
+            object_hash,
+            tables: Vec::new(),
+        };
+        stack.reload()?;
+        Ok(stack)
+    }
+
+    /// Read the list of tables again to see changes made by others, reusing the tables that didn't change.
+    pub fn reload(&mut self) -> Result<(), open::Error> {
+        let mut attempts = 0;
+        loop {
+            let names = read_table_names(&self.list_path())?;
+            match self.load_tables(names) {
+                Ok(tables) => {
+                    self.tables = tables;
+                    return Ok(());
+                }
+                // A concurrent compaction may have removed tables that were listed when we read the list.
+                Err(open::Error::Table {
+                    source: table::open::Error::Io(err),
+                    ..
+                }) if err.kind() == std::io::ErrorKind::NotFound && attempts < 10 => {
+                    attempts += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn load_tables(&self, names: Vec<String>) -> Result<Vec<(String, OwnShared<Table>)>, open::Error> {
+        names
+            .into_iter()
+            .map(|name| {
+                if let Some((_, table)) = self.tables.iter().find(|(existing, _)| *existing == name) {
+                    return Ok((name, table.clone()));
+                }
+                let path = self.dir.join(&name);
+                let table = Table::at(&path, self.object_hash).map_err(|source| open::Error::Table { source, path })?;
+                Ok((name, OwnShared::new(table)))
+            })
+            .collect()
+    }
+}
+
+/// Access
+impl Stack {
+    /// The directory containing all tables and their list.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The kind of object ids stored in all tables.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Return `true` if there is no table in the stack.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Return all tables, from oldest to newest.
+    pub fn tables(&self) -> impl Iterator<Item = &Table> + '_ {
+        self.tables.iter().map(|(_, table)| &**table)
+    }
+
+    /// Return the file names of all tables, from oldest to newest.
+    pub fn table_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.tables.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The update index to use for the records of the next table to be added.
+    pub fn next_update_index(&self) -> u64 {
+        self.tables.last().map_or(1, |(_, table)| table.max_update_index() + 1)
+    }
+
+    fn list_path(&self) -> PathBuf {
+        self.dir.join("tables.list")
+    }
+}
+
+/// Lookup in the merged view of all tables, in which newer records shadow older ones.
+impl Stack {
+    /// Find the reference with the full `name`, or `None` if it doesn't exist or was deleted.
+    pub fn find_ref(&self, name: &BStr) -> Result<Option<Ref>, decode::Error> {
+        for (_, table) in self.tables.iter().rev() {
+            if let Some(r) = table.find_ref(name)? {
+                return Ok((r.value != RefValue::Deletion).then_some(r));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return all existing references, sorted by name.
+    pub fn refs(&self) -> Result<Vec<Ref>, decode::Error> {
+        self.iter_refs(None)?.collect()
+    }
+
+    /// Return an iterator over all existing references whose name starts with `prefix`, if set, sorted by name.
+    ///
+    /// Only a single block of each table is decoded at a time.
+    pub fn iter_refs(&self, prefix: Option<&BStr>) -> Result<Refs, decode::Error> {
+        Ok(Refs {
+            tables: self
+                .tables
+                .iter()
+                .rev()
+                .map(|(_, table)| Ok((table::RefRecords::new(table.clone(), prefix.map(AsRef::as_ref))?, None)))
+                .collect::<Result<_, decode::Error>>()?,
+            prefix: prefix.map(ToOwned::to_owned),
+        })
+    }
+
+    /// Return all existing log entries of the reference with the full `name`, from the most recent to the oldest.
+    pub fn logs_of(&self, name: &BStr) -> Result<Vec<Log>, decode::Error> {
+        let mut logs = BTreeMap::new();
+        for (_, table) in self.tables.iter().rev() {
+            for log in table.logs_of(name)? {
+                logs.entry(Reverse(log.update_index)).or_insert(log);
+            }
+        }
+        Ok(logs
+            .into_values()
+            .filter(|log| log.value != LogValue::Deletion)
+            .collect())
+    }
+
+    /// Return all existing log entries ordered by the name of their reference, and from most recent to oldest.
+    pub fn logs(&self) -> Result<Vec<Log>, decode::Error> {
+        Ok(merge_logs(&self.tables, false)?.into_values().collect())
+    }
+
+    /// Return all existing references which point to `id` directly, or once peeled, sorted by name.
+    pub fn refs_pointing_to(&self, id: &oid) -> Result<Vec<Ref>, decode::Error> {
+        let mut candidates = std::collections::BTreeSet::new();
+        for (_, table) in &self.tables {
+            candidates.extend(table.refs_pointing_to(id)?.into_iter().map(|r| r.name));
+        }
+        let mut out = Vec::new();
+        for name in candidates {
+            if let Some(r) = self.find_ref(name.as_ref())? {
+                match &r.value {
+                    RefValue::Val1(target) if target.as_ref() == id => out.push(r),
+                    RefValue::Val2 { target, peeled } if target.as_ref() == id || peeled.as_ref() == id => out.push(r),
+                    _ => {}
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Mutation
+impl Stack {
+    /// Lock the stack for adding a table, failing according to `fail_mode` if it is already locked.
+    ///
+    /// The returned [`Addition`] holds the most recent version of this stack, which won't change until the lock is released.
+    pub fn lock(&self, fail_mode: gix_lock::acquire::Fail) -> Result<Addition, write::Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        let lock = gix_lock::File::acquire_to_update_resource(self.list_path(), fail_mode, None)?;
+        let mut stack = self.clone();
+        stack.reload()?;
+        Ok(Addition { stack, lock })
+    }
+
+    /// Merge all tables into one, dropping deleted references and log entries, and return the updated stack.
+    pub fn compact_all(&self, fail_mode: gix_lock::acquire::Fail) -> Result<Stack, write::Error> {
+        let Addition { stack, lock } = self.lock(fail_mode)?;
+        if stack.tables.len() < 2 {
+            return Ok(stack);
+        }
+        let num_tables = stack.tables.len();
+        stack.replace_tables(lock, None, Some((0, num_tables)))
+    }
+
+    /// Write the table for `new_records`, if set, and compact the tables in the `compact` range, if set, and list
+    /// the resulting tables in the lock file before committing it.
+    fn replace_tables(
+        mut self,
+        mut lock: gix_lock::File,
+        new_records: Option<(Vec<Ref>, Vec<Log>)>,
+        compact: Option<(usize, usize)>,
+    ) -> Result<Stack, write::Error> {
+        let mut written = Vec::new();
+        let res = (|| -> Result<Vec<String>, write::Error> {
+            if let Some((refs, logs)) = new_records {
+                let min = self.next_update_index();
+                let max = refs
+                    .iter()
+                    .map(|r| r.update_index)
+                    .chain(logs.iter().map(|l| l.update_index))
+                    .max()
+                    .unwrap_or(min)
+                    .max(min);
+                let (name, table) = self.write_table(min, max, refs, logs)?;
+                written.push(name.clone());
+                self.tables.push((name, OwnShared::new(table)));
+            }
+            let compact = compact.or_else(|| {
+                let sizes: Vec<_> = self
+                    .tables
+                    .iter()
+                    .map(|(_, t)| {
+                        (t.as_bytes().len() - table::header_len(t.version()) - table::footer_len(t.version())) as u64
+                    })
+                    .collect();
+                suggest_compaction_segment(&sizes)
+            });
+            let mut obsolete = Vec::new();
+            if let Some((start, end)) = compact.filter(|(start, end)| end - start > 1) {
+                let tables = &self.tables[start..end];
+                let keep_deletions = start != 0;
+                let refs = merge_refs(tables, keep_deletions)?.into_values().collect();
+                let logs = merge_logs(tables, keep_deletions)?.into_values().collect();
+                let min = tables[0].1.min_update_index();
+                let max = tables[tables.len() - 1].1.max_update_index();
+                let (name, table) = self.write_table(min, max, refs, logs)?;
+                written.push(name.clone());
+                obsolete.extend(
+                    self.tables
+                        .splice(start..end, Some((name, OwnShared::new(table))))
+                        .map(|(name, _)| name),
+                );
+            }
+
+            let mut list = Vec::new();
+            for (name, _) in &self.tables {
+                list.extend_from_slice(name.as_bytes());
+                list.push(b'\n');
+            }
+            lock.write_all(&list)?;
+            Ok(obsolete)
+        })();
+
+        match res {
+            Ok(obsolete) => {
+                lock.commit().map_err(|err| err.error)?;
+                for name in obsolete {
+                    std::fs::remove_file(self.dir.join(name)).ok();
+                }
+                Ok(self)
+            }
+            Err(err) => {
+                for name in written {
+                    std::fs::remove_file(self.dir.join(name)).ok();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn write_table(
+        &self,
+        min_update_index: u64,
+        max_update_index: u64,
+        refs: Vec<Ref>,
+        logs: Vec<Log>,
+    ) -> Result<(String, Table), write::Error> {
+        let mut writer = crate::reftable::write::Writer::new(
+            min_update_index,
+            max_update_index,
+            crate::reftable::write::Options {
+                object_hash: self.object_hash,
+                ..Default::default()
+            },
+        );
+        for r in refs {
+            writer.add_ref(r);
+        }
+        for log in logs {
+            writer.add_log(log);
+        }
+        let data = writer.finish()?;
+        let name = format!(
+            "0x{min_update_index:012x}-0x{max_update_index:012x}-{:08x}.ref",
+            random_suffix()
+        );
+        std::fs::write(self.dir.join(&name), &data)?;
+        let table = Table::from_bytes(data, self.object_hash)?;
+        Ok((name, table))
+    }
+}
+
+/// A locked [`Stack`] to which a new table can be added.
+///
+/// Dropping it releases the lock without changing the stack.
+pub struct Addition {
+    stack: Stack,
+    lock: gix_lock::File,
+}
+
+impl Addition {
+    /// The stack as it was when the lock was acquired, which is the state the new table will be added to.
+    pub fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    /// The update index to use for all records of the new table.
+    pub fn next_update_index(&self) -> u64 {
+        self.stack.next_update_index()
+    }
+
+    /// Write a table with `refs` and `logs` and add it to the stack, compacting it automatically if tables don't shrink
+    /// geometrically anymore. Return the updated stack.
+    ///
+    /// Records should use [`next_update_index()`](Self::next_update_index()), but log records may use smaller
+    /// indices to delete existing entries.
+    pub fn commit(self, refs: Vec<Ref>, logs: Vec<Log>) -> Result<Stack, write::Error> {
+        if refs.is_empty() && logs.is_empty() {
+            return Ok(self.stack);
+        }
+        self.stack.replace_tables(self.lock, Some((refs, logs)), None)
+    }
+}
+
+/// Return the tables in the range `start..end` that need to be compacted to restore a geometric sequence of `sizes`,
+/// from oldest to newest.
+fn suggest_compaction_segment(sizes: &[u64]) -> Option<(usize, usize)> {
+    if sizes.len() < 2 {
+        return None;
+    }
+    let mut end = None;
+    let mut bytes = 0;
+    let mut idx = sizes.len() - 1;
+    while idx > 0 {
+        if sizes[idx - 1] < sizes[idx] * GEOMETRIC_FACTOR {
+            end = Some(idx + 1);
+            bytes = sizes[idx];
+            break;
+        }
+        idx -= 1;
+    }
+    let end = end?;
+    let mut start = end - 1;
+    while idx > 0 {
+        let current = bytes;
+        bytes += sizes[idx - 1];
+        if sizes[idx - 1] < current * GEOMETRIC_FACTOR {
+            start = idx - 1;
+        }
+        idx -= 1;
+    }
+    Some((start, end))
+}
+
+/// An iterator over the merged view of the references in all tables of a [`Stack`], sorted by name.
+///
+/// It's obtained with [`Stack::iter_refs()`] and keeps the tables it reads from alive.
+pub struct Refs {
+    /// The records of each table along with the next one to be merged, from newest to oldest table.
+    tables: Vec<(table::RefRecords<OwnShared<Table>>, Option<Ref>)>,
+    prefix: Option<BString>,
+}
+
+impl Iterator for Refs {
+    type Item = Result<Ref, decode::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for (records, next) in &mut self.tables {
+                if next.is_none() {
+                    match records.next() {
+                        Some(Ok(r)) => *next = Some(r),
+                        Some(Err(err)) => return Some(Err(err)),
+                        None => {}
+                    }
+                }
+            }
+            // The first of all equal names is the one of the newest table, shadowing the others.
+            let newest = self
+                .tables
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, (_, next))| next.as_ref().map(|r| (idx, r)))
+                .min_by(|(_, lhs), (_, rhs)| lhs.name.cmp(&rhs.name))?
+                .0;
+            let r = self.tables[newest].1.take().expect("present");
+            for (_, next) in &mut self.tables {
+                if matches!(next, Some(next) if next.name == r.name) {
+                    *next = None;
+                }
+            }
+            if matches!(&self.prefix, Some(prefix) if !r.name.starts_with(prefix)) {
+                self.tables.clear();
+                return None;
+            }
+            if r.value != RefValue::Deletion {
+                return Some(Ok(r));
+            }
+        }
+    }
+}
+
+fn merge_refs(
+    tables: &[(String, OwnShared<Table>)],
+    keep_deletions: bool,
+) -> Result<BTreeMap<BString, Ref>, decode::Error> {
+    let mut refs = BTreeMap::new();
+    for (_, table) in tables.iter().rev() {
+        for r in table.refs()? {
+            refs.entry(r.name.clone()).or_insert(r);
+        }
+    }
+    if !keep_deletions {
+        refs.retain(|_, r| r.value != RefValue::Deletion);
+    }
+    Ok(refs)
+}
+
+fn merge_logs(
+    tables: &[(String, OwnShared<Table>)],
+    keep_deletions: bool,
+) -> Result<BTreeMap<(BString, Reverse<u64>), Log>, decode::Error> {
+    let mut logs = BTreeMap::new();
+    for (_, table) in tables.iter().rev() {
+        for log in table.logs()? {
+            logs.entry((log.name.clone(), Reverse(log.update_index))).or_insert(log);
+        }
+    }
+    if !keep_deletions {
+        logs.retain(|_, log| log.value != LogValue::Deletion);
+    }
+    Ok(logs)
+}
+
+fn read_table_names(path: &Path) -> Result<Vec<String>, open::Error> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(open::Error::ReadList {
+            source,
+            path: path.to_owned(),
+        }),
+    }
+}
+
+fn random_suffix() -> u32 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    hasher.finish() as u32
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod open {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Stack::at()`](crate::reftable::Stack::at()) and [`Stack::reload()`](crate::reftable::Stack::reload()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the list of tables at {path:?}")]
+        ReadList { source: std::io::Error, path: PathBuf },
+        #[error("The table at {path:?} could not be opened")]
+        Table {
+            source: crate::reftable::table::open::Error,
+            path: PathBuf,
+        },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write {
+    /// The error returned when adding tables to a [`Stack`](crate::reftable::Stack) or compacting it.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The list of tables could not be locked")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error("The stack could not be reloaded after locking it")]
+        Open(#[from] super::open::Error),
+        #[error("Existing tables could not be read")]
+        Decode(#[from] crate::reftable::decode::Error),
+        #[error("A new table could not be written")]
+        Write(#[from] crate::reftable::write::Error),
+        #[error("An IO error occurred while writing a table or the list of tables")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::suggest_compaction_segment;
+
+    #[test]
+    fn compaction_segments_restore_geometric_sequences() {
+        assert_eq!(suggest_compaction_segment(&[]), None);
+        assert_eq!(suggest_compaction_segment(&[1]), None);
+        assert_eq!(suggest_compaction_segment(&[64, 32, 16, 8, 4, 2, 1]), None);
+        assert_eq!(
+            suggest_compaction_segment(&[64, 32, 16, 8, 4, 3, 1]),
+            Some((0, 6)),
+            "merged tables grow, so older tables have to be merged as well"
+        );
+        assert_eq!(suggest_compaction_segment(&[128, 32, 16, 8, 4, 3, 1]), Some((1, 6)));
+        assert_eq!(suggest_compaction_segment(&[1, 1]), Some((0, 2)));
+        assert_eq!(suggest_compaction_segment(&[10, 1, 1]), Some((1, 3)));
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use gix_features::threading::{get_mut, MutableOnDemand, OwnShared};
+use gix_object::bstr::BString;
+
+use crate::{file::find::base_dir_and_relative_name, reftable, FullName, FullNameRef, Namespace, Reference, Target};
+
+/// The stacks loaded by a store and all of its clones.
+pub(crate) type SharedStacks = OwnShared<MutableOnDemand<Vec<LoadedStack>>>;
+
+/// A stack along with the modification time of its list of tables when it was loaded, or `None` if there was no list.
+#[derive(Debug)]
+pub(crate) struct LoadedStack {
+    stack: reftable::Stack,
+    modified: Option<SystemTime>,
+}
+
+/// References that are stored as files even if reftables are used, as they can contain more than a single value.
+const SPECIAL_REFS: &[&str] = &["FETCH_HEAD", "MERGE_HEAD"];
+
+/// Initialization
+impl reftable::Store {
+    /// Create a new instance for the repository at `git_dir`, typically the `.git/` directory, whose tables store object
+    /// ids of kind `object_hash`.
+    pub fn at(git_dir: PathBuf, object_hash: gix_hash::Kind) -> Self {
+        reftable::Store {
+            git_dir,
+            common_dir: None,
+            object_hash,
+            namespace: None,
+            stacks: Default::default(),
+        }
+    }
+
+    /// Like [`at()`](reftable::Store::at()), but for _linked_ work-trees which use `git_dir` for their private references
+    /// and `common_dir` for shared references.
+    pub fn for_linked_worktree(git_dir: PathBuf, common_dir: PathBuf, object_hash: gix_hash::Kind) -> Self {
+        reftable::Store {
+            git_dir,
+            common_dir: Some(common_dir),
+            object_hash,
+            namespace: None,
+            stacks: Default::default(),
+        }
+    }
+}
+
+/// Access
+impl reftable::Store {
+    /// Return the `.git` directory whose `reftable/` directory holds all references, or the private ones of a linked work-tree.
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// If this is a linked work tree, there will be `Some(git_dir)` pointing to the parent repository,
+    /// whose `reftable/` directory holds the shared references.
+    pub fn common_dir(&self) -> Option<&Path> {
+        self.common_dir.as_deref()
+    }
+
+    /// Similar to [`common_dir()`](reftable::Store::common_dir()), but it will produce either the common-dir, or the
+    /// git-dir if the former isn't present.
+    pub fn common_dir_resolved(&self) -> &Path {
+        self.common_dir.as_deref().unwrap_or(&self.git_dir)
+    }
+
+    /// The kind of object ids stored in all tables.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// Return `true` if the reference with the full `name` is stored in a reftable, which is the case for all references
+    /// but `FETCH_HEAD` and `MERGE_HEAD`.
+    pub fn stores(&self, name: &FullNameRef) -> bool {
+        !SPECIAL_REFS.iter().any(|special| name.as_bstr() == *special)
+    }
+}
+
+impl reftable::Store {
+    /// Return the directory in which the reference with the full `name` is stored when reading it as part of `namespace`,
+    /// and, for references that aren't stored in a reftable, the path of its file relative to it.
+    pub(crate) fn base_dir_and_relative_path<'a>(
+        &self,
+        name: &'a FullNameRef,
+        namespace: Option<&Namespace>,
+    ) -> (Cow<'_, Path>, Cow<'a, Path>) {
+        let (base, name) = base_dir_and_relative_name(&self.git_dir, self.common_dir_resolved(), name, false);
+        (
+            base,
+            match namespace {
+                None => gix_path::to_native_path_on_windows(name.as_bstr()),
+                Some(namespace) => {
+                    gix_path::to_native_path_on_windows(namespace.to_owned().into_namespaced_name(name).into_inner())
+                }
+            },
+        )
+    }
+
+    /// Return the directory of the stack storing the reference with the full `name` when reading it as part of `namespace`,
+    /// along with the name it is stored under.
+    pub(crate) fn location(&self, name: &FullNameRef, namespace: Option<&Namespace>) -> (PathBuf, BString) {
+        let is_reflog = true;
+        let (base, name) = base_dir_and_relative_name(&self.git_dir, self.common_dir_resolved(), name, is_reflog);
+        let name = match namespace {
+            None => name.as_bstr().to_owned(),
+            Some(namespace) => namespace.to_owned().into_namespaced_name(name).0,
+        };
+        (base.join("reftable"), name)
+    }
+
+    /// Return the up-to-date stack in `dir`, which is only reloaded if the modification time of its list of tables changed.
+    /// Tables that were loaded before are reused.
+    ///
+    /// Just like with `packed-refs`, changes made within the precision of the modification time may be missed.
+    pub(crate) fn stack(&self, dir: &Path) -> Result<reftable::Stack, reftable::stack::open::Error> {
+        self.load_stack(dir, false)
+    }
+
+    /// Forcefully reload the stack in `dir`, which is needed after changing it as the modification time of its
+    /// list of tables might not have changed.
+    pub(crate) fn force_refresh_stack(&self, dir: &Path) -> Result<(), reftable::stack::open::Error> {
+        self.load_stack(dir, true).map(|_| ())
+    }
+
+    fn load_stack(&self, dir: &Path, force: bool) -> Result<reftable::Stack, reftable::stack::open::Error> {
+        // The modification time must be obtained before reading the list to not miss changes made while reading it.
+        let modified = dir.join("tables.list").metadata().and_then(|m| m.modified()).ok();
+        let mut stacks = get_mut(&self.stacks);
+        match stacks.iter_mut().find(|loaded| loaded.stack.dir() == dir) {
+            Some(loaded) => {
+                if force || loaded.modified != modified {
+                    loaded.stack.reload()?;
+                    loaded.modified = modified;
+                }
+                Ok(loaded.stack.clone())
+            }
+            None => {
+                let stack = reftable::Stack::at(dir, self.object_hash)?;
+                stacks.push(LoadedStack {
+                    stack: stack.clone(),
+                    modified,
+                });
+                Ok(stack)
+            }
+        }
+    }
+
+    /// Return `true` if the reference with the full `name`, read as part of `namespace`, has at least one log entry.
+    pub(crate) fn log_exists(&self, name: &FullNameRef, namespace: Option<&Namespace>) -> bool {
+        let (dir, name) = self.location(name, namespace);
+        let logs = self
+            .stack(&dir)
+            .ok()
+            .and_then(|stack| stack.logs_of(name.as_ref()).ok());
+        matches!(logs, Some(logs) if !logs.is_empty())
+    }
+
+    /// Write the log of the reference with the full `name`, read as part of `namespace`, into `buf` in the format of
+    /// reflog files, oldest entry first.
+    /// Return `false` if there is no log.
+    pub(crate) fn log(
+        &self,
+        name: &FullNameRef,
+        namespace: Option<&Namespace>,
+        buf: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        let (dir, name) = self.location(name, namespace);
+        let logs = self
+            .stack(&dir)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
+            .logs_of(name.as_ref())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        buf.clear();
+        for log in logs.iter().rev() {
+            if let reftable::LogValue::Update(line) = &log.value {
+                line.write_to(buf)?;
+            }
+        }
+        Ok(!logs.is_empty())
+    }
+}
+
+/// Turn the `value` of the reftable record `name` into a reference, or `None` if it is a deletion.
+pub(crate) fn to_reference(name: FullName, value: reftable::RefValue) -> Option<Reference> {
+    let (target, peeled) = match value {
+        reftable::RefValue::Deletion => return None,
+        reftable::RefValue::Val1(id) => (Target::Peeled(id), None),
+        reftable::RefValue::Val2 { target, peeled } => (Target::Peeled(target), Some(peeled)),
+        reftable::RefValue::Symref(target) => (Target::Symbolic(FullName(target)), None),
+    };
+    Some(Reference { name, target, peeled })
+}
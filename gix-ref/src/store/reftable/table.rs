@@ -0,0 +1,466 @@
+use std::{ops::Deref, path::Path};
+
+use gix_hash::oid;
+use gix_object::bstr::BStr;
+
+use crate::reftable::{
+    block::{Block, Context},
+    decode::Error,
+    record::{log_key, Record},
+    Log, Ref, RefValue, Table, BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_OBJ, BLOCK_TYPE_REF, MAGIC,
+};
+
+/// The identifier of SHA-1 in the header of version 2 tables.
+pub(crate) const HASH_ID_SHA1: u32 = u32::from_be_bytes(*b"sha1");
+/// The identifier of SHA-256 in the header of version 2 tables.
+pub(crate) const HASH_ID_SHA256: u32 = u32::from_be_bytes(*b"s256");
+
+/// The positions of all sections, as stored in the footer of a table.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Footer {
+    pub ref_index_position: u64,
+    pub obj_position: u64,
+    pub obj_id_len: u8,
+    pub obj_index_position: u64,
+    pub log_position: u64,
+    pub log_index_position: u64,
+}
+
+/// The size of the header of a table in the given format `version`.
+pub(crate) fn header_len(version: u8) -> usize {
+    if version == 1 {
+        24
+    } else {
+        28
+    }
+}
+
+/// The size of the footer of a table in the given format `version`.
+pub(crate) fn footer_len(version: u8) -> usize {
+    header_len(version) + 5 * 8 + 4
+}
+
+fn be64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data[..8].try_into().expect("8 bytes"))
+}
+
+/// Initialization
+impl Table {
+    /// Parse `data` as table, assuring it uses `object_hash` for object ids.
+    ///
+    /// Only the header and footer are validated here, blocks are decoded as they are accessed.
+    pub fn from_bytes(data: Vec<u8>, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        if data.len() < 5 || &data[..4] != MAGIC {
+            return Err(if data.len() < footer_len(1) + header_len(1) {
+                Error::TooShort {
+                    expected: footer_len(1) + header_len(1),
+                    actual: data.len(),
+                }
+            } else {
+                Error::Magic
+            });
+        }
+        let version = data[4];
+        if version != 1 && version != 2 {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let (hl, fl) = (header_len(version), footer_len(version));
+        if data.len() < hl + fl {
+            return Err(Error::TooShort {
+                expected: hl + fl,
+                actual: data.len(),
+            });
+        }
+        let block_size = u32::from_be_bytes([0, data[5], data[6], data[7]]);
+        let min_update_index = be64(&data[8..]);
+        let max_update_index = be64(&data[16..]);
+        let actual_hash = if version == 2 {
+            let hash_id = u32::from_be_bytes(data[24..28].try_into().expect("4 bytes"));
+            match hash_id {
+                HASH_ID_SHA1 => gix_hash::Kind::Sha1,
+                // SHA-256 is only known to `gix-hash` if its `sha256` feature is enabled.
+                HASH_ID_SHA256 => gix_hash::Kind::from_hex_len(64).ok_or(Error::UnsupportedHash { hash_id })?,
+                _ => return Err(Error::UnsupportedHash { hash_id }),
+            }
+        } else {
+            gix_hash::Kind::Sha1
+        };
+        if actual_hash != object_hash {
+            return Err(Error::ObjectHashMismatch {
+                expected: object_hash,
+                actual: actual_hash,
+            });
+        }
+
+        let footer_start = data.len() - fl;
+        let footer_data = &data[footer_start..];
+        if footer_data[..hl] != data[..hl] {
+            return Err(Error::FooterHeaderMismatch);
+        }
+        let expected = u32::from_be_bytes(footer_data[fl - 4..].try_into().expect("4 bytes"));
+        let actual = gix_features::hash::crc32(&footer_data[..fl - 4]);
+        if expected != actual {
+            return Err(Error::Checksum { expected, actual });
+        }
+        let positions = &footer_data[hl..];
+        let obj = be64(&positions[8..]);
+        let footer = Footer {
+            ref_index_position: be64(positions),
+            obj_position: obj >> 5,
+            obj_id_len: (obj & 0x1f) as u8,
+            obj_index_position: be64(&positions[16..]),
+            log_position: be64(&positions[24..]),
+            log_index_position: be64(&positions[32..]),
+        };
+        Ok(Table {
+            data,
+            object_hash,
+            version,
+            block_size,
+            min_update_index,
+            max_update_index,
+            footer,
+        })
+    }
+
+    /// Read the table at `path` and parse it with [`from_bytes()`](Self::from_bytes()).
+    pub fn at(path: &Path, object_hash: gix_hash::Kind) -> Result<Self, open::Error> {
+        let data = std::fs::read(path)?;
+        Ok(Self::from_bytes(data, object_hash)?)
+    }
+}
+
+/// Access
+impl Table {
+    /// The format version of the table, either 1 or 2.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The kind of object ids stored in the table.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The size of blocks that aren't log blocks.
+    pub fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    /// The smallest update index of all records in the table.
+    pub fn min_update_index(&self) -> u64 {
+        self.min_update_index
+    }
+
+    /// The largest update index of all records in the table.
+    pub fn max_update_index(&self) -> u64 {
+        self.max_update_index
+    }
+
+    /// The raw bytes of the whole table.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Lookup
+impl Table {
+    /// Find the ref record for the reference with the full `name`, which may be a [deletion](RefValue::Deletion).
+    pub fn find_ref(&self, name: &BStr) -> Result<Option<Ref>, Error> {
+        Ok(self
+            .section_records(BLOCK_TYPE_REF, Some(name))?
+            .next()
+            .transpose()?
+            .and_then(|(key, record)| match record {
+                Record::Ref(r) if name == key.as_slice() => Some(r),
+                _ => None,
+            }))
+    }
+
+    /// Return all ref records in the order of their names, including deletions.
+    pub fn refs(&self) -> Result<Vec<Ref>, Error> {
+        RefRecords::new(self, None)?.collect()
+    }
+
+    /// Return all log records, ordered by name and from the most recent to the oldest entry of each reference,
+    /// including deletions.
+    pub fn logs(&self) -> Result<Vec<Log>, Error> {
+        self.section_records(BLOCK_TYPE_LOG, None)?
+            .filter_map(|res| match res {
+                Ok((_, Record::Log(l))) => Some(Ok(l)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+
+    /// Return the log records of the reference with the full `name`, from the most recent to the oldest, including deletions.
+    pub fn logs_of(&self, name: &BStr) -> Result<Vec<Log>, Error> {
+        let key = log_key(name, u64::MAX);
+        let mut out = Vec::new();
+        for res in self.section_records(BLOCK_TYPE_LOG, Some(&key))? {
+            match res? {
+                (_, Record::Log(log)) if log.name == name => out.push(log),
+                _ => break,
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return all ref records in this table which point to `id` directly or once peeled, using the object index if present.
+    pub fn refs_pointing_to(&self, id: &oid) -> Result<Vec<Ref>, Error> {
+        let points_to_id = |r: &Ref| match &r.value {
+            RefValue::Val1(target) => target.as_ref() == id,
+            RefValue::Val2 { target, peeled } => target.as_ref() == id || peeled.as_ref() == id,
+            RefValue::Deletion | RefValue::Symref(_) => false,
+        };
+        let prefix_len = self.footer.obj_id_len as usize;
+        if self.footer.obj_position == 0 || prefix_len == 0 || prefix_len > id.as_bytes().len() {
+            return Ok(self.refs()?.into_iter().filter(points_to_id).collect());
+        }
+        let prefix = &id.as_bytes()[..prefix_len];
+        let positions = match self.section_records(BLOCK_TYPE_OBJ, Some(prefix))?.next().transpose()? {
+            Some((key, Record::Obj(obj))) if key == prefix => obj.positions,
+            _ => return Ok(Vec::new()),
+        };
+        if positions.is_empty() {
+            return Ok(self.refs()?.into_iter().filter(points_to_id).collect());
+        }
+        let mut out = Vec::new();
+        for position in positions {
+            let block = self.block_at(position as usize)?.ok_or(Error::Block {
+                offset: position,
+                message: "an object record points to a block that doesn't exist",
+            })?;
+            for res in block.iter(self.context()) {
+                if let (_, Record::Ref(r)) = res? {
+                    if points_to_id(&r) {
+                        out.push(r);
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Block access
+impl Table {
+    fn context(&self) -> Context {
+        Context {
+            object_hash: self.object_hash,
+            min_update_index: self.min_update_index,
+        }
+    }
+
+    /// The offset of the footer, which is where all blocks end.
+    fn blocks_end(&self) -> usize {
+        self.data.len() - footer_len(self.version)
+    }
+
+    pub(crate) fn block_at(&self, offset: usize) -> Result<Option<Block<'_>>, Error> {
+        let header_off = if offset == 0 { header_len(self.version) } else { 0 };
+        Block::at(
+            &self.data,
+            offset,
+            header_off,
+            self.block_size as usize,
+            self.blocks_end(),
+        )
+    }
+
+    /// Return the offset of the first block of the section with blocks of `block_type`, along with its index position,
+    /// or `None` if there is no such section.
+    fn section(&self, block_type: u8) -> Option<(usize, u64)> {
+        let first_block_type = self.data.get(header_len(self.version)).copied();
+        let has_first_block = header_len(self.version) + 4 <= self.blocks_end();
+        match block_type {
+            BLOCK_TYPE_REF => (has_first_block && first_block_type == Some(BLOCK_TYPE_REF))
+                .then_some((0, self.footer.ref_index_position)),
+            BLOCK_TYPE_OBJ => (self.footer.obj_position > 0)
+                .then_some((self.footer.obj_position as usize, self.footer.obj_index_position)),
+            BLOCK_TYPE_LOG => {
+                if self.footer.log_position > 0 {
+                    Some((self.footer.log_position as usize, self.footer.log_index_position))
+                } else {
+                    (has_first_block && first_block_type == Some(BLOCK_TYPE_LOG))
+                        .then_some((0, self.footer.log_index_position))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Return an iterator over all records in the section of `block_type`, starting at the first record with a key equal to
+    /// or greater than `seek`, if set.
+    fn section_records(&self, block_type: u8, seek: Option<&[u8]>) -> Result<SectionRecords<&Table>, Error> {
+        SectionRecords::new(self, block_type, seek)
+    }
+
+    /// Return the block of the section of `block_type` starting at `start` which contains `key` along with its offset,
+    /// or `None` if there is no such block.
+    fn seek_in_section(
+        &self,
+        block_type: u8,
+        start: usize,
+        index_position: u64,
+        key: &[u8],
+    ) -> Result<Option<(usize, Block<'_>)>, Error> {
+        let block_offset = if index_position > 0 {
+            match self.seek_in_index(index_position as usize, key)? {
+                Some(offset) => offset,
+                None => return Ok(None),
+            }
+        } else {
+            let mut candidate = start;
+            let mut offset = start;
+            while let Some(block) = self.block_at(offset)?.filter(|b| b.block_type == block_type) {
+                if block.first_key()?.as_slice() > key {
+                    break;
+                }
+                candidate = offset;
+                offset += block.full_size;
+            }
+            candidate
+        };
+
+        let block = self
+            .block_at(block_offset)?
+            .filter(|b| b.block_type == block_type)
+            .ok_or(Error::Block {
+                offset: block_offset as u64,
+                message: "expected a block of the indexed type",
+            })?;
+        Ok(Some((block_offset, block)))
+    }
+
+    /// Follow the index starting at `position` down to the block that contains `key` if it exists, and return its offset.
+    fn seek_in_index(&self, mut position: usize, key: &[u8]) -> Result<Option<usize>, Error> {
+        loop {
+            let block = self.block_at(position)?.ok_or(Error::Block {
+                offset: position as u64,
+                message: "the index points to a block that doesn't exist",
+            })?;
+            if block.block_type != BLOCK_TYPE_INDEX {
+                return Ok(Some(position));
+            }
+            match block.seek(key, self.context())?.next().transpose()? {
+                Some((_, Record::Index(index))) => position = index.position as usize,
+                Some(_) => {
+                    return Err(Error::Block {
+                        offset: position as u64,
+                        message: "index blocks must only contain index records",
+                    })
+                }
+                None => {
+                    // The top-level index may span multiple blocks, try the next one.
+                    let next = position + block.full_size;
+                    match self.block_at(next)? {
+                        Some(next_block) if next_block.block_type == BLOCK_TYPE_INDEX => position = next,
+                        _ => return Ok(None),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the records of a section, spanning multiple blocks, reading one block at a time.
+///
+/// `T` is a way to access the table, which allows the iterator to own it.
+struct SectionRecords<T> {
+    table: T,
+    block_type: u8,
+    next_offset: Option<usize>,
+    records: std::vec::IntoIter<(Vec<u8>, Record)>,
+}
+
+impl<T: Deref<Target = Table>> SectionRecords<T> {
+    /// Iterate all records in the section of `block_type` of `table`, starting at the first record with a key equal to
+    /// or greater than `seek`, if set.
+    fn new(table: T, block_type: u8, seek: Option<&[u8]>) -> Result<Self, Error> {
+        let (next_offset, records) = match table.section(block_type) {
+            None => (None, Vec::new()),
+            Some((start, index_position)) => match seek {
+                None => (Some(start), Vec::new()),
+                Some(key) => match table.seek_in_section(block_type, start, index_position, key)? {
+                    Some((offset, block)) => (
+                        Some(offset + block.full_size),
+                        block.seek(key, table.context())?.collect::<Result<_, _>>()?,
+                    ),
+                    None => (None, Vec::new()),
+                },
+            },
+        };
+        Ok(SectionRecords {
+            table,
+            block_type,
+            next_offset,
+            records: records.into_iter(),
+        })
+    }
+}
+
+impl<T: Deref<Target = Table>> Iterator for SectionRecords<T> {
+    type Item = Result<(Vec<u8>, Record), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.records.next() {
+                return Some(Ok(record));
+            }
+            let offset = self.next_offset.take()?;
+            let block = match self.table.block_at(offset) {
+                Ok(Some(block)) if block.block_type == self.block_type => block,
+                Ok(_) => return None,
+                Err(err) => return Some(Err(err)),
+            };
+            match block.iter(self.table.context()).collect::<Result<Vec<_>, _>>() {
+                Ok(records) => self.records = records.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+            self.next_offset = Some(offset + block.full_size);
+        }
+    }
+}
+
+/// An iterator over the ref records of a table in the order of their names, including deletions.
+///
+/// `T` is a way to access the table, which allows the iterator to own it.
+pub(crate) struct RefRecords<T>(SectionRecords<T>);
+
+impl<T: Deref<Target = Table>> RefRecords<T> {
+    /// Iterate the ref records of `table`, starting at the first one whose name is equal to or greater than `seek`, if set.
+    pub(crate) fn new(table: T, seek: Option<&[u8]>) -> Result<Self, Error> {
+        SectionRecords::new(table, BLOCK_TYPE_REF, seek).map(RefRecords)
+    }
+}
+
+impl<T: Deref<Target = Table>> Iterator for RefRecords<T> {
+    type Item = Result<Ref, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for res in self.0.by_ref() {
+            match res {
+                Ok((_, Record::Ref(r))) => return Some(Ok(r)),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod open {
+    /// The error returned by [`Table::at()`](crate::reftable::Table::at()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The table could not be read")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Decode(#[from] crate::reftable::decode::Error),
+    }
+}
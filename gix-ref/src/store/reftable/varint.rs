@@ -0,0 +1,80 @@
+//! The variable-length integer encoding used throughout reftables, which is the same as the one for offsets in packs.
+
+/// Append the varint encoding of `value` to `out`.
+pub(crate) fn encode(mut value: u64, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 10];
+    let mut pos = buf.len() - 1;
+    buf[pos] = (value & 0x7f) as u8;
+    loop {
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+        value -= 1;
+        pos -= 1;
+        buf[pos] = 0x80 | (value & 0x7f) as u8;
+    }
+    out.extend_from_slice(&buf[pos..]);
+}
+
+/// Decode a varint from the front of `input` and advance it past the consumed bytes,
+/// or return `None` if `input` ends early or the value overflows.
+pub(crate) fn decode(input: &mut &[u8]) -> Option<u64> {
+    let (&first, mut rest) = input.split_first()?;
+    let mut value = u64::from(first & 0x7f);
+    let mut byte = first;
+    while byte & 0x80 != 0 {
+        let (&next, remaining) = rest.split_first()?;
+        if value >= (u64::MAX >> 7) {
+            return None;
+        }
+        value = ((value + 1) << 7) | u64::from(next & 0x7f);
+        byte = next;
+        rest = remaining;
+    }
+    *input = rest;
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn round_trip() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            255,
+            256,
+            16383,
+            16384,
+            16511,
+            1 << 32,
+            u64::MAX >> 1,
+            u64::MAX,
+        ] {
+            let mut buf = Vec::new();
+            super::encode(value, &mut buf);
+            let mut input = buf.as_slice();
+            assert_eq!(super::decode(&mut input), Some(value), "{value}");
+            assert!(input.is_empty());
+        }
+    }
+
+    #[test]
+    fn known_encodings() {
+        let mut buf = Vec::new();
+        super::encode(128, &mut buf);
+        assert_eq!(buf, [0x80, 0x00], "the offset encoding removes redundancy");
+        buf.clear();
+        super::encode(127, &mut buf);
+        assert_eq!(buf, [0x7f]);
+    }
+
+    #[test]
+    fn truncated_input_is_none() {
+        assert_eq!(super::decode(&mut &[0x80][..]), None);
+        assert_eq!(super::decode(&mut &[][..]), None);
+    }
+}
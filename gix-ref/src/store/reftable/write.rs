@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+
+use gix_hash::ObjectId;
+
+use crate::reftable::{
+    block,
+    record::{Index, Obj, Record},
+    table::{header_len, Footer, HASH_ID_SHA256},
+    Log, Ref, RefValue, BLOCK_TYPE_INDEX, BLOCK_TYPE_LOG, BLOCK_TYPE_OBJ, BLOCK_TYPE_REF, DEFAULT_BLOCK_SIZE,
+    DEFAULT_RESTART_INTERVAL, MAGIC,
+};
+
+/// If a section has more blocks than this, it receives an index, which then is indexed itself until it's small enough.
+const INDEX_THRESHOLD: usize = 3;
+
+/// Options to control how tables are written.
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// The size of all blocks except for log blocks, which are compressed. Blocks are padded to this size.
+    pub block_size: u32,
+    /// The amount of records after which a record stores its full key to allow binary searches within a block.
+    pub restart_interval: usize,
+    /// The kind of object ids stored in the table.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, don't write object blocks that map object ids to the references pointing to them.
+    pub skip_index_objects: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            block_size: DEFAULT_BLOCK_SIZE,
+            restart_interval: DEFAULT_RESTART_INTERVAL,
+            object_hash: gix_hash::Kind::Sha1,
+            skip_index_objects: false,
+        }
+    }
+}
+
+/// The error returned by [`Writer::finish()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Block sizes must be between 256 bytes and 16MB, got {block_size}")]
+    BlockSize { block_size: u32 },
+    #[error("The record with key {key:?} is too large to fit into a block")]
+    EntryTooBig { key: gix_object::bstr::BString },
+    #[error("The update index {update_index} of reference {name:?} isn't within the range {min}..={max} of the table")]
+    UpdateIndexOutOfRange {
+        name: gix_object::bstr::BString,
+        update_index: u64,
+        min: u64,
+        max: u64,
+    },
+    #[error("The reference {name:?} was added more than once")]
+    DuplicateRef { name: gix_object::bstr::BString },
+    #[error("The log entry {update_index} of {name:?} was added more than once")]
+    DuplicateLog {
+        name: gix_object::bstr::BString,
+        update_index: u64,
+    },
+    #[error("A log block could not be compressed")]
+    Compress(#[from] std::io::Error),
+}
+
+/// A utility to produce a table from ref and log records, which can be added in any order.
+#[derive(Debug, Clone)]
+pub struct Writer {
+    options: Options,
+    min_update_index: u64,
+    max_update_index: u64,
+    refs: Vec<Ref>,
+    logs: Vec<Log>,
+}
+
+struct Section {
+    index_position: u64,
+    index_blocks: usize,
+}
+
+/// Lifecycle
+impl Writer {
+    /// Create a new instance for a table with records whose update indices are within `min_update_index..=max_update_index`.
+    pub fn new(min_update_index: u64, max_update_index: u64, options: Options) -> Self {
+        Writer {
+            options,
+            min_update_index,
+            max_update_index,
+            refs: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    /// Add the ref record `r`.
+    pub fn add_ref(&mut self, r: Ref) -> &mut Self {
+        self.refs.push(r);
+        self
+    }
+
+    /// Add the log record `log`.
+    pub fn add_log(&mut self, log: Log) -> &mut Self {
+        self.logs.push(log);
+        self
+    }
+
+    /// Produce the table with all records, sorted as needed.
+    pub fn finish(mut self) -> Result<Vec<u8>, Error> {
+        if !(256..(1 << 24)).contains(&self.options.block_size) {
+            return Err(Error::BlockSize {
+                block_size: self.options.block_size,
+            });
+        }
+        let (min, max) = (self.min_update_index, self.max_update_index);
+        // Log records store their update index in full, which allows them to refer to older entries to delete them.
+        if let Some(r) = self.refs.iter().find(|r| !(min..=max).contains(&r.update_index)) {
+            return Err(Error::UpdateIndexOutOfRange {
+                name: r.name.clone(),
+                update_index: r.update_index,
+                min,
+                max,
+            });
+        }
+        self.refs.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(pair) = self.refs.windows(2).find(|pair| pair[0].name == pair[1].name) {
+            return Err(Error::DuplicateRef {
+                name: pair[0].name.clone(),
+            });
+        }
+        self.logs
+            .sort_by(|a, b| a.name.cmp(&b.name).then(b.update_index.cmp(&a.update_index)));
+        if let Some(pair) = self
+            .logs
+            .windows(2)
+            .find(|pair| pair[0].name == pair[1].name && pair[0].update_index == pair[1].update_index)
+        {
+            return Err(Error::DuplicateLog {
+                name: pair[0].name.clone(),
+                update_index: pair[0].update_index,
+            });
+        }
+
+        let header = self.header();
+        let mut out = Vec::new();
+        let mut footer = Footer::default();
+        let refs = std::mem::take(&mut self.refs);
+        if !refs.is_empty() {
+            let mut ids_to_blocks = BTreeMap::<ObjectId, Vec<u64>>::new();
+            let section = self.write_section(
+                &mut out,
+                &header,
+                BLOCK_TYPE_REF,
+                refs.into_iter().map(Record::Ref),
+                Some(&mut ids_to_blocks),
+            )?;
+            footer.ref_index_position = section.index_position;
+            if !self.options.skip_index_objects && section.index_blocks > 0 && !ids_to_blocks.is_empty() {
+                let obj_id_len = ids_to_blocks
+                    .keys()
+                    .zip(ids_to_blocks.keys().skip(1))
+                    .map(|(a, b)| {
+                        a.as_bytes()
+                            .iter()
+                            .zip(b.as_bytes())
+                            .take_while(|(a, b)| a == b)
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                footer.obj_position = out.len() as u64;
+                footer.obj_id_len = obj_id_len as u8;
+                let section = self.write_section(
+                    &mut out,
+                    &header,
+                    BLOCK_TYPE_OBJ,
+                    ids_to_blocks.into_iter().map(|(id, positions)| {
+                        Record::Obj(Obj {
+                            prefix: id.as_bytes()[..obj_id_len].to_vec(),
+                            positions,
+                        })
+                    }),
+                    None,
+                )?;
+                footer.obj_index_position = section.index_position;
+            }
+        }
+
+        let logs = std::mem::take(&mut self.logs);
+        if !logs.is_empty() {
+            footer.log_position = out.len() as u64;
+            let section = self.write_section(
+                &mut out,
+                &header,
+                BLOCK_TYPE_LOG,
+                logs.into_iter().map(Record::Log),
+                None,
+            )?;
+            footer.log_index_position = section.index_position;
+        }
+
+        if out.is_empty() {
+            out.extend_from_slice(&header);
+        }
+        let footer_start = out.len();
+        out.extend_from_slice(&header);
+        for value in [
+            footer.ref_index_position,
+            (footer.obj_position << 5) | u64::from(footer.obj_id_len),
+            footer.obj_index_position,
+            footer.log_position,
+            footer.log_index_position,
+        ] {
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+        let crc = gix_features::hash::crc32(&out[footer_start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+        Ok(out)
+    }
+}
+
+/// Utilities
+impl Writer {
+    /// Like `git`, write version 1 tables for SHA-1, and version 2 tables that identify the hash otherwise.
+    fn header(&self) -> Vec<u8> {
+        let version = if self.options.object_hash == gix_hash::Kind::Sha1 {
+            1
+        } else {
+            2
+        };
+        let mut header = Vec::with_capacity(header_len(version));
+        header.extend_from_slice(MAGIC);
+        header.push(version);
+        header.extend_from_slice(&self.options.block_size.to_be_bytes()[1..]);
+        header.extend_from_slice(&self.min_update_index.to_be_bytes());
+        header.extend_from_slice(&self.max_update_index.to_be_bytes());
+        if version == 2 {
+            header.extend_from_slice(&HASH_ID_SHA256.to_be_bytes());
+        }
+        header
+    }
+
+    fn new_block(&self, block_type: u8, out: &[u8], header: &[u8]) -> block::Writer {
+        block::Writer::new(
+            block_type,
+            self.options.block_size as usize,
+            if out.is_empty() { header.len() } else { 0 },
+            self.options.restart_interval,
+            self.min_update_index,
+        )
+    }
+
+    /// Write all `records` into blocks of `block_type`, along with as many levels of index blocks as needed.
+    /// If `ids_to_blocks` is set, the offsets of ref blocks are collected for each object id they refer to.
+    fn write_section(
+        &self,
+        out: &mut Vec<u8>,
+        header: &[u8],
+        block_type: u8,
+        records: impl Iterator<Item = Record>,
+        ids_to_blocks: Option<&mut BTreeMap<ObjectId, Vec<u64>>>,
+    ) -> Result<Section, Error> {
+        let mut index = self.write_blocks(out, header, block_type, records, ids_to_blocks)?;
+        let mut section = Section {
+            index_position: 0,
+            index_blocks: 0,
+        };
+        while index.len() > INDEX_THRESHOLD {
+            section.index_position = out.len() as u64;
+            let level = std::mem::take(&mut index);
+            index = self.write_blocks(
+                out,
+                header,
+                BLOCK_TYPE_INDEX,
+                level.into_iter().map(Record::Index),
+                None,
+            )?;
+            section.index_blocks += index.len();
+        }
+        Ok(section)
+    }
+
+    /// Write `records` into as many blocks as needed and return index records pointing to each of the written blocks.
+    fn write_blocks(
+        &self,
+        out: &mut Vec<u8>,
+        header: &[u8],
+        block_type: u8,
+        records: impl Iterator<Item = Record>,
+        mut ids_to_blocks: Option<&mut BTreeMap<ObjectId, Vec<u64>>>,
+    ) -> Result<Vec<Index>, Error> {
+        let mut index = Vec::new();
+        let mut block = self.new_block(block_type, out, header);
+        let mut block_start = out.len() as u64;
+        for mut record in records {
+            let key = record.key();
+            if !block.add(&key, &record) {
+                if !block.is_empty() {
+                    self.flush_block(out, header, block, &mut index)?;
+                    block = self.new_block(block_type, out, header);
+                    block_start = out.len() as u64;
+                }
+                if !block.add(&key, &record) {
+                    // Object records may point to too many blocks, in which case readers scan all refs instead.
+                    match &mut record {
+                        Record::Obj(obj) if !obj.positions.is_empty() => obj.positions.clear(),
+                        _ => return Err(Error::EntryTooBig { key: key.into() }),
+                    }
+                    if !block.add(&key, &record) {
+                        return Err(Error::EntryTooBig { key: key.into() });
+                    }
+                }
+            }
+            if let (Some(ids_to_blocks), Record::Ref(r)) = (ids_to_blocks.as_deref_mut(), &record) {
+                let ids = match &r.value {
+                    RefValue::Val1(id) => [Some(id), None],
+                    RefValue::Val2 { target, peeled } => [Some(target), Some(peeled)],
+                    RefValue::Deletion | RefValue::Symref(_) => [None, None],
+                };
+                for id in ids.into_iter().flatten() {
+                    let positions = ids_to_blocks.entry(*id).or_default();
+                    if positions.last() != Some(&block_start) {
+                        positions.push(block_start);
+                    }
+                }
+            }
+        }
+        if !block.is_empty() {
+            self.flush_block(out, header, block, &mut index)?;
+        }
+        Ok(index)
+    }
+
+    fn flush_block(
+        &self,
+        out: &mut Vec<u8>,
+        header: &[u8],
+        block: block::Writer,
+        index: &mut Vec<Index>,
+    ) -> Result<(), Error> {
+        let is_first = out.is_empty();
+        index.push(Index {
+            last_key: block.last_key().to_vec(),
+            position: out.len() as u64,
+        });
+        let is_log = block.block_type() == BLOCK_TYPE_LOG;
+        let mut bytes = block.finish()?;
+        if is_first {
+            bytes[..header.len()].copy_from_slice(header);
+        }
+        if !is_log {
+            bytes.resize(self.options.block_size as usize, 0);
+        }
+        out.extend_from_slice(&bytes);
+        Ok(())
+    }
+}
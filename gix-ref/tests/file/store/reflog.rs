@@ -67,3 +67,34 @@ mod iter_rev {
         Ok(())
     }
 }
+
+mod rewrite {
+    use gix_ref::FullNameRef;
+
+    #[test]
+    fn drop_lines_and_remove_log() -> crate::Result {
+        let (_tmp, store) = crate::file::store_writable("make_repo_for_reflog.sh")?;
+        let name: &FullNameRef = "refs/heads/main".try_into()?;
+        let mut buf = Vec::new();
+        let mut lines: Vec<_> = store
+            .reflog_iter(name, &mut buf)?
+            .expect("exists")
+            .map(|line| line.map(|line| line.to_owned()))
+            .collect::<Result<_, _>>()?;
+        lines.remove(1);
+        store.reflog_rewrite(name, &lines)?;
+
+        let actual: Vec<_> = store
+            .reflog_iter(name, &mut buf)?
+            .expect("still exists")
+            .map(|line| line.map(|line| line.to_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(actual, lines, "lines are written in order");
+        assert!(store.reflog_exists(name)?);
+
+        store.reflog_rewrite(name, &[])?;
+        assert!(!store.reflog_exists(name)?, "logs without lines are removed");
+        store.reflog_rewrite(name, &[])?;
+        Ok(())
+    }
+}
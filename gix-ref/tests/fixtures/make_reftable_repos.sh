@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each invocation of `git` that changes references adds a table to the stack, so the stack has multiple tables.
+function make_repo() {
+  local name="${1:?}" object_format="${2:?}"
+  git init -q --ref-format=reftable --object-format="$object_format" "$name"
+  (cd "$name"
+    git checkout -q -b main
+    git commit -q --allow-empty -m c1
+    git branch to-delete
+    git branch other
+    git commit -q --allow-empty -m c2
+    git tag -m "annotated" annotated
+    git tag lightweight HEAD~1
+    git symbolic-ref refs/heads/symbolic refs/heads/other
+    git branch -D to-delete
+
+    git for-each-ref --format='%(refname) %(objectname) %(*objectname)' > ../"$name".refs
+  )
+}
+
+make_repo sha1 sha1
+make_repo sha256 sha256
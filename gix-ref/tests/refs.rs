@@ -11,5 +11,6 @@ mod fullname;
 mod namespace;
 mod packed;
 mod reference;
+mod reftable;
 mod store;
 mod transaction;
@@ -0,0 +1,106 @@
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+use gix_ref::reftable::{LogValue, RefValue, Stack};
+
+/// A reference name along with the id it points to and its peeled id, if it's an annotated tag.
+type ExpectedRef = (BString, ObjectId, Option<ObjectId>);
+
+/// Open the reftable stack of the repository `name` as written by `git`, along with the references `git` lists for it
+/// as `(name, id, peeled id)`.
+fn stack_and_expected_refs(
+    name: &str,
+    object_hash: gix_hash::Kind,
+) -> crate::Result<Option<(Stack, Vec<ExpectedRef>)>> {
+    if gix_testtools::should_skip_as_git_version_is_smaller_than(2, 45, 0) {
+        return Ok(None);
+    }
+    let dir = gix_testtools::scripted_fixture_read_only("make_reftable_repos.sh")?;
+    let stack = Stack::at(dir.join(name).join(".git").join("reftable"), object_hash)?;
+    let expected = std::fs::read(dir.join(format!("{name}.refs")))?
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split_str(" ");
+            let name = tokens.next().expect("name").into();
+            let id = ObjectId::from_hex(tokens.next().expect("id")).expect("valid");
+            let peeled = tokens
+                .next()
+                .filter(|hex| !hex.is_empty())
+                .map(|hex| ObjectId::from_hex(hex).expect("valid"));
+            (name, id, peeled)
+        })
+        .collect();
+    Ok(Some((stack, expected)))
+}
+
+fn assert_refs_match_git(name: &str, object_hash: gix_hash::Kind) -> crate::Result {
+    let Some((stack, expected)) = stack_and_expected_refs(name, object_hash)? else {
+        return Ok(());
+    };
+    assert!(stack.tables().count() > 1, "each ref update of git adds a table");
+    assert!(stack.tables().all(|t| t.object_hash() == object_hash));
+    assert_eq!(
+        stack.find_ref("HEAD".into())?.map(|r| r.value),
+        Some(RefValue::Symref("refs/heads/main".into()))
+    );
+    assert_eq!(
+        stack.find_ref("refs/heads/to-delete".into())?,
+        None,
+        "deletions hide the records of older tables"
+    );
+
+    let mut names: Vec<_> = stack
+        .refs()?
+        .into_iter()
+        .map(|r| r.name)
+        .filter(|name| name != "HEAD")
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        expected.iter().map(|(name, _, _)| name.clone()).collect::<Vec<_>>(),
+        "git lists the same references, sorted by name"
+    );
+
+    for (name, id, peeled) in expected {
+        let mut value = stack.find_ref(name.as_ref())?.expect("present").value;
+        if let RefValue::Symref(target) = &value {
+            assert_eq!(name, "refs/heads/symbolic");
+            value = stack.find_ref(target.as_ref())?.expect("target present").value;
+        }
+        match value {
+            RefValue::Val1(actual) => {
+                assert_eq!(actual, id, "{name}");
+                assert_eq!(peeled, None, "{name} isn't an annotated tag");
+            }
+            RefValue::Val2 {
+                target,
+                peeled: actual_peeled,
+            } => {
+                assert_eq!(target, id, "{name}");
+                assert_eq!(Some(actual_peeled), peeled, "{name}");
+            }
+            other => unreachable!("{name} has an unexpected value: {other:?}"),
+        }
+    }
+
+    let main_id = match stack.find_ref("refs/heads/main".into())?.expect("present").value {
+        RefValue::Val1(id) => id,
+        other => unreachable!("main is a branch: {other:?}"),
+    };
+    let logs = stack.logs_of("refs/heads/main".into())?;
+    match &logs.first().expect("git writes reflogs into the tables as well").value {
+        LogValue::Update(line) => assert_eq!(line.new_oid, main_id, "the newest entry comes first"),
+        LogValue::Deletion => unreachable!("reflogs of main weren't deleted"),
+    }
+    Ok(())
+}
+
+#[test]
+fn sha1_tables_written_by_git() -> crate::Result {
+    assert_refs_match_git("sha1", gix_hash::Kind::Sha1)
+}
+
+#[test]
+fn sha256_tables_written_by_git() -> crate::Result {
+    assert_refs_match_git("sha256", gix_hash::Kind::Sha256)
+}
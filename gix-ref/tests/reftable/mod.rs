@@ -0,0 +1,51 @@
+use gix_date::{time::Sign, Time};
+use gix_hash::ObjectId;
+use gix_ref::{
+    log::Line,
+    reftable::{Log, LogValue, Ref, RefValue},
+};
+
+fn id(n: u32) -> ObjectId {
+    let mut bytes = [0u8; 20];
+    bytes[..4].copy_from_slice(&n.to_be_bytes());
+    bytes[19] = 0xff;
+    ObjectId::from_bytes_or_panic(&bytes)
+}
+
+fn val1(name: &str, update_index: u64, n: u32) -> Ref {
+    Ref {
+        name: name.into(),
+        update_index,
+        value: RefValue::Val1(id(n)),
+    }
+}
+
+fn log(name: &str, update_index: u64, previous: u32, new: u32, message: &str) -> Log {
+    log_with_offset(name, update_index, previous, new, message, 5 * 3600 + 30 * 60)
+}
+
+fn log_with_offset(name: &str, update_index: u64, previous: u32, new: u32, message: &str, offset: i32) -> Log {
+    Log {
+        name: name.into(),
+        update_index,
+        value: LogValue::Update(Line {
+            previous_oid: id(previous),
+            new_oid: id(new),
+            signature: gix_actor::Signature {
+                name: "committer".into(),
+                email: "committer@example.com".into(),
+                time: Time {
+                    seconds: 1711398853,
+                    offset,
+                    sign: if offset < 0 { Sign::Minus } else { Sign::Plus },
+                },
+            },
+            message: message.into(),
+        }),
+    }
+}
+
+mod git;
+mod stack;
+mod store;
+mod table;
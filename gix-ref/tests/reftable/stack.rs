@@ -0,0 +1,191 @@
+use gix_lock::acquire::Fail;
+use gix_ref::reftable::{LogValue, Ref, RefValue, Stack};
+
+use crate::reftable::{id, log, val1};
+
+fn stack() -> crate::Result<(Stack, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    Ok((Stack::at(dir.path().join("reftable"), gix_hash::Kind::Sha1)?, dir))
+}
+
+fn add(stack: &Stack, refs: impl IntoIterator<Item = (&'static str, Option<u32>)>) -> crate::Result<Stack> {
+    let addition = stack.lock(Fail::Immediately)?;
+    let update_index = addition.next_update_index();
+    let refs = refs
+        .into_iter()
+        .map(|(name, n)| match n {
+            Some(n) => val1(name, update_index, n),
+            None => Ref {
+                name: name.into(),
+                update_index,
+                value: RefValue::Deletion,
+            },
+        })
+        .collect();
+    Ok(addition.commit(refs, Vec::new())?)
+}
+
+#[test]
+fn a_missing_directory_is_an_empty_stack() -> crate::Result {
+    let (stack, _tmp) = stack()?;
+    assert!(stack.is_empty());
+    assert_eq!(stack.next_update_index(), 1);
+    assert_eq!(stack.find_ref("HEAD".into())?, None);
+    assert!(stack.refs()?.is_empty());
+    assert!(stack.logs()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn newer_tables_shadow_older_ones() -> crate::Result {
+    let (stack, _tmp) = stack()?;
+    let stack = add(&stack, [("refs/heads/main", Some(1)), ("refs/heads/dev", Some(2))])?;
+    assert_eq!(stack.next_update_index(), 2);
+    let stack = add(&stack, [("refs/heads/main", Some(3)), ("refs/heads/dev", None)])?;
+
+    assert_eq!(
+        stack.find_ref("refs/heads/main".into())?,
+        Some(val1("refs/heads/main", 2, 3))
+    );
+    assert_eq!(
+        stack.find_ref("refs/heads/dev".into())?,
+        None,
+        "deletions hide older records"
+    );
+    assert_eq!(stack.refs()?, vec![val1("refs/heads/main", 2, 3)]);
+    assert_eq!(stack.refs_pointing_to(&id(3))?.len(), 1);
+    assert!(stack.refs_pointing_to(&id(2))?.is_empty(), "deleted refs point nowhere");
+
+    let reopened = Stack::at(stack.dir(), gix_hash::Kind::Sha1)?;
+    assert_eq!(
+        reopened.table_names().collect::<Vec<_>>(),
+        stack.table_names().collect::<Vec<_>>(),
+        "the list of tables was written"
+    );
+    assert_eq!(reopened.refs()?, stack.refs()?);
+    Ok(())
+}
+
+#[test]
+fn refs_are_streamed_in_order_and_can_be_filtered_by_prefix() -> crate::Result {
+    let (stack, _tmp) = stack()?;
+    let stack = add(&stack, [("refs/heads/main", Some(1)), ("refs/tags/v1", Some(2))])?;
+    let stack = add(&stack, [("refs/heads/dev", Some(3)), ("refs/heads/main", None)])?;
+    let stack = add(&stack, [("refs/heads/main", Some(4)), ("refs/notes/commits", Some(5))])?;
+
+    let names = |prefix: Option<&str>| -> crate::Result<Vec<String>> {
+        Ok(stack
+            .iter_refs(prefix.map(Into::into))?
+            .map(|r| r.map(|r| r.name.to_string()))
+            .collect::<Result<_, _>>()?)
+    };
+    assert_eq!(
+        names(None)?,
+        [
+            "refs/heads/dev",
+            "refs/heads/main",
+            "refs/notes/commits",
+            "refs/tags/v1"
+        ]
+    );
+    assert_eq!(names(Some("refs/heads/"))?, ["refs/heads/dev", "refs/heads/main"]);
+    assert_eq!(names(Some("refs/remotes/"))?, Vec::<String>::new());
+    assert_eq!(
+        stack
+            .iter_refs(Some("refs/heads/m".into()))?
+            .collect::<Result<Vec<_>, _>>()?,
+        vec![val1("refs/heads/main", 3, 4)],
+        "the record of the newest table is returned"
+    );
+    Ok(())
+}
+
+#[test]
+fn other_instances_see_changes_after_reloading() -> crate::Result {
+    let (mut stack, _tmp) = stack()?;
+    let other = add(&stack, [("HEAD", Some(1))])?;
+    assert!(stack.is_empty(), "instances don't change by themselves");
+    stack.reload()?;
+    assert_eq!(stack.find_ref("HEAD".into())?, other.find_ref("HEAD".into())?);
+    Ok(())
+}
+
+#[test]
+fn the_stack_can_only_be_locked_once() -> crate::Result {
+    let (stack, _tmp) = stack()?;
+    let addition = stack.lock(Fail::Immediately)?;
+    assert!(stack.lock(Fail::Immediately).is_err());
+    drop(addition);
+    assert!(
+        stack.lock(Fail::Immediately).is_ok(),
+        "dropping an addition releases the lock"
+    );
+    Ok(())
+}
+
+#[test]
+fn tables_are_compacted_automatically() -> crate::Result {
+    let (mut stack, _tmp) = stack()?;
+    for n in 0..20u32 {
+        stack = add(&stack, [("refs/heads/main", Some(n))])?;
+    }
+    let num_tables = stack.tables().count();
+    assert!(
+        num_tables < 6,
+        "tables of similar size are merged, leaving a geometric sequence, got {num_tables}"
+    );
+    assert_eq!(
+        std::fs::read_dir(stack.dir())?.count(),
+        num_tables + 1,
+        "compacted tables are removed, only the list remains"
+    );
+    assert_eq!(
+        stack.find_ref("refs/heads/main".into())?,
+        Some(val1("refs/heads/main", 20, 19))
+    );
+    assert_eq!(stack.next_update_index(), 21);
+    Ok(())
+}
+
+#[test]
+fn compacting_everything_drops_deletions() -> crate::Result {
+    let (stack, _tmp) = stack()?;
+    let addition = stack.lock(Fail::Immediately)?;
+    let stack = addition.commit(
+        vec![val1("refs/heads/main", 1, 1), val1("refs/heads/gone", 1, 2)],
+        (1..=3u32)
+            .map(|n| log("refs/heads/main", n.into(), n - 1, n, "commit"))
+            .collect(),
+    )?;
+
+    let addition = stack.lock(Fail::Immediately)?;
+    assert_eq!(
+        addition.next_update_index(),
+        4,
+        "the table covers the update indices of its logs"
+    );
+    let mut tombstone = log("refs/heads/main", 2, 1, 2, "commit");
+    tombstone.value = LogValue::Deletion;
+    let stack = addition.commit(
+        vec![Ref {
+            name: "refs/heads/gone".into(),
+            update_index: 4,
+            value: RefValue::Deletion,
+        }],
+        vec![tombstone],
+    )?;
+    let logs: Vec<_> = stack
+        .logs_of("refs/heads/main".into())?
+        .into_iter()
+        .map(|l| l.update_index)
+        .collect();
+    assert_eq!(logs, [3, 1], "log deletions apply to entries in older tables");
+
+    let stack = stack.compact_all(Fail::Immediately)?;
+    assert_eq!(stack.tables().count(), 1);
+    let table = stack.tables().next().expect("one table");
+    assert_eq!(table.refs()?, vec![val1("refs/heads/main", 1, 1)], "deletions are gone");
+    assert_eq!(table.logs()?.len(), 2, "log deletions are applied");
+    assert_eq!((table.min_update_index(), table.max_update_index()), (1, 4));
+    Ok(())
+}
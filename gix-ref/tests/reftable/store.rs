@@ -0,0 +1,290 @@
+use gix_lock::acquire::Fail;
+use gix_ref::{
+    file,
+    store::{init, RefStorage, WriteReflog},
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    file::transaction::prepare_and_commit::{committer, create_at},
+    hex_to_id,
+};
+
+fn reftable_store() -> crate::Result<(gix_testtools::tempfile::TempDir, file::Store)> {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let store = file::Store::at(
+        dir.path().into(),
+        init::Options {
+            write_reflog: WriteReflog::Normal,
+            ref_storage: RefStorage::Reftable,
+            ..Default::default()
+        },
+    );
+    Ok((dir, store))
+}
+
+fn update(store: &file::Store, edits: impl IntoIterator<Item = RefEdit>) -> crate::Result<Vec<RefEdit>> {
+    Ok(store
+        .transaction()
+        .prepare(edits, Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?)
+}
+
+fn set_symbolic(name: &str, target: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected: PreviousValue::Any,
+            new: Target::Symbolic(target.try_into().expect("valid")),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn set_peeled(name: &str, hex: &str, message: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: message.into(),
+            },
+            expected: PreviousValue::MustExist,
+            new: Target::Peeled(hex_to_id(hex)),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn delete(name: &str) -> RefEdit {
+    RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::Any,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    }
+}
+
+fn reflog_messages(store: &file::Store, name: &str) -> crate::Result<Option<Vec<String>>> {
+    let mut buf = Vec::new();
+    Ok(match store.reflog_iter(name, &mut buf)? {
+        None => None,
+        Some(iter) => Some(
+            iter.map(|line| line.map(|line| line.message.to_string()))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+const FIRST: &str = "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391";
+const SECOND: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+#[test]
+fn references_are_written_to_the_reftable_stack_only() -> crate::Result {
+    let (dir, store) = reftable_store()?;
+    assert_eq!(store.ref_storage(), RefStorage::Reftable);
+    update(
+        &store,
+        [create_at("refs/heads/main"), set_symbolic("HEAD", "refs/heads/main")],
+    )?;
+
+    assert!(!dir.path().join("refs/heads/main").exists(), "no loose refs");
+    assert!(!dir.path().join("HEAD").exists());
+    assert!(!dir.path().join("logs").exists(), "no reflog files");
+    assert!(dir.path().join("reftable/tables.list").is_file());
+
+    let main = store.find("main")?;
+    assert_eq!(main.name.as_bstr(), "refs/heads/main");
+    assert_eq!(main.target.try_id(), Some(hex_to_id(FIRST).as_ref()));
+    let head = store.find("HEAD")?;
+    assert_eq!(
+        head.target.try_name().map(gix_ref::FullNameRef::as_bstr),
+        Some("refs/heads/main".into())
+    );
+    assert!(store.try_find("refs/heads/other")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn updates_and_deletions_with_reflogs() -> crate::Result {
+    let (_dir, store) = reftable_store()?;
+    update(&store, [create_at("refs/heads/main"), create_at("refs/heads/dev")])?;
+    update(&store, [set_peeled("refs/heads/main", SECOND, "commit: second")])?;
+    update(&store, [delete("refs/heads/dev")])?;
+
+    assert_eq!(
+        store.find("main")?.target.try_id(),
+        Some(hex_to_id(SECOND).as_ref()),
+        "newer values shadow older ones"
+    );
+    assert!(store.try_find("dev")?.is_none(), "deleted references are gone");
+
+    assert_eq!(
+        reflog_messages(&store, "refs/heads/main")?,
+        Some(vec!["log peeled".into(), "commit: second".into()]),
+        "logs are returned oldest first, like reflog files"
+    );
+    assert_eq!(
+        reflog_messages(&store, "refs/heads/dev")?,
+        None,
+        "logs are deleted along with the reference"
+    );
+    assert!(store.reflog_exists("refs/heads/main")?);
+    assert!(!store.reflog_exists("refs/heads/dev")?);
+
+    let mut buf = [0u8; 256];
+    let newest = store
+        .reflog_iter_rev("refs/heads/main", &mut buf)?
+        .expect("log exists")
+        .next()
+        .expect("one line")?;
+    assert_eq!(newest.new_oid, hex_to_id(SECOND));
+    assert_eq!(newest.previous_oid, hex_to_id(FIRST));
+    Ok(())
+}
+
+#[test]
+fn expectations_are_checked_against_the_stack() -> crate::Result {
+    let (_dir, store) = reftable_store()?;
+    update(&store, [create_at("refs/heads/main")])?;
+    let mut edit = create_at("refs/heads/main");
+    if let Change::Update { new, .. } = &mut edit.change {
+        *new = Target::Peeled(hex_to_id(SECOND));
+    }
+    let err = store
+        .transaction()
+        .prepare([edit], Fail::Immediately, Fail::Immediately)
+        .expect_err("the reference exists with a different value");
+    assert!(err.to_string().contains("refs/heads/main"), "{err}");
+
+    let err = store
+        .transaction()
+        .prepare(
+            [set_peeled("refs/heads/missing", SECOND, "")],
+            Fail::Immediately,
+            Fail::Immediately,
+        )
+        .expect_err("the reference must exist");
+    assert!(err.to_string().contains("refs/heads/missing"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn iteration_is_sorted_and_supports_prefixes() -> crate::Result {
+    let (_dir, store) = reftable_store()?;
+    update(
+        &store,
+        [
+            create_at("refs/tags/v1"),
+            create_at("refs/heads/main"),
+            create_at("refs/heads/dev"),
+            set_symbolic("HEAD", "refs/heads/main"),
+        ],
+    )?;
+    update(&store, [delete("refs/heads/dev")])?;
+
+    let names = |iter: file::iter::LooseThenPacked<'_, '_>| -> crate::Result<Vec<String>> {
+        Ok(iter
+            .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+            .collect::<Result<_, _>>()?)
+    };
+    let platform = store.iter()?;
+    assert_eq!(names(platform.all()?)?, ["refs/heads/main", "refs/tags/v1"]);
+    assert_eq!(names(platform.prefixed("refs/tags/".as_ref())?)?, ["refs/tags/v1"]);
+    Ok(())
+}
+
+#[test]
+fn the_general_store_finds_references_in_reftables() -> crate::Result {
+    let (dir, store) = reftable_store()?;
+    update(&store, [create_at("refs/heads/main")])?;
+
+    let store = gix_ref::Store::at(
+        dir.path().into(),
+        init::Options {
+            ref_storage: RefStorage::Reftable,
+            ..Default::default()
+        },
+    )?;
+    let handle = store.to_handle();
+    assert_eq!(handle.find("main")?.target.try_id(), Some(hex_to_id(FIRST).as_ref()));
+    assert!(handle.try_find("refs/heads/missing")?.is_none());
+    assert!(handle.find("missing").is_err());
+
+    assert!(handle.try_find("FETCH_HEAD")?.is_none());
+    std::fs::write(
+        dir.path().join("FETCH_HEAD"),
+        format!("{SECOND}\t\tbranch 'main' of https://example.com/repo\n"),
+    )?;
+    assert_eq!(
+        handle.find("FETCH_HEAD")?.target.try_id(),
+        Some(hex_to_id(SECOND).as_ref()),
+        "special references are read from files"
+    );
+    Ok(())
+}
+
+#[test]
+fn the_reftable_store_streams_references_of_its_namespace() -> crate::Result {
+    let (dir, store) = reftable_store()?;
+    update(&store, [create_at("refs/heads/main"), create_at("refs/tags/v1")])?;
+    let mut namespaced = store.clone();
+    namespaced.namespace = Some(gix_ref::namespace::expand("ns")?);
+    update(&namespaced, [create_at("refs/heads/dev")])?;
+
+    let mut store = gix_ref::reftable::Store::at(dir.path().into(), gix_hash::Kind::Sha1);
+    let names = |iter: gix_ref::reftable::iter::Iter| -> crate::Result<Vec<String>> {
+        Ok(iter
+            .map(|r| r.map(|r| r.name.as_bstr().to_string()))
+            .collect::<Result<_, _>>()?)
+    };
+    assert_eq!(
+        names(store.iter()?)?,
+        ["refs/heads/main", "refs/namespaces/ns/refs/heads/dev", "refs/tags/v1"]
+    );
+    assert_eq!(names(store.iter_prefixed("refs/tags/".into())?)?, ["refs/tags/v1"]);
+
+    store.namespace = namespaced.namespace;
+    assert_eq!(names(store.iter()?)?, ["refs/heads/dev"], "the namespace is stripped");
+    assert_eq!(
+        store.try_find("dev")?.expect("present").target.try_id(),
+        Some(hex_to_id(FIRST).as_ref())
+    );
+    assert!(
+        store.try_find("main")?.is_none(),
+        "references outside of the namespace aren't visible"
+    );
+    Ok(())
+}
+
+#[test]
+fn changes_made_by_other_stores_are_seen_once_the_list_of_tables_changes() -> crate::Result {
+    let (dir, store) = reftable_store()?;
+    update(&store, [create_at("refs/heads/main")])?;
+    assert_eq!(store.find("main")?.target.try_id(), Some(hex_to_id(FIRST).as_ref()));
+
+    let other = file::Store::at(
+        dir.path().into(),
+        init::Options {
+            ref_storage: RefStorage::Reftable,
+            ..Default::default()
+        },
+    );
+    update(&other, [set_peeled("refs/heads/main", SECOND, "moved")])?;
+    assert_eq!(
+        other.find("main")?.target.try_id(),
+        Some(hex_to_id(SECOND).as_ref()),
+        "the writer sees its own changes"
+    );
+    assert_eq!(
+        store.find("main")?.target.try_id(),
+        Some(hex_to_id(SECOND).as_ref()),
+        "the stack is reloaded as the list of tables changed"
+    );
+    Ok(())
+}
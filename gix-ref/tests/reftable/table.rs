@@ -0,0 +1,308 @@
+use gix_object::bstr::ByteSlice;
+use gix_ref::reftable::{decode, write, LogValue, Ref, RefValue, Table};
+
+use crate::reftable::{id, log, log_with_offset, val1};
+
+fn write_table(
+    min: u64,
+    max: u64,
+    options: write::Options,
+    refs: impl IntoIterator<Item = Ref>,
+    logs: impl IntoIterator<Item = gix_ref::reftable::Log>,
+) -> crate::Result<Table> {
+    let mut writer = write::Writer::new(min, max, options);
+    for r in refs {
+        writer.add_ref(r);
+    }
+    for l in logs {
+        writer.add_log(l);
+    }
+    Ok(Table::from_bytes(writer.finish()?, gix_hash::Kind::Sha1)?)
+}
+
+fn small_blocks() -> write::Options {
+    write::Options {
+        block_size: 256,
+        restart_interval: 3,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn empty_table_has_header_and_footer_only() -> crate::Result {
+    let table = write_table(1, 1, Default::default(), None, None)?;
+    assert_eq!(table.as_bytes().len(), 24 + 68, "version 1 header and footer");
+    assert_eq!(table.version(), 1);
+    assert_eq!(table.block_size(), 4096);
+    assert!(table.refs()?.is_empty());
+    assert!(table.logs()?.is_empty());
+    assert_eq!(table.find_ref("HEAD".into())?, None);
+    Ok(())
+}
+
+#[test]
+fn sha256_tables_are_version_2_and_identify_their_hash() -> crate::Result {
+    let options = write::Options {
+        object_hash: gix_hash::Kind::Sha256,
+        ..Default::default()
+    };
+    let id = gix_hash::ObjectId::from_bytes_or_panic(&[0x42; 32]);
+    let mut writer = write::Writer::new(1, 1, options);
+    writer.add_ref(Ref {
+        name: "refs/heads/main".into(),
+        update_index: 1,
+        value: RefValue::Val1(id),
+    });
+    let data = writer.finish()?;
+    assert_eq!(&data[24..28], b"s256", "the hash id follows the version 1 header");
+
+    let table = Table::from_bytes(data.clone(), gix_hash::Kind::Sha256)?;
+    assert_eq!(table.version(), 2);
+    assert_eq!(table.object_hash(), gix_hash::Kind::Sha256);
+    assert_eq!(
+        table.find_ref("refs/heads/main".into())?.map(|r| r.value),
+        Some(RefValue::Val1(id))
+    );
+
+    assert!(matches!(
+        Table::from_bytes(data.clone(), gix_hash::Kind::Sha1),
+        Err(decode::Error::ObjectHashMismatch {
+            expected: gix_hash::Kind::Sha1,
+            actual: gix_hash::Kind::Sha256
+        })
+    ));
+
+    let mut unknown_hash = data;
+    unknown_hash[24..28].copy_from_slice(b"abcd");
+    assert!(matches!(
+        Table::from_bytes(unknown_hash, gix_hash::Kind::Sha256),
+        Err(decode::Error::UnsupportedHash { hash_id }) if hash_id == u32::from_be_bytes(*b"abcd")
+    ));
+    Ok(())
+}
+
+#[test]
+fn all_value_types_round_trip() -> crate::Result {
+    let refs = vec![
+        Ref {
+            name: "HEAD".into(),
+            update_index: 5,
+            value: RefValue::Symref("refs/heads/main".into()),
+        },
+        val1("refs/heads/main", 5, 1),
+        Ref {
+            name: "refs/tags/v1".into(),
+            update_index: 6,
+            value: RefValue::Val2 {
+                target: id(2),
+                peeled: id(1),
+            },
+        },
+        Ref {
+            name: "refs/heads/gone".into(),
+            update_index: 6,
+            value: RefValue::Deletion,
+        },
+    ];
+    let table = write_table(5, 6, Default::default(), refs.clone(), None)?;
+    assert_eq!((table.min_update_index(), table.max_update_index()), (5, 6));
+
+    let mut expected = refs.clone();
+    expected.sort();
+    assert_eq!(table.refs()?, expected, "refs are sorted by name");
+    for r in refs {
+        assert_eq!(table.find_ref(r.name.as_bstr())?, Some(r));
+    }
+    assert_eq!(table.find_ref("refs/heads/ma".into())?, None, "only exact matches");
+    assert_eq!(table.find_ref("refs/heads/mainline".into())?, None);
+    assert_eq!(table.find_ref("refs/zz".into())?, None);
+    Ok(())
+}
+
+#[test]
+fn many_refs_in_small_blocks_are_indexed() -> crate::Result {
+    let refs: Vec<_> = (0..500u32)
+        .map(|n| val1(&format!("refs/heads/branch-{n:04}"), 1, n % 50))
+        .collect();
+    let table = write_table(1, 1, small_blocks(), refs.clone(), None)?;
+    assert!(
+        table.as_bytes().len() > 256 * 40,
+        "the table spans many blocks and thus needs a multi-level index"
+    );
+    assert_eq!(table.refs()?, refs);
+    for r in refs.iter().step_by(7) {
+        assert_eq!(table.find_ref(r.name.as_bstr())?.as_ref(), Some(r));
+    }
+    assert_eq!(table.find_ref("refs/heads/branch-0499x".into())?, None);
+    assert_eq!(table.find_ref("refs/heads/a".into())?, None);
+
+    let pointing_to_7: Vec<_> = table.refs_pointing_to(&id(7))?.into_iter().map(|r| r.name).collect();
+    assert_eq!(
+        pointing_to_7,
+        (0..10u32)
+            .map(|n| format!("refs/heads/branch-{:04}", n * 50 + 7))
+            .collect::<Vec<_>>(),
+        "the object index finds all refs pointing to an object"
+    );
+    assert!(table.refs_pointing_to(&id(51))?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn objects_can_be_found_without_object_index() -> crate::Result {
+    let refs = (0..500u32).map(|n| val1(&format!("refs/tags/t{n}"), 1, n % 3));
+    let table = write_table(
+        1,
+        1,
+        write::Options {
+            skip_index_objects: true,
+            ..small_blocks()
+        },
+        refs,
+        None,
+    )?;
+    assert_eq!(table.refs_pointing_to(&id(1))?.len(), 167);
+    Ok(())
+}
+
+#[test]
+fn logs_are_compressed_and_ordered_newest_first() -> crate::Result {
+    let logs = vec![
+        log("HEAD", 1, 0, 1, "commit (initial): first"),
+        log("HEAD", 2, 1, 2, "commit: second"),
+        log_with_offset(
+            "refs/heads/main",
+            1,
+            0,
+            1,
+            "branch: Created from HEAD",
+            -(2 * 3600 + 15 * 60),
+        ),
+        log("HEAD", 3, 2, 3, "commit: third"),
+    ];
+    let table = write_table(1, 3, Default::default(), None, logs.clone())?;
+    assert!(
+        table.as_bytes().len() < 4096,
+        "log blocks are compressed and not padded"
+    );
+
+    let head: Vec<_> = table
+        .logs_of("HEAD".into())?
+        .into_iter()
+        .map(|l| l.update_index)
+        .collect();
+    assert_eq!(head, [3, 2, 1]);
+    assert_eq!(table.logs_of("refs/heads/main".into())?, vec![logs[2].clone()]);
+    assert!(table.logs_of("refs/heads".into())?.is_empty());
+
+    let all = table.logs()?;
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[0], logs[3]);
+    assert_eq!(all[3], logs[2], "sorted by name, then newest first");
+    match &all[3].value {
+        LogValue::Update(line) => {
+            assert_eq!(
+                line.signature.time.offset,
+                -(2 * 3600 + 15 * 60),
+                "timezones round-trip"
+            );
+            assert_eq!(line.message, "branch: Created from HEAD", "no trailing newline");
+        }
+        LogValue::Deletion => unreachable!("not a deletion"),
+    }
+    Ok(())
+}
+
+#[test]
+fn many_logs_span_multiple_blocks() -> crate::Result {
+    let logs: Vec<_> = (1..=300u64)
+        .map(|n| {
+            log(
+                "refs/heads/main",
+                n,
+                n as u32 - 1,
+                n as u32,
+                &format!("commit: number {n} {}", "x".repeat(50)),
+            )
+        })
+        .collect();
+    let refs = (0..100u32).map(|n| val1(&format!("refs/heads/b{n}"), 300, n));
+    let table = write_table(1, 300, small_blocks(), refs, logs)?;
+    let found = table.logs_of("refs/heads/main".into())?;
+    assert_eq!(found.len(), 300);
+    assert_eq!(found[0].update_index, 300);
+    assert_eq!(found[299].update_index, 1);
+    assert_eq!(table.refs()?.len(), 100);
+    assert_eq!(
+        table.find_ref("refs/heads/b99".into())?,
+        Some(val1("refs/heads/b99", 300, 99))
+    );
+    Ok(())
+}
+
+#[test]
+fn writer_validates_its_input() {
+    let mut writer = write::Writer::new(2, 3, Default::default());
+    writer.add_ref(val1("refs/heads/main", 1, 1));
+    assert!(matches!(
+        writer.finish(),
+        Err(write::Error::UpdateIndexOutOfRange { update_index: 1, .. })
+    ));
+
+    let mut writer = write::Writer::new(1, 1, Default::default());
+    writer
+        .add_ref(val1("refs/heads/main", 1, 1))
+        .add_ref(val1("refs/heads/main", 1, 2));
+    assert!(matches!(writer.finish(), Err(write::Error::DuplicateRef { .. })));
+
+    let writer = write::Writer::new(
+        1,
+        1,
+        write::Options {
+            block_size: 10,
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        writer.finish(),
+        Err(write::Error::BlockSize { block_size: 10 })
+    ));
+
+    let mut writer = write::Writer::new(1, 1, small_blocks());
+    writer.add_ref(val1(&format!("refs/heads/{}", "a".repeat(300)), 1, 1));
+    assert!(matches!(writer.finish(), Err(write::Error::EntryTooBig { .. })));
+}
+
+#[test]
+fn corrupt_tables_are_rejected() -> crate::Result {
+    let mut writer = write::Writer::new(1, 1, Default::default());
+    writer.add_ref(val1("refs/heads/main", 1, 1));
+    let data = writer.finish()?;
+
+    let mut bad_crc = data.clone();
+    *bad_crc.last_mut().expect("not empty") ^= 1;
+    assert!(matches!(
+        Table::from_bytes(bad_crc, gix_hash::Kind::Sha1),
+        Err(decode::Error::Checksum { .. })
+    ));
+
+    let mut bad_magic = data.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(
+        Table::from_bytes(bad_magic, gix_hash::Kind::Sha1),
+        Err(decode::Error::Magic)
+    ));
+
+    let mut bad_version = data.clone();
+    bad_version[4] = 3;
+    assert!(matches!(
+        Table::from_bytes(bad_version, gix_hash::Kind::Sha1),
+        Err(decode::Error::UnsupportedVersion { version: 3 })
+    ));
+
+    assert!(matches!(
+        Table::from_bytes(data[..50].to_vec(), gix_hash::Kind::Sha1),
+        Err(decode::Error::TooShort { .. })
+    ));
+    Ok(())
+}
@@ -0,0 +1,131 @@
+/// The flags used to mark commits while searching for merge-bases.
+pub type Flags = u8;
+
+/// The error returned by the [`merge_base()`][function::merge_base()] function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A commit could not be inserted into the graph")]
+    InsertCommit(#[from] gix_revwalk::graph::try_lookup_or_insert_default::Error),
+}
+
+pub(crate) mod function {
+    use gix_hash::ObjectId;
+    use gix_revwalk::{graph, Graph, PriorityQueue};
+
+    use super::{Error, Flags};
+
+    /// The commit can be reached from the first commit.
+    const PARENT1: Flags = 1 << 0;
+    /// The commit can be reached from one of the other commits.
+    const PARENT2: Flags = 1 << 1;
+    /// The commit is reachable from a common commit, and thus can't be a best common ancestor.
+    const STALE: Flags = 1 << 2;
+    /// The commit was already recorded as common ancestor.
+    const RESULT: Flags = 1 << 3;
+
+    /// Given a commit at `first` and a list of `others`, return the best common ancestors of `first` with any of `others`,
+    /// or `None` if there is no common ancestor. The commits are traversed using `graph`, which will be cleared before use.
+    ///
+    /// Commits are returned in the order they were encountered, which is from newest to oldest commit date.
+    /// No returned commit is an ancestor of another returned commit, just like `git merge-base --all`.
+    pub fn merge_base(
+        first: ObjectId,
+        others: &[ObjectId],
+        graph: &mut Graph<'_, graph::Commit<Flags>>,
+    ) -> Result<Option<Vec<ObjectId>>, Error> {
+        let _span = gix_trace::coarse!("gix_revision::merge_base()", ?first, ?others);
+        if others.is_empty() || others.contains(&first) {
+            return Ok(Some(vec![first]));
+        }
+
+        graph.clear();
+        let bases = paint_down_to_common(first, others, graph)?;
+        graph.clear();
+        let bases = remove_redundant(bases, graph)?;
+        Ok((!bases.is_empty()).then_some(bases))
+    }
+
+    /// Mark all commits reachable from `first` and `others` and return the commits reachable from both, which
+    /// aren't reachable from another common commit. Note that the returned commits may still be ancestors of each other.
+    fn paint_down_to_common(
+        first: ObjectId,
+        others: &[ObjectId],
+        graph: &mut Graph<'_, graph::Commit<Flags>>,
+    ) -> Result<Vec<ObjectId>, Error> {
+        let mut queue = PriorityQueue::<gix_date::SecondsSinceUnixEpoch, ObjectId>::new();
+        for (id, flag) in std::iter::once((first, PARENT1)).chain(others.iter().map(|id| (*id, PARENT2))) {
+            if let Some(commit) = graph.try_lookup_or_insert_commit(id, |flags| *flags |= flag)? {
+                queue.insert(commit.commit_time, id);
+            }
+        }
+
+        let mut out = Vec::new();
+        while queue
+            .iter_unordered()
+            .any(|id| matches!(graph.get(id), Some(commit) if commit.data & STALE == 0))
+        {
+            let id = queue.pop_value().expect("non-empty as checked by loop condition");
+            let commit = graph.get_mut(&id).expect("queued commits are in graph");
+            let mut flags = commit.data & (PARENT1 | PARENT2 | STALE);
+            if flags & (PARENT1 | PARENT2) == (PARENT1 | PARENT2) {
+                if commit.data & RESULT == 0 {
+                    commit.data |= RESULT;
+                    out.push(id);
+                }
+                flags |= STALE;
+            }
+            for parent_id in commit.parents.clone() {
+                if let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? {
+                    if parent.data & flags == flags {
+                        continue;
+                    }
+                    parent.data |= flags;
+                    queue.insert(parent.commit_time, parent_id);
+                }
+            }
+        }
+        out.retain(|id| matches!(graph.get(id), Some(commit) if commit.data & STALE == 0));
+        Ok(out)
+    }
+
+    /// Remove all commits from `bases` that are reachable from other commits in `bases`.
+    fn remove_redundant(
+        bases: Vec<ObjectId>,
+        graph: &mut Graph<'_, graph::Commit<Flags>>,
+    ) -> Result<Vec<ObjectId>, Error> {
+        if bases.len() < 2 {
+            return Ok(bases);
+        }
+        let mut redundant = vec![false; bases.len()];
+        for idx in 0..bases.len() {
+            if redundant[idx] {
+                continue;
+            }
+            let others: Vec<_> = bases
+                .iter()
+                .enumerate()
+                .filter(|(other_idx, _)| *other_idx != idx && !redundant[*other_idx])
+                .map(|(_, id)| *id)
+                .collect();
+            if others.is_empty() {
+                break;
+            }
+            graph.clear();
+            paint_down_to_common(bases[idx], &others, graph)?;
+            for (other_idx, id) in bases.iter().enumerate() {
+                let flags = graph.get(id).map(|commit| commit.data).unwrap_or_default();
+                if other_idx == idx {
+                    redundant[idx] |= flags & PARENT2 != 0;
+                } else {
+                    redundant[other_idx] |= flags & PARENT1 != 0;
+                }
+            }
+        }
+        Ok(bases
+            .into_iter()
+            .zip(redundant)
+            .filter_map(|(id, redundant)| (!redundant).then_some(id))
+            .collect())
+    }
+}
@@ -0,0 +1,54 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+
+function commit() {
+  local name=${1:?first argument is the commit message and tag name}
+  git commit -q --allow-empty -m "$name"
+  git tag "$name"
+}
+
+function merge() {
+  local name=${1:?first argument is the commit message and tag name}
+  local other=${2:?second argument is the branch to merge}
+  git merge -q --no-ff -m "$name" "$other"
+  git tag "$name"
+}
+
+# Store the result of `git merge-base --all` for the given commits in `baseline`, one case per line
+# in the format `<names>: <commit-ids> -> <merge-base-ids>`.
+function baseline() {
+  echo "$*: $(git rev-parse "$@" | xargs) -> $(git merge-base --all "$@" | xargs || true)" >> baseline
+}
+
+commit root
+git checkout -q -b a
+commit a1
+git checkout -q -b b root
+commit b1
+
+git checkout -q a
+merge a2 b1
+git checkout -q b
+merge b2 a1
+
+git checkout -q -b c root
+commit c1
+commit c2
+
+git checkout -q --orphan unrelated
+commit u1
+
+git checkout -q a
+commit a3
+
+baseline a1 b1
+baseline a2 b2
+baseline a3 b2
+baseline a2 b1
+baseline c2 a3
+baseline c2 a2 b2
+baseline root c2
+baseline c2 c2
+baseline u1 a1
@@ -0,0 +1,32 @@
+use gix_hash::ObjectId;
+
+#[test]
+fn baseline() -> gix_testtools::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_merge_base_repos.sh")?;
+    let store = gix_odb::at(root.join(".git/objects"))?;
+    let to_ids = |hexes: &str| {
+        hexes
+            .split(' ')
+            .filter(|hex| !hex.is_empty())
+            .map(|hex| ObjectId::from_hex(hex.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let mut num_cases = 0;
+    let mut graph = gix_revision::Graph::new(&store, None);
+    for line in std::fs::read_to_string(root.join("baseline"))?.lines() {
+        let (names, ids) = line.split_once(": ").expect("valid baseline");
+        let (commits, expected) = ids.split_once(" -> ").expect("valid baseline");
+        let commits = to_ids(commits)?;
+        let mut expected = to_ids(expected)?;
+        expected.sort();
+
+        let actual = gix_revision::merge_base(commits[0], &commits[1..], &mut graph)?;
+        let mut actual = actual.unwrap_or_default();
+        actual.sort();
+        assert_eq!(actual, expected, "{names}");
+        num_cases += 1;
+    }
+    assert_eq!(num_cases, 9, "all cases were tested");
+    Ok(())
+}
@@ -1,5 +1,7 @@
 #[cfg(feature = "describe")]
 mod describe;
+#[cfg(feature = "merge_base")]
+mod merge_base;
 mod spec;
 pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + 'static>>;
 
@@ -1,2 +1,16 @@
-#![deny(rust_2018_idioms)]
+//! Parse and write the instructions of sequencer operations like interactive rebases, and persist their state
+//! compatibly with `git`.
+//!
+//! The instructions, like `pick <commit> <summary>`, are stored in a [todo list](todo::List) which is written to
+//! `git-rebase-todo` for the user to edit. The [state](rebase::State) of a rebase that is in progress is kept in the
+//! `.git/rebase-merge` directory so that it can be resumed by any implementation, including `git` itself.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+///
+#[allow(clippy::empty_docs)]
+pub mod todo;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod rebase;
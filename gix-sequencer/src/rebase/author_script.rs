@@ -0,0 +1,59 @@
+use bstr::{BString, ByteSlice, ByteVec};
+
+/// Serialize `author` in the format of the `author-script` file, which can be sourced by a Bourne shell.
+pub(crate) fn encode(author: gix_actor::SignatureRef<'_>) -> BString {
+    let mut out = BString::default();
+    for (name, value) in [
+        ("GIT_AUTHOR_NAME", author.name.to_owned()),
+        ("GIT_AUTHOR_EMAIL", author.email.to_owned()),
+        ("GIT_AUTHOR_DATE", format!("@{}", author.time.to_bstring()).into()),
+    ] {
+        out.push_str(name);
+        out.push(b'=');
+        out.extend_from_slice(&gix_quote::single(value.as_bstr()));
+        out.push(b'\n');
+    }
+    out
+}
+
+/// Parse the `author-script` file in `input`, or return `None` if it's malformed.
+pub(crate) fn decode(input: &[u8]) -> Option<gix_actor::Signature> {
+    let (mut name, mut email, mut time) = (None, None, None);
+    for line in input.lines() {
+        let (key, value) = line.split_once_str("=")?;
+        let value = unquote(value)?;
+        match key {
+            b"GIT_AUTHOR_NAME" => name = Some(value),
+            b"GIT_AUTHOR_EMAIL" => email = Some(value),
+            b"GIT_AUTHOR_DATE" => {
+                let value = value.to_str().ok()?;
+                time = Some(gix_date::parse(value.strip_prefix('@').unwrap_or(value), None).ok()?);
+            }
+            _ => {}
+        }
+    }
+    Some(gix_actor::Signature {
+        name: name?,
+        email: email?,
+        time: time?,
+    })
+}
+
+/// Undo the quoting of [`gix_quote::single()`], where `'` and `!` are written as `'\''` and `'\!'` respectively.
+fn unquote(mut input: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    input = input.strip_prefix(b"'")?;
+    loop {
+        let end = input.find_byte(b'\'')?;
+        out.extend_from_slice(&input[..end]);
+        input = &input[end + 1..];
+        match input {
+            [] => return Some(out),
+            [b'\\', escaped, b'\'', rest @ ..] => {
+                out.push(*escaped);
+                input = rest;
+            }
+            _ => return None,
+        }
+    }
+}
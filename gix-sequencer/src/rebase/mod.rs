@@ -0,0 +1,94 @@
+use bstr::BString;
+use gix_hash::ObjectId;
+
+use crate::todo;
+
+/// The name of the directory inside of the `.git` directory which holds the [state](State) of a rebase.
+pub const DIR_NAME: &str = "rebase-merge";
+
+/// A reference that is updated to a rewritten commit when a rebase completes, as done by `git rebase --update-refs`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateRef {
+    /// The full name of the reference, like `refs/heads/feature`.
+    pub name: BString,
+    /// The commit the reference pointed to when the rebase started.
+    pub before: ObjectId,
+    /// The rewritten commit the reference will point to, or the null id if its `update-ref` instruction wasn't carried out yet.
+    pub after: ObjectId,
+}
+
+/// The state of a rebase in progress, as stored in the `.git/rebase-merge` directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    /// The full name of the branch that is rebased, like `refs/heads/main`, or `None` if `HEAD` was detached.
+    pub head_name: Option<BString>,
+    /// The commit onto which the commits are rebased.
+    pub onto: ObjectId,
+    /// The commit `HEAD` pointed to when the rebase started.
+    pub orig_head: ObjectId,
+    /// If `true`, the rebase was started interactively, with the user having had the opportunity to edit the todo list.
+    pub interactive: bool,
+    /// The instructions that still have to be carried out, from `git-rebase-todo`.
+    pub todo: todo::List,
+    /// The instructions that were carried out already, the last one of which is the one currently carried out, from `done`.
+    pub done: todo::List,
+    /// The commit of the instruction that caused the rebase to stop, from `stopped-sha`.
+    pub stopped_at: Option<ObjectId>,
+    /// If set, the rebase stopped to allow amending this commit, which is the rewritten version of `stopped_at`,
+    /// as done by the `edit` instruction.
+    pub amend: Option<ObjectId>,
+    /// The message of the commit that is about to be created, from `message`.
+    pub message: Option<BString>,
+    /// The author of the commit that is about to be created, from `author-script`.
+    ///
+    /// If set and [`amend`](Self::amend) isn't set, the rebase stopped before the commit could be created,
+    /// typically due to conflicts.
+    pub author: Option<gix_actor::Signature>,
+    /// The `squash` and `fixup` instructions applied to the current commit so far, from `current-fixups`.
+    pub current_fixups: todo::List,
+    /// The combined message of all squashed commits so far, from `message-squash`.
+    pub squash_message: Option<BString>,
+    /// The references to update once the rebase completes, from `update-refs`.
+    pub update_refs: Vec<UpdateRef>,
+}
+
+impl State {
+    /// Create a new state for rebasing the commits in `todo` of the branch `head_name` (or detached `HEAD`)
+    /// at `orig_head` onto `onto`.
+    pub fn new(head_name: Option<BString>, onto: ObjectId, orig_head: ObjectId, todo: todo::List) -> Self {
+        State {
+            head_name,
+            onto,
+            orig_head,
+            interactive: false,
+            todo,
+            done: Default::default(),
+            stopped_at: None,
+            amend: None,
+            message: None,
+            author: None,
+            current_fixups: Default::default(),
+            squash_message: None,
+            update_refs: Vec::new(),
+        }
+    }
+
+    /// Return the instruction that is currently carried out or that caused the rebase to stop, if there is one.
+    pub fn current(&self) -> Option<&todo::Instruction> {
+        self.done.commands().last()
+    }
+
+    /// Return the number of the current instruction, starting at 1, along with the total number of instructions,
+    /// similar to the `msgnum` and `end` files.
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.done.num_commands();
+        (done, done + self.todo.num_commands())
+    }
+}
+
+mod author_script;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod read;
+mod write;
@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{
+    rebase::{author_script, State, UpdateRef},
+    todo,
+};
+
+/// The error returned by [`State::from_dir()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Could not parse object id in '{}'", path.display())]
+    ObjectId {
+        path: PathBuf,
+        source: gix_hash::decode::Error,
+    },
+    #[error("Could not parse todo list in '{}'", path.display())]
+    Todo { path: PathBuf, source: todo::decode::Error },
+    #[error("The author script at '{}' is malformed", path.display())]
+    AuthorScript { path: PathBuf },
+    #[error("The file '{}' is expected to contain the reference name, its previous and its new object id, on three lines each", path.display())]
+    UpdateRefs { path: PathBuf },
+}
+
+/// Deserialization
+impl State {
+    /// Read the state of a rebase from `dir`, which typically is `.git/rebase-merge`, using `resolve` to turn possibly
+    /// abbreviated commits in todo lists into object ids.
+    pub fn from_dir(dir: &Path, mut resolve: impl FnMut(&BStr) -> Option<ObjectId>) -> Result<Self, Error> {
+        let read = |name: &str| -> Result<Option<Vec<u8>>, Error> {
+            let path = dir.join(name);
+            match std::fs::read(&path) {
+                Ok(buf) => Ok(Some(buf)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(source) => Err(Error::Io { path, source }),
+            }
+        };
+        let required = |name: &str| -> Result<Vec<u8>, Error> {
+            read(name)?.ok_or_else(|| Error::Io {
+                path: dir.join(name),
+                source: std::io::ErrorKind::NotFound.into(),
+            })
+        };
+        let object_id = |name: &str, buf: &[u8]| {
+            ObjectId::from_hex(buf.trim()).map_err(|source| Error::ObjectId {
+                path: dir.join(name),
+                source,
+            })
+        };
+        let optional_id = |name: &str| -> Result<Option<ObjectId>, Error> {
+            read(name)?.map(|buf| object_id(name, &buf)).transpose()
+        };
+        let mut todo_list = |name: &str| -> Result<todo::List, Error> {
+            Ok(match read(name)? {
+                Some(buf) => todo::List::from_bytes(&buf, &mut resolve).map_err(|source| Error::Todo {
+                    path: dir.join(name),
+                    source,
+                })?,
+                None => Default::default(),
+            })
+        };
+
+        let head_name = required("head-name")?.trim().as_bstr().to_owned();
+        let onto = object_id("onto", &required("onto")?)?;
+        let orig_head = object_id("orig-head", &required("orig-head")?)?;
+        let todo = todo_list("git-rebase-todo")?;
+        let done = todo_list("done")?;
+        let current_fixups = todo_list("current-fixups")?;
+
+        let author = read("author-script")?
+            .map(|buf| {
+                author_script::decode(&buf).ok_or_else(|| Error::AuthorScript {
+                    path: dir.join("author-script"),
+                })
+            })
+            .transpose()?;
+
+        let mut update_refs = Vec::new();
+        if let Some(buf) = read("update-refs")? {
+            let error = || Error::UpdateRefs {
+                path: dir.join("update-refs"),
+            };
+            let mut lines = buf.lines();
+            while let Some(name) = lines.next() {
+                let (before, after) = (lines.next().ok_or_else(error)?, lines.next().ok_or_else(error)?);
+                update_refs.push(UpdateRef {
+                    name: name.into(),
+                    before: object_id("update-refs", before)?,
+                    after: object_id("update-refs", after)?,
+                });
+            }
+        }
+
+        Ok(State {
+            head_name: (head_name != "detached HEAD").then_some(head_name),
+            onto,
+            orig_head,
+            interactive: dir.join("interactive").is_file(),
+            todo,
+            done,
+            stopped_at: optional_id("stopped-sha")?,
+            amend: optional_id("amend")?,
+            message: read("message")?.map(Into::into),
+            author,
+            current_fixups,
+            squash_message: read("message-squash")?.map(Into::into),
+            update_refs,
+        })
+    }
+}
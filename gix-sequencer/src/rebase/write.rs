@@ -0,0 +1,85 @@
+use std::{io, path::Path};
+
+use bstr::ByteSlice;
+
+use crate::rebase::{author_script, State};
+
+/// Serialization
+impl State {
+    /// Write this state into `dir`, which typically is `.git/rebase-merge`, creating it if needed, in a way
+    /// that is compatible with `git rebase`.
+    ///
+    /// Files for optional state that isn't set are removed.
+    pub fn write_to_dir(&self, dir: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let write = |name: &str, data: Option<&[u8]>| -> io::Result<()> {
+            let path = dir.join(name);
+            match data {
+                Some(data) => std::fs::write(path, data),
+                None => match std::fs::remove_file(path) {
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                    res => res,
+                },
+            }
+        };
+        let line = |data: &[u8]| {
+            let mut buf = data.to_vec();
+            buf.push(b'\n');
+            buf
+        };
+        let id = |id: &gix_hash::ObjectId| line(id.to_hex().to_string().as_bytes());
+
+        write(
+            "head-name",
+            Some(&line(
+                self.head_name.as_ref().map_or(b"detached HEAD", |name| name.as_bytes()),
+            )),
+        )?;
+        write("onto", Some(&id(&self.onto)))?;
+        write("orig-head", Some(&id(&self.orig_head)))?;
+        write("interactive", self.interactive.then_some(b"".as_slice()))?;
+
+        let mut buf = Vec::new();
+        self.todo.write_to(&mut buf)?;
+        write("git-rebase-todo", Some(&buf))?;
+        buf.clear();
+        self.done.write_to(&mut buf)?;
+        write("done", Some(&buf))?;
+
+        let (msgnum, end) = self.progress();
+        write("msgnum", Some(&line(msgnum.to_string().as_bytes())))?;
+        write("end", Some(&line(end.to_string().as_bytes())))?;
+
+        write("stopped-sha", self.stopped_at.as_ref().map(id).as_deref())?;
+        write("amend", self.amend.as_ref().map(id).as_deref())?;
+        write("message", self.message.as_ref().map(|msg| msg.as_bytes()))?;
+        write(
+            "author-script",
+            self.author
+                .as_ref()
+                .map(|author| author_script::encode(author.to_ref()))
+                .as_ref()
+                .map(|script| script.as_bytes()),
+        )?;
+        write("message-squash", self.squash_message.as_ref().map(|msg| msg.as_bytes()))?;
+
+        let current_fixups = (!self.current_fixups.instructions.is_empty())
+            .then(|| {
+                let mut buf = Vec::new();
+                self.current_fixups.write_to(&mut buf).map(|_| buf)
+            })
+            .transpose()?;
+        write("current-fixups", current_fixups.as_deref())?;
+
+        let update_refs = (!self.update_refs.is_empty()).then(|| {
+            let mut buf = Vec::new();
+            for update in &self.update_refs {
+                buf.extend_from_slice(&line(update.name.as_bytes()));
+                buf.extend_from_slice(&id(&update.before));
+                buf.extend_from_slice(&id(&update.after));
+            }
+            buf
+        });
+        write("update-refs", update_refs.as_deref())
+    }
+}
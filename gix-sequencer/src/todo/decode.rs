@@ -0,0 +1,177 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::todo::{FixupMessage, Instruction, List};
+
+/// The error returned by [`List::from_bytes()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number}: unknown command '{command}'")]
+    UnknownCommand { line_number: usize, command: BString },
+    #[error("Line {line_number}: '{command}' needs an argument")]
+    MissingArgument { line_number: usize, command: &'static str },
+    #[error("Line {line_number}: '{command}' doesn't accept arguments, got '{arguments}'")]
+    UnexpectedArguments {
+        line_number: usize,
+        command: &'static str,
+        arguments: BString,
+    },
+    #[error("Line {line_number}: could not resolve '{name}' to a commit")]
+    UnresolvedCommit { line_number: usize, name: BString },
+}
+
+/// Parsing
+impl List {
+    /// Parse all lines in `input`, as found in a `git-rebase-todo` or `done` file.
+    ///
+    /// Commits are given as possibly abbreviated object ids or even revisions, which is why `resolve` is called to turn
+    /// them into object ids, returning `None` if that isn't possible.
+    /// Empty lines and lines starting with `#` are retained as [comments](Instruction::Comment).
+    pub fn from_bytes(input: &[u8], mut resolve: impl FnMut(&BStr) -> Option<ObjectId>) -> Result<List, Error> {
+        let mut instructions = Vec::new();
+        for (line_number, line) in input.lines().enumerate() {
+            instructions.push(parse_line(line, line_number + 1, &mut resolve)?);
+        }
+        Ok(List { instructions })
+    }
+}
+
+fn parse_line(
+    line: &[u8],
+    line_number: usize,
+    resolve: &mut dyn FnMut(&BStr) -> Option<ObjectId>,
+) -> Result<Instruction, Error> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with(b"#") {
+        return Ok(Instruction::Comment(line.trim_end().into()));
+    }
+    let (command, args) = split_word(trimmed);
+    let command = match command {
+        b"p" | b"pick" => "pick",
+        b"r" | b"reword" => "reword",
+        b"e" | b"edit" => "edit",
+        b"s" | b"squash" => "squash",
+        b"f" | b"fixup" => "fixup",
+        b"x" | b"exec" => "exec",
+        b"b" | b"break" => "break",
+        b"d" | b"drop" => "drop",
+        b"l" | b"label" => "label",
+        b"t" | b"reset" => "reset",
+        b"m" | b"merge" => "merge",
+        b"u" | b"update-ref" => "update-ref",
+        b"noop" => "noop",
+        _ => {
+            return Err(Error::UnknownCommand {
+                line_number,
+                command: command.into(),
+            })
+        }
+    };
+
+    match command {
+        "break" | "noop" => {
+            if !args.is_empty() {
+                return Err(Error::UnexpectedArguments {
+                    line_number,
+                    command,
+                    arguments: args.into(),
+                });
+            }
+            return Ok(if command == "break" {
+                Instruction::Break
+            } else {
+                Instruction::Noop
+            });
+        }
+        _ if args.is_empty() => return Err(Error::MissingArgument { line_number, command }),
+        _ => {}
+    }
+
+    let mut resolve = |name: &[u8]| {
+        resolve(name.as_bstr()).ok_or_else(|| Error::UnresolvedCommit {
+            line_number,
+            name: name.into(),
+        })
+    };
+    Ok(match command {
+        "exec" => Instruction::Exec { command: args.into() },
+        "label" => Instruction::Label {
+            name: split_word(args).0.into(),
+        },
+        "update-ref" => Instruction::UpdateRef {
+            name: split_word(args).0.into(),
+        },
+        "reset" => {
+            let (name, summary) = split_word(args);
+            Instruction::Reset {
+                name: name.into(),
+                summary: summary.into(),
+            }
+        }
+        "merge" => {
+            let (mut commit, mut edit_message, mut args) = (None, false, args);
+            let (flag, rest) = split_word(args);
+            if flag == b"-C" || flag == b"-c" {
+                let (name, rest) = split_word(rest);
+                if name.is_empty() {
+                    return Err(Error::MissingArgument { line_number, command });
+                }
+                commit = Some(resolve(name)?);
+                edit_message = flag == b"-c";
+                args = rest;
+            }
+            let mut labels = Vec::new();
+            while !args.is_empty() && !args.starts_with(b"#") {
+                let (label, rest) = split_word(args);
+                labels.push(label.into());
+                args = rest;
+            }
+            if labels.is_empty() {
+                return Err(Error::MissingArgument { line_number, command });
+            }
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary: args.into(),
+            }
+        }
+        _ => {
+            let (mut name, mut summary) = split_word(args);
+            let mut message = FixupMessage::Discard;
+            if command == "fixup" && (name == b"-C" || name == b"-c") {
+                message = if name == b"-C" {
+                    FixupMessage::Use
+                } else {
+                    FixupMessage::UseAndEdit
+                };
+                (name, summary) = split_word(summary);
+                if name.is_empty() {
+                    return Err(Error::MissingArgument { line_number, command });
+                }
+            }
+            let commit = resolve(name)?;
+            let summary = summary.into();
+            match command {
+                "pick" => Instruction::Pick { commit, summary },
+                "reword" => Instruction::Reword { commit, summary },
+                "edit" => Instruction::Edit { commit, summary },
+                "squash" => Instruction::Squash { commit, summary },
+                "fixup" => Instruction::Fixup {
+                    commit,
+                    message,
+                    summary,
+                },
+                "drop" => Instruction::Drop { commit, summary },
+                _ => unreachable!("BUG: all other commands were handled"),
+            }
+        }
+    })
+}
+
+/// Return the first word of `input`, and everything after it without leading whitespace.
+fn split_word(input: &[u8]) -> (&[u8], &[u8]) {
+    let end = input.iter().position(u8::is_ascii_whitespace).unwrap_or(input.len());
+    (&input[..end], input[end..].trim_start())
+}
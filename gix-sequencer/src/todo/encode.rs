@@ -0,0 +1,79 @@
+use std::io;
+
+use crate::todo::{FixupMessage, Instruction, List};
+
+/// Serialization
+impl List {
+    /// Write all instructions to `out`, one per line, in a format suitable for `git-rebase-todo` and `done` files.
+    ///
+    /// Commits are written as full object ids.
+    pub fn write_to(&self, mut out: &mut dyn io::Write) -> io::Result<()> {
+        for instruction in &self.instructions {
+            instruction.write_to(&mut out)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialization
+impl Instruction {
+    /// Write this instruction to `out` as a single line without trailing newline.
+    pub fn write_to(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let Some(command) = self.command_name() else {
+            if let Instruction::Comment(line) = self {
+                out.write_all(line)?;
+            }
+            return Ok(());
+        };
+        out.write_all(command.as_bytes())?;
+        let (arg, summary) = match self {
+            Instruction::Pick { commit, summary }
+            | Instruction::Reword { commit, summary }
+            | Instruction::Edit { commit, summary }
+            | Instruction::Squash { commit, summary }
+            | Instruction::Drop { commit, summary } => (Some(commit.to_hex().to_string().into()), Some(summary)),
+            Instruction::Fixup {
+                commit,
+                message,
+                summary,
+            } => {
+                match message {
+                    FixupMessage::Discard => {}
+                    FixupMessage::Use => out.write_all(b" -C")?,
+                    FixupMessage::UseAndEdit => out.write_all(b" -c")?,
+                }
+                (Some(commit.to_hex().to_string().into()), Some(summary))
+            }
+            Instruction::Exec { command } => (Some(command.clone()), None),
+            Instruction::Label { name } | Instruction::UpdateRef { name } => (Some(name.clone()), None),
+            Instruction::Reset { name, summary } => (Some(name.clone()), Some(summary)),
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary,
+            } => {
+                if let Some(commit) = commit {
+                    out.write_all(if *edit_message { b" -c " } else { b" -C " })?;
+                    out.write_all(commit.to_hex().to_string().as_bytes())?;
+                }
+                for label in labels {
+                    out.write_all(b" ")?;
+                    out.write_all(label)?;
+                }
+                (None, Some(summary))
+            }
+            Instruction::Break | Instruction::Noop | Instruction::Comment(_) => (None, None),
+        };
+        if let Some(arg) = arg {
+            out.write_all(b" ")?;
+            out.write_all(&arg)?;
+        }
+        if let Some(summary) = summary.filter(|summary| !summary.is_empty()) {
+            out.write_all(b" ")?;
+            out.write_all(summary)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// Determines which commit message to keep when a commit is melded into the previous commit with `fixup`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FixupMessage {
+    /// Keep the message of the previous commit, as done by `fixup <commit>`.
+    #[default]
+    Discard,
+    /// Use the message of the fixup commit instead, as done by `fixup -C <commit>`.
+    Use,
+    /// Use the message of the fixup commit instead, but let the user edit it, as done by `fixup -c <commit>`.
+    UseAndEdit,
+}
+
+/// A single line of a todo list, like `pick <commit> <summary>`.
+///
+/// The `summary` of instructions referring to commits is the remainder of the line after the commit, which typically
+/// is the subject of the commit. It's informational only and ignored when carrying out the instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// Use `commit` as is.
+    Pick {
+        /// The commit to pick.
+        commit: ObjectId,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Use `commit`, but edit its message.
+    Reword {
+        /// The commit to pick.
+        commit: ObjectId,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Use `commit`, but stop afterwards to allow amending it.
+    Edit {
+        /// The commit to pick.
+        commit: ObjectId,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Meld `commit` into the previous commit, and combine both of their messages.
+    Squash {
+        /// The commit to meld into the previous one.
+        commit: ObjectId,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Meld `commit` into the previous commit, and keep one of the messages depending on `message`.
+    Fixup {
+        /// The commit to meld into the previous one.
+        commit: ObjectId,
+        /// Which message to keep.
+        message: FixupMessage,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Run `command` with the shell, and stop if it fails.
+    Exec {
+        /// The command to run, which is the remainder of the line.
+        command: BString,
+    },
+    /// Stop here.
+    Break,
+    /// Remove `commit`.
+    Drop {
+        /// The commit to drop.
+        commit: ObjectId,
+        /// The remainder of the line.
+        summary: BString,
+    },
+    /// Label the current commit with `name`.
+    Label {
+        /// The name of the label.
+        name: BString,
+    },
+    /// Reset the current commit to the one labelled `name`.
+    Reset {
+        /// The name of a label, or a revision.
+        name: BString,
+        /// The remainder of the line after the name, typically a comment like `# <summary>`.
+        summary: BString,
+    },
+    /// Create a merge commit of the current commit and the commits labelled `labels`.
+    Merge {
+        /// The original merge commit whose message to use, or `None` to use the default message.
+        commit: Option<ObjectId>,
+        /// If `true`, the message of `commit` should be edited.
+        edit_message: bool,
+        /// The labels of the commits to merge.
+        labels: Vec<BString>,
+        /// The remainder of the line after the labels, typically a comment like `# <summary>`.
+        summary: BString,
+    },
+    /// Update the reference `name` to the current commit once the operation completes.
+    UpdateRef {
+        /// The full name of the reference to update.
+        name: BString,
+    },
+    /// Do nothing.
+    Noop,
+    /// A line that is empty or starts with `#`, which is retained as is, without the trailing newline.
+    Comment(BString),
+}
+
+/// A list of instructions, as stored in `git-rebase-todo` or `done`.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct List {
+    /// The instructions in the order in which they are carried out.
+    pub instructions: Vec<Instruction>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+mod encode;
+
+/// Access
+impl Instruction {
+    /// Return the name of the command of this instruction, like `pick`, or `None` if it is a comment.
+    pub fn command_name(&self) -> Option<&'static str> {
+        Some(match self {
+            Instruction::Pick { .. } => "pick",
+            Instruction::Reword { .. } => "reword",
+            Instruction::Edit { .. } => "edit",
+            Instruction::Squash { .. } => "squash",
+            Instruction::Fixup { .. } => "fixup",
+            Instruction::Exec { .. } => "exec",
+            Instruction::Break => "break",
+            Instruction::Drop { .. } => "drop",
+            Instruction::Label { .. } => "label",
+            Instruction::Reset { .. } => "reset",
+            Instruction::Merge { .. } => "merge",
+            Instruction::UpdateRef { .. } => "update-ref",
+            Instruction::Noop => "noop",
+            Instruction::Comment(_) => return None,
+        })
+    }
+
+    /// Return the commit this instruction refers to, if there is one.
+    pub fn commit(&self) -> Option<&ObjectId> {
+        match self {
+            Instruction::Pick { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. }
+            | Instruction::Squash { commit, .. }
+            | Instruction::Fixup { commit, .. }
+            | Instruction::Drop { commit, .. } => Some(commit),
+            Instruction::Merge { commit, .. } => commit.as_ref(),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::UpdateRef { .. }
+            | Instruction::Noop
+            | Instruction::Comment(_) => None,
+        }
+    }
+
+    /// Return `true` if this instruction melds a commit into the previous one, i.e. if it's `squash` or `fixup`.
+    pub fn is_fixup_or_squash(&self) -> bool {
+        matches!(self, Instruction::Squash { .. } | Instruction::Fixup { .. })
+    }
+
+    /// Return `true` if this is a comment.
+    pub fn is_comment(&self) -> bool {
+        matches!(self, Instruction::Comment(_))
+    }
+}
+
+/// Access
+impl List {
+    /// Return an iterator over all instructions that aren't comments.
+    pub fn commands(&self) -> impl Iterator<Item = &Instruction> + '_ {
+        self.instructions.iter().filter(|insn| !insn.is_comment())
+    }
+
+    /// Return the amount of instructions that aren't comments.
+    pub fn num_commands(&self) -> usize {
+        self.commands().count()
+    }
+}
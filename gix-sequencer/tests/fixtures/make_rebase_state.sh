@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local file=$1 content=$2 message=$3
+  echo "$content" > "$file"
+  git add "$file"
+  git commit -qm "$message"
+}
+
+git init -q edit
+(cd edit
+  commit a 1 one
+  commit b 1 two
+  git branch mid
+  commit c 1 three
+  commit b 2 "fixup! two"
+  git checkout -q -b feature
+
+  GIT_SEQUENCE_EDITOR="sed -i -e 's/^pick \(.*three\)$/edit \1/' -e '\$a exec true'" \
+    git rebase -q -i --autosquash --update-refs --root 2>/dev/null
+  git rev-parse HEAD > ../edit.head
+)
+
+git init -q conflict
+(cd conflict
+  commit file base base
+  git checkout -q -b other
+  commit file theirs "their change"
+  commit other 1 "unrelated change"
+  git checkout -q main 2>/dev/null || git checkout -q master
+  commit file ours "our change"
+
+  git rebase -q -i "$(git symbolic-ref --short HEAD)" other 2>/dev/null || test -f .git/rebase-merge/stopped-sha
+)
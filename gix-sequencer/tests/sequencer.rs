@@ -0,0 +1,292 @@
+pub use gix_testtools::Result;
+
+mod todo {
+    use bstr::{BStr, ByteSlice};
+    use gix_hash::ObjectId;
+    use gix_sequencer::todo::{decode, FixupMessage, Instruction, List};
+
+    fn id(hex: char) -> ObjectId {
+        ObjectId::from_hex(hex.to_string().repeat(40).as_bytes()).expect("valid hex")
+    }
+
+    /// Resolve `a`, `b` and `c` to ids made of that character, along with full ids, and fail to resolve everything else.
+    fn resolve(name: &BStr) -> Option<ObjectId> {
+        match name.as_bytes() {
+            [c @ (b'a' | b'b' | b'c')] => Some(id(*c as char)),
+            _ => ObjectId::from_hex(name).ok(),
+        }
+    }
+
+    fn parse(input: &str) -> Result<List, decode::Error> {
+        List::from_bytes(input.as_bytes(), resolve)
+    }
+
+    fn encode(list: &List) -> String {
+        let mut buf = Vec::new();
+        list.write_to(&mut buf).expect("writing to memory works");
+        String::from_utf8(buf).expect("valid UTF-8")
+    }
+
+    #[test]
+    fn all_commands_with_abbreviations() -> crate::Result {
+        let list = parse(
+            "p a one\nr b\ne c three  words \ns a\nf b\nf -C c fix\nfixup -c a\n\n# comment\nx make test\nb\nd a\n\
+             l onto\nt onto # reset\nm -C b onto other # Merge\nmerge topic\nu refs/heads/mid\nnoop\n",
+        )?;
+        assert_eq!(
+            list.instructions,
+            vec![
+                Instruction::Pick {
+                    commit: id('a'),
+                    summary: "one".into()
+                },
+                Instruction::Reword {
+                    commit: id('b'),
+                    summary: "".into()
+                },
+                Instruction::Edit {
+                    commit: id('c'),
+                    summary: "three  words".into()
+                },
+                Instruction::Squash {
+                    commit: id('a'),
+                    summary: "".into()
+                },
+                Instruction::Fixup {
+                    commit: id('b'),
+                    message: FixupMessage::Discard,
+                    summary: "".into()
+                },
+                Instruction::Fixup {
+                    commit: id('c'),
+                    message: FixupMessage::Use,
+                    summary: "fix".into()
+                },
+                Instruction::Fixup {
+                    commit: id('a'),
+                    message: FixupMessage::UseAndEdit,
+                    summary: "".into()
+                },
+                Instruction::Comment("".into()),
+                Instruction::Comment("# comment".into()),
+                Instruction::Exec {
+                    command: "make test".into()
+                },
+                Instruction::Break,
+                Instruction::Drop {
+                    commit: id('a'),
+                    summary: "".into()
+                },
+                Instruction::Label { name: "onto".into() },
+                Instruction::Reset {
+                    name: "onto".into(),
+                    summary: "# reset".into()
+                },
+                Instruction::Merge {
+                    commit: Some(id('b')),
+                    edit_message: false,
+                    labels: vec!["onto".into(), "other".into()],
+                    summary: "# Merge".into()
+                },
+                Instruction::Merge {
+                    commit: None,
+                    edit_message: false,
+                    labels: vec!["topic".into()],
+                    summary: "".into()
+                },
+                Instruction::UpdateRef {
+                    name: "refs/heads/mid".into()
+                },
+                Instruction::Noop,
+            ]
+        );
+        assert_eq!(list.num_commands(), 16);
+
+        let encoded = encode(&list);
+        assert_eq!(
+            encoded.lines().nth(5),
+            Some(format!("fixup -C {} fix", id('c')).as_str()),
+            "commands are spelled out and ids are written in full"
+        );
+        assert_eq!(parse(&encoded)?, list, "encoding roundtrips");
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse("pick a\nfrobnicate a"),
+            Err(decode::Error::UnknownCommand { line_number: 2, .. })
+        ));
+        assert!(matches!(
+            parse("pick"),
+            Err(decode::Error::MissingArgument {
+                line_number: 1,
+                command: "pick"
+            })
+        ));
+        assert!(matches!(
+            parse("fixup -C"),
+            Err(decode::Error::MissingArgument { command: "fixup", .. })
+        ));
+        assert!(matches!(
+            parse("merge -C a # no labels"),
+            Err(decode::Error::MissingArgument { command: "merge", .. })
+        ));
+        assert!(matches!(
+            parse("break now"),
+            Err(decode::Error::UnexpectedArguments { command: "break", .. })
+        ));
+        assert!(matches!(
+            parse("pick d"),
+            Err(decode::Error::UnresolvedCommit { line_number: 1, .. })
+        ));
+    }
+}
+
+mod rebase {
+    use std::path::{Path, PathBuf};
+
+    use bstr::{BStr, ByteSlice};
+    use gix_hash::ObjectId;
+    use gix_sequencer::{
+        rebase::{State, DIR_NAME},
+        todo::Instruction,
+    };
+
+    fn fixture() -> crate::Result<PathBuf> {
+        gix_testtools::scripted_fixture_read_only("make_rebase_state.sh")
+    }
+
+    fn state_dir(root: &Path, name: &str) -> PathBuf {
+        root.join(name).join(".git").join(DIR_NAME)
+    }
+
+    fn resolve(name: &BStr) -> Option<ObjectId> {
+        ObjectId::from_hex(name).ok()
+    }
+
+    #[test]
+    fn stopped_for_editing_with_update_refs() -> crate::Result {
+        let root = fixture()?;
+        let state = State::from_dir(&state_dir(&root, "edit"), resolve)?;
+        assert_eq!(
+            state.head_name.as_ref().map(|name| name.as_bstr()),
+            Some("refs/heads/feature".into())
+        );
+        assert!(state.interactive);
+        assert_eq!(state.progress(), (5, 7), "git counts update-ref and exec, too");
+
+        let Some(Instruction::Edit { commit, summary }) = state.current() else {
+            panic!("stopped at the edit instruction, got {:?}", state.current())
+        };
+        assert_eq!(summary, "three");
+        assert_eq!(state.stopped_at, Some(*commit));
+        let head = ObjectId::from_hex(std::fs::read(root.join("edit.head"))?.trim())?;
+        assert_eq!(state.amend, Some(head), "amend is the rewritten commit");
+        assert_eq!(
+            state.message.as_ref().map(|msg| msg.as_bstr()),
+            Some("three\n\n".into())
+        );
+        assert!(state.author.is_some());
+
+        assert!(
+            state
+                .done
+                .commands()
+                .any(|insn| matches!(insn, Instruction::Fixup { summary, .. } if summary == "fixup! two")),
+            "autosquash moved the fixup"
+        );
+        assert_eq!(state.update_refs.len(), 2);
+        let mid = state
+            .update_refs
+            .iter()
+            .find(|update| update.name == "refs/heads/mid")
+            .expect("present");
+        assert!(!mid.after.is_null(), "the update-ref instruction was carried out");
+        let main = state
+            .update_refs
+            .iter()
+            .find(|update| update.name != "refs/heads/mid")
+            .expect("present");
+        assert!(main.after.is_null(), "the update-ref instruction is still to do");
+        assert_eq!(main.before, state.orig_head);
+        Ok(())
+    }
+
+    #[test]
+    fn stopped_due_to_conflicts() -> crate::Result {
+        let root = fixture()?;
+        let state = State::from_dir(&state_dir(&root, "conflict"), resolve)?;
+        assert_eq!(
+            state.head_name.as_ref().map(|name| name.as_bstr()),
+            Some("refs/heads/other".into())
+        );
+        assert_eq!(state.progress(), (1, 2));
+        assert_eq!(state.current().and_then(Instruction::commit), state.stopped_at.as_ref());
+        assert_eq!(state.amend, None, "a conflict stops before the commit is created");
+        let author = state
+            .author
+            .as_ref()
+            .expect("author is retained for the commit to create");
+        assert_eq!(author.email, "author@example.com");
+        assert_eq!(
+            state.message.as_ref().map(|msg| msg.as_bstr()),
+            Some("their change\n\n".into())
+        );
+        assert!(state.update_refs.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn write_is_compatible_with_git() -> crate::Result {
+        let root = fixture()?;
+        for name in ["edit", "conflict"] {
+            let dir = state_dir(&root, name);
+            let state = State::from_dir(&dir, resolve)?;
+
+            let tmp = gix_testtools::tempfile::TempDir::new()?;
+            state.write_to_dir(tmp.path())?;
+            assert_eq!(State::from_dir(tmp.path(), resolve)?, state, "{name}: state roundtrips");
+
+            for file in [
+                "head-name",
+                "onto",
+                "orig-head",
+                "interactive",
+                "git-rebase-todo",
+                "done",
+                "msgnum",
+                "stopped-sha",
+                "amend",
+                "message",
+                "author-script",
+                "update-refs",
+            ] {
+                let (expected, actual) = (
+                    std::fs::read(dir.join(file)).ok(),
+                    std::fs::read(tmp.path().join(file)).ok(),
+                );
+                assert_eq!(
+                    actual.as_ref().map(|buf| buf.as_bstr()),
+                    expected.as_ref().map(|buf| buf.as_bstr()),
+                    "{name}: {file} is written like git does"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn detached_head_and_optional_files() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let id = ObjectId::null(gix_hash::Kind::Sha1);
+        let state = State::new(None, id, id, Default::default());
+        state.write_to_dir(tmp.path())?;
+        assert_eq!(std::fs::read(tmp.path().join("head-name"))?, b"detached HEAD\n");
+        assert!(!tmp.path().join("interactive").exists());
+        assert!(!tmp.path().join("author-script").exists());
+        assert_eq!(State::from_dir(tmp.path(), resolve)?, state);
+        Ok(())
+    }
+}
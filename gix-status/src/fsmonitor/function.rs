@@ -0,0 +1,68 @@
+use bstr::{BStr, ByteSlice};
+use gix_index::entry::Flags;
+
+use crate::fsmonitor::{Changes, Monitor, Token};
+
+/// Query `monitor` for changes since the token stored in the fsmonitor extension of `index` and set the
+/// [`FSMONITOR_VALID`](Flags::FSMONITOR_VALID) flag on all entries that are known to be unchanged, while clearing it on all others.
+/// Directories of the untracked cache that contain a changed path are invalidated as well.
+///
+/// Return the new token, which should be stored with [`FsMonitor::from_entries()`](gix_index::extension::FsMonitor::from_entries())
+/// once the entries that were found to be unchanged by a status check are marked as valid.
+///
+/// Note that if the query fails, all entries are considered changed.
+pub fn refresh(
+    index: &mut gix_index::State,
+    monitor: &mut dyn Monitor,
+) -> Result<Token, crate::fsmonitor::refresh::Error> {
+    let since = index
+        .mark_fs_monitor_valid_entries()
+        .then(|| index.fs_monitor().map(|fs_monitor| fs_monitor.token().clone()))
+        .flatten();
+    let response = match monitor.query(since.as_ref()) {
+        Ok(response) => response,
+        Err(err) => {
+            invalidate_all(index);
+            return Err(crate::fsmonitor::refresh::Error::Query(err));
+        }
+    };
+    match response.changes {
+        Changes::Paths(paths) if since.is_some() => {
+            if paths.iter().any(|path| path.is_empty() || path.as_slice() == b"/") {
+                invalidate_all(index);
+            } else {
+                for path in &paths {
+                    invalidate_path(index, path.as_bstr());
+                }
+            }
+        }
+        Changes::Paths(_) | Changes::All => invalidate_all(index),
+    }
+    Ok(response.token)
+}
+
+fn invalidate_all(index: &mut gix_index::State) {
+    for entry in index.entries_mut() {
+        entry.flags.remove(Flags::FSMONITOR_VALID);
+    }
+}
+
+/// Clear the valid flag of the entry at `path` and of all entries below it, in case it's a directory.
+fn invalidate_path(index: &mut gix_index::State, path: &BStr) {
+    if let Some(untracked) = index.untracked_mut() {
+        // A trailing slash invalidates the directory itself, instead of only its parent.
+        untracked.invalidate_path(path);
+    }
+    let path = path.strip_suffix(b"/").unwrap_or(path).as_bstr();
+    let Some(range) = index.prefixed_entries_range(path) else {
+        return;
+    };
+    let (entries, path_backing) = index.entries_mut_and_pathbacking();
+    for entry in &mut entries[range] {
+        let entry_path = entry.path_in(path_backing);
+        let is_affected = entry_path.len() == path.len() || entry_path.get(path.len()) == Some(&b'/');
+        if is_affected {
+            entry.flags.remove(Flags::FSMONITOR_VALID);
+        }
+    }
+}
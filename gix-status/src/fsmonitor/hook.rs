@@ -0,0 +1,125 @@
+use std::process::Stdio;
+
+use bstr::{BString, ByteSlice};
+
+use crate::fsmonitor::{Changes, Hook, Monitor, Response, Token};
+
+/// The version of the protocol used to communicate with the `core.fsmonitor` hook.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// The hook receives the time of the last query in nanoseconds since the unix epoch, and outputs changed paths.
+    V1,
+    /// The hook receives an opaque token it produced previously, and outputs a new token followed by changed paths.
+    #[default]
+    V2,
+}
+
+/// The error returned by [`Hook::run()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not run fsmonitor hook '{command}'")]
+    Spawn { command: BString, source: std::io::Error },
+    #[error("The fsmonitor hook '{command}' failed with {status}")]
+    Failed {
+        command: BString,
+        status: std::process::ExitStatus,
+    },
+    #[error("The fsmonitor hook '{command}' didn't output a token")]
+    MissingToken { command: BString },
+}
+
+/// The token git sends to hooks of version 2 if there was no previous query, to which they typically respond with a new token
+/// and a trivial response.
+const FAKE_TOKEN: &str = "builtin:fake";
+
+impl Hook {
+    /// Run the hook to learn which paths changed since the query that produced `since`, as documented for [`Monitor::query()`].
+    ///
+    /// Tokens of a different protocol version than ours are ignored, which causes all paths to be considered changed.
+    pub fn run(&self, since: Option<&Token>) -> Result<Response, Error> {
+        match self.version {
+            Version::V1 => self.run_v1(since),
+            Version::V2 => self.run_v2(since),
+        }
+    }
+
+    fn run_v1(&self, since: Option<&Token>) -> Result<Response, Error> {
+        let token = Token::V1 {
+            nanos_since_1970: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64),
+        };
+        let changes = match since {
+            Some(Token::V1 { nanos_since_1970 }) => {
+                Changes::Paths(parse_paths(&self.spawn("1", &nanos_since_1970.to_string())?))
+            }
+            Some(Token::V2 { .. }) | None => Changes::All,
+        };
+        Ok(Response { token, changes })
+    }
+
+    fn run_v2(&self, since: Option<&Token>) -> Result<Response, Error> {
+        let since = match since {
+            Some(Token::V2 { token }) => Some(token.to_str_lossy()),
+            Some(Token::V1 { .. }) | None => None,
+        };
+        let stdout = self.spawn("2", since.as_deref().unwrap_or(FAKE_TOKEN))?;
+        let (token, paths) = match stdout.find_byte(0) {
+            Some(pos) => (&stdout[..pos], &stdout[pos + 1..]),
+            None => (stdout.as_slice(), &[][..]),
+        };
+        if token.is_empty() {
+            return Err(Error::MissingToken {
+                command: self.command.clone(),
+            });
+        }
+        Ok(Response {
+            token: Token::V2 { token: token.into() },
+            changes: if since.is_some() {
+                Changes::Paths(parse_paths(paths))
+            } else {
+                Changes::All
+            },
+        })
+    }
+
+    fn spawn(&self, version: &str, token: &str) -> Result<Vec<u8>, Error> {
+        let mut cmd: std::process::Command =
+            gix_command::prepare(gix_path::from_bstr(self.command.as_bstr()).into_owned())
+                .with_shell()
+                .with_context(self.context.clone())
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .arg(version)
+                .arg(token)
+                .into();
+        cmd.current_dir(&self.worktree_dir);
+        gix_trace::debug!(cmd = ?cmd, "running fsmonitor hook");
+        let out = cmd.output().map_err(|source| Error::Spawn {
+            command: self.command.clone(),
+            source,
+        })?;
+        if !out.status.success() {
+            return Err(Error::Failed {
+                command: self.command.clone(),
+                status: out.status,
+            });
+        }
+        Ok(out.stdout)
+    }
+}
+
+impl Monitor for Hook {
+    fn query(&mut self, since: Option<&Token>) -> Result<Response, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(self.run(since)?)
+    }
+}
+
+fn parse_paths(out: &[u8]) -> Vec<BString> {
+    out.split(|b| *b == 0)
+        .filter(|path| !path.is_empty())
+        .map(Into::into)
+        .collect()
+}
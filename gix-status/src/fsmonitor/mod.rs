@@ -0,0 +1,70 @@
+//! Use a filesystem monitor to learn which index entries may have changed since the last query, to avoid checking all of them.
+//!
+//! The last token received from the filesystem monitor is stored in the `FSMN` index extension, along with a bitmap of the
+//! entries that were dirty at the time. Entries that are neither dirty nor reported as changed by the next query
+//! receive the [`FSMONITOR_VALID`](gix_index::entry::Flags::FSMONITOR_VALID) flag, which makes
+//! [`index_as_worktree()`](crate::index_as_worktree()) skip them.
+use bstr::BString;
+
+pub use gix_index::extension::fs_monitor::Token;
+
+mod function;
+pub use function::refresh;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod hook;
+
+/// A filesystem monitor which can tell which paths changed since a previous query.
+pub trait Monitor {
+    /// Return all paths that changed since the query that produced `since`, along with a new token to pass next time.
+    ///
+    /// If `since` is `None`, there was no previous query, and it's valid to return [`Changes::All`].
+    fn query(&mut self, since: Option<&Token>) -> Result<Response, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// The response of a [`Monitor`] to a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The token to pass to the next query, which should identify the point in time right before this query was started.
+    pub token: Token,
+    /// The paths that changed.
+    pub changes: Changes,
+}
+
+/// The changes reported by a [`Monitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// Everything has to be considered changed, for instance because the monitor was just started or lost track.
+    All,
+    /// Only these worktree-relative paths changed.
+    ///
+    /// Paths may refer to files or directories, and directories may also be indicated by a trailing slash.
+    /// In any case, all entries at or below a path are considered changed.
+    Paths(Vec<BString>),
+}
+
+/// A filesystem monitor that runs the `core.fsmonitor` hook with either version of its protocol.
+#[derive(Debug, Clone)]
+pub struct Hook {
+    /// The command to run through the shell, as configured in `core.fsmonitor`.
+    pub command: BString,
+    /// The version of the protocol to use, as configured in `core.fsmonitorHookVersion`.
+    pub version: hook::Version,
+    /// The directory to run the hook in, which is the root of the worktree.
+    pub worktree_dir: std::path::PathBuf,
+    /// Additional information to pass to the hook in its environment.
+    pub context: gix_command::Context,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod refresh {
+    /// The error returned by [`refresh()`](super::refresh()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not query the filesystem monitor")]
+        Query(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    }
+}
@@ -4,6 +4,7 @@
 //! * index and working tree
 //! * index and tree
 //! * find untracked files
+//! * query a filesystem monitor to avoid checking unchanged files
 //!
 //! While also being able to check check if the working tree is dirty, quickly.
 //!
@@ -20,9 +21,16 @@ pub use index_as_worktree::function::index_as_worktree;
 
 #[cfg(feature = "worktree-rewrites")]
 pub mod index_as_worktree_with_renames;
+
+pub mod fsmonitor;
 #[cfg(feature = "worktree-rewrites")]
 pub use index_as_worktree_with_renames::function::index_as_worktree_with_renames;
 
+#[cfg(feature = "tree-index")]
+pub mod tree_index;
+#[cfg(feature = "tree-index")]
+pub use tree_index::function::tree_index;
+
 /// A stack that validates we are not going through a symlink in a way that is read-only.
 ///
 /// It can efficiently validate paths when these are queried in sort-order, which leads to each component
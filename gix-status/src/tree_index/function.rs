@@ -0,0 +1,395 @@
+use std::sync::atomic::Ordering;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_diff::tree::visit::Action;
+use gix_index::entry::{Flags, Stage};
+use gix_object::{tree::EntryMode, FindExt};
+
+use crate::tree_index::{Change, Context, Error, Options, Outcome};
+
+/// Compare the tree with `tree_id` to `index` and call `delegate` for each [change](Change), similar to
+/// `git diff-index --cached <tree>`.
+///
+/// * `tree_id`
+///     - The tree to compare the index to, typically `HEAD^{tree}`.
+/// * `index`
+///     - The index whose entries are compared to the tree. Its `TREE` extension is used to skip over all entries
+///       of directories that are known to be unchanged.
+/// * `objects`
+///     - A way to obtain trees, and blobs for similarity checks during rewrite tracking.
+/// * `ctx`
+///     - Additional information that is needed to filter paths and to find rewrites.
+/// * `options`
+///     - Control if rewrites should be tracked.
+/// * `delegate`
+///     - Receives each change, and may cancel the operation by returning [`Action::Cancel`].
+///
+/// Changes are produced in the order of their paths, unless [rewrites](Options::rewrites) are tracked, which is
+/// when all changes are collected and sorted by their location once all of them are known.
+///
+/// Note that sparse directory entries of the index are reported as a [modification](Change::Modification) of
+/// the whole directory if its tree changed, as their content isn't known to the index.
+/// Further, the empty tree is never looked up, so `tree_id` may refer to it even if it's not in `objects`.
+pub fn tree_index(
+    tree_id: &gix_hash::oid,
+    index: &gix_index::State,
+    objects: &impl gix_object::FindObjectOrHeader,
+    ctx: Context<'_>,
+    options: Options,
+    mut delegate: impl FnMut(Change) -> Action,
+) -> Result<Outcome, Error> {
+    let _span = gix_features::trace::coarse!("gix_status::tree_index", tree_id = %tree_id);
+    let Context {
+        pathspec,
+        pathspec_attributes,
+        resource_cache,
+        should_interrupt,
+    } = ctx;
+    let mut changes = options.rewrites.map(|_| Vec::new());
+    let mut walk = Walk {
+        index,
+        entries: index.entries(),
+        cursor: 0,
+        objects,
+        pathspec,
+        pathspec_attributes,
+        should_interrupt,
+        path: BString::default(),
+        buffers: Vec::new(),
+        out: Outcome::default(),
+        cancelled: false,
+        changes: changes.as_mut(),
+        delegate: &mut delegate,
+    };
+    walk.tree(tree_id, index.tree())?;
+    let Walk { mut out, cancelled, .. } = walk;
+
+    if let Some((changes, rewrites)) = changes.zip(options.rewrites) {
+        if cancelled {
+            return Ok(out);
+        }
+        let changed_entries: Vec<_> = changes.iter().filter_map(Change::index).collect();
+        let mut tracker = gix_diff::rewrites::Tracker::<Change>::new(rewrites);
+        let mut changes: Vec<_> = changes
+            .into_iter()
+            .filter_map(|change| {
+                let location = change.location().to_owned();
+                tracker.try_push_change(change, location.as_ref())
+            })
+            .collect();
+        out.rewrites = Some(
+            tracker
+                .emit(
+                    |dest, source| {
+                        changes.push(match source {
+                            None => dest.change,
+                            Some(source) => {
+                                let Change::Addition {
+                                    location,
+                                    index,
+                                    entry_mode,
+                                    id,
+                                } = dest.change
+                                else {
+                                    unreachable!("BUG: only additions can be destinations of rewrites")
+                                };
+                                Change::Rewrite {
+                                    source_location: source.location.to_owned(),
+                                    source_entry_mode: source.entry_mode,
+                                    source_id: source.id,
+                                    location,
+                                    index,
+                                    entry_mode,
+                                    id,
+                                    diff: source.diff,
+                                    copy: source.kind == gix_diff::rewrites::tracker::visit::SourceKind::Copy,
+                                }
+                            }
+                        });
+                        Action::Continue
+                    },
+                    resource_cache,
+                    objects,
+                    |push| {
+                        // Unchanged entries are the same in the tree and in the index, and changed ones were tracked already.
+                        for (entry_index, entry) in index.entries().iter().enumerate() {
+                            let mode = index_entry_mode(entry);
+                            if is_hidden(entry)
+                                || entry.stage() != Stage::Unconflicted
+                                || !mode.is_blob_or_symlink()
+                                || changed_entries.binary_search(&entry_index).is_ok()
+                            {
+                                continue;
+                            }
+                            let location = entry.path(index);
+                            push(
+                                Change::Modification {
+                                    location: location.to_owned(),
+                                    index: entry_index,
+                                    previous_entry_mode: mode,
+                                    previous_id: entry.id,
+                                    entry_mode: mode,
+                                    id: entry.id,
+                                },
+                                location,
+                            );
+                        }
+                        Ok::<_, std::convert::Infallible>(())
+                    },
+                )
+                .map_err(|err| Error::RewriteTracker(Box::new(err)))?,
+        );
+
+        changes.sort_by(|a, b| a.location().cmp(b.location()));
+        for change in changes {
+            if delegate(change) == Action::Cancel {
+                break;
+            }
+        }
+    }
+    Ok(out)
+}
+
+struct Walk<'a, 'index, Find, Delegate> {
+    index: &'index gix_index::State,
+    entries: &'index [gix_index::Entry],
+    /// The index of the next entry in `entries` that wasn't handled yet.
+    cursor: usize,
+    objects: &'a Find,
+    pathspec: &'a mut gix_pathspec::Search,
+    pathspec_attributes: &'a mut dyn FnMut(
+        &BStr,
+        gix_pathspec::attributes::glob::pattern::Case,
+        bool,
+        &mut gix_pathspec::attributes::search::Outcome,
+    ) -> bool,
+    should_interrupt: &'a std::sync::atomic::AtomicBool,
+    /// The path of the current directory with a trailing slash, or the path of the current entry.
+    path: BString,
+    /// Buffers for tree data, one for each level of the directory hierarchy.
+    buffers: Vec<Vec<u8>>,
+    out: Outcome,
+    cancelled: bool,
+    /// If set, changes are collected here instead of being passed to `delegate`.
+    changes: Option<&'a mut Vec<Change>>,
+    delegate: &'a mut Delegate,
+}
+
+impl<Find, Delegate> Walk<'_, '_, Find, Delegate>
+where
+    Find: gix_object::Find,
+    Delegate: FnMut(Change) -> Action,
+{
+    /// Compare the tree with `tree_id` at the current directory path to all index entries with the same prefix,
+    /// using `cache` as the respective node of the `TREE` extension to possibly skip the comparison entirely.
+    fn tree(&mut self, tree_id: &gix_hash::oid, cache: Option<&gix_index::extension::Tree>) -> Result<(), Error> {
+        if self.should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if let Some(num_entries) = cache
+            .filter(|cache| cache.id == tree_id)
+            .and_then(|cache| cache.num_entries)
+        {
+            let end = (self.cursor + num_entries as usize).min(self.entries.len());
+            self.out.entries_skipped_by_tree_cache += end - self.cursor;
+            self.cursor = end;
+            return Ok(());
+        }
+
+        let mut buf = self.buffers.pop().unwrap_or_default();
+        // The empty tree doesn't have to exist in the object database, so it's never looked up.
+        let tree = if tree_id == gix_hash::ObjectId::empty_tree(tree_id.kind()) {
+            gix_object::TreeRefIter::from_bytes(&[], tree_id.kind())
+        } else {
+            self.out.trees_read += 1;
+            self.objects.find_tree_iter(tree_id, &mut buf)?
+        };
+        let dir_len = self.path.len();
+        for entry in tree {
+            let entry = entry?;
+            self.path.truncate(dir_len);
+            self.path.extend_from_slice(entry.filename);
+            if entry.mode.is_tree() {
+                self.path.push(b'/');
+            }
+            self.additions_before_current_path()?;
+            if self.cancelled {
+                break;
+            }
+
+            if entry.mode.is_tree() {
+                match self.entries.get(self.cursor) {
+                    Some(sparse_dir) if sparse_dir.mode.is_sparse() && sparse_dir.path(self.index) == self.path => {
+                        let entry_index = self.cursor;
+                        self.cursor += 1;
+                        self.out.entries_processed += 1;
+                        if sparse_dir.id != entry.oid {
+                            self.emit_if_included(
+                                Change::Modification {
+                                    location: self.path.clone(),
+                                    index: entry_index,
+                                    previous_entry_mode: entry.mode,
+                                    previous_id: entry.oid.to_owned(),
+                                    entry_mode: entry.mode,
+                                    id: sparse_dir.id,
+                                },
+                                true,
+                            );
+                        }
+                    }
+                    _ => {
+                        let dir = self.path[..self.path.len() - 1].as_bstr();
+                        if self.pathspec.can_match_relative_path(dir, Some(true)) {
+                            let cache = cache.and_then(|cache| {
+                                cache
+                                    .children
+                                    .iter()
+                                    .find(|child| child.name.as_slice() == entry.filename.as_bytes())
+                            });
+                            self.tree(entry.oid, cache)?;
+                        } else {
+                            self.skip_entries_with_prefix();
+                        }
+                    }
+                }
+            } else {
+                let entries_with_path = self.entries[self.cursor..]
+                    .iter()
+                    .take_while(|e| e.path(self.index) == self.path)
+                    .count();
+                let entry_index = self.cursor;
+                self.cursor += entries_with_path;
+                self.out.entries_processed += entries_with_path;
+                match self.entries.get(entry_index).filter(|_| entries_with_path != 0) {
+                    Some(index_entry) if index_entry.stage() != Stage::Unconflicted => {
+                        // Conflicts aren't reported here, but are visible when comparing the index to the worktree.
+                    }
+                    Some(index_entry) if !is_hidden(index_entry) => {
+                        let entry_mode = index_entry_mode(index_entry);
+                        if index_entry.id != entry.oid || entry_mode != entry.mode {
+                            self.emit_if_included(
+                                Change::Modification {
+                                    location: self.path.clone(),
+                                    index: entry_index,
+                                    previous_entry_mode: entry.mode,
+                                    previous_id: entry.oid.to_owned(),
+                                    entry_mode,
+                                    id: index_entry.id,
+                                },
+                                entry_mode.is_commit(),
+                            );
+                        }
+                    }
+                    _ => self.emit_if_included(
+                        Change::Deletion {
+                            location: self.path.clone(),
+                            entry_mode: entry.mode,
+                            id: entry.oid.to_owned(),
+                        },
+                        entry.mode.is_commit(),
+                    ),
+                }
+            }
+            if self.cancelled {
+                break;
+            }
+        }
+        self.path.truncate(dir_len);
+        self.buffers.push(buf);
+        if self.cancelled {
+            return Ok(());
+        }
+        self.additions_with_prefix()
+    }
+
+    /// Report all entries as added that sort before the current path, which are all in the current directory
+    /// as entries before it were handled already.
+    fn additions_before_current_path(&mut self) -> Result<(), Error> {
+        while let Some(entry) = self.entries.get(self.cursor) {
+            if entry.path(self.index) >= self.path.as_bstr() {
+                break;
+            }
+            self.addition(self.cursor)?;
+            if self.cancelled {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Report all remaining entries in the current directory as added.
+    fn additions_with_prefix(&mut self) -> Result<(), Error> {
+        while let Some(entry) = self.entries.get(self.cursor) {
+            if !entry.path(self.index).starts_with(&self.path) {
+                break;
+            }
+            self.addition(self.cursor)?;
+            if self.cancelled {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn addition(&mut self, entry_index: usize) -> Result<(), Error> {
+        if self.should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        let entry = &self.entries[entry_index];
+        self.cursor += 1;
+        self.out.entries_processed += 1;
+        if is_hidden(entry) || entry.stage() != Stage::Unconflicted {
+            return Ok(());
+        }
+        let entry_mode = index_entry_mode(entry);
+        self.emit_if_included(
+            Change::Addition {
+                location: entry.path(self.index).to_owned(),
+                index: entry_index,
+                entry_mode,
+                id: entry.id,
+            },
+            entry_mode.is_tree() || entry_mode.is_commit(),
+        );
+        Ok(())
+    }
+
+    /// Skip all entries in the directory at the current path, as they are excluded by pathspecs.
+    fn skip_entries_with_prefix(&mut self) {
+        while self
+            .entries
+            .get(self.cursor)
+            .map_or(false, |entry| entry.path(self.index).starts_with(&self.path))
+        {
+            self.cursor += 1;
+        }
+    }
+
+    fn emit_if_included(&mut self, change: Change, is_dir: bool) {
+        let location = change.location();
+        let location = location.strip_suffix(b"/").unwrap_or(location).as_bstr();
+        let is_excluded = self
+            .pathspec
+            .pattern_matching_relative_path(location, Some(is_dir), self.pathspec_attributes)
+            .map_or(true, |m| m.is_excluded());
+        if is_excluded {
+            return;
+        }
+        match self.changes.as_mut() {
+            Some(changes) => changes.push(change),
+            None => self.cancelled = (self.delegate)(change) == Action::Cancel,
+        }
+    }
+}
+
+/// Return `true` if `entry` isn't visible in the tree that would be written from the index.
+fn is_hidden(entry: &gix_index::Entry) -> bool {
+    entry.flags.contains(Flags::INTENT_TO_ADD)
+}
+
+fn index_entry_mode(entry: &gix_index::Entry) -> EntryMode {
+    entry
+        .mode
+        .to_tree_entry_mode()
+        .unwrap_or(gix_object::tree::EntryKind::Blob.into())
+}
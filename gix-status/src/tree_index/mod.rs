@@ -0,0 +1,7 @@
+//! Changes between a tree and an index, similar to `git diff-index --cached`, along with optional rename tracking.
+///
+#[allow(clippy::empty_docs)]
+mod types;
+pub use types::{Change, Context, Error, Options, Outcome, Summary};
+
+pub(super) mod function;
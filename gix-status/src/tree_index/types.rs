@@ -0,0 +1,229 @@
+use bstr::{BStr, BString};
+use gix_object::tree::EntryMode;
+use std::sync::atomic::AtomicBool;
+
+/// The error returned by [`tree_index()`](crate::tree_index()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The operation was interrupted")]
+    Interrupted,
+    #[error(transparent)]
+    FindTree(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    RewriteTracker(Box<gix_diff::rewrites::tracker::emit::Error>),
+}
+
+/// Options that control how the status of a tree compared to an index is computed.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Options {
+    /// If `Some(_)`, additions and deletions will be matched to find renames and copies, as configured.
+    ///
+    /// Note that when rewrite tracking is enabled, additions and deletions are delayed until all changes are known.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// The context for [`tree_index()`](crate::tree_index()).
+pub struct Context<'a> {
+    /// The pathspec to limit the amount of paths that are checked. Can be empty to allow all paths.
+    pub pathspec: &'a mut gix_pathspec::Search,
+    /// The `attributes` callback for use in [gix_pathspec::Search::pattern_matching_relative_path()], which happens when
+    /// pathspecs use attributes for filtering.
+    pub pathspec_attributes: &'a mut dyn FnMut(
+        &BStr,
+        gix_pathspec::attributes::glob::pattern::Case,
+        bool,
+        &mut gix_pathspec::attributes::search::Outcome,
+    ) -> bool,
+    /// A cache for blobs that are diffed to determine their similarity, used only if [rewrites](Options::rewrites)
+    /// are configured. It should be set to convert resources [to the Git representation](gix_diff::blob::pipeline::Mode::ToGit)
+    /// as both sides are read from the object database.
+    pub resource_cache: &'a mut gix_diff::blob::Platform,
+    /// A flag to query to learn if cancellation is requested.
+    pub should_interrupt: &'a AtomicBool,
+}
+
+/// Provide additional information collected during the runtime of [`tree_index()`](crate::tree_index()).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// The amount of index entries that were compared to their counterpart in the tree.
+    pub entries_processed: usize,
+    /// The amount of index entries that didn't need to be compared as the `TREE` extension of the index
+    /// showed that their directory is unchanged.
+    pub entries_skipped_by_tree_cache: usize,
+    /// The amount of trees that were read from the object database.
+    pub trees_read: usize,
+    /// The result of the rewrite operation, if [rewrites were configured](Options::rewrites).
+    pub rewrites: Option<gix_diff::rewrites::Outcome>,
+}
+
+/// A change between a tree and an index, as seen from the tree, i.e. an [addition](Change::Addition) is an entry
+/// that only exists in the index.
+///
+/// Note that entries with conflicts and entries that are only marked as *intent-to-add* are never reported, just like
+/// `git diff-index --cached` won't show them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// An entry exists in the index, but not in the tree.
+    Addition {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The index of the entry for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the entry in the index.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the index.
+        id: gix_hash::ObjectId,
+    },
+    /// An entry exists in the tree, but not in the index.
+    Deletion {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The mode of the entry in the tree.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the tree.
+        id: gix_hash::ObjectId,
+    },
+    /// An entry exists in both, the tree and the index, but its mode or object id differ.
+    Modification {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The index of the entry for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the entry in the tree.
+        previous_entry_mode: EntryMode,
+        /// The object id of the entry in the tree.
+        previous_id: gix_hash::ObjectId,
+        /// The mode of the entry in the index.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the index.
+        id: gix_hash::ObjectId,
+    },
+    /// The rewrite tracking matched an entry of the tree with an added entry of the index, and considers them
+    /// equal enough, depending on the tracker settings.
+    Rewrite {
+        /// The repository-relative path of the source of the rewrite, which is the entry in the tree.
+        source_location: BString,
+        /// The mode of the source of the rewrite.
+        source_entry_mode: EntryMode,
+        /// The object id of the source of the rewrite.
+        source_id: gix_hash::ObjectId,
+        /// The repository-relative path of the destination of the rewrite, which is the entry in the index.
+        location: BString,
+        /// The index of the destination entry for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the destination entry in the index.
+        entry_mode: EntryMode,
+        /// The object id of the destination entry in the index.
+        id: gix_hash::ObjectId,
+        /// It's `None` if `source_id` is equal to `id`, as identity made an actual diff computation unnecessary.
+        /// Otherwise, and if enabled, it's `Some(stats)` to indicate how similar both entries were.
+        diff: Option<gix_diff::blob::DiffLineStats>,
+        /// If true, this rewrite is created by copy, and `source_id` is pointing to its source, which still exists.
+        /// Otherwise, it's a rename, and the source was deleted.
+        copy: bool,
+    },
+}
+
+/// An easy to grasp summary of the changes of the index compared to a tree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Summary {
+    /// An entry exists in the index but not in the tree.
+    Added,
+    /// An entry exists in the tree but not in the index.
+    Removed,
+    /// An entry exists in both, but its content changed.
+    Modified,
+    /// An entry exists in both, but changed its type, for instance from a file to a symlink.
+    TypeChange,
+    /// An entry was renamed from its location in the tree.
+    Renamed,
+    /// An entry was copied from a location in the tree.
+    Copied,
+}
+
+/// Access
+impl Change {
+    /// Return the repository-relative path of the entry in the index, or in the tree if it was deleted.
+    pub fn location(&self) -> &BStr {
+        match self {
+            Change::Addition { location, .. }
+            | Change::Deletion { location, .. }
+            | Change::Modification { location, .. }
+            | Change::Rewrite { location, .. } => location.as_ref(),
+        }
+    }
+
+    /// Return the index of the entry in [`gix_index::State::entries()`], or `None` if it was deleted.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Change::Addition { index, .. } | Change::Modification { index, .. } | Change::Rewrite { index, .. } => {
+                Some(*index)
+            }
+            Change::Deletion { .. } => None,
+        }
+    }
+
+    /// Return a simplified summary of this change.
+    pub fn summary(&self) -> Summary {
+        match self {
+            Change::Addition { .. } => Summary::Added,
+            Change::Deletion { .. } => Summary::Removed,
+            Change::Modification {
+                previous_entry_mode,
+                entry_mode,
+                ..
+            } => {
+                if previous_entry_mode.kind() == entry_mode.kind()
+                    || (previous_entry_mode.is_blob() && entry_mode.is_blob())
+                {
+                    Summary::Modified
+                } else {
+                    Summary::TypeChange
+                }
+            }
+            Change::Rewrite { copy, .. } => {
+                if *copy {
+                    Summary::Copied
+                } else {
+                    Summary::Renamed
+                }
+            }
+        }
+    }
+}
+
+impl gix_diff::rewrites::tracker::Change for Change {
+    fn id(&self) -> &gix_hash::oid {
+        match self {
+            Change::Addition { id, .. }
+            | Change::Deletion { id, .. }
+            | Change::Modification { id, .. }
+            | Change::Rewrite { id, .. } => id,
+        }
+    }
+
+    fn kind(&self) -> gix_diff::rewrites::tracker::ChangeKind {
+        use gix_diff::rewrites::tracker::ChangeKind;
+        match self {
+            Change::Addition { .. } | Change::Rewrite { .. } => ChangeKind::Addition,
+            Change::Deletion { .. } => ChangeKind::Deletion,
+            Change::Modification { .. } => ChangeKind::Modification,
+        }
+    }
+
+    fn entry_mode(&self) -> EntryMode {
+        match self {
+            Change::Addition { entry_mode, .. }
+            | Change::Deletion { entry_mode, .. }
+            | Change::Modification { entry_mode, .. }
+            | Change::Rewrite { entry_mode, .. } => *entry_mode,
+        }
+    }
+
+    fn id_and_entry_mode(&self) -> (&gix_hash::oid, EntryMode) {
+        (self.id(), self.entry_mode())
+    }
+}
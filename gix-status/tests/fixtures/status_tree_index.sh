@@ -0,0 +1,33 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q staged-changes
+(cd staged-changes
+  touch empty
+  echo "content" > executable
+  chmod +x executable
+
+  mkdir -p dir/sub
+  echo "other content" > dir/content
+  echo "different content" > dir/content2
+  printf '1\n2\n3\n4\n5\n6\n' > dir/similar
+  echo "unchanged" > dir/sub/unchanged
+
+  git add -A
+  git commit -q -m "Commit"
+  git rev-parse @^{tree} > .git/HEAD_TREE
+
+  echo "change" >> dir/content
+  git update-index --chmod=-x executable
+  git mv dir/content2 renamed
+  git mv dir/similar similar-renamed
+  printf '1\n2\n3\n4\n5\n7\n' > similar-renamed
+  git rm -q empty
+  echo "new" > added
+  echo "intent" > intent-to-add
+  cp dir/sub/unchanged copied
+  git add dir/content similar-renamed added copied
+  git add -N intent-to-add
+
+  echo "unstaged" >> dir/sub/unchanged
+)
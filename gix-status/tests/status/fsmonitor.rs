@@ -0,0 +1,255 @@
+use bstr::{BStr, BString};
+use gix_index::{entry::Flags, extension::FsMonitor};
+use gix_status::fsmonitor::{self, Changes, Monitor, Response, Token};
+
+use crate::status::index_as_worktree::{fixture_with_index, Change, Expectation};
+
+/// A filesystem monitor that reports whatever it is told, to simulate a watcher running in-process.
+#[derive(Default)]
+struct Watcher {
+    queries: usize,
+    changed: Vec<&'static str>,
+    received: Vec<Option<Token>>,
+}
+
+impl Watcher {
+    fn changed(paths: &[&'static str]) -> Self {
+        Watcher {
+            changed: paths.to_vec(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Monitor for Watcher {
+    fn query(&mut self, since: Option<&Token>) -> Result<Response, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.queries += 1;
+        self.received.push(since.cloned());
+        Ok(Response {
+            token: token(&self.queries.to_string()),
+            changes: match since {
+                Some(_) => Changes::Paths(self.changed.drain(..).map(Into::into).collect()),
+                None => Changes::All,
+            },
+        })
+    }
+}
+
+struct Failing;
+
+impl Monitor for Failing {
+    fn query(
+        &mut self,
+        _since: Option<&Token>,
+    ) -> Result<Response, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, "watcher is gone").into())
+    }
+}
+
+fn token(value: &str) -> Token {
+    Token::V2 { token: value.into() }
+}
+
+/// Pretend that a previous query with `token` found all entries of `index` unchanged.
+fn set_all_valid(index: &mut gix_index::State, token: Token) {
+    for entry in index.entries_mut() {
+        entry.flags.insert(Flags::FSMONITOR_VALID);
+    }
+    index.set_fs_monitor(Some(FsMonitor::from_entries(token, index.entries())));
+}
+
+fn valid_paths(index: &gix_index::State) -> Vec<BString> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(Flags::FSMONITOR_VALID))
+        .map(|e| e.path(index).to_owned())
+        .collect()
+}
+
+#[test]
+fn only_entries_reported_as_changed_are_checked() {
+    let mut watcher = Watcher::changed(&["dir/", "does-not-exist"]);
+    let mut valid = Vec::new();
+    let content2_changed = (
+        BStr::new(b"dir/content2"),
+        1,
+        Change::Modification {
+            executable_bit_changed: false,
+            content_change: Some(()),
+            set_entry_stat_size_zero: false,
+        }
+        .into(),
+    );
+    let expected: &[Expectation<'_>] = &[
+        #[cfg(not(windows))]
+        (
+            BStr::new(b"dir/content"),
+            0,
+            Change::Modification {
+                executable_bit_changed: true,
+                content_change: None,
+                set_entry_stat_size_zero: false,
+            }
+            .into(),
+        ),
+        content2_changed,
+    ];
+    fixture_with_index(
+        "status_changed",
+        |index| {
+            set_all_valid(index, token("0"));
+            let new_token = fsmonitor::refresh(index, &mut watcher).expect("query succeeds");
+            assert_eq!(new_token, token("1"));
+            valid = valid_paths(index);
+        },
+        expected,
+    );
+    assert_eq!(watcher.received, [Some(token("0"))]);
+    assert_eq!(
+        valid,
+        ["empty", "executable"],
+        "all entries in the changed directory are invalidated, and the others are skipped even though they changed"
+    );
+}
+
+#[test]
+fn all_entries_are_checked_without_previous_token() {
+    let mut watcher = Watcher::changed(&["empty"]);
+    fixture_with_index(
+        "status_unchanged",
+        |index| {
+            for entry in index.entries_mut() {
+                entry.flags.insert(Flags::FSMONITOR_VALID);
+            }
+            assert!(index.fs_monitor().is_none());
+            fsmonitor::refresh(index, &mut watcher).expect("query succeeds");
+            assert!(
+                valid_paths(index).is_empty(),
+                "the valid flags are cleared if there is nothing to trust"
+            );
+        },
+        &[],
+    );
+    assert_eq!(watcher.received, [None]);
+}
+
+#[test]
+fn reporting_the_root_invalidates_everything() {
+    let mut watcher = Watcher::changed(&["/"]);
+    fixture_with_index(
+        "status_unchanged",
+        |index| {
+            set_all_valid(index, token("0"));
+            fsmonitor::refresh(index, &mut watcher).expect("query succeeds");
+            assert!(valid_paths(index).is_empty());
+        },
+        &[],
+    );
+}
+
+#[test]
+fn query_failures_invalidate_all_entries() {
+    fixture_with_index(
+        "status_unchanged",
+        |index| {
+            set_all_valid(index, token("0"));
+            let err = fsmonitor::refresh(index, &mut Failing).unwrap_err();
+            assert_eq!(err.to_string(), "Could not query the filesystem monitor");
+            assert!(valid_paths(index).is_empty());
+        },
+        &[],
+    );
+}
+
+mod hook {
+    use gix_status::fsmonitor::{hook, Changes, Hook, Response, Token};
+
+    fn hook_with_output(
+        version: hook::Version,
+        output: &str,
+    ) -> crate::Result<(Hook, gix_testtools::tempfile::TempDir)> {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let script = tmp.path().join("fsmonitor-hook");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"$1 $2\" > args\nprintf '{output}'\n"),
+        )?;
+        Ok((
+            Hook {
+                command: "sh fsmonitor-hook".into(),
+                version,
+                worktree_dir: tmp.path().to_owned(),
+                context: Default::default(),
+            },
+            tmp,
+        ))
+    }
+
+    fn args(tmp: &gix_testtools::tempfile::TempDir) -> crate::Result<String> {
+        Ok(std::fs::read_to_string(tmp.path().join("args"))?.trim_end().to_owned())
+    }
+
+    #[test]
+    fn v2_outputs_token_and_paths() -> crate::Result {
+        let (hook, tmp) = hook_with_output(hook::Version::V2, "token-2\\0a\\0dir/\\0")?;
+        let res = hook.run(Some(&Token::V2 {
+            token: "token-1".into(),
+        }))?;
+        assert_eq!(args(&tmp)?, "2 token-1", "the hook runs in the worktree");
+        assert_eq!(
+            res,
+            Response {
+                token: Token::V2 {
+                    token: "token-2".into()
+                },
+                changes: Changes::Paths(vec!["a".into(), "dir/".into()])
+            }
+        );
+
+        let res = hook.run(None)?;
+        assert_eq!(
+            args(&tmp)?,
+            "2 builtin:fake",
+            "git uses this token if there is no previous one"
+        );
+        assert_eq!(
+            res.changes,
+            Changes::All,
+            "paths can't be trusted without a previous token"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn v1_outputs_paths_and_uses_the_time_as_token() -> crate::Result {
+        let (hook, tmp) = hook_with_output(hook::Version::V1, "a\\0")?;
+        let res = hook.run(None)?;
+        assert_eq!(res.changes, Changes::All);
+        assert!(
+            !tmp.path().join("args").exists(),
+            "without token there is no need to ask"
+        );
+        let Token::V1 { nanos_since_1970 } = res.token else {
+            panic!("v1 tokens are timestamps")
+        };
+
+        let res = hook.run(Some(&res.token))?;
+        assert_eq!(args(&tmp)?, format!("1 {nanos_since_1970}"));
+        assert_eq!(res.changes, Changes::Paths(vec!["a".into()]));
+        assert!(matches!(res.token, Token::V1 { nanos_since_1970: now } if now >= nanos_since_1970));
+        Ok(())
+    }
+
+    #[test]
+    fn failures_are_errors() -> crate::Result {
+        let (mut hook, _tmp) = hook_with_output(hook::Version::V2, "")?;
+        assert!(
+            matches!(hook.run(None), Err(hook::Error::MissingToken { .. })),
+            "v2 hooks must output a token"
+        );
+        hook.command = "exit 1".into();
+        assert!(matches!(hook.run(None), Err(hook::Error::Failed { .. })));
+        Ok(())
+    }
+}
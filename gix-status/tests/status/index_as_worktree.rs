@@ -29,15 +29,15 @@ pub(super) const TEST_OPTIONS: index::entry::stat::Options = index::entry::stat:
     use_stdev: false,
 };
 
-type Change = WorktreeChange<(), ()>;
-type EntryStatus = WorktreeEntryStatus<(), ()>;
-type Expectation<'a> = (&'a BStr, usize, EntryStatus);
+pub(super) type Change = WorktreeChange<(), ()>;
+pub(super) type EntryStatus = WorktreeEntryStatus<(), ()>;
+pub(super) type Expectation<'a> = (&'a BStr, usize, EntryStatus);
 
 fn fixture(name: &str, expected_status: &[Expectation<'_>]) -> Outcome {
     fixture_filtered(name, &[], expected_status)
 }
 
-fn fixture_with_index(
+pub(super) fn fixture_with_index(
     name: &str,
     prepare_index: impl FnMut(&mut gix_index::State),
     expected_status: &[Expectation<'_>],
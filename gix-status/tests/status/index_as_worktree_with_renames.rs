@@ -240,6 +240,7 @@ fn fixture_filtered_detailed(
             git_dir_realpath: &git_dir_real,
             current_dir: &cwd,
             ignore_case_index_lookup: None,
+            untracked_cache: None,
         },
     };
     let options = Options {
@@ -0,0 +1,262 @@
+use crate::status::fixture_path;
+use gix_diff::tree::visit::Action;
+use gix_object::tree::{EntryKind, EntryMode};
+use gix_status::tree_index::{Change, Context, Options, Outcome, Summary};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn staged_changes() {
+    let (changes, out) = fixture("staged-changes", &[], None);
+    assert_eq!(
+        summaries(&changes),
+        [
+            ("added", Summary::Added),
+            ("copied", Summary::Added),
+            ("dir/content", Summary::Modified),
+            ("dir/content2", Summary::Removed),
+            ("dir/similar", Summary::Removed),
+            ("empty", Summary::Removed),
+            ("executable", Summary::Modified),
+            ("renamed", Summary::Added),
+            ("similar-renamed", Summary::Added),
+        ],
+        "unstaged changes and intent-to-add entries aren't visible"
+    );
+    let Change::Modification {
+        previous_entry_mode,
+        entry_mode,
+        previous_id,
+        id,
+        ..
+    } = &changes[6]
+    else {
+        unreachable!("the executable bit was removed")
+    };
+    assert_eq!(*previous_entry_mode, EntryMode::from(EntryKind::BlobExecutable));
+    assert_eq!(*entry_mode, EntryMode::from(EntryKind::Blob));
+    assert_eq!(previous_id, id, "only the mode changed");
+
+    assert_eq!(
+        out,
+        Outcome {
+            entries_processed: 7,
+            entries_skipped_by_tree_cache: 1,
+            trees_read: 2,
+            rewrites: None,
+        },
+        "the unchanged `dir/sub` isn't read at all as the tree cache knows it, but `dir` was changed"
+    );
+}
+
+#[test]
+fn staged_changes_with_rewrites() {
+    let (changes, out) = fixture("staged-changes", &[], Some(Default::default()));
+    assert_eq!(
+        summaries(&changes),
+        [
+            ("added", Summary::Added),
+            ("copied", Summary::Added),
+            ("dir/content", Summary::Modified),
+            ("empty", Summary::Removed),
+            ("executable", Summary::Modified),
+            ("renamed", Summary::Renamed),
+            ("similar-renamed", Summary::Renamed),
+        ],
+    );
+    let rewrites: Vec<_> = changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::Rewrite {
+                source_location,
+                location,
+                diff,
+                copy,
+                ..
+            } => Some((
+                source_location.to_string(),
+                location.to_string(),
+                diff.map(|diff| diff.similarity),
+                *copy,
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        rewrites,
+        [
+            ("dir/content2".into(), "renamed".into(), None, false),
+            ("dir/similar".into(), "similar-renamed".into(), Some(0.8333333), false),
+        ]
+    );
+    assert_eq!(out.rewrites.expect("enabled").num_similarity_checks, 3);
+}
+
+#[test]
+fn copies_from_unchanged_sources() {
+    let (changes, _out) = fixture(
+        "staged-changes",
+        &[],
+        Some(gix_diff::Rewrites {
+            copies: Some(gix_diff::rewrites::Copies {
+                source: gix_diff::rewrites::CopySource::FromSetOfModifiedFilesAndAllSources,
+                percentage: None,
+            }),
+            percentage: None,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        summaries(&changes),
+        [
+            ("added", Summary::Added),
+            ("copied", Summary::Copied),
+            ("dir/content", Summary::Modified),
+            ("dir/similar", Summary::Removed),
+            ("empty", Summary::Removed),
+            ("executable", Summary::Modified),
+            ("renamed", Summary::Renamed),
+            ("similar-renamed", Summary::Added),
+        ],
+        "without similarity, only exact renames are found, and copies can have unchanged sources"
+    );
+    let Change::Rewrite { source_location, .. } = &changes[1] else {
+        unreachable!("it's a copy")
+    };
+    assert_eq!(source_location, "dir/sub/unchanged");
+}
+
+#[test]
+fn pathspecs_limit_the_changes() {
+    let (changes, out) = fixture("staged-changes", &["dir/"], None);
+    assert_eq!(
+        summaries(&changes),
+        [
+            ("dir/content", Summary::Modified),
+            ("dir/content2", Summary::Removed),
+            ("dir/similar", Summary::Removed),
+        ],
+    );
+    assert_eq!(out.trees_read, 2);
+
+    let (changes, out) = fixture("staged-changes", &["e*"], None);
+    assert_eq!(
+        summaries(&changes),
+        [("empty", Summary::Removed), ("executable", Summary::Modified)],
+    );
+    assert_eq!(out.trees_read, 1, "directories that can't match aren't read");
+}
+
+#[test]
+fn empty_tree_shows_all_entries_as_added() {
+    let mut changes = Vec::new();
+    let out = fixture_with_delegate(
+        "staged-changes",
+        Some(gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1)),
+        &["dir/"],
+        None,
+        |change| {
+            changes.push(change);
+            Action::Continue
+        },
+    );
+    assert_eq!(
+        summaries(&changes),
+        [("dir/content", Summary::Added), ("dir/sub/unchanged", Summary::Added)],
+    );
+    assert_eq!(
+        out.trees_read, 0,
+        "the empty tree doesn't have to exist in the object database"
+    );
+}
+
+#[test]
+fn cancellation_stops_the_operation() {
+    let mut num_changes = 0;
+    fixture_with_delegate("staged-changes", None, &[], None, |_change| {
+        num_changes += 1;
+        Action::Cancel
+    });
+    assert_eq!(num_changes, 1);
+}
+
+fn summaries(changes: &[Change]) -> Vec<(&str, Summary)> {
+    changes
+        .iter()
+        .map(|change| {
+            (
+                std::str::from_utf8(change.location()).expect("valid UTF-8"),
+                change.summary(),
+            )
+        })
+        .collect()
+}
+
+fn fixture(name: &str, pathspecs: &[&str], rewrites: Option<gix_diff::Rewrites>) -> (Vec<Change>, Outcome) {
+    let mut changes = Vec::new();
+    let out = fixture_with_delegate(name, None, pathspecs, rewrites, |change| {
+        changes.push(change);
+        Action::Continue
+    });
+    (changes, out)
+}
+
+fn fixture_with_delegate(
+    name: &str,
+    tree_id: Option<gix_hash::ObjectId>,
+    pathspecs: &[&str],
+    rewrites: Option<gix_diff::Rewrites>,
+    delegate: impl FnMut(Change) -> Action,
+) -> Outcome {
+    let worktree = fixture_path("status_tree_index.sh").join(name);
+    let git_dir = worktree.join(".git");
+    let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default()).unwrap();
+    let tree_id = tree_id.unwrap_or_else(|| {
+        gix_hash::ObjectId::from_hex(
+            std::fs::read_to_string(git_dir.join("HEAD_TREE"))
+                .unwrap()
+                .trim()
+                .as_bytes(),
+        )
+        .unwrap()
+    });
+    let objects = gix_odb::at(git_dir.join("objects")).unwrap();
+    let mut pathspec = gix_pathspec::Search::from_specs(
+        crate::status::index_as_worktree::to_pathspecs(pathspecs),
+        None,
+        std::path::Path::new(""),
+    )
+    .expect("valid specs can be normalized");
+    let stack = gix_worktree::Stack::from_state_and_ignore_case(
+        worktree.clone(),
+        false,
+        gix_worktree::stack::State::AttributesStack(Default::default()),
+        &index,
+        index.path_backing(),
+    );
+    let mut resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            Default::default(),
+            gix_filter::Pipeline::new(Default::default(), Default::default()),
+            vec![],
+            Default::default(),
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        stack,
+    );
+
+    gix_status::tree_index(
+        &tree_id,
+        &index,
+        &objects,
+        Context {
+            pathspec: &mut pathspec,
+            pathspec_attributes: &mut |_, _, _, _| unreachable!("no attribute pathspecs"),
+            resource_cache: &mut resource_cache,
+            should_interrupt: &Default::default(),
+        },
+        Options { rewrites },
+        delegate,
+    )
+    .expect("no error")
+}
@@ -92,14 +92,16 @@ where
                     path: dest.to_path_buf(),
                 })?;
 
-            let filtered = filters.convert_to_worktree(
-                obj.data,
-                entry_path,
-                &mut |_, attrs| {
-                    path_cache.matching_attributes(attrs);
-                },
-                filter_process_delay,
-            )?;
+            let filtered = filters
+                .convert_to_worktree(
+                    obj.data,
+                    entry_path,
+                    &mut |_, attrs| {
+                        path_cache.matching_attributes(attrs);
+                    },
+                    filter_process_delay,
+                )
+                .map_err(Box::new)?;
             let (num_bytes, file, set_executable_after_creation) = match filtered {
                 ToWorktreeOutcome::Unchanged(buf) | ToWorktreeOutcome::Buffer(buf) => {
                     let (mut file, flag) = open_file(
@@ -165,7 +167,7 @@ where
         }
         gix_index::entry::Mode::DIR => {
             gix_features::trace::warn!(
-                "Skipped sparse directory at '{entry_path}' ({id}) as it cannot yet be handled",
+                "Skipped sparse directory at '{entry_path}' ({id}) as it isn't marked to be excluded from the worktree",
                 id = entry.id
             );
             0
@@ -237,6 +239,7 @@ fn open_options(path: &Path, destination_is_initially_empty: bool, overwrite_exi
     options
         .create_new(destination_is_initially_empty && !overwrite_existing)
         .create(!destination_is_initially_empty || overwrite_existing)
+        .truncate(true)
         .write(true);
     options
 }
@@ -9,7 +9,8 @@ use crate::checkout::chunk;
 /// respective path in `dir`.
 /// Use `files` to count each fully checked out file, and count the amount written `bytes`. If `should_interrupt` is `true`, the
 /// operation will abort.
-/// `options` provide a lot of context on how to perform the operation.
+/// `options` provide a lot of context on how to perform the operation, and may contain sparse-checkout patterns
+/// to decide which entries to write into the worktree.
 ///
 /// ### Handling the return value
 ///
@@ -28,12 +29,45 @@ pub fn checkout<Find>(
 where
     Find: gix_object::Find + Send + Clone,
 {
+    if let Some(patterns) = options.sparse_checkout.as_ref() {
+        apply_sparse_checkout(index, patterns, &objects, options.validate)?;
+    }
     let paths = index.take_path_backing();
     let res = checkout_inner(index, &paths, dir, objects, files, bytes, should_interrupt, options);
     index.return_path_backing(paths);
     res
 }
 
+/// Set or clear the [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag of all entries in `index` according to
+/// the sparse-checkout `patterns`, after expanding all sparse directories that may contain included entries using `objects`.
+/// `validate` controls how path components of expanded entries are validated.
+///
+/// Conflicting entries are never excluded from the worktree, and the worktree itself isn't touched.
+/// This is what [`checkout()`] does if [`Options::sparse_checkout`](crate::checkout::Options::sparse_checkout) is set.
+pub fn apply_sparse_checkout<Find>(
+    index: &mut gix_index::State,
+    patterns: &gix_worktree::sparse::Patterns,
+    objects: Find,
+    validate: gix_worktree::validate::path::component::Options,
+) -> Result<(), crate::checkout::Error>
+where
+    Find: gix_object::Find,
+{
+    index.expand_sparse_directories(objects, validate, |dir| {
+        patterns.directory(dir) != gix_worktree::sparse::Directory::Excluded
+    })?;
+    for (entry, entry_path) in index.entries_mut_with_paths() {
+        if entry.mode.is_sparse() {
+            continue;
+        }
+        let is_conflicted = entry.stage() != gix_index::entry::Stage::Unconflicted;
+        entry
+            .flags
+            .set_skip_worktree(!is_conflicted && !patterns.is_included(entry_path));
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn checkout_inner<Find>(
     index: &mut gix_index::State,
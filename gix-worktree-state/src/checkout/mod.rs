@@ -68,6 +68,13 @@ pub struct Options {
     pub filters: gix_filter::Pipeline,
     /// Control how long-running processes may use the 'delay' capability.
     pub filter_process_delay: gix_filter::driver::apply::Delay,
+    /// If set, the sparse-checkout patterns, typically from `.git/info/sparse-checkout`, decide which entries are checked out.
+    /// The [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag of each entry is set or cleared accordingly,
+    /// and sparse directory entries that may contain included entries are expanded beforehand.
+    ///
+    /// Note that files of entries that are newly excluded from the worktree are not removed.
+    /// If `None`, entries are checked out according to their current `SKIP_WORKTREE` flag.
+    pub sparse_checkout: Option<gix_worktree::sparse::Patterns>,
 }
 
 /// The error returned by the [checkout()][crate::checkout()] function.
@@ -87,7 +94,7 @@ pub enum Error {
         path: std::path::PathBuf,
     },
     #[error(transparent)]
-    Filter(#[from] gix_filter::pipeline::convert::to_worktree::Error),
+    Filter(#[from] Box<gix_filter::pipeline::convert::to_worktree::Error>),
     #[error(transparent)]
     FilterListDelayed(#[from] gix_filter::driver::delayed::list::Error),
     #[error(transparent)]
@@ -96,6 +103,8 @@ pub enum Error {
     FilterPathUnknown { rela_path: BString },
     #[error("The following paths were delayed and apparently forgotten to be processed by the filter driver: ")]
     FilterPathsUnprocessed { rela_paths: Vec<BString> },
+    #[error("Could not expand sparse directory entries to check out their content")]
+    ExpandSparseDirectories(#[from] gix_index::sparse::expand::Error),
 }
 
 mod chunk;
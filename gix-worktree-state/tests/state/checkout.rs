@@ -62,6 +62,51 @@ fn submodules_are_instantiated_as_directories() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn sparse_directories_and_excluded_entries_are_skipped() -> crate::Result {
+    let (_source_tree, destination, index, outcome) =
+        checkout_index_in_tmp_dir(opts_from_probe(), "make_sparse_index", None)?;
+    assert!(index.is_sparse());
+    assert_eq!(
+        outcome.files_updated, 8,
+        "sparse directories are counted as skipped entries"
+    );
+    assert!(outcome.errors.is_empty());
+    assert_eq!(
+        stripped_prefix(&destination, &dir_structure(&destination)),
+        paths(["a", "b", "c1/a", "c1/b", "c1/c2/a", "c1/c2/b"]),
+    );
+    Ok(())
+}
+
+#[test]
+fn sparse_checkout_patterns_set_skip_worktree_and_expand_sparse_directories() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.sparse_checkout = Some(gix_worktree::sparse::Patterns::from_cone_directories(
+        ["c1/c3".into(), "d/c4".into()],
+        gix_worktree::glob::pattern::Case::Sensitive,
+    ));
+    let (_source_tree, destination, index, outcome) = checkout_index_in_tmp_dir(opts, "make_sparse_index", None)?;
+    assert!(outcome.errors.is_empty());
+    assert_eq!(
+        stripped_prefix(&destination, &dir_structure(&destination)),
+        paths(["a", "b", "c1/a", "c1/b", "c1/c3/a", "c1/c3/b", "d/a", "d/b", "d/c4/a", "d/c4/b", "d/c4/c5"]),
+    );
+    assert!(
+        !index.is_sparse(),
+        "the sparse directories were expanded as they contain included entries"
+    );
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        assert_eq!(
+            entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE),
+            path.starts_with(b"c1/c2/"),
+            "{path}: c1/c2 was previously included, but now it's not"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn accidental_writes_through_symlinks_are_prevented_if_overwriting_is_forbidden() {
     let mut opts = opts_from_probe();
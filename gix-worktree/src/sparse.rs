@@ -0,0 +1,320 @@
+use std::{borrow::Cow, collections::BTreeSet};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_glob::pattern::Case;
+
+/// The patterns of a sparse checkout, typically read from `.git/info/sparse-checkout`, which decide which index entries
+/// are checked out into the worktree and which are marked with [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE).
+///
+/// In _cone mode_, patterns are restricted to directories which are included recursively, along with all files directly
+/// contained in their parent directories and in the root of the repository.
+/// This allows to decide if entire directories are excluded, which is the foundation for sparse indices.
+/// Otherwise, patterns are interpreted like `.gitignore` patterns, with matching entries being included.
+#[derive(Debug, Clone)]
+pub struct Patterns {
+    inner: Inner,
+    case: Case,
+}
+
+#[derive(Debug, Clone)]
+enum Inner {
+    Cone {
+        /// Directories that are included recursively, without leading or trailing slash.
+        recursive: BTreeSet<BString>,
+        /// Directories whose files are included, as they lead to a recursively included directory.
+        parents: BTreeSet<BString>,
+    },
+    NonCone {
+        /// The lines of the patterns file, for serialization.
+        lines: Vec<BString>,
+        patterns: gix_glob::search::pattern::List<gix_ignore::search::Ignore>,
+    },
+}
+
+/// Describes how the entries of a directory are affected by sparse-checkout [patterns](Patterns).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Directory {
+    /// All entries of the directory are included in the worktree, recursively.
+    Included,
+    /// Some entries of the directory may be included in the worktree, which also is the case
+    /// for all directories if the patterns aren't in cone mode.
+    Partial,
+    /// No entry of the directory is included in the worktree, so all of them may be represented
+    /// by a single sparse directory entry in a sparse index.
+    Excluded,
+}
+
+/// Instantiation
+impl Patterns {
+    /// Parse the patterns in `bytes`, typically the content of the `.git/info/sparse-checkout` file, and try to interpret
+    /// them in cone mode if `cone_mode` is `true`.
+    /// If that fails as patterns don't follow the restrictions of cone mode, we fall back to non-cone mode, just like `git` does.
+    /// `case` determines if paths are matched case-sensitively.
+    pub fn from_bytes(bytes: &[u8], cone_mode: bool, case: Case) -> Self {
+        let cone = cone_mode.then(|| parse_cone(bytes, case)).flatten();
+        let inner = match cone {
+            Some((recursive, parents)) => Inner::Cone { recursive, parents },
+            None => {
+                if cone_mode {
+                    gix_features::trace::warn!(
+                        "Sparse-checkout patterns are not compatible with cone mode - falling back to non-cone mode"
+                    );
+                }
+                non_cone(
+                    bytes
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(|line| line.as_bstr().to_owned())
+                        .collect(),
+                )
+            }
+        };
+        Patterns { inner, case }
+    }
+
+    /// Create cone-mode patterns which include the given `directories` recursively, like `git sparse-checkout set --cone` would.
+    /// Leading and trailing slashes of each directory are ignored, and `case` determines if paths are matched case-sensitively.
+    pub fn from_cone_directories<'a>(directories: impl IntoIterator<Item = &'a BStr>, case: Case) -> Self {
+        let mut out = Patterns {
+            inner: Inner::Cone {
+                recursive: Default::default(),
+                parents: Default::default(),
+            },
+            case,
+        };
+        out.add(directories);
+        out
+    }
+
+    /// Create non-cone patterns from the given `.gitignore` style `patterns`, with matching paths being included in the worktree,
+    /// like `git sparse-checkout set --no-cone` would.
+    /// `case` determines if paths are matched case-sensitively.
+    pub fn from_non_cone_patterns<'a>(patterns: impl IntoIterator<Item = &'a BStr>, case: Case) -> Self {
+        Patterns {
+            inner: non_cone(patterns.into_iter().map(ToOwned::to_owned).collect()),
+            case,
+        }
+    }
+}
+
+/// Mutation
+impl Patterns {
+    /// Add the given `patterns`, which are directories to include recursively in cone mode, or `.gitignore` style patterns otherwise.
+    pub fn add<'a>(&mut self, patterns: impl IntoIterator<Item = &'a BStr>) {
+        let case = self.case;
+        match &mut self.inner {
+            Inner::Cone { recursive, parents } => {
+                let mut all: BTreeSet<_> = std::mem::take(recursive);
+                all.extend(
+                    patterns
+                        .into_iter()
+                        .map(|dir| normalize_case(dir.trim_with(|c| c == '/').as_bstr(), case).into_owned())
+                        .filter(|dir| !dir.is_empty()),
+                );
+                parents.clear();
+                for dir in &all {
+                    if has_ancestor_in(dir.as_bstr(), &all) {
+                        continue;
+                    }
+                    let mut parent = dir.as_bstr();
+                    while let Some(pos) = parent.rfind_byte(b'/') {
+                        parent = parent[..pos].as_bstr();
+                        parents.insert(parent.to_owned());
+                    }
+                    recursive.insert(dir.clone());
+                }
+            }
+            Inner::NonCone { lines, .. } => {
+                let mut lines = std::mem::take(lines);
+                lines.extend(patterns.into_iter().map(ToOwned::to_owned));
+                self.inner = non_cone(lines);
+            }
+        }
+    }
+}
+
+/// Access
+impl Patterns {
+    /// Return `true` if these patterns are in cone mode.
+    pub fn is_cone(&self) -> bool {
+        matches!(self.inner, Inner::Cone { .. })
+    }
+
+    /// Return the patterns in a form suitable for display, similar to what `git sparse-checkout list` shows.
+    ///
+    /// In cone mode, these are the recursively included directories, otherwise these are all `.gitignore` style patterns.
+    pub fn list(&self) -> Vec<&BStr> {
+        match &self.inner {
+            Inner::Cone { recursive, .. } => recursive.iter().map(AsRef::as_ref).collect(),
+            Inner::NonCone { lines, .. } => lines.iter().map(AsRef::as_ref).collect(),
+        }
+    }
+
+    /// Return `true` if the file or symlink at `rela_path` should be present in the worktree.
+    pub fn is_included(&self, rela_path: &BStr) -> bool {
+        match &self.inner {
+            Inner::Cone { recursive, parents } => {
+                let rela_path = normalize_case(rela_path, self.case);
+                let Some(pos) = rela_path.rfind_byte(b'/') else {
+                    return true;
+                };
+                let dir = rela_path[..pos].as_bstr();
+                parents.contains(dir) || recursive.contains(dir) || has_ancestor_in(dir, recursive)
+            }
+            Inner::NonCone { patterns, .. } => {
+                let mut path = rela_path;
+                let mut is_dir = false;
+                loop {
+                    let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+                    if let Some(m) = gix_ignore::search::pattern_matching_relative_path(
+                        patterns,
+                        path,
+                        basename_pos,
+                        Some(is_dir),
+                        self.case,
+                    ) {
+                        return !m.pattern.is_negative();
+                    }
+                    match basename_pos {
+                        Some(pos) => {
+                            path = path[..pos - 1].as_bstr();
+                            is_dir = true;
+                        }
+                        None => return false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return how the entries of the directory at `rela_path` are affected by these patterns.
+    pub fn directory(&self, rela_path: &BStr) -> Directory {
+        match &self.inner {
+            Inner::Cone { recursive, parents } => {
+                let dir = normalize_case(rela_path, self.case);
+                if recursive.contains(dir.as_ref()) || has_ancestor_in(dir.as_ref(), recursive) {
+                    Directory::Included
+                } else if parents.contains(dir.as_ref()) {
+                    Directory::Partial
+                } else {
+                    Directory::Excluded
+                }
+            }
+            Inner::NonCone { .. } => Directory::Partial,
+        }
+    }
+
+    /// Serialize these patterns into `out` in the format of the `.git/info/sparse-checkout` file.
+    pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        match &self.inner {
+            Inner::Cone { recursive, parents } => {
+                out.write_all(b"/*\n!/*/\n")?;
+                for parent in parents {
+                    let parent = escape(parent.as_bstr());
+                    writeln!(out, "/{parent}/\n!/{parent}/*/")?;
+                }
+                for dir in recursive {
+                    writeln!(out, "/{}/", escape(dir.as_bstr()))?;
+                }
+            }
+            Inner::NonCone { lines, .. } => {
+                for line in lines {
+                    out.write_all(line)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn non_cone(lines: Vec<BString>) -> Inner {
+    let mut buf = BString::default();
+    for line in &lines {
+        buf.push_str(line);
+        buf.push(b'\n');
+    }
+    Inner::NonCone {
+        patterns: gix_glob::search::pattern::List::from_bytes(&buf, "info/sparse-checkout".into(), None),
+        lines,
+    }
+}
+
+/// Parse `bytes` as cone-mode patterns and return `(recursive, parents)`, or `None` if they aren't compatible with cone mode.
+fn parse_cone(bytes: &[u8], case: Case) -> Option<(BTreeSet<BString>, BTreeSet<BString>)> {
+    let mut recursive = BTreeSet::new();
+    let mut parents = BTreeSet::new();
+    let mut has_root = false;
+    for line in bytes.lines() {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+        match line {
+            b"/*" | b"!/*/" => {
+                has_root = true;
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(dir) = line.strip_prefix(b"!/").and_then(|dir| dir.strip_suffix(b"/*/")) {
+            let dir = normalize_case(unescape(dir)?.as_bstr(), case).into_owned();
+            if !recursive.remove(&dir) {
+                return None;
+            }
+            parents.insert(dir);
+        } else if let Some(dir) = line.strip_prefix(b"/").and_then(|dir| dir.strip_suffix(b"/")) {
+            let dir = unescape(dir)?;
+            if dir.is_empty() {
+                return None;
+            }
+            recursive.insert(normalize_case(dir.as_bstr(), case).into_owned());
+        } else {
+            return None;
+        }
+    }
+    has_root.then_some((recursive, parents))
+}
+
+/// Remove escapes from `dir`, or return `None` if it contains unescaped wildcards, which aren't supported in cone mode.
+fn unescape(dir: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    let mut bytes = dir.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => out.push(*bytes.next()?),
+            b'*' | b'?' | b'[' => return None,
+            _ => out.push(b),
+        }
+    }
+    Some(out)
+}
+
+fn escape(dir: &BStr) -> BString {
+    let mut out = BString::default();
+    for &b in dir.iter() {
+        if matches!(b, b'\\' | b'*' | b'?' | b'[') {
+            out.push(b'\\');
+        }
+        out.push(b);
+    }
+    out
+}
+
+fn normalize_case(path: &BStr, case: Case) -> Cow<'_, BStr> {
+    match case {
+        Case::Sensitive => Cow::Borrowed(path),
+        Case::Fold => Cow::Owned(path.to_ascii_lowercase().into()),
+    }
+}
+
+fn has_ancestor_in(dir: &BStr, set: &BTreeSet<BString>) -> bool {
+    let mut dir = dir;
+    while let Some(pos) = dir.rfind_byte(b'/') {
+        dir = dir[..pos].as_bstr();
+        if set.contains(dir) {
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,28 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function make_repo() {
+  touch a b
+  mkdir c1
+  (cd c1 && touch a b && mkdir c2 && cd c2 && touch a b && mkdir c6 && touch c6/a)
+  (cd c1 && mkdir c3 && cd c3 && touch a b)
+  mkdir d
+  (cd d && touch a b && mkdir c4 && cd c4 && touch a b c5)
+  mkdir e
+  (cd e && touch a.txt b.rs && mkdir c7 && touch c7/a.txt c7/b.rs)
+
+  git add .
+  git commit -q -m "init"
+}
+
+git init -q cone
+(cd cone
+  make_repo
+  git sparse-checkout set --cone c1/c2 e/c7 d/c4
+)
+
+git init -q non-cone
+(cd non-cone
+  make_repo
+  git sparse-checkout set --no-cone '/*' '!/*/' '*.txt' 'c2/' '!c6/' '/d/c4/c5'
+)
@@ -1,5 +1,6 @@
 use gix_hash::ObjectId;
 
+mod sparse;
 mod stack;
 
 pub use gix_testtools::Result;
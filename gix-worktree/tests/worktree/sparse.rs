@@ -0,0 +1,120 @@
+use bstr::{BStr, ByteSlice};
+use gix_glob::pattern::Case;
+use gix_worktree::sparse::{Directory, Patterns};
+
+fn repo(name: &str) -> crate::Result<(Patterns, gix_index::File, Vec<u8>)> {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_sparse_checkout.sh")?;
+    let git_dir = root.join(name).join(".git");
+    let sparse_checkout = std::fs::read(git_dir.join("info").join("sparse-checkout"))?;
+    let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default())?;
+    Ok((
+        Patterns::from_bytes(&sparse_checkout, name == "cone", Case::Sensitive),
+        index,
+        sparse_checkout,
+    ))
+}
+
+fn assert_skip_worktree_matches_git(patterns: &Patterns, index: &gix_index::File) {
+    for entry in index.entries() {
+        let path = entry.path(index);
+        assert_eq!(
+            patterns.is_included(path),
+            !entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE),
+            "{path}: inclusion must match what git decided"
+        );
+    }
+}
+
+#[test]
+fn cone_mode_matches_git() -> crate::Result {
+    let (patterns, index, _) = repo("cone")?;
+    assert!(patterns.is_cone());
+    assert_eq!(patterns.list(), ["c1/c2", "d/c4", "e/c7"]);
+    assert_skip_worktree_matches_git(&patterns, &index);
+
+    for (dir, expected) in [
+        ("c1", Directory::Partial),
+        ("c1/c2", Directory::Included),
+        ("c1/c2/c6", Directory::Included),
+        ("c1/c3", Directory::Excluded),
+        ("d", Directory::Partial),
+        ("d/c4", Directory::Included),
+        ("e/c7", Directory::Included),
+        ("f", Directory::Excluded),
+    ] {
+        assert_eq!(patterns.directory(dir.into()), expected, "{dir}");
+    }
+    Ok(())
+}
+
+#[test]
+fn non_cone_mode_matches_git() -> crate::Result {
+    let (patterns, index, _) = repo("non-cone")?;
+    assert!(!patterns.is_cone());
+    assert_eq!(
+        patterns.list(),
+        ["/*", "!/*/", "*.txt", "c2/", "!c6/", "/d/c4/c5"],
+        "lines are kept as they are"
+    );
+    assert_skip_worktree_matches_git(&patterns, &index);
+    assert_eq!(
+        patterns.directory("d".into()),
+        Directory::Partial,
+        "without cone mode, nothing is known about directories"
+    );
+    Ok(())
+}
+
+#[test]
+fn non_cone_patterns_fall_back_to_non_cone_mode() -> crate::Result {
+    let (_, index, sparse_checkout) = repo("non-cone")?;
+    let patterns = Patterns::from_bytes(&sparse_checkout, true, Case::Sensitive);
+    assert!(!patterns.is_cone(), "these patterns can't be represented in cone mode");
+    assert_skip_worktree_matches_git(&patterns, &index);
+    Ok(())
+}
+
+#[test]
+fn cone_directories_serialize_like_git() -> crate::Result {
+    let (_, index, expected) = repo("cone")?;
+    let patterns = Patterns::from_cone_directories(
+        ["/e/c7/", "c1/c2", "d/c4", "c1/c2/c6"].into_iter().map(<&BStr>::from),
+        Case::Sensitive,
+    );
+    assert_eq!(patterns.list(), ["c1/c2", "d/c4", "e/c7"], "subdirectories are merged");
+    let mut buf = Vec::new();
+    patterns.write_to(&mut buf)?;
+    assert_eq!(buf.as_bstr(), expected.as_bstr());
+    assert_skip_worktree_matches_git(&patterns, &index);
+
+    let reparsed = Patterns::from_bytes(&buf, true, Case::Sensitive);
+    assert_eq!(reparsed.list(), patterns.list(), "it round-trips");
+    Ok(())
+}
+
+#[test]
+fn add_to_cone() {
+    let mut patterns = Patterns::from_cone_directories(Some("a/b/c".into()), Case::Sensitive);
+    patterns.add(["a/b".into(), "x".into()]);
+    assert_eq!(
+        patterns.list(),
+        ["a/b", "x"],
+        "directories already included by parents are removed"
+    );
+    assert!(
+        patterns.is_included("a/file".into()),
+        "parent directories include their files"
+    );
+    assert!(patterns.is_included("a/b/c/d/file".into()));
+    assert!(patterns.is_included("root-file".into()));
+    assert!(!patterns.is_included("a/c/file".into()));
+    assert!(!patterns.is_included("y/file".into()));
+}
+
+#[test]
+fn cone_mode_with_case_folding() {
+    let patterns = Patterns::from_cone_directories(Some("Dir/Sub".into()), Case::Fold);
+    assert!(patterns.is_included("dir/sub/file".into()));
+    assert!(patterns.is_included("DIR/file".into()));
+    assert_eq!(patterns.directory("DIR/SUB".into()), Directory::Included);
+}
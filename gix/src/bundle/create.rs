@@ -0,0 +1,19 @@
+use crate::bstr::BString;
+
+/// The error returned by [Repository::create_bundle()](crate::Repository::create_bundle()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::Error),
+    #[error("The rev-spec '{spec}' can't be used to select what to bundle")]
+    UnsupportedSpec { spec: BString },
+    #[error("Refusing to create a bundle without references")]
+    Empty,
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    PackObjects(#[from] crate::pack_objects::Error),
+    #[error("Could not write the bundle header")]
+    Io(#[from] std::io::Error),
+}
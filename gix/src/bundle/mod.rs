@@ -0,0 +1,62 @@
+//! Read, verify, create and unbundle git bundles, files with a pack and the references it provides.
+//!
+//! Use [`File::at()`] to open a bundle, [`Repository::verify_bundle()`](crate::Repository::verify_bundle()) to assure
+//! a repository has all of its prerequisites and [`Repository::unbundle()`](crate::Repository::unbundle()) to add its
+//! objects to the object database. New bundles are written with [`Repository::create_bundle()`](crate::Repository::create_bundle()).
+//!
+//! With the `blocking-network-client` feature, the path to a bundle can also be used as URL of a remote, to clone or
+//! fetch from it like from any other repository.
+
+pub use gix_bundle::{decode, file, is_bundle, File, Header, Prerequisite, Ref, Version};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod create;
+#[cfg(feature = "blocking-network-client")]
+pub(crate) mod transport;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod verify {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [Repository::verify_bundle()](crate::Repository::verify_bundle()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The bundle uses {bundle} objects, but the repository uses {repository}")]
+        ObjectHashMismatch {
+            bundle: gix_hash::Kind,
+            repository: gix_hash::Kind,
+        },
+        #[error("The repository lacks {} prerequisite commit(s) of the bundle, like {}", missing.len(), missing[0])]
+        MissingPrerequisites { missing: Vec<ObjectId> },
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod unbundle {
+    /// The error returned by [Repository::unbundle()](crate::Repository::unbundle()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Verify(#[from] super::verify::Error),
+        #[error(transparent)]
+        PackThreads(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        PackIndexVersion(#[from] crate::config::key::GenericError),
+        #[error("Could not open the pack of the bundle at \"{}\"", path.display())]
+        OpenPack {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::bundle::write::Error),
+        #[error("Could not remove the keep-file of the written pack")]
+        RemoveKeepFile(#[source] std::io::Error),
+    }
+}
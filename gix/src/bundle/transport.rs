@@ -0,0 +1,208 @@
+//! An in-process server which provides the references and the pack of a bundle via protocol V2, to allow fetching from
+//! bundles with the same machinery that is used for all other remotes.
+use std::{
+    io::{self, BufReader, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use gix_protocol::transport::{client::git, Protocol};
+
+use crate::bstr::{BString, ByteSlice};
+
+/// The largest amount of pack data to send per packet line, leaving room for its length and the sideband.
+const MAX_DATA_LEN: usize = 65515;
+
+/// Return a connection to an in-process server providing the references and the pack of `bundle`.
+pub(crate) fn connect(bundle: gix_bundle::File, trace: bool) -> git::Connection<Responses, Requests> {
+    let requests = Requests::default();
+    let path = gix_path::into_bstr(bundle.path.as_path()).into_owned();
+    let responses = Responses {
+        requests: requests.0.clone(),
+        buf: advertisement(&bundle.header),
+        pos: 0,
+        pack: None,
+        bundle,
+    };
+    git::Connection::new(
+        responses,
+        requests,
+        Protocol::V2,
+        path,
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+        trace,
+    )
+}
+
+/// Collects all requests of the client for [`Responses`] to answer them.
+#[derive(Default)]
+pub(crate) struct Requests(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Requests {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().expect("no panics while locked").extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Produces the responses to all complete commands written to [`Requests`], or signals the end of the stream if there is none.
+pub(crate) struct Responses {
+    requests: Arc<Mutex<Vec<u8>>>,
+    bundle: gix_bundle::File,
+    /// The response to send, up to `pos`.
+    buf: Vec<u8>,
+    pos: usize,
+    /// The pack to send in sideband packets once `buf` is depleted.
+    pack: Option<BufReader<std::fs::File>>,
+}
+
+impl Read for Responses {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let len = out.len().min(self.buf.len() - self.pos);
+                out[..len].copy_from_slice(&self.buf[self.pos..][..len]);
+                self.pos += len;
+                return Ok(len);
+            }
+            self.buf.clear();
+            self.pos = 0;
+
+            if let Some(pack) = self.pack.as_mut() {
+                self.buf.resize(5 + MAX_DATA_LEN, 0);
+                let len = pack.read(&mut self.buf[5..])?;
+                if len == 0 {
+                    self.pack = None;
+                    self.buf.clear();
+                    self.buf.extend_from_slice(b"0000");
+                } else {
+                    self.buf[..4].copy_from_slice(format!("{:04x}", len + 5).as_bytes());
+                    self.buf[4] = 1;
+                    self.buf.truncate(len + 5);
+                }
+                continue;
+            }
+
+            match self.next_command()? {
+                Some(lines) => self.respond(&lines)?,
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+impl Responses {
+    /// Remove the next complete command from the requests and return its lines without delimiters and trailing newlines,
+    /// or `None` if there is none yet.
+    fn next_command(&mut self) -> io::Result<Option<Vec<BString>>> {
+        let mut requests = self.requests.lock().expect("no panics while locked");
+        let mut lines = Vec::new();
+        let mut pos = 0;
+        loop {
+            let Some(hex_len) = requests.get(pos..pos + 4) else {
+                return Ok(None);
+            };
+            let len = std::str::from_utf8(hex_len)
+                .ok()
+                .and_then(|hex_len| usize::from_str_radix(hex_len, 16).ok())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid packet line length"))?;
+            match len {
+                0 => {
+                    requests.drain(..pos + 4);
+                    return Ok(Some(lines));
+                }
+                1 | 2 => pos += 4,
+                3 => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid packet line length")),
+                _ => {
+                    let Some(line) = requests.get(pos + 4..pos + len) else {
+                        return Ok(None);
+                    };
+                    lines.push(line.strip_suffix(b"\n").unwrap_or(line).into());
+                    pos += len;
+                }
+            }
+        }
+    }
+
+    /// Fill our buffer with the response to the command in `lines`.
+    fn respond(&mut self, lines: &[BString]) -> io::Result<()> {
+        let args = || lines.iter().map(|line| line.as_slice());
+        let Some(command) = args().find_map(|line| line.strip_prefix(b"command=")) else {
+            return Ok(());
+        };
+        match command {
+            b"ls-refs" => {
+                let prefixes: Vec<_> = args().filter_map(|line| line.strip_prefix(b"ref-prefix ")).collect();
+                let header = &self.bundle.header;
+                for r in &header.refs {
+                    if !prefixes.is_empty() && !prefixes.iter().any(|prefix| r.name.starts_with(prefix)) {
+                        continue;
+                    }
+                    let mut line = format!("{} {}", r.id, r.name);
+                    if r.name == "HEAD" {
+                        if let Some(target) = head_target(header) {
+                            line.push_str(&format!(" symref-target:{target}"));
+                        }
+                    }
+                    line.push('\n');
+                    encode(&mut self.buf, line.as_bytes());
+                }
+                self.buf.extend_from_slice(b"0000");
+            }
+            b"fetch" => {
+                if !args().any(|line| line == b"done") {
+                    encode(&mut self.buf, b"acknowledgments\n");
+                    encode(&mut self.buf, b"ready\n");
+                    self.buf.extend_from_slice(b"0001");
+                }
+                encode(&mut self.buf, b"packfile\n");
+                self.pack = Some(self.bundle.pack()?);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("bundles don't support the '{}' command", command.as_bstr()),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the capability advertisement for a bundle with `header`.
+fn advertisement(header: &gix_bundle::Header) -> Vec<u8> {
+    let mut out = Vec::new();
+    for line in [
+        "version 2\n".to_owned(),
+        "ls-refs\n".into(),
+        "fetch\n".into(),
+        format!(
+            "object-format={}\n",
+            header.object_hash.to_string().to_ascii_lowercase()
+        ),
+    ] {
+        encode(&mut out, line.as_bytes());
+    }
+    out.extend_from_slice(b"0000");
+    out
+}
+
+/// Guess the branch `HEAD` points to as the first branch pointing to the same commit, as bundles don't store symbolic refs.
+fn head_target(header: &gix_bundle::Header) -> Option<&BString> {
+    let head = header.refs.iter().find(|r| r.name == "HEAD")?;
+    header
+        .refs
+        .iter()
+        .find(|r| r.id == head.id && r.name.starts_with(b"refs/heads/"))
+        .map(|r| &r.name)
+}
+
+/// Append `data` as packet line to `out`.
+fn encode(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(format!("{:04x}", data.len() + 4).as_bytes());
+    out.extend_from_slice(data);
+}
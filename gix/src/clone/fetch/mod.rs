@@ -35,6 +35,8 @@ pub enum Error {
     },
     #[error("Failed to update HEAD with values from remote")]
     HeadUpdate(#[from] crate::reference::edit::Error),
+    #[error("Failed to reopen the repository after adjusting its object format to the one of the remote")]
+    Reopen(#[from] crate::open::Error),
 }
 
 /// Modification
@@ -72,7 +74,11 @@ impl PrepareFetch {
         progress: &mut dyn crate::DynNestedProgress,
         should_interrupt: &std::sync::atomic::AtomicBool,
     ) -> Result<(crate::Repository, crate::remote::fetch::Outcome), Error> {
-        use crate::{bstr::ByteVec, remote, remote::fetch::RefLogMessage};
+        use crate::{
+            bstr::{ByteSlice, ByteVec},
+            remote,
+            remote::fetch::{RefLogMessage, WriteFetchHead},
+        };
 
         let repo = self
             .repo
@@ -110,7 +116,10 @@ impl PrepareFetch {
             clone_fetch_tags = remote::fetch::Tags::All.into();
         }
 
-        let config = util::write_remote_to_local_config_file(&mut remote, remote_name.clone())?;
+        let mut config = Some(util::write_remote_to_local_config_file(
+            &mut remote,
+            remote_name.clone(),
+        )?);
 
         // Now we are free to apply remote configuration we don't want to be written to disk.
         if let Some(fetch_tags) = clone_fetch_tags {
@@ -125,23 +134,33 @@ impl PrepareFetch {
         )
         .expect("valid")
         .to_owned();
-        let pending_pack: remote::fetch::Prepare<'_, '_, _> = {
-            let mut connection = remote.connect(remote::Direction::Fetch).await?;
-            if let Some(f) = self.configure_connection.as_mut() {
-                f(&mut connection).map_err(Error::RemoteConnection)?;
+        let fetch_options = {
+            let mut opts = self.fetch_options.clone();
+            if !opts.extra_refspecs.contains(&head_refspec) {
+                opts.extra_refspecs.push(head_refspec)
             }
-            connection
-                .prepare_fetch(&mut *progress, {
-                    let mut opts = self.fetch_options.clone();
-                    if !opts.extra_refspecs.contains(&head_refspec) {
-                        opts.extra_refspecs.push(head_refspec)
-                    }
-                    opts
-                })
-                .await?
+            opts
         };
-        if pending_pack.ref_map().object_hash != repo.object_hash() {
-            unimplemented!("configure repository to expect a different object hash as advertised by the server")
+        let mut pending_pack = prepare_fetch(
+            &remote,
+            self.configure_connection.as_mut(),
+            fetch_options.clone(),
+            progress,
+        )
+        .await?;
+        let remote_object_hash = pending_pack.ref_map().object_hash;
+        if remote_object_hash != repo.object_hash() {
+            // The repository was created before we knew which hash the remote uses, so adjust it and start over.
+            drop(pending_pack);
+            util::set_object_hash(repo, remote_object_hash)?;
+            util::append_config_to_repo_config(repo, config.take().expect("not yet appended"));
+            remote = repo
+                .find_remote(remote_name.as_slice().as_bstr())
+                .map_err(|err| Error::RemoteConfiguration(err.into()))?;
+            if let Some(fetch_tags) = clone_fetch_tags {
+                remote = remote.with_fetch_tags(fetch_tags);
+            }
+            pending_pack = prepare_fetch(&remote, self.configure_connection.as_mut(), fetch_options, progress).await?;
         }
         let reflog_message = {
             let mut b = self.url.to_bstring();
@@ -150,6 +169,7 @@ impl PrepareFetch {
         };
         let outcome = pending_pack
             .with_write_packed_refs_only(true)
+            .with_write_fetch_head(WriteFetchHead::Never)
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
@@ -157,7 +177,9 @@ impl PrepareFetch {
             .receive_inner(progress, should_interrupt)
             .await?;
 
-        util::append_config_to_repo_config(repo, config);
+        if let Some(config) = config {
+            util::append_config_to_repo_config(repo, config);
+        }
         util::update_head(
             repo,
             &outcome.ref_map.remote_refs,
@@ -184,4 +206,22 @@ impl PrepareFetch {
     }
 }
 
+/// Connect to `remote` and obtain its refs to prepare fetching a pack.
+#[gix_protocol::maybe_async::maybe_async]
+async fn prepare_fetch<'remote, 'repo>(
+    remote: &'remote crate::Remote<'repo>,
+    configure_connection: Option<&mut super::ConfigureConnectionFn>,
+    fetch_options: crate::remote::ref_map::Options,
+    progress: &mut dyn crate::DynNestedProgress,
+) -> Result<
+    crate::remote::fetch::Prepare<'remote, 'repo, Box<dyn gix_protocol::transport::client::Transport + Send>>,
+    Error,
+> {
+    let mut connection = remote.connect(crate::remote::Direction::Fetch).await?;
+    if let Some(f) = configure_connection {
+        f(&mut connection).map_err(Error::RemoteConnection)?;
+    }
+    Ok(connection.prepare_fetch(progress, fetch_options).await?)
+}
+
 mod util;
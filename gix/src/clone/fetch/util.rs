@@ -53,6 +53,31 @@ fn write_to_local_config(config: &gix_config::File<'static>, mode: WriteMode) ->
     config.write_to_filter(&mut local_config, &mut |s| s.meta().source == gix_config::Source::Local)
 }
 
+/// Persist `object_hash` as object format of the newly created `repo` and reopen it, as all of its stores depend on it.
+/// In-memory configuration is retained.
+pub fn set_object_hash(repo: &mut Repository, object_hash: gix_hash::Kind) -> Result<(), Error> {
+    let format = object_hash.to_string().to_ascii_lowercase();
+    let set_format = |config: &mut gix_config::File<'static>| {
+        config
+            .set_raw_value("core", None, "repositoryFormatVersion", "1")
+            .expect("valid at compile time");
+        config
+            .set_raw_value("extensions", None, "objectFormat", format.as_str())
+            .expect("valid at compile time");
+    };
+
+    let path = local_config_meta(repo).path.expect("local config with path set");
+    let mut local_config = gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local)?;
+    set_format(&mut local_config);
+    std::fs::write(path, local_config.to_bstring())?;
+
+    let mut config = (*repo.config.resolved).clone();
+    set_format(&mut config);
+    *repo = crate::ThreadSafeRepository::open_opts(repo.git_dir(), repo.options.clone())?.to_thread_local();
+    repo.reread_values_and_clear_caches_replacing_config(config.into())?;
+    Ok(())
+}
+
 pub fn append_config_to_repo_config(repo: &mut Repository, config: gix_config::File<'static>) {
     let repo_config = gix_features::threading::OwnShared::make_mut(&mut repo.config.resolved);
     repo_config.append(config);
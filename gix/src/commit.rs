@@ -20,6 +20,12 @@ pub enum Error {
     WriteObject(#[from] crate::object::write::Error),
     #[error(transparent)]
     ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[cfg(feature = "signing")]
+    #[error(transparent)]
+    SigningOptions(#[from] crate::signing::options::Error),
+    #[cfg(feature = "signing")]
+    #[error(transparent)]
+    Sign(#[from] crate::signing::sign::Error),
 }
 
 ///
@@ -248,3 +254,24 @@ pub mod describe {
         }
     }
 }
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "notes")]
+pub mod note {
+    /// The error returned by [Commit::note()](crate::Commit::note()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Init(#[from] crate::note::init::Error),
+        #[error(transparent)]
+        Find(#[from] crate::note::find::Error),
+        #[error(transparent)]
+        OpenReferences(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        IterReferences(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        ReadReference(Box<dyn std::error::Error + Send + Sync + 'static>),
+    }
+}
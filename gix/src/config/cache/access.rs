@@ -20,7 +20,7 @@ use crate::{
 impl Cache {
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_algorithm(&self) -> Result<gix_diff::blob::Algorithm, config::diff::algorithm::Error> {
-        use crate::config::{cache::util::ApplyLeniencyDefault, diff::algorithm::Error};
+        use crate::config::cache::util::ApplyLeniencyDefault;
         self.diff_algorithm
             .get_or_try_init(|| {
                 let name = self
@@ -29,10 +29,6 @@ impl Cache {
                     .unwrap_or_else(|| Cow::Borrowed("myers".into()));
                 config::tree::Diff::ALGORITHM
                     .try_into_algorithm(name)
-                    .or_else(|err| match err {
-                        Error::Unimplemented { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Histogram),
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
             })
             .copied()
@@ -83,12 +79,6 @@ impl Cache {
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
-                    .or_else(|err| match err {
-                        config::diff::algorithm::Error::Unimplemented { .. } if self.lenient_config => {
-                            Ok(gix_diff::blob::Algorithm::Histogram)
-                        }
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
                     .map_err(|err| config::diff::drivers::Error {
                         name: driver.name.clone(),
@@ -101,6 +91,44 @@ impl Cache {
         Ok(out)
     }
 
+    #[cfg(feature = "merge")]
+    pub(crate) fn merge_drivers(&self) -> Vec<gix_merge::blob::Driver> {
+        let mut out = Vec::<gix_merge::blob::Driver>::new();
+        for section in self
+            .resolved
+            .sections_by_name("merge")
+            .into_iter()
+            .flatten()
+            .filter(|s| (self.filter_config_section)(s.meta()))
+        {
+            let Some(name) = section.header().subsection_name().filter(|n| !n.is_empty()) else {
+                continue;
+            };
+
+            let driver = match out.iter_mut().find(|d| d.name == name) {
+                Some(existing) => existing,
+                None => {
+                    out.push(gix_merge::blob::Driver {
+                        name: name.into(),
+                        ..Default::default()
+                    });
+                    out.last_mut().expect("just pushed")
+                }
+            };
+
+            if let Some(display_name) = section.value(config::tree::Merge::DRIVER_NAME.name) {
+                driver.display_name = display_name.into_owned();
+            }
+            if let Some(command) = section.value(config::tree::Merge::DRIVER_COMMAND.name) {
+                driver.command = command.into_owned();
+            }
+            if let Some(recursive) = section.value(config::tree::Merge::DRIVER_RECURSIVE.name) {
+                driver.recursive = Some(recursive.into_owned());
+            }
+        }
+        out
+    }
+
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_pipeline_options(
         &self,
@@ -130,7 +158,11 @@ impl Cache {
     }
 
     /// Returns a user agent for use with servers.
-    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    #[cfg(any(
+        feature = "async-network-client",
+        feature = "blocking-network-client",
+        feature = "blocking-network-server"
+    ))]
     pub(crate) fn user_agent_tuple(&self) -> (&'static str, Option<Cow<'static, str>>) {
         use config::tree::Gitoxide;
         let agent = self
@@ -145,7 +177,11 @@ impl Cache {
     }
 
     /// Return `true` if packet-tracing is enabled. Lenient and defaults to `false`.
-    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    #[cfg(any(
+        feature = "async-network-client",
+        feature = "blocking-network-client",
+        feature = "blocking-network-server"
+    ))]
     pub(crate) fn trace_packet(&self) -> bool {
         use config::tree::Gitoxide;
 
@@ -254,6 +290,80 @@ impl Cache {
         })
     }
 
+    /// Return the `core.fsmonitor` hook if it's set in a trusted file, along with the version of the protocol to use with it.
+    #[cfg(feature = "status")]
+    pub(crate) fn fs_monitor_hook(
+        &self,
+    ) -> Result<Option<(crate::bstr::BString, gix_status::fsmonitor::hook::Version)>, config::key::GenericErrorWithValue>
+    {
+        use crate::config::cache::util::ApplyLeniencyDefault;
+        let Some(command) = self.resolved.string_filter(
+            "core",
+            None,
+            Core::FSMONITOR.name,
+            &mut self.filter_config_section.clone(),
+        ) else {
+            return Ok(None);
+        };
+        if let Ok(enabled) = gix_config::Boolean::try_from(command.as_ref()) {
+            if enabled.0 {
+                gix_trace::warn!(
+                    "ignoring core.fsmonitor=true as the built-in filesystem monitor daemon isn't supported"
+                );
+            }
+            return Ok(None);
+        }
+        let version = self
+            .resolved
+            .string("core", None, Core::FSMONITOR_HOOK_VERSION.name)
+            .map(|value| {
+                Core::FSMONITOR_HOOK_VERSION
+                    .try_into_hook_version(value)
+                    .with_lenient_default(self.lenient_config)
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Some((command.into_owned(), version)))
+    }
+
+    #[cfg(feature = "index")]
+    pub(crate) fn sparse_options(&self) -> Result<gix_index::sparse::Options, boolean::Error> {
+        Ok(gix_index::sparse::Options {
+            sparse_checkout: boolean(self, "core.sparseCheckout", &Core::SPARSE_CHECKOUT, false)?,
+            directory_patterns_only: boolean(self, "core.sparseCheckoutCone", &Core::SPARSE_CHECKOUT_CONE, true)?,
+            write_sparse_index: boolean(self, "index.sparse", &config::tree::Index::SPARSE, false)?,
+        })
+    }
+
+    /// Read the patterns in `info/sparse-checkout` of `git_dir` if `core.sparseCheckout` is enabled, or return `None` if it
+    /// is disabled or if there is no such file.
+    #[cfg(feature = "worktree-mutation")]
+    pub(crate) fn sparse_checkout_patterns(
+        &self,
+        git_dir: &std::path::Path,
+    ) -> Result<Option<gix_worktree::sparse::Patterns>, config::sparse_checkout_patterns::Error> {
+        let options = self.sparse_options()?;
+        if !options.sparse_checkout {
+            return Ok(None);
+        }
+        let path = git_dir.join("info").join("sparse-checkout");
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(config::sparse_checkout_patterns::Error::Io { path, source }),
+        };
+        let case = if self.fs_capabilities()?.ignore_case {
+            gix_glob::pattern::Case::Fold
+        } else {
+            gix_glob::pattern::Case::Sensitive
+        };
+        Ok(Some(gix_worktree::sparse::Patterns::from_bytes(
+            &bytes,
+            options.directory_patterns_only,
+            case,
+        )))
+    }
+
     #[cfg(feature = "index")]
     pub(crate) fn stat_options(&self) -> Result<gix_index::entry::stat::Options, config::stat_options::Error> {
         use crate::config::tree::gitoxide;
@@ -319,8 +429,7 @@ impl Cache {
         )?;
         let capabilities = self.fs_capabilities()?;
         let filters = {
-            let mut filters =
-                gix_filter::Pipeline::new(repo.command_context()?, crate::filter::Pipeline::options(repo)?);
+            let mut filters = crate::filter::plumbing_pipeline(repo)?;
             if let Ok(mut head) = repo.head() {
                 let ctx = filters.driver_context_mut();
                 ctx.ref_name = head.referent_name().map(|name| name.as_bstr().to_owned());
@@ -350,6 +459,7 @@ impl Cache {
             destination_is_initially_empty: false,
             overwrite_existing: false,
             keep_going: false,
+            sparse_checkout: self.sparse_checkout_patterns(git_dir)?,
             stat_options: self.stat_options().map_err(|err| match err {
                 config::stat_options::Error::ConfigCheckStat(err) => {
                     config::checkout_options::Error::ConfigCheckStat(err)
@@ -367,10 +477,7 @@ impl Cache {
         source: gix_worktree::stack::state::ignore::Source,
         buf: &mut Vec<u8>,
     ) -> Result<gix_worktree::stack::state::Ignore, config::exclude_stack::Error> {
-        let excludes_file = match self.excludes_file().transpose()? {
-            Some(user_path) => Some(user_path),
-            None => self.xdg_config_path("ignore")?,
-        };
+        let excludes_file = self.excludes_file_or_default()?;
         Ok(gix_worktree::stack::state::Ignore::new(
             overrides.unwrap_or_default(),
             gix_ignore::Search::from_git_dir(git_dir, excludes_file, buf)?,
@@ -378,6 +485,15 @@ impl Cache {
             source,
         ))
     }
+
+    /// Return the path to `core.excludesFile`, or to the user's ignore file in the XDG configuration directory if unset.
+    #[cfg(feature = "excludes")]
+    pub(crate) fn excludes_file_or_default(&self) -> Result<Option<PathBuf>, config::exclude_stack::Error> {
+        Ok(match self.excludes_file().transpose()? {
+            Some(user_path) => Some(user_path),
+            None => self.xdg_config_path("ignore")?,
+        })
+    }
     // TODO: at least one test, maybe related to core.attributesFile configuration.
     #[cfg(feature = "attributes")]
     pub(crate) fn assemble_attribute_globals(
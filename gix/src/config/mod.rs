@@ -122,8 +122,6 @@ pub mod diff {
         pub enum Error {
             #[error("Unknown diff algorithm named '{name}'")]
             Unknown { name: BString },
-            #[error("The '{name}' algorithm is not yet implemented")]
-            Unimplemented { name: BString },
         }
     }
 
@@ -193,6 +191,25 @@ pub mod checkout_options {
         FilterPipelineOptions(#[from] crate::filter::pipeline::options::Error),
         #[error(transparent)]
         CommandContext(#[from] crate::config::command_context::Error),
+        #[error(transparent)]
+        SparseCheckoutPatterns(#[from] super::sparse_checkout_patterns::Error),
+    }
+}
+
+///
+#[cfg(feature = "attributes")]
+pub mod sparse_checkout_patterns {
+    /// The error produced when reading the patterns of a sparse checkout.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] super::boolean::Error),
+        #[error("Could not read sparse-checkout patterns from '{}'", path.display())]
+        Io {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
     }
 }
 
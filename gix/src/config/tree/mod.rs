@@ -18,12 +18,18 @@ pub(crate) mod root {
     impl Tree {
         /// The `author` section.
         pub const AUTHOR: sections::Author = sections::Author;
+        /// The `blame` section.
+        #[cfg(feature = "blame")]
+        pub const BLAME: sections::Blame = sections::Blame;
         /// The `branch` section.
         pub const BRANCH: sections::Branch = sections::Branch;
         /// The `checkout` section.
         pub const CHECKOUT: sections::Checkout = sections::Checkout;
         /// The `clone` section.
         pub const CLONE: sections::Clone = sections::Clone;
+        /// The `commit` section.
+        #[cfg(feature = "signing")]
+        pub const COMMIT: sections::Commit = sections::Commit;
         /// The `committer` section.
         pub const COMMITTER: sections::Committer = sections::Committer;
         /// The `core` section.
@@ -39,6 +45,9 @@ pub(crate) mod root {
         pub const FETCH: sections::Fetch = sections::Fetch;
         /// The `gitoxide` section.
         pub const GITOXIDE: sections::Gitoxide = sections::Gitoxide;
+        /// The `gpg` section.
+        #[cfg(feature = "signing")]
+        pub const GPG: sections::Gpg = sections::Gpg;
         /// The `http` section.
         pub const HTTP: sections::Http = sections::Http;
         /// The `index` section.
@@ -47,12 +56,21 @@ pub(crate) mod root {
         pub const INIT: sections::Init = sections::Init;
         /// The `mailmap` section.
         pub const MAILMAP: sections::Mailmap = sections::Mailmap;
+        /// The `merge` section.
+        #[cfg(feature = "merge")]
+        pub const MERGE: sections::Merge = sections::Merge;
+        /// The `notes` section.
+        #[cfg(feature = "notes")]
+        pub const NOTES: sections::Notes = sections::Notes;
         /// The `pack` section.
         pub const PACK: sections::Pack = sections::Pack;
         /// The `protocol` section.
         pub const PROTOCOL: sections::Protocol = sections::Protocol;
         /// The `push` section.
         pub const PUSH: sections::Push = sections::Push;
+        /// The `rebase` section.
+        #[cfg(feature = "rebase")]
+        pub const REBASE: sections::Rebase = sections::Rebase;
         /// The `remote` section.
         pub const REMOTE: sections::Remote = sections::Remote;
         /// The `safe` section.
@@ -62,6 +80,9 @@ pub(crate) mod root {
         /// The `status` section.
         #[cfg(feature = "status")]
         pub const STATUS: sections::Status = sections::Status;
+        /// The `tag` section.
+        #[cfg(feature = "signing")]
+        pub const TAG: sections::Tag = sections::Tag;
         /// The `user` section.
         pub const USER: sections::User = sections::User;
         /// The `url` section.
@@ -71,9 +92,13 @@ pub(crate) mod root {
         pub fn sections(&self) -> &[&dyn Section] {
             &[
                 &Self::AUTHOR,
+                #[cfg(feature = "blame")]
+                &Self::BLAME,
                 &Self::BRANCH,
                 &Self::CHECKOUT,
                 &Self::CLONE,
+                #[cfg(feature = "signing")]
+                &Self::COMMIT,
                 &Self::COMMITTER,
                 &Self::CORE,
                 &Self::CREDENTIAL,
@@ -82,18 +107,28 @@ pub(crate) mod root {
                 &Self::EXTENSIONS,
                 &Self::FETCH,
                 &Self::GITOXIDE,
+                #[cfg(feature = "signing")]
+                &Self::GPG,
                 &Self::HTTP,
                 &Self::INDEX,
                 &Self::INIT,
                 &Self::MAILMAP,
+                #[cfg(feature = "merge")]
+                &Self::MERGE,
+                #[cfg(feature = "notes")]
+                &Self::NOTES,
                 &Self::PACK,
                 &Self::PROTOCOL,
                 &Self::PUSH,
+                #[cfg(feature = "rebase")]
+                &Self::REBASE,
                 &Self::REMOTE,
                 &Self::SAFE,
                 &Self::SSH,
                 #[cfg(feature = "status")]
                 &Self::STATUS,
+                #[cfg(feature = "signing")]
+                &Self::TAG,
                 &Self::USER,
                 &Self::URL,
             ]
@@ -102,6 +137,10 @@ pub(crate) mod root {
 }
 
 mod sections;
+#[cfg(feature = "rebase")]
+pub use sections::Rebase;
+#[cfg(feature = "blame")]
+pub use sections::{blame, Blame};
 pub use sections::{
     branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, ssh, Author,
     Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init, Mailmap,
@@ -109,6 +148,12 @@ pub use sections::{
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{diff, Diff};
+#[cfg(feature = "signing")]
+pub use sections::{gpg, Commit, Gpg, Tag};
+#[cfg(feature = "merge")]
+pub use sections::{merge, Merge};
+#[cfg(feature = "notes")]
+pub use sections::{notes, Notes};
 #[cfg(feature = "status")]
 pub use sections::{status, Status};
 
@@ -26,6 +26,19 @@ impl Core {
     pub const EDITOR: keys::Program = keys::Program::new_program("editor", &config::Tree::CORE);
     /// The `core.fileMode` key.
     pub const FILE_MODE: keys::Boolean = keys::Boolean::new_boolean("fileMode", &config::Tree::CORE);
+    /// The `core.fsmonitor` key.
+    #[cfg(feature = "status")]
+    pub const FSMONITOR: keys::Program = keys::Program::new_program("fsmonitor", &config::Tree::CORE).with_deviation(
+        "only hooks are supported, setting it to `true` to use the built-in daemon disables the filesystem monitor",
+    );
+    /// The `core.fsmonitorHookVersion` key.
+    #[cfg(feature = "status")]
+    pub const FSMONITOR_HOOK_VERSION: FsmonitorHookVersion = FsmonitorHookVersion::new_with_validate(
+        "fsmonitorHookVersion",
+        &config::Tree::CORE,
+        validate::FsmonitorHookVersion,
+    )
+    .with_deviation("if unset, version 2 is used without falling back to version 1");
     /// The `core.ignoreCase` key.
     pub const IGNORE_CASE: keys::Boolean = keys::Boolean::new_boolean("ignoreCase", &config::Tree::CORE);
     /// The `core.filesRefLockTimeout` key.
@@ -51,10 +64,19 @@ impl Core {
     /// The `core.repositoryFormatVersion` key.
     pub const REPOSITORY_FORMAT_VERSION: keys::UnsignedInteger =
         keys::UnsignedInteger::new_unsigned_integer("repositoryFormatVersion", &config::Tree::CORE);
+    /// The `core.sparseCheckout` key.
+    pub const SPARSE_CHECKOUT: keys::Boolean = keys::Boolean::new_boolean("sparseCheckout", &config::Tree::CORE);
+    /// The `core.sparseCheckoutCone` key.
+    pub const SPARSE_CHECKOUT_CONE: keys::Boolean =
+        keys::Boolean::new_boolean("sparseCheckoutCone", &config::Tree::CORE);
     /// The `core.symlinks` key.
     pub const SYMLINKS: keys::Boolean = keys::Boolean::new_boolean("symlinks", &config::Tree::CORE);
     /// The `core.trustCTime` key.
     pub const TRUST_C_TIME: keys::Boolean = keys::Boolean::new_boolean("trustCTime", &config::Tree::CORE);
+    /// The `core.untrackedCache` key.
+    #[cfg(feature = "status")]
+    pub const UNTRACKED_CACHE: UntrackedCache =
+        UntrackedCache::new_with_validate("untrackedCache", &config::Tree::CORE, validate::UntrackedCache);
     /// The `core.worktree` key.
     pub const WORKTREE: keys::Any = keys::Any::new("worktree", &config::Tree::CORE)
         .with_environment_override("GIT_WORK_TREE")
@@ -77,6 +99,8 @@ impl Core {
         .with_environment_override("GIT_NO_REPLACE_OBJECTS");
     /// The `core.commitGraph` key.
     pub const COMMIT_GRAPH: keys::Boolean = keys::Boolean::new_boolean("commitGraph", &config::Tree::CORE);
+    /// The `core.notesRef` key.
+    pub const NOTES_REF: keys::Any = keys::Any::new("notesRef", &config::Tree::CORE);
     /// The `core.safecrlf` key.
     #[cfg(feature = "attributes")]
     pub const SAFE_CRLF: SafeCrlf = SafeCrlf::new_with_validate("safecrlf", &config::Tree::CORE, validate::SafeCrlf);
@@ -110,6 +134,10 @@ impl Section for Core {
             &Self::DISAMBIGUATE,
             &Self::EDITOR,
             &Self::FILE_MODE,
+            #[cfg(feature = "status")]
+            &Self::FSMONITOR,
+            #[cfg(feature = "status")]
+            &Self::FSMONITOR_HOOK_VERSION,
             &Self::IGNORE_CASE,
             &Self::FILES_REF_LOCK_TIMEOUT,
             &Self::PACKED_REFS_TIMEOUT,
@@ -117,8 +145,12 @@ impl Section for Core {
             &Self::LOG_ALL_REF_UPDATES,
             &Self::PRECOMPOSE_UNICODE,
             &Self::REPOSITORY_FORMAT_VERSION,
+            &Self::SPARSE_CHECKOUT,
+            &Self::SPARSE_CHECKOUT_CONE,
             &Self::SYMLINKS,
             &Self::TRUST_C_TIME,
+            #[cfg(feature = "status")]
+            &Self::UNTRACKED_CACHE,
             &Self::WORKTREE,
             &Self::PROTECT_HFS,
             &Self::PROTECT_NTFS,
@@ -128,6 +160,7 @@ impl Section for Core {
             &Self::SSH_COMMAND,
             &Self::USE_REPLACE_REFS,
             &Self::COMMIT_GRAPH,
+            &Self::NOTES_REF,
             #[cfg(feature = "attributes")]
             &Self::SAFE_CRLF,
             #[cfg(feature = "attributes")]
@@ -152,6 +185,14 @@ pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
+/// The `core.untrackedCache` key.
+#[cfg(feature = "status")]
+pub type UntrackedCache = keys::Any<validate::UntrackedCache>;
+
+/// The `core.fsmonitorHookVersion` key.
+#[cfg(feature = "status")]
+pub type FsmonitorHookVersion = keys::Any<validate::FsmonitorHookVersion>;
+
 #[cfg(feature = "attributes")]
 mod filter {
     use super::validate;
@@ -357,6 +398,59 @@ mod log_all_ref_updates {
     }
 }
 
+#[cfg(feature = "status")]
+mod untracked_cache {
+    use crate::{config, config::tree::core::UntrackedCache, status};
+
+    impl UntrackedCache {
+        /// Returns the way the untracked cache should be used as parsed from `value`, which is either a boolean or `keep`.
+        /// Like with [`LogAllRefUpdates`](super::LogAllRefUpdates), the boolean interpretation has to be tried first.
+        pub fn try_into_untracked_cache(
+            &'static self,
+            value: Option<Result<bool, gix_config::value::Error>>,
+        ) -> Result<Option<status::UntrackedCache>, config::key::GenericErrorWithValue> {
+            match value {
+                Some(Ok(bool)) => Ok(Some(if bool {
+                    status::UntrackedCache::Enable
+                } else {
+                    status::UntrackedCache::Disable
+                })),
+                Some(Err(err)) => match err.input {
+                    val if val.eq_ignore_ascii_case(b"keep") => Ok(Some(status::UntrackedCache::Keep)),
+                    val => Err(config::key::GenericErrorWithValue::from_value(self, val)),
+                },
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "status")]
+mod fsmonitor_hook_version {
+    use std::borrow::Cow;
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::core::FsmonitorHookVersion,
+    };
+
+    impl FsmonitorHookVersion {
+        /// Convert `value` into the version of the protocol to use when communicating with the `core.fsmonitor` hook.
+        pub fn try_into_hook_version(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<gix_status::fsmonitor::hook::Version, config::key::GenericErrorWithValue> {
+            use gix_status::fsmonitor::hook::Version;
+            Ok(match value.as_ref().as_bytes() {
+                b"1" => Version::V1,
+                b"2" => Version::V2,
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
 mod check_stat {
     use std::borrow::Cow;
 
@@ -472,6 +566,27 @@ mod validate {
         }
     }
 
+    #[cfg(feature = "status")]
+    pub struct UntrackedCache;
+    #[cfg(feature = "status")]
+    impl keys::Validate for UntrackedCache {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::UNTRACKED_CACHE
+                .try_into_untracked_cache(Some(gix_config::Boolean::try_from(value).map(|b| b.0)))?;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "status")]
+    pub struct FsmonitorHookVersion;
+    #[cfg(feature = "status")]
+    impl keys::Validate for FsmonitorHookVersion {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::FSMONITOR_HOOK_VERSION.try_into_hook_version(value.into())?;
+            Ok(())
+        }
+    }
+
     pub struct CheckStat;
     impl keys::Validate for CheckStat {
         fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -5,8 +5,10 @@ use crate::{
 
 impl Diff {
     /// The `diff.algorithm` key.
-    pub const ALGORITHM: Algorithm = Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm)
-                                        .with_deviation("'patience' diff is not implemented and can default to 'histogram' if lenient config is used, and defaults to histogram if unset for fastest and best results");
+    pub const ALGORITHM: Algorithm =
+        Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm).with_deviation(
+            "'patience' places hunks like 'git diff --no-indent-heuristic' as the indent heuristic isn't implemented",
+        );
     /// The `diff.renameLimit` key.
     pub const RENAME_LIMIT: keys::UnsignedInteger = keys::UnsignedInteger::new_unsigned_integer(
         "renameLimit",
@@ -17,6 +19,16 @@ impl Diff {
     );
     /// The `diff.renames` key.
     pub const RENAMES: Renames = Renames::new_renames("renames", &config::Tree::DIFF);
+    /// The `diff.context` key.
+    pub const CONTEXT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("context", &config::Tree::DIFF);
+    /// The `diff.interHunkContext` key.
+    pub const INTER_HUNK_CONTEXT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("interHunkContext", &config::Tree::DIFF);
+    /// The `diff.noprefix` key.
+    pub const NO_PREFIX: keys::Boolean = keys::Boolean::new_boolean("noprefix", &config::Tree::DIFF);
+    /// The `diff.mnemonicPrefix` key.
+    pub const MNEMONIC_PREFIX: keys::Boolean = keys::Boolean::new_boolean("mnemonicPrefix", &config::Tree::DIFF);
 
     /// The `diff.<driver>.command` key.
     pub const DRIVER_COMMAND: keys::Program = keys::Program::new_program("command", &config::Tree::DIFF)
@@ -47,6 +59,10 @@ impl Section for Diff {
             &Self::ALGORITHM,
             &Self::RENAME_LIMIT,
             &Self::RENAMES,
+            &Self::CONTEXT,
+            &Self::INTER_HUNK_CONTEXT,
+            &Self::NO_PREFIX,
+            &Self::MNEMONIC_PREFIX,
             &Self::DRIVER_COMMAND,
             &Self::DRIVER_TEXTCONV,
             &Self::DRIVER_ALGORITHM,
@@ -70,7 +86,6 @@ mod algorithm {
 
     use crate::{
         bstr::BStr,
-        config,
         config::{diff::algorithm::Error, tree::sections::diff::Algorithm},
     };
 
@@ -84,9 +99,7 @@ mod algorithm {
             } else if name.eq_ignore_ascii_case(b"histogram") {
                 gix_diff::blob::Algorithm::Histogram
             } else if name.eq_ignore_ascii_case(b"patience") {
-                return Err(config::diff::algorithm::Error::Unimplemented {
-                    name: name.into_owned(),
-                });
+                gix_diff::blob::Algorithm::Patience
             } else {
                 return Err(Error::Unknown {
                     name: name.into_owned(),
@@ -9,13 +9,40 @@ impl Extensions {
     /// The `extensions.objectFormat` key.
     pub const OBJECT_FORMAT: ObjectFormat =
         ObjectFormat::new_with_validate("objectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat).with_note(
-            "Support for SHA256 is prepared but not fully implemented yet. For now we abort when encountered",
+            "Support for SHA256 requires the `sha256` feature, and such repositories can't be opened otherwise",
         );
+    /// The `extensions.refStorage` key.
+    pub const REF_STORAGE: RefStorage =
+        RefStorage::new_with_validate("refStorage", &config::Tree::EXTENSIONS, validate::RefStorage);
 }
 
 /// The `core.checkStat` key.
 pub type ObjectFormat = keys::Any<validate::ObjectFormat>;
 
+/// The `extensions.refStorage` key.
+pub type RefStorage = keys::Any<validate::RefStorage>;
+
+mod ref_storage {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::sections::extensions::RefStorage};
+
+    impl RefStorage {
+        pub fn try_into_ref_storage(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<gix_ref::store::RefStorage, config::key::GenericErrorWithValue> {
+            if value.as_ref() == "files" {
+                Ok(gix_ref::store::RefStorage::Files)
+            } else if value.as_ref() == "reftable" {
+                Ok(gix_ref::store::RefStorage::Reftable)
+            } else {
+                Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
+            }
+        }
+    }
+}
+
 mod object_format {
     use std::borrow::Cow;
 
@@ -27,10 +54,13 @@ mod object_format {
             value: Cow<'_, BStr>,
         ) -> Result<gix_hash::Kind, config::key::GenericErrorWithValue> {
             if value.as_ref().eq_ignore_ascii_case(b"sha1") {
-                Ok(gix_hash::Kind::Sha1)
-            } else {
-                Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
+                return Ok(gix_hash::Kind::Sha1);
+            }
+            #[cfg(feature = "sha256")]
+            if value.as_ref().eq_ignore_ascii_case(b"sha256") {
+                return Ok(gix_hash::Kind::Sha256);
             }
+            Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
         }
     }
 }
@@ -41,7 +71,7 @@ impl Section for Extensions {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::OBJECT_FORMAT, &Self::WORKTREE_CONFIG]
+        &[&Self::OBJECT_FORMAT, &Self::REF_STORAGE, &Self::WORKTREE_CONFIG]
     }
 }
 
@@ -56,4 +86,13 @@ mod validate {
             Ok(())
         }
     }
+
+    pub struct RefStorage;
+
+    impl keys::Validate for RefStorage {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Extensions::REF_STORAGE.try_into_ref_storage(value.into())?;
+            Ok(())
+        }
+    }
 }
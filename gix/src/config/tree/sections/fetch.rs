@@ -10,6 +10,8 @@ impl Fetch {
         &config::Tree::FETCH,
         validate::NegotiationAlgorithm,
     );
+    /// The `fetch.writeFetchHead` key.
+    pub const WRITE_FETCH_HEAD: keys::Boolean = keys::Boolean::new_boolean("writeFetchHead", &config::Tree::FETCH);
     /// The `fetch.recurseSubmodules` key.
     #[cfg(feature = "attributes")]
     pub const RECURSE_SUBMODULES: RecurseSubmodules =
@@ -24,6 +26,7 @@ impl Section for Fetch {
     fn keys(&self) -> &[&dyn Key] {
         &[
             &Self::NEGOTIATION_ALGORITHM,
+            &Self::WRITE_FETCH_HEAD,
             #[cfg(feature = "attributes")]
             &Self::RECURSE_SUBMODULES,
         ]
@@ -0,0 +1,146 @@
+use crate::{
+    config,
+    config::tree::{keys, Gpg, Key, Section},
+};
+
+impl Gpg {
+    /// The `gpg.format` key.
+    pub const FORMAT: Format = Format::new_with_validate("format", &config::Tree::GPG, validate::Format);
+    /// The `gpg.program` key.
+    pub const PROGRAM: keys::Executable = keys::Executable::new_executable("program", &config::Tree::GPG)
+        .with_note("Used for OpenPGP signatures if `gpg.openpgp.program` isn't set");
+
+    /// The `gpg.openpgp` section.
+    pub const OPENPGP: OpenPgp = OpenPgp;
+    /// The `gpg.x509` section.
+    pub const X509: X509 = X509;
+    /// The `gpg.ssh` section.
+    pub const SSH: Ssh = Ssh;
+}
+
+impl Section for Gpg {
+    fn name(&self) -> &str {
+        "gpg"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::FORMAT, &Self::PROGRAM]
+    }
+
+    fn sub_sections(&self) -> &[&dyn Section] {
+        &[&Self::OPENPGP, &Self::X509, &Self::SSH]
+    }
+}
+
+/// The `gpg.format` key.
+pub type Format = keys::Any<validate::Format>;
+
+/// The `gpg.openpgp` sub-section.
+#[derive(Copy, Clone, Default)]
+pub struct OpenPgp;
+
+impl OpenPgp {
+    /// The `gpg.openpgp.program` key.
+    pub const PROGRAM: keys::Executable = keys::Executable::new_executable("program", &Gpg::OPENPGP);
+}
+
+impl Section for OpenPgp {
+    fn name(&self) -> &str {
+        "openpgp"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::PROGRAM]
+    }
+
+    fn parent(&self) -> Option<&dyn Section> {
+        Some(&config::Tree::GPG)
+    }
+}
+
+/// The `gpg.x509` sub-section.
+#[derive(Copy, Clone, Default)]
+pub struct X509;
+
+impl X509 {
+    /// The `gpg.x509.program` key.
+    pub const PROGRAM: keys::Executable = keys::Executable::new_executable("program", &Gpg::X509);
+}
+
+impl Section for X509 {
+    fn name(&self) -> &str {
+        "x509"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::PROGRAM]
+    }
+
+    fn parent(&self) -> Option<&dyn Section> {
+        Some(&config::Tree::GPG)
+    }
+}
+
+/// The `gpg.ssh` sub-section.
+#[derive(Copy, Clone, Default)]
+pub struct Ssh;
+
+impl Ssh {
+    /// The `gpg.ssh.program` key.
+    pub const PROGRAM: keys::Executable = keys::Executable::new_executable("program", &Gpg::SSH);
+    /// The `gpg.ssh.allowedSignersFile` key.
+    pub const ALLOWED_SIGNERS_FILE: keys::Path = keys::Path::new_path("allowedSignersFile", &Gpg::SSH);
+}
+
+impl Section for Ssh {
+    fn name(&self) -> &str {
+        "ssh"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::PROGRAM, &Self::ALLOWED_SIGNERS_FILE]
+    }
+
+    fn parent(&self) -> Option<&dyn Section> {
+        Some(&config::Tree::GPG)
+    }
+}
+
+mod format {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::gpg::Format, signing};
+
+    impl Format {
+        /// Convert `value` into the format of signatures to create.
+        pub fn try_into_format(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<signing::Format, config::key::GenericErrorWithValue> {
+            Ok(if value.as_ref() == "openpgp" {
+                signing::Format::OpenPgp
+            } else if value.as_ref() == "x509" {
+                signing::Format::X509
+            } else if value.as_ref() == "ssh" {
+                signing::Format::Ssh
+            } else {
+                return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()));
+            })
+        }
+    }
+}
+
+mod validate {
+    use crate::{
+        bstr::BStr,
+        config::tree::{keys, Gpg},
+    };
+
+    pub struct Format;
+    impl keys::Validate for Format {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            Gpg::FORMAT.try_into_format(value.into())?;
+            Ok(())
+        }
+    }
+}
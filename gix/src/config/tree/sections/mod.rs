@@ -5,6 +5,13 @@
 pub struct Author;
 mod author;
 
+/// The `blame` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "blame")]
+pub struct Blame;
+#[cfg(feature = "blame")]
+pub mod blame;
+
 /// The `branch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Branch;
@@ -20,6 +27,13 @@ pub mod checkout;
 pub struct Clone;
 mod clone;
 
+/// The `commit` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "signing")]
+pub struct Commit;
+#[cfg(feature = "signing")]
+mod commit;
+
 /// The `committer` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Committer;
@@ -57,6 +71,13 @@ pub mod fetch;
 pub struct Gitoxide;
 pub mod gitoxide;
 
+/// The `gpg` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "signing")]
+pub struct Gpg;
+#[cfg(feature = "signing")]
+pub mod gpg;
+
 /// The `http` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Http;
@@ -76,6 +97,20 @@ mod init;
 pub struct Mailmap;
 mod mailmap;
 
+/// The `merge` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "merge")]
+pub struct Merge;
+#[cfg(feature = "merge")]
+pub mod merge;
+
+/// The `notes` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "notes")]
+pub struct Notes;
+#[cfg(feature = "notes")]
+pub mod notes;
+
 /// The `pack` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Pack;
@@ -91,6 +126,13 @@ pub mod protocol;
 pub struct Push;
 pub mod push;
 
+/// The `rebase` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "rebase")]
+pub struct Rebase;
+#[cfg(feature = "rebase")]
+mod rebase;
+
 /// The `remote` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Remote;
@@ -113,6 +155,13 @@ pub struct Status;
 #[cfg(feature = "status")]
 pub mod status;
 
+/// The `tag` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "signing")]
+pub struct Tag;
+#[cfg(feature = "signing")]
+mod tag;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
@@ -0,0 +1,74 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Notes, Section, SubSectionRequirement},
+};
+
+impl Notes {
+    /// The `notes.displayRef` key.
+    pub const DISPLAY_REF: keys::Any = keys::Any::new("displayRef", &config::Tree::NOTES);
+    /// The `notes.mergeStrategy` key.
+    pub const MERGE_STRATEGY: MergeStrategy =
+        MergeStrategy::new_with_validate("mergeStrategy", &config::Tree::NOTES, validate::MergeStrategy);
+    /// The `notes.<name>.mergeStrategy` key.
+    pub const NAMED_MERGE_STRATEGY: MergeStrategy =
+        MergeStrategy::new_with_validate("mergeStrategy", &config::Tree::NOTES, validate::MergeStrategy)
+            .with_subsection_requirement(Some(SubSectionRequirement::Parameter("name")));
+}
+
+impl Section for Notes {
+    fn name(&self) -> &str {
+        "notes"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::DISPLAY_REF, &Self::MERGE_STRATEGY, &Self::NAMED_MERGE_STRATEGY]
+    }
+}
+
+/// The `notes.mergeStrategy` key.
+pub type MergeStrategy = keys::Any<validate::MergeStrategy>;
+
+mod merge_strategy {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::sections::notes::MergeStrategy};
+    use gix_note::merge::Strategy;
+
+    impl MergeStrategy {
+        /// Derive the strategy to use to resolve conflicting notes.
+        pub fn try_into_merge_strategy(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<Strategy, config::key::GenericErrorWithValue> {
+            let strategy = if value.as_ref() == "manual" {
+                Strategy::Manual
+            } else if value.as_ref() == "ours" {
+                Strategy::Ours
+            } else if value.as_ref() == "theirs" {
+                Strategy::Theirs
+            } else if value.as_ref() == "union" {
+                Strategy::Union
+            } else if value.as_ref() == "cat_sort_uniq" {
+                Strategy::CatSortUniq
+            } else {
+                return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned()));
+            };
+            Ok(strategy)
+        }
+    }
+}
+
+mod validate {
+    use crate::{
+        bstr::BStr,
+        config::tree::{keys, Notes},
+    };
+
+    pub struct MergeStrategy;
+    impl keys::Validate for MergeStrategy {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            Notes::MERGE_STRATEGY.try_into_merge_strategy(value.into())?;
+            Ok(())
+        }
+    }
+}
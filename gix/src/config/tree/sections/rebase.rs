@@ -0,0 +1,21 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Rebase, Section},
+};
+
+impl Rebase {
+    /// The `rebase.autoSquash` key.
+    pub const AUTO_SQUASH: keys::Boolean = keys::Boolean::new_boolean("autoSquash", &config::Tree::REBASE);
+    /// The `rebase.updateRefs` key.
+    pub const UPDATE_REFS: keys::Boolean = keys::Boolean::new_boolean("updateRefs", &config::Tree::REBASE);
+}
+
+impl Section for Rebase {
+    fn name(&self) -> &str {
+        "rebase"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::AUTO_SQUASH, &Self::UPDATE_REFS]
+    }
+}
@@ -20,10 +20,17 @@ pub mod rename {
 ///
 #[cfg(feature = "blob-diff")]
 mod utils {
-    use gix_diff::{rewrites::Copies, Rewrites};
+    use gix_diff::{
+        blob::{header::PrefixStyle, ContextSize},
+        rewrites::Copies,
+        Rewrites,
+    };
 
     use crate::{
-        config::{cache::util::ApplyLeniency, tree::Diff},
+        config::{
+            cache::util::ApplyLeniency,
+            tree::{keys, Diff, Key},
+        },
         diff::rename::Tracking,
         Repository,
     };
@@ -42,6 +49,80 @@ mod utils {
         }
     }
 
+    ///
+    #[allow(clippy::empty_docs)]
+    pub mod render_options {
+        /// The error returned by [`render_options()`](super::render_options()).
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error(transparent)]
+            ConfigDiffContext(#[from] crate::config::unsigned_integer::Error),
+            #[error(transparent)]
+            ConfigDiffPrefix(#[from] crate::config::boolean::Error),
+        }
+    }
+
+    /// Options to control how diffs are rendered as text, like `git diff` does it.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct RenderOptions {
+        /// The amount of unchanged lines to show around each change, from `diff.context`.
+        pub context_size: ContextSize,
+        /// The amount of unchanged lines between hunks that may be shown to merge them, from `diff.interHunkContext`.
+        pub inter_hunk_context: u32,
+        /// The prefixes to use for the paths of both sides of the diff, from `diff.noprefix` and `diff.mnemonicPrefix`.
+        pub prefix_style: PrefixStyle,
+    }
+
+    impl Default for RenderOptions {
+        fn default() -> Self {
+            RenderOptions {
+                context_size: ContextSize::default(),
+                inter_hunk_context: 0,
+                prefix_style: PrefixStyle::Default,
+            }
+        }
+    }
+
+    /// Create an instance by reading all relevant information from the `config`uration, while being `lenient` or not.
+    ///
+    /// Note that `diff.noprefix` takes precedence over `diff.mnemonicPrefix`, just like in `git`.
+    pub fn render_options(
+        config: &gix_config::File<'static>,
+        lenient: bool,
+    ) -> Result<RenderOptions, render_options::Error> {
+        let default = RenderOptions::default();
+        let integer = |key: &'static keys::UnsignedInteger| {
+            config
+                .integer_by_key(key.logical_name().as_str())
+                .map(|value| key.try_into_u32(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+        let boolean = |key: &'static keys::Boolean| {
+            config
+                .boolean_by_key(key.logical_name().as_str())
+                .map(|value| key.enrich_error(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+
+        let context_size = integer(&Diff::CONTEXT)?.map_or(default.context_size, ContextSize::symmetrical);
+        let inter_hunk_context = integer(&Diff::INTER_HUNK_CONTEXT)?.unwrap_or(default.inter_hunk_context);
+        let prefix_style = if boolean(&Diff::NO_PREFIX)?.unwrap_or(false) {
+            PrefixStyle::None
+        } else if boolean(&Diff::MNEMONIC_PREFIX)?.unwrap_or(false) {
+            PrefixStyle::Mnemonic
+        } else {
+            default.prefix_style
+        };
+        Ok(RenderOptions {
+            context_size,
+            inter_hunk_context,
+            prefix_style,
+        })
+    }
+
     ///
     #[allow(clippy::empty_docs)]
     pub mod resource_cache {
@@ -123,7 +204,7 @@ mod utils {
             },
             gix_diff::blob::Pipeline::new(
                 roots,
-                gix_filter::Pipeline::new(repo.command_context()?, crate::filter::Pipeline::options(repo)?),
+                crate::filter::plumbing_pipeline(repo)?,
                 repo.config.diff_drivers()?,
                 repo.config.diff_pipeline_options()?,
             ),
@@ -134,4 +215,4 @@ mod utils {
     }
 }
 #[cfg(feature = "blob-diff")]
-pub use utils::{new_rewrites, resource_cache};
+pub use utils::{new_rewrites, render_options, resource_cache, RenderOptions};
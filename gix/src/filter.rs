@@ -80,6 +80,9 @@ pub struct Pipeline<'repo> {
 /// Lifecycle
 impl<'repo> Pipeline<'repo> {
     /// Extract options from `repo` that are needed to properly drive a standard git filter pipeline.
+    ///
+    /// With the `lfs` feature, a driver for `filter=lfs` is always present so the in-process LFS filter can be used
+    /// even if `git-lfs` isn't configured.
     pub fn options(repo: &'repo Repository) -> Result<gix_filter::pipeline::Options, pipeline::options::Error> {
         let config = &repo.config.resolved;
         let encodings =
@@ -104,7 +107,18 @@ impl<'repo> Pipeline<'repo> {
             .string_by_key("core.eol")
             .map(|value| Core::EOL.try_into_eol(value))
             .transpose()?;
-        let drivers = extract_drivers(repo)?;
+        #[cfg_attr(not(feature = "lfs"), allow(unused_mut))]
+        let mut drivers = extract_drivers(repo)?;
+        #[cfg(feature = "lfs")]
+        if !drivers.iter().any(|driver| driver.name == gix_lfs::DRIVER_NAME) {
+            drivers.push(gix_filter::Driver {
+                name: gix_lfs::DRIVER_NAME.into(),
+                clean: None,
+                smudge: None,
+                process: None,
+                required: true,
+            });
+        }
         Ok(gix_filter::pipeline::Options {
             drivers,
             eol_config: gix_filter::eol::Configuration { auto_crlf, eol },
@@ -117,9 +131,8 @@ impl<'repo> Pipeline<'repo> {
     /// Create a new instance by extracting all necessary information and configuration from a `repo` along with `cache` for accessing
     /// attributes. The `index` is used for some filters which may access it under very specific circumstances.
     pub fn new(repo: &'repo Repository, cache: gix_worktree::Stack) -> Result<Self, pipeline::options::Error> {
-        let pipeline = gix_filter::Pipeline::new(repo.command_context()?, Self::options(repo)?);
         Ok(Pipeline {
-            inner: pipeline,
+            inner: plumbing_pipeline(repo)?,
             cache,
             repo,
         })
@@ -199,6 +212,17 @@ impl<'repo> Pipeline<'repo> {
     }
 }
 
+/// Create a plumbing pipeline with [options](Pipeline::options()) from `repo`, and with all in-process filters registered.
+pub(crate) fn plumbing_pipeline(repo: &Repository) -> Result<gix_filter::Pipeline, pipeline::options::Error> {
+    #[cfg_attr(not(feature = "lfs"), allow(unused_mut))]
+    let mut pipeline = gix_filter::Pipeline::new(repo.command_context()?, Pipeline::options(repo)?);
+    #[cfg(feature = "lfs")]
+    pipeline
+        .driver_state_mut()
+        .register_in_process(gix_lfs::DRIVER_NAME, std::sync::Arc::new(lfs::filter(repo)));
+    Ok(pipeline)
+}
+
 /// Obtain a list of all configured driver, but ignore those in sections that we don't trust enough.
 fn extract_drivers(repo: &Repository) -> Result<Vec<gix_filter::Driver>, pipeline::options::Error> {
     repo.config
@@ -229,3 +253,100 @@ fn extract_drivers(repo: &Repository) -> Result<Vec<gix_filter::Driver>, pipelin
         })
         .collect::<Result<Vec<_>, pipeline::options::Error>>()
 }
+
+#[cfg(feature = "lfs")]
+mod lfs {
+    use crate::{bstr::ByteSlice, remote, Repository};
+
+    /// Create the in-process LFS filter for `repo`, which downloads missing objects from the LFS remote if possible.
+    /// Otherwise, their pointers are checked out instead.
+    pub(super) fn filter(repo: &Repository) -> gix_lfs::Filter {
+        let filter = gix_lfs::Filter::new(gix_lfs::Store::at(repo.common_dir().join("lfs")));
+        match transfer(repo) {
+            Some(adapter) => filter.with_transfer(adapter),
+            None => filter.with_keep_pointer_if_missing(true),
+        }
+    }
+
+    /// Like `git-lfs`, use `lfs.url` or `remote.<name>.lfsurl` of the default remote as LFS endpoint,
+    /// or derive it from the URL of the default remote.
+    fn transfer(repo: &Repository) -> Option<Box<dyn gix_lfs::transfer::Adapter + Send>> {
+        let config = &repo.config.resolved;
+        let mut filter = repo.filter_config_section();
+        let remote_name = repo.remote_default_name(remote::Direction::Fetch);
+        let (url, is_endpoint) = match config
+            .string_filter_by_key("lfs.url", &mut filter)
+            .or_else(|| config.string_filter("remote", remote_name.as_deref(), "lfsurl", &mut filter))
+        {
+            Some(url) => (gix_url::parse(url.as_ref()).ok()?, true),
+            None => (
+                repo.find_remote(remote_name?.as_ref())
+                    .ok()?
+                    .url(remote::Direction::Fetch)?
+                    .clone(),
+                false,
+            ),
+        };
+        match url.scheme {
+            gix_url::Scheme::File => {
+                let path = gix_path::from_bstr(url.path.as_bstr()).into_owned();
+                let git_dir = path.join(".git");
+                let git_dir = if is_endpoint || !git_dir.is_dir() {
+                    path
+                } else {
+                    git_dir
+                };
+                Some(Box::new(gix_lfs::transfer::File::at(git_dir.join("lfs"))))
+            }
+            #[cfg(any(
+                feature = "blocking-http-transport-reqwest",
+                feature = "blocking-http-transport-curl"
+            ))]
+            gix_url::Scheme::Http | gix_url::Scheme::Https => {
+                let url = url.to_bstring().to_string();
+                Some(Box::new(http::Lazy {
+                    endpoint: if is_endpoint {
+                        url.trim_end_matches('/').to_owned()
+                    } else {
+                        gix_lfs::batch::endpoint(&url)
+                    },
+                    client: None,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    #[cfg(any(
+        feature = "blocking-http-transport-reqwest",
+        feature = "blocking-http-transport-curl"
+    ))]
+    mod http {
+        use gix_lfs::{batch::Client, transfer, Pointer};
+        use gix_transport::client::http::Impl;
+
+        /// A batch client which is only created once it's needed, as creating an HTTP implementation can be costly.
+        pub(super) struct Lazy {
+            pub(super) endpoint: String,
+            pub(super) client: Option<Client<Impl>>,
+        }
+
+        impl Lazy {
+            fn client(&mut self) -> &mut Client<Impl> {
+                let endpoint = &self.endpoint;
+                self.client
+                    .get_or_insert_with(|| Client::new(Impl::default(), endpoint.clone()))
+            }
+        }
+
+        impl transfer::Adapter for Lazy {
+            fn download(&mut self, pointer: &Pointer, out: &mut dyn std::io::Write) -> Result<(), transfer::Error> {
+                self.client().download(pointer, out)
+            }
+
+            fn upload(&mut self, pointer: &Pointer, src: &mut dyn std::io::Read) -> Result<(), transfer::Error> {
+                self.client().upload(pointer, src)
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Add, remove and move entries of the index along with their files in the worktree, similar to `git add`, `git rm` and `git mv`.
+//!
+//! All changes are made to a copy of the index which is kept in memory until it's written back with [`Platform::write()`].
+//! Whenever entries change, the `TREE` extension and the untracked cache are invalidated for their paths so that they remain valid.
+use crate::Repository;
+
+mod platform;
+
+/// A platform to add, remove and move entries of the index, and to write the changed index back to disk.
+///
+/// Obtained with [`Repository::index_mut()`].
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) index: gix_index::File,
+    pub(crate) pipeline: Option<crate::filter::Pipeline<'repo>>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blob {
+    /// The error returned when turning a file in the worktree into a blob.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read '{}' from the worktree", path.display())]
+        ReadFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        Time(#[from] std::time::SystemTimeError),
+        #[error(transparent)]
+        FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+        #[error(transparent)]
+        ConvertToGit(#[from] crate::filter::pipeline::convert_to_git::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod add {
+    use crate::bstr::BString;
+
+    /// Options for use in [`Platform::add()`](crate::index_mutation::Platform::add()).
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Options {
+        /// If `true`, only update or remove entries that are already tracked, but don't add untracked files,
+        /// similar to `git add --update`.
+        pub update_only: bool,
+        /// If `true`, untracked files are recorded with an empty blob and the intent to add them later,
+        /// similar to `git add --intent-to-add`. Tracked entries are updated as usual.
+        pub intent_to_add: bool,
+        /// If `true`, also add files that are ignored, similar to `git add --force`.
+        pub force: bool,
+    }
+
+    /// The outcome of [`Platform::add()`](crate::index_mutation::Platform::add()).
+    #[derive(Default, Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// Untracked files that were added.
+        pub added: Vec<BString>,
+        /// Tracked entries whose content or mode changed.
+        pub modified: Vec<BString>,
+        /// Tracked entries that were removed as their files don't exist in the worktree anymore.
+        pub removed: Vec<BString>,
+        /// Files that were named explicitly, but weren't added as they are ignored and [`Options::force`] wasn't set.
+        pub ignored: Vec<BString>,
+    }
+
+    /// The error returned by [`Platform::add()`](crate::index_mutation::Platform::add()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot add files in a bare repository")]
+        BareRepository,
+        #[error(transparent)]
+        FilesystemOptions(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        StatOptions(#[from] crate::config::stat_options::Error),
+        #[error(transparent)]
+        Pathspec(#[from] crate::pathspec::init::Error),
+        #[error(transparent)]
+        Dirwalk(#[from] crate::dirwalk::Error),
+        #[error(transparent)]
+        Blob(#[from] super::blob::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod remove {
+    use crate::bstr::BString;
+
+    /// Options for use in [`Platform::remove()`](crate::index_mutation::Platform::remove()).
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Options {
+        /// If `true`, only remove entries from the index, but keep their files in the worktree, similar to `git rm --cached`.
+        pub cached: bool,
+        /// If `true`, remove entries even if they have changes that would be lost, similar to `git rm --force`.
+        pub force: bool,
+        /// If `true`, allow patterns to match directories and remove all of their entries, similar to `git rm -r`.
+        pub recursive: bool,
+    }
+
+    /// The outcome of [`Platform::remove()`](crate::index_mutation::Platform::remove()).
+    #[derive(Default, Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// The paths of all entries that were removed from the index.
+        pub removed: Vec<BString>,
+    }
+
+    /// The error returned by [`Platform::remove()`](crate::index_mutation::Platform::remove()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot remove files in a bare repository")]
+        BareRepository,
+        #[error("Not removing '{pattern}' recursively without the recursive option")]
+        NotRecursive { pattern: BString },
+        #[error("'{path}' has local modifications")]
+        LocalModifications { path: BString },
+        #[error("'{path}' has changes staged in the index")]
+        StagedChanges { path: BString },
+        #[error("'{path}' has staged content different from both the file and HEAD")]
+        StagedContentDiffers { path: BString },
+        #[error("Could not remove '{}' from the worktree", path.display())]
+        RemoveFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        FilesystemOptions(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        StatOptions(#[from] crate::config::stat_options::Error),
+        #[error(transparent)]
+        Pathspec(#[from] crate::pathspec::init::Error),
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        Blob(#[from] super::blob::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod rename {
+    use crate::bstr::BString;
+
+    /// The outcome of [`Platform::rename()`](crate::index_mutation::Platform::rename()).
+    #[derive(Default, Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// The previous and the new path of all entries that were moved.
+        pub moved: Vec<(BString, BString)>,
+    }
+
+    /// The error returned by [`Platform::rename()`](crate::index_mutation::Platform::rename()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot move files in a bare repository")]
+        BareRepository,
+        #[error("'{path}' isn't tracked")]
+        NotTracked { path: BString },
+        #[error("'{path}' has conflicts which have to be resolved first")]
+        Conflict { path: BString },
+        #[error("'{path}' doesn't exist in the worktree")]
+        SourceMissing { path: BString },
+        #[error("Cannot move '{source_path}' into itself at '{destination}'")]
+        DestinationInsideSource { source_path: BString, destination: BString },
+        #[error("'{path}' already exists")]
+        DestinationExists { path: BString },
+        #[error("The directory to move '{path}' into doesn't exist")]
+        DestinationDirectoryMissing { path: BString },
+        #[error("Could not move '{}' to '{}'", source_path.display(), destination.display())]
+        Rename {
+            source_path: std::path::PathBuf,
+            destination: std::path::PathBuf,
+            source: std::io::Error,
+        },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write {
+    /// The error returned by [`Platform::write()`](crate::index_mutation::Platform::write()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigSkipHash(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+    }
+}
+
+impl Repository {
+    /// Return a platform to add, remove and move entries of a copy of the index, similar to `git add`, `git rm` and `git mv`.
+    ///
+    /// If there is no index yet, like in a newly initialized repository, an empty one is used and created when writing it.
+    pub fn index_mut(&self) -> Result<Platform<'_>, crate::worktree::open_index::Error> {
+        let index = self.open_index().or_else(|err| match err {
+            crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                Ok(gix_index::File::from_state(
+                    gix_index::State::new(self.object_hash()),
+                    self.index_path(),
+                ))
+            }
+            err => Err(err),
+        })?;
+        Ok(Platform {
+            repo: self,
+            index,
+            pipeline: None,
+        })
+    }
+}
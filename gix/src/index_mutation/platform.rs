@@ -0,0 +1,459 @@
+use std::{io::Read, path::Path, sync::atomic::AtomicBool};
+
+use gix_dir::entry::{Kind, PathspecMatch, Status};
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stage, Stat};
+use gix_pathspec::search::MatchKind;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::cache::util::ApplyLeniencyDefault,
+    index_mutation::{add, blob, remove, rename, write, Platform},
+    worktree::stack::state::attributes::Source,
+};
+
+/// Access
+impl Platform<'_> {
+    /// Return the index with all changes made so far.
+    pub fn index(&self) -> &gix_index::File {
+        &self.index
+    }
+
+    /// Turn this instance into the index with all changes made so far, without writing it.
+    pub fn into_index(self) -> gix_index::File {
+        self.index
+    }
+}
+
+/// Mutation
+impl Platform<'_> {
+    /// Add all files matching `patterns` to the index, similar to `git add`, or all files if `patterns` is empty.
+    ///
+    /// Tracked entries are updated if their file changed, and removed if their file was deleted. Conflicts at their paths
+    /// are resolved by replacing all stages with the file in the worktree.
+    /// Untracked files are found with a [directory walk](crate::Repository::dirwalk()) which skips ignored files unless
+    /// [`options.force`](add::Options::force) is set, and are added unless [`options.update_only`](add::Options::update_only)
+    /// is set. The content of files is converted with the filter pipeline before it's written as blob, and their mode
+    /// reflects if they are executable or symbolic links if the filesystem supports it.
+    ///
+    /// Submodules and nested repositories are left untouched.
+    pub fn add(
+        &mut self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: add::Options,
+    ) -> Result<add::Outcome, add::Error> {
+        let workdir = self.repo.work_dir().ok_or(add::Error::BareRepository)?;
+        let patterns: Vec<BString> = patterns.into_iter().map(|p| p.as_ref().to_owned()).collect();
+        let capabilities = self.repo.filesystem_options()?;
+        let stat_options = self.repo.stat_options()?;
+        let mut out = add::Outcome::default();
+
+        let mut pathspec = self
+            .repo
+            .pathspec(false, &patterns, true, &self.index, Source::WorktreeThenIdMapping)?;
+        let mut tracked = Vec::<BString>::new();
+        for entry in self.index.entries() {
+            if entry.mode.is_sparse() || entry.mode.is_submodule() || entry.flags.contains(Flags::SKIP_WORKTREE) {
+                continue;
+            }
+            let path = entry.path(&self.index);
+            if tracked.last().map_or(false, |last| last == path) {
+                continue;
+            }
+            if pathspec.is_included(path, Some(false)) {
+                tracked.push(path.to_owned());
+            }
+        }
+        drop(pathspec);
+
+        for rela_path in tracked {
+            let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+            let md = match metadata(&path)? {
+                Some(md) if !md.is_dir() => md,
+                _ => {
+                    self.index.remove_entries(|_, path, _| path == rela_path);
+                    out.removed.push(rela_path);
+                    continue;
+                }
+            };
+            let entry = self
+                .index
+                .entry_by_path(rela_path.as_bstr())
+                .expect("entries of tracked paths are only removed here");
+            let mode = mode_from_fs(Some(entry.mode), &md, &capabilities);
+            let stat = Stat::from_fs(&md).map_err(blob::Error::from)?;
+            let is_unconflicted = entry.stage() == Stage::Unconflicted && !entry.flags.contains(Flags::INTENT_TO_ADD);
+            if is_unconflicted
+                && entry.mode == mode
+                && entry.stat.matches(&stat, stat_options)
+                && !entry.stat.is_racy(self.index.timestamp(), stat_options)
+            {
+                continue;
+            }
+            let (previous_id, previous_mode) = (entry.id, entry.mode);
+            let id = self.worktree_blob(&path, rela_path.as_bstr(), &md, mode, true)?;
+            self.index
+                .upsert_entry(stat, id, Flags::empty(), mode, rela_path.as_bstr());
+            if !is_unconflicted || id != previous_id || mode != previous_mode {
+                out.modified.push(rela_path);
+            }
+        }
+
+        if !options.update_only {
+            for rela_path in self.untracked_files(&patterns, options.force, &mut out.ignored)? {
+                let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+                let Some(md) = metadata(&path)? else {
+                    continue;
+                };
+                let mode = mode_from_fs(None, &md, &capabilities);
+                let stat = Stat::from_fs(&md).map_err(blob::Error::from)?;
+                let (id, flags) = if options.intent_to_add {
+                    (
+                        ObjectId::empty_blob(self.repo.object_hash()),
+                        Flags::INTENT_TO_ADD | Flags::EXTENDED,
+                    )
+                } else {
+                    (
+                        self.worktree_blob(&path, rela_path.as_bstr(), &md, mode, true)?,
+                        Flags::empty(),
+                    )
+                };
+                self.index.upsert_entry(stat, id, flags, mode, rela_path.as_bstr());
+                out.added.push(rela_path);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Remove all entries matching `patterns` from the index and their files from the worktree, similar to `git rm`.
+    ///
+    /// Unless [`options.force`](remove::Options::force) is set, nothing is removed if any of the matching entries has changes
+    /// that would be lost, which are changes in the worktree or changes compared to `HEAD`.
+    /// If [`options.cached`](remove::Options::cached) is set, files are kept in the worktree, which allows entries to
+    /// be removed as long as they match either their file or `HEAD`.
+    /// Directories that become empty are removed as well.
+    pub fn remove(
+        &mut self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: remove::Options,
+    ) -> Result<remove::Outcome, remove::Error> {
+        let workdir = self.repo.work_dir().ok_or(remove::Error::BareRepository)?;
+        let mut pathspec = self
+            .repo
+            .pathspec(false, patterns, true, &self.index, Source::WorktreeThenIdMapping)?;
+        let mut matched = Vec::<BString>::new();
+        for entry in self.index.entries() {
+            let path = entry.path(&self.index);
+            if entry.mode.is_sparse() || matched.last().map_or(false, |last| last == path) {
+                continue;
+            }
+            let Some(m) = pathspec
+                .pattern_matching_relative_path(path, Some(false))
+                .filter(|m| !m.is_excluded())
+            else {
+                continue;
+            };
+            if !options.recursive && matches!(m.kind, MatchKind::Always | MatchKind::Prefix) {
+                return Err(remove::Error::NotRecursive {
+                    pattern: m.pattern.path().to_owned(),
+                });
+            }
+            matched.push(path.to_owned());
+        }
+        drop(pathspec);
+
+        if !options.force {
+            let head_tree = if self.repo.head()?.is_unborn() {
+                self.repo.empty_tree()
+            } else {
+                self.repo.find_object(self.repo.head_tree_id()?)?.into_tree()
+            };
+            let capabilities = self.repo.filesystem_options()?;
+            let stat_options = self.repo.stat_options()?;
+            let mut buf = Vec::new();
+            for rela_path in &matched {
+                let entry = self
+                    .index
+                    .entry_by_path(rela_path.as_bstr())
+                    .expect("matched paths are tracked");
+                if entry.stage() != Stage::Unconflicted {
+                    continue;
+                }
+                let (id, mode, entry_stat) = (entry.id, entry.mode, entry.stat);
+                let matches_head = head_tree
+                    .lookup_entry_by_path(gix_path::from_bstr(rela_path.as_bstr()), &mut buf)?
+                    .map_or(false, |head| {
+                        head.object_id() == id && Some(head.mode()) == mode.to_tree_entry_mode()
+                    });
+                let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+                let matches_worktree = match metadata(&path)? {
+                    None => true,
+                    Some(md) => {
+                        !md.is_dir()
+                            && mode_from_fs(Some(mode), &md, &capabilities) == mode
+                            && ((entry_stat.matches(&Stat::from_fs(&md).map_err(blob::Error::from)?, stat_options)
+                                && !entry_stat.is_racy(self.index.timestamp(), stat_options))
+                                || self.worktree_blob(&path, rela_path.as_bstr(), &md, mode, false)? == id)
+                    }
+                };
+                let path = rela_path.clone();
+                match (options.cached, matches_head, matches_worktree) {
+                    (_, true, true) | (true, true, false) | (true, false, true) => {}
+                    (_, false, false) => return Err(remove::Error::StagedContentDiffers { path }),
+                    (false, false, true) => return Err(remove::Error::StagedChanges { path }),
+                    (false, true, false) => return Err(remove::Error::LocalModifications { path }),
+                }
+            }
+        }
+
+        self.index
+            .remove_entries(|_, path, _| matched.binary_search_by(|probe| probe.as_bstr().cmp(path)).is_ok());
+        if !options.cached {
+            for rela_path in &matched {
+                let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+                match std::fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(_) if path.symlink_metadata().map_or(true, |md| md.is_dir()) => continue,
+                    Err(source) => return Err(remove::Error::RemoveFile { path, source }),
+                }
+                let mut dir = path.parent();
+                while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+                    if std::fs::remove_dir(parent).is_err() {
+                        break;
+                    }
+                    dir = parent.parent();
+                }
+            }
+        }
+        Ok(remove::Outcome { removed: matched })
+    }
+
+    /// Move the tracked file or directory at the repository-relative `source` path to `destination` in the worktree
+    /// and in the index, similar to `git mv`.
+    ///
+    /// If `destination` is an existing directory, `source` is moved into it. Otherwise, `destination` must not exist yet,
+    /// but the directory containing it has to.
+    /// All entries keep their stat information, so modifications of moved files are still detected.
+    pub fn rename(&mut self, source: &BStr, destination: &BStr) -> Result<rename::Outcome, rename::Error> {
+        let workdir = self.repo.work_dir().ok_or(rename::Error::BareRepository)?;
+        let source = source.trim_end_with(|c| c == '/').as_bstr();
+        let mut destination = destination.trim_end_with(|c| c == '/').as_bstr().to_owned();
+
+        let range = match self.index.entry_range(source) {
+            Some(range) => range,
+            None => {
+                let mut dir = source.to_owned();
+                dir.push_byte(b'/');
+                self.index
+                    .prefixed_entries_range(dir.as_bstr())
+                    .ok_or_else(|| rename::Error::NotTracked { path: source.into() })?
+            }
+        };
+        if let Some(conflict) = self.index.entries()[range.clone()]
+            .iter()
+            .find(|entry| entry.stage() != Stage::Unconflicted)
+        {
+            return Err(rename::Error::Conflict {
+                path: conflict.path(&self.index).to_owned(),
+            });
+        }
+        let source_path = workdir.join(gix_path::from_bstr(source));
+        if source_path.symlink_metadata().is_err() {
+            return Err(rename::Error::SourceMissing { path: source.into() });
+        }
+
+        let destination_is_dir = destination.is_empty()
+            || workdir
+                .join(gix_path::from_bstr(destination.as_bstr()))
+                .symlink_metadata()
+                .map_or(false, |md| md.is_dir());
+        if destination_is_dir {
+            let name = source.rsplit_str("/").next().expect("split always yields one item");
+            if !destination.is_empty() {
+                destination.push_byte(b'/');
+            }
+            destination.extend_from_slice(name);
+        }
+        if destination == source || (destination.starts_with(source) && destination.get(source.len()) == Some(&b'/')) {
+            return Err(rename::Error::DestinationInsideSource {
+                source_path: source.into(),
+                destination,
+            });
+        }
+        let destination_path = workdir.join(gix_path::from_bstr(destination.as_bstr()));
+        let mut destination_dir = destination.clone();
+        destination_dir.push_byte(b'/');
+        if destination_path.symlink_metadata().is_ok()
+            || self.index.entry_range(destination.as_bstr()).is_some()
+            || self.index.prefixed_entries_range(destination_dir.as_bstr()).is_some()
+        {
+            return Err(rename::Error::DestinationExists { path: destination });
+        }
+        if let Some((parent, _name)) = destination.rsplit_once_str("/") {
+            if !workdir.join(gix_path::from_bstr(parent.as_bstr())).is_dir() {
+                return Err(rename::Error::DestinationDirectoryMissing { path: parent.into() });
+            }
+        }
+
+        std::fs::rename(&source_path, &destination_path).map_err(|err| rename::Error::Rename {
+            source_path,
+            destination: destination_path,
+            source: err,
+        })?;
+        let moved: Vec<_> = self.index.entries()[range.clone()]
+            .iter()
+            .map(|entry| (entry.clone(), entry.path(&self.index).to_owned()))
+            .collect();
+        self.index.remove_entries(|idx, _, _| range.contains(&idx));
+        let mut out = rename::Outcome::default();
+        for (entry, path) in moved {
+            let mut new_path = destination.clone();
+            new_path.extend_from_slice(&path[source.len()..]);
+            self.index
+                .upsert_entry(entry.stat, entry.id, entry.flags, entry.mode, new_path.as_bstr());
+            out.moved.push((path, new_path));
+        }
+        Ok(out)
+    }
+
+    /// Write the index back to the file it was read from, or to the place where it would be if it didn't exist,
+    /// while holding a lock for it.
+    ///
+    /// The checksum is skipped if `index.skipHash` is set.
+    pub fn write(&mut self) -> Result<(), write::Error> {
+        let skip_hash = self
+            .repo
+            .config
+            .resolved
+            .boolean("index", None, "skipHash")
+            .map(|res| crate::config::tree::Index::SKIP_HASH.enrich_error(res))
+            .transpose()
+            .with_lenient_default(self.repo.config.lenient_config)?
+            .unwrap_or_default();
+        self.index.write(gix_index::write::Options {
+            extensions: Default::default(),
+            skip_hash,
+        })?;
+        Ok(())
+    }
+}
+
+/// Utilities
+impl Platform<'_> {
+    /// Return the repository-relative paths of all untracked files matching `patterns`, including ignored ones if `force` is `true`.
+    /// Ignored files that were named explicitly are added to `ignored` otherwise.
+    fn untracked_files(
+        &self,
+        patterns: &[BString],
+        force: bool,
+        ignored: &mut Vec<BString>,
+    ) -> Result<Vec<BString>, add::Error> {
+        let options = self
+            .repo
+            .dirwalk_options()?
+            .emit_untracked(gix_dir::walk::EmissionMode::Matching)
+            .emit_ignored(Some(gix_dir::walk::EmissionMode::Matching));
+        let mut files = Vec::new();
+        let mut walks = vec![patterns.to_vec()];
+        while let Some(patterns) = walks.pop() {
+            let mut collect = gix_dir::walk::delegate::Collect::default();
+            self.repo
+                .dirwalk(&self.index, &patterns, &AtomicBool::default(), options, &mut collect)?;
+            for (entry, _dir_status) in collect.unorded_entries {
+                let is_file = matches!(entry.disk_kind, Some(Kind::File | Kind::Symlink));
+                match entry.status {
+                    Status::Untracked if is_file => files.push(entry.rela_path),
+                    Status::Ignored(_) if force => {
+                        if is_file {
+                            files.push(entry.rela_path);
+                        } else if entry.disk_kind == Some(Kind::Directory)
+                            && entry.pathspec_match == Some(PathspecMatch::Always)
+                        {
+                            // Ignored directories are only traversed if a pattern matches them, so name them explicitly.
+                            let mut pattern = BString::from(":(literal)");
+                            pattern.extend_from_slice(&entry.rela_path);
+                            walks.push(vec![pattern]);
+                        }
+                    }
+                    Status::Ignored(_) if entry.pathspec_match == Some(PathspecMatch::Verbatim) => {
+                        ignored.push(entry.rela_path);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        files.sort();
+        ignored.sort();
+        Ok(files)
+    }
+
+    /// Read the file at `path` with metadata `md` and the repository-relative `rela_path`, and return the id of the blob
+    /// with its content as it would be stored with `mode`. The blob is written to the object database if `write` is `true`.
+    fn worktree_blob(
+        &mut self,
+        path: &Path,
+        rela_path: &BStr,
+        md: &gix_index::fs::Metadata,
+        mode: Mode,
+        write: bool,
+    ) -> Result<ObjectId, blob::Error> {
+        let read_error = |source| blob::Error::ReadFile {
+            path: path.to_owned(),
+            source,
+        };
+        let mut buf = Vec::new();
+        if md.is_symlink() {
+            let target = std::fs::read_link(path).map_err(read_error)?;
+            buf.extend_from_slice(gix_path::into_bstr(target).as_ref());
+        } else if mode == Mode::SYMLINK {
+            // Without support for symlinks, they are checked out as files with their target as content.
+            buf = std::fs::read(path).map_err(read_error)?;
+        } else {
+            if self.pipeline.is_none() {
+                self.pipeline = Some(self.repo.filter_pipeline(None)?.0);
+            }
+            let pipeline = self.pipeline.as_mut().expect("just set");
+            let file = std::fs::File::open(path).map_err(read_error)?;
+            pipeline
+                .convert_to_git(file, gix_path::from_bstr(rela_path).as_ref(), &self.index)?
+                .read_to_end(&mut buf)
+                .map_err(read_error)?;
+        }
+        Ok(if write {
+            self.repo.write_blob(&buf)?.detach()
+        } else {
+            gix_object::compute_hash(self.repo.object_hash(), gix_object::Kind::Blob, &buf)
+        })
+    }
+}
+
+/// Return the metadata of `path` without following symlinks, or `None` if it doesn't exist.
+fn metadata(path: &Path) -> Result<Option<gix_index::fs::Metadata>, blob::Error> {
+    match gix_index::fs::Metadata::from_path_no_follow(path) {
+        Ok(md) => Ok(Some(md)),
+        Err(_) if path.parent().map_or(false, |parent| !parent.is_dir()) => Ok(None),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(blob::Error::ReadFile {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
+
+/// Return the mode for a file with metadata `md`, keeping the mode of its `previous` entry if the filesystem
+/// as described by `capabilities` can't represent it.
+fn mode_from_fs(previous: Option<Mode>, md: &gix_index::fs::Metadata, capabilities: &gix_fs::Capabilities) -> Mode {
+    if (md.is_symlink() && capabilities.symlink) || (!capabilities.symlink && previous == Some(Mode::SYMLINK)) {
+        return Mode::SYMLINK;
+    }
+    let is_executable = if capabilities.executable_bit {
+        md.is_executable()
+    } else {
+        previous == Some(Mode::FILE_EXECUTABLE)
+    };
+    if is_executable {
+        Mode::FILE_EXECUTABLE
+    } else {
+        Mode::FILE
+    }
+}
@@ -95,6 +95,8 @@
 pub use gix_actor as actor;
 #[cfg(feature = "attributes")]
 pub use gix_attributes as attrs;
+#[cfg(feature = "blame")]
+pub use gix_blame as blame;
 #[cfg(feature = "command")]
 pub use gix_command as command;
 pub use gix_commitgraph as commitgraph;
@@ -110,6 +112,7 @@ pub use gix_features::{
     progress::{Count, DynNestedProgress, NestedProgress, Progress},
     threading,
 };
+pub use gix_fetchhead as fetchhead;
 pub use gix_fs as fs;
 pub use gix_glob as glob;
 pub use gix_hash as hash;
@@ -119,6 +122,8 @@ pub use gix_ignore as ignore;
 #[doc(inline)]
 #[cfg(feature = "index")]
 pub use gix_index as index;
+#[cfg(feature = "lfs")]
+pub use gix_lfs as lfs;
 pub use gix_lock as lock;
 #[cfg(feature = "credentials")]
 pub use gix_negotiate as negotiate;
@@ -133,6 +138,8 @@ pub use gix_ref as refs;
 pub use gix_refspec as refspec;
 pub use gix_revwalk as revwalk;
 pub use gix_sec as sec;
+#[cfg(feature = "rebase")]
+pub use gix_sequencer as sequencer;
 pub use gix_tempfile as tempfile;
 pub use gix_trace as trace;
 pub use gix_traverse as traverse;
@@ -294,6 +301,16 @@ pub fn open_opts(directory: impl Into<std::path::PathBuf>, options: open::Option
     ThreadSafeRepository::open_opts(directory, options).map(Into::into)
 }
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "bundle")]
+pub mod bundle;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "pack-objects")]
+pub mod pack_objects;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod create;
@@ -310,6 +327,40 @@ pub mod config;
 #[allow(clippy::empty_docs)]
 #[cfg(feature = "mailmap")]
 pub mod mailmap;
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "merge")]
+pub mod merge;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "notes")]
+pub mod note;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "rebase")]
+pub mod rebase;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "stash")]
+pub mod stash;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "sparse-checkout")]
+pub mod sparse_checkout;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "index-mutation")]
+pub mod index_mutation;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "signing")]
+pub mod signing;
 
 ///
 #[allow(clippy::empty_docs)]
@@ -324,6 +375,11 @@ pub mod filter;
 #[allow(clippy::empty_docs)]
 pub mod remote;
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blocking-network-server")]
+pub mod upload_pack;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod init;
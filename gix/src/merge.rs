@@ -0,0 +1 @@
+pub use gix_merge::*;
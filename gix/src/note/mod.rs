@@ -0,0 +1,435 @@
+//!
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, FullNameRef, Target,
+};
+
+use crate::{commit, ext::ObjectIdExt, Blob, Id, Repository};
+
+pub use gix_note::{combine, Notes};
+
+/// The notes reference used if `core.notesRef` isn't set.
+pub const DEFAULT_REF: &str = "refs/notes/commits";
+
+/// A platform to read and modify the notes stored in a single notes reference, like `refs/notes/commits`.
+///
+/// Obtain it with [`Repository::notes()`] or [`Repository::notes_with_ref()`].
+#[derive(Clone)]
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) name: FullName,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init {
+    use crate::bstr::BString;
+
+    /// The error returned by [Repository::notes()](crate::Repository::notes()) and
+    /// [Repository::notes_with_ref()](crate::Repository::notes_with_ref()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The notes reference name '{name}' is invalid")]
+        InvalidName {
+            name: BString,
+            source: gix_validate::reference::name::Error,
+        },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod find {
+    /// The error returned by [Platform::notes()](super::Platform::notes()) and [Platform::find()](super::Platform::find()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        ObjectKind(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        FromTree(#[from] gix_note::from_tree::Error),
+        #[error(transparent)]
+        FindNote(#[from] gix_note::find::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod edit {
+    use gix_hash::ObjectId;
+
+    /// The error returned by the methods of [Platform](super::Platform) that change notes, like [add()](super::Platform::add()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Object {target} already has a note, and overwriting it wasn't allowed")]
+        NoteExists { target: ObjectId },
+        #[error("Object {target} has no note")]
+        NoteMissing { target: ObjectId },
+        #[error(transparent)]
+        Find(#[from] super::find::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        WriteTree(#[from] gix_note::write::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge {
+    use gix_hash::ObjectId;
+    use gix_ref::FullName;
+
+    pub use gix_note::merge::{Conflict, Strategy};
+
+    /// The error returned by [Platform::merge()](super::Platform::merge()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The notes reference '{}' to merge doesn't exist", name.as_bstr())]
+        MissingReference { name: FullName },
+        #[error(transparent)]
+        Find(#[from] super::find::Error),
+        #[error(transparent)]
+        MergeStrategy(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error(transparent)]
+        Merge(#[from] gix_note::merge::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        WriteTree(#[from] gix_note::write::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+    }
+
+    /// The result of [Platform::merge()](super::Platform::merge()).
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Outcome {
+        /// All notes of the other reference were already merged, nothing was changed.
+        UpToDate,
+        /// Our notes were contained in the notes of the other reference, which is why our notes reference now points
+        /// to their notes `commit`.
+        FastForward {
+            /// The notes commit our notes reference now points to.
+            commit: ObjectId,
+        },
+        /// Both notes were merged, and the merged notes were committed as merge `commit`.
+        Merged {
+            /// The newly created merge commit.
+            commit: ObjectId,
+        },
+        /// Some notes were changed differently on both sides and couldn't be resolved with [`Strategy::Manual`],
+        /// so nothing was committed.
+        ///
+        /// `outcome` contains all merged notes, with our notes in place of the conflicting ones.
+        Conflicted {
+            /// The merged notes along with the conflicts.
+            outcome: gix_note::merge::Outcome,
+        },
+    }
+}
+
+/// Access
+impl<'repo> Platform<'repo> {
+    /// Return the name of the notes reference, like `refs/notes/commits`.
+    pub fn reference_name(&self) -> &FullNameRef {
+        self.name.as_ref()
+    }
+
+    /// Return the id of the commit the notes reference points to, or `None` if it doesn't exist yet.
+    pub fn commit_id(&self) -> Result<Option<Id<'repo>>, find::Error> {
+        commit_id(self.repo, self.name.as_ref())
+    }
+
+    /// Return all notes, which are empty if the notes reference doesn't exist yet.
+    pub fn notes(&self) -> Result<Notes, find::Error> {
+        notes_at(self.repo, self.commit_id()?)
+    }
+
+    /// Return the note attached to `target`, or `None` if there is none.
+    pub fn find(&self, target: impl Into<ObjectId>) -> Result<Option<Blob<'repo>>, find::Error> {
+        let target = target.into();
+        let Some(commit) = self.commit_id()? else {
+            return Ok(None);
+        };
+        let tree = commit.object()?.peel_to_tree()?.id;
+        Ok(match gix_note::find(&tree, &target, &self.repo.objects)? {
+            Some(note) => Some(self.repo.find_object(note)?.try_into_blob()?),
+            None => None,
+        })
+    }
+}
+
+/// Modification
+impl<'repo> Platform<'repo> {
+    /// Attach `note` to `target` and commit the change, similar to `git notes add`, returning the new notes commit.
+    ///
+    /// If `target` already has a note, it is replaced if `overwrite` is `true`, or an error is returned otherwise.
+    pub fn add(
+        &self,
+        target: impl Into<ObjectId>,
+        note: impl AsRef<[u8]>,
+        overwrite: bool,
+    ) -> Result<Id<'repo>, edit::Error> {
+        let target = target.into();
+        let parent = self.commit_id()?;
+        let mut notes = notes_at(self.repo, parent)?;
+        if notes.contains(&target) && !overwrite {
+            return Err(edit::Error::NoteExists { target });
+        }
+        notes.insert(target, self.repo.write_blob(note)?.detach());
+        self.commit(&notes, "Notes added by 'git notes add'", parent)
+    }
+
+    /// Append `note` to the note of `target`, separated by an empty line, and commit the change, similar to `git notes append`.
+    /// If `target` has no note yet, `note` is added as is.
+    ///
+    /// Return the new notes commit.
+    pub fn append(&self, target: impl Into<ObjectId>, note: impl AsRef<[u8]>) -> Result<Id<'repo>, edit::Error> {
+        let target = target.into();
+        let parent = self.commit_id()?;
+        let mut notes = notes_at(self.repo, parent)?;
+        let combined = match notes.get(&target) {
+            Some(existing) => {
+                let existing = self.repo.find_object(*existing)?;
+                combine::concatenate(&existing.data, note.as_ref())
+            }
+            None => note.as_ref().to_owned(),
+        };
+        notes.insert(target, self.repo.write_blob(combined)?.detach());
+        self.commit(&notes, "Notes added by 'git notes append'", parent)
+    }
+
+    /// Attach the note of `from` to `to` as well and commit the change, similar to `git notes copy`, returning the new notes commit.
+    ///
+    /// If `to` already has a note, it is replaced if `overwrite` is `true`, or an error is returned otherwise.
+    pub fn copy(
+        &self,
+        from: impl Into<ObjectId>,
+        to: impl Into<ObjectId>,
+        overwrite: bool,
+    ) -> Result<Id<'repo>, edit::Error> {
+        let (from, to) = (from.into(), to.into());
+        let parent = self.commit_id()?;
+        let mut notes = notes_at(self.repo, parent)?;
+        let note = *notes.get(&from).ok_or(edit::Error::NoteMissing { target: from })?;
+        if notes.contains(&to) && !overwrite {
+            return Err(edit::Error::NoteExists { target: to });
+        }
+        notes.insert(to, note);
+        self.commit(&notes, "Notes added by 'git notes copy'", parent)
+    }
+
+    /// Remove the note of `target` and commit the change, similar to `git notes remove`, returning the new notes commit.
+    ///
+    /// It's an error if `target` has no note.
+    pub fn remove(&self, target: impl Into<ObjectId>) -> Result<Id<'repo>, edit::Error> {
+        let target = target.into();
+        let parent = self.commit_id()?;
+        let mut notes = notes_at(self.repo, parent)?;
+        if notes.remove(&target).is_none() {
+            return Err(edit::Error::NoteMissing { target });
+        }
+        self.commit(&notes, "Notes removed by 'git notes remove'", parent)
+    }
+
+    /// Merge the notes of the notes reference `other` into our notes, similar to `git notes merge`.
+    ///
+    /// Notes that were changed differently on both sides are resolved with `strategy`, or if `None`, the strategy
+    /// configured in `notes.<name>.mergeStrategy` or `notes.mergeStrategy`, with `<name>` being our reference name without
+    /// the `refs/notes/` prefix. It defaults to [`Strategy::Manual`](merge::Strategy::Manual), in which case conflicts are
+    /// returned and nothing is committed.
+    ///
+    /// Merges are committed with our and their notes commit as parents.
+    pub fn merge(
+        &self,
+        other: &FullNameRef,
+        strategy: Option<merge::Strategy>,
+    ) -> Result<merge::Outcome, merge::Error> {
+        let strategy = match strategy {
+            Some(strategy) => strategy,
+            None => self.merge_strategy()?,
+        };
+        let theirs = commit_id(self.repo, other)?
+            .ok_or_else(|| merge::Error::MissingReference { name: other.to_owned() })?
+            .detach();
+        let Some(ours) = self.commit_id()?.map(Id::detach) else {
+            self.set_reference(theirs, PreviousValue::MustNotExist, other)?;
+            return Ok(merge::Outcome::FastForward { commit: theirs });
+        };
+        if ours == theirs {
+            return Ok(merge::Outcome::UpToDate);
+        }
+
+        let base = gix_revision::merge_base(ours, &[theirs], &mut self.repo.revision_graph())?
+            .and_then(|bases| bases.first().copied());
+        if base == Some(theirs) {
+            return Ok(merge::Outcome::UpToDate);
+        }
+        if base == Some(ours) {
+            self.set_reference(theirs, PreviousValue::MustExistAndMatch(Target::Peeled(ours)), other)?;
+            return Ok(merge::Outcome::FastForward { commit: theirs });
+        }
+
+        let base = notes_at(self.repo, base.map(|id| id.attach(self.repo)))?;
+        let outcome = gix_note::merge(
+            &base,
+            &notes_at(self.repo, Some(ours.attach(self.repo)))?,
+            &notes_at(self.repo, Some(theirs.attach(self.repo)))?,
+            strategy,
+            &self.repo.objects,
+            |object| gix_odb::Write::write(&self.repo.objects, object),
+        )?;
+        if !outcome.conflicts.is_empty() {
+            return Ok(merge::Outcome::Conflicted { outcome });
+        }
+        let message = format!("Merged notes from {} into {}", other.as_bstr(), self.name.as_bstr());
+        let tree = outcome
+            .notes
+            .write_tree(|object| gix_odb::Write::write(&self.repo.objects, object))?;
+        let commit = self.write_commit(&message, tree, smallvec::smallvec![ours, theirs])?;
+        Ok(merge::Outcome::Merged { commit })
+    }
+}
+
+/// Utilities
+impl<'repo> Platform<'repo> {
+    fn merge_strategy(&self) -> Result<merge::Strategy, merge::Error> {
+        use crate::{
+            bstr::ByteSlice,
+            config::{cache::util::ApplyLeniency, tree::Notes},
+        };
+
+        let config = &self.repo.config;
+        let name = self.name.as_bstr();
+        let short_name = name.strip_prefix(b"refs/notes/").map_or(name, ByteSlice::as_bstr);
+        let value = match config
+            .resolved
+            .string("notes", Some(short_name), Notes::NAMED_MERGE_STRATEGY.name)
+        {
+            Some(value) => Some((&Notes::NAMED_MERGE_STRATEGY, value)),
+            None => config
+                .resolved
+                .string("notes", None, Notes::MERGE_STRATEGY.name)
+                .map(|value| (&Notes::MERGE_STRATEGY, value)),
+        };
+        Ok(value
+            .map(|(key, value)| key.try_into_merge_strategy(value))
+            .transpose()
+            .with_leniency(config.lenient_config)?
+            .unwrap_or_default())
+    }
+
+    /// Write `notes` as tree and commit it on top of `parent`, the current notes commit, with `message`.
+    fn commit(&self, notes: &Notes, message: &str, parent: Option<Id<'repo>>) -> Result<Id<'repo>, edit::Error> {
+        let tree = notes.write_tree(|object| gix_odb::Write::write(&self.repo.objects, object))?;
+        Ok(self
+            .write_commit(message, tree, parent.map(Id::detach).into_iter().collect())?
+            .attach(self.repo))
+    }
+
+    /// Write a commit with `message`, `tree` and `parents` and point our reference to it, which is expected to point to
+    /// the first parent.
+    fn write_commit(
+        &self,
+        message: &str,
+        tree: ObjectId,
+        parents: smallvec::SmallVec<[ObjectId; 1]>,
+    ) -> Result<ObjectId, commit::Error> {
+        let committer = self.repo.committer().ok_or(commit::Error::CommitterMissing)??;
+        let author = self.repo.author().ok_or(commit::Error::AuthorMissing)??;
+        let commit = gix_object::Commit {
+            message: format!("{message}\n").into(),
+            tree,
+            author: author.into(),
+            committer: committer.into(),
+            encoding: None,
+            parents,
+            extra_headers: Default::default(),
+        };
+        let id = self.repo.write_object(&commit)?.detach();
+        let expected = match commit.parents.first() {
+            Some(previous) => PreviousValue::MustExistAndMatch(Target::Peeled(*previous)),
+            None => PreviousValue::MustNotExist,
+        };
+        self.edit_reference(id, expected, format!("notes: {message}"))?;
+        Ok(id)
+    }
+
+    fn set_reference(&self, id: ObjectId, expected: PreviousValue, other: &FullNameRef) -> Result<(), commit::Error> {
+        let message = format!(
+            "notes: Fast-forward notes from {} into {}",
+            other.as_bstr(),
+            self.name.as_bstr()
+        );
+        self.edit_reference(id, expected, message)
+    }
+
+    fn edit_reference(&self, id: ObjectId, expected: PreviousValue, message: String) -> Result<(), commit::Error> {
+        self.repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: message.into(),
+                },
+                expected,
+                new: Target::Peeled(id),
+            },
+            name: self.name.clone(),
+            deref: false,
+        })?;
+        Ok(())
+    }
+}
+
+fn commit_id<'repo>(repo: &'repo Repository, name: &FullNameRef) -> Result<Option<Id<'repo>>, find::Error> {
+    Ok(match repo.try_find_reference(name)? {
+        Some(mut reference) => Some(reference.peel_to_id_in_place()?),
+        None => None,
+    })
+}
+
+fn notes_at(repo: &Repository, commit: Option<Id<'_>>) -> Result<Notes, find::Error> {
+    Ok(match commit {
+        Some(commit) => {
+            let tree = commit.object()?.peel_to_tree()?.id;
+            Notes::from_tree(&tree, &repo.objects)?
+        }
+        None => Notes::default(),
+    })
+}
+
+/// Turn `name` into a full notes reference name like git does, so `foo` and `notes/foo` become `refs/notes/foo`.
+pub(crate) fn expand_ref_name(name: &crate::bstr::BStr) -> Result<FullName, init::Error> {
+    use crate::bstr::{BString, ByteSlice};
+
+    let full_name: BString = if name.starts_with(b"refs/notes/") {
+        name.to_owned()
+    } else if name.starts_with(b"notes/") {
+        [b"refs/".as_slice(), name.as_bytes()].concat().into()
+    } else {
+        [b"refs/notes/".as_slice(), name.as_bytes()].concat().into()
+    };
+    FullName::try_from(full_name.clone()).map_err(|source| init::Error::InvalidName {
+        name: full_name,
+        source,
+    })
+}
@@ -157,14 +157,71 @@ impl<'repo> Commit<'repo> {
         }
     }
 
+    /// Return the note attached to this commit, similar to what `git log` would display.
+    ///
+    /// The notes reference configured in `core.notesRef`, or `refs/notes/commits` by default, is searched first,
+    /// followed by each of the notes references in `notes.displayRef`, which may be a glob pattern like `refs/notes/*`.
+    /// Return the first note that was found, or `None` if there is none.
+    #[cfg(feature = "notes")]
+    pub fn note(&self) -> Result<Option<crate::Blob<'repo>>, crate::commit::note::Error> {
+        use crate::bstr::ByteSlice;
+        use crate::commit::note::Error;
+
+        let repo = self.repo;
+        if let Some(note) = repo.notes()?.find(self.id)? {
+            return Ok(Some(note));
+        }
+        for name in repo
+            .config
+            .resolved
+            .strings_by_key("notes.displayRef")
+            .unwrap_or_default()
+        {
+            if !name.contains_str("*") && !name.contains_str("?") && !name.contains_str("[") {
+                if let Some(note) = repo.notes_with_ref(name.as_ref())?.find(self.id)? {
+                    return Ok(Some(note));
+                }
+                continue;
+            }
+            for reference in repo.references()?.prefixed("refs/notes/")? {
+                let reference = reference.map_err(Error::ReadReference)?;
+                if !gix_glob::wildmatch(
+                    name.as_ref(),
+                    reference.name().as_bstr(),
+                    gix_glob::wildmatch::Mode::empty(),
+                ) {
+                    continue;
+                }
+                if let Some(note) = repo.notes_with_ref(reference.name().as_bstr())?.find(self.id)? {
+                    return Ok(Some(note));
+                }
+            }
+        }
+        Ok(None)
+    }
+
     /// Extracts the PGP signature and the data that was used to create the signature, or `None` if it wasn't signed.
-    // TODO: make it possible to verify the signature, probably by wrapping `SignedData`. It's quite some work to do it properly.
     pub fn signature(
         &self,
     ) -> Result<Option<(std::borrow::Cow<'_, BStr>, gix_object::commit::SignedData<'_>)>, gix_object::decode::Error>
     {
         gix_object::CommitRefIter::signature(&self.data)
     }
+
+    /// Verify the signature of this commit with the program matching its format, configured as in
+    /// [`Repository::signing_options()`](crate::Repository::signing_options()), or return `None` if it isn't signed.
+    ///
+    /// This is similar to `git verify-commit`, but a bad signature is reported in the returned verification instead of an error.
+    #[cfg(feature = "signing")]
+    pub fn verify_signature(&self) -> Result<Option<crate::signing::Verification>, crate::signing::verify::Error> {
+        let Some((signature, signed_data)) = self.signature()? else {
+            return Ok(None);
+        };
+        self.repo
+            .signing_options()?
+            .verify(signed_data.to_bstring().as_ref(), signature.as_ref())
+            .map(Some)
+    }
 }
 
 impl<'r> std::fmt::Debug for Commit<'r> {
@@ -23,6 +23,18 @@ impl<'repo> Tag<'repo> {
     pub fn tagger(&self) -> Result<Option<gix_actor::SignatureRef<'_>>, gix_object::decode::Error> {
         gix_object::TagRefIter::from_bytes(&self.data).tagger()
     }
+
+    /// Verify the signature of this tag with the program matching its format, configured as in
+    /// [`Repository::signing_options()`](crate::Repository::signing_options()), or return `None` if it isn't signed.
+    ///
+    /// This is similar to `git verify-tag`, but a bad signature is reported in the returned verification instead of an error.
+    #[cfg(feature = "signing")]
+    pub fn verify_signature(&self) -> Result<Option<crate::signing::Verification>, crate::signing::verify::Error> {
+        let Some((signed_data, signature)) = crate::signing::split_tag_signature(&self.data) else {
+            return Ok(None);
+        };
+        self.repo.signing_options()?.verify(signed_data, signature).map(Some)
+    }
 }
 
 /// Remove Lifetime
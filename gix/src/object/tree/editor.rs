@@ -0,0 +1,59 @@
+use gix_hash::ObjectId;
+use gix_object::bstr::{BStr, ByteSlice};
+pub use gix_object::tree::editor::Error;
+
+use crate::{object::tree::EntryKind, Id, Repository};
+
+/// An editor to create new trees from an existing one by inserting, replacing and removing entries by path,
+/// without the need for an index or a worktree.
+///
+/// Obtained with [`Tree::edit()`](crate::Tree::edit()). Only trees along the edited paths are loaded, and only those
+/// are written to the object database with [`write()`](Self::write()).
+pub struct Editor<'repo> {
+    inner: gix_object::tree::Editor<'repo>,
+    repo: &'repo Repository,
+}
+
+/// Lifecycle
+impl<'repo> Editor<'repo> {
+    pub(crate) fn new(root: gix_object::Tree, repo: &'repo Repository) -> Self {
+        Editor {
+            inner: gix_object::tree::Editor::new(root, &repo.objects, repo.object_hash()),
+            repo,
+        }
+    }
+}
+
+/// Mutation
+impl<'repo> Editor<'repo> {
+    /// Insert an entry of `kind` pointing to `id` at the slash-separated `rela_path`, or replace the entry that is already there.
+    ///
+    /// Trees leading up to the entry are created as needed, replacing non-tree entries that are in their way.
+    /// See [`gix_object::tree::Editor::upsert()`] for details.
+    pub fn upsert(
+        &mut self,
+        rela_path: impl AsRef<BStr>,
+        kind: EntryKind,
+        id: impl Into<ObjectId>,
+    ) -> Result<&mut Self, Error> {
+        self.inner.upsert(rela_path.as_ref().split_str("/"), kind, id.into())?;
+        Ok(self)
+    }
+
+    /// Remove the entry at the slash-separated `rela_path` along with everything underneath it, if it exists.
+    ///
+    /// Trees that become empty due to this are removed as well.
+    pub fn remove(&mut self, rela_path: impl AsRef<BStr>) -> Result<&mut Self, Error> {
+        self.inner.remove(rela_path.as_ref().split_str("/"))?;
+        Ok(self)
+    }
+
+    /// Write all edited trees to the object database and return the id of the new root tree.
+    ///
+    /// The editor can be used for further edits afterwards, which are based on the tree that was just written.
+    pub fn write(&mut self) -> Result<Id<'repo>, crate::object::write::Error> {
+        let repo = self.repo;
+        let id = self.inner.write(|tree| repo.write_object(tree).map(Id::detach))?;
+        Ok(Id::from_id(id, repo))
+    }
+}
@@ -24,14 +24,20 @@ impl<'repo> Tree<'repo> {
         Id::from_id(self.id, self.repo)
     }
 
+    /// Return an editor to create a new tree from this one by changing entries by path, which is useful to
+    /// create commits without an index or a worktree.
+    pub fn edit(&self) -> Result<Editor<'repo>, gix_object::decode::Error> {
+        Ok(Editor::new(self.decode()?.into(), self.repo))
+    }
+
     /// Parse our tree data and return the parse tree for direct access to its entries.
     pub fn decode(&self) -> Result<gix_object::TreeRef<'_>, gix_object::decode::Error> {
-        gix_object::TreeRef::from_bytes(&self.data)
+        gix_object::TreeRef::from_bytes(&self.data, self.id.kind())
     }
 
     /// Find the entry named `name` by iteration, or return `None` if it wasn't found.
     pub fn find_entry(&self, name: impl PartialEq<BStr>) -> Option<EntryRef<'repo, '_>> {
-        TreeRefIter::from_bytes(&self.data)
+        TreeRefIter::from_bytes(&self.data, self.id.kind())
             .filter_map(Result::ok)
             .find(|entry| name.eq(entry.filename))
             .map(|entry| EntryRef {
@@ -59,7 +65,7 @@ impl<'repo> Tree<'repo> {
         buf.clear();
         buf.extend_from_slice(&self.data);
         while let Some(component) = path.next() {
-            match TreeRefIter::from_bytes(buf)
+            match TreeRefIter::from_bytes(buf, self.id.kind())
                 .filter_map(Result::ok)
                 .find(|entry| component.eq(entry.filename))
             {
@@ -101,7 +107,7 @@ impl<'repo> Tree<'repo> {
     {
         let mut path = path.into_iter().peekable();
         while let Some(component) = path.next() {
-            match TreeRefIter::from_bytes(&self.data)
+            match TreeRefIter::from_bytes(&self.data, self.id.kind())
                 .filter_map(Result::ok)
                 .find(|entry| component.eq(entry.filename))
             {
@@ -173,6 +179,11 @@ impl<'repo> Tree<'repo> {
 #[cfg(feature = "blob-diff")]
 pub mod diff;
 
+///
+#[allow(clippy::empty_docs)]
+pub mod editor;
+pub use editor::Editor;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod traverse;
@@ -0,0 +1,64 @@
+//! Select the objects another repository lacks and write them into a pack, similar to `git pack-objects`.
+//!
+//! This is used to send packs when pushing and when serving fetches, and to write the pack of bundles.
+///
+#[allow(clippy::empty_docs)]
+pub mod select;
+pub use select::{select, Selection};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
+pub use write::write;
+
+/// The error returned when selecting objects or writing them into a pack.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not prepare the object database for writing the pack")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    LookupCommit(#[from] gix_revwalk::graph::try_lookup_or_insert_default::Error),
+    #[error(transparent)]
+    Find(#[from] gix_object::find::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Config(#[from] crate::config::unsigned_integer::Error),
+    #[error(transparent)]
+    UseBitmapsConfig(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    BlobsWithoutDelta(#[from] crate::repository::pack_blobs_without_delta::Error),
+    #[error(transparent)]
+    CountObjectsWithBitmap(#[from] gix_pack::bitmap::reachable::Error),
+    #[error(transparent)]
+    CountObjects(#[from] gix_pack::data::output::count::objects::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// The progress ids used when writing packs.
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of objects that were counted to be part of the pack.
+    CountObjects,
+    /// The amount of bytes written into the pack.
+    WritePack,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CountObjects => *b"POCO",
+            ProgressId::WritePack => *b"POWP",
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_hash::ObjectId;
+use gix_object::{Find, FindExt};
+
+use super::Error;
+
+const OURS: u8 = 1 << 0;
+const THEIRS: u8 = 1 << 1;
+const DONE: u8 = 1 << 2;
+
+/// A function returning `true` if the blob with the given id should be selected.
+pub type BlobFilter<'a> = dyn Fn(&gix_hash::oid) -> Result<bool, Error> + 'a;
+
+/// Constraints on the objects chosen by [`select()`].
+#[derive(Default, Clone, Copy)]
+pub struct Options<'a> {
+    /// Commits whose parents aren't selected as they are going to be shallow for the receiver.
+    pub shallow: Option<&'a gix_hashtable::HashSet>,
+    /// Commits which are shallow for the receiver, which thus can't be assumed to have their parents.
+    pub receiver_shallow: Option<&'a gix_hashtable::HashSet>,
+    /// If set, blobs for which it returns `false` aren't selected.
+    pub blob_filter: Option<&'a BlobFilter<'a>>,
+    /// If set, it's used to find the objects to select without traversing the commit graph, as long as there are no other
+    /// constraints and it knows all objects reachable from `ours`.
+    ///
+    /// Note that the [`boundary`](Selection::boundary) isn't known then.
+    pub bitmap: Option<&'a gix_pack::bitmap::Index>,
+}
+
+/// The objects another repository lacks, as returned by [`select()`].
+#[derive(Debug, Clone)]
+pub struct Selection {
+    /// The objects to write into the pack.
+    pub objects: Vec<ObjectId>,
+    /// The commits the receiver has which are parents of selected commits, with the most recent commit first.
+    ///
+    /// It's empty if a [`bitmap`](Options::bitmap) was used.
+    pub boundary: Vec<ObjectId>,
+    /// All trees and blobs reachable from the `boundary`, or from the tips of the receiver if a [`bitmap`](Options::bitmap)
+    /// was used, which the receiver is known to have.
+    pub edge: gix_hashtable::HashSet,
+}
+
+/// Return all objects reachable from `ours` that are not reachable from `theirs`, which are typically the tips of the receiver.
+///
+/// Commits are traversed by commit time, and the traversal stops once all commits in the queue are reachable from `theirs`.
+/// Trees and blobs are excluded if they are reachable from the trees of the commits at the boundary, i.e. those that
+/// the receiver has and which are parents of selected commits.
+/// Tags in `theirs` are peeled, and objects in `theirs` that don't exist locally are ignored.
+pub fn select(
+    repo: &crate::Repository,
+    ours: impl IntoIterator<Item = ObjectId>,
+    theirs: impl IntoIterator<Item = ObjectId>,
+    options: Options<'_>,
+    should_interrupt: &AtomicBool,
+) -> Result<Selection, Error> {
+    let (ours, theirs): (Vec<_>, Vec<_>) = (ours.into_iter().collect(), theirs.into_iter().collect());
+    let has_constraints =
+        options.shallow.is_some() || options.receiver_shallow.is_some() || options.blob_filter.is_some();
+    if let Some(bitmap) = options.bitmap.filter(|_| !has_constraints) {
+        if let Some(selection) = select_with_bitmap(repo, bitmap, &ours, &theirs, should_interrupt)? {
+            return Ok(selection);
+        }
+    }
+
+    let is_in = |set: Option<&gix_hashtable::HashSet>, id: &ObjectId| matches!(set, Some(set) if set.contains(id));
+    let mut buf = Vec::new();
+    let mut out = Vec::new();
+    let mut seen = gix_hashtable::HashSet::default();
+    let mut trees = Vec::new();
+    let mut graph = repo.revision_graph::<gix_revwalk::graph::Commit<u8>>();
+    let mut queue = gix_revwalk::PriorityQueue::<gix_date::SecondsSinceUnixEpoch, ObjectId>::new();
+    for mut id in ours {
+        loop {
+            let object = repo.objects.find(&id, &mut buf)?;
+            match object.kind {
+                gix_object::Kind::Tag => {
+                    if seen.insert(id) {
+                        out.push(id);
+                    }
+                    id = object.decode()?.into_tag().expect("tag").target();
+                }
+                gix_object::Kind::Commit => {
+                    if let Some(commit) = graph.try_lookup_or_insert_commit(id, |flags| *flags |= OURS)? {
+                        queue.insert(commit.commit_time, id);
+                    }
+                    break;
+                }
+                gix_object::Kind::Tree => {
+                    trees.push(id);
+                    break;
+                }
+                gix_object::Kind::Blob => {
+                    if seen.insert(id) {
+                        out.push(id);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    for mut id in theirs {
+        while let Some(object) = repo.objects.try_find(&id, &mut buf)? {
+            if object.kind != gix_object::Kind::Tag {
+                break;
+            }
+            id = object.decode()?.into_tag().expect("tag").target();
+        }
+        if let Some(commit) = graph.try_lookup_or_insert_commit(id, |flags| *flags |= THEIRS)? {
+            queue.insert(commit.commit_time, id);
+        }
+    }
+
+    while queue
+        .iter_unordered()
+        .any(|id| matches!(graph.get(id), Some(commit) if commit.data & THEIRS == 0))
+    {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        let id = queue.pop_value().expect("non-empty as checked by loop condition");
+        let commit = graph.get_mut(&id).expect("queued commits are in graph");
+        if commit.data & DONE != 0 {
+            continue;
+        }
+        commit.data |= DONE;
+        let mut flags = commit.data & (OURS | THEIRS);
+        if is_in(options.shallow, &id) {
+            flags &= !OURS;
+        }
+        if is_in(options.receiver_shallow, &id) {
+            flags &= !THEIRS;
+        }
+        if flags == 0 {
+            continue;
+        }
+        for parent_id in commit.parents.clone() {
+            if let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |parent_flags| *parent_flags |= flags)? {
+                if parent.data & DONE == 0 {
+                    queue.insert(parent.commit_time, parent_id);
+                }
+            }
+        }
+    }
+
+    let graph = graph.detach();
+    let mut commits: Vec<_> = graph
+        .iter()
+        .filter_map(|(id, commit)| (commit.data & (OURS | THEIRS) == OURS).then_some(*id))
+        .collect();
+    commits.sort_by(|lhs, rhs| {
+        graph[rhs]
+            .commit_time
+            .cmp(&graph[lhs].commit_time)
+            .then_with(|| lhs.cmp(rhs))
+    });
+    let (mut boundary, mut in_boundary) = (Vec::new(), gix_hashtable::HashSet::default());
+    for id in &commits {
+        for parent_id in &graph[id].parents {
+            if matches!(graph.get(parent_id), Some(parent) if parent.data & THEIRS != 0)
+                && in_boundary.insert(*parent_id)
+            {
+                boundary.push(*parent_id);
+            }
+        }
+    }
+    let mut edge = gix_hashtable::HashSet::default();
+    for id in &boundary {
+        let tree_id = repo.objects.find_commit(id, &mut buf)?.tree();
+        collect_tree(repo, tree_id, &mut edge, None, options, &mut buf, should_interrupt)?;
+    }
+    seen.extend(edge.iter().copied());
+    for id in commits {
+        out.push(id);
+        let tree_id = repo.objects.find_commit(&id, &mut buf)?.tree();
+        trees.push(tree_id);
+    }
+    for tree_id in trees {
+        collect_tree(
+            repo,
+            tree_id,
+            &mut seen,
+            Some(&mut out),
+            options,
+            &mut buf,
+            should_interrupt,
+        )?;
+    }
+    Ok(Selection {
+        objects: out,
+        boundary,
+        edge,
+    })
+}
+
+/// Select all objects reachable from `ours` that are not reachable from `theirs` using `bitmap`, or return `None` if
+/// it doesn't know all of them.
+fn select_with_bitmap(
+    repo: &crate::Repository,
+    bitmap: &gix_pack::bitmap::Index,
+    ours: &[ObjectId],
+    theirs: &[ObjectId],
+    should_interrupt: &AtomicBool,
+) -> Result<Option<Selection>, Error> {
+    let Some((counts, _outcome)) = gix_pack::data::output::count::objects_with_bitmap(
+        bitmap,
+        ours.iter().copied(),
+        theirs.iter().copied(),
+        &repo.objects,
+    )?
+    else {
+        return Ok(None);
+    };
+    let mut buf = Vec::new();
+    let mut edge = gix_hashtable::HashSet::default();
+    for mut id in theirs.iter().copied() {
+        let tree_id = loop {
+            let Some(object) = repo.objects.try_find(&id, &mut buf)? else {
+                break None;
+            };
+            match object.kind {
+                gix_object::Kind::Tag => id = object.decode()?.into_tag().expect("tag").target(),
+                gix_object::Kind::Commit => break Some(object.decode()?.into_commit().expect("commit").tree()),
+                gix_object::Kind::Tree => break Some(id),
+                gix_object::Kind::Blob => break None,
+            }
+        };
+        if let Some(tree_id) = tree_id {
+            collect_tree(
+                repo,
+                tree_id,
+                &mut edge,
+                None,
+                Options::default(),
+                &mut buf,
+                should_interrupt,
+            )?;
+        }
+    }
+    Ok(Some(Selection {
+        objects: counts.into_iter().map(|count| count.id).collect(),
+        boundary: Vec::new(),
+        edge,
+    }))
+}
+
+/// Insert `tree_id` and all trees and blobs reachable from it into `seen`, and add newly seen ones to `out` if set,
+/// unless they are blobs that don't pass the filter of `options`.
+fn collect_tree(
+    repo: &crate::Repository,
+    tree_id: ObjectId,
+    seen: &mut gix_hashtable::HashSet<ObjectId>,
+    mut out: Option<&mut Vec<ObjectId>>,
+    options: Options<'_>,
+    buf: &mut Vec<u8>,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let mut stack = vec![tree_id];
+    while let Some(tree_id) = stack.pop() {
+        if !seen.insert(tree_id) {
+            continue;
+        }
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if let Some(out) = out.as_mut() {
+            out.push(tree_id);
+        }
+        for entry in repo.objects.find_tree(&tree_id, buf)?.entries {
+            let id = entry.oid.to_owned();
+            if entry.mode.is_tree() {
+                stack.push(id);
+            } else if entry.mode.is_blob_or_symlink() && seen.insert(id) {
+                if let Some(out) = out.as_mut() {
+                    let passes_filter = match options.blob_filter {
+                        Some(filter) => filter(&id)?,
+                        None => true,
+                    };
+                    if passes_filter {
+                        out.push(id);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_features::progress::{Count, DynNestedProgress, NestedProgress, Progress};
+use gix_object::Find;
+use gix_pack::data::output;
+
+use super::{Error, ProgressId, Selection};
+
+/// Options for [`write()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// If `true`, deltas may refer to bases in the [`edge`](Selection::edge) of the selection which aren't part of the pack,
+    /// as the receiver is known to have them.
+    pub thin_pack: bool,
+    /// If `true`, deltas refer to bases in the pack by offset, otherwise they refer to them by id, for receivers
+    /// which don't support the `ofs-delta` capability.
+    pub ofs_delta: bool,
+}
+
+/// Write a pack with all objects of `selection` into `out`, and return the amount of objects in the pack.
+///
+/// Objects are delta-compressed as configured by `pack.window` and `pack.depth`, unless they are blobs with the `delta`
+/// attribute unset, using up to `pack.threads` threads.
+pub fn write<P>(
+    repo: &crate::Repository,
+    selection: Selection,
+    out: &mut dyn std::io::Write,
+    options: Options,
+    progress: &mut P,
+    should_interrupt: &AtomicBool,
+) -> Result<usize, Error>
+where
+    P: NestedProgress,
+    P::SubProgress: 'static,
+{
+    let Selection { objects, edge, .. } = selection;
+    let mut handle = repo.objects.clone().into_arc()?;
+    handle.prevent_pack_unload();
+    let counts = {
+        let mut progress = progress.add_child_with_id("counting objects".to_string(), ProgressId::CountObjects.into());
+        progress.init(None, gix_features::progress::count("objects"));
+        let (mut counts, _outcome) = output::count::objects_unthreaded(
+            &handle,
+            &mut objects.into_iter().map(Ok),
+            &progress,
+            should_interrupt,
+            output::count::objects::ObjectExpansion::AsIs,
+        )?;
+        counts.shrink_to_fit();
+        let blobs_without_delta = repo.pack_blobs_without_delta()?;
+        for count in &mut counts {
+            count.no_delta = blobs_without_delta.contains(&count.id);
+        }
+        counts
+    };
+
+    let num_objects = counts.len();
+    let entries = gix_features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        handle,
+        Box::new(progress.add_child("creating entries")) as Box<dyn DynNestedProgress>,
+        output::entry::iter_from_counts::Options {
+            thread_limit: repo.pack_thread_limit()?,
+            mode: repo.pack_delta_compression()?,
+            allow_thin_pack: options.thin_pack,
+            chunk_size: 1000,
+            version: Default::default(),
+        },
+    ));
+
+    let mut buf = Vec::new();
+    let mut ids_by_position = Vec::new();
+    let entries = entries.map(|entries| {
+        let mut entries = entries?;
+        for entry in &mut entries {
+            match entry.kind {
+                output::entry::Kind::DeltaOid { id } if !edge.contains(&id) => {
+                    // The receiver isn't known to have the base, so the object is sent as a whole.
+                    let count = output::Count {
+                        id: entry.id,
+                        entry_pack_location: output::count::PackLocation::NotLookedUp,
+                        no_delta: true,
+                    };
+                    *entry = match repo
+                        .objects
+                        .try_find(&entry.id, &mut buf)
+                        .map_err(output::entry::iter_from_counts::Error::Find)?
+                    {
+                        Some(object) => output::Entry::from_data(&count, &object)?,
+                        None => output::Entry::invalid(),
+                    };
+                }
+                output::entry::Kind::DeltaRef { object_index } if !options.ofs_delta => {
+                    entry.kind = output::entry::Kind::DeltaOid {
+                        id: ids_by_position[object_index],
+                    };
+                }
+                _ => {}
+            }
+            if !options.ofs_delta {
+                ids_by_position.push(entry.id);
+            }
+        }
+        Ok(entries)
+    });
+
+    let mut write_progress = progress.add_child_with_id("writing pack".to_string(), ProgressId::WritePack.into());
+    write_progress.init(None, gix_features::progress::bytes());
+    for written in output::bytes::FromEntriesIter::new(
+        entries,
+        out,
+        num_objects as u32,
+        gix_pack::data::Version::default(),
+        repo.object_hash(),
+    ) {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        write_progress.inc_by(written? as usize);
+    }
+    Ok(num_objects)
+}
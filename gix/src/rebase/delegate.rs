@@ -0,0 +1,118 @@
+use gix_hash::ObjectId;
+use gix_rebase::DelegateError;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BStr, BString},
+    rebase::{run, LABEL_PREFIX},
+    worktree, Repository,
+};
+
+/// Carry out all operations of a rebase that need the repository.
+pub(crate) struct Delegate<'a> {
+    repo: &'a Repository,
+    edit_message: &'a mut dyn FnMut(&BStr) -> BString,
+    tree_merge_options: gix_merge::tree::Options,
+}
+
+impl<'a> Delegate<'a> {
+    pub(crate) fn new(
+        repo: &'a Repository,
+        edit_message: &'a mut dyn FnMut(&BStr) -> BString,
+    ) -> Result<Self, run::Error> {
+        Ok(Delegate {
+            repo,
+            edit_message,
+            tree_merge_options: repo.tree_merge_options()?,
+        })
+    }
+}
+
+impl gix_rebase::Delegate for Delegate<'_> {
+    fn merge_trees(
+        &mut self,
+        ancestor: &gix_hash::oid,
+        ours: &gix_hash::oid,
+        theirs: &gix_hash::oid,
+        labels: gix_merge::blob::Labels<'_>,
+    ) -> Result<gix_merge::tree::Outcome, DelegateError> {
+        Ok(self
+            .repo
+            .merge_trees(ancestor, ours, theirs, labels, self.tree_merge_options)?)
+    }
+
+    fn merge_commits(
+        &mut self,
+        ours: ObjectId,
+        theirs: ObjectId,
+        labels: gix_merge::blob::Labels<'_>,
+    ) -> Result<gix_merge::tree::Outcome, DelegateError> {
+        Ok(self
+            .repo
+            .merge_commits(ours, theirs, labels, self.tree_merge_options)?
+            .tree_merge)
+    }
+
+    fn edit_message(&mut self, message: &BStr) -> Result<BString, DelegateError> {
+        Ok((self.edit_message)(message))
+    }
+
+    fn exec(&mut self, command: &BStr, head: ObjectId) -> Result<(bool, ObjectId), DelegateError> {
+        let workdir = self.repo.work_dir().ok_or(worktree::checkout::Error::BareRepository)?;
+        worktree::tree::checkout(self.repo, self.repo.find_object(head)?.peel_to_tree()?.id, &[])?;
+        self.repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("rebase (exec): {command}").into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(head),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+
+        let mut cmd: std::process::Command = gix_command::prepare(gix_path::from_bstr(command).into_owned())
+            .with_shell()
+            .into();
+        let status = cmd.current_dir(workdir).status()?;
+        Ok((status.success(), self.repo.head_id()?.detach()))
+    }
+
+    fn set_label(&mut self, name: &BStr, commit: ObjectId) -> Result<(), DelegateError> {
+        self.repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("rebase (label) '{name}'").into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(commit),
+            },
+            name: format!("{LABEL_PREFIX}{name}").try_into()?,
+            deref: false,
+        })?;
+        Ok(())
+    }
+
+    fn resolve_label(&mut self, name: &BStr) -> Result<Option<ObjectId>, DelegateError> {
+        let label = format!("{LABEL_PREFIX}{name}");
+        if let Some(mut reference) = self.repo.try_find_reference(label.as_str())? {
+            return Ok(Some(reference.peel_to_id_in_place()?.detach()));
+        }
+        Ok(self.repo.rev_parse_single(name).ok().map(crate::Id::detach))
+    }
+
+    #[cfg_attr(not(feature = "signing"), allow(unused_variables))]
+    fn sign_commit(&mut self, commit: &mut gix_object::Commit) -> Result<(), DelegateError> {
+        #[cfg(feature = "signing")]
+        self.repo.sign_commit_if_enabled(commit)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+//!
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+pub use gix_rebase::{todo::Commit, Outcome, Stop};
+pub use gix_sequencer::{
+    rebase::{State, UpdateRef},
+    todo,
+};
+
+mod delegate;
+
+/// The prefix of the references that hold the labels created by the `label` instruction.
+const LABEL_PREFIX: &str = "refs/rewritten/";
+
+/// Options for use in [`Repository::rebase()`], typically obtained with [`Repository::rebase_options()`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The commit to rebase onto, or `None` to rebase onto the upstream commit, similar to `git rebase --onto`.
+    pub onto: Option<ObjectId>,
+    /// If `true`, commits created with `git commit --fixup` or `git commit --squash` are moved after the commit they refer to
+    /// and melded into it, similar to `git rebase --autosquash`.
+    pub autosquash: bool,
+    /// If `true`, local branches pointing to one of the rebased commits are updated to point to the rewritten commit,
+    /// similar to `git rebase --update-refs`.
+    pub update_refs: bool,
+    /// If `true`, always rewrite commits even if they could be reused as is, similar to `git rebase --force-rebase`.
+    pub force: bool,
+}
+
+/// A rebase that was prepared with [`Repository::rebase()`], but that isn't started yet.
+///
+/// Its todo list can still be adjusted, similar to what `git rebase --interactive` allows.
+pub struct Prepare<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) state: State,
+    pub(crate) force: bool,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init {
+    /// The error returned by [Repository::rebase()](crate::Repository::rebase()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Another operation is in progress: {state:?}")]
+        InProgress { state: crate::state::InProgress },
+        #[error("Cannot rebase with uncommitted changes")]
+        UncommittedChanges,
+        #[error("Cannot rebase without a commit checked out")]
+        UnbornHead,
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::status::is_dirty::Error),
+        #[error(transparent)]
+        Index(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::worktree::write_tree::Error),
+        #[error(transparent)]
+        RevWalk(#[from] crate::revision::walk::Error),
+        #[error(transparent)]
+        Traverse(#[from] gix_traverse::commit::simple::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        ReferenceIter(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ReferenceIterInit(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        ObtainRefDuringIteration(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod status {
+    /// The error returned by [Repository::rebase_status()](crate::Repository::rebase_status()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ReadState(#[from] gix_sequencer::rebase::read::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod run {
+    use crate::bstr::BString;
+
+    /// The error returned by [Prepare::start()](super::Prepare::start()) and
+    /// [Repository::rebase_continue()](crate::Repository::rebase_continue()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("No rebase is in progress")]
+        NotInProgress,
+        #[error("The path '{path}' still has conflicts which have to be resolved and added to the index first")]
+        UnresolvedConflict { path: BString },
+        #[error("There are changes in the worktree which have to be added to the index first")]
+        UnstagedChanges,
+        #[error(transparent)]
+        Status(#[from] super::status::Error),
+        #[error(transparent)]
+        HeadId(#[from] crate::reference::head_id::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::status::is_dirty::Error),
+        #[error(transparent)]
+        Index(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::worktree::write_tree::Error),
+        #[error(transparent)]
+        Checkout(#[from] crate::worktree::checkout::Error),
+        #[error(transparent)]
+        TreeMergeOptions(#[from] crate::repository::merge::tree_merge_options::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Rebase(#[from] gix_rebase::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        CommitterTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        ReferenceIter(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ReferenceIterInit(#[from] crate::reference::iter::init::Error),
+        #[error("Could not write the state of the rebase")]
+        WriteState(#[source] std::io::Error),
+        #[error("Could not remove the state of the rebase")]
+        RemoveState(#[source] std::io::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod abort {
+    /// The error returned by [Repository::rebase_abort()](crate::Repository::rebase_abort()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("No rebase is in progress")]
+        NotInProgress,
+        #[error(transparent)]
+        Status(#[from] super::status::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Checkout(#[from] crate::worktree::checkout::Error),
+        #[error(transparent)]
+        Finish(#[from] super::run::Error),
+    }
+}
+
+/// Access
+impl Prepare<'_> {
+    /// Return the state of the rebase to start.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Return the todo list for modification, which marks the rebase as interactive.
+    pub fn todo_mut(&mut self) -> &mut todo::List {
+        self.state.interactive = true;
+        &mut self.state.todo
+    }
+}
+
+/// Execution
+impl Prepare<'_> {
+    /// Check out the commit to rebase onto and carry out all instructions of the todo list until the rebase finishes
+    /// or stops.
+    ///
+    /// `edit_message` is called with the message of each commit that should be edited, like with `reword` or `squash`,
+    /// and returns the edited message.
+    ///
+    /// If the rebase stops, its [state](State) is written into the `.git/rebase-merge` directory, and the worktree
+    /// and index reflect the current commit, or the conflicts to resolve. Use [`Repository::rebase_continue()`] to
+    /// resume or [`Repository::rebase_abort()`] to abort the rebase.
+    pub fn start(self, edit_message: &mut dyn FnMut(&BStr) -> BString) -> Result<Outcome, run::Error> {
+        let Prepare { repo, state, force } = self;
+        let mut edits = vec![ref_edit(
+            "ORIG_HEAD".try_into().expect("valid"),
+            Target::Peeled(state.orig_head),
+            RefLog::AndReference,
+            "rebase (start)".into(),
+        )];
+        edits.push(detach_head(
+            state.onto,
+            format!("rebase (start): checkout {}", state.onto).into(),
+        ));
+        crate::worktree::tree::checkout(repo, tree_of(repo, state.onto)?, &[])?;
+        repo.edit_references(edits).map_err(crate::commit::Error::from)?;
+        let onto = state.onto;
+        drive(repo, state, onto, None, force, edit_message)
+    }
+}
+
+fn tree_of(repo: &Repository, commit: ObjectId) -> Result<ObjectId, run::Error> {
+    Ok(repo.find_object(commit)?.peel_to_tree()?.id)
+}
+
+/// Run the rebase in `state` starting at `head`, and update `HEAD`, the worktree and the index accordingly.
+pub(crate) fn drive(
+    repo: &Repository,
+    mut state: State,
+    head: ObjectId,
+    resolved_tree: Option<ObjectId>,
+    force: bool,
+    edit_message: &mut dyn FnMut(&BStr) -> BString,
+) -> Result<Outcome, run::Error> {
+    let committer = repo.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+    let mut delegate = delegate::Delegate::new(repo, edit_message)?;
+    let outcome = gix_rebase::run(
+        &mut state,
+        head,
+        resolved_tree,
+        &repo.objects,
+        |object| gix_odb::Write::write(&repo.objects, object),
+        &mut delegate,
+        gix_rebase::Options { committer, force },
+    );
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            state.write_to_dir(&state_dir(repo)).map_err(run::Error::WriteState)?;
+            return Err(err.into());
+        }
+    };
+
+    match &outcome {
+        Outcome::Finished { head } => {
+            crate::worktree::tree::checkout(repo, tree_of(repo, *head)?, &[])?;
+            finish(repo, &state, *head)?;
+        }
+        Outcome::Stopped { head, reason } => {
+            match reason {
+                Stop::Conflict { merge } => crate::worktree::tree::checkout(repo, merge.tree, &merge.conflicts)?,
+                _ => crate::worktree::tree::checkout(repo, tree_of(repo, *head)?, &[])?,
+            };
+            let mut edits = vec![detach_head(*head, reflog_message(&state))];
+            if let Some(stopped_at) = state.stopped_at {
+                edits.push(ref_edit(
+                    "REBASE_HEAD".try_into().expect("valid"),
+                    Target::Peeled(stopped_at),
+                    RefLog::AndReference,
+                    "rebase".into(),
+                ));
+            }
+            repo.edit_references(edits).map_err(crate::commit::Error::from)?;
+            state.write_to_dir(&state_dir(repo)).map_err(run::Error::WriteState)?;
+        }
+    }
+    Ok(outcome)
+}
+
+/// Update the rebased branch and all references to update to their rewritten commits, put `HEAD` back onto the branch,
+/// and remove all state of the rebase.
+fn finish(repo: &Repository, state: &State, head: ObjectId) -> Result<(), run::Error> {
+    let mut edits = Vec::new();
+    let head_name = state
+        .head_name
+        .as_ref()
+        .and_then(|name| FullName::try_from(name.as_bstr()).ok());
+    match &head_name {
+        Some(name) => {
+            edits.push(ref_edit(
+                name.clone(),
+                Target::Peeled(head),
+                RefLog::AndReference,
+                format!("rebase (finish): {} onto {}", name.as_bstr(), state.onto).into(),
+            ));
+            edits.push(ref_edit(
+                "HEAD".try_into().expect("valid"),
+                Target::Symbolic(name.clone()),
+                RefLog::AndReference,
+                format!("rebase (finish): returning to {}", name.as_bstr()).into(),
+            ));
+        }
+        None => edits.push(detach_head(head, "rebase (finish)".into())),
+    }
+    for update in state.update_refs.iter().filter(|update| !update.after.is_null()) {
+        if let Ok(name) = FullName::try_from(update.name.as_bstr()) {
+            edits.push(ref_edit(
+                name,
+                Target::Peeled(update.after),
+                RefLog::AndReference,
+                "rebase (update-refs)".into(),
+            ));
+        }
+    }
+    repo.edit_references(edits).map_err(crate::commit::Error::from)?;
+    remove_state(repo)
+}
+
+/// Put `HEAD` back to where it was before the rebase started and remove all state of the rebase.
+pub(crate) fn abort(repo: &Repository, state: &State) -> Result<(), run::Error> {
+    let head_name = state
+        .head_name
+        .as_ref()
+        .and_then(|name| FullName::try_from(name.as_bstr()).ok());
+    let edit = match head_name {
+        Some(name) => ref_edit(
+            "HEAD".try_into().expect("valid"),
+            Target::Symbolic(name.clone()),
+            RefLog::AndReference,
+            format!("rebase (abort): returning to {}", name.as_bstr()).into(),
+        ),
+        None => detach_head(
+            state.orig_head,
+            format!("rebase (abort): returning to {}", state.orig_head).into(),
+        ),
+    };
+    repo.edit_reference(edit).map_err(crate::commit::Error::from)?;
+    remove_state(repo)
+}
+
+/// Remove the state directory along with all labels and `REBASE_HEAD`.
+pub(crate) fn remove_state(repo: &Repository) -> Result<(), run::Error> {
+    let mut edits: Vec<_> = repo
+        .references()?
+        .prefixed(LABEL_PREFIX)?
+        .filter_map(Result::ok)
+        .map(|label| label.detach().name)
+        .chain(
+            repo.try_find_reference("REBASE_HEAD")
+                .ok()
+                .flatten()
+                .map(|r| r.detach().name),
+        )
+        .map(|name| RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name,
+            deref: false,
+        })
+        .collect();
+    edits.dedup_by(|a, b| a.name == b.name);
+    repo.edit_references(edits).map_err(crate::commit::Error::from)?;
+    match std::fs::remove_dir_all(state_dir(repo)) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(run::Error::RemoveState(err)),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn state_dir(repo: &Repository) -> std::path::PathBuf {
+    repo.path().join(gix_sequencer::rebase::DIR_NAME)
+}
+
+fn detach_head(head: ObjectId, message: BString) -> RefEdit {
+    ref_edit(
+        "HEAD".try_into().expect("valid"),
+        Target::Peeled(head),
+        RefLog::AndReference,
+        message,
+    )
+}
+
+fn ref_edit(name: FullName, new: Target, mode: RefLog, message: BString) -> RefEdit {
+    RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode,
+                force_create_reflog: false,
+                message,
+            },
+            expected: PreviousValue::Any,
+            new,
+        },
+        name,
+        deref: false,
+    }
+}
+
+/// Produce a reflog message like `git` does for the instruction the rebase stopped at.
+fn reflog_message(state: &State) -> BString {
+    match state.current() {
+        Some(instruction) => {
+            let summary = match instruction {
+                todo::Instruction::Pick { summary, .. }
+                | todo::Instruction::Reword { summary, .. }
+                | todo::Instruction::Edit { summary, .. }
+                | todo::Instruction::Squash { summary, .. }
+                | todo::Instruction::Fixup { summary, .. } => summary.clone(),
+                todo::Instruction::Exec { command } => command.clone(),
+                _ => BString::default(),
+            };
+            format!(
+                "rebase ({}): {}",
+                instruction.command_name().unwrap_or_default(),
+                summary
+            )
+            .trim_end()
+            .into()
+        }
+        None => "rebase".into(),
+    }
+}
@@ -34,6 +34,15 @@ mod error {
             source: Box<gix_discover::is_git::Error>,
             url: gix_url::Url,
         },
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        #[error(transparent)]
+        OpenBundle(#[from] gix_bundle::file::Error),
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        #[error(transparent)]
+        VerifyBundle(#[from] crate::bundle::verify::Error),
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        #[error("Cannot push to the bundle at {}", url.to_bstring())]
+        PushToBundle { url: gix_url::Url },
     }
 
     impl gix_protocol::transport::IsSpuriousError for Error {
@@ -76,6 +85,10 @@ impl<'repo> Remote<'repo> {
     /// The transport used for connection can be configured via `transport_mut().configure()` assuming the actually
     /// used transport is well known. If that's not the case, the transport can be created by hand and passed to
     /// [to_connection_with_transport()][Self::to_connection_with_transport()].
+    ///
+    /// With the `bundle` feature, `file://` urls or paths to bundle files connect to an in-process server that provides
+    /// the references and the pack of the bundle, once it was verified that the repository has all of its prerequisites.
+    /// Bundles can't be pushed to.
     #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-async-std"))]
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn connect(
@@ -83,6 +96,19 @@ impl<'repo> Remote<'repo> {
         direction: crate::remote::Direction,
     ) -> Result<Connection<'_, 'repo, Box<dyn Transport + Send>>, Error> {
         let (url, version) = self.sanitized_url_and_version(direction)?;
+        #[cfg(all(feature = "bundle", feature = "blocking-network-client"))]
+        if url.scheme == gix_url::Scheme::File {
+            let path = gix_path::from_bstr(url.path.as_ref());
+            if gix_bundle::is_bundle(&path) {
+                if direction == crate::remote::Direction::Push {
+                    return Err(Error::PushToBundle { url });
+                }
+                let bundle = gix_bundle::File::at(path)?;
+                self.repo.verify_bundle(&bundle.header)?;
+                let transport = crate::bundle::transport::connect(bundle, self.repo.config.trace_packet());
+                return Ok(self.to_connection_with_transport(Box::new(transport)));
+            }
+        }
         #[cfg(feature = "blocking-network-client")]
         let scheme_is_ssh = url.scheme == gix_url::Scheme::Ssh;
         let transport = gix_protocol::transport::connect(
@@ -109,6 +135,10 @@ impl<'repo> Remote<'repo> {
         direction: crate::remote::Direction,
     ) -> Result<(gix_url::Url, gix_protocol::transport::Protocol), Error> {
         fn sanitize(mut url: gix_url::Url) -> Result<gix_url::Url, Error> {
+            #[cfg(feature = "bundle")]
+            if url.scheme == gix_url::Scheme::File && gix_bundle::is_bundle(&gix_path::from_bstr(url.path.as_ref())) {
+                return Ok(url);
+            }
             if url.scheme == gix_url::Scheme::File {
                 let mut dir = gix_path::to_native_path_on_windows(Cow::Borrowed(url.path.as_ref()));
                 let kind = gix_discover::is_git(dir.as_ref())
@@ -139,6 +169,11 @@ impl<'repo> Remote<'repo> {
         let version = crate::config::tree::Protocol::VERSION
             .try_into_protocol_version(self.repo.config.resolved.integer("protocol", None, "version"))
             .map_err(|err| Error::UnknownProtocol { source: err })?;
+        // `receive-pack` doesn't support V2 and would answer with a V0 advertisement anyway.
+        let version = match direction {
+            crate::remote::Direction::Push => gix_protocol::transport::Protocol::V1,
+            crate::remote::Direction::Fetch => version,
+        };
 
         let url = self.url(direction).ok_or(Error::MissingUrl { direction })?.to_owned();
         if !self.repo.config.url_scheme()?.allow(&url.scheme) {
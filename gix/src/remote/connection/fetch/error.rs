@@ -45,6 +45,13 @@ pub enum Error {
     RejectShallowRemote,
     #[error(transparent)]
     NegotiationAlgorithmConfig(#[from] config::key::GenericErrorWithValue),
+    #[error("Could not obtain configuration to learn if FETCH_HEAD should be written")]
+    WriteFetchHeadConfig(#[source] config::boolean::Error),
+    #[error("Could not write FETCH_HEAD at \"{}\"", path.display())]
+    WriteFetchHead {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
     #[error("Failed to read remaining bytes in stream")]
     ReadRemainingBytes(#[source] std::io::Error),
 }
@@ -0,0 +1,142 @@
+use std::io::Write;
+
+use gix_fetchhead::{Entry, FetchHead};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::{cache::util::ApplyLeniency, tree::Fetch},
+    remote,
+    remote::{
+        fetch,
+        fetch::{refs::update::Mode, Error, SpecIndex, WriteFetchHead},
+    },
+    Remote, Repository,
+};
+
+/// Return `true` if `FETCH_HEAD` should be written according to `mode`, which may defer to `fetch.writeFetchHead`.
+pub(crate) fn enabled(repo: &Repository, mode: WriteFetchHead) -> Result<bool, Error> {
+    Ok(match mode {
+        WriteFetchHead::Never => false,
+        WriteFetchHead::Overwrite | WriteFetchHead::Append => repo
+            .config
+            .resolved
+            .boolean_filter_by_key("fetch.writeFetchHead", &mut repo.filter_config_section())
+            .map(|value| Fetch::WRITE_FETCH_HEAD.enrich_error(value))
+            .transpose()
+            .with_leniency(repo.config.lenient_config)
+            .map_err(Error::WriteFetchHeadConfig)?
+            .unwrap_or(true),
+    })
+}
+
+/// Produce the entries of `FETCH_HEAD` for all `mappings` whose objects were received, along with their `updates`,
+/// with those to merge coming first.
+///
+/// Like `git`, we merge the remote branches configured in `branch.<name>.merge` of the current branch if it is
+/// associated with `remote`. Otherwise we merge all explicitly mapped refs if `remote` is anonymous, as its refspecs
+/// were provided by the caller, or the remote ref matched by the first refspec if it isn't a pattern.
+pub(crate) fn entries(
+    repo: &Repository,
+    remote: &Remote<'_>,
+    mappings: &[fetch::Mapping],
+    updates: &[fetch::refs::Update],
+) -> Vec<Entry> {
+    let url = remote
+        .url(remote::Direction::Fetch)
+        .map(|url| {
+            let mut url = url.clone();
+            url.set_user(None);
+            url.set_password(None);
+            gix_fetchhead::shorten_url(url.to_bstring().as_bstr()).to_owned()
+        })
+        .unwrap_or_default();
+    let refspecs = remote.refspecs(remote::Direction::Fetch);
+    let merge_refs = merge_refs(repo, remote);
+    let merge_first_ref = merge_refs.is_none()
+        && refspecs
+            .first()
+            .and_then(|spec| spec.to_ref().source().map(|src| !src.contains(&b'*')))
+            == Some(true);
+
+    let mut seen = std::collections::HashSet::new();
+    let mut entries = Vec::new();
+    for (mapping, update) in mappings.iter().zip(updates) {
+        let Some(id) = mapping.remote.as_id() else { continue };
+        let is_implicit = matches!(mapping.spec_index, SpecIndex::Implicit(_));
+        match update.mode {
+            Mode::ImplicitTagNotSentByRemote | Mode::RejectedSourceObjectNotFound { .. } => continue,
+            Mode::NoChangeNeeded if is_implicit => continue,
+            _ => {}
+        }
+        let remote_ref = match mapping.remote.as_name() {
+            Some(name) if name == "HEAD" => None,
+            Some(name) => Some(BString::from(name)),
+            None => Some(id.to_hex().to_string().into()),
+        };
+        if !seen.insert((remote_ref.clone(), id.to_owned())) {
+            continue;
+        }
+        let for_merge = !is_implicit
+            && match &merge_refs {
+                Some(merge_refs) => matches!(&remote_ref, Some(name) if merge_refs.contains(name)),
+                None if remote.name().is_none() => true,
+                None => merge_first_ref && mapping.spec_index == SpecIndex::ExplicitInRemote(0),
+            };
+        entries.push(Entry {
+            id: id.to_owned(),
+            for_merge,
+            remote_ref,
+            url: url.clone(),
+        });
+    }
+    entries.sort_by_key(|entry| !entry.for_merge);
+    entries
+}
+
+/// Write `entries` to `FETCH_HEAD`, appending them to the existing ones if `mode` is [`WriteFetchHead::Append`].
+pub(crate) fn write(repo: &Repository, entries: Vec<Entry>, mode: WriteFetchHead) -> Result<(), Error> {
+    let path = repo.git_dir().join("FETCH_HEAD");
+    let mut buf = Vec::new();
+    FetchHead { entries }
+        .write_to(&mut buf)
+        .expect("writing to memory never fails");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(mode == WriteFetchHead::Append)
+        .truncate(mode != WriteFetchHead::Append)
+        .open(&path)
+        .map_err(|source| Error::WriteFetchHead {
+            path: path.clone(),
+            source,
+        })?;
+    file.write_all(&buf)
+        .map_err(|source| Error::WriteFetchHead { path, source })
+}
+
+/// Return the full names of the remote references configured to be merged into the current branch,
+/// if it is associated with `remote`.
+fn merge_refs(repo: &Repository, remote: &Remote<'_>) -> Option<Vec<BString>> {
+    let head_name = repo.head_name().ok()??;
+    let short_name = head_name.shorten();
+    let branch_remote = repo.branch_remote_name(short_name, remote::Direction::Fetch)?;
+    if Some(branch_remote.as_bstr()) != remote.name().map(remote::Name::as_bstr) {
+        return None;
+    }
+    let merge_refs: Vec<BString> = repo
+        .config
+        .resolved
+        .strings("branch", Some(short_name), "merge")?
+        .into_iter()
+        .map(|name| {
+            if name.starts_with(b"refs/") {
+                name.into_owned()
+            } else {
+                let mut full = BString::from("refs/heads/");
+                full.extend_from_slice(&name);
+                full
+            }
+        })
+        .collect();
+    (!merge_refs.is_empty()).then_some(merge_refs)
+}
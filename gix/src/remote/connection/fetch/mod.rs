@@ -13,7 +13,7 @@ use crate::{
 mod error;
 pub use error::Error;
 
-use crate::remote::fetch::WritePackedRefs;
+use crate::remote::fetch::{WriteFetchHead, WritePackedRefs};
 
 /// The way reflog messages should be composed whenever a ref is written with recent objects from a remote.
 pub enum RefLogMessage {
@@ -194,6 +194,7 @@ where
             dry_run: DryRun::No,
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
+            write_fetch_head: WriteFetchHead::default(),
             shallow: Default::default(),
         })
     }
@@ -210,6 +211,7 @@ where
 }
 
 mod config;
+mod fetch_head;
 mod receive_pack;
 ///
 #[path = "update_refs/mod.rs"]
@@ -225,6 +227,7 @@ where
     dry_run: DryRun,
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
+    write_fetch_head: WriteFetchHead,
     shallow: remote::fetch::Shallow,
 }
 
@@ -260,6 +263,15 @@ where
         self
     }
 
+    /// Define how to write `.git/FETCH_HEAD` with information about everything that was fetched,
+    /// with [`WriteFetchHead::Overwrite`] being the default.
+    ///
+    /// It's never written in dry-run mode.
+    pub fn with_write_fetch_head(mut self, mode: WriteFetchHead) -> Self {
+        self.write_fetch_head = mode;
+        self
+    }
+
     /// Define what to do when the current repository is a shallow clone.
     ///
     /// *Has no effect if the current repository is not as shallow clone.*
@@ -16,7 +16,7 @@ use crate::{
     },
     remote,
     remote::{
-        connection::fetch::config,
+        connection::fetch::{config, fetch_head},
         fetch,
         fetch::{
             negotiate, negotiate::Algorithm, outcome, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage,
@@ -332,6 +332,11 @@ where
             self.write_packed_refs,
         )?;
 
+        if matches!(self.dry_run, fetch::DryRun::No) && fetch_head::enabled(repo, self.write_fetch_head)? {
+            let entries = fetch_head::entries(repo, con.remote, &self.ref_map.mappings, &update_refs.updates);
+            fetch_head::write(repo, entries, self.write_fetch_head)?;
+        }
+
         if let Some(bundle) = write_pack_bundle.as_mut() {
             if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
                 if let Some(path) = bundle.keep_path.take() {
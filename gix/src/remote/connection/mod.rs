@@ -29,3 +29,8 @@ pub mod ref_map;
 ///
 #[allow(clippy::empty_docs)]
 pub mod fetch;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blocking-network-client")]
+pub mod push;
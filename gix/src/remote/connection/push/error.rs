@@ -0,0 +1,38 @@
+use crate::bstr::BString;
+
+/// The error returned by [`send()`](super::Prepare::send()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Server lack feature {feature:?}: {description}")]
+    MissingServerFeature {
+        feature: &'static str,
+        description: &'static str,
+    },
+    #[error("Cannot push to a remote that uses {remote:?} while local repository uses {local} for object hashes")]
+    IncompatibleObjectHash { local: gix_hash::Kind, remote: BString },
+    #[error(transparent)]
+    Client(#[from] gix_protocol::transport::client::Error),
+    #[error("Failed to communicate with the remote")]
+    Io(#[from] std::io::Error),
+    #[error("Could not decode a packetline of the status report")]
+    Packetline(#[from] gix_protocol::transport::packetline::decode::Error),
+    #[error("Could not decode the status report of the remote")]
+    Report(#[from] gix_protocol::push::response::Error),
+    #[error(transparent)]
+    LookupCommit(#[from] gix_revwalk::graph::try_lookup_or_insert_default::Error),
+    #[error(transparent)]
+    PackObjects(#[from] crate::pack_objects::Error),
+    #[error("Could not update local remote-tracking references after pushing")]
+    UpdateTrackingRefs(#[from] crate::reference::edit::Error),
+}
+
+impl gix_protocol::transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Client(err) => err.is_spurious(),
+            Error::Io(err) => err.is_spurious(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use gix_ref::FullName;
+use gix_refspec::{instruction, Instruction};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::{
+        cache::util::ApplyLeniencyDefault,
+        tree::{Push, Section},
+    },
+    push,
+    remote::{
+        push::{Mapping, Source},
+        Direction,
+    },
+    Remote,
+};
+
+/// The error returned when turning push refspecs into [mappings][Mapping] as part of
+/// [`prepare_push()`](crate::remote::Connection::prepare_push()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    PushDefault(#[from] crate::config::key::GenericErrorWithValue),
+    #[error("Nothing to push as the remote has no push refspecs and 'push.default' is set to 'nothing'")]
+    PushDefaultNothing,
+    #[error("Cannot push without a refspec as HEAD is detached or unborn")]
+    DetachedHead,
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error("The current branch '{branch}' has no upstream branch")]
+    NoUpstream { branch: BString },
+    #[error("The upstream branch '{upstream}' of the current branch '{branch}' has a different name, refusing to push with 'push.default=simple'")]
+    UpstreamNameMismatch { branch: BString, upstream: BString },
+    #[error("Cannot push the current branch '{branch}' to its upstream as it is configured for a different remote")]
+    UpstreamOfOtherRemote { branch: BString },
+    #[error(transparent)]
+    BranchRemoteRefName(#[from] crate::repository::branch_remote_ref_name::Error),
+    #[error("The source {spec:?} of a push refspec did not match any local reference or object")]
+    UnknownSource { spec: BString },
+    #[error(
+        "The destination {spec:?} neither matches a reference on the remote nor could it be inferred from its source"
+    )]
+    UnknownDestination { spec: BString },
+    #[error("The destination {spec:?} matches more than one reference on the remote")]
+    AmbiguousDestination { spec: BString },
+    #[error(transparent)]
+    ValidateRefName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FollowReference(#[from] gix_ref::file::find::existing::Error),
+    #[error(transparent)]
+    OpenReferenceIter(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    InitReferenceIter(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a local reference")]
+    IterReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Produce mappings for all push refspecs of `remote`, or the ones implied by `push.default`, against `remote_refs`.
+pub(crate) fn from_remote(
+    remote: &Remote<'_>,
+    remote_refs: &[gix_protocol::handshake::Ref],
+) -> Result<Vec<Mapping>, Error> {
+    let implied_specs;
+    let specs = match remote.refspecs(Direction::Push) {
+        specs if !specs.is_empty() => specs,
+        _ => {
+            implied_specs = specs_from_push_default(remote)?;
+            &implied_specs
+        }
+    };
+    let repo = remote.repo;
+    let mut out = Vec::<Mapping>::new();
+    for spec in specs {
+        let Instruction::Push(instruction) = spec.to_ref().instruction() else {
+            unreachable!("push refspecs are validated to be push instructions")
+        };
+        match instruction {
+            instruction::Push::AllMatchingBranches { allow_non_fast_forward } => {
+                for local in repo.references()?.local_branches()? {
+                    let local = local.map_err(Error::IterReference)?;
+                    let Some(remote_id) = remote_id(remote_refs, local.name().as_bstr()) else {
+                        continue;
+                    };
+                    let Some(source) = resolve_ref(local)? else { continue };
+                    let remote_name = source.as_name().expect("source is a ref").to_owned();
+                    push_mapping(
+                        &mut out,
+                        remote,
+                        Mapping {
+                            source: Some(source),
+                            remote: remote_name,
+                            remote_id: Some(remote_id),
+                            force: allow_non_fast_forward,
+                            tracking: None,
+                        },
+                    )?;
+                }
+            }
+            instruction::Push::Delete { ref_or_pattern } => {
+                if let Some((prefix, suffix)) = split_pattern(ref_or_pattern) {
+                    for (name, remote_id) in advertised(remote_refs) {
+                        if match_pattern(name, prefix, suffix).is_some() {
+                            push_mapping(
+                                &mut out,
+                                remote,
+                                Mapping {
+                                    source: None,
+                                    remote: name.to_owned().try_into()?,
+                                    remote_id: Some(remote_id),
+                                    force: true,
+                                    tracking: None,
+                                },
+                            )?;
+                        }
+                    }
+                } else {
+                    let name = expand_destination(ref_or_pattern, None, remote_refs)?;
+                    push_mapping(
+                        &mut out,
+                        remote,
+                        Mapping {
+                            source: None,
+                            remote_id: remote_id(remote_refs, name.as_bstr()),
+                            remote: name,
+                            force: true,
+                            tracking: None,
+                        },
+                    )?;
+                }
+            }
+            instruction::Push::Matching {
+                src,
+                dst,
+                allow_non_fast_forward,
+            } => {
+                if let Some((prefix, suffix)) = split_pattern(src) {
+                    for local in repo.references()?.all()? {
+                        let local = local.map_err(Error::IterReference)?;
+                        let Some(matched) =
+                            match_pattern(local.name().as_bstr(), prefix, suffix).map(ToOwned::to_owned)
+                        else {
+                            continue;
+                        };
+                        let remote_name: FullName = BString::from(dst.replace("*", matched)).try_into()?;
+                        let Some(source) = resolve_ref(local)? else { continue };
+                        push_mapping(
+                            &mut out,
+                            remote,
+                            Mapping {
+                                source: Some(source),
+                                remote_id: remote_id(remote_refs, remote_name.as_bstr()),
+                                remote: remote_name,
+                                force: allow_non_fast_forward,
+                                tracking: None,
+                            },
+                        )?;
+                    }
+                } else {
+                    let source = resolve_source(remote, src)?;
+                    let remote_name = match source.as_name() {
+                        // Like `git push origin HEAD`, update the branch `HEAD` points to.
+                        Some(name) if src == "HEAD" && dst == "HEAD" => name.to_owned(),
+                        _ => expand_destination(dst, Some(&source), remote_refs)?,
+                    };
+                    push_mapping(
+                        &mut out,
+                        remote,
+                        Mapping {
+                            source: Some(source),
+                            remote_id: remote_id(remote_refs, remote_name.as_bstr()),
+                            remote: remote_name,
+                            force: allow_non_fast_forward,
+                            tracking: None,
+                        },
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Add `mapping` to `out` along with its tracking reference, unless a mapping for the same remote reference exists already.
+fn push_mapping(out: &mut Vec<Mapping>, remote: &Remote<'_>, mut mapping: Mapping) -> Result<(), Error> {
+    if out.iter().any(|m| m.remote == mapping.remote) {
+        return Ok(());
+    }
+    mapping.tracking = tracking_ref(remote, mapping.remote.as_bstr())?;
+    out.push(mapping);
+    Ok(())
+}
+
+/// Return the local tracking reference of `remote_name` according to the fetch refspecs of `remote`.
+fn tracking_ref(remote: &Remote<'_>, remote_name: &BStr) -> Result<Option<FullName>, Error> {
+    let group = gix_refspec::MatchGroup::from_fetch_specs(
+        remote
+            .refspecs(Direction::Fetch)
+            .iter()
+            .map(gix_refspec::RefSpec::to_ref)
+            .filter(|spec| spec.source().is_some() && spec.destination().is_some()),
+    );
+    let null_id = remote.repo.object_hash().null();
+    let out = group.match_remotes(
+        Some(gix_refspec::match_group::Item {
+            full_ref_name: remote_name,
+            target: &null_id,
+            object: None,
+        })
+        .into_iter(),
+    );
+    Ok(out
+        .mappings
+        .into_iter()
+        .next()
+        .and_then(|m| m.rhs)
+        .map(|name| FullName::try_from(name.into_owned()))
+        .transpose()?)
+}
+
+/// Derive the refspecs to use from `push.default` when pushing the current branch to `remote`.
+fn specs_from_push_default(remote: &Remote<'_>) -> Result<Vec<gix_refspec::RefSpec>, Error> {
+    let repo = remote.repo;
+    let push_default = repo
+        .config
+        .resolved
+        .string(Push.name(), None, Push::DEFAULT.name)
+        .map_or(Ok(Default::default()), |v| {
+            Push::DEFAULT
+                .try_into_default(v)
+                .with_lenient_default(repo.config.lenient_config)
+        })?;
+    let spec = match push_default {
+        push::Default::Nothing => return Err(Error::PushDefaultNothing),
+        push::Default::Matching => BString::from(":"),
+        push::Default::Current | push::Default::Upstream | push::Default::Simple => {
+            let branch = repo.head_name()?.ok_or(Error::DetachedHead)?;
+            let short_name = branch.shorten();
+            let upstream_remote = repo
+                .branch_remote_name(short_name, Direction::Fetch)
+                .map_or_else(|| "origin".into(), |name| name.as_bstr().to_owned());
+            let is_upstream_remote = remote.name().map(crate::remote::Name::as_bstr) == Some(upstream_remote.as_ref());
+            let upstream = || -> Result<FullName, Error> {
+                if !is_upstream_remote {
+                    return Err(Error::UpstreamOfOtherRemote {
+                        branch: short_name.to_owned(),
+                    });
+                }
+                Ok(repo
+                    .branch_remote_ref_name(branch.as_ref(), Direction::Fetch)
+                    .ok_or_else(|| Error::NoUpstream {
+                        branch: short_name.to_owned(),
+                    })??
+                    .into_owned())
+            };
+            let destination = match push_default {
+                push::Default::Upstream => upstream()?,
+                push::Default::Simple if is_upstream_remote => {
+                    let upstream = upstream()?;
+                    if upstream != branch {
+                        return Err(Error::UpstreamNameMismatch {
+                            branch: short_name.to_owned(),
+                            upstream: upstream.shorten().to_owned(),
+                        });
+                    }
+                    upstream
+                }
+                _ => branch.clone(),
+            };
+            let mut spec = branch.as_bstr().to_owned();
+            spec.push(b':');
+            spec.extend_from_slice(destination.as_bstr());
+            spec
+        }
+    };
+    Ok(vec![gix_refspec::parse(
+        spec.as_ref(),
+        gix_refspec::parse::Operation::Push,
+    )
+    .expect("valid refspecs are produced from valid ref names")
+    .to_owned()])
+}
+
+/// Resolve `src` of a push refspec to something local.
+fn resolve_source(remote: &Remote<'_>, src: &BStr) -> Result<Source, Error> {
+    let repo = remote.repo;
+    if let Some(r) = repo.try_find_reference(src)? {
+        if let Some(source) = resolve_ref(r)? {
+            return Ok(source);
+        }
+    }
+    if let Ok(id) = gix_hash::ObjectId::from_hex(src) {
+        return Ok(Source::ObjectId(id));
+    }
+    #[cfg(feature = "revision")]
+    if let Ok(id) = repo.rev_parse_single(src) {
+        return Ok(Source::ObjectId(id.detach()));
+    }
+    Err(Error::UnknownSource { spec: src.to_owned() })
+}
+
+/// Follow `r` to its direct target, or return `None` if it is unborn.
+fn resolve_ref(mut r: crate::Reference<'_>) -> Result<Option<Source>, Error> {
+    // `follow()` would also peel packed references, but annotated tags must be pushed as they are.
+    while r.target().try_name().is_some() {
+        let Some(next) = r.follow() else { break };
+        r = match next {
+            Ok(next) => next,
+            Err(gix_ref::file::find::existing::Error::NotFound { .. }) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+    }
+    Ok(r.try_id().map(|id| Source::Ref {
+        name: r.name().to_owned(),
+        id: id.detach(),
+    }))
+}
+
+/// Turn the possibly partial `dst` into a full reference name, by looking for it in `remote_refs` or by deriving
+/// it from `source`.
+fn expand_destination(
+    dst: &BStr,
+    source: Option<&Source>,
+    remote_refs: &[gix_protocol::handshake::Ref],
+) -> Result<FullName, Error> {
+    if dst.starts_with(b"refs/") {
+        return Ok(dst.to_owned().try_into()?);
+    }
+    let candidates: Vec<BString> = ["refs/", "refs/tags/", "refs/heads/", "refs/remotes/"]
+        .into_iter()
+        .map(|prefix| {
+            let mut name = BString::from(prefix);
+            name.extend_from_slice(dst);
+            name
+        })
+        .collect();
+    let mut matches = advertised(remote_refs).filter(|(name, _)| candidates.iter().any(|c| c == name));
+    if let Some((name, _)) = matches.next() {
+        if matches.next().is_some() {
+            return Err(Error::AmbiguousDestination { spec: dst.to_owned() });
+        }
+        return Ok(name.to_owned().try_into()?);
+    }
+    match source.and_then(Source::as_name).map(gix_ref::FullNameRef::as_bstr) {
+        Some(name) if name.starts_with(b"refs/heads/") || name.starts_with(b"refs/tags/") => {
+            let category_len = name[b"refs/".len()..]
+                .find_byte(b'/')
+                .expect("present as checked above")
+                + b"refs/".len()
+                + 1;
+            let mut full_name = BString::from(&name[..category_len]);
+            full_name.extend_from_slice(dst);
+            Ok(full_name.try_into()?)
+        }
+        _ => Err(Error::UnknownDestination { spec: dst.to_owned() }),
+    }
+}
+
+/// Return the id the reference named `name` has on the remote.
+fn remote_id(remote_refs: &[gix_protocol::handshake::Ref], name: &BStr) -> Option<gix_hash::ObjectId> {
+    advertised(remote_refs).find_map(|(candidate, id)| (candidate == name).then_some(id))
+}
+
+/// Return all advertised references that exist on the remote, along with the object they point to.
+fn advertised(remote_refs: &[gix_protocol::handshake::Ref]) -> impl Iterator<Item = (&BStr, gix_hash::ObjectId)> {
+    remote_refs.iter().filter_map(|r| {
+        let (name, target, _peeled) = r.unpack();
+        target.map(|id| (name, id.to_owned()))
+    })
+}
+
+/// Split a pattern with a single `*` into its prefix and suffix, or return `None` if `spec` is no pattern.
+fn split_pattern(spec: &BStr) -> Option<(&[u8], &[u8])> {
+    let pos = spec.find_byte(b'*')?;
+    Some((&spec[..pos], &spec[pos + 1..]))
+}
+
+/// Return the portion of `name` matched by the `*` between `prefix` and `suffix`.
+fn match_pattern<'a>(name: &'a BStr, prefix: &[u8], suffix: &[u8]) -> Option<&'a BStr> {
+    (name.len() > prefix.len() + suffix.len() && name.starts_with(prefix) && name.ends_with(suffix))
+        .then(|| name[prefix.len()..name.len() - suffix.len()].as_bstr())
+}
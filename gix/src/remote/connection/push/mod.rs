@@ -0,0 +1,334 @@
+use gix_features::progress::Progress;
+use gix_protocol::transport::client::Transport;
+
+use crate::{
+    bstr::BString,
+    remote::{
+        fetch::DryRun,
+        push::{Lease, Mapping},
+        Connection, Direction,
+    },
+};
+
+mod error;
+pub use error::Error;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod mappings;
+mod send;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod prepare {
+    use crate::bstr::BString;
+
+    /// The error returned by [`prepare_push()`][super::Connection::prepare_push()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Failed to configure the transport before connecting to {url:?}")]
+        GatherTransportConfig {
+            url: BString,
+            source: crate::config::transport::Error,
+        },
+        #[error("Failed to configure the transport layer")]
+        ConfigureTransport(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        ConfigureCredentials(#[from] crate::config::credential_helpers::Error),
+        #[error(transparent)]
+        Handshake(#[from] gix_protocol::handshake::Error),
+        #[error("The server did not advertise its references as part of the handshake, which is required for pushing")]
+        MissingRefAdvertisement,
+        #[error(transparent)]
+        Mappings(#[from] super::mappings::Error),
+    }
+
+    impl gix_protocol::transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Handshake(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Information about the outcome of a push, as returned by [`Prepare::send()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The planned updates, one for each mapping, along with their final status.
+    pub updates: Vec<Update>,
+    /// All refs advertised by the remote before the push.
+    pub remote_refs: Vec<gix_protocol::handshake::Ref>,
+    /// The report of the server, or `None` if nothing was sent to it, for instance in dry-run mode or if
+    /// all updates were rejected locally.
+    pub report: Option<gix_protocol::push::Report>,
+    /// The amount of objects contained in the pack we sent, or `None` if no pack was sent.
+    pub num_objects_sent: Option<usize>,
+}
+
+impl Outcome {
+    /// Return `true` if none of the updates was rejected, either locally or by the remote.
+    ///
+    /// Note that updates in dry-run mode are considered successful.
+    pub fn is_success(&self) -> bool {
+        self.report
+            .as_ref()
+            .map_or(true, |r| r.unpack == gix_protocol::push::response::Unpack::Ok)
+            && self
+                .updates
+                .iter()
+                .all(|u| !matches!(u.status, update::Status::Rejected(_)))
+    }
+}
+
+/// A reference update along with its status, as part of the [Outcome] of a push.
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// The mapping this update was derived from.
+    pub mapping: Mapping,
+    /// What happened to the update.
+    pub status: update::Status,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod update {
+    use crate::bstr::BString;
+
+    /// The status of a reference [update][super::Update].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Status {
+        /// The remote reference already points to the desired object, or doesn't exist when it should be deleted,
+        /// so there was nothing to do.
+        UpToDate,
+        /// The update would have been sent, but wasn't as dry-run mode was enabled.
+        DryRun,
+        /// The update was accepted by the remote.
+        Ok {
+            /// If `true`, the update was not a fast-forward and was only possible as it was forced.
+            forced: bool,
+            /// Additional information provided by the server if `report-status-v2` is supported.
+            options: Vec<gix_protocol::push::response::Options>,
+        },
+        /// The update was rejected, either locally before sending or by the remote.
+        Rejected(Rejection),
+    }
+
+    /// The reason for a rejected reference update.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Rejection {
+        /// The remote reference isn't an ancestor of the new value and the update wasn't forced.
+        NonFastForward,
+        /// The object the remote reference points to isn't available locally, so it's unknown if the update would be a fast-forward.
+        /// Fetching first should resolve this.
+        FetchFirst,
+        /// The remote reference is a tag which already exists, and it would only be changed if forced.
+        AlreadyExists,
+        /// The lease for the remote reference didn't hold, i.e. the remote reference didn't have the `expected` value.
+        Stale {
+            /// The value we expected the remote reference to have, with `None` meaning it was expected to not exist.
+            expected: Option<gix_hash::ObjectId>,
+        },
+        /// The remote reference to delete doesn't exist.
+        RemoteRefMissing,
+        /// The update was fine by itself, but not performed as another update of an atomic push was rejected.
+        AtomicPushFailed,
+        /// The remote rejected the update with the given `reason`.
+        Remote {
+            /// The reason as provided by the remote, like `non-fast-forward`, `pre-receive hook declined` or `unpacker error`.
+            reason: BString,
+        },
+    }
+}
+
+/// The progress ids used in during various steps of the push operation.
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+/// The progress of writing the pack sent to the remote uses the ids of [`pack_objects::ProgressId`](crate::pack_objects::ProgressId).
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The progress name is defined by the remote and the progress messages it sets, along with their progress values and limits.
+    RemoteProgress,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::RemoteProgress => *b"PURP",
+        }
+    }
+}
+
+impl<'remote, 'repo, T> Connection<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Perform a handshake with the `receive-pack` service of the remote to learn about its references, and map our
+    /// [push refspecs][crate::Remote::refspecs()] to them.
+    ///
+    /// If the remote doesn't have push refspecs, `push.default` is used to determine what to push, with `simple` being the default.
+    /// Note that explicit refspecs, like the ones given on the command-line of `git push`, can be set with
+    /// [`Remote::replace_refspecs()`][crate::Remote::replace_refspecs()] before connecting.
+    ///
+    /// Note that at this point, the `transport` should already be configured using the [`transport_mut()`][Self::transport_mut()]
+    /// method, as it will be consumed here.
+    ///
+    /// From there additional properties of the push can be adjusted to override the defaults.
+    ///
+    /// ### Configuration
+    ///
+    /// - `push.default` is used if the remote has no push refspecs.
+    /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    #[allow(clippy::result_large_err)]
+    pub fn prepare_push(mut self, mut progress: impl Progress) -> Result<Prepare<'remote, 'repo, T>, prepare::Error> {
+        let _span = gix_trace::coarse!("remote::Connection::prepare_push()");
+        let mut credentials_storage;
+        let url = self.transport.to_url();
+        let authenticate = match self.authenticate.as_mut() {
+            Some(f) => f,
+            None => {
+                let url = self.remote.url(Direction::Push).map_or_else(
+                    || gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"),
+                    ToOwned::to_owned,
+                );
+                credentials_storage = self.configured_credentials(url)?;
+                &mut credentials_storage
+            }
+        };
+
+        if self.transport_options.is_none() {
+            self.transport_options = self
+                .remote
+                .repo
+                .transport_options(url.as_ref(), self.remote.name().map(crate::remote::Name::as_bstr))
+                .map_err(|err| prepare::Error::GatherTransportConfig {
+                    source: err,
+                    url: url.into_owned(),
+                })?;
+        }
+        if let Some(config) = self.transport_options.as_ref() {
+            self.transport.configure(&**config)?;
+        }
+        let mut handshake = gix_protocol::handshake(
+            &mut self.transport,
+            gix_protocol::transport::Service::ReceivePack,
+            authenticate,
+            Vec::new(),
+            &mut progress,
+        )?;
+        let (additional_haves, remote_refs): (Vec<_>, Vec<_>) = handshake
+            .refs
+            .take()
+            .ok_or(prepare::Error::MissingRefAdvertisement)?
+            .into_iter()
+            .partition(|r| r.unpack().0 == ".have");
+        let additional_haves = additional_haves
+            .into_iter()
+            .filter_map(|r| r.unpack().1.map(ToOwned::to_owned))
+            .collect();
+        let mappings = mappings::from_remote(self.remote, &remote_refs)?;
+        Ok(Prepare {
+            con: Some(self),
+            handshake,
+            remote_refs,
+            additional_haves,
+            mappings,
+            dry_run: DryRun::No,
+            atomic: false,
+            push_options: Vec::new(),
+            leases: Vec::new(),
+        })
+    }
+}
+
+/// A structure to hold the result of the handshake with the remote and configure the upcoming push operation.
+pub struct Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    con: Option<Connection<'remote, 'repo, T>>,
+    handshake: gix_protocol::handshake::Outcome,
+    remote_refs: Vec<gix_protocol::handshake::Ref>,
+    /// Objects the remote has without them being reachable from `remote_refs`, as advertised with `.have` lines.
+    additional_haves: Vec<gix_hash::ObjectId>,
+    mappings: Vec<Mapping>,
+    dry_run: DryRun,
+    atomic: bool,
+    push_options: Vec<BString>,
+    leases: Vec<Lease>,
+}
+
+/// Access
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Return all references the remote advertised during the handshake.
+    pub fn remote_refs(&self) -> &[gix_protocol::handshake::Ref] {
+        &self.remote_refs
+    }
+
+    /// Return the information the server provided during the handshake, like its capabilities.
+    ///
+    /// Note that the `refs` field is always `None` as the refs are available via [`remote_refs()`][Self::remote_refs()].
+    pub fn handshake(&self) -> &gix_protocol::handshake::Outcome {
+        &self.handshake
+    }
+
+    /// Return the mappings between local sources and remote references that would be sent to the remote.
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+}
+
+/// Builder
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// If dry run is enabled, no change to the remote or the local repository will be made.
+    ///
+    /// This works by determining the status of all updates locally, without sending them.
+    pub fn with_dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = if enabled { DryRun::Yes } else { DryRun::No };
+        self
+    }
+
+    /// If enabled, the remote is asked to either apply all reference updates or none of them, which fails if the
+    /// server doesn't support the `atomic` capability.
+    ///
+    /// Note that local rejections also cause all other updates to be rejected.
+    pub fn with_atomic(mut self, enabled: bool) -> Self {
+        self.atomic = enabled;
+        self
+    }
+
+    /// Transmit the given push `options` to the server, which passes them on to its hooks.
+    /// This fails if the server doesn't support the `push-options` capability.
+    pub fn with_push_options(mut self, options: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.push_options = options.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Protect remote references with the given `leases`, which allows them to be updated even if the update isn't
+    /// a fast-forward, but only if they still have the expected value.
+    ///
+    /// This is equivalent to `git push --force-with-lease`.
+    pub fn with_force_with_lease(mut self, leases: impl IntoIterator<Item = Lease>) -> Self {
+        self.leases = leases.into_iter().collect();
+        self
+    }
+}
+
+impl<'remote, 'repo, T> Drop for Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    fn drop(&mut self) {
+        if let Some(mut con) = self.con.take() {
+            gix_protocol::indicate_end_of_interaction(&mut con.transport, con.trace).ok();
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::progress::NestedProgress;
+use gix_hash::ObjectId;
+use gix_protocol::transport::{
+    client::{ExtendedBufRead, MessageKind, Transport, WriteMode},
+    packetline::read::ProgressAction,
+    Protocol,
+};
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{
+    update::{Rejection, Status},
+    Error, Outcome, Prepare, ProgressId, Update,
+};
+use crate::{
+    bstr::{BString, ByteSlice},
+    remote::{
+        fetch::DryRun,
+        push::{lease, Mapping},
+    },
+};
+
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Check all [mappings][Self::mappings()] locally, send the updates that passed along with a pack containing all objects
+    /// the remote doesn't have yet, and return the status of each update as reported by the remote.
+    ///
+    /// Updates are rejected locally if they aren't fast-forwards and aren't forced, if they would change an existing tag,
+    /// if the object currently on the remote isn't present locally, or if a [lease][Self::with_force_with_lease()] didn't hold.
+    /// After a successful push, the remote-tracking references of all accepted updates are adjusted to match their remote.
+    ///
+    /// Note that rejected updates are not an error, use [`Outcome::is_success()`] to learn if all updates went through.
+    /// `should_interrupt` can be used to abort the operation while objects are counted or the pack is written.
+    pub fn send<P>(mut self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
+    where
+        P: NestedProgress,
+        P::SubProgress: 'static,
+    {
+        let _span = gix_trace::coarse!("remote::push::Prepare::send()");
+        let mut con = self.con.take().expect("send() can only be called once");
+        let repo = con.remote.repo;
+        let capabilities = &self.handshake.capabilities;
+
+        if let Some(format) = capabilities.capability("object-format").and_then(|c| c.value()) {
+            if format.to_str().ok().and_then(|format| format.parse().ok()) != Some(repo.object_hash()) {
+                return Err(Error::IncompatibleObjectHash {
+                    local: repo.object_hash(),
+                    remote: format.to_owned(),
+                });
+            }
+        }
+
+        let mut updates = Vec::with_capacity(self.mappings.len());
+        for mapping in std::mem::take(&mut self.mappings) {
+            let status = self.local_status(repo, &mapping)?;
+            updates.push((mapping, status));
+        }
+        if self.atomic
+            && updates
+                .iter()
+                .any(|(_, status)| matches!(status, Some(Status::Rejected(_))))
+        {
+            for (_, status) in updates.iter_mut().filter(|(_, status)| status.is_none()) {
+                *status = Some(Status::Rejected(Rejection::AtomicPushFailed));
+            }
+        }
+
+        let pending = updates.iter().filter(|(_, status)| status.is_none()).count();
+        if pending == 0 || self.dry_run == DryRun::Yes {
+            gix_protocol::indicate_end_of_interaction(&mut con.transport, con.trace).ok();
+            return Ok(Outcome {
+                updates: updates
+                    .into_iter()
+                    .map(|(mapping, status)| Update {
+                        mapping,
+                        status: status.unwrap_or(Status::DryRun),
+                    })
+                    .collect(),
+                remote_refs: std::mem::take(&mut self.remote_refs),
+                report: None,
+                num_objects_sent: None,
+            });
+        }
+
+        let mut features = Vec::new();
+        if capabilities.contains("report-status-v2") {
+            features.push(("report-status-v2", None));
+        } else if capabilities.contains("report-status") {
+            features.push(("report-status", None));
+        } else {
+            return Err(Error::MissingServerFeature {
+                feature: "report-status",
+                description: "Without a status report, we can't know if the remote accepted our updates",
+            });
+        }
+        let use_sideband = capabilities.contains("side-band-64k");
+        if use_sideband {
+            features.push(("side-band-64k", None));
+        }
+        if self.atomic {
+            if !capabilities.contains("atomic") {
+                return Err(Error::MissingServerFeature {
+                    feature: "atomic",
+                    description: "Atomic pushes need to be supported by the remote",
+                });
+            }
+            features.push(("atomic", None));
+        }
+        if !self.push_options.is_empty() {
+            if !capabilities.contains("push-options") {
+                return Err(Error::MissingServerFeature {
+                    feature: "push-options",
+                    description: "Push options can only be transmitted if the remote supports them",
+                });
+            }
+            features.push(("push-options", None));
+        }
+        let has_deletions = updates
+            .iter()
+            .any(|(mapping, status)| status.is_none() && mapping.source.is_none());
+        if has_deletions {
+            if !capabilities.contains("delete-refs") {
+                return Err(Error::MissingServerFeature {
+                    feature: "delete-refs",
+                    description: "The remote doesn't allow references to be deleted",
+                });
+            }
+            features.push(("delete-refs", None));
+        }
+        // Like git, deltas may refer to objects the remote has unless it asks us not to.
+        let thin_pack = !capabilities.contains("no-thin");
+        let ofs_delta = capabilities.contains("ofs-delta");
+        if ofs_delta {
+            features.push(("ofs-delta", None));
+        }
+        let object_format = repo.object_hash().to_string().to_ascii_lowercase();
+        if capabilities.contains("object-format") {
+            features.push(("object-format", Some(object_format.as_str())));
+        }
+        let agent = repo.config.user_agent_tuple();
+        features.push((agent.0, agent.1.as_deref()));
+
+        let commands: Vec<_> = updates
+            .iter()
+            .filter(|(_, status)| status.is_none())
+            .map(|(mapping, _)| gix_protocol::push::Command {
+                old_id: mapping.remote_id.unwrap_or_else(|| repo.object_hash().null()),
+                new_id: mapping
+                    .source
+                    .as_ref()
+                    .map_or_else(|| repo.object_hash().null(), |source| source.id().to_owned()),
+                ref_name: mapping.remote.as_bstr().to_owned(),
+            })
+            .collect();
+        let objects = if commands.iter().all(gix_protocol::push::Command::is_delete) {
+            None
+        } else {
+            let bitmap = repo.pack_bitmap().map_err(crate::pack_objects::Error::from)?;
+            Some(crate::pack_objects::select(
+                repo,
+                commands.iter().filter(|cmd| !cmd.is_delete()).map(|cmd| cmd.new_id),
+                self.remote_refs
+                    .iter()
+                    .filter_map(|r| r.unpack().1.map(ToOwned::to_owned))
+                    .chain(self.additional_haves.iter().copied()),
+                crate::pack_objects::select::Options {
+                    bitmap: bitmap.as_ref(),
+                    ..Default::default()
+                },
+                should_interrupt,
+            )?)
+        };
+
+        let mut request = con
+            .transport
+            .request(WriteMode::Binary, MessageKind::Flush, con.trace)?;
+        for (idx, command) in commands.iter().enumerate() {
+            request.write_all(&command.to_line(if idx == 0 { &features } else { &[] }))?;
+        }
+        request.write_message(MessageKind::Flush)?;
+        if !self.push_options.is_empty() {
+            for option in &self.push_options {
+                request.write_all(option)?;
+            }
+            request.write_message(MessageKind::Flush)?;
+        }
+        let (mut writer, mut reader) = request.into_parts();
+        let num_objects_sent = match objects {
+            Some(objects) => Some(crate::pack_objects::write(
+                repo,
+                objects,
+                &mut writer,
+                crate::pack_objects::write::Options { thin_pack, ofs_delta },
+                &mut progress,
+                should_interrupt,
+            )?),
+            None => None,
+        };
+        writer.flush()?;
+        drop(writer);
+
+        reader.reset(Protocol::V1);
+        let report = if use_sideband {
+            reader.set_progress_handler(Some(Box::new({
+                let mut remote_progress =
+                    progress.add_child_with_id("remote".to_string(), ProgressId::RemoteProgress.into());
+                move |is_err: bool, data: &[u8]| {
+                    gix_protocol::RemoteProgress::translate_to_progress(is_err, data, &mut remote_progress);
+                    if should_interrupt.load(Ordering::Relaxed) {
+                        ProgressAction::Interrupt
+                    } else {
+                        ProgressAction::Continue
+                    }
+                }
+            })));
+            let mut buf = Vec::new();
+            std::io::Read::read_to_end(&mut reader, &mut buf)?;
+            gix_protocol::push::Report::from_packetlines(&buf)?
+        } else {
+            let mut lines = Vec::<BString>::new();
+            while let Some(line) = reader.readline() {
+                if let Some(line) = line??.as_slice() {
+                    lines.push(line.into());
+                }
+            }
+            gix_protocol::push::Report::from_lines(lines.iter().map(|line| line.as_bytes()))?
+        };
+        drop(reader);
+
+        let updates: Vec<_> = updates
+            .into_iter()
+            .map(|(mapping, status)| {
+                let status = match status {
+                    Some(status) => status,
+                    None => match report
+                        .commands
+                        .iter()
+                        .find(|cmd| cmd.ref_name == mapping.remote.as_bstr())
+                        .map(|cmd| &cmd.status)
+                    {
+                        Some(gix_protocol::push::response::Status::Ok { options }) => Status::Ok {
+                            forced: mapping.remote_id.is_some()
+                                && mapping.source.is_some()
+                                && !is_fast_forward(repo, &mapping).unwrap_or(false),
+                            options: options.clone(),
+                        },
+                        Some(gix_protocol::push::response::Status::Rejected { reason }) => {
+                            Status::Rejected(Rejection::Remote { reason: reason.clone() })
+                        }
+                        None => Status::Rejected(Rejection::Remote {
+                            reason: "no status was reported".into(),
+                        }),
+                    },
+                };
+                Update { mapping, status }
+            })
+            .collect();
+
+        let edits: Vec<_> = updates
+            .iter()
+            .filter(|update| matches!(update.status, Status::Ok { .. }))
+            .filter_map(|update| {
+                let name = update.mapping.tracking.clone()?;
+                let change = match update.mapping.source.as_ref() {
+                    Some(source) => Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: "update by push".into(),
+                        },
+                        expected: PreviousValue::Any,
+                        new: gix_ref::Target::Peeled(source.id().to_owned()),
+                    },
+                    None => {
+                        repo.try_find_reference(name.as_ref()).ok().flatten()?;
+                        Change::Delete {
+                            expected: PreviousValue::Any,
+                            log: RefLog::AndReference,
+                        }
+                    }
+                };
+                Some(RefEdit {
+                    change,
+                    name,
+                    deref: false,
+                })
+            })
+            .collect();
+        if !edits.is_empty() {
+            repo.edit_references(edits)?;
+        }
+
+        Ok(Outcome {
+            updates,
+            remote_refs: std::mem::take(&mut self.remote_refs),
+            report: Some(report),
+            num_objects_sent,
+        })
+    }
+
+    /// Return the status of `mapping` if it can be determined without the remote, or `None` if it should be sent.
+    fn local_status(&self, repo: &crate::Repository, mapping: &Mapping) -> Result<Option<Status>, Error> {
+        let new_id = mapping.source.as_ref().map(|source| source.id().to_owned());
+        if new_id == mapping.remote_id {
+            return Ok(Some(if new_id.is_some() {
+                Status::UpToDate
+            } else {
+                Status::Rejected(Rejection::RemoteRefMissing)
+            }));
+        }
+        if let Some(lease) = self.leases.iter().find(|lease| lease.remote == mapping.remote) {
+            let expected = match &lease.expected {
+                lease::Expected::TrackingRef => match mapping.tracking.as_ref() {
+                    Some(tracking) => repo
+                        .try_find_reference(tracking.as_ref())
+                        .ok()
+                        .flatten()
+                        .and_then(|r| r.try_id().map(crate::Id::detach)),
+                    None => None,
+                },
+                lease::Expected::Id(id) => Some(*id),
+                lease::Expected::Absent => None,
+            };
+            return Ok((expected != mapping.remote_id).then_some(Status::Rejected(Rejection::Stale { expected })));
+        }
+        let (Some(remote_id), Some(_)) = (mapping.remote_id, new_id) else {
+            return Ok(None);
+        };
+        if mapping.force {
+            return Ok(None);
+        }
+        Ok(if mapping.remote.as_bstr().starts_with(b"refs/tags/") {
+            Some(Status::Rejected(Rejection::AlreadyExists))
+        } else if !repo.has_object(remote_id) {
+            Some(Status::Rejected(Rejection::FetchFirst))
+        } else if !is_fast_forward(repo, mapping)? {
+            Some(Status::Rejected(Rejection::NonFastForward))
+        } else {
+            None
+        })
+    }
+}
+
+/// Return `true` if the remote object of `mapping` is an ancestor of its source, which both have to be commits.
+///
+/// Commit times can't be used to stop the walk early as clocks may be skewed, but generation numbers of the commit-graph can.
+fn is_fast_forward(repo: &crate::Repository, mapping: &Mapping) -> Result<bool, Error> {
+    let (Some(ancestor), Some(descendant)) = (mapping.remote_id, mapping.source.as_ref().map(|s| s.id().to_owned()))
+    else {
+        return Ok(false);
+    };
+    let mut graph = repo.revision_graph::<gix_revwalk::graph::Commit<()>>();
+    let Some(ancestor_generation) = graph
+        .try_lookup_or_insert_commit(ancestor, |_| {})?
+        .map(|commit| commit.generation)
+    else {
+        return Ok(false);
+    };
+    let mut queue = gix_revwalk::PriorityQueue::<gix_date::SecondsSinceUnixEpoch, ObjectId>::new();
+    if let Some(commit) = graph.try_lookup_or_insert_commit(descendant, |_| {})? {
+        queue.insert(commit.commit_time, descendant);
+    }
+    let mut seen = gix_hashtable::HashSet::default();
+    while let Some((_time, id)) = queue.pop() {
+        if id == ancestor {
+            return Ok(true);
+        }
+        if !seen.insert(id) {
+            continue;
+        }
+        let commit = graph.get(&id).expect("queued commits are in graph");
+        if let (Some(generation), Some(ancestor_generation)) = (commit.generation, ancestor_generation) {
+            // Parents always have a lower generation than their children, so the ancestor can't be reached from here.
+            if generation <= ancestor_generation {
+                continue;
+            }
+        }
+        let parents = commit.parents.clone();
+        for parent_id in parents {
+            if let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? {
+                queue.insert(parent.commit_time, parent_id);
+            }
+        }
+    }
+    Ok(false)
+}
@@ -37,6 +37,20 @@ pub(crate) enum WritePackedRefs {
     Only,
 }
 
+/// Describe how to write `.git/FETCH_HEAD` after fetching.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
+pub enum WriteFetchHead {
+    /// Replace the previous content of `FETCH_HEAD` with what was fetched, unless `fetch.writeFetchHead` is `false`.
+    #[default]
+    Overwrite,
+    /// Append what was fetched to the existing entries, similar to `git fetch --append`,
+    /// unless `fetch.writeFetchHead` is `false`.
+    Append,
+    /// Don't write `FETCH_HEAD` at all, similar to `git fetch --no-write-fetch-head`.
+    Never,
+}
+
 /// Describe how to handle tags when fetching
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Tags {
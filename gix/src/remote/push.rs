@@ -0,0 +1,78 @@
+#[cfg(feature = "blocking-network-client")]
+pub use super::connection::push::{mappings, prepare, update, Error, Outcome, Prepare, ProgressId, Update};
+
+/// The local side of a push [Mapping], providing the object to send to the remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A local reference, along with the object it points to after following symbolic references.
+    Ref {
+        /// The full name of the local reference, like `refs/heads/main`.
+        name: gix_ref::FullName,
+        /// The object the reference points to, which may also be an annotated tag.
+        id: gix_hash::ObjectId,
+    },
+    /// An object as it was spelled out in the refspec or resolved from a revision specification.
+    ObjectId(gix_hash::ObjectId),
+}
+
+impl Source {
+    /// Return the id of the object to send to the remote.
+    pub fn id(&self) -> &gix_hash::oid {
+        match self {
+            Source::Ref { id, .. } | Source::ObjectId(id) => id,
+        }
+    }
+
+    /// Return the name of the local reference, if this source is a reference.
+    pub fn as_name(&self) -> Option<&gix_ref::FullNameRef> {
+        match self {
+            Source::Ref { name, .. } => Some(name.as_ref()),
+            Source::ObjectId(_) => None,
+        }
+    }
+}
+
+/// A mapping between something local and the remote reference it should update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// The object to update the `remote` reference with, or `None` if the remote reference should be deleted.
+    pub source: Option<Source>,
+    /// The full name of the reference to update on the remote.
+    pub remote: gix_ref::FullName,
+    /// The object `remote` currently points to as advertised by the remote, or `None` if it doesn't exist there.
+    pub remote_id: Option<gix_hash::ObjectId>,
+    /// If `true`, the update is allowed even if it isn't a fast-forward, as indicated by a `+` refspec.
+    pub force: bool,
+    /// The local remote-tracking reference that mirrors `remote` as per the fetch refspecs of the remote, if there is one.
+    ///
+    /// It will be updated after a successful push.
+    pub tracking: Option<gix_ref::FullName>,
+}
+
+/// Protect the update of a remote reference by requiring it to have a certain value, as used by `git push --force-with-lease`.
+///
+/// A lease makes it safe to perform non-fast-forward updates as these only succeed if nobody else changed the remote
+/// reference in the mean time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    /// The full name of the remote reference to protect, like `refs/heads/main`.
+    pub remote: gix_ref::FullName,
+    /// The value the remote reference is expected to have.
+    pub expected: lease::Expected,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod lease {
+    /// The value a remote reference is expected to have as part of a [Lease][super::Lease].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Expected {
+        /// The remote reference is expected to point to the same object as its local remote-tracking reference,
+        /// or to not exist if there is no such tracking reference.
+        TrackingRef,
+        /// The remote reference is expected to point to the given object.
+        Id(gix_hash::ObjectId),
+        /// The remote reference is expected to not exist.
+        Absent,
+    }
+}
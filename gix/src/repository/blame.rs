@@ -0,0 +1,211 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use gix_hash::ObjectId;
+use gix_traverse::commit::simple::Sorting;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::tree::Blame,
+    Repository,
+};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blame_options {
+    use crate::bstr::BString;
+
+    /// The error returned by [Repository::blame_options()](crate::Repository::blame_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        IgnoreRevsFilePath(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not read the file with revisions to ignore at '{}'", path.display())]
+        ReadIgnoreRevsFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error("Line {line_number} of '{}' isn't a full hexadecimal object id: '{line}'", path.display())]
+        InvalidIgnoreRev {
+            path: std::path::PathBuf,
+            line_number: usize,
+            line: BString,
+            source: gix_hash::decode::Error,
+        },
+        #[error(transparent)]
+        Rewrites(#[from] crate::diff::new_rewrites::Error),
+    }
+}
+
+/// The error returned by [Repository::blame()](crate::Repository::blame()) and
+/// [Repository::blame_incremental()](crate::Repository::blame_incremental()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    DiffResourceCache(#[from] crate::repository::diff::resource_cache::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    Blame(#[from] gix_blame::Error),
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+}
+
+/// The outcome of [Repository::blame()](crate::Repository::blame()).
+///
+/// It dereferences to the [`gix_blame::Outcome`] it wraps.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The hunks of the blamed file along with the commits they were attributed to.
+    pub inner: gix_blame::Outcome,
+    /// Information about each commit that lines were attributed to, keyed by its id.
+    pub commits: HashMap<ObjectId, Commit>,
+}
+
+impl std::ops::Deref for Outcome {
+    type Target = gix_blame::Outcome;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Information about a commit that lines were attributed to, as part of an [`Outcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The author of the commit, with name and email mapped according to the mailmap of the repository.
+    pub author: gix_actor::Signature,
+    /// The committer of the commit, with name and email mapped according to the mailmap of the repository.
+    pub committer: gix_actor::Signature,
+    /// The first paragraph of the commit message.
+    pub summary: BString,
+    /// If `true`, the commit has no parents, and all lines it contains were attributed to it.
+    pub is_boundary: bool,
+}
+
+impl Repository {
+    /// Obtain options for use in [`blame()`](Self::blame()) which are configured like `git blame` would be.
+    ///
+    /// This means that the revisions listed in the file at `blame.ignoreRevsFile` are ignored, and that renames are always
+    /// followed, configured by `diff.renames` and `diff.renameLimit` if set.
+    /// Relative paths to the ignore-revs file are resolved against the work tree, or the `git` directory in bare repositories.
+    /// If the file doesn't exist, it is ignored.
+    pub fn blame_options(&self) -> Result<gix_blame::Options, blame_options::Error> {
+        let mut ignore_revs = HashSet::new();
+        if let Some(path) = self
+            .config
+            .trusted_file_path("blame", None, Blame::IGNORE_REVS_FILE.name)
+            .transpose()?
+        {
+            let path = self.work_dir().unwrap_or_else(|| self.git_dir()).join(path);
+            match std::fs::read(&path) {
+                Ok(buf) => {
+                    for (line_number, line) in buf.lines().enumerate() {
+                        let line = line.find_byte(b'#').map_or(line, |pos| &line[..pos]).trim();
+                        if line.is_empty() {
+                            continue;
+                        }
+                        let id = ObjectId::from_hex(line).map_err(|err| blame_options::Error::InvalidIgnoreRev {
+                            path: path.clone(),
+                            line_number: line_number + 1,
+                            line: line.into(),
+                            source: err,
+                        })?;
+                        ignore_revs.insert(id);
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(blame_options::Error::ReadIgnoreRevsFile { path, source: err }),
+            }
+        }
+
+        let rewrites =
+            crate::diff::new_rewrites(&self.config.resolved, self.config.lenient_config)?.unwrap_or_default();
+        Ok(gix_blame::Options {
+            ranges: Vec::new(),
+            ignore_whitespace: false,
+            ignore_revs,
+            rewrites: Some(rewrites),
+        })
+    }
+
+    /// Attribute each line of the file at `file_path` as seen in the commit `suspect` to the commit that introduced it,
+    /// similar to `git blame`, using `options` which can be obtained with [`blame_options()`](Self::blame_options()).
+    ///
+    /// `file_path` is relative to the root of the repository. The history is traversed by commit time, using the commit-graph
+    /// if it is available and allowed by configuration.
+    /// Authors and committers of the commits lines are attributed to are mapped with the [mailmap](Self::open_mailmap()),
+    /// just like `git blame` does.
+    pub fn blame(
+        &self,
+        file_path: impl AsRef<BStr>,
+        suspect: impl Into<ObjectId>,
+        options: gix_blame::Options,
+    ) -> Result<Outcome, Error> {
+        self.blame_incremental(file_path, suspect, options, |_, _| Ok(()))
+    }
+
+    /// Like [`blame()`](Self::blame()), but call `on_entry` with each entry and the commit it was attributed to as soon
+    /// as it was found, similar to `git blame --incremental`.
+    ///
+    /// See [`gix_blame::file_incremental()`] for details about the order of entries. If `on_entry` fails, the blame is aborted.
+    pub fn blame_incremental(
+        &self,
+        file_path: impl AsRef<BStr>,
+        suspect: impl Into<ObjectId>,
+        options: gix_blame::Options,
+        mut on_entry: impl FnMut(
+            &gix_blame::BlameEntry,
+            &Commit,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>,
+    ) -> Result<Outcome, Error> {
+        let mut resource_cache = self.diff_resource_cache(gix_diff::blob::pipeline::Mode::ToGit, Default::default())?;
+        let traverse = self
+            .rev_walk(Some(suspect.into()))
+            .sorting(Sorting::ByCommitTimeNewestFirst)
+            .all()?
+            .map(|info| {
+                info.map(|info| gix_traverse::commit::Info {
+                    id: info.id,
+                    parent_ids: info.parent_ids,
+                    commit_time: info.commit_time,
+                })
+            });
+
+        let mailmap = self.open_mailmap();
+        let mut commits = HashMap::new();
+        let inner = gix_blame::file_incremental(
+            &self.objects,
+            traverse,
+            &mut resource_cache,
+            file_path.as_ref(),
+            options,
+            |entry| {
+                let commit = match commits.entry(entry.commit_id) {
+                    Entry::Occupied(occupied) => occupied.into_mut(),
+                    Entry::Vacant(vacant) => vacant.insert(self.blame_commit(&mailmap, entry.commit_id)?),
+                };
+                on_entry(entry, commit)
+            },
+        )?;
+        Ok(Outcome { inner, commits })
+    }
+
+    /// Obtain information about the commit with `id` that lines were attributed to.
+    fn blame_commit(&self, mailmap: &gix_mailmap::Snapshot, id: ObjectId) -> Result<Commit, Error> {
+        let commit = self.find_object(id)?.try_into_commit()?;
+        let commit = commit.decode()?;
+        let is_boundary = commit.parents().next().is_none();
+        Ok(Commit {
+            author: mailmap.resolve(commit.author()),
+            committer: mailmap.resolve(commit.committer()),
+            summary: commit.message_summary().into_owned(),
+            is_boundary,
+        })
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress::NestedProgress;
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+
+use crate::{
+    bstr::BStr,
+    bundle,
+    config::{cache::util::ApplyLeniency, tree::Pack},
+    Repository,
+};
+
+impl Repository {
+    /// Assure that this repository can use the pack of the bundle described by `header`, as it uses the same kind of hash
+    /// and contains all of its prerequisite commits, similar to `git bundle verify`.
+    pub fn verify_bundle(&self, header: &bundle::Header) -> Result<(), bundle::verify::Error> {
+        if header.object_hash != self.object_hash() {
+            return Err(bundle::verify::Error::ObjectHashMismatch {
+                bundle: header.object_hash,
+                repository: self.object_hash(),
+            });
+        }
+        let mut missing = Vec::new();
+        for prerequisite in &header.prerequisites {
+            let is_commit = matches!(self.try_find_header(prerequisite.id)?, Some(header) if header.kind() == gix_object::Kind::Commit);
+            if !is_commit {
+                missing.push(prerequisite.id);
+            }
+        }
+        if !missing.is_empty() {
+            return Err(bundle::verify::Error::MissingPrerequisites { missing });
+        }
+        Ok(())
+    }
+
+    /// Verify `bundle` and write its pack along with an index into the object database, similar to `git bundle unbundle`.
+    ///
+    /// References aren't created or updated, but can be obtained from the [header](bundle::File::header) of `bundle`.
+    pub fn unbundle(
+        &self,
+        bundle: &bundle::File,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_pack::bundle::write::Outcome, bundle::unbundle::Error> {
+        self.verify_bundle(&bundle.header)?;
+        let mut pack = bundle.pack().map_err(|source| bundle::unbundle::Error::OpenPack {
+            path: bundle.path.clone(),
+            source,
+        })?;
+        let thread_limit = self.pack_thread_limit()?;
+        let index_version = self
+            .config
+            .resolved
+            .integer("pack", None, Pack::INDEX_VERSION.name)
+            .map(|value| Pack::INDEX_VERSION.try_into_index_version(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(gix_pack::index::Version::V2);
+        let outcome = gix_pack::Bundle::write_to_directory(
+            &mut pack,
+            Some(&self.objects.store_ref().path().join("pack")),
+            progress,
+            should_interrupt,
+            Some(Box::new(self.objects.clone())),
+            gix_pack::bundle::write::Options {
+                thread_limit,
+                index_version,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                object_hash: self.object_hash(),
+            },
+        )?;
+        if let Some(keep_path) = &outcome.keep_path {
+            std::fs::remove_file(keep_path).map_err(bundle::unbundle::Error::RemoveKeepFile)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Write a bundle with all objects selected by the rev-`specs` into `out` and return its header, similar to
+    /// `git bundle create`.
+    ///
+    /// Each of the `specs` is parsed with [`rev_parse()`](Self::rev_parse()), and references mentioned by them are
+    /// provided by the bundle. Specs like `main` or `^v1` include or exclude commits and their history, `v1..main`
+    /// includes `main` without what's reachable from `v1`, and `main^!` only includes the commit `main` points to.
+    /// Commits at the boundary to excluded history become the prerequisites of the bundle.
+    pub fn create_bundle<P>(
+        &self,
+        specs: impl IntoIterator<Item = impl AsRef<BStr>>,
+        out: &mut dyn std::io::Write,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<bundle::Header, bundle::create::Error>
+    where
+        P: NestedProgress,
+        P::SubProgress: 'static,
+    {
+        use gix_revision::Spec;
+
+        let mut buf = Vec::new();
+        let (mut ours, mut theirs, mut refs) = (Vec::new(), Vec::new(), Vec::<bundle::Ref>::new());
+        let mut add_ref = |reference: Option<&gix_ref::Reference>, id: ObjectId| {
+            if let Some(reference) = reference {
+                let name = reference.name.as_bstr();
+                if !refs.iter().any(|r| r.name == name) {
+                    refs.push(bundle::Ref { id, name: name.into() });
+                }
+            }
+        };
+        for spec in specs {
+            let spec = spec.as_ref();
+            let parsed = self.rev_parse(spec)?;
+            let (first, second) = (parsed.first_reference().cloned(), parsed.second_reference().cloned());
+            match parsed.detach() {
+                Spec::Include(id) => {
+                    ours.push(id);
+                    add_ref(first.as_ref(), id);
+                }
+                Spec::Exclude(id) => theirs.push(id),
+                Spec::Range { from, to } => {
+                    theirs.push(from);
+                    ours.push(to);
+                    add_ref(second.as_ref(), to);
+                }
+                Spec::ExcludeParents(id) => {
+                    ours.push(id);
+                    theirs.extend(self.objects.find_commit(&id, &mut buf)?.parents());
+                    add_ref(first.as_ref(), id);
+                }
+                Spec::Merge { .. } | Spec::IncludeOnlyParents(_) => {
+                    return Err(bundle::create::Error::UnsupportedSpec { spec: spec.into() })
+                }
+            }
+        }
+        if refs.is_empty() {
+            return Err(bundle::create::Error::Empty);
+        }
+
+        let selection = crate::pack_objects::select(self, ours, theirs, Default::default(), should_interrupt)?;
+        let mut prerequisites = Vec::with_capacity(selection.boundary.len());
+        for id in selection.boundary.iter().copied() {
+            let comment = self
+                .objects
+                .find_commit(&id, &mut buf)?
+                .message()
+                .summary()
+                .into_owned();
+            prerequisites.push(bundle::Prerequisite { id, comment });
+        }
+        let header = bundle::Header {
+            version: bundle::Version::minimal(self.object_hash(), false),
+            object_hash: self.object_hash(),
+            filter: None,
+            prerequisites,
+            refs,
+        };
+        header.write_to(out)?;
+        crate::pack_objects::write(
+            self,
+            selection,
+            out,
+            crate::pack_objects::write::Options {
+                thin_pack: false,
+                ofs_delta: true,
+            },
+            &mut progress,
+            should_interrupt,
+        )?;
+        Ok(header)
+    }
+}
@@ -18,6 +18,12 @@ pub mod resource_cache {
 
 /// Diff-utilities
 impl Repository {
+    /// Return options to render diffs as text just like `git diff` would, as configured by `diff.context`,
+    /// `diff.interHunkContext`, `diff.noprefix` and `diff.mnemonicPrefix`.
+    pub fn diff_render_options(&self) -> Result<crate::diff::RenderOptions, crate::diff::render_options::Error> {
+        crate::diff::render_options(&self.config.resolved, self.config.lenient_config)
+    }
+
     /// Create a resource cache for diffable objects, and configured with everything it needs to know to perform diffs
     /// faithfully just like `git` would.
     /// `mode` controls what version of a resource should be diffed.
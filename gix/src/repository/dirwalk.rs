@@ -70,6 +70,7 @@ impl Repository {
                 excludes: Some(&mut excludes.inner),
                 objects: &self.objects,
                 explicit_traversal_root: (!options.empty_patterns_match_prefix).then_some(workdir),
+                untracked_cache: None,
             },
             options.into(),
             delegate,
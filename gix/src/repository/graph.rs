@@ -1,3 +1,5 @@
+use crate::repository::write_commit_graph;
+
 impl crate::Repository {
     /// Create a graph data-structure capable of accelerating graph traversals and storing state of type `T` with each commit
     /// it encountered.
@@ -31,4 +33,88 @@ impl crate::Repository {
     pub fn commit_graph(&self) -> Result<gix_commitgraph::Graph, gix_commitgraph::init::Error> {
         gix_commitgraph::at(self.objects.store_ref().path().join("info"))
     }
+
+    /// Write a commit-graph with all commits reachable from the commits at `tips` into the object database,
+    /// as configured by `options`. This is similar to `git commit-graph write --reachable`.
+    ///
+    /// When writing a [layer](gix_commitgraph::write::Split) of a split commit-graph, only commits that aren't part of it yet
+    /// are traversed. [Changed-path Bloom filters](gix_commitgraph::write::Options::changed_paths) are computed by diffing the
+    /// tree of each commit with the one of its first parent.
+    ///
+    /// Note that commit-graphs can't be written for shallow repositories.
+    pub fn write_commit_graph(
+        &self,
+        tips: impl IntoIterator<Item = impl Into<gix_hash::ObjectId>>,
+        options: gix_commitgraph::write::Options,
+    ) -> Result<gix_commitgraph::write::Outcome, write_commit_graph::Error> {
+        use gix_commitgraph::write::Split;
+        use gix_object::FindExt;
+
+        if self.shallow_commits()?.is_some() {
+            return Err(write_commit_graph::Error::Shallow);
+        }
+        let existing = matches!(options.split, Some(Split::Merge | Split::NoMerge))
+            .then(|| self.commit_graph().ok())
+            .flatten();
+        let mut commits = Vec::new();
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut queue: Vec<gix_hash::ObjectId> = tips.into_iter().map(Into::into).collect();
+        let mut buf = Vec::new();
+        while let Some(id) = queue.pop() {
+            if !seen.insert(id) || existing.as_ref().map_or(false, |graph| graph.lookup(id).is_some()) {
+                continue;
+            }
+            let commit = self.objects.find_commit(&id, &mut buf)?;
+            let parents: Vec<_> = commit.parents().collect();
+            queue.extend(parents.iter().copied());
+            commits.push(gix_commitgraph::write::Commit {
+                id,
+                tree: commit.tree(),
+                parents,
+                committer_time: commit.committer.time.seconds.try_into().unwrap_or_default(),
+            });
+        }
+        drop(existing);
+
+        let mut state = gix_diff::tree::State::default();
+        let (mut lhs_buf, mut rhs_buf) = (Vec::new(), Vec::new());
+        Ok(gix_commitgraph::write(
+            &self.objects.store_ref().path().join("info"),
+            commits,
+            self.object_hash(),
+            |commit| {
+                let lhs = match commit.parents.first() {
+                    Some(parent) => {
+                        let tree = self.objects.find_commit_iter(parent, &mut lhs_buf)?.tree_id()?;
+                        self.objects.find_tree_iter(&tree, &mut lhs_buf)?
+                    }
+                    None => gix_object::TreeRefIter::from_bytes(&[], self.object_hash()),
+                };
+                let rhs = self.objects.find_tree_iter(&commit.tree, &mut rhs_buf)?;
+                let mut recorder = gix_diff::tree::Recorder::default();
+                gix_diff::tree::Changes::from(lhs).needed_to_obtain(rhs, &mut state, &self.objects, &mut recorder)?;
+                Ok(Some(
+                    recorder
+                        .records
+                        .into_iter()
+                        .filter_map(|change| {
+                            use gix_diff::tree::recorder::Change::*;
+                            match change {
+                                Addition { entry_mode, path, .. } | Deletion { entry_mode, path, .. } => {
+                                    (!entry_mode.is_tree()).then_some(path)
+                                }
+                                Modification {
+                                    previous_entry_mode,
+                                    entry_mode,
+                                    path,
+                                    ..
+                                } => (!(previous_entry_mode.is_tree() && entry_mode.is_tree())).then_some(path),
+                            }
+                        })
+                        .collect(),
+                ))
+            },
+            options,
+        )?)
+    }
 }
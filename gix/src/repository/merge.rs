@@ -0,0 +1,287 @@
+use gix_merge::blob::builtin_driver::text;
+
+use crate::{
+    bstr::BStr,
+    config::{cache::util::ApplyLeniency, tree::Merge},
+    prelude::ObjectIdExt,
+    Id, Repository,
+};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge_resource_cache {
+    /// The error returned by [Repository::merge_resource_cache()](crate::Repository::merge_resource_cache()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+        #[error(transparent)]
+        ConflictStyle(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        Index(#[from] crate::repository::index_or_load_from_head::Error),
+        #[error(transparent)]
+        AttributeStack(#[from] crate::config::attribute_stack::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree_merge_options {
+    /// The error returned by [Repository::tree_merge_options()](crate::Repository::tree_merge_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Rewrites(#[from] crate::diff::new_rewrites::Error),
+        #[error(transparent)]
+        ConfigMergeRenames(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        ConfigMergeRenameLimit(#[from] crate::config::unsigned_integer::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge_trees {
+    /// The error returned by [Repository::merge_trees()](crate::Repository::merge_trees()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        MergeResourceCache(#[from] super::merge_resource_cache::Error),
+        #[error(transparent)]
+        DiffResourceCache(#[from] crate::repository::diff::resource_cache::Error),
+        #[error(transparent)]
+        TreeMerge(#[from] gix_merge::tree::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge_base {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [Repository::merge_base()](crate::Repository::merge_base()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error("No merge base was found between {first} and {second}")]
+        NotFound { first: ObjectId, second: ObjectId },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge_commits {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [Repository::merge_commits()](crate::Repository::merge_commits()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs, clippy::large_enum_variant)]
+    pub enum Error {
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        ObjectKind(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] super::merge_trees::Error),
+    }
+
+    /// The outcome of [Repository::merge_commits()](crate::Repository::merge_commits()).
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The result of merging the trees of both commits, with the tree of their merge-base as ancestor.
+        pub tree_merge: gix_merge::tree::Outcome,
+        /// All merge-bases that were found between both commits, which is empty if they share no history.
+        ///
+        /// If there is more than one, they were merged into a virtual merge-base first, similar to what `git` does.
+        pub merge_bases: Vec<ObjectId>,
+    }
+}
+
+/// Merge-utilities
+impl Repository {
+    /// Create a platform for merging blobs, configured with all drivers, the conflict style and the diff algorithm
+    /// just like `git` would.
+    ///
+    /// Note that attributes will always be obtained from the current `HEAD` index, or the `HEAD` tree if there is
+    /// no index.
+    pub fn merge_resource_cache(&self) -> Result<gix_merge::blob::Platform, merge_resource_cache::Error> {
+        let index = self.index_or_load_from_head()?;
+        let attr_stack = self
+            .attributes_only(&index, gix_worktree::stack::state::attributes::Source::IdMapping)?
+            .inner;
+
+        let style = self
+            .config
+            .resolved
+            .string_by_key("merge.conflictStyle")
+            .map(|value| Merge::CONFLICT_STYLE.try_into_conflict_style(value))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .unwrap_or_default();
+        let options = gix_merge::blob::platform::Options {
+            text: text::Options {
+                diff_algorithm: self.config.diff_algorithm()?,
+                conflict: text::Conflict::Keep {
+                    style,
+                    marker_size: text::Conflict::DEFAULT_MARKER_SIZE
+                        .try_into()
+                        .expect("default marker size isn't zero"),
+                },
+            },
+            resolve_binary_with: None,
+            default_driver: self
+                .config
+                .resolved
+                .string_by_key("merge.default")
+                .map(std::borrow::Cow::into_owned),
+        };
+        Ok(gix_merge::blob::Platform::new(
+            self.config.merge_drivers(),
+            attr_stack,
+            options,
+        ))
+    }
+
+    /// Return the options to use when merging trees, as configured by `merge.renames` and `merge.renameLimit`, which
+    /// default to their `diff.*` counterparts.
+    ///
+    /// Just like in `git`, rename tracking is enabled by default.
+    pub fn tree_merge_options(&self) -> Result<gix_merge::tree::Options, tree_merge_options::Error> {
+        let lenient = self.config.lenient_config;
+        let renames = self
+            .config
+            .resolved
+            .boolean_by_key("merge.renames")
+            .map(|value| Merge::RENAMES.enrich_error(value))
+            .transpose()
+            .with_leniency(lenient)?;
+        let mut rewrites = match renames {
+            Some(false) => None,
+            Some(true) => Some(Default::default()),
+            None => Some(crate::diff::new_rewrites(&self.config.resolved, lenient)?.unwrap_or_default()),
+        };
+        if let Some(rewrites) = rewrites.as_mut() {
+            if let Some(limit) = self
+                .config
+                .resolved
+                .integer_by_key("merge.renameLimit")
+                .map(|value| Merge::RENAME_LIMIT.try_into_usize(value))
+                .transpose()
+                .with_leniency(lenient)?
+            {
+                rewrites.limit = limit;
+            }
+        }
+        Ok(gix_merge::tree::Options { rewrites })
+    }
+
+    /// Merge the trees `ours` and `theirs` using `ancestor` as their common ancestor, similar to `git merge-tree`,
+    /// and write all new objects into the object database.
+    /// `labels` are used to annotate conflict markers, and `options` are typically obtained with
+    /// [`tree_merge_options()`](Self::tree_merge_options()).
+    ///
+    /// Note that the merge is performed entirely in the object database, and neither the worktree nor the index
+    /// are affected.
+    pub fn merge_trees(
+        &self,
+        ancestor: impl AsRef<gix_hash::oid>,
+        ours: impl AsRef<gix_hash::oid>,
+        theirs: impl AsRef<gix_hash::oid>,
+        labels: gix_merge::blob::Labels<'_>,
+        options: gix_merge::tree::Options,
+    ) -> Result<gix_merge::tree::Outcome, merge_trees::Error> {
+        let mut blob_merge = self.merge_resource_cache()?;
+        let mut diff_cache = self.diff_resource_cache(gix_diff::blob::pipeline::Mode::ToGit, Default::default())?;
+        Ok(gix_merge::tree::merge(
+            ancestor.as_ref(),
+            ours.as_ref(),
+            theirs.as_ref(),
+            labels,
+            &self.objects,
+            |object| gix_odb::Write::write(&self.objects, object),
+            &mut blob_merge,
+            &mut diff_cache,
+            options,
+        )?)
+    }
+
+    /// Return the best common ancestor of the commits `one` and `two`, or an error if there is none.
+    ///
+    /// If there are multiple best common ancestors, the one that was encountered first is returned, similar to
+    /// `git merge-base`.
+    pub fn merge_base(
+        &self,
+        one: impl Into<gix_hash::ObjectId>,
+        two: impl Into<gix_hash::ObjectId>,
+    ) -> Result<Id<'_>, merge_base::Error> {
+        let (first, second) = (one.into(), two.into());
+        let mut graph = self.revision_graph();
+        let bases = gix_revision::merge_base(first, &[second], &mut graph)?;
+        bases
+            .and_then(|bases| bases.first().copied())
+            .map(|id| id.attach(self))
+            .ok_or(merge_base::Error::NotFound { first, second })
+    }
+
+    /// Merge the commits `ours` and `theirs` by merging their trees, using the tree of their merge-base as common ancestor,
+    /// similar to `git merge-tree --write-tree`. `labels` and `options` are passed to [`merge_trees()`](Self::merge_trees()).
+    ///
+    /// If there is no merge-base, the empty tree is used as ancestor. If there are multiple merge-bases, their trees
+    /// are merged into a virtual merge-base first.
+    pub fn merge_commits(
+        &self,
+        ours: impl Into<gix_hash::ObjectId>,
+        theirs: impl Into<gix_hash::ObjectId>,
+        labels: gix_merge::blob::Labels<'_>,
+        options: gix_merge::tree::Options,
+    ) -> Result<merge_commits::Outcome, merge_commits::Error> {
+        let (ours, theirs) = (ours.into(), theirs.into());
+        let mut graph = self.revision_graph();
+        let merge_bases = gix_revision::merge_base(ours, &[theirs], &mut graph)?.unwrap_or_default();
+
+        let tree_of = |id: gix_hash::ObjectId| -> Result<gix_hash::ObjectId, merge_commits::Error> {
+            Ok(self.find_object(id)?.try_into_commit()?.tree_id()?.detach())
+        };
+        let ancestor_tree = match merge_bases.split_first() {
+            None => gix_hash::ObjectId::empty_tree(self.object_hash()),
+            Some((first, rest)) => {
+                let mut virtual_base = tree_of(*first)?;
+                for base in rest {
+                    let ancestor = match gix_revision::merge_base(*first, &[*base], &mut graph)? {
+                        Some(bases) => tree_of(bases[0])?,
+                        None => gix_hash::ObjectId::empty_tree(self.object_hash()),
+                    };
+                    virtual_base = self
+                        .merge_trees(
+                            ancestor,
+                            virtual_base,
+                            tree_of(*base)?,
+                            gix_merge::blob::Labels {
+                                ancestor: None,
+                                current: Some(BStr::new("Temporary merge branch 1")),
+                                other: Some(BStr::new("Temporary merge branch 2")),
+                            },
+                            options,
+                        )?
+                        .tree;
+                }
+                virtual_base
+            }
+        };
+
+        let tree_merge = self.merge_trees(ancestor_tree, tree_of(ours)?, tree_of(theirs)?, labels, options)?;
+        Ok(merge_commits::Outcome {
+            tree_merge,
+            merge_bases,
+        })
+    }
+}
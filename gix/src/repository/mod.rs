@@ -38,6 +38,12 @@ impl crate::Repository {
 
 #[cfg(any(feature = "attributes", feature = "excludes"))]
 pub mod attributes;
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blame")]
+pub mod blame;
+#[cfg(feature = "bundle")]
+mod bundle;
 mod cache;
 mod config;
 ///
@@ -62,18 +68,34 @@ mod kind;
 mod location;
 #[cfg(feature = "mailmap")]
 mod mailmap;
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "merge")]
+pub mod merge;
+#[cfg(feature = "notes")]
+mod notes;
 mod object;
+#[cfg(feature = "pack-objects")]
+mod pack_objects;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
 #[cfg(feature = "revision")]
 mod revision;
 mod shallow;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "stash")]
+mod stash;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
 mod thread_safe;
+#[cfg(feature = "blocking-network-server")]
+mod upload_pack;
 mod worktree;
 
 ///
@@ -94,6 +116,23 @@ pub mod index_from_tree {
     }
 }
 
+///
+#[allow(clippy::empty_docs)]
+pub mod fetch_head {
+    /// The error returned by [Repository::fetch_head()](crate::Repository::fetch_head()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read FETCH_HEAD at \"{}\"", path.display())]
+        Io {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        Decode(#[from] gix_fetchhead::decode::Error),
+    }
+}
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod branch_remote_ref_name {
@@ -128,6 +167,22 @@ pub mod branch_remote_tracking_ref_name {
     }
 }
 
+///
+#[cfg(feature = "pack-objects")]
+pub mod pack_blobs_without_delta {
+    /// The error returned by [Repository::pack_blobs_without_delta()](crate::Repository::pack_blobs_without_delta()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        AttributeStack(#[from] crate::config::attribute_stack::Error),
+        #[error("Could not obtain the attributes of an index entry")]
+        Attributes(#[from] std::io::Error),
+    }
+}
+
 ///
 #[cfg(feature = "attributes")]
 pub mod pathspec_defaults_ignore_case {
@@ -191,3 +246,21 @@ pub mod worktree_archive {
     /// The error returned by [`Repository::worktree_archive()`][crate::Repository::worktree_archive()].
     pub type Error = gix_archive::Error;
 }
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write_commit_graph {
+    /// The error returned by [`Repository::write_commit_graph()`][crate::Repository::write_commit_graph()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Commit-graphs can't be written for shallow repositories")]
+        Shallow,
+        #[error(transparent)]
+        ShallowCommits(#[from] crate::shallow::open::Error),
+        #[error(transparent)]
+        FindCommit(#[from] gix_object::find::existing_object::Error),
+        #[error(transparent)]
+        Write(#[from] gix_commitgraph::write::Error<gix_diff::tree::changes::Error>),
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{bstr::BStr, note, Repository};
+
+impl Repository {
+    /// Return a platform to read and modify the notes in the notes reference configured in `core.notesRef`,
+    /// or `refs/notes/commits` if it isn't set, similar to `git notes`.
+    pub fn notes(&self) -> Result<note::Platform<'_>, note::init::Error> {
+        match self.config.resolved.string_by_key("core.notesRef") {
+            Some(name) => self.notes_with_ref(name.as_ref()),
+            None => self.notes_with_ref(note::DEFAULT_REF.into()),
+        }
+    }
+
+    /// Return a platform to read and modify the notes in the notes reference `name`, similar to `git notes --ref <name>`.
+    ///
+    /// Like in `git`, `name` is expanded to a full reference name, so `ci` and `notes/ci` both refer to `refs/notes/ci`.
+    pub fn notes_with_ref(&self, name: &BStr) -> Result<note::Platform<'_>, note::init::Error> {
+        Ok(note::Platform {
+            repo: self,
+            name: note::expand_ref_name(name)?,
+        })
+    }
+}
@@ -201,6 +201,9 @@ impl crate::Repository {
     ///
     /// It will be created with `constraint` which is most commonly to [only create it][PreviousValue::MustNotExist]
     /// or to [force overwriting a possibly existing tag](PreviousValue::Any).
+    ///
+    /// If `tag.gpgSign` is set and the `signing` feature is enabled, the tag is signed as configured in
+    /// [`signing_options()`](Self::signing_options()), and the message is terminated with a newline if needed.
     #[momo]
     pub fn tag(
         &self,
@@ -211,7 +214,8 @@ impl crate::Repository {
         message: impl AsRef<str>,
         constraint: PreviousValue,
     ) -> Result<Reference<'_>, tag::Error> {
-        let tag = gix_object::Tag {
+        #[cfg_attr(not(feature = "signing"), allow(unused_mut))]
+        let mut tag = gix_object::Tag {
             target: target.as_ref().into(),
             target_kind,
             name: name.as_ref().into(),
@@ -219,6 +223,21 @@ impl crate::Repository {
             message: message.as_ref().into(),
             pgp_signature: None,
         };
+        #[cfg(feature = "signing")]
+        if let Some(options) = self.signing_options_if_enabled(&crate::config::tree::Tag::GPG_SIGN, "tag.gpgSign")? {
+            use gix_object::{bstr::ByteVec, WriteTo};
+            // The signature is appended to the message, which thus has to end in a newline.
+            if !tag.message.ends_with(b"\n") {
+                tag.message.push_byte(b'\n');
+            }
+            let mut data = Vec::new();
+            tag.write_to(&mut data).expect("write to memory works");
+            let default_key = tag.tagger.as_ref().map(|tagger| signing_identity(tagger.to_ref()));
+            let signature = options.sign(&data, default_key.as_ref().map(AsRef::as_ref))?;
+            data.extend_from_slice(&signature);
+            let tag_id = self.write_object_inner(&data, gix_object::Kind::Tag)?;
+            return self.tag_reference(name, tag_id, constraint).map_err(Into::into);
+        }
         let tag_id = self.write_object(&tag)?;
         self.tag_reference(name, tag_id, constraint).map_err(Into::into)
     }
@@ -226,6 +245,9 @@ impl crate::Repository {
     /// Similar to [`commit(…)`][crate::Repository::commit()], but allows to create the commit with `committer` and `author` specified.
     ///
     /// This forces setting the commit time and author time by hand. Note that typically, committer and author are the same.
+    ///
+    /// If `commit.gpgSign` is set and the `signing` feature is enabled, the commit is signed as configured in
+    /// [`signing_options()`](Self::signing_options()).
     pub fn commit_as<'a, 'c, Name, E>(
         &self,
         committer: impl Into<gix_actor::SignatureRef<'c>>,
@@ -265,7 +287,8 @@ impl crate::Repository {
 
         // TODO: possibly use CommitRef to save a few allocations (but will have to allocate for object ids anyway.
         //       This can be made vastly more efficient though if we wanted to, so we lie in the API
-        let commit = gix_object::Commit {
+        #[cfg_attr(not(feature = "signing"), allow(unused_mut))]
+        let mut commit = gix_object::Commit {
             message: message.into(),
             tree,
             author: author.into(),
@@ -274,6 +297,8 @@ impl crate::Repository {
             parents,
             extra_headers: Default::default(),
         };
+        #[cfg(feature = "signing")]
+        self.sign_commit_if_enabled(&mut commit)?;
 
         let commit_id = self.write_object(&commit)?;
         self.edit_reference(RefEdit {
@@ -301,6 +326,23 @@ impl crate::Repository {
         Ok(commit_id)
     }
 
+    /// Sign `commit` with the key of its committer and store the signature in its `gpgsig` header if `commit.gpgSign` is set.
+    #[cfg(feature = "signing")]
+    pub(crate) fn sign_commit_if_enabled(&self, commit: &mut gix_object::Commit) -> Result<(), commit::Error> {
+        if let Some(options) =
+            self.signing_options_if_enabled(&crate::config::tree::Commit::GPG_SIGN, "commit.gpgSign")?
+        {
+            use gix_object::WriteTo;
+            let mut payload = Vec::new();
+            commit.write_to(&mut payload).expect("write to memory works");
+            let mut signature = options.sign(&payload, Some(signing_identity(commit.committer.to_ref()).as_ref()))?;
+            // Each line of a header value is terminated when writing it, and a trailing newline would add an empty line.
+            signature.pop();
+            commit.extra_headers.push(("gpgsig".into(), signature));
+        }
+        Ok(())
+    }
+
     /// Create a new commit object with `message` referring to `tree` with `parents`, and point `reference`
     /// to it. The commit is written without message encoding field, which can be assumed to be UTF-8.
     /// `author` and `committer` fields are pre-set from the configuration, which can be altered
@@ -352,3 +394,13 @@ impl crate::Repository {
         }
     }
 }
+
+/// Return the identity of `signature` as `Name <email>`, which selects the key to sign with if `user.signingKey` isn't set.
+#[cfg(feature = "signing")]
+fn signing_identity(signature: gix_actor::SignatureRef<'_>) -> crate::bstr::BString {
+    let mut identity = crate::bstr::BString::from(signature.name);
+    identity.extend_from_slice(b" <");
+    identity.extend_from_slice(signature.email);
+    identity.extend_from_slice(b">");
+    identity
+}
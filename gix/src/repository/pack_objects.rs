@@ -0,0 +1,97 @@
+use gix_hash::ObjectId;
+use gix_pack::data::output::entry::iter_from_counts::Mode;
+
+use crate::{
+    config::{cache::util::ApplyLeniency, tree::Pack},
+    Repository,
+};
+
+impl Repository {
+    /// Return the way objects should be delta-compressed when writing packs, as configured by `pack.window` and `pack.depth`,
+    /// similar to `git pack-objects`.
+    pub fn pack_delta_compression(&self) -> Result<Mode, crate::config::unsigned_integer::Error> {
+        let read = |key: &'static crate::config::tree::keys::UnsignedInteger, default: usize| {
+            self.config
+                .resolved
+                .integer_filter("pack", None, key.name, &mut self.filter_config_section())
+                .map(|value| key.try_into_usize(value))
+                .transpose()
+                .with_leniency(self.options.lenient_config)
+                .map(|value| value.unwrap_or(default))
+        };
+        Ok(Mode::DeltaCompression {
+            window: read(&Pack::WINDOW, 10)?,
+            depth: read(&Pack::DEPTH, 50)?,
+        })
+    }
+
+    /// Return the ids of all blobs in the index whose path has the `delta` attribute unset, which should never be
+    /// delta-compressed when writing packs.
+    pub fn pack_blobs_without_delta(
+        &self,
+    ) -> Result<gix_hashtable::HashSet<ObjectId>, super::pack_blobs_without_delta::Error> {
+        let index = self.index_or_empty()?;
+        let mut attributes = self.attributes_only(
+            &index,
+            gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping.adjust_for_bare(self.is_bare()),
+        )?;
+        let mut matches = attributes.selected_attribute_matches(["delta"]);
+        let mut out = gix_hashtable::HashSet::default();
+        for entry in index.entries() {
+            let platform = attributes.at_entry(entry.path(&index), Some(entry.mode))?;
+            if platform.matching_attributes(&mut matches)
+                && matches.iter_selected().any(|m| m.assignment.state.is_unset())
+            {
+                out.insert(entry.id);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return the reachability bitmap to use when selecting objects to write into packs, unless `pack.useBitmaps` is `false`
+    /// or there is no bitmap.
+    ///
+    /// The bitmap of the multi-pack index is preferred over the one of any pack.
+    pub fn pack_bitmap(&self) -> Result<Option<gix_pack::bitmap::Index>, crate::config::boolean::Error> {
+        let use_bitmaps = self
+            .config
+            .resolved
+            .boolean_filter("pack", None, Pack::USE_BITMAPS.name, &mut self.filter_config_section())
+            .map(|value| Pack::USE_BITMAPS.enrich_error(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(true);
+        if !use_bitmaps {
+            return Ok(None);
+        }
+        let pack_dir = self.objects.store_ref().path().join("pack");
+        if let Some(bitmap) = gix_pack::multi_index::File::at(pack_dir.join("multi-pack-index"))
+            .ok()
+            .and_then(|index| gix_pack::bitmap::Index::at_multi_index(index).ok())
+        {
+            return Ok(Some(bitmap));
+        }
+        let Ok(entries) = std::fs::read_dir(&pack_dir) else {
+            return Ok(None);
+        };
+        Ok(entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(std::ffi::OsStr::new("bitmap")))
+            .find_map(|path| {
+                gix_pack::index::File::at(path.with_extension("idx"), self.object_hash())
+                    .ok()
+                    .and_then(|index| gix_pack::bitmap::Index::at_pack_index(index).ok())
+            }))
+    }
+
+    /// Return the amount of threads to use at most when writing packs, as configured by `pack.threads`, or `None` to use all cores.
+    pub(crate) fn pack_thread_limit(&self) -> Result<Option<usize>, crate::config::unsigned_integer::Error> {
+        self.config
+            .resolved
+            .integer_filter("pack", None, Pack::THREADS.name, &mut self.filter_config_section())
+            .map(|threads| Pack::THREADS.try_into_usize(threads))
+            .transpose()
+            .with_leniency(self.options.lenient_config)
+    }
+}
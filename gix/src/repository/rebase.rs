@@ -0,0 +1,155 @@
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString},
+    config::{cache::util::ApplyLeniency, tree::Rebase},
+    rebase, Repository,
+};
+
+impl Repository {
+    /// Return the options for use in [`rebase()`](Self::rebase()) as configured by `rebase.autoSquash` and
+    /// `rebase.updateRefs`.
+    pub fn rebase_options(&self) -> Result<rebase::Options, crate::config::boolean::Error> {
+        let boolean = |key: &'static crate::config::tree::keys::Boolean, name: &str| {
+            self.config
+                .resolved
+                .boolean_by_key(name)
+                .map(|value| key.enrich_error(value))
+                .transpose()
+                .with_leniency(self.config.lenient_config)
+                .map(Option::unwrap_or_default)
+        };
+        Ok(rebase::Options {
+            autosquash: boolean(&Rebase::AUTO_SQUASH, "rebase.autoSquash")?,
+            update_refs: boolean(&Rebase::UPDATE_REFS, "rebase.updateRefs")?,
+            ..Default::default()
+        })
+    }
+
+    /// Prepare rebasing the commits of the current branch that aren't reachable from `upstream` onto `upstream`,
+    /// or onto [`options.onto`](rebase::Options::onto) if set, similar to `git rebase [--onto <onto>] <upstream>`.
+    ///
+    /// Merge commits are dropped. The rebase is started with [`Prepare::start()`](rebase::Prepare::start()),
+    /// after optionally adjusting its todo list. Rewritten commits are signed if `commit.gpgSign` is set,
+    /// just like in [`commit_as()`](Self::commit_as()).
+    ///
+    /// Fails if another operation is in progress, or if there are uncommitted changes.
+    pub fn rebase(
+        &self,
+        upstream: impl Into<ObjectId>,
+        options: rebase::Options,
+    ) -> Result<rebase::Prepare<'_>, rebase::init::Error> {
+        if let Some(state) = self.state() {
+            return Err(rebase::init::Error::InProgress { state });
+        }
+        let upstream = upstream.into();
+        let head = self.head()?;
+        let head_id = head.id().ok_or(rebase::init::Error::UnbornHead)?.detach();
+        let head_name = head.referent_name().map(ToOwned::to_owned);
+
+        if self.is_dirty()? {
+            return Err(rebase::init::Error::UncommittedChanges);
+        }
+
+        let mut upstream_commits = gix_hashtable::HashSet::default();
+        for info in self.rev_walk([upstream]).all()? {
+            upstream_commits.insert(info?.id);
+        }
+        let mut commits = Vec::new();
+        for info in self
+            .rev_walk([head_id])
+            .selected(move |id| !upstream_commits.contains(id))?
+        {
+            let info = info?;
+            if info.parent_ids.len() > 1 {
+                continue;
+            }
+            commits.push(rebase::Commit {
+                id: info.id,
+                summary: info.object()?.message()?.summary().into_owned(),
+            });
+        }
+        commits.reverse();
+
+        let mut branches = Vec::new();
+        if options.update_refs {
+            for reference in self.references()?.local_branches()? {
+                let mut reference = reference?;
+                if Some(reference.name()) == head_name.as_ref().map(AsRef::as_ref) {
+                    continue;
+                }
+                let id = reference.peel_to_id_in_place()?.detach();
+                branches.push((reference.name().as_bstr().to_owned(), id));
+            }
+        }
+        let (todo, update_refs) = gix_rebase::todo::build(
+            &commits,
+            &gix_rebase::todo::Options {
+                autosquash: options.autosquash,
+                update_refs: branches,
+            },
+        );
+        let mut state = rebase::State::new(
+            head_name.map(|name| name.as_bstr().to_owned()),
+            options.onto.unwrap_or(upstream),
+            head_id,
+            todo,
+        );
+        state.update_refs = update_refs;
+        Ok(rebase::Prepare {
+            repo: self,
+            state,
+            force: options.force,
+        })
+    }
+
+    /// Return the state of the rebase in progress, as stored in `.git/rebase-merge` by `git rebase` or by us,
+    /// or `None` if there is no such rebase.
+    pub fn rebase_status(&self) -> Result<Option<rebase::State>, rebase::status::Error> {
+        let dir = rebase::state_dir(self);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        Ok(Some(rebase::State::from_dir(&dir, |name| {
+            self.rev_parse_single(name).ok().map(crate::Id::detach)
+        })?))
+    }
+
+    /// Resume the rebase in progress after the user resolved conflicts or amended the commit the rebase stopped at,
+    /// similar to `git rebase --continue`.
+    ///
+    /// All changes must be added to the index, which is used to create the commit that couldn't be created due to conflicts,
+    /// or to amend the current commit if it's the one the rebase stopped at for editing.
+    /// `edit_message` is used as in [`Prepare::start()`](rebase::Prepare::start()).
+    pub fn rebase_continue(
+        &self,
+        edit_message: &mut dyn FnMut(&BStr) -> BString,
+    ) -> Result<rebase::Outcome, rebase::run::Error> {
+        let state = self.rebase_status()?.ok_or(rebase::run::Error::NotInProgress)?;
+        let index = self.open_index()?;
+        if let Some(entry) = index
+            .entries()
+            .iter()
+            .find(|entry| entry.stage() != gix_index::entry::Stage::Unconflicted)
+        {
+            return Err(rebase::run::Error::UnresolvedConflict {
+                path: entry.path(&index).to_owned(),
+            });
+        }
+        if self.has_unstaged_changes()? {
+            return Err(rebase::run::Error::UnstagedChanges);
+        }
+        let tree = crate::worktree::tree::write_tree(self, &index)?;
+        let head = self.head_id()?.detach();
+        rebase::drive(self, state, head, Some(tree), false, edit_message)
+    }
+
+    /// Abort the rebase in progress and restore the branch that was rebased, along with the worktree and the index,
+    /// similar to `git rebase --abort`.
+    pub fn rebase_abort(&self) -> Result<(), rebase::abort::Error> {
+        let state = self.rebase_status()?.ok_or(rebase::abort::Error::NotInProgress)?;
+        crate::worktree::tree::checkout(self, self.find_object(state.orig_head)?.peel_to_tree()?.id, &[])?;
+        rebase::abort(self, &state)?;
+        Ok(())
+    }
+}
@@ -217,6 +217,20 @@ impl crate::Repository {
         Ok(self.head_commit()?.tree_id()?)
     }
 
+    /// Return all entries of `.git/FETCH_HEAD` as written by the most recent fetch, or `None` if it doesn't exist.
+    ///
+    /// Note that `FETCH_HEAD` itself, for instance in [`rev_parse()`](Self::rev_parse()), resolves to the
+    /// object of the first entry.
+    pub fn fetch_head(&self) -> Result<Option<gix_fetchhead::FetchHead>, crate::repository::fetch_head::Error> {
+        let path = self.git_dir().join("FETCH_HEAD");
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(crate::repository::fetch_head::Error::Io { path, source }),
+        };
+        Ok(Some(gix_fetchhead::FetchHead::from_bytes(&data)?))
+    }
+
     /// Find the reference with the given partial or full `name`, like `main`, `HEAD`, `heads/branch` or `origin/other`,
     /// or return an error if it wasn't found.
     ///
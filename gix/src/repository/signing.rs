@@ -0,0 +1,66 @@
+use crate::{
+    bstr::BStr,
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{gpg, keys, Gpg, User},
+    },
+    signing, Repository,
+};
+
+impl Repository {
+    /// Return the options to create and verify signatures of commits and tags as configured by `gpg.format`,
+    /// `gpg.program`, `gpg.<format>.program`, `gpg.ssh.allowedSignersFile` and `user.signingKey`.
+    ///
+    /// If `user.signingKey` is a path to an SSH key, it's interpolated just like the allowed signers file.
+    pub fn signing_options(&self) -> Result<signing::Options, signing::options::Error> {
+        let format = self
+            .config
+            .resolved
+            .string_by_key("gpg.format")
+            .map(|value| Gpg::FORMAT.try_into_format(value))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .unwrap_or_default();
+
+        let snapshot = self.config_snapshot();
+        let program = |key: &str| snapshot.trusted_program(key).map(std::borrow::Cow::into_owned);
+        let is_literal_ssh_key = |key: &BStr| key.starts_with(b"key::") || key.starts_with(b"ssh-");
+        let signing_key = match self.config.resolved.string_by_key("user.signingKey") {
+            Some(key) if format == signing::Format::Ssh && !is_literal_ssh_key(key.as_ref()) => self
+                .config
+                .trusted_file_path("user", None, User::SIGNING_KEY.name)
+                .transpose()?
+                .map(|path| gix_path::into_bstr(path).into_owned()),
+            key => key.map(std::borrow::Cow::into_owned),
+        };
+        Ok(signing::Options {
+            format,
+            signing_key,
+            openpgp_program: program("gpg.openpgp.program").or_else(|| program("gpg.program")),
+            x509_program: program("gpg.x509.program"),
+            ssh_program: program("gpg.ssh.program"),
+            ssh_allowed_signers_file: self
+                .config
+                .trusted_file_path("gpg", Some("ssh".into()), gpg::Ssh::ALLOWED_SIGNERS_FILE.name)
+                .transpose()?
+                .map(std::borrow::Cow::into_owned),
+        })
+    }
+
+    /// Return the signing options if signing is enabled by the boolean `key`, which is found under `name`.
+    pub(crate) fn signing_options_if_enabled(
+        &self,
+        key: &'static keys::Boolean,
+        name: &str,
+    ) -> Result<Option<signing::Options>, signing::options::Error> {
+        let enabled = self
+            .config
+            .resolved
+            .boolean_by_key(name)
+            .map(|value| key.enrich_error(value))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .unwrap_or_default();
+        enabled.then(|| self.signing_options()).transpose()
+    }
+}
@@ -0,0 +1,448 @@
+use std::{collections::HashSet, io::Read};
+
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode, Stage};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    stash,
+    worktree::tree::{checkout, write_tree},
+    Repository,
+};
+
+impl Repository {
+    /// Save the changes in the index and the worktree as new stash entry and reset both to the commit that is checked out,
+    /// similar to `git stash push`.
+    ///
+    /// With [`options.include_untracked`](stash::Options::include_untracked), untracked files are saved and removed as well.
+    /// Return the id of the new stash commit, or `None` if there was nothing to stash.
+    pub fn stash(&self, options: stash::Options) -> Result<Option<ObjectId>, stash::save::Error> {
+        use crate::status::index_worktree::iter::Item;
+        use gix_status::index_as_worktree::{Change as StatusChange, EntryStatus};
+
+        let workdir = self.work_dir().ok_or(stash::save::Error::BareRepository)?;
+        let head = self.head()?;
+        let head_id = head.id().ok_or(stash::save::Error::UnbornHead)?;
+        let branch = head
+            .referent_name()
+            .map_or_else(|| "(no branch)".into(), |name| name.shorten().to_owned());
+        let head_commit = head_id.object()?.into_commit();
+        let head_tree = head_commit.tree_id()?.detach();
+        let description = format!(
+            "{branch}: {} {}",
+            head_id.shorten_or_id(),
+            head_commit.message()?.summary()
+        );
+        let head_id = head_id.detach();
+
+        let index = self.open_index()?;
+        if let Some(entry) = index
+            .entries()
+            .iter()
+            .find(|entry| entry.stage() != Stage::Unconflicted)
+        {
+            return Err(stash::save::Error::Conflict {
+                path: entry.path(&index).to_owned(),
+            });
+        }
+        let index_tree = write_tree(self, &index)?;
+
+        let (mut modified, mut removed, mut untracked) = (Vec::new(), HashSet::<BString>::new(), Vec::new());
+        let status = self
+            .status(gix_features::progress::Discard)?
+            .index_worktree_rewrites(None)
+            .index_worktree_submodules(None)
+            .untracked_files(if options.include_untracked {
+                crate::status::UntrackedFiles::Files
+            } else {
+                crate::status::UntrackedFiles::None
+            })
+            .into_index_worktree_iter(Vec::new())?;
+        for item in status {
+            match item? {
+                Item::Modification { rela_path, status, .. } => match status {
+                    EntryStatus::Change(StatusChange::Removed) => {
+                        removed.insert(rela_path);
+                    }
+                    EntryStatus::Change(StatusChange::Modification { .. } | StatusChange::Type)
+                    | EntryStatus::IntentToAdd => modified.push(rela_path),
+                    EntryStatus::Conflict(_) => return Err(stash::save::Error::Conflict { path: rela_path }),
+                    EntryStatus::Change(StatusChange::SubmoduleModification(_)) | EntryStatus::NeedsUpdate(_) => {}
+                },
+                Item::DirectoryContents { entry, .. }
+                    if entry.status == gix_dir::entry::Status::Untracked
+                        && matches!(
+                            entry.disk_kind,
+                            Some(gix_dir::entry::Kind::File | gix_dir::entry::Kind::Symlink)
+                        ) =>
+                {
+                    untracked.push(entry.rela_path);
+                }
+                Item::DirectoryContents { .. } | Item::Rewrite { .. } => {}
+            }
+        }
+
+        let capabilities = self.filesystem_options()?;
+        let (mut pipeline, _) = self.filter_pipeline(None)?;
+        let mut worktree_state = gix_index::State::clone(&index);
+        for rela_path in modified {
+            let (mode, id) =
+                self.blob_from_worktree(&mut pipeline, &index, &capabilities, rela_path.as_bstr(), |stat| {
+                    let entry = index
+                        .entry_by_path(rela_path.as_bstr())
+                        .expect("status only reports entries that exist");
+                    entry
+                        .mode
+                        .change_to_match_fs(stat, capabilities.symlink, capabilities.executable_bit)
+                        .map_or(entry.mode, |change| change.apply(entry.mode))
+                })?;
+            if mode == Mode::COMMIT {
+                removed.insert(rela_path);
+                continue;
+            }
+            let entry = worktree_state
+                .entry_mut_by_path_and_stage(rela_path.as_bstr(), Stage::Unconflicted)
+                .expect("status only reports entries that exist");
+            entry.id = id;
+            entry.mode = mode;
+            entry.flags.remove(Flags::INTENT_TO_ADD);
+        }
+        worktree_state.remove_entries(|_, path, _| removed.contains(path));
+        let worktree_tree = write_tree(self, &worktree_state)?;
+
+        if index_tree == head_tree && worktree_tree == index_tree && untracked.is_empty() {
+            return Ok(None);
+        }
+
+        let commit =
+            |message: String, tree: ObjectId, parents: Vec<ObjectId>| -> Result<ObjectId, stash::save::Error> {
+                let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+                let author = self.author().ok_or(crate::commit::Error::AuthorMissing)??;
+                let commit = gix_object::Commit {
+                    message: message.into(),
+                    tree,
+                    author: author.into(),
+                    committer: committer.into(),
+                    encoding: None,
+                    parents: parents.into(),
+                    extra_headers: Default::default(),
+                };
+                Ok(self.write_object(&commit)?.detach())
+            };
+        let index_commit = commit(format!("index on {description}\n"), index_tree, vec![head_id])?;
+        let mut parents = vec![head_id, index_commit];
+        if !untracked.is_empty() {
+            let mut untracked_state = gix_index::State::new(self.object_hash());
+            for rela_path in &untracked {
+                let (mode, id) =
+                    self.blob_from_worktree(&mut pipeline, &index, &capabilities, rela_path.as_bstr(), |stat| {
+                        if stat.is_symlink() && capabilities.symlink {
+                            Mode::SYMLINK
+                        } else if capabilities.executable_bit && stat.is_executable() {
+                            Mode::FILE_EXECUTABLE
+                        } else {
+                            Mode::FILE
+                        }
+                    })?;
+                untracked_state.dangerously_push_entry(
+                    Default::default(),
+                    id,
+                    Flags::empty(),
+                    mode,
+                    rela_path.as_bstr(),
+                );
+            }
+            untracked_state.sort_entries();
+            let untracked_tree = write_tree(self, &untracked_state)?;
+            parents.push(commit(
+                format!("untracked files on {description}\n"),
+                untracked_tree,
+                Vec::new(),
+            )?);
+        }
+        let message = match &options.message {
+            Some(message) => format!("On {branch}: {message}"),
+            None => format!("WIP on {description}"),
+        };
+        let stash_commit = commit(message.clone(), worktree_tree, parents)?;
+
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(stash_commit),
+            },
+            name: stash::REF_NAME.try_into().expect("valid"),
+            deref: false,
+        })
+        .map_err(crate::commit::Error::from)?;
+
+        drop(pipeline);
+        drop(index);
+        checkout(self, head_tree, &[])?;
+        for rela_path in untracked {
+            let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+            std::fs::remove_file(&path).map_err(|source| stash::save::Error::ReadFile {
+                path: path.clone(),
+                source,
+            })?;
+            let mut dir = path.parent();
+            while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+                if std::fs::remove_dir(parent).is_err() {
+                    break;
+                }
+                dir = parent.parent();
+            }
+        }
+        Ok(Some(stash_commit))
+    }
+
+    /// Return all stash entries as recorded in the reflog of `refs/stash`, with the most recent one first, similar to
+    /// `git stash list`.
+    ///
+    /// The position of an entry is the `n` in `stash@{n}`, as used in [`stash_apply()`](Self::stash_apply()) and
+    /// [`stash_drop()`](Self::stash_drop()).
+    pub fn stash_list(&self) -> Result<Vec<stash::Entry>, stash::list::Error> {
+        let mut entries = Vec::new();
+        if let Some(reference) = self.try_find_reference(stash::REF_NAME)? {
+            let mut platform = reference.log_iter();
+            if let Some(lines) = platform.all()? {
+                for line in lines {
+                    let line = line?;
+                    entries.push(stash::Entry {
+                        id: line.new_oid(),
+                        message: line.message.to_owned(),
+                    });
+                }
+            }
+        }
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Apply the changes of the stash entry at `stash@{index}` to the index and the worktree, similar to `git stash apply`.
+    ///
+    /// The worktree must not have changes that aren't in the index yet. Changes in the stash are merged with the current
+    /// state, and conflicts are recorded in the index and the worktree to be resolved, like with `git merge`.
+    /// Unless [`options.index`](stash::apply::Options::index) is set, the changes that were stashed from the index
+    /// remain unstaged, except for files that were added.
+    pub fn stash_apply(
+        &self,
+        index: usize,
+        options: stash::apply::Options,
+    ) -> Result<stash::apply::Outcome, stash::apply::Error> {
+        let entry = self
+            .stash_list()?
+            .into_iter()
+            .nth(index)
+            .ok_or(stash::apply::Error::NoSuchEntry { index })?;
+        let stash_commit = self.find_object(entry.id)?.into_commit();
+        let parents: Vec<_> = stash_commit.parent_ids().map(crate::Id::detach).collect();
+        let (Some(base), Some(index_commit)) = (parents.first(), parents.get(1)) else {
+            return Err(stash::apply::Error::NotAStash { id: entry.id });
+        };
+        let tree_of =
+            |id: ObjectId| -> Result<ObjectId, stash::apply::Error> { Ok(self.find_object(id)?.peel_to_tree()?.id) };
+        let (base_tree, stash_index_tree, stash_tree) = (
+            tree_of(*base)?,
+            tree_of(*index_commit)?,
+            stash_commit.tree_id()?.detach(),
+        );
+
+        let current = self.open_index()?;
+        if let Some(entry) = current
+            .entries()
+            .iter()
+            .find(|entry| entry.stage() != Stage::Unconflicted)
+        {
+            return Err(stash::apply::Error::Conflict {
+                path: entry.path(&current).to_owned(),
+            });
+        }
+        if self.has_unstaged_changes()? {
+            return Err(stash::apply::Error::UncommittedChanges);
+        }
+        let current_tree = write_tree(self, &current)?;
+        drop(current);
+
+        let merge_options = self.tree_merge_options()?;
+        let mut target_tree = current_tree;
+        if options.index && stash_index_tree != base_tree {
+            let merge = self.merge_trees(
+                base_tree,
+                current_tree,
+                stash_index_tree,
+                Default::default(),
+                merge_options,
+            )?;
+            if merge.has_conflicts() {
+                return Err(stash::apply::Error::IndexConflicts);
+            }
+            target_tree = merge.tree;
+        }
+        let merge = self.merge_trees(
+            base_tree,
+            current_tree,
+            stash_tree,
+            gix_merge::blob::Labels {
+                ancestor: Some("Stash base".into()),
+                current: Some("Updated upstream".into()),
+                other: Some("Stashed changes".into()),
+            },
+            merge_options,
+        )?;
+        if let Some(untracked) = parents.get(2) {
+            stash::checkout_untracked(self, tree_of(*untracked)?)?;
+        }
+        if merge.has_conflicts() {
+            checkout(self, merge.tree, &merge.conflicts)?;
+            return Ok(stash::apply::Outcome {
+                conflicts: merge.conflicts,
+            });
+        }
+        checkout(self, merge.tree, &[])?;
+
+        let merged = self.open_index()?;
+        let target = gix_index::State::from_tree(&target_tree, &self.objects, self.config.protect_options()?)?;
+        let mut state = gix_index::State::new(self.object_hash());
+        for entry in merged.entries() {
+            let path = entry.path(&merged);
+            match target.entry_by_path(path) {
+                Some(target) if target.id == entry.id && target.mode == entry.mode => {
+                    state.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+                }
+                Some(target) => {
+                    state.dangerously_push_entry(Default::default(), target.id, Flags::empty(), target.mode, path);
+                }
+                None if !options.index => {
+                    state.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+                }
+                None => {}
+            }
+        }
+        for entry in target.entries() {
+            let path = entry.path(&target);
+            if merged.entry_by_path(path).is_none() {
+                state.dangerously_push_entry(Default::default(), entry.id, Flags::empty(), entry.mode, path);
+            }
+        }
+        state.sort_entries();
+        drop(merged);
+        gix_index::File::from_state(state, self.index_path()).write(Default::default())?;
+        Ok(stash::apply::Outcome { conflicts: Vec::new() })
+    }
+
+    /// Like [`stash_apply()`](Self::stash_apply()), but also drop the stash entry at `stash@{index}` if it could be applied
+    /// without conflicts, similar to `git stash pop`.
+    pub fn stash_pop(
+        &self,
+        index: usize,
+        options: stash::apply::Options,
+    ) -> Result<stash::apply::Outcome, stash::apply::Error> {
+        let outcome = self.stash_apply(index, options)?;
+        if outcome.conflicts.is_empty() {
+            self.stash_drop(index)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Remove the stash entry at `stash@{index}` from the reflog of `refs/stash` and return the id of its commit,
+    /// similar to `git stash drop`.
+    ///
+    /// `refs/stash` is updated to point to the most recent remaining entry, or removed if there is none.
+    pub fn stash_drop(&self, index: usize) -> Result<ObjectId, stash::drop::Error> {
+        let no_such_entry = stash::drop::Error::NoSuchEntry { index };
+        let reference = self.try_find_reference(stash::REF_NAME)?.ok_or(no_such_entry)?;
+        let name = reference.name().to_owned();
+        let mut lines = Vec::new();
+        {
+            let mut platform = reference.log_iter();
+            if let Some(iter) = platform.all()? {
+                for line in iter {
+                    lines.push(line?.to_owned());
+                }
+            }
+        }
+        if index >= lines.len() {
+            return Err(stash::drop::Error::NoSuchEntry { index });
+        }
+        let position = lines.len() - 1 - index;
+        let dropped = lines.remove(position);
+        if lines.is_empty() {
+            self.edit_reference(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped.new_oid)),
+                    log: RefLog::AndReference,
+                },
+                name,
+                deref: false,
+            })?;
+            return Ok(dropped.new_oid);
+        }
+
+        if let Some(next) = lines.get_mut(position) {
+            next.previous_oid = dropped.previous_oid;
+        } else {
+            let newest = lines.last().expect("non-empty");
+            self.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: newest.message.clone(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped.new_oid)),
+                    new: Target::Peeled(newest.new_oid),
+                },
+                name: name.clone(),
+                deref: false,
+            })?;
+        }
+        self.refs.reflog_rewrite(name.as_ref(), &lines)?;
+        Ok(dropped.new_oid)
+    }
+
+    /// Hash the file at `rela_path` in the worktree after converting it with `pipeline`, and return its mode as computed
+    /// by `mode` from its metadata along with the id of the written blob.
+    fn blob_from_worktree(
+        &self,
+        pipeline: &mut crate::filter::Pipeline<'_>,
+        index: &gix_index::State,
+        capabilities: &gix_fs::Capabilities,
+        rela_path: &BStr,
+        mode: impl FnOnce(&gix_index::fs::Metadata) -> Mode,
+    ) -> Result<(Mode, ObjectId), stash::save::Error> {
+        let path = self
+            .work_dir()
+            .ok_or(stash::save::Error::BareRepository)?
+            .join(gix_path::from_bstr(rela_path));
+        let read_error = |source| stash::save::Error::ReadFile {
+            path: path.clone(),
+            source,
+        };
+        let stat = gix_index::fs::Metadata::from_path_no_follow(&path).map_err(read_error)?;
+        let mode = mode(&stat);
+        let mut buf = Vec::new();
+        if mode == Mode::COMMIT {
+            return Ok((mode, self.object_hash().null()));
+        } else if mode == Mode::SYMLINK && capabilities.symlink {
+            let target = std::fs::read_link(&path).map_err(read_error)?;
+            buf.extend_from_slice(gix_path::into_bstr(target).as_ref());
+        } else {
+            let file = std::fs::File::open(&path).map_err(read_error)?;
+            pipeline
+                .convert_to_git(file, gix_path::from_bstr(rela_path).as_ref(), index)?
+                .read_to_end(&mut buf)
+                .map_err(read_error)?;
+        }
+        Ok((mode, self.write_blob(&buf)?.detach()))
+    }
+}
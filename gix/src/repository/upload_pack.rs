@@ -0,0 +1,16 @@
+use crate::{upload_pack, Repository};
+
+impl Repository {
+    /// Return a server for the `upload-pack` service of this repository, to let clients fetch from it with
+    /// protocol V2 similar to `git upload-pack`.
+    pub fn upload_pack(&self) -> upload_pack::Server<'_> {
+        upload_pack::Server {
+            repo: self,
+            options: gix_protocol::upload_pack::Options {
+                agent: self.config.user_agent_tuple().1.expect("always set").into_owned(),
+                object_hash: self.object_hash(),
+                trace: self.config.trace_packet(),
+            },
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use gix_hash::ObjectId;
-use gix_object::FindExt;
+use gix_object::{bstr::BString, FindExt};
 
 use crate::{ext::ObjectIdExt, revision, Repository};
 
@@ -95,6 +95,7 @@ pub struct Platform<'repo> {
     pub(crate) parents: gix_traverse::commit::Parents,
     pub(crate) use_commit_graph: Option<bool>,
     pub(crate) commit_graph: Option<gix_commitgraph::Graph>,
+    pub(crate) paths: Vec<BString>,
 }
 
 impl<'repo> Platform<'repo> {
@@ -106,6 +107,7 @@ impl<'repo> Platform<'repo> {
             parents: Default::default(),
             use_commit_graph: None,
             commit_graph: None,
+            paths: Vec::new(),
         }
     }
 }
@@ -143,6 +145,29 @@ impl<'repo> Platform<'repo> {
         self.commit_graph = graph;
         self
     }
+
+    /// Only yield commits that changed any of the given `paths`, which are slash-separated and relative to the root of the
+    /// repository. A path may refer to a file or to a directory, which is changed if anything inside of it changed.
+    ///
+    /// A commit is considered changed if the entries at `paths` differ from the ones in each of its parents, or if any of them exist
+    /// in case of a root commit. This means merge commits are only yielded if they introduce changes of their own.
+    /// Note that, unlike with `git log -- <paths>`, the history isn't simplified so all reachable commits are considered.
+    ///
+    /// If the commit-graph is used and contains changed-path Bloom filters, these are used to quickly skip commits that didn't
+    /// change any of the `paths`.
+    pub fn paths(mut self, paths: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.paths = paths
+            .into_iter()
+            .map(|path| {
+                let mut path = path.into();
+                while path.last() == Some(&b'/') {
+                    path.pop();
+                }
+                path
+            })
+            .collect();
+        self
+    }
 }
 
 /// Produce the iterator
@@ -162,9 +187,19 @@ impl<'repo> Platform<'repo> {
             parents,
             use_commit_graph,
             commit_graph,
+            paths,
         } = self;
+        let use_commit_graph = use_commit_graph.map_or_else(|| repo.config.may_use_commit_graph(), Ok)?;
+        let changed_paths = (!paths.is_empty()).then(|| {
+            iter::ChangedPaths::new(
+                repo,
+                paths,
+                use_commit_graph.then(|| repo.commit_graph().ok()).flatten(),
+            )
+        });
         Ok(revision::Walk {
             repo,
+            changed_paths,
             inner: Box::new(
                 gix_traverse::commit::Simple::filtered(tips, &repo.objects, {
                     // Note that specific shallow handling for commit-graphs isn't needed as these contain
@@ -197,12 +232,7 @@ impl<'repo> Platform<'repo> {
                 })
                 .sorting(sorting)?
                 .parents(parents)
-                .commit_graph(
-                    commit_graph.or(use_commit_graph
-                        .map_or_else(|| self.repo.config.may_use_commit_graph(), Ok)?
-                        .then(|| self.repo.commit_graph().ok())
-                        .flatten()),
-                ),
+                .commit_graph(commit_graph.or(use_commit_graph.then(|| repo.commit_graph().ok()).flatten())),
             ),
         })
     }
@@ -218,9 +248,19 @@ impl<'repo> Platform<'repo> {
 }
 
 pub(crate) mod iter {
+    use gix_commitgraph::bloom;
+    use gix_hash::ObjectId;
+    use gix_object::{
+        bstr::{BStr, BString, ByteSlice},
+        tree::EntryMode,
+        FindExt,
+    };
+    use gix_traverse::commit::simple::Error;
+
     /// The iterator returned by [`crate::revision::walk::Platform::all()`].
     pub struct Walk<'repo> {
         pub(crate) repo: &'repo crate::Repository,
+        pub(crate) changed_paths: Option<ChangedPaths<'repo>>,
         pub(crate) inner:
             Box<dyn Iterator<Item = Result<gix_traverse::commit::Info, gix_traverse::commit::simple::Error>> + 'repo>,
     }
@@ -229,9 +269,136 @@ pub(crate) mod iter {
         type Item = Result<super::Info<'repo>, gix_traverse::commit::simple::Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            self.inner
-                .next()
-                .map(|res| res.map(|info| super::Info::new(info, self.repo)))
+            loop {
+                let info = match self.inner.next()? {
+                    Ok(info) => info,
+                    Err(err) => return Some(Err(err)),
+                };
+                if let Some(changed_paths) = self.changed_paths.as_mut() {
+                    match changed_paths.is_changed(&info) {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+                return Some(Ok(super::Info::new(info, self.repo)));
+            }
+        }
+    }
+
+    /// Decide if commits changed a set of paths, with the help of Bloom filters if possible.
+    pub(crate) struct ChangedPaths<'repo> {
+        repo: &'repo crate::Repository,
+        paths: Vec<BString>,
+        /// The commit-graph along with the keys of each path and its leading directories, if it has Bloom filters.
+        bloom: Option<(gix_commitgraph::Graph, bloom::Settings, Vec<Vec<bloom::Key>>)>,
+        entries: Vec<Option<(ObjectId, EntryMode)>>,
+        buf: Vec<u8>,
+    }
+
+    impl<'repo> ChangedPaths<'repo> {
+        pub(crate) fn new(
+            repo: &'repo crate::Repository,
+            paths: Vec<BString>,
+            graph: Option<gix_commitgraph::Graph>,
+        ) -> Self {
+            let bloom = graph.and_then(|graph| {
+                let settings = *graph.bloom_filter_settings()?;
+                let keys = paths
+                    .iter()
+                    .map(|path| {
+                        let mut path = path.as_bstr();
+                        let mut keys = vec![bloom::Key::new(path, &settings)];
+                        while let Some(pos) = path.rfind_byte(b'/') {
+                            path = path[..pos].as_bstr();
+                            keys.push(bloom::Key::new(path, &settings));
+                        }
+                        keys
+                    })
+                    .collect();
+                Some((graph, settings, keys))
+            });
+            ChangedPaths {
+                repo,
+                paths,
+                bloom,
+                entries: Vec::new(),
+                buf: Vec::new(),
+            }
+        }
+
+        /// Return `true` if the commit described by `info` changed any of our paths compared to all of its parents.
+        pub(crate) fn is_changed(&mut self, info: &gix_traverse::commit::Info) -> Result<bool, Error> {
+            if let Some((graph, settings, keys)) = &self.bloom {
+                let filter = graph.commit_by_id(info.id).and_then(|commit| {
+                    commit
+                        .bloom_filter_settings()
+                        .filter(|commit_settings| commit_settings.is_compatible_with(settings))?;
+                    commit.bloom_filter()
+                });
+                if let Some(filter) = filter {
+                    if !keys.iter().any(|keys| keys.iter().all(|key| filter.contains(key))) {
+                        // None of the paths changed compared to the first parent.
+                        return Ok(false);
+                    }
+                }
+            }
+
+            let tree = self.tree_of(&info.id)?;
+            self.entries.clear();
+            for path in &self.paths {
+                let entry = entry_at_path(self.repo, tree, path.as_bstr(), &mut self.buf)?;
+                self.entries.push(entry);
+            }
+            if info.parent_ids.is_empty() {
+                return Ok(self.entries.iter().any(Option::is_some));
+            }
+            for parent_id in &info.parent_ids {
+                let parent_tree = self.tree_of(parent_id)?;
+                let mut is_same = true;
+                for (path, entry) in self.paths.iter().zip(&self.entries) {
+                    if entry_at_path(self.repo, parent_tree, path.as_bstr(), &mut self.buf)? != *entry {
+                        is_same = false;
+                        break;
+                    }
+                }
+                if is_same {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+
+        fn tree_of(&mut self, commit_id: &gix_hash::oid) -> Result<ObjectId, Error> {
+            Ok(self
+                .repo
+                .objects
+                .find_commit_iter(commit_id, &mut self.buf)?
+                .tree_id()?)
+        }
+    }
+
+    /// Return the id and mode of the entry at the slash-separated `path` in the tree with `tree_id`, if it exists.
+    fn entry_at_path(
+        repo: &crate::Repository,
+        mut tree_id: ObjectId,
+        path: &BStr,
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<(ObjectId, EntryMode)>, Error> {
+        let mut components = path.split_str("/").peekable();
+        while let Some(component) = components.next() {
+            let entry = repo
+                .objects
+                .find_tree_iter(&tree_id, buf)?
+                .filter_map(Result::ok)
+                .find(|entry| entry.filename == component)
+                .map(|entry| (entry.oid.to_owned(), entry.mode));
+            match entry {
+                Some(entry) if components.peek().is_none() => return Ok(Some(entry)),
+                Some((id, mode)) if mode.is_tree() => tree_id = id,
+                _ => return Ok(None),
+            }
         }
+        Ok(None)
     }
 }
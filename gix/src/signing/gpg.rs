@@ -0,0 +1,162 @@
+use std::ffi::OsStr;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    signing::{program, sign, verify, Status, Verification},
+};
+
+/// Sign `payload` with `key` using `program`, which is `gpg` or `gpgsm` or compatible, and return the armored signature.
+pub(super) fn sign(program: &OsStr, key: &BStr, payload: &[u8]) -> Result<BString, sign::Error> {
+    let out = program::run(
+        program,
+        [
+            OsStr::new("--status-fd=2"),
+            "-bsau".as_ref(),
+            gix_path::from_bstr(key).as_os_str(),
+        ],
+        payload,
+    )
+    .map_err(|source| sign::Error::Spawn {
+        program: program.to_owned(),
+        source,
+    })?;
+    // Like `git`, don't trust the exit code alone but also require the status line that announces the signature.
+    if !out.status.success() || out.stderr.find(b"\n[GNUPG:] SIG_CREATED ").is_none() {
+        return Err(sign::Error::Failed {
+            program: program.to_owned(),
+            stderr: out.stderr.into(),
+        });
+    }
+    Ok(out.stdout.into())
+}
+
+/// Verify `signature` of `payload` using `program` with `args`, and interpret the machine-readable status it produces.
+pub(super) fn verify(
+    program: &OsStr,
+    args: &[&str],
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<Verification, verify::Error> {
+    let (_signature_file, signature_path) = program::tempfile(signature)?;
+    let out = program::run(
+        program,
+        args.iter().map(OsStr::new).chain([
+            "--status-fd=1".as_ref(),
+            "--verify".as_ref(),
+            signature_path.as_os_str(),
+            "-".as_ref(),
+        ]),
+        payload,
+    )
+    .map_err(|source| verify::Error::Spawn {
+        program: program.to_owned(),
+        source,
+    })?;
+    let mut verification = parse_status(out.stdout.as_bstr());
+    verification.output = out.stderr.into();
+    Ok(verification)
+}
+
+/// Parse the lines written to the status file descriptor by `gpg --status-fd`.
+///
+/// Only the first line that states the result of the verification is considered, while the full fingerprint
+/// may be provided by another line.
+/// If there is no such line at all, the signature couldn't be verified and is considered bad.
+fn parse_status(status: &BStr) -> Verification {
+    let mut result = None::<(Status, Option<BString>)>;
+    let mut key_id = None;
+    let mut fingerprint = None;
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix(b"[GNUPG:] ") else {
+            continue;
+        };
+        let mut tokens = line.splitn_str(3, " ");
+        let keyword = tokens.next().unwrap_or_default();
+        let status = match keyword {
+            b"GOODSIG" => Status::Good,
+            b"BADSIG" | b"EXPSIG" | b"EXPKEYSIG" | b"REVKEYSIG" => Status::Bad,
+            b"ERRSIG" => Status::UnknownKey,
+            b"VALIDSIG" => {
+                fingerprint = tokens.next().map(|fpr| fpr.as_bstr().to_owned());
+                continue;
+            }
+            _ => continue,
+        };
+        if result.is_none() {
+            key_id = tokens.next().map(|id| id.as_bstr().to_owned());
+            // `ERRSIG` is followed by algorithm identifiers, while all others are followed by the user-id.
+            let signer = (status != Status::UnknownKey)
+                .then(|| tokens.next())
+                .flatten()
+                .map(|uid| uid.as_bstr().to_owned());
+            result = Some((status, signer));
+        }
+    }
+    let (status, signer) = result.unwrap_or((Status::Bad, None));
+    Verification {
+        status,
+        signer,
+        fingerprint: fingerprint.or(key_id),
+        output: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status_of_good_signature() {
+        let verification = parse_status(
+            "[GNUPG:] NEWSIG\n[GNUPG:] KEY_CONSIDERED 0123456789ABCDEF0123456789ABCDEF01234567 0\n\
+             [GNUPG:] SIG_ID abc 2024-01-01 1704067200\n\
+             [GNUPG:] GOODSIG 89ABCDEF01234567 Jane Doe <jane@example.com>\n\
+             [GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2024-01-01 1704067200 0 4 0 22 10 00 0123456789ABCDEF0123456789ABCDEF01234567\n\
+             [GNUPG:] TRUST_ULTIMATE 0 pgp\n"
+                .into(),
+        );
+        assert_eq!(verification.status, Status::Good);
+        assert_eq!(
+            verification.signer.as_ref().map(|s| s.as_bstr()),
+            Some("Jane Doe <jane@example.com>".into())
+        );
+        assert_eq!(
+            verification.fingerprint.as_ref().map(|s| s.as_bstr()),
+            Some("0123456789ABCDEF0123456789ABCDEF01234567".into()),
+            "the full fingerprint is preferred over the key-id"
+        );
+    }
+
+    #[test]
+    fn parse_status_of_bad_and_unknown_signatures() {
+        let verification =
+            parse_status("[GNUPG:] NEWSIG\n[GNUPG:] BADSIG 89ABCDEF01234567 Jane Doe <jane@example.com>\n".into());
+        assert_eq!(verification.status, Status::Bad);
+        assert_eq!(
+            verification.fingerprint.as_ref().map(|s| s.as_bstr()),
+            Some("89ABCDEF01234567".into()),
+            "the key-id is used if there is no fingerprint"
+        );
+
+        let verification = parse_status(
+            "[GNUPG:] NEWSIG\n[GNUPG:] ERRSIG 89ABCDEF01234567 22 10 00 1704067200 9 -\n[GNUPG:] NO_PUBKEY 89ABCDEF01234567\n"
+                .into(),
+        );
+        assert_eq!(verification.status, Status::UnknownKey);
+        assert_eq!(
+            verification.signer, None,
+            "algorithm identifiers aren't mistaken for a signer"
+        );
+        assert_eq!(
+            verification.fingerprint.as_ref().map(|s| s.as_bstr()),
+            Some("89ABCDEF01234567".into())
+        );
+
+        let verification = parse_status("".into());
+        assert_eq!(
+            verification.status,
+            Status::Bad,
+            "no status means the signature couldn't be verified"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Create and verify signatures of commits and tags, similar to `git commit -S`, `git tag -s` and `git verify-commit`.
+//!
+//! Signatures are created and verified by external programs, which are `gpg` for OpenPGP, `gpgsm` for X.509
+//! and `ssh-keygen` for SSH signatures, just like `git` does it. The format of new signatures is controlled by `gpg.format`,
+//! the programs by `gpg.program` and `gpg.<format>.program`, and the key to sign with by `user.signingKey`.
+//! SSH signatures are verified against the principals listed in `gpg.ssh.allowedSignersFile`.
+//!
+//! Use [`Repository::signing_options()`](crate::Repository::signing_options()) to obtain [`Options`] as configured.
+use std::{
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+mod gpg;
+mod program;
+mod ssh;
+
+/// The format of a signature, which also determines the program used to create or verify it.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// An OpenPGP signature, created with `gpg`.
+    #[default]
+    OpenPgp,
+    /// An X.509 signature, created with `gpgsm`.
+    X509,
+    /// An SSH signature, created with `ssh-keygen -Y sign`.
+    Ssh,
+}
+
+impl Format {
+    /// All formats along with the line that starts their signatures.
+    const BEGIN_MARKERS: &'static [(Format, &'static [u8])] = &[
+        (Format::OpenPgp, b"-----BEGIN PGP SIGNATURE-----"),
+        (Format::OpenPgp, b"-----BEGIN PGP MESSAGE-----"),
+        (Format::X509, b"-----BEGIN SIGNED MESSAGE-----"),
+        (Format::Ssh, b"-----BEGIN SSH SIGNATURE-----"),
+    ];
+
+    /// Determine the format of `signature` by the line it starts with, or return `None` if it's not a known signature.
+    pub fn from_signature(signature: &[u8]) -> Option<Self> {
+        Self::BEGIN_MARKERS
+            .iter()
+            .find_map(|(format, marker)| signature.starts_with(marker).then_some(*format))
+    }
+
+    /// The program `git` uses for signatures of this format if none is configured.
+    pub fn default_program(&self) -> &'static str {
+        match self {
+            Format::OpenPgp => "gpg",
+            Format::X509 => "gpgsm",
+            Format::Ssh => "ssh-keygen",
+        }
+    }
+}
+
+/// Options to create and verify signatures, typically obtained with [`Repository::signing_options()`](crate::Repository::signing_options()).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The format of signatures to create, as configured by `gpg.format`.
+    ///
+    /// Signatures are always verified with the program matching their own format.
+    pub format: Format,
+    /// The key to sign with, as configured by `user.signingKey`.
+    ///
+    /// For OpenPGP and X.509, it's anything the program accepts to select a key. If unset, the identity of the committer
+    /// or tagger is used instead.
+    /// For SSH, it's the path to a private key, or to a public key whose private key is held by `ssh-agent`, or a public key
+    /// itself if it's prefixed with `key::` or starts with `ssh-`. It must be set to create SSH signatures.
+    pub signing_key: Option<BString>,
+    /// The program to create and verify OpenPGP signatures with, as configured by `gpg.openpgp.program` or `gpg.program`.
+    /// If `None`, `gpg` is used.
+    pub openpgp_program: Option<OsString>,
+    /// The program to create and verify X.509 signatures with, as configured by `gpg.x509.program`.
+    /// If `None`, `gpgsm` is used.
+    pub x509_program: Option<OsString>,
+    /// The program to create and verify SSH signatures with, as configured by `gpg.ssh.program`.
+    /// If `None`, `ssh-keygen` is used.
+    pub ssh_program: Option<OsString>,
+    /// The file listing the principals and the public keys they may sign with, as configured by `gpg.ssh.allowedSignersFile`.
+    ///
+    /// If `None`, SSH signatures can only be checked for integrity, and are reported with [`Status::UnknownKey`] at best.
+    pub ssh_allowed_signers_file: Option<PathBuf>,
+}
+
+/// The outcome of verifying a signature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// The signature matches the signed data and was made by a known key that is allowed to sign.
+    Good,
+    /// The signature doesn't match the signed data, or it was made by an expired or revoked key.
+    Bad,
+    /// The signature matches the signed data as far as it could be checked, but the key it was made with is unknown.
+    ///
+    /// This is the case if the public key isn't available to `gpg` or `gpgsm`, or if the key isn't listed in the
+    /// allowed signers file for SSH signatures.
+    UnknownKey,
+}
+
+/// The result of [`Options::verify()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Whether or not the signature could be verified.
+    pub status: Status,
+    /// The identity that made the signature if known, like `Name <email>` for OpenPGP or the principal for SSH signatures.
+    pub signer: Option<BString>,
+    /// The fingerprint of the key the signature was made with if known, or the long key-id if the public key isn't available to `gpg`.
+    pub fingerprint: Option<BString>,
+    /// The human-readable output of the verification program, suitable for display.
+    pub output: BString,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod options {
+    /// The error returned by [Repository::signing_options()](crate::Repository::signing_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Format(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        Boolean(#[from] crate::config::boolean::Error),
+        #[error("Could not interpolate the path of the SSH signing key or the allowed signers file")]
+        InterpolatePath(#[from] gix_config::path::interpolate::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod sign {
+    use std::ffi::OsString;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [Options::sign()](crate::signing::Options::sign()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("A signing key must be configured in `user.signingKey` to create {format:?} signatures")]
+        MissingSigningKey { format: crate::signing::Format },
+        #[error("Could not run signing program {program:?}")]
+        Spawn { program: OsString, source: std::io::Error },
+        #[error("Signing program {program:?} failed to sign the data: {stderr}")]
+        Failed { program: OsString, stderr: BString },
+        #[error("Could not write the data to sign or the signing key into a temporary file, or read the signature")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod verify {
+    use std::ffi::OsString;
+
+    /// The error returned by [Options::verify()](crate::signing::Options::verify()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The signature is of an unknown format")]
+        UnknownFormat,
+        #[error("Could not run verification program {program:?}")]
+        Spawn { program: OsString, source: std::io::Error },
+        #[error("Could not write the signature into a temporary file")]
+        Io(#[from] std::io::Error),
+        #[error("Could not obtain the signature of the object")]
+        Decode(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        Options(#[from] crate::signing::options::Error),
+    }
+}
+
+/// Access
+impl Options {
+    /// Return the program to use for creating and verifying signatures of `format`.
+    pub fn program(&self, format: Format) -> &OsStr {
+        match format {
+            Format::OpenPgp => self.openpgp_program.as_deref(),
+            Format::X509 => self.x509_program.as_deref(),
+            Format::Ssh => self.ssh_program.as_deref(),
+        }
+        .unwrap_or_else(|| format.default_program().as_ref())
+    }
+}
+
+/// Signatures
+impl Options {
+    /// Sign `payload` in the configured [`format`](Self::format) and return the signature, which ends with a newline.
+    ///
+    /// `default_key` is used to select the key for OpenPGP and X.509 signatures if [`signing_key`](Self::signing_key)
+    /// isn't set, and is typically the identity of the committer or tagger as `Name <email>`.
+    pub fn sign(&self, payload: &[u8], default_key: Option<&BStr>) -> Result<BString, sign::Error> {
+        let program = self.program(self.format);
+        let key = self.signing_key.as_ref().map(|key| key.as_bstr());
+        let mut signature = match self.format {
+            Format::OpenPgp | Format::X509 => gpg::sign(
+                program,
+                key.or(default_key)
+                    .ok_or(sign::Error::MissingSigningKey { format: self.format })?,
+                payload,
+            )?,
+            Format::Ssh => ssh::sign(
+                program,
+                key.ok_or(sign::Error::MissingSigningKey { format: self.format })?,
+                payload,
+            )?,
+        };
+        // Signatures must not contain carriage returns, in case the program produced them on Windows.
+        signature.retain(|b| *b != b'\r');
+        if signature.last() != Some(&b'\n') {
+            signature.push(b'\n');
+        }
+        Ok(signature)
+    }
+
+    /// Verify that `signature` was made for `payload`, using the program for the format of `signature`.
+    ///
+    /// Note that a signature that doesn't match is reported with [`Status::Bad`], and only failures to run the program
+    /// are errors.
+    pub fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<Verification, verify::Error> {
+        let format = Format::from_signature(signature).ok_or(verify::Error::UnknownFormat)?;
+        let program = self.program(format);
+        match format {
+            Format::OpenPgp => gpg::verify(program, &["--keyid-format=long"], payload, signature),
+            Format::X509 => gpg::verify(program, &[], payload, signature),
+            Format::Ssh => ssh::verify(program, self.ssh_allowed_signers_file.as_deref(), payload, signature),
+        }
+    }
+}
+
+/// Split the data of a signed tag object into the signed payload and the signature, or return `None` if it isn't signed.
+///
+/// The signature starts at the last line that starts a signature of any [`Format`], just like `git` determines it.
+pub fn split_tag_signature(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut line_start = 0;
+    let mut signature_start = None;
+    for line in data.lines_with_terminator() {
+        if Format::from_signature(line).is_some() {
+            signature_start = Some(line_start);
+        }
+        line_start += line.len();
+    }
+    signature_start.map(|pos| data.split_at(pos))
+}
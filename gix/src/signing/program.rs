@@ -0,0 +1,39 @@
+use std::{ffi::OsStr, io::Write, path::PathBuf, process::Stdio};
+
+/// Run `program` with `args` while feeding it `stdin`, and return its output once it exits.
+pub(super) fn run<I, A>(program: &OsStr, args: I, stdin: &[u8]) -> std::io::Result<std::process::Output>
+where
+    I: IntoIterator<Item = A>,
+    A: AsRef<OsStr>,
+{
+    let mut cmd: std::process::Command = gix_command::prepare(program)
+        .args(args.into_iter().map(|arg| arg.as_ref().to_owned()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .into();
+    gix_trace::debug!(cmd = ?cmd, "Running signing program");
+    let mut child = cmd.spawn()?;
+    let mut input = child.stdin.take().expect("configured");
+    // Write from another thread to not deadlock if the program writes a lot before consuming all of its input.
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            // The program may exit without reading everything, which is reflected in its output.
+            input.write_all(stdin).ok();
+        });
+        child.wait_with_output()
+    })
+}
+
+/// Write `data` into a new temporary file that is removed when the returned handle is dropped, and return its path.
+pub(super) fn tempfile(
+    data: &[u8],
+) -> std::io::Result<(gix_tempfile::Handle<gix_tempfile::handle::Writable>, PathBuf)> {
+    let mut file = gix_tempfile::new(
+        std::env::temp_dir(),
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )?;
+    let path = file.with_mut(|file| file.write_all(data).map(|()| file.path().to_owned()))??;
+    Ok((file, path))
+}
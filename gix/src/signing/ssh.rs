@@ -0,0 +1,215 @@
+use std::{ffi::OsStr, path::Path};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    signing::{program, sign, verify, Status, Verification},
+};
+
+/// The namespace of signatures made by `git`, which prevents them from being mistaken for signatures of something else.
+const NAMESPACE: &str = "git";
+
+/// Sign `payload` with `key` using `program`, which is `ssh-keygen` or compatible, and return the armored signature.
+///
+/// `key` is either the path to a key file, or a public key itself if it's prefixed with `key::` or starts with `ssh-`.
+pub(super) fn sign(program: &OsStr, key: &BStr, payload: &[u8]) -> Result<BString, sign::Error> {
+    let literal_key = key
+        .strip_prefix(b"key::")
+        .or_else(|| key.starts_with(b"ssh-").then_some(key.as_bytes()));
+    // Literal public keys need to be written to a file, with the private key expected to be held by `ssh-agent`.
+    let key_file = literal_key.map(program::tempfile).transpose()?;
+    let key_path = match &key_file {
+        Some((_handle, path)) => path.as_os_str().to_owned(),
+        None => gix_path::from_bstr(key).into_owned().into_os_string(),
+    };
+
+    let (_payload_file, payload_path) = program::tempfile(payload)?;
+    let mut args = vec![
+        OsStr::new("-Y"),
+        "sign".as_ref(),
+        "-n".as_ref(),
+        NAMESPACE.as_ref(),
+        "-f".as_ref(),
+        key_path.as_os_str(),
+    ];
+    if key_file.is_some() {
+        args.push("-U".as_ref());
+    }
+    args.push(payload_path.as_os_str());
+    let out = program::run(program, args, &[]).map_err(|source| sign::Error::Spawn {
+        program: program.to_owned(),
+        source,
+    })?;
+
+    let mut signature_path = payload_path.into_os_string();
+    signature_path.push(".sig");
+    let signature = std::fs::read(&signature_path);
+    std::fs::remove_file(&signature_path).ok();
+    match signature {
+        Ok(signature) if out.status.success() => Ok(signature.into()),
+        _ => Err(sign::Error::Failed {
+            program: program.to_owned(),
+            stderr: out.stderr.into(),
+        }),
+    }
+}
+
+/// Verify `signature` of `payload` using `program` and the principals in the `allowed_signers` file.
+///
+/// Like `git`, all principals associated with the key of the signature are tried. If there are none, or if there is no
+/// `allowed_signers` file, the signature is only checked for integrity so it's [unknown](Status::UnknownKey) at best.
+pub(super) fn verify(
+    program: &OsStr,
+    allowed_signers: Option<&Path>,
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<Verification, verify::Error> {
+    let (_signature_file, signature_path) = program::tempfile(signature)?;
+    let run = |args: &[&OsStr], stdin: &[u8]| {
+        program::run(program, args, stdin).map_err(|source| verify::Error::Spawn {
+            program: program.to_owned(),
+            source,
+        })
+    };
+
+    if let Some(allowed_signers) = allowed_signers {
+        let out = run(
+            &[
+                "-Y".as_ref(),
+                "find-principals".as_ref(),
+                "-f".as_ref(),
+                allowed_signers.as_os_str(),
+                "-s".as_ref(),
+                signature_path.as_os_str(),
+            ],
+            &[],
+        )?;
+        let principals = out.stdout.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        if out.status.success() && !principals.is_empty() {
+            let mut verification = None;
+            for principal in principals {
+                let principal = gix_path::from_bstr(principal.as_bstr());
+                let out = run(
+                    &[
+                        "-Y".as_ref(),
+                        "verify".as_ref(),
+                        "-n".as_ref(),
+                        NAMESPACE.as_ref(),
+                        "-f".as_ref(),
+                        allowed_signers.as_os_str(),
+                        "-I".as_ref(),
+                        principal.as_os_str(),
+                        "-s".as_ref(),
+                        signature_path.as_os_str(),
+                    ],
+                    payload,
+                )?;
+                let current = parse_output(out.status.success(), out.stdout, out.stderr);
+                let is_good = current.status == Status::Good;
+                verification = Some(current);
+                if is_good {
+                    break;
+                }
+            }
+            return Ok(verification.expect("at least one principal"));
+        }
+    }
+
+    let out = run(
+        &[
+            "-Y".as_ref(),
+            "check-novalidate".as_ref(),
+            "-n".as_ref(),
+            NAMESPACE.as_ref(),
+            "-s".as_ref(),
+            signature_path.as_os_str(),
+        ],
+        payload,
+    )?;
+    Ok(parse_output(out.status.success(), out.stdout, out.stderr))
+}
+
+/// Interpret the output of `ssh-keygen -Y verify` or `ssh-keygen -Y check-novalidate`, which look like
+/// `Good "git" signature for <principal> with <algorithm> key <fingerprint>` and
+/// `Good "git" signature with <algorithm> key <fingerprint>` respectively if they succeed.
+fn parse_output(success: bool, stdout: Vec<u8>, stderr: Vec<u8>) -> Verification {
+    let mut output = BString::from(stdout);
+    let mut verification = Verification {
+        status: Status::Bad,
+        signer: None,
+        fingerprint: None,
+        output: Default::default(),
+    };
+    let good = format!("Good \"{NAMESPACE}\" signature ");
+    if let Some(line) = output
+        .lines()
+        .next()
+        .filter(|_| success)
+        .and_then(|line| line.strip_prefix(good.as_bytes()))
+    {
+        let (signer, key) = match line.strip_prefix(b"for ") {
+            Some(rest) => {
+                let pos = rest.rfind(" with ").unwrap_or(rest.len());
+                (Some(rest[..pos].as_bstr().to_owned()), &rest[pos..])
+            }
+            None => (None, line),
+        };
+        verification.status = if signer.is_some() {
+            Status::Good
+        } else {
+            Status::UnknownKey
+        };
+        verification.signer = signer;
+        verification.fingerprint = key
+            .rfind(" key ")
+            .map(|pos| key[pos + b" key ".len()..].trim().as_bstr().to_owned());
+    }
+    output.extend_from_slice(&stderr);
+    verification.output = output;
+    verification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_output_of_verify_and_check_novalidate() {
+        let verification = parse_output(
+            true,
+            b"Good \"git\" signature for jane@example.com with ED25519 key SHA256:abc/def+123\n".to_vec(),
+            Vec::new(),
+        );
+        assert_eq!(verification.status, Status::Good);
+        assert_eq!(
+            verification.signer.as_ref().map(|s| s.as_bstr()),
+            Some("jane@example.com".into())
+        );
+        assert_eq!(
+            verification.fingerprint.as_ref().map(|s| s.as_bstr()),
+            Some("SHA256:abc/def+123".into())
+        );
+
+        let verification = parse_output(
+            true,
+            b"Good \"git\" signature with ED25519 key SHA256:abc/def+123\n".to_vec(),
+            Vec::new(),
+        );
+        assert_eq!(verification.status, Status::UnknownKey);
+        assert_eq!(verification.signer, None);
+        assert_eq!(
+            verification.fingerprint.as_ref().map(|s| s.as_bstr()),
+            Some("SHA256:abc/def+123".into())
+        );
+
+        let verification = parse_output(
+            false,
+            Vec::new(),
+            b"Signature verification failed: incorrect signature\n".to_vec(),
+        );
+        assert_eq!(verification.status, Status::Bad);
+        assert_eq!(
+            verification.output,
+            "Signature verification failed: incorrect signature\n"
+        );
+    }
+}
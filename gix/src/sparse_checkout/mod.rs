@@ -0,0 +1,120 @@
+//! Configure which parts of the worktree are checked out, similar to `git sparse-checkout`.
+//!
+//! The patterns are stored in `.git/info/sparse-checkout` and take effect if `core.sparseCheckout` is enabled.
+//! In _cone mode_, which is controlled by `core.sparseCheckoutCone`, patterns are directories that are included recursively,
+//! which allows to store all excluded directories as single entries in a sparse index if `index.sparse` is enabled.
+pub use gix_worktree::sparse::{Directory, Patterns};
+
+use crate::{bstr::BString, Repository};
+
+mod platform;
+
+/// A platform to read and change the sparse-checkout patterns of a repository, and to update its index and worktree to match them.
+///
+/// Obtained with [`Repository::sparse_checkout()`].
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo mut Repository,
+}
+
+/// Options for use in [`Platform::set()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, patterns are directories to include recursively, similar to `git sparse-checkout set --cone`.
+    /// Otherwise, they are `.gitignore` style patterns with matching paths being included, like with `--no-cone`.
+    pub cone: bool,
+    /// If `Some(true)`, write a sparse index in cone mode, similar to `git sparse-checkout set --sparse-index`,
+    /// or `Some(false)` to always write a regular index.
+    /// If `None`, `index.sparse` is left unchanged.
+    pub sparse_index: Option<bool>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            cone: true,
+            sparse_index: None,
+        }
+    }
+}
+
+/// The outcome of changing or reapplying sparse-checkout patterns.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The outcome of checking out files that are newly included by the patterns.
+    pub checkout: gix_worktree_state::checkout::Outcome,
+    /// Files that are newly excluded by the patterns and were removed from the worktree.
+    pub removed: Vec<BString>,
+    /// Files that are excluded by the patterns, but were left in the worktree as they have modifications.
+    /// They remain included, just like `git` does it.
+    pub kept_modified: Vec<BString>,
+    /// Files that are newly included by the patterns, but were left untouched as they already existed in the worktree.
+    pub kept_existing: Vec<BString>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod patterns {
+    /// The error returned by [Platform::patterns()](crate::sparse_checkout::Platform::patterns()).
+    pub type Error = crate::config::sparse_checkout_patterns::Error;
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod update {
+    /// The error returned when changing or reapplying sparse-checkout patterns.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot use a sparse checkout in a bare repository")]
+        BareRepository,
+        #[error("The repository doesn't use a sparse checkout")]
+        NotEnabled,
+        #[error(transparent)]
+        Patterns(#[from] super::patterns::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        SetConfig(#[from] crate::config::set_value::Error),
+        #[error(transparent)]
+        CommitConfig(#[from] crate::config::Error),
+        #[error(transparent)]
+        ReadConfigFile(#[from] gix_config::file::init::from_paths::Error),
+        #[error("Could not write '{}'", path.display())]
+        WriteFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error("Could not remove '{}' from the worktree", path.display())]
+        RemoveFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        Status(#[from] crate::status::Error),
+        #[error(transparent)]
+        StatusIter(#[from] crate::status::index_worktree::iter::Error),
+        #[error(transparent)]
+        StatusItem(#[from] crate::status::index_worktree::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        Checkout(#[from] gix_worktree_state::checkout::Error),
+        #[error(transparent)]
+        ExpandSparseDirectories(#[from] gix_index::sparse::expand::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+    }
+}
+
+impl Repository {
+    /// Return a platform to read and change the sparse-checkout patterns of this repository, similar to `git sparse-checkout`.
+    pub fn sparse_checkout(&mut self) -> Platform<'_> {
+        Platform { repo: self }
+    }
+}
@@ -0,0 +1,272 @@
+use std::{collections::HashSet, sync::atomic::AtomicBool};
+
+use gix_index::entry::{Flags, Stage};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::tree::{Core, Index, Key},
+    sparse_checkout::{patterns, update, Directory, Options, Outcome, Patterns, Platform},
+};
+
+/// Access
+impl Platform<'_> {
+    /// Return the sparse-checkout patterns as stored in `.git/info/sparse-checkout`, or `None` if `core.sparseCheckout`
+    /// isn't enabled or if there is no such file.
+    pub fn patterns(&self) -> Result<Option<Patterns>, patterns::Error> {
+        self.repo.config.sparse_checkout_patterns(self.repo.git_dir())
+    }
+}
+
+/// Mutation
+impl Platform<'_> {
+    /// Enable sparse checkouts and replace all patterns with the given `patterns`, before updating the index and worktree
+    /// to match them, similar to `git sparse-checkout set`.
+    ///
+    /// `patterns` are directories to include recursively if [`options.cone`](Options::cone) is `true`, or `.gitignore`
+    /// style patterns otherwise. Configuration is written to the repository-local configuration file.
+    pub fn set<'a>(
+        mut self,
+        patterns: impl IntoIterator<Item = &'a BStr>,
+        options: Options,
+    ) -> Result<Outcome, update::Error> {
+        self.repo.work_dir().ok_or(update::Error::BareRepository)?;
+        let case = self.case()?;
+        let patterns = if options.cone {
+            Patterns::from_cone_directories(patterns, case)
+        } else {
+            Patterns::from_non_cone_patterns(patterns, case)
+        };
+        self.write_patterns(&patterns)?;
+        let mut values: Vec<(&'static dyn Key, &str)> = vec![
+            (&Core::SPARSE_CHECKOUT, "true"),
+            (&Core::SPARSE_CHECKOUT_CONE, bool_str(options.cone)),
+        ];
+        if let Some(sparse_index) = options.sparse_index {
+            values.push((&Index::SPARSE, bool_str(sparse_index)));
+        }
+        self.set_config(&values)?;
+        self.apply(&patterns)
+    }
+
+    /// Add `patterns` to the existing ones, before updating the index and worktree to match them,
+    /// similar to `git sparse-checkout add`.
+    ///
+    /// Fail if sparse checkouts aren't enabled.
+    pub fn add_patterns<'a>(self, patterns: impl IntoIterator<Item = &'a BStr>) -> Result<Outcome, update::Error> {
+        self.repo.work_dir().ok_or(update::Error::BareRepository)?;
+        let mut current = self.patterns()?.ok_or(update::Error::NotEnabled)?;
+        current.add(patterns);
+        self.write_patterns(&current)?;
+        self.apply(&current)
+    }
+
+    /// Update the index and worktree to match the current patterns, similar to `git sparse-checkout reapply`.
+    ///
+    /// This is useful after files that were kept despite being excluded were committed or reverted.
+    /// Fail if sparse checkouts aren't enabled.
+    pub fn reapply(self) -> Result<Outcome, update::Error> {
+        self.repo.work_dir().ok_or(update::Error::BareRepository)?;
+        let patterns = self.patterns()?.ok_or(update::Error::NotEnabled)?;
+        self.apply(&patterns)
+    }
+
+    /// Check out all files and disable sparse checkouts, similar to `git sparse-checkout disable`.
+    ///
+    /// Like `git`, the patterns file is overwritten to include everything.
+    pub fn disable(mut self) -> Result<Outcome, update::Error> {
+        self.repo.work_dir().ok_or(update::Error::BareRepository)?;
+        let patterns = Patterns::from_non_cone_patterns(Some("/*".into()), self.case()?);
+        self.write_patterns(&patterns)?;
+        let outcome = self.apply(&patterns)?;
+        self.set_config(&[(&Core::SPARSE_CHECKOUT, "false")])?;
+        Ok(outcome)
+    }
+}
+
+/// Utilities
+impl Platform<'_> {
+    fn case(&self) -> Result<gix_glob::pattern::Case, crate::config::boolean::Error> {
+        Ok(if self.repo.config.fs_capabilities()?.ignore_case {
+            gix_glob::pattern::Case::Fold
+        } else {
+            gix_glob::pattern::Case::Sensitive
+        })
+    }
+
+    fn write_patterns(&self, patterns: &Patterns) -> Result<(), update::Error> {
+        let path = self.repo.git_dir().join("info").join("sparse-checkout");
+        let mut buf = Vec::new();
+        patterns.write_to(&mut buf).expect("writing to memory never fails");
+        std::fs::create_dir_all(path.parent().expect("parent exists"))
+            .and_then(|_| std::fs::write(&path, buf))
+            .map_err(|source| update::Error::WriteFile { path, source })
+    }
+
+    /// Persist `values` in the local configuration file and apply them to the in-memory configuration.
+    fn set_config(&mut self, values: &[(&'static dyn Key, &str)]) -> Result<(), update::Error> {
+        let path = self.repo.common_dir().join("config");
+        let mut local_config = gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local)?;
+        for (key, value) in values {
+            local_config
+                .set_raw_value(key.section().name(), None, key.name(), *value)
+                .expect("valid at compile time");
+        }
+        std::fs::write(&path, local_config.to_bstring()).map_err(|source| update::Error::WriteFile { path, source })?;
+
+        let mut config = self.repo.config_snapshot_mut();
+        for (key, value) in values {
+            config.set_value(*key, *value)?;
+        }
+        config.commit()?;
+        Ok(())
+    }
+
+    /// Update the index and the worktree to match `patterns`.
+    ///
+    /// Files that are newly excluded are removed from the worktree unless they are modified, and files that are newly
+    /// included are checked out unless they already exist. Only these files are touched.
+    fn apply(&self, patterns: &Patterns) -> Result<Outcome, update::Error> {
+        use crate::status::index_worktree::iter::Item;
+        use gix_status::index_as_worktree::{Change, EntryStatus};
+
+        let repo = &*self.repo;
+        let workdir = repo.work_dir().ok_or(update::Error::BareRepository)?;
+        let previous = repo.open_index()?;
+        let mut index = previous.clone();
+        let validate = repo.config.protect_options()?;
+        gix_worktree_state::apply_sparse_checkout(&mut index, patterns, &repo.objects, validate)?;
+
+        // Entries that aren't in the previous index were contained in sparse directories.
+        let was_excluded = |path: &BStr| {
+            previous
+                .entry_by_path(path)
+                .map_or(true, |entry| entry.flags.contains(Flags::SKIP_WORKTREE))
+        };
+        let (mut newly_excluded, mut newly_included) = (Vec::new(), Vec::new());
+        for (idx, entry) in index.entries().iter().enumerate() {
+            if entry.mode.is_sparse() || entry.mode.is_submodule() {
+                continue;
+            }
+            let path = entry.path(&index);
+            match (entry.flags.contains(Flags::SKIP_WORKTREE), was_excluded(path)) {
+                (true, false) => newly_excluded.push(path.to_owned()),
+                (false, true) => newly_included.push(idx),
+                _ => {}
+            }
+        }
+
+        let mut modified = HashSet::<BString>::new();
+        if !newly_excluded.is_empty() {
+            let status = repo
+                .status(gix_features::progress::Discard)?
+                .index_worktree_rewrites(None)
+                .index_worktree_submodules(None)
+                .untracked_files(crate::status::UntrackedFiles::None)
+                .into_index_worktree_iter(Vec::new())?;
+            for item in status {
+                if let Item::Modification { rela_path, status, .. } = item? {
+                    match status {
+                        EntryStatus::Change(Change::Removed) | EntryStatus::NeedsUpdate(_) => {}
+                        _ => {
+                            modified.insert(rela_path);
+                        }
+                    }
+                }
+            }
+        }
+        drop(previous);
+
+        let (mut removed, mut kept_modified) = (Vec::new(), Vec::new());
+        for rela_path in newly_excluded {
+            if modified.contains(&rela_path) {
+                if let Some(entry) = index.entry_mut_by_path_and_stage(rela_path.as_bstr(), Stage::Unconflicted) {
+                    entry.flags.set_skip_worktree(false);
+                }
+                kept_modified.push(rela_path);
+                continue;
+            }
+            let path = workdir.join(gix_path::from_bstr(rela_path.as_bstr()));
+            match std::fs::remove_file(&path) {
+                Ok(()) => removed.push(rela_path),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(source) => return Err(update::Error::RemoveFile { path, source }),
+            }
+            let mut dir = path.parent();
+            while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+                if std::fs::remove_dir(parent).is_err() {
+                    break;
+                }
+                dir = parent.parent();
+            }
+        }
+
+        let mut kept_existing = Vec::new();
+        newly_included.retain(|idx| {
+            let rela_path = index.entries()[*idx].path(&index);
+            let exists = workdir.join(gix_path::from_bstr(rela_path)).symlink_metadata().is_ok();
+            if exists {
+                kept_existing.push(rela_path.to_owned());
+            }
+            !exists
+        });
+
+        let checkout = if newly_included.is_empty() {
+            Default::default()
+        } else {
+            // Only check out the newly included entries, and restore the flags of all others afterwards.
+            let flags: Vec<_> = index.entries().iter().map(|entry| entry.flags).collect();
+            for entry in index.entries_mut() {
+                entry.flags.insert(Flags::SKIP_WORKTREE);
+            }
+            for idx in newly_included {
+                index.entries_mut()[idx].flags.remove(Flags::SKIP_WORKTREE);
+            }
+            let mut opts = repo
+                .config
+                .checkout_options(repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+            opts.sparse_checkout = None;
+            let res = gix_worktree_state::checkout(
+                &mut index,
+                workdir,
+                repo.objects.clone().into_arc()?,
+                &gix_features::progress::Discard,
+                &gix_features::progress::Discard,
+                &AtomicBool::default(),
+                opts,
+            );
+            for (entry, flags) in index.entries_mut().iter_mut().zip(flags) {
+                entry.flags = flags;
+            }
+            res?
+        };
+
+        let use_sparse_index = matches!(
+            repo.config.sparse_options()?.sparse_mode(),
+            gix_index::sparse::Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs
+        );
+        if use_sparse_index && patterns.is_cone() {
+            index.collapse_sparse_directories(
+                |dir| patterns.directory(dir) == Directory::Excluded,
+                |tree| repo.write_object(tree).map(crate::Id::detach),
+            )?;
+        } else {
+            index.expand_sparse_directories(&repo.objects, validate, |_| true)?;
+        }
+        index.write(Default::default())?;
+
+        Ok(Outcome {
+            checkout,
+            removed,
+            kept_modified,
+            kept_existing,
+        })
+    }
+}
+
+fn bool_str(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
@@ -0,0 +1,227 @@
+//! Save local changes as stash entries and bring them back later, similar to `git stash`.
+//!
+//! A stash entry is a commit whose tree is the state of the worktree, with the commit that was checked out
+//! as first parent, a commit with the state of the index as second parent, and optionally a commit with all
+//! untracked files as third parent. Entries are listed from the reflog of `refs/stash`, with the most recent one first.
+use std::sync::atomic::AtomicBool;
+
+use gix_hash::ObjectId;
+
+use crate::{bstr::BString, Repository};
+
+/// The name of the reference whose reflog holds all stash entries.
+pub const REF_NAME: &str = "refs/stash";
+
+/// Options for use in [`Repository::stash()`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The message to describe the stash entry with, or `None` to describe it with the commit it's based on,
+    /// similar to `git stash push --message`.
+    pub message: Option<BString>,
+    /// If `true`, untracked files that aren't ignored are stashed as well and removed from the worktree,
+    /// similar to `git stash push --include-untracked`.
+    pub include_untracked: bool,
+}
+
+/// An entry as listed by [`Repository::stash_list()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The id of the stash commit, with the state of the worktree.
+    pub id: ObjectId,
+    /// The message of the reflog entry, like `WIP on main: 1234567 subject`.
+    pub message: BString,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod save {
+    use crate::bstr::BString;
+
+    /// The error returned by [Repository::stash()](crate::Repository::stash()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot stash changes in a bare repository")]
+        BareRepository,
+        #[error("Cannot stash changes without a commit checked out")]
+        UnbornHead,
+        #[error("The path '{path}' has conflicts which have to be resolved first")]
+        Conflict { path: BString },
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        Index(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        Status(#[from] crate::status::Error),
+        #[error(transparent)]
+        StatusIter(#[from] crate::status::index_worktree::iter::Error),
+        #[error(transparent)]
+        StatusItem(#[from] crate::status::index_worktree::Error),
+        #[error(transparent)]
+        FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+        #[error(transparent)]
+        ConvertToGit(#[from] crate::filter::pipeline::convert_to_git::Error),
+        #[error(transparent)]
+        FilesystemOptions(#[from] crate::config::boolean::Error),
+        #[error("Could not read '{}' from the worktree", path.display())]
+        ReadFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::worktree::write_tree::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        SignatureTime(#[from] crate::config::time::Error),
+        #[error(transparent)]
+        Checkout(#[from] crate::worktree::checkout::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod list {
+    /// The error returned by [Repository::stash_list()](crate::Repository::stash_list()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error("Could not read the reflog of the stash")]
+        ReadLog(#[from] std::io::Error),
+        #[error(transparent)]
+        DecodeLog(#[from] gix_ref::file::log::iter::decode::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod apply {
+    /// Options for use in [`Repository::stash_apply()`](crate::Repository::stash_apply()) and
+    /// [`Repository::stash_pop()`](crate::Repository::stash_pop()).
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct Options {
+        /// If `true`, also restore the changes that were added to the index, similar to `git stash apply --index`.
+        pub index: bool,
+    }
+
+    /// The outcome of applying a stash entry.
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// Conflicts between the stashed changes and the current state, which are recorded in the index and in the worktree
+        /// and have to be resolved manually. The stash entry is kept if there are any.
+        pub conflicts: Vec<gix_merge::tree::Conflict>,
+    }
+
+    /// The error returned by [Repository::stash_apply()](crate::Repository::stash_apply()) and
+    /// [Repository::stash_pop()](crate::Repository::stash_pop()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot apply a stash in a bare repository")]
+        BareRepository,
+        #[error("There is no stash entry at stash@{{{index}}}")]
+        NoSuchEntry { index: usize },
+        #[error("The commit {id} isn't a stash entry as it lacks the commit with the state of the index")]
+        NotAStash { id: gix_hash::ObjectId },
+        #[error("Cannot apply a stash with uncommitted changes in the worktree")]
+        UncommittedChanges,
+        #[error("The path '{path}' has conflicts which have to be resolved first")]
+        Conflict { path: crate::bstr::BString },
+        #[error("Conflicts in the index, try again without restoring the index")]
+        IndexConflicts,
+        #[error("The untracked file '{path}' of the stash already exists in the worktree")]
+        UntrackedFileExists { path: crate::bstr::BString },
+        #[error(transparent)]
+        List(#[from] super::list::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::status::is_dirty::Error),
+        #[error(transparent)]
+        Index(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::worktree::write_tree::Error),
+        #[error(transparent)]
+        TreeMergeOptions(#[from] crate::repository::merge::tree_merge_options::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] crate::repository::merge::merge_trees::Error),
+        #[error(transparent)]
+        ProtectOptions(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] gix_index::init::from_tree::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        Checkout(#[from] crate::worktree::checkout::Error),
+        #[error(transparent)]
+        CheckoutUntracked(#[from] gix_worktree_state::checkout::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+        #[error(transparent)]
+        Drop(#[from] super::drop::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod drop {
+    /// The error returned by [Repository::stash_drop()](crate::Repository::stash_drop()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("There is no stash entry at stash@{{{index}}}")]
+        NoSuchEntry { index: usize },
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error("Could not read the reflog of the stash")]
+        ReadLog(#[from] std::io::Error),
+        #[error(transparent)]
+        DecodeLog(#[from] gix_ref::file::log::iter::decode::Error),
+        #[error(transparent)]
+        EditReference(#[from] crate::reference::edit::Error),
+        #[error(transparent)]
+        RewriteLog(#[from] gix_ref::file::log::rewrite::Error),
+    }
+}
+
+/// Write all files of the tree with `id` into the worktree of `repo`, leaving the index untouched.
+pub(crate) fn checkout_untracked(repo: &Repository, id: ObjectId) -> Result<(), apply::Error> {
+    let workdir = repo.work_dir().ok_or(apply::Error::BareRepository)?;
+    let state = gix_index::State::from_tree(&id, &repo.objects, repo.config.protect_options()?)?;
+    for entry in state.entries() {
+        let path = entry.path(&state);
+        if workdir.join(gix_path::from_bstr(path)).symlink_metadata().is_ok() {
+            return Err(apply::Error::UntrackedFileExists { path: path.to_owned() });
+        }
+    }
+    let mut index = gix_index::File::from_state(state, repo.index_path());
+    let opts = repo
+        .config
+        .checkout_options(repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+    gix_worktree_state::checkout(
+        &mut index,
+        workdir,
+        repo.objects.clone().into_arc()?,
+        &gix_features::progress::Discard,
+        &gix_features::progress::Discard,
+        &AtomicBool::default(),
+        opts,
+    )?;
+    Ok(())
+}
@@ -23,6 +23,8 @@ pub enum Error {
     StatOptions(#[from] config::stat_options::Error),
     #[error(transparent)]
     ResourceCache(#[from] crate::diff::resource_cache::Error),
+    #[error(transparent)]
+    ExcludesFile(#[from] crate::config::exclude_stack::Error),
 }
 
 /// Options for use with [Repository::index_worktree_status()].
@@ -75,6 +77,10 @@ impl Repository {
     ///     - A progress indication for index modification checks.
     /// * `should_interrupt`
     ///     - A flag to stop the whole operation.
+    /// * `untracked_cache`
+    ///     - If `Some(cache)`, it's used to accelerate the directory walk, and updated as a by-product if the walk completed.
+    ///       Typically this is a copy of the [untracked cache of the index](gix_index::State::untracked()), which can be set
+    ///       back if it changed.
     /// * `options`
     ///     - Additional configuration for all parts of the operation.
     ///
@@ -95,6 +101,7 @@ impl Repository {
         submodule: impl SubmoduleStatus<Output = U, Error = E> + Send + Clone,
         progress: &mut dyn gix_features::progress::Progress,
         should_interrupt: &AtomicBool,
+        untracked_cache: Option<&mut gix_index::extension::UntrackedCache>,
         options: Options,
     ) -> Result<gix_status::index_as_worktree_with_renames::Outcome, Error>
     where
@@ -134,6 +141,14 @@ impl Repository {
                 new_root: Some(workdir.to_owned()),
             },
         )?;
+        let stat_options = self.stat_options()?;
+        let (info_exclude, excludes_file) = match untracked_cache {
+            Some(_) => (
+                Some(self.git_dir().join("info").join("exclude")),
+                self.config.excludes_file_or_default()?,
+            ),
+            None => (None, None),
+        };
 
         let out = gix_status::index_as_worktree_with_renames(
             index,
@@ -151,6 +166,12 @@ impl Repository {
                     git_dir_realpath: git_dir_realpath.as_path(),
                     current_dir: cwd,
                     ignore_case_index_lookup: accelerate_lookup.as_ref(),
+                    untracked_cache: untracked_cache.map(|cache| gix_dir::walk::UntrackedCache {
+                        cache,
+                        info_exclude: info_exclude.as_deref(),
+                        excludes_file: excludes_file.as_deref(),
+                        stat: stat_options,
+                    }),
                 },
             },
             gix_status::index_as_worktree_with_renames::Options {
@@ -159,7 +180,7 @@ impl Repository {
                 tracked_file_modifications: gix_status::index_as_worktree::Options {
                     fs: fs_caps,
                     thread_limit: options.thread_limit,
-                    stat: self.stat_options()?,
+                    stat: stat_options,
                 },
                 dirwalk: options.dirwalk_options.map(Into::into),
                 rewrites: options.rewrites,
@@ -275,6 +296,8 @@ mod submodule_status {
 ///
 /// Changes to the index are collected and it's possible to write the index back using [iter::Outcome::write_changes()].
 /// Note that these changes are not observable, they will always be kept.
+/// This includes the untracked cache, which is updated by the directory walk as [configured](crate::status::Platform::untracked_cache()),
+/// and the token of the [filesystem monitor](crate::status::Platform::fs_monitor()) along with the entries it considers unchanged.
 ///
 /// ### Parallel Operation
 ///
@@ -303,6 +326,8 @@ pub struct Iter {
     out: Option<iter::Outcome>,
     /// The set of `(entry_index, change)` we extracted in order to potentially write back the index with the changes applied.
     changes: Vec<(usize, iter::ApplyChange)>,
+    /// The indices of all entries that were reported as changed, which the filesystem monitor must not consider valid.
+    changed_entries: Vec<usize>,
 }
 
 ///
@@ -322,6 +347,16 @@ pub mod iter {
         NewStat(crate::index::entry::Stat),
     }
 
+    /// The information needed to update the fsmonitor extension of the index after a status run.
+    struct FsMonitorUpdate {
+        /// The token returned by the filesystem monitor.
+        token: gix_status::fsmonitor::Token,
+        /// If `true`, all entries were checked, which means that those that aren't `changed` are unchanged.
+        all_checked: bool,
+        /// The indices of entries that were reported as changed.
+        changed: Vec<usize>,
+    }
+
     /// The data the thread sends over to the receiving iterator.
     pub struct Outcome {
         /// The outcome of the index-to-worktree comparison operation.
@@ -330,6 +365,10 @@ pub mod iter {
         pub index: crate::worktree::IndexPersistedOrInMemory,
         skip_hash: bool,
         changes: Option<Vec<(usize, iter::ApplyChange)>>,
+        /// The untracked cache to set in the index, if it differs from the one in the index, with `Some(None)` removing it.
+        untracked_cache: Option<Option<gix_index::extension::UntrackedCache>>,
+        /// The fsmonitor extension to set in the index, with `Some(None)` removing it.
+        fs_monitor: Option<Option<FsMonitorUpdate>>,
     }
 
     impl Outcome {
@@ -339,20 +378,27 @@ pub mod iter {
         /// additional work can be prevented by writing the changes back to the index.
         pub fn has_changes(&self) -> bool {
             self.changes.as_ref().map_or(false, |changes| !changes.is_empty())
+                || self.untracked_cache.is_some()
+                || self.fs_monitor.is_some()
         }
 
         /// Write the changes if there are any back to the index file.
         /// This can only be done once as the changes are consumed in the process, if there were any.
         pub fn write_changes(&mut self) -> Option<Result<(), gix_index::file::write::Error>> {
             let _span = gix_features::trace::coarse!("gix::status::index_worktree::iter::Outcome::write_changes()");
-            let changes = self.changes.take()?;
+            let changes = self.changes.take();
+            let untracked_cache = self.untracked_cache.take();
+            let fs_monitor = self.fs_monitor.take();
+            if changes.is_none() && untracked_cache.is_none() && fs_monitor.is_none() {
+                return None;
+            }
             let mut index = match &self.index {
                 IndexPersistedOrInMemory::Persisted(persisted) => (***persisted).clone(),
                 IndexPersistedOrInMemory::InMemory(index) => index.clone(),
             };
 
             let entries = index.entries_mut();
-            for (entry_index, change) in changes {
+            for (entry_index, change) in changes.into_iter().flatten() {
                 let entry = &mut entries[entry_index];
                 match change {
                     ApplyChange::SetSizeToZero => {
@@ -363,6 +409,25 @@ pub mod iter {
                     }
                 }
             }
+            if let Some(untracked_cache) = untracked_cache {
+                index.set_untracked(untracked_cache);
+            }
+            if let Some(fs_monitor) = fs_monitor {
+                let fs_monitor = fs_monitor.map(|update| {
+                    use gix_index::entry::Flags;
+                    let entries = index.entries_mut();
+                    if update.all_checked {
+                        for entry in entries.iter_mut().filter(|entry| !entry.mode.is_submodule()) {
+                            entry.flags.insert(Flags::FSMONITOR_VALID);
+                        }
+                    }
+                    for entry_index in update.changed {
+                        entries[entry_index].flags.remove(Flags::FSMONITOR_VALID);
+                    }
+                    gix_index::extension::FsMonitor::from_entries(update.token, index.entries())
+                });
+                index.set_fs_monitor(fs_monitor);
+            }
 
             Some(index.write(crate::index::write::Options {
                 extensions: Default::default(),
@@ -637,6 +702,7 @@ pub mod iter {
                 let (tx, rx) = std::sync::mpsc::channel();
                 let mut collect = Collect { tx };
                 let patterns: Vec<_> = patterns.into_iter().collect();
+                let mut fs_monitor = self.fs_monitor;
                 let join = std::thread::Builder::new()
                     .name("gix::status::index_worktree::iter::producer".into())
                     .spawn({
@@ -644,8 +710,13 @@ pub mod iter {
                         let options = self.index_worktree_options;
                         let should_interrupt = should_interrupt.clone();
                         let mut progress = self.progress;
+                        let untracked_cache_mode = self.untracked_cache;
                         move || -> Result<_, crate::status::index_worktree::Error> {
                             let repo = repo.to_thread_local();
+                            let all_checked = patterns.is_empty();
+                            let (index, mut fs_monitor) =
+                                refresh_fs_monitor(index, fs_monitor.as_deref_mut(), all_checked);
+                            let mut untracked_cache = initial_untracked_cache(&index, untracked_cache_mode);
                             let out = repo.index_worktree_status(
                                 &index,
                                 patterns,
@@ -654,13 +725,23 @@ pub mod iter {
                                 submodule,
                                 &mut progress,
                                 &should_interrupt,
+                                untracked_cache.as_mut(),
                                 options,
                             )?;
+                            let untracked_cache =
+                                (untracked_cache.as_ref() != index.untracked()).then_some(untracked_cache);
+                            if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+                                if let Some(Some(update)) = fs_monitor.as_mut() {
+                                    update.all_checked = false;
+                                }
+                            }
                             Ok(Outcome {
                                 index_worktree: out,
                                 index,
                                 changes: None,
                                 skip_hash,
+                                untracked_cache,
+                                fs_monitor,
                             })
                         }
                     })
@@ -670,6 +751,7 @@ pub mod iter {
                     rx_and_join: Some((rx, join)),
                     should_interrupt,
                     changes: Vec::new(),
+                    changed_entries: Vec::new(),
                     out: None,
                 })
             }
@@ -681,6 +763,10 @@ pub mod iter {
                 let options = self.index_worktree_options;
                 let mut progress = self.progress;
                 let repo = repo.to_thread_local();
+                let patterns: Vec<_> = patterns.into_iter().collect();
+                let mut fs_monitor = self.fs_monitor;
+                let (index, fs_monitor) = refresh_fs_monitor(index, fs_monitor.as_deref_mut(), patterns.is_empty());
+                let mut untracked_cache = initial_untracked_cache(&index, self.untracked_cache);
                 let out = repo.index_worktree_status(
                     &index,
                     patterns,
@@ -689,17 +775,22 @@ pub mod iter {
                     submodule,
                     &mut progress,
                     &should_interrupt,
+                    untracked_cache.as_mut(),
                     options,
                 )?;
+                let untracked_cache = (untracked_cache.as_ref() != index.untracked()).then_some(untracked_cache);
                 let mut out = Outcome {
                     index_worktree: out,
                     index,
                     changes: None,
                     skip_hash,
+                    untracked_cache,
+                    fs_monitor,
                 };
                 let mut iter = super::Iter {
                     items: Vec::new().into_iter(),
                     changes: Vec::new(),
+                    changed_entries: Vec::new(),
                     out: None,
                 };
                 let items = collect
@@ -708,6 +799,9 @@ pub mod iter {
                     .filter_map(|item| iter.maybe_keep_index_change(item))
                     .collect::<Vec<_>>();
                 out.changes = (!iter.changes.is_empty()).then(|| std::mem::take(&mut iter.changes));
+                if let Some(Some(update)) = out.fs_monitor.as_mut() {
+                    update.changed = std::mem::take(&mut iter.changed_entries);
+                }
                 iter.items = items.into_iter();
                 iter.out = Some(out);
                 Ok(iter)
@@ -715,6 +809,47 @@ pub mod iter {
         }
     }
 
+    /// Return the untracked cache to use with `index` according to `mode`.
+    fn initial_untracked_cache(
+        index: &gix_index::State,
+        mode: crate::status::UntrackedCache,
+    ) -> Option<gix_index::extension::UntrackedCache> {
+        match mode {
+            crate::status::UntrackedCache::Keep => index.untracked().cloned(),
+            crate::status::UntrackedCache::Enable => Some(index.untracked().cloned().unwrap_or_default()),
+            crate::status::UntrackedCache::Disable => None,
+        }
+    }
+
+    /// Query `monitor` to mark all entries of `index` that are known to be unchanged, which requires a copy of `index`.
+    /// Return the index to use, along with the update to apply to the fsmonitor extension of the index.
+    fn refresh_fs_monitor(
+        index: IndexPersistedOrInMemory,
+        monitor: Option<&mut (dyn gix_status::fsmonitor::Monitor + Send + 'static)>,
+        all_checked: bool,
+    ) -> (IndexPersistedOrInMemory, Option<Option<FsMonitorUpdate>>) {
+        let Some(monitor) = monitor else {
+            let update = index.fs_monitor().is_some().then_some(None);
+            return (index, update);
+        };
+        let mut index = match index {
+            IndexPersistedOrInMemory::Persisted(persisted) => (**persisted).clone(),
+            IndexPersistedOrInMemory::InMemory(index) => index,
+        };
+        let update = match gix_status::fsmonitor::refresh(&mut index, monitor) {
+            Ok(token) => Some(Some(FsMonitorUpdate {
+                token,
+                all_checked,
+                changed: Vec::new(),
+            })),
+            Err(_err) => {
+                gix_trace::warn!("ignoring filesystem monitor: {_err:#?}");
+                None
+            }
+        };
+        (IndexPersistedOrInMemory::InMemory(index), update)
+    }
+
     impl Iterator for super::Iter {
         type Item = Result<Item, index_worktree::Error>;
 
@@ -734,6 +869,9 @@ pub mod iter {
                         break match handle.join().expect("no panic") {
                             Ok(mut out) => {
                                 out.changes = Some(std::mem::take(&mut self.changes));
+                                if let Some(Some(update)) = out.fs_monitor.as_mut() {
+                                    update.changed = std::mem::take(&mut self.changed_entries);
+                                }
                                 self.out = Some(out);
                                 None
                             }
@@ -763,6 +901,19 @@ pub mod iter {
 
     impl super::Iter {
         fn maybe_keep_index_change(&mut self, item: Item) -> Option<Item> {
+            match &item {
+                Item::Modification {
+                    status: gix_status::index_as_worktree::EntryStatus::NeedsUpdate(_),
+                    ..
+                }
+                | Item::DirectoryContents { .. } => {}
+                Item::Modification { entry_index, .. } => self.changed_entries.push(*entry_index),
+                Item::Rewrite { source, .. } => {
+                    if let RewriteSource::RewriteFromIndex { source_entry_index, .. } = source {
+                        self.changed_entries.push(*source_entry_index);
+                    }
+                }
+            }
             let change = match item {
                 Item::Modification {
                     status: gix_status::index_as_worktree::EntryStatus::NeedsUpdate(stat),
@@ -0,0 +1,145 @@
+use crate::bstr::{BStr, BString};
+use crate::status::{index_worktree, tree_index, Platform};
+use crate::worktree::IndexPersistedOrInMemory;
+use gix_diff::tree::visit::Action;
+
+/// The error returned by [Platform::into_iter()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Index(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    Head(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    TreeIndex(#[from] tree_index::Error),
+    #[error(transparent)]
+    IndexWorktreeIter(#[from] index_worktree::iter::Error),
+    #[error(transparent)]
+    IndexWorktree(#[from] index_worktree::Error),
+}
+
+/// The item produced by the [iterator](crate::status::Iter).
+#[derive(Clone, PartialEq, Debug)]
+pub enum Item {
+    /// A change between the tree and the index, i.e. a staged change.
+    TreeIndex(gix_status::tree_index::Change),
+    /// A change between the index and the worktree, i.e. an unstaged change or an untracked file.
+    IndexWorktree(index_worktree::iter::Item),
+}
+
+impl Item {
+    /// Return the repository-relative path at which the change is located, which is the destination of rewrites.
+    pub fn location(&self) -> &BStr {
+        match self {
+            Item::TreeIndex(change) => change.location(),
+            Item::IndexWorktree(item) => match item {
+                index_worktree::iter::Item::Modification { rela_path, .. } => rela_path.as_ref(),
+                index_worktree::iter::Item::DirectoryContents { entry, .. } => entry.rela_path.as_ref(),
+                index_worktree::iter::Item::Rewrite { dirwalk_entry, .. } => dirwalk_entry.rela_path.as_ref(),
+            },
+        }
+    }
+}
+
+/// The outcome of a completed [iteration](crate::status::Iter).
+pub struct Outcome {
+    /// The outcome of the comparison between the tree and the index, or `None` if it was
+    /// [disabled](Platform::head_tree()).
+    pub tree_index: Option<gix_status::tree_index::Outcome>,
+    /// The outcome of the comparison between the index and the worktree, which also allows to write back the index.
+    pub index_worktree: index_worktree::iter::Outcome,
+}
+
+/// Lifecycle
+impl<'repo, Progress> Platform<'repo, Progress>
+where
+    Progress: gix_features::progress::Progress,
+{
+    /// Turn the platform into an iterator for changes between the head tree and the index, followed by changes between the
+    /// index and the working tree, similar to `git status`.
+    ///
+    /// * `patterns`
+    ///     - Optional patterns to use to limit the paths to look at. If empty, all paths are considered.
+    #[doc(alias = "diff_tree_to_index", alias = "git2")]
+    pub fn into_iter(mut self, patterns: impl IntoIterator<Item = BString>) -> Result<crate::status::Iter, Error> {
+        let repo = self.repo;
+        let index = match self.index.take() {
+            None => IndexPersistedOrInMemory::Persisted(repo.index_or_empty()?),
+            Some(index) => index,
+        };
+        let patterns: Vec<_> = patterns.into_iter().collect();
+        let head_tree = match self.head_tree {
+            Some(tree_id) => tree_id,
+            None => Some(if repo.head()?.is_unborn() {
+                gix_hash::ObjectId::empty_tree(repo.object_hash())
+            } else {
+                repo.head_tree_id()?.detach()
+            }),
+        };
+
+        let mut changes = Vec::new();
+        let tree_index_outcome = head_tree
+            .map(|tree_id| {
+                let should_interrupt = self.should_interrupt.clone().unwrap_or_default();
+                repo.tree_index_status(
+                    &tree_id,
+                    &index,
+                    &patterns,
+                    self.tree_index_renames,
+                    &should_interrupt,
+                    |change| {
+                        changes.push(change);
+                        Action::Continue
+                    },
+                )
+            })
+            .transpose()?;
+
+        let index_worktree = self.index(index).into_index_worktree_iter(patterns)?;
+        Ok(crate::status::Iter {
+            tree_index: changes.into_iter(),
+            tree_index_outcome,
+            index_worktree: Some(index_worktree),
+            out: None,
+        })
+    }
+}
+
+impl Iterator for crate::status::Iter {
+    type Item = Result<Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(change) = self.tree_index.next() {
+            return Some(Ok(Item::TreeIndex(change)));
+        }
+        let index_worktree = self.index_worktree.as_mut()?;
+        match index_worktree.next() {
+            Some(item) => Some(item.map(Item::IndexWorktree).map_err(Into::into)),
+            None => {
+                let index_worktree = self.index_worktree.take()?.into_outcome()?;
+                self.out = Some(Outcome {
+                    tree_index: self.tree_index_outcome.take(),
+                    index_worktree,
+                });
+                None
+            }
+        }
+    }
+}
+
+/// Access
+impl crate::status::Iter {
+    /// Return the outcome of the iteration, or `None` if the iterator isn't fully consumed.
+    pub fn outcome_mut(&mut self) -> Option<&mut Outcome> {
+        self.out.as_mut()
+    }
+
+    /// Turn the iterator into the iteration outcome, which is `None` on error or if the iteration
+    /// isn't complete.
+    pub fn into_outcome(mut self) -> Option<Outcome> {
+        self.out.take()
+    }
+}
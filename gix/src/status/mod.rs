@@ -12,7 +12,12 @@ where
     progress: Progress,
     index: Option<crate::worktree::IndexPersistedOrInMemory>,
     submodules: Submodule,
+    untracked_cache: UntrackedCache,
+    fs_monitor: Option<Box<dyn gix_status::fsmonitor::Monitor + Send>>,
     index_worktree_options: index_worktree::Options,
+    /// The tree to compare the index to, with `None` meaning `HEAD^{tree}`, and `Some(None)` disabling the comparison.
+    head_tree: Option<Option<gix_hash::ObjectId>>,
+    tree_index_renames: tree_index::TrackRenames,
     should_interrupt: Option<OwnedOrStaticAtomicBool>,
 }
 
@@ -55,6 +60,18 @@ pub enum UntrackedFiles {
     Files,
 }
 
+/// How the untracked cache, as stored in the `UNTR` extension of the index, should be used to accelerate the directory walk.
+#[derive(Default, Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum UntrackedCache {
+    /// Use and update the untracked cache if the index has one, but don't add it if it doesn't.
+    #[default]
+    Keep,
+    /// Use and update the untracked cache, and add it to the index if it doesn't have one yet.
+    Enable,
+    /// Don't use the untracked cache, and remove it from the index if it has one.
+    Disable,
+}
+
 impl Default for Submodule {
     fn default() -> Self {
         Submodule::AsConfigured { check_dirty: false }
@@ -69,6 +86,8 @@ pub enum Error {
     DirwalkOptions(#[from] config::boolean::Error),
     #[error(transparent)]
     ConfigureUntrackedFiles(#[from] config::key::GenericErrorWithValue),
+    #[error(transparent)]
+    CommandContext(#[from] config::command_context::Error),
 }
 
 /// Status
@@ -82,10 +101,17 @@ impl Repository {
     ///
     /// which implies that submodule information is provided by default.
     ///
+    /// Use [`into_iter()`](Platform::into_iter()) to also see the changes between `HEAD^{tree}` and the index,
+    /// with renames tracked as configured by `diff.renames`.
+    ///
     /// Note that `status.showUntrackedFiles` is respected, which leads to untracked files being
     /// collapsed by default. If that needs to be controlled,
     /// [configure the directory walk explicitly](Platform::dirwalk_options) or more [implicitly](Platform::untracked_files).
     ///
+    /// `core.untrackedCache` is respected as well to [use and maintain the untracked cache](Platform::untracked_cache).
+    /// If `core.fsmonitor` is set to a hook, it's queried to [skip the modification check](Platform::fs_monitor) of
+    /// entries that are known to be unchanged.
+    ///
     /// Pass `progress` to receive progress information on file modifications on this repository.
     /// Use [`progress::Discard`](crate::progress::Discard) to discard all progress information.
     ///
@@ -103,7 +129,11 @@ impl Repository {
             progress,
             index: None,
             submodules: Submodule::default(),
+            untracked_cache: UntrackedCache::default(),
+            fs_monitor: None,
             should_interrupt: None,
+            head_tree: None,
+            tree_index_renames: tree_index::TrackRenames::default(),
             index_worktree_options: index_worktree::Options {
                 sorting: None,
                 dirwalk_options: Some(self.dirwalk_options()?),
@@ -123,7 +153,31 @@ impl Repository {
             })
             .transpose()?
             .unwrap_or_default();
-        Ok(platform.untracked_files(untracked))
+        let untracked_cache = config::tree::Core::UNTRACKED_CACHE
+            .try_into_untracked_cache(self.config.resolved.boolean("core", None, "untrackedCache"))
+            .with_lenient_default(self.config.lenient_config)?
+            .unwrap_or_default();
+        let fs_monitor = self.fs_monitor_hook()?;
+        Ok(platform
+            .untracked_files(untracked)
+            .untracked_cache(untracked_cache)
+            .fs_monitor(fs_monitor.map(|hook| Box::new(hook) as _)))
+    }
+
+    /// Return the `core.fsmonitor` hook if one is configured in a trusted configuration file.
+    fn fs_monitor_hook(&self) -> Result<Option<gix_status::fsmonitor::Hook>, Error> {
+        let Some(worktree_dir) = self.work_dir() else {
+            return Ok(None);
+        };
+        let Some((command, version)) = self.config.fs_monitor_hook()? else {
+            return Ok(None);
+        };
+        Ok(Some(gix_status::fsmonitor::Hook {
+            command,
+            version,
+            worktree_dir: worktree_dir.to_owned(),
+            context: self.command_context()?,
+        }))
     }
 }
 
@@ -139,32 +193,45 @@ pub mod is_dirty {
         #[error(transparent)]
         StatusPlatform(#[from] crate::status::Error),
         #[error(transparent)]
-        CreateStatusIterator(#[from] crate::status::index_worktree::iter::Error),
+        CreateStatusIterator(#[from] crate::status::iter::Error),
     }
 
     impl Repository {
         /// Returns `true` if the repository is dirty.
         /// This means it's changed in one of the following ways:
         ///
-        /// * the index was changed in comparison to its working tree
+        /// * the index was changed in comparison to the tree of `HEAD`
         /// * the working tree was changed in comparison to the index
         /// * submodules are taken in consideration, along with their `ignore` and `isActive` configuration
         ///
         /// Note that *untracked files* do *not* affect this flag.
-        ///
-        /// ### Incomplete Implementation Warning
-        ///
-        /// Currently, this does not compute changes between the head and the index.
-        // TODO: use iterator which also tests for head->index changes.
         pub fn is_dirty(&self) -> Result<bool, Error> {
-            let is_dirty = self
+            self.has_changes(true)
+        }
+
+        /// Returns `true` if the working tree was changed in comparison to the index, including submodules,
+        /// while changes between the tree of `HEAD` and the index are ignored.
+        ///
+        /// This is useful for operations that require all changes to be added to the index.
+        #[cfg(feature = "rebase")]
+        pub(crate) fn has_unstaged_changes(&self) -> Result<bool, Error> {
+            self.has_changes(false)
+        }
+
+        fn has_changes(&self, compare_head_tree: bool) -> Result<bool, Error> {
+            let mut status = self
                 .status(gix_features::progress::Discard)?
+                .tree_index_track_renames(crate::status::tree_index::TrackRenames::Disabled);
+            if !compare_head_tree {
+                status = status.head_tree(None);
+            }
+            let is_dirty = status
                 .index_worktree_rewrites(None)
                 .index_worktree_submodules(crate::status::Submodule::AsConfigured { check_dirty: true })
                 .index_worktree_options_mut(|opts| {
                     opts.dirwalk_options = None;
                 })
-                .into_index_worktree_iter(Vec::new())?
+                .into_iter(Vec::new())?
                 .take_while(Result::is_ok)
                 .next()
                 .is_some();
@@ -178,3 +245,28 @@ mod platform;
 ///
 #[allow(clippy::empty_docs)]
 pub mod index_worktree;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree_index;
+
+/// An iterator for changes between `HEAD^{tree}` and the index, followed by changes between the index and the worktree,
+/// similar to what `git status` shows.
+///
+/// Note that the changes between the tree and the index are computed in advance, before the first change
+/// between the index and the worktree is produced, and that the
+/// [notes about index-worktree iteration](index_worktree::Iter) apply as well.
+pub struct Iter {
+    /// The changes between the tree and the index, which are emitted first.
+    tree_index: std::vec::IntoIter<gix_status::tree_index::Change>,
+    /// The outcome of the tree-index comparison, if it was performed.
+    tree_index_outcome: Option<gix_status::tree_index::Outcome>,
+    /// The iterator for changes between the index and the worktree, or `None` once it is exhausted.
+    index_worktree: Option<index_worktree::Iter>,
+    /// The outcome of the operation, only available once the operation has ended.
+    out: Option<iter::Outcome>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod iter;
@@ -1,4 +1,6 @@
-use crate::status::{index_worktree, OwnedOrStaticAtomicBool, Platform, Submodule, UntrackedFiles};
+use crate::status::{
+    index_worktree, tree_index, OwnedOrStaticAtomicBool, Platform, Submodule, UntrackedCache, UntrackedFiles,
+};
 use std::sync::atomic::AtomicBool;
 
 /// Builder
@@ -40,6 +42,28 @@ where
         self.dirwalk_options(|cb| cb.emit_untracked(mode))
     }
 
+    /// Control how the untracked cache of the index is used to accelerate the directory walk, overriding any value
+    /// set by the git configuration.
+    ///
+    /// Note that the cache is only used if the directory walk is compatible with it, and that changes to it
+    /// are only persisted when [writing the changes](index_worktree::iter::Outcome::write_changes()) of the iteration.
+    pub fn untracked_cache(mut self, untracked_cache: UntrackedCache) -> Self {
+        self.untracked_cache = untracked_cache;
+        self
+    }
+
+    /// Use `monitor` to learn which files changed since the last status, so that the modification check can be skipped
+    /// for all others, or don't use a filesystem monitor at all if `None`, overriding the `core.fsmonitor` hook
+    /// that may be configured.
+    ///
+    /// The token of the last query is stored in the `FSMN` extension of the index, which is updated when
+    /// [writing the changes](index_worktree::iter::Outcome::write_changes()) of the iteration, and removed if `monitor`
+    /// is `None`.
+    pub fn fs_monitor(mut self, monitor: Option<Box<dyn gix_status::fsmonitor::Monitor + Send>>) -> Self {
+        self.fs_monitor = monitor;
+        self
+    }
+
     /// Set the interrupt flag to `should_interrupt`, which typically is an application-wide flag
     /// that is ultimately controlled by user interrupts.
     ///
@@ -107,4 +131,19 @@ where
         cb(&mut self.index_worktree_options);
         self
     }
+
+    /// Set the tree to compare the index to when [iterating](Self::into_iter()), or disable the comparison if `None`.
+    ///
+    /// Defaults to `HEAD^{tree}`, or the empty tree if `HEAD` is unborn.
+    pub fn head_tree(mut self, tree_id: impl Into<Option<gix_hash::ObjectId>>) -> Self {
+        self.head_tree = Some(tree_id.into());
+        self
+    }
+
+    /// Configure how renames are tracked between the [head tree](Self::head_tree()) and the index, overriding
+    /// the `diff.renames` configuration.
+    pub fn tree_index_track_renames(mut self, renames: tree_index::TrackRenames) -> Self {
+        self.tree_index_renames = renames;
+        self
+    }
 }
@@ -0,0 +1,118 @@
+use crate::bstr::BStr;
+use crate::{config, Repository};
+use gix_diff::tree::visit::Action;
+use std::sync::atomic::AtomicBool;
+
+/// The error returned by [Repository::tree_index_status()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    TreeIndex(#[from] gix_status::tree_index::Error),
+    #[error(transparent)]
+    AttributesAndExcludes(#[from] config::attribute_stack::Error),
+    #[error(transparent)]
+    Pathspec(#[from] crate::pathspec::init::Error),
+    #[error(transparent)]
+    ResourceCache(#[from] crate::diff::resource_cache::Error),
+    #[error(transparent)]
+    RewritesConfiguration(#[from] crate::diff::new_rewrites::Error),
+}
+
+/// Specify how rewrites between a tree and the index should be tracked.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub enum TrackRenames {
+    /// Use the `diff.renames` configuration to determine if and how rewrites should be tracked, which enables
+    /// rename tracking if it isn't set, just like `git status` does.
+    #[default]
+    AsConfigured,
+    /// Track rewrites as configured by the given value.
+    Given(gix_diff::Rewrites),
+    /// Do not track rewrites at all.
+    Disabled,
+}
+
+impl Repository {
+    /// Obtain the changes between the tree with `tree_id` and `index`, similar to `git diff-index --cached`,
+    /// and call `delegate` with each of them.
+    ///
+    /// * `tree_id`
+    ///     - The tree to compare to, typically `HEAD^{tree}`. It may be the empty tree even if it doesn't exist
+    ///       in the object database.
+    /// * `index`
+    ///     - The index to compare to the tree. Its `TREE` extension is used to skip unchanged directories.
+    /// * `patterns`
+    ///     - Optional patterns to use to limit the paths to look at. If empty, all paths are considered.
+    /// * `renames`
+    ///     - Control how rewrites are tracked.
+    /// * `should_interrupt`
+    ///     - A flag to stop the whole operation.
+    /// * `delegate`
+    ///     - The sink for receiving all changes, which may cancel the operation by returning [`Action::Cancel`].
+    ///
+    /// ### Note
+    ///
+    /// This is a lower-level method, prefer the [`status`](Repository::status()) method for greater ease of use.
+    pub fn tree_index_status(
+        &self,
+        tree_id: &gix_hash::oid,
+        index: &gix_index::State,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        renames: TrackRenames,
+        should_interrupt: &AtomicBool,
+        delegate: impl FnMut(gix_status::tree_index::Change) -> Action,
+    ) -> Result<gix_status::tree_index::Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::tree_index_status");
+        let rewrites = match renames {
+            TrackRenames::AsConfigured => {
+                let is_disabled = matches!(self.config.resolved.boolean_by_key("diff.renames"), Some(Ok(false)));
+                match self.config.diff_renames()? {
+                    Some(rewrites) => Some(rewrites),
+                    None => (!is_disabled).then(Default::default),
+                }
+            }
+            TrackRenames::Given(rewrites) => Some(rewrites),
+            TrackRenames::Disabled => None,
+        };
+        let attributes = self.attributes_only(index, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+        let mut pathspec = crate::Pathspec::new(
+            self,
+            false,
+            patterns,
+            true, /* inherit ignore case */
+            || Ok(attributes.inner.clone()),
+        )?;
+        let mut resource_cache = crate::diff::resource_cache(
+            self,
+            gix_diff::blob::pipeline::Mode::ToGit,
+            attributes.inner,
+            Default::default(),
+        )?;
+
+        let stack = &mut pathspec.stack;
+        let out = gix_status::tree_index(
+            tree_id,
+            index,
+            &self.objects,
+            gix_status::tree_index::Context {
+                pathspec: &mut pathspec.search,
+                pathspec_attributes: &mut |relative_path, case, is_dir, out| {
+                    let stack = stack.as_mut().expect("initialized in advance");
+                    stack
+                        .set_case(case)
+                        .at_entry(
+                            relative_path,
+                            Some(crate::pathspec::is_dir_to_mode(is_dir)),
+                            &self.objects,
+                        )
+                        .map_or(false, |platform| platform.matching_attributes(out))
+                },
+                resource_cache: &mut resource_cache,
+                should_interrupt,
+            },
+            gix_status::tree_index::Options { rewrites },
+            delegate,
+        )?;
+        Ok(out)
+    }
+}
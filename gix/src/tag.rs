@@ -12,6 +12,12 @@ mod error {
         WriteObject(#[from] crate::object::write::Error),
         #[error(transparent)]
         ReferenceEdit(#[from] crate::reference::edit::Error),
+        #[cfg(feature = "signing")]
+        #[error(transparent)]
+        SigningOptions(#[from] crate::signing::options::Error),
+        #[cfg(feature = "signing")]
+        #[error(transparent)]
+        Sign(#[from] crate::signing::sign::Error),
     }
 }
 pub use error::Error;
@@ -0,0 +1,162 @@
+//! Serve fetches from a repository to clients using protocol V2, similar to `git upload-pack`.
+//!
+//! The [`Server`] obtained with [`Repository::upload_pack()`](crate::Repository::upload_pack()) implements the
+//! [`Delegate`](gix_protocol::upload_pack::Delegate) of the `upload-pack` implementation in `gix-protocol`, and can serve
+//! connections of `git daemon`-style servers, processes spawned by `ssh` or requests of smart-HTTP servers.
+use std::io;
+
+use gix_hash::ObjectId;
+use gix_protocol::upload_pack::{fetch, http, ls_refs, Delegate, Options};
+
+mod pack;
+mod progress;
+mod refs;
+mod shallow;
+
+/// The error returned when the repository couldn't be accessed while serving a request.
+///
+/// It's passed on as [`io::Error`] of kind [`Other`](io::ErrorKind::Other), from which it can be obtained
+/// with [`io::Error::into_inner()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FollowReference(#[from] gix_ref::file::find::existing::Error),
+    #[error(transparent)]
+    References(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a reference")]
+    ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    LookupCommit(#[from] gix_revwalk::graph::try_lookup_or_insert_default::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    PackObjects(#[from] crate::pack_objects::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::Other, err),
+        }
+    }
+}
+
+/// A server for the `upload-pack` service of a repository, created with [`Repository::upload_pack()`](crate::Repository::upload_pack()).
+pub struct Server<'repo> {
+    pub(crate) repo: &'repo crate::Repository,
+    /// The options to serve requests with, which default to the agent configured in `gitoxide.userAgent` and
+    /// the object hash of the repository.
+    pub options: Options,
+}
+
+/// Serving
+impl Server<'_> {
+    /// Serve a whole session by reading requests from `input` and writing responses to `out` until the client
+    /// disconnects, as needed by `ssh` or `git daemon`-style servers.
+    pub fn serve(&mut self, input: impl io::Read, out: impl io::Write) -> Result<(), gix_protocol::upload_pack::Error> {
+        let options = self.options.clone();
+        gix_protocol::upload_pack::serve(self, input, out, &options)
+    }
+
+    /// Serve a single request read from `input` and write the response to `out`.
+    pub fn serve_stateless(
+        &mut self,
+        input: impl io::Read,
+        out: impl io::Write,
+    ) -> Result<(), gix_protocol::upload_pack::Error> {
+        let options = self.options.clone();
+        gix_protocol::upload_pack::serve_stateless(self, input, out, &options)
+    }
+
+    /// Write the response for the smart-HTTP `endpoint` to `out`, reading the request from `body` if there is one.
+    ///
+    /// See the [`http`] module for details on identifying the endpoint and the headers to send.
+    pub fn serve_http(
+        &mut self,
+        endpoint: http::Endpoint,
+        body: impl io::Read,
+        out: impl io::Write,
+    ) -> Result<(), gix_protocol::upload_pack::Error> {
+        let options = self.options.clone();
+        http::serve(endpoint, self, body, out, &options)
+    }
+}
+
+impl Delegate for Server<'_> {
+    fn refs(&mut self) -> io::Result<Vec<ls_refs::Ref>> {
+        Ok(refs::all(self.repo)?)
+    }
+
+    fn contains(&mut self, id: &gix_hash::oid) -> io::Result<bool> {
+        Ok(self.repo.has_object(id))
+    }
+
+    fn is_ready(&mut self, wants: &[ObjectId], common: &[ObjectId]) -> io::Result<bool> {
+        Ok(refs::is_ready(self.repo, wants, common)?)
+    }
+
+    fn shallow(&mut self, wants: &[ObjectId], arguments: &fetch::Arguments) -> io::Result<fetch::ShallowUpdate> {
+        Ok(shallow::update(self.repo, wants, arguments)?)
+    }
+
+    fn write_pack(
+        &mut self,
+        pack: fetch::Pack<'_>,
+        out: &mut dyn io::Write,
+        progress: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let options = crate::pack_objects::write::Options {
+            thin_pack: pack.arguments.thin_pack,
+            ofs_delta: pack.arguments.ofs_delta,
+        };
+        let selection = pack::select(self.repo, pack)?;
+        let mut sideband = progress::Sideband::root();
+        let mut out = progress::WithProgress {
+            out,
+            progress,
+            pending: sideband.pending(),
+        };
+        let num_objects = crate::pack_objects::write(
+            self.repo,
+            selection,
+            &mut out,
+            options,
+            &mut sideband,
+            &std::sync::atomic::AtomicBool::default(),
+        )
+        .map_err(Error::from)?;
+        out.send_progress()?;
+        writeln!(out.progress, "Total {num_objects}")?;
+        Ok(())
+    }
+}
+
+/// Follow tags starting at `id` until a different kind of object is reached, and return it along with its kind.
+fn peel_tags(
+    repo: &crate::Repository,
+    mut id: ObjectId,
+    buf: &mut Vec<u8>,
+) -> Result<(ObjectId, gix_object::Kind), Error> {
+    use gix_object::FindExt;
+    loop {
+        let object = repo.objects.find(&id, buf)?;
+        match object.kind {
+            gix_object::Kind::Tag => id = object.decode()?.into_tag().expect("tag").target(),
+            kind => return Ok((id, kind)),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+use gix_protocol::upload_pack::fetch;
+
+use super::Error;
+use crate::pack_objects;
+
+/// Return the objects to send for `pack`, which are reachable from its wants but not from its common commits.
+///
+/// Parents of commits that are shallow on the client after the fetch aren't traversed, while the parents of commits
+/// that aren't shallow anymore are sent. Blobs that don't pass the filter are omitted.
+pub(super) fn select(repo: &crate::Repository, pack: fetch::Pack<'_>) -> Result<pack_objects::Selection, Error> {
+    let client_shallow: gix_hashtable::HashSet = pack.arguments.shallow.iter().copied().collect();
+    let unshallow: gix_hashtable::HashSet = pack.shallow.unshallow.iter().copied().collect();
+    let shallow: gix_hashtable::HashSet = pack
+        .shallow
+        .shallow
+        .iter()
+        .chain(client_shallow.iter().filter(|id| !unshallow.contains(*id)))
+        .copied()
+        .collect();
+
+    let mut buf = Vec::new();
+    let mut ours = pack.wants.to_vec();
+    for id in &unshallow {
+        ours.extend(repo.objects.find_commit(id, &mut buf)?.parents());
+    }
+
+    let filter = pack.arguments.filter;
+    let blob_filter = |id: &gix_hash::oid| -> Result<bool, pack_objects::Error> {
+        Ok(match filter {
+            None => true,
+            Some(fetch::Filter::BlobNone) => false,
+            Some(filter) => filter.includes_blob(repo.find_header(id)?.size()),
+        })
+    };
+    let bitmap = repo.pack_bitmap().map_err(pack_objects::Error::from)?;
+    let mut selection = pack_objects::select(
+        repo,
+        ours,
+        pack.common.iter().copied(),
+        pack_objects::select::Options {
+            shallow: (!shallow.is_empty()).then_some(&shallow),
+            receiver_shallow: (!client_shallow.is_empty()).then_some(&client_shallow),
+            blob_filter: filter.is_some().then_some(&blob_filter as _),
+            bitmap: bitmap.as_ref(),
+        },
+        &AtomicBool::default(),
+    )?;
+    if pack.arguments.include_tag {
+        add_tags(repo, &mut selection.objects, &mut buf)?;
+    }
+    Ok(selection)
+}
+
+/// Add all annotated tags of `repo` to `out` which point to an object in `out`, as requested with `include-tag`.
+fn add_tags(repo: &crate::Repository, out: &mut Vec<ObjectId>, buf: &mut Vec<u8>) -> Result<(), Error> {
+    let mut in_pack: gix_hashtable::HashSet = out.iter().copied().collect();
+    for reference in repo.references()?.tags()? {
+        let reference = reference.map_err(Error::ReadReference)?;
+        let Some(mut id) = reference.target().try_id().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let mut tags = Vec::new();
+        loop {
+            let object = repo.objects.find(&id, buf)?;
+            if object.kind != gix_object::Kind::Tag {
+                break;
+            }
+            tags.push(id);
+            id = object.decode()?.into_tag().expect("tag").target();
+        }
+        if in_pack.contains(&id) {
+            for tag_id in tags {
+                if in_pack.insert(tag_id) {
+                    out.push(tag_id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
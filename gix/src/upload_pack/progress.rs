@@ -0,0 +1,194 @@
+use std::{
+    io,
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use gix_features::progress::{Count, Id, MessageLevel, NestedProgress, Progress, Step, StepShared, Unit, UNKNOWN};
+
+/// The least amount of time between two updates of the same progress.
+const THROTTLE: Duration = Duration::from_millis(100);
+
+/// Progress which is rendered into human-readable messages similar to those of `git`, like `counting objects: 100% (3/3), done.`,
+/// to be sent to the client.
+///
+/// Only the direct children of the root are shown as everything below them is too detailed.
+pub(super) struct Sideband {
+    name: String,
+    id: Id,
+    depth: usize,
+    max: Option<Step>,
+    unit: Option<Unit>,
+    step: StepShared,
+    last_shown: Mutex<Option<Instant>>,
+    /// The messages that are yet to be sent, shared among all progress instances.
+    pending: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Sideband {
+    /// Create the root of all progress, which itself is never shown.
+    pub fn root() -> Self {
+        Sideband {
+            name: String::new(),
+            id: UNKNOWN,
+            depth: 0,
+            max: None,
+            unit: None,
+            step: Default::default(),
+            last_shown: Default::default(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Return the buffer with messages that are yet to be sent.
+    pub fn pending(&self) -> Arc<Mutex<Vec<u8>>> {
+        self.pending.clone()
+    }
+
+    fn is_shown(&self) -> bool {
+        self.depth == 1
+    }
+
+    /// Render the current state of the progress into the pending messages, unless this was done recently.
+    fn show(&self, done: bool) {
+        if !self.is_shown() {
+            return;
+        }
+        let mut last_shown = self.last_shown.lock().expect("no panics while locked");
+        let now = Instant::now();
+        if !done && matches!(*last_shown, Some(last) if now.duration_since(last) < THROTTLE) {
+            return;
+        }
+        *last_shown = Some(now);
+        let step = self.step.load(Ordering::Relaxed);
+        let mut message = match self.max {
+            Some(max) if max > 0 => format!("{}: {:>3}% ({step}/{max})", self.name, step * 100 / max),
+            _ => format!("{}: {step}", self.name),
+        };
+        message.push_str(if done { ", done.\n" } else { "\r" });
+        self.pending
+            .lock()
+            .expect("no panics while locked")
+            .extend_from_slice(message.as_bytes());
+    }
+}
+
+impl Drop for Sideband {
+    fn drop(&mut self) {
+        let was_used = self.unit.is_some() || self.step.load(Ordering::Relaxed) != 0;
+        if was_used {
+            self.show(true);
+        }
+    }
+}
+
+impl Count for Sideband {
+    fn set(&self, step: Step) {
+        self.step.store(step, Ordering::Relaxed);
+        self.show(false);
+    }
+
+    fn step(&self) -> Step {
+        self.step.load(Ordering::Relaxed)
+    }
+
+    fn inc_by(&self, step: Step) {
+        self.step.fetch_add(step, Ordering::Relaxed);
+        self.show(false);
+    }
+
+    fn counter(&self) -> StepShared {
+        self.step.clone()
+    }
+}
+
+impl Progress for Sideband {
+    fn init(&mut self, max: Option<Step>, unit: Option<Unit>) {
+        self.max = max;
+        self.unit = unit;
+    }
+
+    fn unit(&self) -> Option<Unit> {
+        self.unit.clone()
+    }
+
+    fn max(&self) -> Option<Step> {
+        self.max
+    }
+
+    fn set_max(&mut self, max: Option<Step>) -> Option<Step> {
+        std::mem::replace(&mut self.max, max)
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn message(&self, _level: MessageLevel, message: String) {
+        if self.is_shown() {
+            self.pending
+                .lock()
+                .expect("no panics while locked")
+                .extend_from_slice(format!("{}: {message}\n", self.name).as_bytes());
+        }
+    }
+}
+
+impl NestedProgress for Sideband {
+    type SubProgress = Sideband;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Self::SubProgress {
+        self.add_child_with_id(name, UNKNOWN)
+    }
+
+    fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Self::SubProgress {
+        Sideband {
+            name: name.into(),
+            id,
+            depth: self.depth + 1,
+            max: None,
+            unit: None,
+            step: Default::default(),
+            last_shown: Default::default(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+/// A writer for pack data which sends the pending progress messages to `progress` before writing to `out`.
+pub(super) struct WithProgress<'a> {
+    pub out: &'a mut dyn io::Write,
+    pub progress: &'a mut dyn io::Write,
+    pub pending: Arc<Mutex<Vec<u8>>>,
+}
+
+impl WithProgress<'_> {
+    /// Send all pending progress messages.
+    pub fn send_progress(&mut self) -> io::Result<()> {
+        let messages = std::mem::take(&mut *self.pending.lock().expect("no panics while locked"));
+        if !messages.is_empty() {
+            self.progress.write_all(&messages)?;
+        }
+        Ok(())
+    }
+}
+
+impl io::Write for WithProgress<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send_progress()?;
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_progress()?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,111 @@
+use gix_hash::ObjectId;
+use gix_protocol::upload_pack::ls_refs::Ref;
+
+use super::{peel_tags, Error};
+use crate::{bstr::BString, head};
+
+/// Return `HEAD` followed by all references of `repo`, with symbolic references followed to the object they point to.
+pub(super) fn all(repo: &crate::Repository) -> Result<Vec<Ref>, Error> {
+    let mut buf = Vec::new();
+    let mut out = Vec::new();
+    let (object, symref_target) = match repo.head()?.kind {
+        head::Kind::Symbolic(referent) => (referent.target.try_id().map(ToOwned::to_owned), Some(referent.name)),
+        head::Kind::Unborn(name) => (None, Some(name)),
+        head::Kind::Detached { target, .. } => (Some(target), None),
+    };
+    out.push(to_ref(
+        repo,
+        "HEAD".into(),
+        object,
+        symref_target.map(|name| name.as_bstr().to_owned()),
+        &mut buf,
+    )?);
+
+    for reference in repo.references()?.all()? {
+        let reference = reference.map_err(Error::ReadReference)?;
+        let name = reference.name().as_bstr().to_owned();
+        let symref_target = reference.target().try_name().map(|name| name.as_bstr().to_owned());
+        let mut leaf = reference;
+        while let Some(next) = leaf.follow() {
+            match next {
+                Ok(next) => leaf = next,
+                Err(gix_ref::file::find::existing::Error::NotFound { .. }) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let Some(id) = leaf.target().try_id().map(ToOwned::to_owned) else {
+            continue;
+        };
+        out.push(to_ref(repo, name, Some(id), symref_target, &mut buf)?);
+    }
+    Ok(out)
+}
+
+fn to_ref(
+    repo: &crate::Repository,
+    name: BString,
+    object: Option<ObjectId>,
+    symref_target: Option<BString>,
+    buf: &mut Vec<u8>,
+) -> Result<Ref, Error> {
+    let peeled = match object {
+        Some(id) => {
+            let (peeled, _kind) = peel_tags(repo, id, buf)?;
+            (peeled != id).then_some(peeled)
+        }
+        None => None,
+    };
+    Ok(Ref {
+        name,
+        object,
+        symref_target,
+        peeled,
+    })
+}
+
+/// Return `true` if each of the `wants` that is a commit reaches one of the `common` commits.
+///
+/// Ancestors older than the oldest of the `common` commits aren't traversed, as they can't lead to it.
+pub(super) fn is_ready(repo: &crate::Repository, wants: &[ObjectId], common: &[ObjectId]) -> Result<bool, Error> {
+    let mut buf = Vec::new();
+    let mut graph = repo.revision_graph::<gix_revwalk::graph::Commit<()>>();
+    let mut common_commits = gix_hashtable::HashSet::default();
+    let mut oldest = gix_date::SecondsSinceUnixEpoch::MAX;
+    for id in common {
+        if let Some(commit) = graph.try_lookup_or_insert_commit(*id, |_| {})? {
+            oldest = oldest.min(commit.commit_time);
+            common_commits.insert(*id);
+        }
+    }
+    if common_commits.is_empty() {
+        return Ok(false);
+    }
+
+    for want in wants {
+        let (want, kind) = peel_tags(repo, *want, &mut buf)?;
+        if kind != gix_object::Kind::Commit {
+            continue;
+        }
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut queue = vec![want];
+        let mut reaches_common = false;
+        while let Some(id) = queue.pop() {
+            if common_commits.contains(&id) {
+                reaches_common = true;
+                break;
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some(commit) = graph.try_lookup_or_insert_commit(id, |_| {})? {
+                if commit.commit_time >= oldest {
+                    queue.extend(commit.parents.iter().copied());
+                }
+            }
+        }
+        if !reaches_common {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
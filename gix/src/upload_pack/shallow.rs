@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use gix_hash::ObjectId;
+use gix_protocol::upload_pack::fetch::{Arguments, ShallowUpdate};
+
+use super::{peel_tags, Error};
+use crate::bstr::ByteSlice;
+
+type Graph<'repo> = gix_revwalk::Graph<'repo, gix_revwalk::graph::Commit<()>>;
+
+/// Determine the commits that become shallow on the client when fetching `wants` according to the `deepen*` `arguments`,
+/// and the shallow commits of the client that receive their parents.
+pub(super) fn update(
+    repo: &crate::Repository,
+    wants: &[ObjectId],
+    arguments: &Arguments,
+) -> Result<ShallowUpdate, Error> {
+    if !arguments.is_deepening() {
+        return Ok(ShallowUpdate::default());
+    }
+    let client_shallow: gix_hashtable::HashSet = arguments.shallow.iter().copied().collect();
+    let mut graph = repo.revision_graph();
+    let mut out = match arguments.deepen {
+        Some(depth) if arguments.deepen_relative => deepen_relative(&mut graph, &arguments.shallow, depth)?,
+        _ => deepen(repo, &mut graph, wants, arguments, &client_shallow)?,
+    };
+    out.shallow.retain(|id| !client_shallow.contains(id));
+    Ok(out)
+}
+
+/// Add `depth` commits to the history of the client, starting at its shallow commits.
+fn deepen_relative(graph: &mut Graph<'_>, client_shallow: &[ObjectId], depth: u32) -> Result<ShallowUpdate, Error> {
+    let mut out = ShallowUpdate::default();
+    let mut seen = gix_hashtable::HashSet::default();
+    let mut queue: VecDeque<_> = client_shallow.iter().map(|id| (*id, 0)).collect();
+    while let Some((id, commit_depth)) = queue.pop_front() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let Some(commit) = graph.try_lookup_or_insert_commit(id, |_| {})? else {
+            continue;
+        };
+        if commit.parents.is_empty() {
+            continue;
+        }
+        if commit_depth == depth {
+            out.shallow.push(id);
+            continue;
+        }
+        if commit_depth == 0 {
+            out.unshallow.push(id);
+        }
+        queue.extend(commit.parents.iter().map(|parent_id| (*parent_id, commit_depth + 1)));
+    }
+    Ok(out)
+}
+
+/// Walk the history of `wants` breadth-first and stop at the depth, time or excluded commits of `arguments`,
+/// marking the commits whose parents aren't sent as shallow.
+fn deepen(
+    repo: &crate::Repository,
+    graph: &mut Graph<'_>,
+    wants: &[ObjectId],
+    arguments: &Arguments,
+    client_shallow: &gix_hashtable::HashSet,
+) -> Result<ShallowUpdate, Error> {
+    let mut buf = Vec::new();
+    let mut excluded = gix_hashtable::HashSet::default();
+    for name in &arguments.deepen_not {
+        let Some(reference) = repo.try_find_reference(name.as_bstr())? else {
+            continue;
+        };
+        let Some(id) = reference.target().try_id().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let (id, _kind) = peel_tags(repo, id, &mut buf)?;
+        let mut queue = vec![id];
+        while let Some(id) = queue.pop() {
+            if !excluded.insert(id) {
+                continue;
+            }
+            if let Some(commit) = graph.try_lookup_or_insert_commit(id, |_| {})? {
+                queue.extend(commit.parents.iter().copied());
+            }
+        }
+    }
+
+    let mut out = ShallowUpdate::default();
+    let mut seen = gix_hashtable::HashSet::default();
+    let mut queue = VecDeque::new();
+    for want in wants {
+        let (id, kind) = peel_tags(repo, *want, &mut buf)?;
+        if kind == gix_object::Kind::Commit {
+            queue.push_back((id, 1));
+        }
+    }
+    while let Some((id, depth)) = queue.pop_front() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let Some(commit) = graph.try_lookup_or_insert_commit(id, |_| {})? else {
+            continue;
+        };
+        let parents = commit.parents.clone();
+        let mut is_shallow = !parents.is_empty() && matches!(arguments.deepen, Some(limit) if depth >= limit);
+        if !is_shallow {
+            for parent_id in &parents {
+                if excluded.contains(parent_id) {
+                    is_shallow = true;
+                    break;
+                }
+                if let Some(since) = arguments.deepen_since {
+                    if let Some(parent) = graph.try_lookup_or_insert_commit(*parent_id, |_| {})? {
+                        if parent.commit_time < since {
+                            is_shallow = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        if is_shallow {
+            out.shallow.push(id);
+        } else {
+            if client_shallow.contains(&id) && !parents.is_empty() {
+                out.unshallow.push(id);
+            }
+            queue.extend(parents.into_iter().map(|parent_id| (parent_id, depth + 1)));
+        }
+    }
+    Ok(out)
+}
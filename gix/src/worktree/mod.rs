@@ -111,6 +111,9 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
 #[allow(clippy::empty_docs)]
 pub mod proxy;
 
+#[cfg(feature = "rebase")]
+pub(crate) mod tree;
+
 ///
 #[cfg(feature = "index")]
 pub mod open_index {
@@ -141,6 +144,55 @@ pub mod open_index {
     }
 }
 
+///
+#[cfg(feature = "rebase")]
+pub mod write_tree {
+    use crate::bstr::BString;
+
+    /// The error returned when writing the index as tree.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The path '{path}' has conflicts which have to be resolved first")]
+        Conflict { path: BString },
+        #[error(transparent)]
+        EditTree(#[from] crate::object::tree::editor::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+    }
+}
+
+///
+#[cfg(feature = "rebase")]
+pub mod checkout {
+    /// The error returned when updating the worktree and the index to match a tree.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Cannot update the worktree of a bare repository")]
+        BareRepository,
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        ProtectOptions(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] gix_index::init::from_tree::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        Checkout(#[from] gix_worktree_state::checkout::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+        #[error("Could not remove '{}' from the worktree", path.display())]
+        RemoveFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+
 ///
 #[cfg(feature = "excludes")]
 pub mod excludes {
@@ -0,0 +1,108 @@
+use std::{collections::HashSet, sync::atomic::AtomicBool};
+
+use gix_hash::ObjectId;
+use gix_index::entry::Flags;
+
+use super::{checkout, write_tree};
+use crate::{
+    bstr::{BStr, ByteSlice},
+    Repository,
+};
+
+/// Write all entries of `index` as tree and return its id, failing if there are conflicts.
+pub(crate) fn write_tree(repo: &Repository, index: &gix_index::State) -> Result<ObjectId, write_tree::Error> {
+    let mut editor = crate::object::tree::Editor::new(gix_object::Tree::empty(), repo);
+    for entry in index.entries() {
+        let path = entry.path(index);
+        if entry.stage() != gix_index::entry::Stage::Unconflicted {
+            return Err(write_tree::Error::Conflict { path: path.to_owned() });
+        }
+        let Some(mode) = entry
+            .mode
+            .to_tree_entry_mode()
+            .filter(|_| !entry.flags.contains(Flags::INTENT_TO_ADD))
+        else {
+            continue;
+        };
+        // Sparse directories are stored with a trailing slash, but are trees like any other.
+        let path = if entry.mode.is_sparse() {
+            path.strip_suffix(b"/").unwrap_or(path).as_bstr()
+        } else {
+            path
+        };
+        editor.upsert(path, mode.kind(), entry.id)?;
+    }
+    Ok(editor.write()?.detach())
+}
+
+/// Change the worktree and the index to match `tree`, assuming that the worktree matches the current index,
+/// and record `conflicts` in the index.
+///
+/// Files that are tracked but not part of `tree` are removed, all others are written, overwriting what's there.
+pub(crate) fn checkout(
+    repo: &Repository,
+    tree: ObjectId,
+    conflicts: &[gix_merge::tree::Conflict],
+) -> Result<(), checkout::Error> {
+    let workdir = repo.work_dir().ok_or(checkout::Error::BareRepository)?;
+    let previous = repo.open_index()?;
+    let mut index = gix_index::File::from_state(
+        gix_index::State::from_tree(&tree, &repo.objects, repo.config.protect_options()?)?,
+        repo.index_path(),
+    );
+
+    for entry in previous.entries() {
+        let path = entry.path(&previous);
+        if entry.flags.contains(Flags::SKIP_WORKTREE) || index.entry_by_path(path).is_some() {
+            continue;
+        }
+        let path = workdir.join(gix_path::from_bstr(path));
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(source) => return Err(checkout::Error::RemoveFile { path, source }),
+        }
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+            if std::fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+
+    let mut opts = repo
+        .config
+        .checkout_options(repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+    opts.overwrite_existing = true;
+    gix_worktree_state::checkout(
+        &mut index,
+        workdir,
+        repo.objects.clone().into_arc()?,
+        &gix_features::progress::Discard,
+        &gix_features::progress::Discard,
+        &AtomicBool::default(),
+        opts,
+    )?;
+
+    if !conflicts.is_empty() {
+        let paths: HashSet<&BStr> = conflicts.iter().map(|conflict| conflict.path.as_bstr()).collect();
+        index.remove_entries(|_, path, _| paths.contains(path));
+        for conflict in conflicts {
+            for (stage, entry) in [(1, conflict.ancestor), (2, conflict.ours), (3, conflict.theirs)] {
+                if let Some(entry) = entry {
+                    index.dangerously_push_entry(
+                        Default::default(),
+                        entry.id,
+                        Flags::from_bits_retain(stage << 12),
+                        entry.mode.into(),
+                        conflict.path.as_ref(),
+                    );
+                }
+            }
+        }
+        index.sort_entries();
+    }
+    index.write(Default::default())?;
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use gix::bstr::BStr;
+
+use crate::util::named_repo;
+
+fn blamed_commits(repo: &gix::Repository, outcome: &gix::blame::Outcome) -> crate::Result<Vec<(u32, String)>> {
+    outcome
+        .entries
+        .iter()
+        .map(|entry| {
+            let commit = repo.find_object(entry.commit_id)?.try_into_commit()?;
+            Ok((
+                entry.len.get(),
+                commit.message_raw_sloppy().to_string().trim().to_owned(),
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn with_configured_options_follows_renames_and_ignores_revs() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let options = repo.blame_options()?;
+    assert_eq!(
+        options.ignore_revs.iter().copied().collect::<Vec<_>>(),
+        [repo.rev_parse_single("reformat")?.detach()],
+        "the revisions to ignore are read from `blame.ignoreRevsFile`"
+    );
+    assert!(options.rewrites.is_some(), "renames are always followed");
+
+    let outcome = repo.blame("renamed.txt", repo.head_id()?, options)?;
+    assert_eq!(
+        blamed_commits(&repo, &outcome)?,
+        [(3, "first".into()), (1, "rename".into())]
+    );
+    assert_eq!(
+        outcome.entries[0].source_file_name.as_ref().map(AsRef::<BStr>::as_ref),
+        Some("file.txt".into()),
+        "the original name of the file is recorded"
+    );
+    assert_eq!(outcome.blob, b"A\nb\nC\nd\n");
+    Ok(())
+}
+
+#[test]
+fn commits_have_their_signatures_mapped_with_the_mailmap() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo.blame("renamed.txt", repo.head_id()?, repo.blame_options()?)?;
+    assert_eq!(outcome.commits.len(), 2, "there is one entry for each blamed commit");
+    for entry in &outcome.entries {
+        let commit = &outcome.commits[&entry.commit_id];
+        assert_eq!(commit.author.name, "Mapped Author");
+        assert_eq!(commit.author.email, "mapped@example.com");
+        assert_eq!(
+            commit.committer.email, "committer@example.com",
+            "unmapped signatures stay unchanged"
+        );
+    }
+    let first = &outcome.commits[&outcome.entries[0].commit_id];
+    assert_eq!(first.summary, "first");
+    assert!(first.is_boundary, "the root commit is a boundary");
+    assert!(!outcome.commits[&outcome.entries[1].commit_id].is_boundary);
+    Ok(())
+}
+
+#[test]
+fn without_rename_tracking() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let outcome = repo.blame("renamed.txt", repo.head_id()?, Default::default())?;
+    assert_eq!(
+        blamed_commits(&repo, &outcome)?,
+        [(4, "rename".into())],
+        "the file didn't exist before so all lines are blamed on the commit that added it"
+    );
+    Ok(())
+}
+
+#[test]
+fn without_ignored_revs() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut options = repo.blame_options()?;
+    options.ignore_revs.clear();
+    let outcome = repo.blame("renamed.txt", repo.rev_parse_single("rename")?, options)?;
+    assert_eq!(
+        blamed_commits(&repo, &outcome)?,
+        [
+            (1, "reformat".into()),
+            (1, "first".into()),
+            (1, "reformat".into()),
+            (1, "rename".into())
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental_entries_are_passed_with_their_commit() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut seen = Vec::new();
+    let outcome = repo.blame_incremental(
+        "renamed.txt",
+        repo.head_id()?,
+        repo.blame_options()?,
+        |entry, commit| {
+            seen.push((entry.clone(), commit.summary.clone()));
+            Ok(())
+        },
+    )?;
+    assert_eq!(
+        seen.iter().map(|(entry, _)| entry.len.get()).sum::<u32>(),
+        outcome.entries.iter().map(|entry| entry.len.get()).sum::<u32>(),
+        "all lines are passed exactly once, even though adjacent entries aren't merged yet"
+    );
+    for (entry, summary) in &seen {
+        assert_eq!(*summary, outcome.commits[&entry.commit_id].summary);
+    }
+    Ok(())
+}
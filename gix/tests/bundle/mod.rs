@@ -0,0 +1,207 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix::bundle;
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_bundle_repo.sh")
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+}
+
+#[test]
+fn create_is_understood_by_git() -> crate::Result {
+    let fixture = fixture()?;
+    let repo = gix::open_opts(fixture.join("repo"), crate::restricted())?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+
+    let path = tmp.path().join("full.bundle");
+    let header = repo.create_bundle(
+        ["main", "v1"],
+        &mut std::fs::File::create(&path)?,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(header.prerequisites.is_empty());
+    assert_eq!(
+        header.refs.iter().map(|r| r.name.to_string()).collect::<Vec<_>>(),
+        ["refs/heads/main", "refs/tags/v1"]
+    );
+    assert_eq!(
+        header.refs[1].id,
+        repo.find_reference("v1")?.id(),
+        "tags aren't peeled, just like git does it"
+    );
+    assert_eq!(
+        bundle::File::at(&path)?.header,
+        header,
+        "the header is written as returned"
+    );
+
+    let path = path.to_str().expect("valid UTF-8");
+    git(&fixture.join("repo"), &["bundle", "verify", "-q", path])?;
+    git(tmp.path(), &["clone", "-q", "--branch", "main", path, "clone"])?;
+    git(&tmp.path().join("clone"), &["fsck", "--strict"])?;
+    assert_eq!(git(&tmp.path().join("clone"), &["rev-list", "--count", "HEAD"])?, "4");
+    Ok(())
+}
+
+#[test]
+fn create_with_prerequisites() -> crate::Result {
+    let fixture = fixture()?;
+    let repo = gix::open_opts(fixture.join("repo"), crate::restricted())?;
+    let mut out = Vec::new();
+    let header = repo.create_bundle(
+        ["v1..main", "dev"],
+        &mut out,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    let v1 = repo.rev_parse_single("v1^{commit}")?.detach();
+    assert_eq!(
+        header.prerequisites.len(),
+        1,
+        "commits of `dev` reachable from `v1` are excluded"
+    );
+    assert_eq!(header.prerequisites[0].id, v1);
+    assert_eq!(header.prerequisites[0].comment, "commit 2");
+    assert_eq!(
+        header.refs.iter().map(|r| r.name.to_string()).collect::<Vec<_>>(),
+        ["refs/heads/main", "refs/heads/dev"]
+    );
+
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let path = tmp.path().join("incremental.bundle");
+    std::fs::write(&path, &out)?;
+    git(
+        &fixture.join("base"),
+        &["bundle", "verify", "-q", path.to_str().expect("valid UTF-8")],
+    )?;
+
+    for (spec, expected) in [("main...dev", "can't be used"), ("^main", "without references")] {
+        let err = repo
+            .create_bundle([spec], &mut Vec::new(), gix::progress::Discard, &AtomicBool::default())
+            .expect_err("invalid");
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+    Ok(())
+}
+
+#[test]
+fn verify_and_unbundle() -> crate::Result {
+    let fixture = fixture()?;
+    let incremental = bundle::File::at(fixture.join("incremental.bundle"))?;
+
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let empty = gix::init_bare(tmp.path())?;
+    let err = empty
+        .verify_bundle(&incremental.header)
+        .expect_err("prerequisite missing");
+    assert!(matches!(
+        err,
+        bundle::verify::Error::MissingPrerequisites { ref missing } if missing.len() == 1
+    ));
+    assert!(
+        empty
+            .unbundle(&incremental, &mut gix::progress::Discard, &AtomicBool::default())
+            .is_err(),
+        "bundles are verified before unbundling"
+    );
+
+    let tmp = gix_testtools::scripted_fixture_writable("make_bundle_repo.sh")?;
+    let base = gix::open_opts(tmp.path().join("base"), crate::restricted())?;
+    base.verify_bundle(&incremental.header)?;
+    let main = incremental.header.refs[0].id;
+    assert!(!base.has_object(main));
+    let outcome = base.unbundle(&incremental, &mut gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(outcome.index.num_objects, 6, "two commits, two trees and two blobs");
+    assert!(
+        outcome.keep_path.map_or(true, |path| !path.exists()),
+        "keep files are removed"
+    );
+    assert!(base.has_object(main));
+    assert_eq!(base.find_object(main)?.into_commit().ancestors().all()?.count(), 4);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "blocking-network-client")]
+fn clone_from_bundle() -> crate::Result {
+    let fixture = fixture()?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (repo, _outcome) = gix::clone::PrepareFetch::new(
+        fixture.join("full.bundle"),
+        tmp.path(),
+        gix::create::Kind::Bare,
+        Default::default(),
+        crate::restricted(),
+    )?
+    .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+    let remote = gix::open_opts(fixture.join("repo"), crate::restricted())?;
+    for (ours, theirs) in [
+        ("refs/heads/main", "refs/heads/main"),
+        ("refs/remotes/origin/dev", "refs/heads/dev"),
+        ("refs/tags/v1", "refs/tags/v1"),
+    ] {
+        assert_eq!(
+            repo.find_reference(ours)?.id(),
+            remote.find_reference(theirs)?.id(),
+            "{ours} matches {theirs}"
+        );
+    }
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/main",
+        "HEAD is inferred from the branch pointing to the same commit"
+    );
+    assert_eq!(repo.head_id()?.ancestors().all()?.count(), 4);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "blocking-network-client")]
+fn fetch_from_incremental_bundle() -> crate::Result {
+    use gix::remote::Direction::{Fetch, Push};
+
+    let tmp = gix_testtools::scripted_fixture_writable("make_bundle_repo.sh")?;
+    let root = tmp.path();
+    let base = gix::open_opts(root.join("base"), crate::restricted())?;
+    let remote = base
+        .remote_at(root.join("incremental.bundle"))?
+        .with_refspecs(Some("refs/heads/*:refs/remotes/bundle/*"), Fetch)?;
+    remote
+        .connect(Fetch)?
+        .prepare_fetch(gix::progress::Discard, Default::default())?
+        .receive(gix::progress::Discard, &AtomicBool::default())?;
+
+    let origin = gix::open_opts(root.join("repo"), crate::restricted())?;
+    assert_eq!(
+        base.find_reference("refs/remotes/bundle/main")?.id(),
+        origin.find_reference("main")?.id()
+    );
+
+    assert!(matches!(
+        remote.connect(Push).map(|_| ()).expect_err("read-only"),
+        gix::remote::connect::Error::PushToBundle { .. }
+    ));
+
+    let empty = gix::init_bare(root.join("empty"))?;
+    let err = empty
+        .remote_at(root.join("incremental.bundle"))?
+        .connect(Fetch)
+        .map(|_| ())
+        .expect_err("prerequisites are missing");
+    assert!(matches!(err, gix::remote::connect::Error::VerifyBundle(_)), "{err}");
+    Ok(())
+}
@@ -508,6 +508,37 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "lfs")]
+    fn fetch_and_checkout_with_lfs_from_local_remote() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            gix_testtools::scripted_fixture_read_only("make_lfs_repo.sh")?,
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?;
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let work_dir = repo.work_dir().expect("non-bare");
+        assert_eq!(
+            std::fs::read(work_dir.join("large.bin"))?.as_bstr(),
+            "large file content",
+            "the LFS object is downloaded from the remote and checked out instead of the pointer"
+        );
+        assert_eq!(std::fs::read(work_dir.join("small.txt"))?.as_bstr(), "small\n");
+
+        let pointer = gix::lfs::Pointer::from_bytes(&repo.find_object(repo.rev_parse_single("HEAD:large.bin")?)?.data)?;
+        assert!(
+            gix::lfs::Store::at(repo.git_dir().join("lfs")).contains(&pointer),
+            "the object is now available locally"
+        );
+        Ok(())
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         for version in [
@@ -582,6 +613,41 @@ mod blocking_io {
         }
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn fetch_only_from_sha256_repository() -> crate::Result {
+        let remote_repo = crate::util::named_repo("make_sha256_repo.sh")?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, _out) = gix::prepare_clone_bare(remote_repo.git_dir(), tmp.path())?
+            .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        assert_eq!(
+            repo.object_hash(),
+            gix::hash::Kind::Sha256,
+            "the object format of the remote is adopted"
+        );
+        assert_eq!(
+            repo.head_id()?,
+            remote_repo.head_id()?,
+            "HEAD was set and all objects were received"
+        );
+        assert_eq!(repo.find_reference("v1")?.id(), remote_repo.find_reference("v1")?.id());
+        assert!(repo.find_remote("origin").is_ok(), "the remote is still configured");
+
+        let repo = gix::open_opts(repo.git_dir(), gix::open::Options::isolated())?;
+        assert_eq!(
+            repo.object_hash(),
+            gix::hash::Kind::Sha256,
+            "the object format was persisted"
+        );
+        assert_eq!(repo.remote_names().len(), 1, "the remote wasn't configured twice");
+        assert_eq!(
+            repo.head_commit()?.message_raw()?,
+            "third\n",
+            "objects can be read after reopening"
+        );
+        Ok(())
+    }
 }
 
 #[test]
@@ -176,6 +176,72 @@ mod status {
     }
 }
 
+#[cfg(feature = "notes")]
+mod notes {
+    use crate::config::tree::bcow;
+    use gix::config::tree::{Key, Notes};
+    use gix::note::merge::Strategy;
+
+    #[test]
+    fn merge_strategy() -> crate::Result {
+        for (actual, expected) in [
+            ("manual", Strategy::Manual),
+            ("ours", Strategy::Ours),
+            ("theirs", Strategy::Theirs),
+            ("union", Strategy::Union),
+            ("cat_sort_uniq", Strategy::CatSortUniq),
+        ] {
+            assert_eq!(Notes::MERGE_STRATEGY.try_into_merge_strategy(bcow(actual))?, expected);
+            assert!(Notes::MERGE_STRATEGY.validate(actual.into()).is_ok());
+        }
+
+        assert_eq!(
+            Notes::MERGE_STRATEGY
+                .try_into_merge_strategy(bcow("Union"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"notes.mergeStrategy=Union\" was invalid",
+            "case-sensitive comparisons"
+        );
+        assert_eq!(Notes::NAMED_MERGE_STRATEGY.logical_name(), "notes.<name>.mergeStrategy");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "signing")]
+mod gpg {
+    use gix::{
+        config::tree::{gpg, Gpg, Key},
+        signing::Format,
+    };
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn format() -> crate::Result {
+        for (actual, expected) in [
+            ("openpgp", Format::OpenPgp),
+            ("x509", Format::X509),
+            ("ssh", Format::Ssh),
+        ] {
+            assert_eq!(Gpg::FORMAT.try_into_format(bcow(actual))?, expected);
+            assert!(Gpg::FORMAT.validate(actual.into()).is_ok());
+        }
+
+        assert_eq!(
+            Gpg::FORMAT.try_into_format(bcow("SSH")).unwrap_err().to_string(),
+            "The key \"gpg.format=SSH\" was invalid",
+            "case-sensitive comparisons"
+        );
+        assert_eq!(
+            gpg::Ssh::ALLOWED_SIGNERS_FILE.logical_name(),
+            "gpg.ssh.allowedSignersFile"
+        );
+        assert_eq!(gpg::OpenPgp::PROGRAM.logical_name(), "gpg.openpgp.program");
+        Ok(())
+    }
+}
+
 mod push {
     use crate::config::tree::bcow;
     use gix::config::tree::Push;
@@ -346,17 +412,12 @@ mod diff {
             ("Default", Algorithm::Myers),
             ("minimal", Algorithm::MyersMinimal),
             ("histogram", Algorithm::Histogram),
+            ("patience", Algorithm::Patience),
+            ("Patience", Algorithm::Patience),
         ] {
             assert_eq!(Diff::ALGORITHM.try_into_algorithm(bcow(actual))?, expected);
             assert!(Diff::ALGORITHM.validate(actual.into()).is_ok());
         }
-        assert_eq!(
-            Diff::ALGORITHM
-                .try_into_algorithm(bcow("patience"))
-                .unwrap_err()
-                .to_string(),
-            "The 'patience' algorithm is not yet implemented"
-        );
         assert_eq!(
             Diff::ALGORITHM.try_into_algorithm(bcow("foo")).unwrap_err().to_string(),
             "Unknown diff algorithm named 'foo'"
@@ -466,6 +527,65 @@ mod core {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "status")]
+    fn untracked_cache() -> crate::Result {
+        use gix::status::UntrackedCache;
+        assert_eq!(
+            Core::UNTRACKED_CACHE.try_into_untracked_cache(Some(Ok(true)))?,
+            Some(UntrackedCache::Enable)
+        );
+        assert!(Core::UNTRACKED_CACHE.validate("true".into()).is_ok());
+        assert_eq!(
+            Core::UNTRACKED_CACHE.try_into_untracked_cache(Some(Ok(false)))?,
+            Some(UntrackedCache::Disable)
+        );
+        assert!(Core::UNTRACKED_CACHE.validate("0".into()).is_ok());
+        let boolean = |value| {
+            gix_config::Boolean::try_from(bcow(value))
+                .map(|b| Some(b.0))
+                .transpose()
+        };
+        assert_eq!(
+            Core::UNTRACKED_CACHE.try_into_untracked_cache(boolean("keep"))?,
+            Some(UntrackedCache::Keep)
+        );
+        assert!(Core::UNTRACKED_CACHE.validate("keep".into()).is_ok());
+        assert_eq!(
+            Core::UNTRACKED_CACHE
+                .try_into_untracked_cache(boolean("invalid"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.untrackedCache=invalid\" was invalid"
+        );
+        assert!(Core::UNTRACKED_CACHE.validate("invalid".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "status")]
+    fn fsmonitor_hook_version() -> crate::Result {
+        use gix::status::plumbing::fsmonitor::hook::Version;
+        assert_eq!(
+            Core::FSMONITOR_HOOK_VERSION.try_into_hook_version(bcow("1"))?,
+            Version::V1
+        );
+        assert_eq!(
+            Core::FSMONITOR_HOOK_VERSION.try_into_hook_version(bcow("2"))?,
+            Version::V2
+        );
+        assert!(Core::FSMONITOR_HOOK_VERSION.validate("2".into()).is_ok());
+        assert_eq!(
+            Core::FSMONITOR_HOOK_VERSION
+                .try_into_hook_version(bcow("3"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.fsmonitorHookVersion=3\" was invalid"
+        );
+        assert!(Core::FSMONITOR_HOOK_VERSION.validate("3".into()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn abbrev() -> crate::Result {
         let object_hash = gix_hash::Kind::Sha1;
@@ -667,6 +787,11 @@ mod extensions {
             gix_hash::Kind::Sha1,
             "case-insensitive"
         );
+        #[cfg(feature = "sha256")]
+        assert_eq!(
+            Extensions::OBJECT_FORMAT.try_into_object_format(bcow("sha256"))?,
+            gix_hash::Kind::Sha256
+        );
         assert_eq!(
             Extensions::OBJECT_FORMAT
                 .try_into_object_format(bcow("invalid"))
@@ -675,9 +800,35 @@ mod extensions {
             "The key \"extensions.objectFormat=invalid\" was invalid"
         );
         assert!(Extensions::OBJECT_FORMAT.validate("sha1".into()).is_ok());
+        assert_eq!(
+            Extensions::OBJECT_FORMAT.validate("sha256".into()).is_ok(),
+            cfg!(feature = "sha256")
+        );
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_storage(bcow("files"))?,
+            gix_ref::store::RefStorage::Files
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE.try_into_ref_storage(bcow("reftable"))?,
+            gix_ref::store::RefStorage::Reftable
+        );
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_ref_storage(bcow("invalid"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=invalid\" was invalid"
+        );
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("invalid".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
@@ -47,3 +47,49 @@ fn resource_cache() -> crate::Result {
     );
     Ok(())
 }
+
+#[test]
+fn render_options() -> crate::Result {
+    use gix::diff::blob::{header::PrefixStyle, ContextSize};
+    let mut repo = named_repo("make_diff_repo.sh")?;
+    assert_eq!(
+        repo.diff_render_options()?,
+        gix::diff::RenderOptions {
+            context_size: ContextSize::symmetrical(3),
+            inter_hunk_context: 0,
+            prefix_style: PrefixStyle::Default,
+        },
+        "defaults are the same as in git"
+    );
+
+    let mut config = repo.config_snapshot_mut();
+    config.set_raw_value("diff", None, "context", "5")?;
+    config.set_raw_value("diff", None, "interHunkContext", "2")?;
+    config.set_raw_value("diff", None, "mnemonicPrefix", "true")?;
+    drop(config);
+    assert_eq!(
+        repo.diff_render_options()?,
+        gix::diff::RenderOptions {
+            context_size: ContextSize::symmetrical(5),
+            inter_hunk_context: 2,
+            prefix_style: PrefixStyle::Mnemonic,
+        }
+    );
+
+    repo.config_snapshot_mut()
+        .set_raw_value("diff", None, "noprefix", "true")?;
+    assert_eq!(
+        repo.diff_render_options()?.prefix_style,
+        PrefixStyle::None,
+        "noprefix takes precedence"
+    );
+
+    repo.config_snapshot_mut()
+        .set_raw_value("diff", None, "context", "-1")?;
+    assert_eq!(
+        repo.diff_render_options()?.context_size,
+        ContextSize::symmetrical(3),
+        "invalid values are defaulted in lenient mode, which is the default"
+    );
+    Ok(())
+}
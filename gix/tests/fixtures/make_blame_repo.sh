@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+printf 'a\nb\nc\n' > file.txt
+git add . && git commit -q -m first
+git tag first
+
+printf 'A\nb\nC\n' > file.txt
+git commit -q -am reformat
+git tag reformat
+
+git mv file.txt renamed.txt
+printf 'd\n' >> renamed.txt
+git commit -q -am rename
+git tag rename
+
+git rev-parse reformat > .git-blame-ignore-revs
+git config blame.ignoreRevsFile .git-blame-ignore-revs
+git commit-graph write --no-progress --reachable
+
+echo 'Mapped Author <mapped@example.com> <author@example.com>' > .mailmap
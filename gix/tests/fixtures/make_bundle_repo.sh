@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  for i in 1 2 3 4; do
+    echo "$i" > "file$i"
+    git add .
+    git commit -q -m "commit $i"
+    if [ $i = 2 ]; then
+      git tag -a -m "version 1" v1
+    fi
+  done
+  git branch dev main~1
+
+  git bundle create -q ../full.bundle --all
+  git bundle create -q ../incremental.bundle v1..main
+)
+
+git init -q --bare base
+git -C base fetch -q ../repo refs/tags/v1:refs/tags/v1
@@ -0,0 +1,28 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+mkdir -p dir/sub
+echo a > a
+echo b > dir/b
+git add . && git commit -q -m c1
+
+echo c > dir/sub/c
+git add . && git commit -q -m c2
+
+echo a2 > a
+git commit -q -am c3
+
+git checkout -q -b side
+echo side > dir/b
+git commit -q -am side
+
+git checkout -q main
+echo other > other
+git add . && git commit -q -m c4
+
+git merge -q --no-ff -m merge side
+
+git commit-graph write --no-progress --reachable --changed-paths
@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git config core.autocrlf false
+
+echo "ignored*" > .gitignore
+echo "*.txt text" > .gitattributes
+echo a > a
+echo b > b
+mkdir -p dir/sub
+echo c > dir/c
+echo d > dir/sub/d
+git add .
+git commit -q -m "initial commit"
+
+echo changed >> a
+rm b
+chmod +x dir/c
+echo new > dir/sub/new
+echo ignored > ignored-file
+mkdir ignored-dir
+echo ignored > ignored-dir/file
+printf 'one\r\ntwo\r\n' > crlf.txt
+ln -s a link
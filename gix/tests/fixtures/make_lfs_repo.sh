@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+content="large file content"
+oid=$(printf '%s' "$content" | sha256sum | cut -d ' ' -f 1)
+mkdir -p .git/lfs/objects/${oid:0:2}/${oid:2:2}
+printf '%s' "$content" > .git/lfs/objects/${oid:0:2}/${oid:2:2}/$oid
+
+echo '*.bin filter=lfs diff=lfs merge=lfs -text' > .gitattributes
+cat <<POINTER > large.bin
+version https://git-lfs.github.com/spec/v1
+oid sha256:$oid
+size ${#content}
+POINTER
+echo small > small.txt
+
+git add .
+git commit -q -m "add large file"
@@ -0,0 +1,28 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 1 9 > file
+echo unrelated > unrelated
+git add . && git commit -q -m base
+git tag base
+
+git checkout -q -b ours
+sed -i 's/^2$/2-ours/' file
+git commit -q -am ours
+
+git checkout -q -b theirs base
+sed -i 's/^8$/8-theirs/' file
+git mv file renamed
+git commit -q -am theirs
+
+git checkout -q -b conflicting base
+sed -i 's/^2$/2-conflicting/' file
+git commit -q -am conflicting
+
+git checkout -q main
+
+git merge-tree --write-tree ours theirs > clean.expected
+git merge-tree --write-tree --name-only --no-messages ours conflicting > conflicting.expected || true
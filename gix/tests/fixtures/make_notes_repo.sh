@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for name in first second third; do
+  git commit -q --allow-empty -m "$name"
+  git tag "$name"
+done
+
+git notes add -m "default" first
+git notes --ref ci add -m "ci passed" second
+git notes --ref review add -m "looks good" third
+git config notes.displayRef refs/notes/ci
+git config --add notes.displayRef 'refs/notes/rev*'
+
+git notes --ref base add -m "base" first
+git update-ref refs/notes/ahead refs/notes/base
+git notes --ref ahead add -m "ahead" second
+
+git update-ref refs/notes/ours refs/notes/base
+git update-ref refs/notes/theirs refs/notes/base
+git notes --ref ours append -m "ours" first
+git notes --ref theirs append -m "theirs" first
+git notes --ref theirs add -m "theirs only" third
+git config notes.ours.mergeStrategy cat_sort_uniq
+
+git update-ref refs/notes/merged-by-git refs/notes/ours
+git notes --ref merged-by-git merge -q -s cat_sort_uniq refs/notes/theirs
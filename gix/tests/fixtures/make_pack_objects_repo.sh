@@ -0,0 +1,10 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+echo "*.bin -delta" > .gitattributes
+echo "binary" > data.bin
+echo "text" > file.txt
+git add .
+git commit -q -m "initial"
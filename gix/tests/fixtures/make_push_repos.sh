@@ -0,0 +1,43 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# IMPORTANT: keep this repo small as it's used for writes, hence will be executed for each writer!
+git init -q base
+(cd base
+  touch a
+  git add a
+  git commit -q -m c1
+  git tag -m "annotated" v1.0
+  git branch diverged
+  git branch to-delete
+  git branch other
+  git branch skewed
+)
+
+git clone -q --bare base remote.git
+
+git clone -q remote.git local
+(cd local
+  git config push.default simple
+  echo b > b
+  git add b
+  git commit -q -m c2
+
+  git checkout -q -b diverged origin/diverged
+  git commit -q --allow-empty -m "local diverged"
+
+  git checkout -q -b skewed origin/skewed
+  GIT_COMMITTER_DATE="1990-01-01 00:00:00 +0000" git commit -q --allow-empty -m "committed with a clock that is behind"
+  git commit -q --allow-empty -m "committed with the correct time"
+  git checkout -q main
+  git commit-graph write --no-progress --reachable
+)
+
+git clone -q remote.git other-clone
+(cd other-clone
+  git checkout -q -b diverged origin/diverged
+  git commit -q --allow-empty -m "remote diverged"
+  git checkout -q -b other origin/other
+  git commit -q --allow-empty -m "unknown to local"
+  git push -q origin diverged other
+)
@@ -0,0 +1,44 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function baseline() {
+  git init -q
+  git checkout -q -b main
+  printf '1\n2\n3\n' >a
+  git add a
+  git commit -q -m "base"
+  git tag base
+
+  git checkout -q -b upstream
+  printf 'upstream\n2\n3\n' >a
+  echo u >u
+  git add a u
+  git commit -q -m "upstream"
+  git checkout -q main
+}
+
+(mkdir feature && cd feature
+  baseline
+  git checkout -q -b feature base
+  echo one >f
+  git add f
+  git commit -q -m "one"
+  git branch part
+  echo two >g
+  git add g
+  git commit -q -m "two"
+  echo "one fixed" >f
+  git add f
+  git commit -q -m "fixup! one"
+)
+
+(mkdir conflict && cd conflict
+  baseline
+  git checkout -q -b conflict base
+  printf 'conflict\n2\n3\n' >a
+  git add a
+  git commit -q -m "conflicting"
+  echo c >c
+  git add c
+  git commit -q -m "after"
+)
@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256
+git checkout -q -b main
+
+echo a > a
+mkdir dir && echo b > dir/b
+git add . && git commit -q -m first
+git tag -a -m "annotated" v1
+
+echo c >> a
+git commit -q -am second
+git gc -q
+
+echo d > d
+git add d && git commit -q -m third
+
+git commit-graph write --no-progress --reachable
+git multi-pack-index write --no-progress
@@ -0,0 +1,16 @@
+#!/bin/bash
+set -eu -o pipefail
+
+ssh-keygen -q -t ed25519 -N "" -C signer@example.com -f signer-key
+ssh-keygen -q -t ed25519 -N "" -C stranger@example.com -f stranger-key
+echo "signer@example.com $(cat signer-key.pub)" > allowed-signers
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  git config gpg.format ssh
+
+  echo a > a
+  git add a
+  git commit -q -m "initial commit"
+)
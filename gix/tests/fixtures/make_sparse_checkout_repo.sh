@@ -0,0 +1,10 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+touch a b
+mkdir -p c1/c2 c1/c3 d/c4/c5 e
+touch c1/a c1/c2/a c1/c3/a d/c4/a d/c4/c5/a e/a
+git add .
+git commit -q -m "initial commit"
@@ -0,0 +1,19 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+printf '1\n2\n3\n' >a
+echo b >b
+mkdir dir
+echo c >dir/c
+git add .
+git commit -q -m "initial commit"
+
+printf '1\n2\n3\nworktree\n' >a
+git stash -q
+
+echo "b staged" >b
+git add b
+echo "c in worktree" >dir/c
+git stash push -q -m "second"
@@ -13,3 +13,29 @@ git init -q untracked-only
   mkdir new
   touch new/untracked subdir/untracked
 )
+
+git init -q staged-changes
+(cd staged-changes
+  echo "content" > content
+  mkdir dir
+  echo "to be renamed" > dir/original
+
+  git add .
+  git commit -q -m init
+
+  echo "change" >> content
+  git mv dir/original renamed
+  echo "new" > added
+  git add content added
+  echo "unstaged" >> content
+)
+
+git init -q staged-only
+(cd staged-only
+  touch tracked
+  git add tracked
+  git commit -q -m init
+
+  touch added
+  git add added
+)
@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for i in 1 2 3 4 5; do
+  echo "$i" > "file$i"
+  if [ $i = 1 ]; then
+    head -c 4096 /dev/zero | tr '\0' 'x' > large
+  fi
+  git add .
+  GIT_COMMITTER_DATE="$((1000000000 + i * 86400)) +0000" git commit -q -m "commit $i"
+  if [ $i = 2 ]; then
+    git tag -a -m "version 1" v1
+  fi
+done
+
+git checkout -q -b dev HEAD~2
+echo dev > dev
+git add dev
+GIT_COMMITTER_DATE="$((1000000000 + 6 * 86400)) +0000" git commit -q -m "dev"
+git checkout -q main
@@ -2,6 +2,10 @@
 mod util;
 use util::*;
 
+#[cfg(feature = "blame")]
+mod blame;
+#[cfg(feature = "bundle")]
+mod bundle;
 mod clone;
 mod commit;
 mod config;
@@ -9,14 +13,30 @@ mod config;
 mod diff;
 mod head;
 mod id;
+#[cfg(feature = "index-mutation")]
+mod index_mutation;
 mod init;
+#[cfg(feature = "merge")]
+mod merge;
+#[cfg(feature = "notes")]
+mod note;
 mod object;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
 mod repository;
 #[cfg(feature = "revision")]
 mod revision;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "sparse-checkout")]
+mod sparse_checkout;
+#[cfg(feature = "stash")]
+mod stash;
 #[cfg(feature = "status")]
 mod status;
 #[cfg(feature = "attributes")]
 mod submodule;
+#[cfg(feature = "blocking-network-server")]
+mod upload_pack;
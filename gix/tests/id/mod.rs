@@ -134,6 +134,38 @@ mod ancestors {
         Ok(())
     }
 
+    #[test]
+    fn paths() -> crate::Result {
+        let repo = crate::named_repo("make_changed_paths_repo.sh")?;
+        let head = repo.head_id()?;
+        for use_commit_graph in [false, true] {
+            for (paths, expected) in [
+                (&["a"][..], &["c3", "c1"][..]),
+                (&["dir"], &["side", "c2", "c1"]),
+                (&["dir/"], &["side", "c2", "c1"]),
+                (&["dir/sub/c"], &["c2"]),
+                (&["other"], &["c4"]),
+                (&["other", "dir/sub"], &["c4", "c2"]),
+                (&["a", "dir/b"], &["side", "c3", "c1"]),
+                (&["does-not-exist", "a/b"], &[]),
+            ] {
+                let actual = head
+                    .ancestors()
+                    .sorting(commit::simple::Sorting::ByCommitTimeNewestFirst)
+                    .use_commit_graph(use_commit_graph)
+                    .paths(paths.iter().copied())
+                    .all()?
+                    .map(|info| -> crate::Result<_> { Ok(info?.object()?.message()?.summary().to_string()) })
+                    .collect::<Result<Vec<_>, _>>()?;
+                assert_eq!(
+                    actual, expected,
+                    "{paths:?}: merges are only included if they differ from all parents, commit-graph: {use_commit_graph}"
+                );
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn filtered() -> crate::Result {
         let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
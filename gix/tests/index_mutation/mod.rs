@@ -0,0 +1,312 @@
+use std::path::Path;
+
+use gix::{
+    bstr::BStr,
+    index_mutation::{add, remove, rename},
+};
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable_with_args(
+        "make_index_mutation_repo.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )?;
+    let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+    Ok((repo, tmp))
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+}
+
+fn bstrs<'a>(patterns: &'a [&'a str]) -> impl Iterator<Item = &'a BStr> {
+    patterns.iter().map(|pattern| (*pattern).into())
+}
+
+/// Rebuild the index with `git add --all` and assert it has the same entries and tree as the one we wrote.
+fn assert_index_matches_git_add_all(dir: &Path) -> crate::Result {
+    let entries = git(dir, &["ls-files", "--stage"])?;
+    let tree = git(dir, &["write-tree"])?;
+    std::fs::remove_file(dir.join(".git/index"))?;
+    git(dir, &["add", "--all"])?;
+    assert_eq!(git(dir, &["ls-files", "--stage"])?, entries);
+    assert_eq!(
+        git(dir, &["write-tree"])?,
+        tree,
+        "the TREE extension was invalidated where needed"
+    );
+    Ok(())
+}
+
+#[test]
+fn add_everything_matches_git() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let mut index = repo.index_mut()?;
+    assert!(
+        index.index().tree().expect("written by git").num_entries.is_some(),
+        "the TREE extension is valid initially"
+    );
+    let outcome = index.add(bstrs(&[]), Default::default())?;
+    assert_eq!(
+        outcome,
+        add::Outcome {
+            added: vec!["crlf.txt".into(), "dir/sub/new".into(), "link".into()],
+            modified: vec!["a".into(), "dir/c".into()],
+            removed: vec!["b".into()],
+            ignored: Vec::new(),
+        },
+        "ignored files are skipped silently unless they are named explicitly"
+    );
+    let tree = index.index().tree().expect("still present");
+    assert_eq!(tree.num_entries, None, "the root tree is invalidated");
+    index.write()?;
+
+    assert_eq!(
+        git(dir, &["status", "--porcelain"])?,
+        "M  a\nD  b\nA  crlf.txt\nM  dir/c\nA  dir/sub/new\nA  link"
+    );
+    assert_eq!(git(dir, &["show", ":crlf.txt"])?, "one\ntwo", "filters are applied");
+    assert_eq!(
+        git(dir, &["ls-files", "--stage", "dir/c", "link"])?,
+        "100755 f2ad6c76f0115a6ba5b00456a849810e7ec0af20 0\tdir/c\n120000 2e65efe2a145dda7ee51d1741299f848e5bf752e 0\tlink",
+        "executable bits and symlinks are recorded"
+    );
+    assert_index_matches_git_add_all(dir)
+}
+
+#[test]
+fn add_with_patterns_and_update_only() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let mut index = repo.index_mut()?;
+    let outcome = index.add(
+        bstrs(&["dir"]),
+        add::Options {
+            update_only: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome,
+        add::Outcome {
+            modified: vec!["dir/c".into()],
+            ..Default::default()
+        }
+    );
+
+    let outcome = index.add(bstrs(&["dir"]), Default::default())?;
+    assert_eq!(
+        outcome,
+        add::Outcome {
+            added: vec!["dir/sub/new".into()],
+            ..Default::default()
+        },
+        "unchanged entries aren't reported"
+    );
+    index.write()?;
+    assert_eq!(
+        git(dir, &["status", "--porcelain"])?,
+        " M a\n D b\nM  dir/c\nA  dir/sub/new\n?? crlf.txt\n?? link"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignored_files_are_only_added_with_force() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let mut index = repo.index_mut()?;
+    let outcome = index.add(bstrs(&["ignored-file", "dir/sub/new"]), Default::default())?;
+    assert_eq!(
+        outcome,
+        add::Outcome {
+            added: vec!["dir/sub/new".into()],
+            ignored: vec!["ignored-file".into()],
+            ..Default::default()
+        },
+        "explicitly named ignored files are reported"
+    );
+
+    let force = add::Options {
+        force: true,
+        ..Default::default()
+    };
+    let outcome = index.add(bstrs(&["ignored-file"]), force)?;
+    assert_eq!(outcome.added, ["ignored-file"]);
+    let outcome = index.add(bstrs(&[]), force)?;
+    assert_eq!(
+        outcome.added,
+        ["crlf.txt", "ignored-dir/file", "link"],
+        "ignored directories are traversed as well"
+    );
+    index.write()?;
+    assert_eq!(
+        git(dir, &["ls-files", "ignored-dir", "ignored-file"])?,
+        "ignored-dir/file\nignored-file"
+    );
+    Ok(())
+}
+
+#[test]
+fn intent_to_add() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let mut index = repo.index_mut()?;
+    let outcome = index.add(
+        bstrs(&["dir/sub/new"]),
+        add::Options {
+            intent_to_add: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.added, ["dir/sub/new"]);
+    let entry = index.index().entry_by_path("dir/sub/new".into()).expect("added");
+    assert!(entry.flags.contains(gix::index::entry::Flags::INTENT_TO_ADD));
+    assert_eq!(entry.id, gix::ObjectId::empty_blob(repo.object_hash()));
+    index.write()?;
+    assert_eq!(git(dir, &["status", "--porcelain", "dir/sub/new"])?, " A dir/sub/new");
+
+    let mut index = repo.index_mut()?;
+    let outcome = index.add(bstrs(&["dir/sub/new"]), Default::default())?;
+    assert_eq!(
+        outcome.modified,
+        ["dir/sub/new"],
+        "files that were intended to be added are added for real"
+    );
+    index.write()?;
+    assert_eq!(git(dir, &["status", "--porcelain", "dir/sub/new"])?, "A  dir/sub/new");
+    Ok(())
+}
+
+#[test]
+fn remove_refuses_to_lose_changes() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let mut index = repo.index_mut()?;
+    assert!(matches!(
+        index.remove(bstrs(&["dir"]), Default::default()),
+        Err(remove::Error::NotRecursive { pattern }) if pattern == "dir"
+    ));
+    assert!(matches!(
+        index.remove(bstrs(&["a"]), Default::default()),
+        Err(remove::Error::LocalModifications { path }) if path == "a"
+    ));
+    assert!(
+        matches!(
+            index.remove(bstrs(&["dir/c"]), Default::default()),
+            Err(remove::Error::LocalModifications { path }) if path == "dir/c"
+        ),
+        "mode changes count as modification"
+    );
+
+    let cached = remove::Options {
+        cached: true,
+        ..Default::default()
+    };
+    let outcome = index.remove(bstrs(&["a"]), cached)?;
+    assert_eq!(outcome.removed, ["a"]);
+    assert!(dir.join("a").is_file(), "files are kept");
+
+    index.add(bstrs(&["a"]), Default::default())?;
+    assert!(matches!(
+        index.remove(bstrs(&["a"]), Default::default()),
+        Err(remove::Error::StagedChanges { path }) if path == "a"
+    ));
+
+    let outcome = index.remove(
+        bstrs(&["b", "dir/sub"]),
+        remove::Options {
+            recursive: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.removed, ["b", "dir/sub/d"]);
+    assert!(!dir.join("dir/sub/d").exists());
+    assert!(dir.join("dir/sub/new").is_file(), "untracked files are kept");
+    index.write()?;
+    assert_eq!(
+        git(dir, &["status", "--porcelain", "--untracked-files=no"])?,
+        "M  a\nD  b\n M dir/c\nD  dir/sub/d"
+    );
+
+    let mut index = repo.index_mut()?;
+    let outcome = index.remove(
+        bstrs(&["a", "dir/c"]),
+        remove::Options {
+            force: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.removed, ["a", "dir/c"]);
+    assert!(!dir.join("a").exists() && !dir.join("dir/c").exists());
+    assert!(dir.join("dir").is_dir(), "directories that aren't empty are kept");
+    Ok(())
+}
+
+#[test]
+fn rename_matches_git_mv() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let (_, expected) = repo_rw()?;
+    let mut index = repo.index_mut()?;
+    let outcome = index.rename("dir".into(), "moved".into())?;
+    assert_eq!(
+        outcome.moved,
+        [
+            ("dir/c".into(), "moved/c".into()),
+            ("dir/sub/d".into(), "moved/sub/d".into())
+        ]
+    );
+    let outcome = index.rename("a".into(), "moved/sub/".into())?;
+    assert_eq!(
+        outcome.moved,
+        [("a".into(), "moved/sub/a".into())],
+        "files are moved into existing directories"
+    );
+    index.write()?;
+    assert!(tmp.path().join("moved/sub/new").is_file(), "untracked files move along");
+
+    git(expected.path(), &["mv", "dir", "moved"])?;
+    git(expected.path(), &["mv", "a", "moved/sub/"])?;
+    for args in [
+        &["ls-files", "--stage"][..],
+        &["status", "--porcelain"],
+        &["write-tree"],
+    ] {
+        assert_eq!(git(tmp.path(), args)?, git(expected.path(), args)?, "{args:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn rename_errors() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let mut index = repo.index_mut()?;
+    assert!(matches!(
+        index.rename("dir/sub/new".into(), "x".into()),
+        Err(rename::Error::NotTracked { .. })
+    ));
+    assert!(matches!(
+        index.rename("b".into(), "x".into()),
+        Err(rename::Error::SourceMissing { .. })
+    ));
+    assert!(matches!(
+        index.rename("a".into(), "dir/c".into()),
+        Err(rename::Error::DestinationExists { .. })
+    ));
+    assert!(matches!(
+        index.rename("dir".into(), "dir/sub".into()),
+        Err(rename::Error::DestinationInsideSource { .. })
+    ));
+    assert!(matches!(
+        index.rename("a".into(), "missing/a".into()),
+        Err(rename::Error::DestinationDirectoryMissing { path }) if path == "missing"
+    ));
+    Ok(())
+}
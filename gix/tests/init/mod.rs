@@ -38,6 +38,54 @@ mod bare {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "sha256")]
+    fn init_with_sha256_object_format() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo: gix::Repository = gix::ThreadSafeRepository::init_opts(
+            tmp.path(),
+            gix::create::Kind::Bare,
+            gix::create::Options {
+                object_hash: gix::hash::Kind::Sha256,
+                ..Default::default()
+            },
+            gix::open::Options::isolated().config_overrides(["user.name=a", "user.email=a@example.com"]),
+        )?
+        .into();
+        assert_eq!(repo.object_hash(), gix::hash::Kind::Sha256);
+        let config = repo.config_snapshot();
+        assert_eq!(config.integer("core.repositoryFormatVersion"), Some(1));
+        assert_eq!(
+            config.string("extensions.objectFormat").expect("set").as_ref(),
+            "sha256"
+        );
+
+        let blob = repo.write_blob("hello\n")?;
+        assert_eq!(
+            blob,
+            gix::ObjectId::from_hex(b"2cf8d83d9ee29543b34a87727421fdecb7e3f3a183d337639025de576db9ebb4")?,
+            "matches `echo hello | git hash-object --stdin` in a SHA-256 repository"
+        );
+        let tree = repo.write_object(gix::objs::Tree {
+            entries: vec![gix::objs::tree::Entry {
+                mode: gix::objs::tree::EntryKind::Blob.into(),
+                filename: "file".into(),
+                oid: blob.detach(),
+            }],
+        })?;
+        let commit = repo.commit("HEAD", "initial", tree, gix::commit::NO_PARENT_IDS)?;
+
+        let repo = gix::open_opts(repo.git_dir(), gix::open::Options::isolated())?;
+        assert_eq!(repo.object_hash(), gix::hash::Kind::Sha256);
+        let head = repo.head_commit()?;
+        assert_eq!(head.id, commit);
+        assert_eq!(head.tree_id()?, tree);
+        let entry = head.tree()?.find_entry("file").expect("present").object()?;
+        assert_eq!(entry.id, blob);
+        assert_eq!(entry.data, b"hello\n");
+        Ok(())
+    }
+
     #[test]
     fn init_into_non_empty_directory_is_not_allowed() -> crate::Result {
         let tmp = tempfile::tempdir()?;
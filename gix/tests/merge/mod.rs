@@ -0,0 +1,62 @@
+use gix::merge::tree::ConflictKind;
+
+use crate::util::repo_rw;
+
+fn expected_tree(repo: &gix::Repository, name: &str) -> crate::Result<gix::ObjectId> {
+    let baseline = std::fs::read_to_string(repo.work_dir().expect("non-bare").join(name))?;
+    Ok(gix::ObjectId::from_hex(
+        baseline.lines().next().expect("tree id").as_bytes(),
+    )?)
+}
+
+#[test]
+fn merge_commits_with_rename_tracking() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let ours = repo.rev_parse_single("ours")?;
+    let theirs = repo.rev_parse_single("theirs")?;
+    assert_eq!(repo.merge_base(ours, theirs)?, repo.rev_parse_single("base")?);
+
+    let options = repo.tree_merge_options()?;
+    assert!(options.rewrites.is_some(), "rename tracking is enabled by default");
+    let outcome = repo.merge_commits(ours, theirs, Default::default(), options)?;
+    assert_eq!(outcome.merge_bases, [repo.rev_parse_single("base")?.detach()]);
+    assert!(!outcome.tree_merge.has_conflicts());
+    assert_eq!(
+        outcome.tree_merge.tree,
+        expected_tree(&repo, "clean.expected")?,
+        "the modification in 'ours' is applied to the renamed file in 'theirs'"
+    );
+    assert!(
+        repo.find_object(outcome.tree_merge.tree).is_ok(),
+        "the merged tree was written to the object database"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_commits_with_conflict() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = repo.merge_commits(
+        repo.rev_parse_single("ours")?,
+        repo.rev_parse_single("conflicting")?,
+        gix::merge::blob::Labels {
+            ancestor: None,
+            current: Some("ours".into()),
+            other: Some("conflicting".into()),
+        },
+        repo.tree_merge_options()?,
+    )?;
+    assert_eq!(
+        outcome.tree_merge.tree,
+        expected_tree(&repo, "conflicting.expected")?,
+        "conflict markers are written like git does"
+    );
+    let conflicts: Vec<_> = outcome
+        .tree_merge
+        .conflicts
+        .iter()
+        .map(|c| (c.path.clone(), c.kind.clone()))
+        .collect();
+    assert_eq!(conflicts, [("file".into(), ConflictKind::Content)]);
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use gix::{
+    bstr::ByteSlice,
+    note::merge::{Outcome, Strategy},
+};
+
+use crate::util::{named_repo, repo_rw};
+
+fn note_of(repo: &gix::Repository, notes_ref: &str, tag: &str) -> crate::Result<Option<String>> {
+    let target = repo.rev_parse_single(tag)?;
+    Ok(repo
+        .notes_with_ref(notes_ref.into())?
+        .find(target)?
+        .map(|blob| blob.data.to_str_lossy().into_owned()))
+}
+
+fn commit_note(repo: &gix::Repository, tag: &str) -> crate::Result<Option<String>> {
+    let commit = repo.rev_parse_single(tag)?.object()?.into_commit();
+    Ok(commit.note()?.map(|blob| blob.data.to_str_lossy().into_owned()))
+}
+
+mod commit {
+    use super::commit_note;
+    use crate::util::named_repo;
+
+    #[test]
+    fn note_is_looked_up_in_notes_ref_and_display_refs() -> crate::Result {
+        let repo = named_repo("make_notes_repo.sh")?;
+        assert_eq!(commit_note(&repo, "first")?.as_deref(), Some("default\n"));
+        assert_eq!(
+            commit_note(&repo, "second")?.as_deref(),
+            Some("ci passed\n"),
+            "`notes.displayRef` can be a full reference name…"
+        );
+        assert_eq!(
+            commit_note(&repo, "third")?.as_deref(),
+            Some("looks good\n"),
+            "…or a glob pattern"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn core_notes_ref_changes_the_default_notes() -> crate::Result {
+        let repo: gix::Repository = gix::open_opts(
+            named_repo("make_notes_repo.sh")?.git_dir(),
+            crate::util::restricted().config_overrides(["core.notesRef=refs/notes/base"]),
+        )?;
+        assert_eq!(repo.notes()?.reference_name().as_bstr(), "refs/notes/base");
+        assert_eq!(commit_note(&repo, "first")?.as_deref(), Some("base\n"));
+        Ok(())
+    }
+}
+
+#[test]
+fn notes_ref_names_are_expanded() -> crate::Result {
+    let repo = named_repo("make_notes_repo.sh")?;
+    assert_eq!(repo.notes()?.reference_name().as_bstr(), "refs/notes/commits");
+    for name in ["ci", "notes/ci", "refs/notes/ci"] {
+        assert_eq!(
+            repo.notes_with_ref(name.into())?.reference_name().as_bstr(),
+            "refs/notes/ci"
+        );
+    }
+    assert_eq!(note_of(&repo, "ci", "second")?.as_deref(), Some("ci passed\n"));
+    assert_eq!(note_of(&repo, "ci", "first")?, None);
+    assert_eq!(note_of(&repo, "does-not-exist", "first")?, None);
+
+    let notes = repo.notes_with_ref("ours".into())?.notes()?;
+    assert_eq!(notes.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn add_append_copy_and_remove() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_notes_repo.sh")?;
+    let notes = repo.notes()?;
+    let (first, second, third) = (
+        repo.rev_parse_single("first")?,
+        repo.rev_parse_single("second")?,
+        repo.rev_parse_single("third")?,
+    );
+    let previous = notes.commit_id()?.expect("notes exist");
+
+    let commit = notes.add(second, "added\n", false)?;
+    let commit_obj = commit.object()?.into_commit();
+    assert_eq!(commit_obj.message_raw()?, "Notes added by 'git notes add'\n");
+    assert_eq!(commit_obj.parent_ids().collect::<Vec<_>>(), [previous]);
+    assert_eq!(notes.commit_id()?, Some(commit), "the notes reference is updated");
+    assert_eq!(note_of(&repo, "commits", "second")?.as_deref(), Some("added\n"));
+
+    assert!(
+        matches!(
+            notes.add(second, "again\n", false),
+            Err(gix::note::edit::Error::NoteExists { .. })
+        ),
+        "existing notes aren't overwritten by default"
+    );
+    notes.add(second, "overwritten\n", true)?;
+    assert_eq!(note_of(&repo, "commits", "second")?.as_deref(), Some("overwritten\n"));
+
+    notes.append(second, "appended\n")?;
+    assert_eq!(
+        note_of(&repo, "commits", "second")?.as_deref(),
+        Some("overwritten\n\nappended\n")
+    );
+    notes.append(third, "appended\n")?;
+    assert_eq!(note_of(&repo, "commits", "third")?.as_deref(), Some("appended\n"));
+
+    assert!(matches!(
+        notes.copy(first, third, false),
+        Err(gix::note::edit::Error::NoteExists { .. })
+    ));
+    notes.copy(first, third, true)?;
+    assert_eq!(note_of(&repo, "commits", "third")?.as_deref(), Some("default\n"));
+
+    let commit = notes.remove(first)?;
+    assert_eq!(
+        commit.object()?.into_commit().message_raw()?,
+        "Notes removed by 'git notes remove'\n"
+    );
+    assert_eq!(note_of(&repo, "commits", "first")?, None);
+    assert!(matches!(
+        notes.remove(first),
+        Err(gix::note::edit::Error::NoteMissing { .. })
+    ));
+    assert!(matches!(
+        notes.copy(first, second, true),
+        Err(gix::note::edit::Error::NoteMissing { .. })
+    ));
+
+    let new_notes = repo.notes_with_ref("new".into())?;
+    let commit = new_notes.add(first, "new\n", false)?;
+    assert_eq!(
+        commit.object()?.into_commit().parent_ids().count(),
+        0,
+        "notes references are created as needed"
+    );
+    assert_eq!(note_of(&repo, "new", "first")?.as_deref(), Some("new\n"));
+    Ok(())
+}
+
+mod merge {
+    use super::*;
+
+    fn name(name: &str) -> gix::refs::FullName {
+        name.try_into().expect("valid")
+    }
+
+    #[test]
+    fn fast_forward_and_up_to_date() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_notes_repo.sh")?;
+        let ahead = repo.find_reference("refs/notes/ahead")?.id().detach();
+
+        let notes = repo.notes_with_ref("ahead".into())?;
+        assert_eq!(notes.merge(name("refs/notes/base").as_ref(), None)?, Outcome::UpToDate);
+
+        let notes = repo.notes_with_ref("base".into())?;
+        assert_eq!(
+            notes.merge(name("refs/notes/ahead").as_ref(), None)?,
+            Outcome::FastForward { commit: ahead }
+        );
+        assert_eq!(notes.commit_id()?.map(gix::Id::detach), Some(ahead));
+
+        let notes = repo.notes_with_ref("new".into())?;
+        assert_eq!(
+            notes.merge(name("refs/notes/ahead").as_ref(), None)?,
+            Outcome::FastForward { commit: ahead },
+            "missing notes references are created"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn configured_strategy_produces_the_same_notes_as_git() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_notes_repo.sh")?;
+        let notes = repo.notes_with_ref("ours".into())?;
+        let (ours, theirs) = (
+            notes.commit_id()?.expect("present").detach(),
+            repo.find_reference("refs/notes/theirs")?.id().detach(),
+        );
+
+        let Outcome::Merged { commit } = notes.merge(name("refs/notes/theirs").as_ref(), None)? else {
+            panic!("expected merge commit")
+        };
+        let commit = repo.find_object(commit)?.into_commit();
+        assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [ours, theirs]);
+        assert_eq!(
+            commit.message_raw()?,
+            "Merged notes from refs/notes/theirs into refs/notes/ours\n"
+        );
+        let expected_tree = repo
+            .find_reference("refs/notes/merged-by-git")?
+            .peel_to_id_in_place()?
+            .object()?
+            .into_commit()
+            .tree_id()?;
+        assert_eq!(
+            commit.tree_id()?,
+            expected_tree,
+            "`notes.ours.mergeStrategy` is `cat_sort_uniq`"
+        );
+        assert_eq!(
+            note_of(&repo, "ours", "first")?.as_deref(),
+            Some("base\nours\ntheirs\n")
+        );
+        assert_eq!(note_of(&repo, "ours", "third")?.as_deref(), Some("theirs only\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_strategies() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_notes_repo.sh")?;
+        let notes = repo.notes_with_ref("ours".into())?;
+        let previous = notes.commit_id()?;
+
+        let Outcome::Conflicted { outcome } =
+            notes.merge(name("refs/notes/theirs").as_ref(), Some(Strategy::Manual))?
+        else {
+            panic!("expected conflict")
+        };
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].target, repo.rev_parse_single("first")?);
+        assert_eq!(notes.commit_id()?, previous, "nothing is committed on conflict");
+
+        assert!(matches!(
+            notes.merge(name("refs/notes/theirs").as_ref(), Some(Strategy::Union))?,
+            Outcome::Merged { .. }
+        ));
+        assert_eq!(
+            note_of(&repo, "ours", "first")?.as_deref(),
+            Some("base\n\nours\n\nbase\n\ntheirs\n")
+        );
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use gix::{bstr::ByteSlice, object::tree::EntryKind};
+
 use crate::util::{named_repo, named_subrepo_opts};
 
 #[cfg(all(feature = "blob-diff", feature = "revision"))]
@@ -25,3 +27,115 @@ fn lookup_entry_by_path() -> crate::Result {
     );
     Ok(())
 }
+
+#[test]
+fn edit() -> crate::Result {
+    let (repo, tmp) = crate::util::basic_rw_repo()?;
+    let head = repo.head_commit()?;
+    let tree = head.tree()?;
+    let this = tree.find_entry("this").expect("present").object_id();
+    let blob = repo.write_blob("new")?.detach();
+
+    let mut editor = tree.edit()?;
+    let id = editor
+        .upsert("a/b/c", EntryKind::Blob, blob)?
+        .upsert("a/b.txt", EntryKind::BlobExecutable, this)?
+        .upsert("this", EntryKind::Link, blob)?
+        .upsert("this.d/e", EntryKind::Blob, this)?
+        .write()?;
+    assert_eq!(
+        id,
+        git_write_tree(
+            tmp.path(),
+            &[
+                &["read-tree", "HEAD"],
+                &["update-index", "--add", "--cacheinfo", &format!("100644,{blob},a/b/c")],
+                &[
+                    "update-index",
+                    "--add",
+                    "--cacheinfo",
+                    &format!("100755,{this},a/b.txt")
+                ],
+                &["update-index", "--cacheinfo", &format!("120000,{blob},this")],
+                &[
+                    "update-index",
+                    "--add",
+                    "--cacheinfo",
+                    &format!("100644,{this},this.d/e")
+                ],
+            ]
+        )?,
+        "the tree is the same as the one git creates from an index"
+    );
+
+    let id = editor
+        .remove("a/b/c")?
+        .remove("this.d")?
+        .remove("missing/file")?
+        .write()?;
+    assert_eq!(
+        id,
+        git_write_tree(
+            tmp.path(),
+            &[
+                &["read-tree", "HEAD"],
+                &[
+                    "update-index",
+                    "--add",
+                    "--cacheinfo",
+                    &format!("100755,{this},a/b.txt")
+                ],
+                &["update-index", "--cacheinfo", &format!("120000,{blob},this")],
+            ]
+        )?,
+        "empty trees are removed, and the editor can be reused after writing"
+    );
+    let tree = id.object()?.into_tree();
+    assert_eq!(
+        tree.lookup_entry_by_path("a/b.txt", &mut Vec::new())?
+            .expect("written")
+            .object_id(),
+        this
+    );
+
+    let signature = gix::actor::Signature {
+        name: "editor".into(),
+        email: "editor@example.com".into(),
+        time: gix::date::Time::new(1, 0),
+    };
+    let commit = repo.commit_as(&signature, &signature, "HEAD", "edited", id, Some(head.id))?;
+    assert_eq!(
+        repo.head_commit()?.tree_id()?,
+        id,
+        "commits can be created without index or worktree"
+    );
+    assert_eq!(commit.object()?.into_commit().parent_ids().next(), Some(head.id()));
+    assert!(
+        matches!(
+            editor.upsert("a//b", EntryKind::Blob, blob),
+            Err(gix::object::tree::editor::Error::InvalidPathComponent { .. })
+        ),
+        "paths are validated"
+    );
+    Ok(())
+}
+
+/// Run all `commands` with a temporary index and return the tree written from it.
+fn git_write_tree(dir: &std::path::Path, commands: &[&[&str]]) -> crate::Result<gix::ObjectId> {
+    let index = dir.join("edit-index");
+    let git = |args: &[&str]| -> crate::Result<Vec<u8>> {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .env("GIT_INDEX_FILE", &index)
+            .current_dir(dir)
+            .output()?;
+        assert!(out.status.success(), "{args:?}: {}", out.stderr.as_bstr());
+        Ok(out.stdout)
+    };
+    for args in commands {
+        git(args)?;
+    }
+    let id = gix::ObjectId::from_hex(git(&["write-tree"])?.trim())?;
+    std::fs::remove_file(index)?;
+    Ok(id)
+}
@@ -0,0 +1,264 @@
+use gix::{
+    bstr::{BString, ByteSlice},
+    rebase::{Outcome, Stop},
+};
+
+fn repo_rw(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_rebase_repo.sh")?;
+    let repo = gix::open_opts(tmp.path().join(name), crate::util::restricted())?;
+    Ok((repo, tmp))
+}
+
+fn summaries(repo: &gix::Repository, tip: &str, count: usize) -> crate::Result<Vec<String>> {
+    let mut out = Vec::new();
+    for info in repo.rev_walk([repo.rev_parse_single(tip)?]).all()?.take(count) {
+        let commit = info?.object()?;
+        out.push(commit.message()?.summary().to_string());
+    }
+    Ok(out)
+}
+
+fn blob(repo: &gix::Repository, spec: &str) -> crate::Result<BString> {
+    Ok(repo.rev_parse_single(spec)?.object()?.detach().data.into())
+}
+
+fn keep_message(message: &gix::bstr::BStr) -> BString {
+    message.to_owned()
+}
+
+#[test]
+fn rebase_onto_upstream_finishes_and_updates_the_branch() -> crate::Result {
+    let (repo, _tmp) = repo_rw("feature")?;
+    let part = repo.rev_parse_single("part")?.detach();
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    let outcome = repo.rebase(upstream, Default::default())?.start(&mut keep_message)?;
+    let Outcome::Finished { head } = outcome else {
+        panic!("the rebase should finish without conflicts: {outcome:?}")
+    };
+
+    assert_eq!(repo.head_name()?.expect("on a branch").as_bstr(), "refs/heads/feature");
+    assert_eq!(repo.head_id()?, head);
+    assert_eq!(
+        summaries(&repo, "feature", 5)?,
+        ["fixup! one", "two", "one", "upstream", "base"]
+    );
+    assert_eq!(
+        repo.rev_parse_single("part")?,
+        part,
+        "without update-refs, other branches stay untouched"
+    );
+    assert!(repo.state().is_none(), "all state is removed");
+    assert!(repo.rebase_status()?.is_none());
+    assert!(!repo.is_dirty()?);
+    assert_eq!(
+        std::fs::read(repo.work_dir().expect("non-bare").join("u"))?,
+        b"u\n",
+        "the worktree was updated"
+    );
+    Ok(())
+}
+
+#[test]
+fn autosquash_and_update_refs_from_configuration() -> crate::Result {
+    let (repo, _tmp) = repo_rw("feature")?;
+    let mut repo = repo;
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("rebase", None, "autoSquash", "true")?;
+        config.set_raw_value("rebase", None, "updateRefs", "true")?;
+    }
+    let options = repo.rebase_options()?;
+    assert!(options.autosquash && options.update_refs);
+
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    let rebase = repo.rebase(upstream, options)?;
+    assert_eq!(
+        rebase
+            .state()
+            .todo
+            .instructions
+            .iter()
+            .filter_map(gix::rebase::todo::Instruction::command_name)
+            .collect::<Vec<_>>(),
+        ["pick", "fixup", "update-ref", "pick"]
+    );
+    let outcome = rebase.start(&mut keep_message)?;
+    assert!(matches!(outcome, Outcome::Finished { .. }));
+
+    assert_eq!(summaries(&repo, "feature", 4)?, ["two", "one", "upstream", "base"]);
+    assert_eq!(
+        repo.rev_parse_single("part")?,
+        repo.rev_parse_single("feature~1")?,
+        "the branch is moved to the rewritten commit"
+    );
+    assert_eq!(blob(&repo, "part:f")?, "one fixed\n", "the fixup was squashed into it");
+    Ok(())
+}
+
+#[test]
+fn conflicts_stop_the_rebase_and_can_be_resolved() -> crate::Result {
+    let (repo, _tmp) = repo_rw("conflict")?;
+    let workdir = repo.work_dir().expect("non-bare").to_owned();
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    let outcome = repo.rebase(upstream, Default::default())?.start(&mut keep_message)?;
+    let Outcome::Stopped {
+        head,
+        reason: Stop::Conflict { .. },
+    } = outcome
+    else {
+        panic!("expected to stop due to a conflict: {outcome:?}")
+    };
+    assert_eq!(head, upstream, "nothing was picked yet");
+    assert_eq!(repo.state(), Some(gix::state::InProgress::Rebase));
+    assert!(repo.head()?.is_detached());
+
+    let status = repo.rebase_status()?.expect("in progress");
+    assert_eq!(
+        status.head_name.as_ref().map(|name| name.as_bstr()),
+        Some("refs/heads/conflict".into())
+    );
+    assert_eq!(status.onto, upstream);
+    assert_eq!(status.stopped_at, Some(repo.rev_parse_single("conflict~1")?.detach()));
+    assert_eq!(status.progress(), (1, 2));
+    assert_eq!(
+        repo.rev_parse_single("REBASE_HEAD")?,
+        repo.rev_parse_single("conflict~1")?
+    );
+    assert!(
+        std::fs::read(workdir.join("a"))?.find(b"<<<<<<<").is_some(),
+        "conflict markers are written"
+    );
+    assert!(repo.open_index()?.entries().iter().any(|entry| entry.stage_raw() != 0));
+
+    assert!(matches!(
+        repo.rebase_continue(&mut keep_message),
+        Err(gix::rebase::run::Error::UnresolvedConflict { .. })
+    ));
+
+    std::fs::write(workdir.join("a"), "resolved\n2\n3\n")?;
+    assert!(gix_testtools::run_git(&workdir, &["add", "a"])?.success());
+    let outcome = repo.rebase_continue(&mut keep_message)?;
+    assert!(matches!(outcome, Outcome::Finished { .. }), "{outcome:?}");
+
+    assert_eq!(
+        summaries(&repo, "conflict", 4)?,
+        ["after", "conflicting", "upstream", "base"]
+    );
+    assert_eq!(blob(&repo, "conflict:a")?, "resolved\n2\n3\n");
+    assert!(repo.rebase_status()?.is_none());
+    assert!(repo.try_find_reference("REBASE_HEAD")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn a_stopped_rebase_can_be_continued_by_git() -> crate::Result {
+    let (repo, _tmp) = repo_rw("conflict")?;
+    let workdir = repo.work_dir().expect("non-bare").to_owned();
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    repo.rebase(upstream, Default::default())?.start(&mut keep_message)?;
+
+    std::fs::write(workdir.join("a"), "resolved\n2\n3\n")?;
+    assert!(gix_testtools::run_git(&workdir, &["add", "a"])?.success());
+    assert!(gix_testtools::run_git(
+        &workdir,
+        &[
+            "-c",
+            "user.name=gitoxide",
+            "-c",
+            "user.email=gitoxide@localhost",
+            "-c",
+            "core.editor=true",
+            "rebase",
+            "--continue",
+        ]
+    )?
+    .success());
+
+    let repo = gix::open_opts(&workdir, crate::util::restricted())?;
+    assert!(repo.state().is_none());
+    assert_eq!(
+        summaries(&repo, "conflict", 4)?,
+        ["after", "conflicting", "upstream", "base"]
+    );
+    Ok(())
+}
+
+#[test]
+fn abort_restores_the_branch() -> crate::Result {
+    let (repo, _tmp) = repo_rw("conflict")?;
+    let workdir = repo.work_dir().expect("non-bare").to_owned();
+    let before = repo.head_id()?.detach();
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    repo.rebase(upstream, Default::default())?.start(&mut keep_message)?;
+
+    repo.rebase_abort()?;
+    assert_eq!(repo.head_name()?.expect("on a branch").as_bstr(), "refs/heads/conflict");
+    assert_eq!(repo.head_id()?, before);
+    assert!(repo.state().is_none());
+    assert!(!repo.is_dirty()?);
+    assert_eq!(std::fs::read(workdir.join("a"))?, b"conflict\n2\n3\n");
+    assert!(!workdir.join("u").exists(), "files of upstream are removed");
+
+    assert!(matches!(
+        repo.rebase_abort(),
+        Err(gix::rebase::abort::Error::NotInProgress)
+    ));
+    Ok(())
+}
+
+#[test]
+fn edit_stops_and_amends_on_continue() -> crate::Result {
+    let (repo, _tmp) = repo_rw("feature")?;
+    let workdir = repo.work_dir().expect("non-bare").to_owned();
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    let mut rebase = repo.rebase(upstream, Default::default())?;
+    let first = rebase.todo_mut().instructions.remove(0);
+    rebase.todo_mut().instructions.insert(
+        0,
+        gix::rebase::todo::Instruction::Edit {
+            commit: *first.commit().expect("pick"),
+            summary: "one".into(),
+        },
+    );
+    let outcome = rebase.start(&mut keep_message)?;
+    assert!(
+        matches!(
+            outcome,
+            Outcome::Stopped {
+                reason: Stop::Edit { .. },
+                ..
+            }
+        ),
+        "{outcome:?}"
+    );
+    let status = repo.rebase_status()?.expect("in progress");
+    assert!(status.interactive);
+    assert!(status.amend.is_some());
+
+    std::fs::write(workdir.join("h"), "added while editing\n")?;
+    assert!(gix_testtools::run_git(&workdir, &["add", "h"])?.success());
+    let outcome = repo.rebase_continue(&mut keep_message)?;
+    assert!(matches!(outcome, Outcome::Finished { .. }), "{outcome:?}");
+    assert_eq!(
+        summaries(&repo, "feature", 5)?,
+        ["fixup! one", "two", "one", "upstream", "base"]
+    );
+    assert_eq!(
+        blob(&repo, "feature~2:h")?,
+        "added while editing\n",
+        "the commit was amended"
+    );
+    Ok(())
+}
+
+#[test]
+fn uncommitted_changes_prevent_the_rebase() -> crate::Result {
+    let (repo, _tmp) = repo_rw("feature")?;
+    std::fs::write(repo.work_dir().expect("non-bare").join("f"), "changed\n")?;
+    let upstream = repo.rev_parse_single("upstream")?.detach();
+    assert!(matches!(
+        repo.rebase(upstream, Default::default()),
+        Err(gix::rebase::init::Error::UncommittedChanges)
+    ));
+    Ok(())
+}
@@ -157,6 +157,67 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_writes_fetch_head_like_git() -> crate::Result {
+        use gix::bstr::ByteSlice;
+        let (repo, tmp) = repo_rw("two-origins");
+        let fetch = |remote: gix::Remote<'_>, mode: fetch::WriteFetchHead| -> crate::Result {
+            remote
+                .connect(Fetch)?
+                .prepare_fetch(gix::progress::Discard, Default::default())?
+                .with_write_fetch_head(mode)
+                .receive(gix::progress::Discard, &AtomicBool::default())?;
+            Ok(())
+        };
+        let fetch_head_path = repo.git_dir().join("FETCH_HEAD");
+
+        assert!(gix_testtools::run_git(&tmp.path().join("two-origins"), &["fetch", "-q", "origin"])?.success());
+        let expected = std::fs::read(&fetch_head_path)?;
+        std::fs::remove_file(&fetch_head_path)?;
+        assert!(repo.fetch_head()?.is_none());
+
+        fetch(repo.find_remote("origin")?, fetch::WriteFetchHead::Overwrite)?;
+        assert_eq!(
+            std::fs::read(&fetch_head_path)?.as_bstr(),
+            expected.as_bstr(),
+            "the upstream branch of `main` is to be merged, everything else is listed after it"
+        );
+        let fetch_head = repo.fetch_head()?.expect("written");
+        let head = fetch_head.head().expect("at least one entry");
+        assert_eq!(head.remote_ref.as_ref().expect("a branch"), "refs/heads/main");
+        assert!(head.for_merge);
+        assert_eq!(fetch_head.to_merge().count(), 1);
+        assert_eq!(
+            repo.rev_parse_single("FETCH_HEAD")?,
+            head.id,
+            "the first entry is what it resolves to"
+        );
+
+        let mut remote = repo.find_remote("changes-on-top-of-origin")?;
+        remote.replace_refspecs(Some("refs/heads/main"), Fetch)?;
+        fetch(remote, fetch::WriteFetchHead::Append)?;
+        let appended = repo.fetch_head()?.expect("written");
+        assert_eq!(appended.entries[..fetch_head.entries.len()], fetch_head.entries[..]);
+        let new: Vec<_> = appended.entries[fetch_head.entries.len()..]
+            .iter()
+            .map(|entry| (entry.for_merge, entry.remote_ref.as_ref().expect("named").to_string()))
+            .collect();
+        assert_eq!(
+            new,
+            [(true, "refs/heads/main".into()), (false, "refs/tags/v1.0".into())],
+            "a single explicit ref is merged, automatically included tags are listed as well"
+        );
+
+        let mut repo = repo;
+        repo.config_snapshot_mut()
+            .set_raw_value("fetch", None, "writeFetchHead", "false")?;
+        std::fs::remove_file(&fetch_head_path)?;
+        fetch(repo.find_remote("origin")?, fetch::WriteFetchHead::Overwrite)?;
+        assert!(repo.fetch_head()?.is_none(), "`fetch.writeFetchHead` is respected");
+        Ok(())
+    }
+
     #[maybe_async::test(
         feature = "blocking-network-client",
         async(feature = "async-network-client-async-std", async_std::test)
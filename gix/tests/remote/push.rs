@@ -0,0 +1,346 @@
+#[cfg(feature = "blocking-network-client")]
+mod blocking_io {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::remote::{
+        push::{lease, update, Lease},
+        Direction::Push,
+    };
+
+    fn repo_rw() -> crate::Result<(gix::Repository, gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable_with_args(
+            "make_push_repos.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let local = gix::open_opts(dir.path().join("local"), crate::restricted())?;
+        let remote = gix::open_opts(dir.path().join("remote.git"), crate::restricted())?;
+        Ok((local, remote, dir))
+    }
+
+    type Prepare<'remote, 'repo> =
+        gix::remote::push::Prepare<'remote, 'repo, Box<dyn gix::protocol::transport::client::Transport + Send>>;
+
+    fn push<'repo>(
+        repo: &'repo gix::Repository,
+        specs: &[&str],
+        configure: impl for<'remote> FnOnce(Prepare<'remote, 'repo>) -> Prepare<'remote, 'repo>,
+    ) -> crate::Result<gix::remote::push::Outcome> {
+        let mut remote = repo.find_remote("origin")?;
+        if !specs.is_empty() {
+            remote.replace_refspecs(specs.iter().copied(), Push)?;
+        }
+        let prepare = remote.connect(Push)?.prepare_push(gix::progress::Discard)?;
+        Ok(configure(prepare).send(gix::progress::Discard, &AtomicBool::default())?)
+    }
+
+    fn id_of(repo: &gix::Repository, name: &str) -> Option<gix::ObjectId> {
+        repo.try_find_reference(name)
+            .expect("no error")
+            .map(|r| r.id().detach())
+    }
+
+    #[test]
+    fn fast_forward_with_push_default_updates_remote_and_tracking_branch() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let local_main = id_of(&repo, "refs/heads/main").expect("present");
+        assert_ne!(id_of(&remote_repo, "refs/heads/main"), Some(local_main));
+
+        let outcome = push(&repo, &[], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(outcome.updates.len(), 1, "simple pushes only the current branch");
+        let update = &outcome.updates[0];
+        assert_eq!(update.mapping.remote.as_bstr(), "refs/heads/main");
+        assert_eq!(
+            update.mapping.tracking.as_ref().map(|n| n.as_bstr().to_owned()),
+            Some("refs/remotes/origin/main".into())
+        );
+        assert_eq!(
+            update.status,
+            update::Status::Ok {
+                forced: false,
+                options: Vec::new()
+            }
+        );
+        assert_eq!(
+            outcome.num_objects_sent,
+            Some(3),
+            "the new commit, its tree and the new blob"
+        );
+        assert_eq!(id_of(&remote_repo, "refs/heads/main"), Some(local_main));
+        assert_eq!(id_of(&repo, "refs/remotes/origin/main"), Some(local_main));
+
+        let outcome = push(&repo, &[], |p| p)?;
+        assert_eq!(outcome.updates[0].status, update::Status::UpToDate);
+        assert!(outcome.report.is_none(), "nothing was sent");
+        Ok(())
+    }
+
+    #[test]
+    fn thin_packs_refer_to_objects_the_remote_has() -> crate::Result {
+        let (repo, _remote_repo, tmp) = repo_rw()?;
+        let (local, remote) = (tmp.path().join("local"), tmp.path().join("remote.git"));
+        let git = |dir: &std::path::Path, args: &[&str]| -> crate::Result<String> {
+            let out = std::process::Command::new("git")
+                .args(["-c", "user.name=a", "-c", "user.email=a@example.com"])
+                .args(args)
+                .current_dir(dir)
+                .output()?;
+            assert!(out.status.success(), "git {args:?} failed");
+            Ok(String::from_utf8(out.stdout)?)
+        };
+        let in_pack = |dir: &std::path::Path| -> crate::Result<usize> {
+            let stats = git(dir, &["count-objects", "-v"])?;
+            let count = stats
+                .lines()
+                .find_map(|line| line.strip_prefix("in-pack: "))
+                .expect("always present");
+            Ok(count.parse()?)
+        };
+
+        let lines: Vec<_> = (0..3000).map(|n| n.to_string()).collect();
+        std::fs::write(local.join("large"), lines.join("\n"))?;
+        git(&local, &["add", "large"])?;
+        git(&local, &["commit", "-q", "-m", "large"])?;
+        std::fs::write(local.join("large"), lines[..2900].join("\n"))?;
+        git(&local, &["commit", "-q", "-a", "-m", "smaller"])?;
+        git(&local, &["repack", "-adq"])?;
+        git(&remote, &["config", "receive.unpackLimit", "1"])?;
+        git(&local, &["push", "-q", "origin", "HEAD~1:refs/heads/main"])?;
+
+        let in_pack_before = in_pack(&remote)?;
+        let outcome = push(&repo, &["main"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome.num_objects_sent,
+            Some(3),
+            "the new commit, its tree and the smaller blob"
+        );
+        assert_eq!(
+            in_pack(&remote)? - in_pack_before,
+            4,
+            "the smaller blob was sent as delta against the larger one, which the remote added to fix the thin pack"
+        );
+        git(&remote, &["fsck", "--strict"])?;
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_does_not_change_anything() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let before = id_of(&remote_repo, "refs/heads/main");
+        let outcome = push(&repo, &["main:new-branch"], |p| p.with_dry_run(true))?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome.updates[0].mapping.remote.as_bstr(),
+            "refs/heads/new-branch",
+            "the destination category is derived from the source"
+        );
+        assert_eq!(outcome.updates[0].status, update::Status::DryRun);
+        assert_eq!(id_of(&remote_repo, "refs/heads/main"), before);
+        assert_eq!(id_of(&remote_repo, "refs/heads/new-branch"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn non_fast_forward_is_rejected_unless_forced() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let outcome = push(&repo, &["refs/heads/diverged"], |p| p)?;
+        assert!(!outcome.is_success());
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Rejected(update::Rejection::FetchFirst),
+            "the remote object isn't known locally"
+        );
+
+        repo.find_remote("origin")?
+            .connect(gix::remote::Direction::Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        let outcome = push(&repo, &["refs/heads/diverged"], |p| p)?;
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Rejected(update::Rejection::NonFastForward)
+        );
+
+        let outcome = push(&repo, &["+refs/heads/diverged"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Ok {
+                forced: true,
+                options: Vec::new()
+            }
+        );
+        assert_eq!(
+            id_of(&remote_repo, "refs/heads/diverged"),
+            id_of(&repo, "refs/heads/diverged")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fast_forwards_with_commits_older_than_the_remote_tip_are_accepted() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let remote_before = id_of(&remote_repo, "refs/heads/skewed").expect("present");
+        let commit_time = |id| -> crate::Result<_> { Ok(repo.find_object(id)?.into_commit().time()?.seconds) };
+        let local_skewed = repo
+            .find_object(id_of(&repo, "refs/heads/skewed").expect("present"))?
+            .into_commit();
+        let skewed_time = commit_time(local_skewed.parent_ids().next().expect("one parent").detach())?;
+        let remote_time = commit_time(remote_before)?;
+        assert!(
+            skewed_time < remote_time,
+            "the new commit on top of the remote tip was committed before it"
+        );
+
+        let outcome = push(&repo, &["refs/heads/skewed"], |p| p)?;
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Ok {
+                forced: false,
+                options: Vec::new()
+            },
+            "reachability is checked without relying on commit times"
+        );
+        assert_eq!(id_of(&remote_repo, "refs/heads/skewed"), Some(local_skewed.id));
+        Ok(())
+    }
+
+    #[test]
+    fn force_with_lease() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let remote_before = id_of(&remote_repo, "refs/heads/diverged");
+        let outcome = push(&repo, &["refs/heads/diverged"], |p| {
+            p.with_force_with_lease(Some(Lease {
+                remote: "refs/heads/diverged".try_into().expect("valid"),
+                expected: lease::Expected::TrackingRef,
+            }))
+        })?;
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Rejected(update::Rejection::Stale {
+                expected: id_of(&repo, "refs/remotes/origin/diverged")
+            }),
+            "the tracking branch doesn't know about the latest changes on the remote"
+        );
+        assert_eq!(id_of(&remote_repo, "refs/heads/diverged"), remote_before);
+
+        let outcome = push(&repo, &["refs/heads/diverged"], |p| {
+            p.with_force_with_lease(Some(Lease {
+                remote: "refs/heads/diverged".try_into().expect("valid"),
+                expected: lease::Expected::Id(remote_before.expect("present")),
+            }))
+        })?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            id_of(&remote_repo, "refs/heads/diverged"),
+            id_of(&repo, "refs/heads/diverged")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn delete_and_create() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        assert!(id_of(&repo, "refs/remotes/origin/to-delete").is_some());
+        let outcome = push(
+            &repo,
+            &[":to-delete", "HEAD:refs/heads/created", ":refs/heads/does-not-exist"],
+            |p| p,
+        )?;
+        assert_eq!(outcome.updates.len(), 3);
+        assert!(matches!(outcome.updates[0].status, update::Status::Ok { .. }));
+        assert!(matches!(outcome.updates[1].status, update::Status::Ok { .. }));
+        assert_eq!(
+            outcome.updates[2].status,
+            update::Status::Rejected(update::Rejection::RemoteRefMissing)
+        );
+        assert_eq!(id_of(&remote_repo, "refs/heads/to-delete"), None);
+        assert_eq!(
+            id_of(&repo, "refs/remotes/origin/to-delete"),
+            None,
+            "tracking branches are removed as well"
+        );
+        assert_eq!(
+            id_of(&remote_repo, "refs/heads/created"),
+            id_of(&repo, "refs/heads/main")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn head_is_pushed_to_the_branch_it_points_to() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let outcome = push(&repo, &["HEAD"], |p| p)?;
+        assert_eq!(outcome.updates.len(), 1);
+        let update = &outcome.updates[0];
+        assert_eq!(
+            update.mapping.remote.as_bstr(),
+            "refs/heads/main",
+            "the destination is the branch HEAD refers to, not 'refs/heads/HEAD'"
+        );
+        assert_eq!(
+            update.mapping.tracking.as_ref().map(|n| n.as_bstr().to_owned()),
+            Some("refs/remotes/origin/main".into())
+        );
+        assert!(matches!(update.status, update::Status::Ok { .. }));
+        assert_eq!(id_of(&remote_repo, "refs/heads/main"), id_of(&repo, "refs/heads/main"));
+        assert_eq!(id_of(&remote_repo, "refs/heads/HEAD"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_pushes_reject_all_updates_if_one_fails() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let before = id_of(&remote_repo, "refs/heads/main");
+        let outcome = push(&repo, &["refs/heads/main", "refs/heads/diverged"], |p| {
+            p.with_atomic(true)
+        })?;
+        assert!(!outcome.is_success());
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Rejected(update::Rejection::AtomicPushFailed)
+        );
+        assert_eq!(
+            outcome.updates[1].status,
+            update::Status::Rejected(update::Rejection::FetchFirst)
+        );
+        assert_eq!(id_of(&remote_repo, "refs/heads/main"), before);
+        Ok(())
+    }
+
+    #[test]
+    fn tags_are_not_overwritten_unless_forced() -> crate::Result {
+        let (repo, remote_repo, _tmp) = repo_rw()?;
+        let outcome = push(&repo, &["main:refs/tags/v1.0"], |p| p)?;
+        assert_eq!(
+            outcome.updates[0].status,
+            update::Status::Rejected(update::Rejection::AlreadyExists)
+        );
+
+        let outcome = push(&repo, &["refs/tags/*:refs/tags/*"], |p| p)?;
+        assert_eq!(outcome.updates.len(), 1);
+        assert_eq!(outcome.updates[0].status, update::Status::UpToDate);
+        assert_eq!(id_of(&remote_repo, "refs/tags/v1.0"), id_of(&repo, "refs/tags/v1.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn push_default_nothing_is_an_error() -> crate::Result {
+        let (mut repo, _remote_repo, _tmp) = repo_rw()?;
+        repo.config_snapshot_mut()
+            .set_raw_value("push", None, "default", "nothing")?;
+        let err = repo
+            .find_remote("origin")?
+            .connect(Push)?
+            .prepare_push(gix::progress::Discard)
+            .map(|_| ())
+            .expect_err("nothing to push");
+        assert!(matches!(
+            err,
+            gix::remote::push::prepare::Error::Mappings(gix::remote::push::mappings::Error::PushDefaultNothing)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::util::{named_subrepo_opts, repo_rw};
+
+#[test]
+fn write_matches_git() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_changed_paths_repo.sh")?;
+    let path = repo.objects.store_ref().path().join("info").join("commit-graph");
+    let expected = std::fs::read(&path)?;
+    std::fs::remove_file(&path)?;
+
+    let outcome = repo.write_commit_graph(
+        Some(repo.head_id()?),
+        gix::commitgraph::write::Options {
+            changed_paths: Some(Default::default()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.path.as_deref(), Some(path.as_path()));
+    assert_eq!(outcome.num_commits, 6);
+    assert_eq!(
+        std::fs::read(&path)?,
+        expected,
+        "changed paths are computed like git does it"
+    );
+
+    let outcome = repo.write_commit_graph(
+        Some(repo.head_id()?),
+        gix::commitgraph::write::Options {
+            split: Some(gix::commitgraph::write::Split::Merge),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome.path, None,
+        "all commits are known so nothing needs to be written"
+    );
+    Ok(())
+}
+
+#[test]
+fn write_fails_in_shallow_repositories() -> crate::Result {
+    let repo = named_subrepo_opts("make_shallow_repo.sh", "shallow", crate::restricted())?;
+    assert!(matches!(
+        repo.write_commit_graph(Some(repo.head_id()?), Default::default()),
+        Err(gix::repository::write_commit_graph::Error::Shallow)
+    ));
+    Ok(())
+}
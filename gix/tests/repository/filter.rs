@@ -60,3 +60,37 @@ fn pipeline_with_autocrlf() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "lfs")]
+fn pipeline_with_lfs_without_git_lfs() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_lfs_repo.sh")?;
+    let (mut pipe, index) = repo.filter_pipeline(None)?;
+
+    let pointer = {
+        let mut out = pipe.convert_to_git("new large content".as_bytes(), Path::new("new.bin"), &index)?;
+        assert!(
+            out.is_changed(),
+            "the in-process LFS filter replaces the content with a pointer"
+        );
+        let mut buf = Vec::new();
+        std::io::Read::read_to_end(&mut out, &mut buf)?;
+        gix::lfs::Pointer::from_bytes(&buf)?
+    };
+    assert_eq!(pointer.size, 17);
+    assert!(
+        gix::lfs::Store::at(repo.git_dir().join("lfs")).contains(&pointer),
+        "the content was stored locally"
+    );
+
+    let pointer = pointer.to_bstring();
+    let mut out = pipe.convert_to_worktree(pointer.as_ref(), "new.bin".into(), Delay::Forbid)?;
+    let mut buf = Vec::new();
+    std::io::Read::read_to_end(&mut out, &mut buf)?;
+    assert_eq!(
+        buf.as_bstr(),
+        "new large content",
+        "pointers are smudged from the local store"
+    );
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use gix::Repository;
 
+mod commit_graph;
 mod config;
 #[cfg(feature = "excludes")]
 mod excludes;
@@ -7,6 +8,8 @@ mod excludes;
 mod filter;
 mod object;
 mod open;
+#[cfg(feature = "pack-objects")]
+mod pack_objects;
 #[cfg(feature = "attributes")]
 mod pathspec;
 mod reference;
@@ -65,6 +65,102 @@ fn bare_repo_with_index() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn bare_repo_with_reftable() -> crate::Result {
+    use gix::refs::reftable::{Ref, RefValue, Stack};
+
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let git_dir = gix::init_bare(tmp.path())?.git_dir().to_owned();
+    let config = std::fs::read_to_string(git_dir.join("config"))?
+        .replace("repositoryformatversion = 0", "repositoryformatversion = 1")
+        + "[extensions]\n\trefStorage = reftable\n";
+    std::fs::write(git_dir.join("config"), config)?;
+    // Like git, keep an invalid `HEAD` so the directory is still recognized as repository.
+    std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/.invalid\n")?;
+
+    let empty_tree = gix::hash::ObjectId::empty_tree(gix::hash::Kind::Sha1);
+    Stack::at(git_dir.join("reftable"), gix::hash::Kind::Sha1)?
+        .lock(gix::lock::acquire::Fail::Immediately)?
+        .commit(
+            vec![
+                Ref {
+                    name: "HEAD".into(),
+                    update_index: 1,
+                    value: RefValue::Symref("refs/heads/main".into()),
+                },
+                Ref {
+                    name: "refs/heads/main".into(),
+                    update_index: 1,
+                    value: RefValue::Val1(empty_tree),
+                },
+            ],
+            Vec::new(),
+        )?;
+
+    let repo = gix::open_opts(&git_dir, gix::open::Options::isolated())?;
+    assert_eq!(repo.refs.ref_storage(), gix::refs::store::RefStorage::Reftable);
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/main",
+        "HEAD is read from the reftable"
+    );
+    assert_eq!(repo.find_reference("main")?.id(), empty_tree);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "sha256")]
+fn sha256_repo() -> crate::Result {
+    let repo = crate::util::named_repo("make_sha256_repo.sh")?;
+    assert_eq!(repo.object_hash(), gix::hash::Kind::Sha256);
+
+    let head = repo.head_commit()?;
+    assert_eq!(head.id.kind(), gix::hash::Kind::Sha256);
+    assert_eq!(
+        head.message_raw()?,
+        "third\n",
+        "the last commit is stored in loose objects"
+    );
+    let first = repo.rev_parse_single("v1^{commit}")?.object()?.into_commit();
+    assert_eq!(first.message_raw()?, "first\n", "older objects are in a pack");
+    assert_eq!(
+        repo.find_reference("v1")?.peel_to_id_in_place()?,
+        first.id,
+        "the annotated tag in the pack can be peeled"
+    );
+
+    let tree = first.tree()?;
+    let entry = tree.find_entry("dir").expect("present");
+    assert!(entry.mode().is_tree());
+    let blob = tree
+        .lookup_entry_by_path("dir/b", &mut Vec::new())?
+        .expect("present")
+        .object()?;
+    assert_eq!(blob.data, b"b\n");
+
+    let index = repo.index()?;
+    assert_eq!(index.object_hash(), gix::hash::Kind::Sha256);
+    assert_eq!(
+        index.entries().iter().map(|e| e.id).collect::<Vec<_>>(),
+        head.tree()?
+            .traverse()
+            .breadthfirst
+            .files()?
+            .into_iter()
+            .filter(|e| !e.mode.is_tree())
+            .map(|e| e.oid)
+            .collect::<Vec<_>>(),
+        "the index matches the tree of the last commit"
+    );
+
+    let graph = repo.commit_graph()?;
+    assert_eq!(graph.num_commits(), 3);
+    let commit = graph.commit_by_id(head.id).expect("present");
+    assert_eq!(commit.generation(), 3);
+    assert_eq!(commit.root_tree_id(), head.tree_id()?.detach());
+    Ok(())
+}
+
 #[test]
 fn none_bare_repo_without_index() -> crate::Result {
     let repo = named_subrepo_opts(
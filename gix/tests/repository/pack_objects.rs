@@ -0,0 +1,33 @@
+use gix::odb::pack::data::output::entry::iter_from_counts::Mode;
+
+#[test]
+fn delta_compression_uses_pack_window_and_depth() -> crate::Result {
+    let repo = crate::named_repo("make_pack_objects_repo.sh")?;
+    assert_eq!(
+        repo.pack_delta_compression()?,
+        Mode::DeltaCompression { window: 10, depth: 50 },
+        "the defaults are the ones of git"
+    );
+
+    let repo = gix::open_opts(
+        repo.path(),
+        crate::restricted().config_overrides(["pack.window=3", "pack.depth=7"]),
+    )?;
+    assert_eq!(
+        repo.pack_delta_compression()?,
+        Mode::DeltaCompression { window: 3, depth: 7 }
+    );
+    Ok(())
+}
+
+#[test]
+fn blobs_without_delta_have_the_delta_attribute_unset() -> crate::Result {
+    let repo = crate::named_repo("make_pack_objects_repo.sh")?;
+    let index = repo.index()?;
+    let expected = index.entry_by_path("data.bin".into()).expect("present").id;
+    assert_eq!(
+        repo.pack_blobs_without_delta()?.into_iter().collect::<Vec<_>>(),
+        [expected]
+    );
+    Ok(())
+}
@@ -55,6 +55,23 @@ fn rebase_interactive() -> Result {
     assert!(repo.head()?.is_detached());
     assert_eq!(repo.state(), Some(gix::state::InProgress::RebaseInteractive));
 
+    #[cfg(feature = "rebase")]
+    {
+        let status = repo.rebase_status()?.expect("rebase in progress");
+        assert!(status.interactive);
+        assert_eq!(
+            status.progress(),
+            (3, 4),
+            "`label onto` and `reset onto` are counted as well"
+        );
+        assert_eq!(status.stopped_at, Some(repo.head_id()?.detach()));
+        assert_eq!(status.amend, status.stopped_at, "it stopped for editing");
+        assert_eq!(status.onto, repo.rev_parse_single("HEAD~1")?);
+        assert_eq!(
+            status.current().and_then(gix::rebase::todo::Instruction::command_name),
+            Some("edit")
+        );
+    }
     Ok(())
 }
 
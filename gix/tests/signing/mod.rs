@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use gix::{
+    bstr::ByteSlice,
+    config::tree::{gpg, Commit, Tag, User},
+    refs::transaction::PreviousValue,
+    signing::{sign, Status},
+};
+
+/// Open the repository with SSH signatures configured, signing with the key of the only allowed signer.
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_signing_repo.sh")?;
+    let mut repo = gix::open_opts(tmp.path().join("repo"), crate::restricted())?;
+    let mut config = repo.config_snapshot_mut();
+    config.set_value(&User::SIGNING_KEY, path(&tmp.path().join("signer-key")).as_str())?;
+    config.set_value(
+        &gpg::Ssh::ALLOWED_SIGNERS_FILE,
+        path(&tmp.path().join("allowed-signers")).as_str(),
+    )?;
+    config.commit()?;
+    Ok((repo, tmp))
+}
+
+fn path(path: &Path) -> String {
+    path.to_str().expect("valid UTF-8").to_owned()
+}
+
+/// Run `git` with the allowed signers file of the fixture in the repository at `root`.
+fn git(root: &Path, args: &[&str]) -> crate::Result<std::process::Output> {
+    let allowed_signers = format!("gpg.ssh.allowedSignersFile={}", path(&root.join("allowed-signers")));
+    Ok(std::process::Command::new("git")
+        .args(["-c", &allowed_signers])
+        .args(args)
+        .current_dir(root.join("repo"))
+        .output()?)
+}
+
+fn commit<'repo>(repo: &'repo gix::Repository, message: &str) -> crate::Result<gix::Id<'repo>> {
+    let head = repo.head_commit()?;
+    Ok(repo.commit("HEAD", message, head.tree_id()?, Some(head.id))?)
+}
+
+#[test]
+fn commits_and_tags_are_signed_if_configured() -> crate::Result {
+    let (mut repo, tmp) = repo_rw()?;
+    let unsigned = commit(&repo, "unsigned")?;
+    assert_eq!(
+        unsigned.object()?.into_commit().verify_signature()?,
+        None,
+        "signing is disabled by default"
+    );
+
+    let mut config = repo.config_snapshot_mut();
+    config.set_value(&Commit::GPG_SIGN, "true")?;
+    config.set_value(&Tag::GPG_SIGN, "true")?;
+    config.commit()?;
+
+    let id = commit(&repo, "signed")?;
+    let commit = id.object()?.into_commit();
+    assert!(
+        commit.signature()?.is_some(),
+        "the signature is stored in the `gpgsig` header"
+    );
+    assert!(
+        commit.data.contains_str("-----END SSH SIGNATURE-----\n\nsigned"),
+        "the header is followed by the message just like `git` does it"
+    );
+    let verification = commit.verify_signature()?.expect("signed");
+    assert_eq!(verification.status, Status::Good);
+    assert_eq!(
+        verification.signer.as_ref().map(|s| s.as_bstr()),
+        Some("signer@example.com".into())
+    );
+    let fingerprint = verification.fingerprint.expect("known for good signatures");
+    assert!(fingerprint.starts_with(b"SHA256:"), "{fingerprint}");
+    let out = git(tmp.path(), &["verify-commit", &id.to_string()])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+
+    let tag_ref = repo.tag(
+        "v1.0.0",
+        id,
+        gix::object::Kind::Commit,
+        Some(repo.committer().expect("present")?),
+        "a multi\nline message",
+        PreviousValue::MustNotExist,
+    )?;
+    let tag = tag_ref.id().object()?.into_tag();
+    let verification = tag.verify_signature()?.expect("signed");
+    assert_eq!(verification.status, Status::Good);
+    assert_eq!(
+        verification.signer.as_ref().map(|s| s.as_bstr()),
+        Some("signer@example.com".into())
+    );
+    let out = git(tmp.path(), &["verify-tag", "v1.0.0"])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+    let out = git(tmp.path(), &["cat-file", "tag", "v1.0.0"])?;
+    assert!(
+        out.stdout
+            .contains_str("a multi\nline message\n-----BEGIN SSH SIGNATURE-----\n"),
+        "the signature follows the message, which was terminated with a newline"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "rebase")]
+fn rebased_commits_are_signed_if_configured() -> crate::Result {
+    let (mut repo, tmp) = repo_rw()?;
+    let base = repo.head_id()?.detach();
+    commit(&repo, "first")?;
+    commit(&repo, "second")?;
+
+    let mut config = repo.config_snapshot_mut();
+    config.set_value(&Commit::GPG_SIGN, "true")?;
+    config.commit()?;
+
+    let outcome = repo
+        .rebase(
+            base,
+            gix::rebase::Options {
+                force: true,
+                ..Default::default()
+            },
+        )?
+        .start(&mut |message| message.to_owned())?;
+    let gix::rebase::Outcome::Finished { head } = outcome else {
+        panic!("the rebase should finish: {outcome:?}")
+    };
+    let second = repo.find_object(head)?.into_commit();
+    let first = repo
+        .find_object(second.parent_ids().next().expect("parent"))?
+        .into_commit();
+    for (commit, message) in [(&first, "first"), (&second, "second")] {
+        assert_eq!(commit.message_raw()?, message);
+        let verification = commit.verify_signature()?.expect("rewritten commits are signed");
+        assert_eq!(verification.status, Status::Good);
+        let out = git(tmp.path(), &["verify-commit", &commit.id.to_string()])?;
+        assert!(out.status.success(), "{}", out.stderr.as_bstr());
+    }
+    Ok(())
+}
+
+#[test]
+fn bad_and_unknown_signatures() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let mut options = repo.signing_options()?;
+    let payload = b"signed data\n";
+    let signature = options.sign(payload, None)?;
+    assert!(signature.starts_with(b"-----BEGIN SSH SIGNATURE-----\n"));
+    assert_eq!(options.verify(payload, &signature)?.status, Status::Good);
+
+    let verification = options.verify(b"other data\n", &signature)?;
+    assert_eq!(verification.status, Status::Bad, "the data doesn't match");
+    assert_eq!(verification.signer, None);
+    assert!(!verification.output.is_empty(), "the reason is provided for display");
+
+    options.signing_key = Some(path(&tmp.path().join("stranger-key")).into());
+    let signature = options.sign(payload, None)?;
+    let verification = options.verify(payload, &signature)?;
+    assert_eq!(
+        verification.status,
+        Status::UnknownKey,
+        "the key isn't in the allowed signers file, but the signature is intact"
+    );
+    assert_eq!(verification.signer, None);
+    assert!(verification.fingerprint.is_some());
+    assert_eq!(
+        options.verify(b"other data\n", &signature)?.status,
+        Status::Bad,
+        "signatures by unknown keys can still be bad"
+    );
+
+    options.ssh_allowed_signers_file = None;
+    options.signing_key = Some(path(&tmp.path().join("signer-key")).into());
+    let signature = options.sign(payload, None)?;
+    assert_eq!(
+        options.verify(payload, &signature)?.status,
+        Status::UnknownKey,
+        "without allowed signers, no key is known"
+    );
+
+    assert!(matches!(
+        options.verify(payload, b"not a signature"),
+        Err(gix::signing::verify::Error::UnknownFormat)
+    ));
+    Ok(())
+}
+
+#[test]
+fn ssh_signatures_need_a_signing_key() -> crate::Result {
+    let (repo, _tmp) = repo_rw()?;
+    let mut options = repo.signing_options()?;
+    options.signing_key = None;
+    assert!(
+        matches!(
+            options.sign(b"data", Some("Name <email>".into())),
+            Err(sign::Error::MissingSigningKey {
+                format: gix::signing::Format::Ssh
+            })
+        ),
+        "unlike OpenPGP keys, SSH keys can't be selected by the identity of the signer"
+    );
+    Ok(())
+}
+
+#[test]
+fn split_tag_signature() {
+    let data = b"object 0000\ntype commit\ntag v1\n\nmessage\n-----BEGIN PGP SIGNATURE-----\nabc\n-----END PGP SIGNATURE-----\n";
+    let (payload, signature) = gix::signing::split_tag_signature(data).expect("signed");
+    assert_eq!(payload.as_bstr(), "object 0000\ntype commit\ntag v1\n\nmessage\n");
+    assert!(signature.starts_with(b"-----BEGIN PGP SIGNATURE-----\n"));
+    assert_eq!(gix::signing::split_tag_signature(b"object 0000\n\nmessage\n"), None);
+}
@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use gix::{bstr::BStr, sparse_checkout};
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_sparse_checkout_repo.sh")?;
+    let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+    Ok((repo, tmp))
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+}
+
+fn files_in_worktree(dir: &Path) -> crate::Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(current) = dirs.pop() {
+        for entry in std::fs::read_dir(current)? {
+            let path = entry?.path();
+            if path.file_name() == Some(".git".as_ref()) {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let rela_path = path.strip_prefix(dir)?.to_str().expect("valid UTF-8");
+                files.push(rela_path.replace('\\', "/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn bstrs<'a>(patterns: &'a [&'a str]) -> impl Iterator<Item = &'a BStr> {
+    patterns.iter().map(|pattern| (*pattern).into())
+}
+
+#[test]
+fn set_in_cone_mode_with_sparse_index_is_understood_by_git() -> crate::Result {
+    let (mut repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    let outcome = repo.sparse_checkout().set(
+        bstrs(&["c1/c2", "d/c4"]),
+        sparse_checkout::Options {
+            cone: true,
+            sparse_index: Some(true),
+        },
+    )?;
+    assert_eq!(outcome.removed, ["c1/c3/a", "e/a"]);
+    assert!(outcome.kept_modified.is_empty() && outcome.kept_existing.is_empty());
+
+    assert_eq!(
+        files_in_worktree(dir)?,
+        ["a", "b", "c1/a", "c1/c2/a", "d/c4/a", "d/c4/c5/a"]
+    );
+    assert_eq!(git(dir, &["sparse-checkout", "list"])?, "c1/c2\nd/c4");
+    assert_eq!(
+        git(dir, &["ls-files", "--sparse"])?,
+        "a\nb\nc1/a\nc1/c2/a\nc1/c3/\nd/c4/a\nd/c4/c5/a\ne/",
+        "excluded directories are collapsed into sparse directory entries"
+    );
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "");
+
+    let patterns = repo.sparse_checkout().patterns()?.expect("enabled");
+    assert!(patterns.is_cone());
+    assert_eq!(patterns.list(), ["c1/c2", "d/c4"]);
+    assert!(!repo.is_dirty()?, "status handles sparse directories");
+    Ok(())
+}
+
+#[test]
+fn modified_files_are_kept_until_reapplied_and_add_checks_out_files() -> crate::Result {
+    let (mut repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    std::fs::write(dir.join("e/a"), "modified")?;
+    let outcome = repo.sparse_checkout().set(bstrs(&["c1"]), Default::default())?;
+    assert_eq!(outcome.removed, ["d/c4/a", "d/c4/c5/a"]);
+    assert_eq!(outcome.kept_modified, ["e/a"], "modified files stay, like git does it");
+    assert_eq!(files_in_worktree(dir)?, ["a", "b", "c1/a", "c1/c2/a", "c1/c3/a", "e/a"]);
+    assert_eq!(git(dir, &["status", "--porcelain"])?, " M e/a");
+
+    git(dir, &["checkout", "e/a"])?;
+    let outcome = repo.sparse_checkout().reapply()?;
+    assert_eq!(outcome.removed, ["e/a"]);
+    assert_eq!(files_in_worktree(dir)?, ["a", "b", "c1/a", "c1/c2/a", "c1/c3/a"]);
+
+    std::fs::create_dir(dir.join("e"))?;
+    std::fs::write(dir.join("e/a"), "")?;
+    let outcome = repo.sparse_checkout().add_patterns(bstrs(&["d/c4/c5", "e"]))?;
+    assert_eq!(
+        outcome.kept_existing,
+        ["e/a"],
+        "files that are present aren't overwritten"
+    );
+    assert_eq!(
+        files_in_worktree(dir)?,
+        ["a", "b", "c1/a", "c1/c2/a", "c1/c3/a", "d/c4/a", "d/c4/c5/a", "e/a"],
+        "files in parent directories of included directories are included as well"
+    );
+    assert_eq!(git(dir, &["sparse-checkout", "list"])?, "c1\nd/c4/c5\ne");
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "");
+    assert_eq!(
+        git(dir, &["ls-files", "-t"])?
+            .lines()
+            .filter(|l| l.starts_with('S'))
+            .count(),
+        0,
+        "nothing is excluded anymore"
+    );
+    Ok(())
+}
+
+#[test]
+fn non_cone_patterns_and_disable() -> crate::Result {
+    let (mut repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    repo.sparse_checkout().set(
+        bstrs(&["/*", "!/*/", "c2/"]),
+        sparse_checkout::Options {
+            cone: false,
+            sparse_index: Some(true),
+        },
+    )?;
+    assert_eq!(files_in_worktree(dir)?, ["a", "b", "c1/c2/a"]);
+    assert!(!repo.sparse_checkout().patterns()?.expect("enabled").is_cone());
+    assert_eq!(
+        git(dir, &["ls-files", "--sparse"])?.lines().count(),
+        8,
+        "sparse indices are only written in cone mode"
+    );
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "");
+
+    let outcome = repo.sparse_checkout().disable()?;
+    assert!(outcome.removed.is_empty() && outcome.kept_existing.is_empty());
+    assert_eq!(
+        files_in_worktree(dir)?,
+        ["a", "b", "c1/a", "c1/c2/a", "c1/c3/a", "d/c4/a", "d/c4/c5/a", "e/a"]
+    );
+    assert!(repo.sparse_checkout().patterns()?.is_none());
+    assert_eq!(git(dir, &["config", "core.sparseCheckout"])?, "false");
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "");
+    Ok(())
+}
+
+#[test]
+fn status_of_sparse_index_created_by_git() -> crate::Result {
+    let (_repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    git(dir, &["sparse-checkout", "set", "--sparse-index", "c1"])?;
+    let repo = gix::open_opts(dir, crate::restricted())?;
+    assert!(repo.open_index()?.is_sparse());
+    assert!(!repo.is_dirty()?);
+
+    std::fs::write(dir.join("c1/a"), "modified")?;
+    assert!(repo.is_dirty()?);
+
+    let mut repo = repo;
+    repo.sparse_checkout().reapply()?;
+    assert!(repo.open_index()?.is_sparse(), "the index stays sparse");
+    assert_eq!(git(dir, &["status", "--porcelain"])?, " M c1/a");
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use gix::stash;
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_stash_repo.sh")?;
+    let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+    Ok((repo, tmp))
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+}
+
+fn messages(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+    Ok(repo
+        .stash_list()?
+        .into_iter()
+        .map(|entry| entry.message.to_string())
+        .collect())
+}
+
+#[test]
+fn list_entries_created_by_git() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let entries = repo.stash_list()?;
+    assert_eq!(entries.len(), 2);
+    for (index, entry) in entries.iter().enumerate() {
+        assert_eq!(
+            entry.id.to_string(),
+            git(tmp.path(), &["rev-parse", &format!("stash@{{{index}}}")])?
+        );
+    }
+    assert_eq!(
+        messages(&repo)?.join("\n"),
+        git(tmp.path(), &["stash", "list", "--format=%gs"])?
+    );
+    assert_eq!(messages(&repo)?[0], "On main: second");
+
+    let empty = gix::init(tmp.path().join("empty"))?;
+    assert!(empty.stash_list()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn stash_is_understood_by_git() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    assert_eq!(repo.stash(Default::default())?, None, "nothing to stash");
+
+    std::fs::write(dir.join("a"), "1\n2\n3\nchanged\n")?;
+    std::fs::write(dir.join("new"), "staged\n")?;
+    git(dir, &["add", "new"])?;
+    std::fs::remove_file(dir.join("b"))?;
+    std::fs::write(dir.join("untracked"), "u\n")?;
+    let id = repo
+        .stash(stash::Options {
+            message: None,
+            include_untracked: true,
+        })?
+        .expect("there are changes");
+
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "", "everything was stashed");
+    assert!(!dir.join("untracked").exists());
+    assert_eq!(id.to_string(), git(dir, &["rev-parse", "stash@{0}"])?);
+    let head = git(dir, &["rev-parse", "--short", "HEAD"])?;
+    assert_eq!(
+        git(dir, &["stash", "list", "--format=%gs"])?.lines().next(),
+        Some(format!("WIP on main: {head} initial commit").as_str())
+    );
+    assert_eq!(
+        git(dir, &["log", "-1", "--format=%B", "stash^2"])?,
+        format!("index on main: {head} initial commit")
+    );
+    assert_eq!(git(dir, &["show", "stash^3:untracked"])?, "u");
+    assert_eq!(git(dir, &["show", "stash^2:new"])?, "staged");
+    assert_eq!(
+        git(dir, &["stash", "show", "--name-status", "stash@{0}"])?,
+        "M\ta\nD\tb\nA\tnew"
+    );
+
+    git(dir, &["stash", "pop", "-q", "--index"])?;
+    assert_eq!(
+        git(dir, &["status", "--porcelain"])?,
+        " M a\n D b\nA  new\n?? untracked",
+        "git restores everything as it was"
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_and_pop_entries_created_by_git() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+
+    let outcome = repo.stash_apply(0, Default::default())?;
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(
+        git(dir, &["status", "--porcelain"])?,
+        " M b\n M dir/c",
+        "without restoring the index, all changes are unstaged"
+    );
+    assert_eq!(std::fs::read(dir.join("dir/c"))?, b"c in worktree\n");
+    assert_eq!(repo.stash_list()?.len(), 2, "applying keeps the entry");
+
+    assert!(matches!(
+        repo.stash_apply(1, Default::default()),
+        Err(stash::apply::Error::UncommittedChanges)
+    ));
+    git(dir, &["checkout", "-q", "--", "."])?;
+
+    let outcome = repo.stash_pop(0, stash::apply::Options { index: true })?;
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "M  b\n M dir/c");
+    assert_eq!(messages(&repo)?, [git(dir, &["stash", "list", "--format=%gs"])?]);
+    assert!(messages(&repo)?[0].starts_with("WIP on main: "));
+
+    assert!(matches!(
+        repo.stash_apply(1, Default::default()),
+        Err(stash::apply::Error::NoSuchEntry { index: 1 })
+    ));
+    Ok(())
+}
+
+#[test]
+fn apply_with_conflicts() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    std::fs::write(dir.join("a"), "1\n2\n3\ncommitted\n")?;
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=a",
+            "-c",
+            "user.email=a@example.com",
+            "commit",
+            "-q",
+            "-am",
+            "change a",
+        ],
+    )?;
+
+    let outcome = repo.stash_pop(1, Default::default())?;
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.conflicts[0].path, "a");
+    assert_eq!(git(dir, &["status", "--porcelain"])?, "UU a");
+    assert_eq!(repo.stash_list()?.len(), 2, "entries with conflicts aren't dropped");
+    Ok(())
+}
+
+#[test]
+fn drop_entries_like_git() -> crate::Result {
+    let (repo, tmp) = repo_rw()?;
+    let dir = tmp.path();
+    std::fs::write(dir.join("b"), "third\n")?;
+    let newest = repo.stash(stash::Options {
+        message: Some("third".into()),
+        include_untracked: false,
+    })?;
+    let expected = repo.stash_list()?;
+    assert_eq!(expected[0].message, "On main: third");
+
+    assert_eq!(repo.stash_drop(1)?, expected[1].id);
+    assert_eq!(
+        messages(&repo)?.join("\n"),
+        git(dir, &["stash", "list", "--format=%gs"])?,
+        "the reflog stays readable by git"
+    );
+    assert_eq!(repo.stash_list()?, [expected[0].clone(), expected[2].clone()]);
+    assert_eq!(repo.find_reference("refs/stash")?.id(), newest.expect("stashed"));
+
+    assert_eq!(repo.stash_drop(0)?, expected[0].id);
+    assert_eq!(
+        repo.find_reference("refs/stash")?.id(),
+        expected[2].id,
+        "the ref follows the newest entry"
+    );
+    assert_eq!(git(dir, &["rev-parse", "stash@{0}"])?, expected[2].id.to_string());
+
+    assert!(matches!(
+        repo.stash_drop(1),
+        Err(stash::drop::Error::NoSuchEntry { index: 1 })
+    ));
+    assert_eq!(repo.stash_drop(0)?, expected[2].id);
+    assert!(
+        repo.try_find_reference("refs/stash")?.is_none(),
+        "dropping the last entry removes the ref"
+    );
+    assert!(repo.stash_list()?.is_empty());
+    assert!(matches!(
+        repo.stash_drop(0),
+        Err(stash::drop::Error::NoSuchEntry { index: 0 })
+    ));
+    Ok(())
+}
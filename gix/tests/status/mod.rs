@@ -16,6 +16,113 @@ pub fn repo(name: &str) -> crate::Result<gix::Repository> {
     )?)
 }
 
+mod iter {
+    use crate::status::repo;
+    use gix::status::iter::Item;
+    use gix::status::plumbing::index_as_worktree_with_renames::Summary as IndexWorktreeSummary;
+    use gix::status::plumbing::tree_index::Summary as TreeIndexSummary;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        Staged(TreeIndexSummary),
+        Unstaged(IndexWorktreeSummary),
+    }
+
+    fn summaries(status: &mut gix::status::Iter) -> crate::Result<Vec<(String, Kind)>> {
+        let mut out = Vec::new();
+        for item in status {
+            let item = item?;
+            let kind = match &item {
+                Item::TreeIndex(change) => Kind::Staged(change.summary()),
+                Item::IndexWorktree(item) => match item.summary() {
+                    Some(summary) => Kind::Unstaged(summary),
+                    None => continue,
+                },
+            };
+            out.push((item.location().to_string(), kind));
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn staged_and_unstaged_changes() -> crate::Result {
+        let repo = repo("staged-changes")?;
+        let mut status = repo.status(gix::progress::Discard)?.into_iter(Vec::new())?;
+        assert_eq!(
+            summaries(&mut status)?,
+            [
+                ("added".into(), Kind::Staged(TreeIndexSummary::Added)),
+                ("content".into(), Kind::Staged(TreeIndexSummary::Modified)),
+                ("renamed".into(), Kind::Staged(TreeIndexSummary::Renamed)),
+                ("content".into(), Kind::Unstaged(IndexWorktreeSummary::Modified)),
+            ],
+            "staged changes come first, then the unstaged ones"
+        );
+        let out = status.into_outcome().expect("iteration done");
+        let tree_index = out.tree_index.expect("the head tree is compared by default");
+        assert_eq!(
+            tree_index
+                .rewrites
+                .expect("renames are tracked by default")
+                .num_similarity_checks,
+            0,
+            "the rename is exact"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn renames_can_be_disabled() -> crate::Result {
+        let repo = repo("staged-changes")?;
+        let mut status = repo
+            .status(gix::progress::Discard)?
+            .tree_index_track_renames(gix::status::tree_index::TrackRenames::Disabled)
+            .into_iter(["dir".into(), "renamed".into()])?;
+        assert_eq!(
+            summaries(&mut status)?,
+            [
+                ("dir/original".into(), Kind::Staged(TreeIndexSummary::Removed)),
+                ("renamed".into(), Kind::Staged(TreeIndexSummary::Added)),
+            ],
+            "pathspecs apply to staged changes as well"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn head_tree_can_be_disabled() -> crate::Result {
+        let repo = repo("staged-changes")?;
+        let mut status = repo
+            .status(gix::progress::Discard)?
+            .head_tree(None)
+            .into_iter(Vec::new())?;
+        let items: Vec<_> = status.by_ref().filter_map(Result::ok).collect();
+        assert_eq!(items.len(), 1, "only the unstaged modification remains");
+        assert!(matches!(items[0], Item::IndexWorktree(_)));
+        assert!(status.outcome_mut().expect("iteration done").tree_index.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_head_tree_shows_all_entries_as_added() -> crate::Result {
+        let repo = repo("staged-changes")?;
+        let mut status = repo
+            .status(gix::progress::Discard)?
+            .head_tree(gix::ObjectId::empty_tree(repo.object_hash()))
+            .into_iter(["added".into(), "renamed".into()])?;
+        assert_eq!(
+            summaries(&mut status)?,
+            [
+                ("added".into(), Kind::Staged(TreeIndexSummary::Added)),
+                ("renamed".into(), Kind::Staged(TreeIndexSummary::Added)),
+            ],
+            "this is what an unborn `HEAD` looks like, and the empty tree doesn't have to exist"
+        );
+        Ok(())
+    }
+}
+
 mod index_worktree {
     mod iter {
         use crate::status::{repo, submodule_repo};
@@ -26,7 +133,7 @@ mod index_worktree {
         fn item_size() {
             assert_eq!(
                 std::mem::size_of::<gix::status::index_worktree::iter::Item>(),
-                264,
+                320,
                 "The size is pretty huge and goes down ideally"
             );
         }
@@ -112,6 +219,161 @@ mod index_worktree {
             Ok(())
         }
 
+        #[test]
+        fn untracked_cache_is_created_and_written_if_configured() -> crate::Result {
+            let tmp = gix_testtools::scripted_fixture_writable("make_status_repos.sh")?;
+            let mut repo = gix::open_opts(tmp.path().join("untracked-only"), gix::open::Options::isolated())?;
+            assert!(repo.open_index()?.untracked().is_none(), "no untracked cache initially");
+
+            repo.config_snapshot_mut()
+                .set_value(&gix::config::tree::Core::UNTRACKED_CACHE, "true")?;
+            let mut status = repo
+                .status(gix::progress::Discard)?
+                .into_index_worktree_iter(Vec::new())?;
+            let items: Vec<_> = status.by_ref().filter_map(Result::ok).collect();
+            assert_eq!(items.len(), 2, "the same untracked files are found as without cache");
+            let out = status.outcome_mut().expect("iteration done");
+            assert!(out.has_changes(), "the new untracked cache wants to be written");
+            out.write_changes().expect("changes present")?;
+
+            let index = repo.open_index()?;
+            let cache = index.untracked().expect("the untracked cache was written");
+            assert_eq!(
+                cache.dir_flags,
+                gix::index::extension::untracked_cache::dir_flags::SHOW_OTHER_DIRECTORIES
+                    | gix::index::extension::untracked_cache::dir_flags::HIDE_EMPTY_DIRECTORIES,
+                "the flags match the collapsing directory walk, just like `git status` would set them"
+            );
+            assert_eq!(cache.exclude_filename_per_dir, ".gitignore");
+            let mut names: Vec<_> = cache.directories.iter().map(|dir| dir.name.to_string()).collect();
+            names.sort();
+            assert_eq!(names, ["", "new", "subdir"], "each visited directory is recorded");
+            Ok(())
+        }
+
+        #[test]
+        fn untracked_cache_is_removed_if_disabled() -> crate::Result {
+            let tmp = gix_testtools::scripted_fixture_writable("make_status_repos.sh")?;
+            let repo = gix::open_opts(tmp.path().join("untracked-only"), gix::open::Options::isolated())?;
+            for mode in [
+                gix::status::UntrackedCache::Enable,
+                gix::status::UntrackedCache::Disable,
+            ] {
+                let mut status = repo
+                    .status(gix::progress::Discard)?
+                    .untracked_cache(mode)
+                    .into_index_worktree_iter(Vec::new())?;
+                for item in status.by_ref() {
+                    item?;
+                }
+                status
+                    .outcome_mut()
+                    .expect("iteration done")
+                    .write_changes()
+                    .expect("changes present")?;
+            }
+            assert!(
+                repo.open_index()?.untracked().is_none(),
+                "the untracked cache was added, and removed once it was disabled"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn fs_monitor_hook_is_trusted_to_skip_unchanged_entries() -> crate::Result {
+            let tmp = gix_testtools::scripted_fixture_writable("make_status_repos.sh")?;
+            let mut repo = gix::open_opts(tmp.path().join("untracked-only"), gix::open::Options::isolated())?;
+            let git_dir = repo.git_dir().to_owned();
+            std::fs::write(
+                git_dir.join("fsmonitor-hook"),
+                "echo \"$1 $2\" >> .git/fsmonitor-args\n\
+                 printf 'token-%s\\0' \"$(wc -l < .git/fsmonitor-args | tr -d ' ')\"\n\
+                 cat .git/fsmonitor-changes 2>/dev/null || true\n",
+            )?;
+            repo.config_snapshot_mut()
+                .set_value(&gix::config::tree::Core::FSMONITOR, "sh .git/fsmonitor-hook")?;
+
+            let modified_paths = |repo: &gix::Repository| -> crate::Result<Vec<String>> {
+                let mut status = repo
+                    .status(gix::progress::Discard)?
+                    .untracked_files(gix::status::UntrackedFiles::None)
+                    .into_index_worktree_iter(Vec::new())?;
+                let mut paths = Vec::new();
+                for item in status.by_ref() {
+                    if let Item::Modification { rela_path, .. } = item? {
+                        paths.push(rela_path.to_string());
+                    }
+                }
+                let out = status.outcome_mut().expect("iteration done");
+                assert!(out.has_changes(), "the new token is always written");
+                out.write_changes().expect("changes present")?;
+                Ok(paths)
+            };
+            let token = |repo: &gix::Repository| -> crate::Result<Option<gix::index::extension::fs_monitor::Token>> {
+                Ok(repo
+                    .open_index()?
+                    .fs_monitor()
+                    .map(|fs_monitor| fs_monitor.token().clone()))
+            };
+
+            assert_eq!(modified_paths(&repo)?, Vec::<String>::new());
+            assert_eq!(
+                token(&repo)?,
+                Some(gix::index::extension::fs_monitor::Token::V2 {
+                    token: "token-1".into()
+                })
+            );
+
+            std::fs::write(repo.work_dir().expect("non-bare").join("this"), "modified")?;
+            assert_eq!(
+                modified_paths(&repo)?,
+                Vec::<String>::new(),
+                "the modification isn't seen as the hook doesn't report it"
+            );
+
+            std::fs::write(git_dir.join("fsmonitor-changes"), "this\0")?;
+            assert_eq!(modified_paths(&repo)?, ["this"], "now the hook reports it");
+            let index = repo.open_index()?;
+            let fs_monitor = index.fs_monitor().expect("present");
+            assert_eq!(
+                fs_monitor.token(),
+                &gix::index::extension::fs_monitor::Token::V2 {
+                    token: "token-3".into()
+                }
+            );
+            assert_eq!(
+                fs_monitor.entry_dirty().to_words(),
+                [0b10],
+                "the changed entry is recorded as dirty, the others remain valid"
+            );
+            assert_eq!(
+                std::fs::read_to_string(git_dir.join("fsmonitor-args"))?,
+                "2 builtin:fake\n2 token-1\n2 token-2\n",
+                "the token of the previous query is passed each time"
+            );
+
+            let mut status = repo
+                .status(gix::progress::Discard)?
+                .fs_monitor(None)
+                .into_index_worktree_iter(Vec::new())?;
+            assert_eq!(
+                status
+                    .by_ref()
+                    .filter_map(Result::ok)
+                    .filter(|item| matches!(item, Item::Modification { .. }))
+                    .count(),
+                1,
+                "without monitor, the modification is still seen"
+            );
+            status
+                .outcome_mut()
+                .expect("iteration done")
+                .write_changes()
+                .expect("changes present")?;
+            assert_eq!(token(&repo)?, None, "the extension is removed without monitor");
+            Ok(())
+        }
+
         #[test]
         fn early_drop_for_is_dirty_emulation() -> crate::Result {
             let repo = submodule_repo("modified-untracked-and-submodule-head-changed-and-modified")?;
@@ -132,7 +394,7 @@ mod index_worktree {
 }
 
 mod is_dirty {
-    use crate::status::submodule_repo;
+    use crate::status::{repo, submodule_repo};
 
     #[test]
     fn various_changes_positive() -> crate::Result {
@@ -165,10 +427,30 @@ mod is_dirty {
         Ok(())
     }
 
+    #[test]
+    fn staged_changes_are_picked_up() -> crate::Result {
+        let repo = repo("staged-only")?;
+        assert!(
+            repo.is_dirty()?,
+            "changes between the head and the index are discovered"
+        );
+        Ok(())
+    }
+
     #[test]
     fn no_changes() -> crate::Result {
-        let repo = submodule_repo("with-submodules")?;
+        let repo = submodule_repo("with-submodules-after-clone")?;
         assert!(!repo.is_dirty()?, "there are no changes");
         Ok(())
     }
+
+    #[test]
+    fn staged_submodules_are_picked_up() -> crate::Result {
+        let repo = submodule_repo("with-submodules")?;
+        assert!(
+            repo.is_dirty()?,
+            "the second submodule was added to the index, but wasn't committed yet"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,358 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+};
+
+use gix::protocol::upload_pack::{daemon, http};
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_upload_pack_repo.sh")
+}
+
+/// Serve `repo_path` to all clients connecting via `git://` and return the URL to use for it.
+fn spawn_daemon(repo_path: PathBuf) -> crate::Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("git://{}/repo", listener.local_addr()?);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.expect("valid connection");
+            let request = daemon::Request::read(&stream).expect("valid initial request");
+            assert_eq!(request.protocol(), gix::protocol::transport::Protocol::V2);
+            let repo = gix::open_opts(&repo_path, crate::restricted()).expect("valid repository");
+            repo.upload_pack()
+                .serve(&stream, &stream)
+                .expect("requests can be served");
+        }
+    });
+    Ok(url)
+}
+
+/// Serve `repo_path` to all clients connecting via smart-HTTP and return the URL to use for it.
+fn spawn_http_server(repo_path: PathBuf) -> crate::Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/repo", listener.local_addr()?);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            serve_http(&repo_path, stream.expect("valid connection")).expect("requests can be served");
+        }
+    });
+    Ok(url)
+}
+
+/// A minimal HTTP/1.1 server, just enough for `git` to talk to it.
+fn serve_http(repo_path: &Path, mut stream: TcpStream) -> crate::Result {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next().expect("method").to_owned();
+    let target = request_line.next().expect("target").to_owned();
+
+    let (mut content_length, mut chunked, mut protocol) = (None, false, String::new());
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => content_length = Some(value.parse::<u64>()?),
+            "transfer-encoding" => chunked = value == "chunked",
+            "git-protocol" => protocol = value.to_owned(),
+            "content-encoding" => panic!("compressed bodies aren't supported"),
+            "expect" => stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?,
+            _ => {}
+        }
+    }
+    assert!(http::is_v2(&protocol), "the client asks for protocol V2");
+
+    let mut body = Vec::new();
+    if let Some(len) = content_length {
+        reader.by_ref().take(len).read_to_end(&mut body)?;
+    } else if chunked {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let len = usize::from_str_radix(line.trim_end(), 16)?;
+            let mut chunk = vec![0; len + 2];
+            reader.read_exact(&mut chunk)?;
+            if len == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..len]);
+        }
+    }
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target.as_str(), None),
+    };
+    let (repo_url_path, endpoint) = http::Endpoint::from_request(&method, path, query).expect("upload-pack request");
+    assert_eq!(repo_url_path, "/repo");
+    let mut response = Vec::new();
+    let repo = gix::open_opts(repo_path, crate::restricted())?;
+    repo.upload_pack()
+        .serve_http(endpoint, body.as_slice(), &mut response)?;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        endpoint.content_type(),
+        response.len()
+    )?;
+    stream.write_all(&response)?;
+    Ok(())
+}
+
+fn git(dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .args(["-c", "protocol.version=2"])
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(
+        out.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    Ok(String::from_utf8(out.stdout)?.trim_end().to_owned())
+}
+
+fn commit_count(dir: &Path) -> crate::Result<usize> {
+    Ok(git(dir, &["rev-list", "--count", "HEAD"])?.parse()?)
+}
+
+#[test]
+fn git_clones_via_git_protocol() -> crate::Result {
+    let fixture = fixture()?;
+    let url = spawn_daemon(fixture.clone())?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(tmp.path(), &["clone", "-q", &url, "clone"])?;
+
+    let clone = tmp.path().join("clone");
+    git(&clone, &["fsck", "--strict"])?;
+    for (ours, theirs) in [
+        ("HEAD", "HEAD"),
+        ("origin/dev", "dev"),
+        ("v1", "v1"),
+        ("v1^{commit}", "v1^{commit}"),
+    ] {
+        assert_eq!(
+            git(&clone, &["rev-parse", ours])?,
+            git(&fixture, &["rev-parse", theirs])?
+        );
+    }
+    assert_eq!(commit_count(&clone)?, 5);
+    assert_eq!(
+        std::fs::read_to_string(clone.join("file5"))?,
+        "5\n",
+        "the worktree was checked out"
+    );
+    Ok(())
+}
+
+#[test]
+fn git_shows_progress_sent_via_sideband() -> crate::Result {
+    let url = spawn_daemon(fixture()?)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let out = std::process::Command::new("git")
+        .args(["-c", "protocol.version=2", "clone", "--progress", &url, "clone"])
+        .current_dir(tmp.path())
+        .output()?;
+    assert!(out.status.success());
+    let stderr = String::from_utf8(out.stderr)?;
+    assert!(
+        stderr.contains("remote: counting objects: 20, done."),
+        "objects are counted: {stderr}"
+    );
+    assert!(stderr.contains("remote: Total 20"), "{stderr}");
+    Ok(())
+}
+
+#[test]
+fn git_fetches_with_negotiation() -> crate::Result {
+    let url = spawn_daemon(fixture()?)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(
+        tmp.path(),
+        &["clone", "-q", "--single-branch", "--branch", "main", &url, "clone"],
+    )?;
+
+    let clone = tmp.path().join("clone");
+    git(&clone, &["fetch", "-q", "origin", "dev:refs/remotes/origin/dev"])?;
+    git(&clone, &["fsck", "--strict"])?;
+    assert_eq!(git(&clone, &["rev-list", "--count", "origin/dev"])?, "4");
+    Ok(())
+}
+
+#[test]
+fn git_clones_and_fetches_using_a_bitmap() -> crate::Result {
+    let fixture = gix_testtools::scripted_fixture_writable("make_upload_pack_repo.sh")?;
+    git(fixture.path(), &["repack", "-adbq"])?;
+    let repo = gix::open_opts(fixture.path(), crate::restricted())?;
+    assert!(
+        repo.pack_bitmap()?.is_some(),
+        "the bitmap is used for selecting objects"
+    );
+
+    let url = spawn_daemon(fixture.path().to_owned())?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(
+        tmp.path(),
+        &["clone", "-q", "--single-branch", "--branch", "main", &url, "clone"],
+    )?;
+    let clone = tmp.path().join("clone");
+    git(&clone, &["fsck", "--strict"])?;
+    assert_eq!(commit_count(&clone)?, 5);
+
+    git(&clone, &["fetch", "-q", "origin", "dev:refs/remotes/origin/dev"])?;
+    git(&clone, &["fsck", "--strict"])?;
+    assert_eq!(git(&clone, &["rev-list", "--count", "origin/dev"])?, "4");
+    Ok(())
+}
+
+#[test]
+fn git_clones_shallow_and_deepens() -> crate::Result {
+    let url = spawn_daemon(fixture()?)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(
+        tmp.path(),
+        &["clone", "-q", "--single-branch", "--depth", "1", &url, "clone"],
+    )?;
+
+    let clone = tmp.path().join("clone");
+    assert_eq!(commit_count(&clone)?, 1);
+    git(&clone, &["fsck"])?;
+
+    git(&clone, &["fetch", "-q", "--deepen", "2"])?;
+    assert_eq!(commit_count(&clone)?, 3, "two more commits are added");
+    git(&clone, &["fsck"])?;
+
+    git(&clone, &["fetch", "-q", "--unshallow"])?;
+    assert_eq!(commit_count(&clone)?, 5);
+    assert!(!clone.join(".git/shallow").exists(), "the clone isn't shallow anymore");
+    git(&clone, &["fsck", "--strict"])?;
+    Ok(())
+}
+
+#[test]
+fn git_clones_shallow_since_date_and_excluding_refs() -> crate::Result {
+    let url = spawn_daemon(fixture()?)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(
+        tmp.path(),
+        &[
+            "clone",
+            "-q",
+            "--shallow-since",
+            "2001-09-12 00:00:00 +0000",
+            &url,
+            "since",
+        ],
+    )?;
+    assert_eq!(
+        commit_count(&tmp.path().join("since"))?,
+        3,
+        "commit 3 is the first one after the date"
+    );
+    git(&tmp.path().join("since"), &["fsck"])?;
+
+    git(tmp.path(), &["clone", "-q", "--shallow-exclude", "v1", &url, "exclude"])?;
+    assert_eq!(
+        commit_count(&tmp.path().join("exclude"))?,
+        3,
+        "commits reachable from v1 aren't sent"
+    );
+    git(&tmp.path().join("exclude"), &["fsck"])?;
+    Ok(())
+}
+
+#[test]
+fn git_clones_partially() -> crate::Result {
+    let url = spawn_daemon(fixture()?)?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let missing_objects = |dir: &Path| -> crate::Result<usize> {
+        Ok(git(dir, &["rev-list", "--objects", "--all", "--missing=print"])?
+            .lines()
+            .filter(|line| line.starts_with('?'))
+            .count())
+    };
+
+    git(
+        tmp.path(),
+        &["clone", "-q", "--no-checkout", "--filter=blob:none", &url, "none"],
+    )?;
+    let clone = tmp.path().join("none");
+    assert_eq!(missing_objects(&clone)?, 7, "all blobs are missing");
+    git(&clone, &["checkout", "-q", "main"])?;
+    assert_eq!(
+        std::fs::read_to_string(clone.join("file1"))?,
+        "1\n",
+        "blobs are fetched on demand"
+    );
+
+    git(
+        tmp.path(),
+        &["clone", "-q", "--no-checkout", "--filter=blob:limit=1k", &url, "limit"],
+    )?;
+    assert_eq!(
+        missing_objects(&tmp.path().join("limit"))?,
+        1,
+        "only the large blob is missing"
+    );
+    Ok(())
+}
+
+#[test]
+fn git_clones_via_smart_http() -> crate::Result {
+    let fixture = fixture()?;
+    let url = spawn_http_server(fixture.clone())?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    git(tmp.path(), &["clone", "-q", &url, "clone"])?;
+
+    let clone = tmp.path().join("clone");
+    git(&clone, &["fsck", "--strict"])?;
+    assert_eq!(git(&clone, &["rev-parse", "v1"])?, git(&fixture, &["rev-parse", "v1"])?);
+    assert_eq!(commit_count(&clone)?, 5);
+
+    git(tmp.path(), &["clone", "-q", "--depth", "2", &url, "shallow"])?;
+    assert_eq!(commit_count(&tmp.path().join("shallow"))?, 2);
+    git(&tmp.path().join("shallow"), &["fsck"])?;
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "blocking-network-client")]
+fn gix_clones_via_git_protocol() -> crate::Result {
+    let fixture = fixture()?;
+    let url = spawn_daemon(fixture.clone())?;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let (repo, _outcome) = gix::clone::PrepareFetch::new(
+        url.as_str(),
+        tmp.path(),
+        gix::create::Kind::Bare,
+        Default::default(),
+        crate::restricted(),
+    )?
+    .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+    let remote = gix::open_opts(&fixture, crate::restricted())?;
+    for (ours, theirs) in [
+        ("refs/heads/main", "refs/heads/main"),
+        ("refs/remotes/origin/dev", "refs/heads/dev"),
+        ("refs/tags/v1", "refs/tags/v1"),
+    ] {
+        assert_eq!(
+            repo.find_reference(ours)?.id(),
+            remote.find_reference(theirs)?.id(),
+            "{ours} matches {theirs}"
+        );
+    }
+    assert_eq!(
+        repo.head_id()?.ancestors().all()?.count(),
+        5,
+        "all commits were received"
+    );
+    Ok(())
+}
@@ -16,8 +16,8 @@ use gix::bstr::{io::BufReadExt, BString};
 
 use crate::plumbing::{
     options::{
-        attributes, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb, revision, tree,
-        Args, Subcommands,
+        attributes, blame, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, merge_tree,
+        odb, revision, tree, Args, Subcommands,
     },
     show_progress,
 };
@@ -378,6 +378,31 @@ pub fn main() -> Result<()> {
                 },
             )
             .map(|_| ()),
+            commitgraph::Subcommands::Write { changed_paths, split } => prepare_and_run(
+                "commitgraph-write",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::commitgraph::write(
+                        repository(Mode::Lenient)?,
+                        gix::commitgraph::write::Options {
+                            split: split.map(|split| match split {
+                                commitgraph::Split::Merge => gix::commitgraph::write::Split::Merge,
+                                commitgraph::Split::NoMerge => gix::commitgraph::write::Split::NoMerge,
+                                commitgraph::Split::Replace => gix::commitgraph::write::Split::Replace,
+                            }),
+                            changed_paths: changed_paths.then(Default::default),
+                            ..Default::default()
+                        },
+                        out,
+                        format,
+                    )
+                },
+            )
+            .map(|_| ()),
             commitgraph::Subcommands::Verify { statistics } => prepare_and_run(
                 "commitgraph-verify",
                 trace,
@@ -680,6 +705,7 @@ pub fn main() -> Result<()> {
                     repository,
                     expansion,
                     thin,
+                    delta_compression,
                     statistics,
                     nondeterministic_count,
                     tips,
@@ -702,6 +728,7 @@ pub fn main() -> Result<()> {
                             let context = core::pack::create::Context {
                                 thread_limit,
                                 thin,
+                                delta_compression,
                                 nondeterministic_thread_count: nondeterministic_count.then_some(counting_threads),
                                 pack_cache_size_in_bytes: pack_cache_size_mb.unwrap_or(0) * 1_000_000,
                                 object_cache_size_in_bytes: object_cache_size_mb.unwrap_or(0) * 1_000_000,
@@ -1069,8 +1096,8 @@ pub fn main() -> Result<()> {
                 progress,
                 progress_keep_open,
                 None,
-                move |_progress, _out, _err| {
-                    core::repository::commit::verify(repository(Mode::Lenient)?, rev_spec.as_deref())
+                move |_progress, _out, err| {
+                    core::repository::commit::verify(repository(Mode::Lenient)?, rev_spec.as_deref(), err)
                 },
             ),
             commit::Subcommands::Describe {
@@ -1202,6 +1229,61 @@ pub fn main() -> Result<()> {
             None,
             move |_progress, out, _err| core::repository::fsck(repository(Mode::Strict)?, spec, out),
         ),
+        Subcommands::Blame(blame::Platform {
+            ranges,
+            ignore_whitespace,
+            ignore_rev,
+            porcelain,
+            incremental,
+            statistics,
+            file,
+            rev,
+        }) => prepare_and_run(
+            "blame",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, err| {
+                core::repository::blame(
+                    repository(Mode::Strict)?,
+                    &file,
+                    rev,
+                    out,
+                    Some(err),
+                    core::repository::blame::Options {
+                        format: if porcelain {
+                            core::repository::blame::Format::Porcelain
+                        } else if incremental {
+                            core::repository::blame::Format::Incremental
+                        } else {
+                            core::repository::blame::Format::Human
+                        },
+                        ranges,
+                        ignore_whitespace,
+                        ignore_revs: ignore_rev,
+                        statistics,
+                    },
+                )
+            },
+        ),
+        Subcommands::MergeTree(merge_tree::Platform {
+            merge_base,
+            name_only,
+            ours,
+            theirs,
+        }) => prepare_and_run(
+            "merge-tree",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::merge_tree(repository(Mode::Strict)?, ours, theirs, merge_base, name_only, out)
+            },
+        ),
         Subcommands::Mailmap(cmd) => match cmd {
             mailmap::Subcommands::Entries => prepare_and_run(
                 "mailmap-entries",
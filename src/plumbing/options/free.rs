@@ -169,6 +169,13 @@ pub mod pack {
             /// Packs produced with this option enabled are only valid in transit, but not at rest.
             thin: bool,
 
+            #[clap(long)]
+            /// if set, objects that would otherwise be written as base objects are delta-compressed against similar objects.
+            ///
+            /// This is configured by `pack.window` and `pack.depth`, and blobs in the index whose `delta` attribute is unset
+            /// are never delta-compressed.
+            delta_compression: bool,
+
             /// The directory into which to write the pack file.
             #[clap(long, short = 'o')]
             output_directory: Option<PathBuf>,
@@ -177,7 +184,8 @@ pub mod pack {
             /// or as branch names.
             ///
             /// If empty, we expect to read objects on stdin and default to 'none' as expansion mode.
-            /// Otherwise the expansion mode is 'tree-traversal' by default.
+            /// Otherwise the expansion mode is 'tree-traversal' by default, which uses reachability bitmaps
+            /// to speed up counting if there are any, unless `pack.useBitmaps` is false.
             tips: Vec<OsString>,
         },
         /// Use the gix-protocol to receive a pack, emulating a clone.
@@ -83,6 +83,8 @@ pub enum Subcommands {
     Archive(archive::Platform),
     #[cfg(feature = "gitoxide-core-tools-clean")]
     Clean(clean::Command),
+    /// Attribute each line of a file to the commit that introduced it, similar to `git blame`.
+    Blame(blame::Platform),
     /// Subcommands for interacting with commit-graphs
     #[clap(subcommand)]
     CommitGraph(commitgraph::Subcommands),
@@ -91,6 +93,8 @@ pub enum Subcommands {
     Odb(odb::Subcommands),
     /// Check for missing objects.
     Fsck(fsck::Platform),
+    /// Merge two trees or commits without touching the index or the worktree, similar to `git merge-tree --write-tree`.
+    MergeTree(merge_tree::Platform),
     /// Interact with tree objects.
     #[clap(subcommand)]
     Tree(tree::Subcommands),
@@ -602,6 +606,71 @@ pub mod fsck {
     }
 }
 
+pub mod blame {
+    use std::{ffi::OsString, ops::Range};
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Only blame the lines in the given 1-based and inclusive range, like `10,20`. May be given multiple times.
+        #[clap(short = 'L', value_parser = parse_range)]
+        pub ranges: Vec<Range<u32>>,
+        /// Ignore whitespace when comparing lines to find where they were introduced.
+        #[clap(short = 'w')]
+        pub ignore_whitespace: bool,
+        /// Attribute the lines changed by the given revision to the commit that introduced them before.
+        /// May be given multiple times, and adds to the revisions listed in `blame.ignoreRevsFile`.
+        #[clap(long)]
+        pub ignore_rev: Vec<String>,
+        /// Print information suitable for consumption by programs, similar to `git blame --porcelain`.
+        #[clap(long, conflicts_with = "incremental")]
+        pub porcelain: bool,
+        /// Print each hunk with information about its commit as soon as it was found, but without the lines themselves.
+        #[clap(long)]
+        pub incremental: bool,
+        /// Print statistics about the performed work to stderr.
+        #[clap(long, short = 's')]
+        pub statistics: bool,
+        /// The path to the file to blame, relative to the root of the repository.
+        pub file: OsString,
+        /// The revspec of the commit to start the blame from, or `HEAD` if unset.
+        pub rev: Option<String>,
+    }
+
+    fn parse_range(range: &str) -> Result<Range<u32>, String> {
+        let (start, end) = range
+            .split_once(',')
+            .ok_or_else(|| format!("Expected a range like '10,20', got '{range}'"))?;
+        let start: u32 = start
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid start of range: {err}"))?;
+        let end: u32 = end
+            .trim()
+            .parse()
+            .map_err(|err| format!("Invalid end of range: {err}"))?;
+        if start == 0 || end < start {
+            return Err(format!("The 1-based range {start},{end} must not be empty"));
+        }
+        Ok(start - 1..end)
+    }
+}
+
+pub mod merge_tree {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Use the given tree-ish as common ancestor instead of computing the merge-base of `ours` and `theirs`.
+        #[clap(long)]
+        pub merge_base: Option<String>,
+        /// Only print the names of conflicting paths, instead of their stages.
+        #[clap(long)]
+        pub name_only: bool,
+        /// The revspec of our side of the merge.
+        pub ours: String,
+        /// The revspec of their side of the merge.
+        pub theirs: String,
+    }
+}
+
 pub mod tree {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -709,6 +778,25 @@ pub mod commitgraph {
             #[clap(default_value = "@")]
             spec: std::ffi::OsString,
         },
+        /// Write a commit-graph with all commits reachable from references, like `git commit-graph write --reachable`.
+        Write {
+            /// Compute and write changed-path Bloom filters to accelerate path-limited history traversals.
+            #[clap(long)]
+            changed_paths: bool,
+            /// Write a new layer of a split commit-graph, and decide how to deal with existing layers.
+            #[clap(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "merge")]
+            split: Option<Split>,
+        },
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+    pub enum Split {
+        /// Merge the new layer with existing layers that aren't much larger than it.
+        Merge,
+        /// Never merge existing layers.
+        NoMerge,
+        /// Replace all existing layers with a single one.
+        Replace,
     }
 }
 
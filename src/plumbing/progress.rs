@@ -94,14 +94,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.loosecompression",
         usage: Planned("")
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned("we want to support huge repos and be the fastest in doing so")
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned("this is a nice improvement over spareCheckout alone and should one day be available too")
-    },
     Record {
         config: "core.gitProxy",
         usage: NotPlanned("the transport mechanism works differently enough to not support it for now, but of course it's possible to add support if there is demand")
@@ -110,10 +102,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "checkout.defaultRemote",
         usage: Planned("needed for correct checkout behaviour, similar to what git does")
     },
-    Record {
-        config: "core.untrackedCache",
-        usage: Planned("needed for fast worktree operation")
-    },
     Record {
         config: "checkout.guess",
         usage: Planned("")
@@ -142,10 +130,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "commitGraph.readChangedPaths",
         usage: NotPlanned("can be considered when the underlying feature is actually used or needed")
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned("we can read sparse indices and support for it will be added early on")
-    },
     Record {
         config: "merge.renormalize",
         usage: Planned("once merging is being implemented, renormalization should be respected")
@@ -270,14 +254,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "advice.updateSparsePath",
         usage: NotApplicable("gitoxide does not yet have an 'advice' system")
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned("together with 'index.sparse' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not")
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned("non-cone mode is deprecated but should still fail gracefully if encountered")
-    },
     Record {
         config: "core.splitIndex",
         usage: NotPlanned("we don't want to be able to create split indices, but we will read them. It's (somewhat) superseded by sparse indices")
@@ -290,10 +266,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "splitIndex.sharedIndexExpire",
         usage: NotPlanned("seems like it's superseded by sparse indices")
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned("together with 'core.sparseCheckout' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not")
-    },
     Record {
         config: "index.version",
         usage: Planned("once V4 indices can be written, we need to be able to set a desired version. For now we write the smallest possible index version only.")
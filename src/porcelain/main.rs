@@ -44,7 +44,7 @@ pub fn main() -> Result<()> {
             gitoxide::shared::STANDARD_RANGE,
             move |_progress, _out, _err| panic!("something went very wrong"),
         ),
-        Subcommands::Init { directory } => core::repository::init(directory).map(|_| ()),
+        Subcommands::Init { directory, object_hash } => core::repository::init(directory, object_hash).map(|_| ()),
         #[cfg(feature = "gitoxide-core-tools")]
         Subcommands::Tool(tool) => match tool {
             #[cfg(feature = "gitoxide-core-tools-query")]